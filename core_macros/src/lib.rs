@@ -7,8 +7,9 @@
 
 use proc_macro::TokenStream;
 use quote::quote;
+use std::collections::HashMap;
 use syn::{
-    Ident, ItemMod, ItemStruct, LitStr, Token,
+    Attribute, Ident, Item, ItemMod, ItemStruct, LitStr, Token,
     parse::{Parse, ParseStream},
     parse_macro_input,
 };
@@ -28,6 +29,9 @@ impl Parse for OperationGroupArgs {
 /// Attribute macro for operation groups
 ///
 /// Usage: #[operation_group(flows)]
+///
+/// Structs annotated with `#[operation]` directly in the module are registered;
+/// other items (input types, helper enums, impls, nested modules) pass through.
 #[proc_macro_attribute]
 pub fn operation_group(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_macro_input!(attr as OperationGroupArgs);
@@ -38,36 +42,23 @@ pub fn operation_group(attr: TokenStream, item: TokenStream) -> TokenStream {
     let vis = &input.vis;
 
     // Extract module content
-    let (_brace, items) = match input.content {
-        Some((brace, items)) => (brace, items),
+    let items = match &input.content {
+        Some((_brace, items)) => items,
         None => {
-            return syn::Error::new_spanned(input, "Module must have content")
-                .to_compile_error()
-                .into();
+            return syn::Error::new_spanned(
+                &input.ident,
+                "#[operation_group] requires an inline module body: `mod name { ... }`",
+            )
+            .to_compile_error()
+            .into();
         }
     };
 
-    // Find all struct items that are actual operations (have a 'deps' field)
-    let operation_structs: Vec<&Ident> = items
-        .iter()
-        .filter_map(|item| {
-            if let syn::Item::Struct(s) = item {
-                // Check if this struct has a 'deps' field
-                let has_deps_field = if let syn::Fields::Named(fields) = &s.fields {
-                    fields
-                        .named
-                        .iter()
-                        .any(|f| f.ident.as_ref().map(|i| i == "deps").unwrap_or(false))
-                } else {
-                    false
-                };
-
-                if has_deps_field { Some(&s.ident) } else { None }
-            } else {
-                None
-            }
-        })
-        .collect();
+    // Structs annotated with #[operation] are the operations; all other items pass
+    // through untouched. Problems are reported alongside the expansion so they
+    // don't cascade into unresolved-module errors elsewhere.
+    let (operation_structs, errors) = collect_operations(items);
+    let errors = errors.map(|e| e.to_compile_error());
 
     // Generate registration calls for all structs
     let registration_calls = operation_structs.iter().map(|struct_name| {
@@ -116,6 +107,8 @@ pub fn operation_group(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     // Pass through the module with added metadata and auto-registration
     let expanded = quote! {
+        #errors
+
         #vis mod #mod_name {
             pub const GROUP_NAME: &str = #group_name;
 
@@ -167,6 +160,136 @@ pub fn operation_group(attr: TokenStream, item: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+/// Whether an attribute is `#[operation(...)]` (possibly path-qualified)
+fn is_operation_attr(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "operation")
+}
+
+/// Whether a struct declares a named `deps` field
+fn has_deps_field(item: &ItemStruct) -> bool {
+    match &item.fields {
+        syn::Fields::Named(fields) => fields
+            .named
+            .iter()
+            .any(|f| f.ident.as_ref().is_some_and(|i| i == "deps")),
+        _ => false,
+    }
+}
+
+/// Check an operation struct has the shape `#[operation]` expands against
+///
+/// The generated `new()` builds `Self { deps }`, so `deps` must be the only field.
+fn check_operation_fields(item: &ItemStruct) -> syn::Result<()> {
+    let only_deps = match &item.fields {
+        syn::Fields::Named(fields) => fields.named.len() == 1 && has_deps_field(item),
+        _ => false,
+    };
+    if only_deps {
+        return Ok(());
+    }
+
+    let message = format!(
+        "operation `{}` must have exactly one field: `pub deps: Arc<Dependencies>`",
+        item.ident
+    );
+    Err(match &item.fields {
+        syn::Fields::Unit => syn::Error::new_spanned(&item.ident, message),
+        fields => syn::Error::new_spanned(fields, message),
+    })
+}
+
+/// Find the operations declared in an `#[operation_group]` module
+///
+/// Returns the well-formed operation structs and any errors, each spanned at the
+/// offending item. Malformed `#[operation]` structs are skipped here; the
+/// `#[operation]` expansion reports them.
+fn collect_operations(items: &[Item]) -> (Vec<&Ident>, Option<syn::Error>) {
+    let mut operations = Vec::new();
+    let mut names: HashMap<String, &Ident> = HashMap::new();
+    let mut errors: Option<syn::Error> = None;
+    let mut push_error = |error: syn::Error| match errors.as_mut() {
+        Some(errors) => errors.combine(error),
+        None => errors = Some(error),
+    };
+
+    for item in items {
+        match item {
+            Item::Struct(s) => {
+                let Some(attr) = s.attrs.iter().find(|a| is_operation_attr(a)) else {
+                    if has_deps_field(s) {
+                        push_error(syn::Error::new_spanned(
+                            &s.ident,
+                            format!(
+                                "struct `{}` has a `deps` field but no #[operation] attribute, \
+                                 so it would not be registered; annotate it with \
+                                 #[operation(...)] or rename the field",
+                                s.ident
+                            ),
+                        ));
+                    }
+                    continue;
+                };
+
+                if check_operation_fields(s).is_err() {
+                    continue;
+                }
+
+                // Argument errors are reported by #[operation] itself
+                let name = attr
+                    .parse_args::<OperationArgs>()
+                    .ok()
+                    .and_then(|args| args.name)
+                    .unwrap_or_else(|| to_snake_case(&s.ident.to_string()));
+                if let Some(previous) = names.get(&name) {
+                    push_error(syn::Error::new_spanned(
+                        attr,
+                        format!(
+                            "operation name `{}` is already used by `{}` in this group",
+                            name, previous
+                        ),
+                    ));
+                    continue;
+                }
+                names.insert(name, &s.ident);
+                operations.push(&s.ident);
+            }
+            Item::Mod(m) => {
+                for nested in nested_operations(m) {
+                    push_error(syn::Error::new_spanned(
+                        &nested.ident,
+                        format!(
+                            "operation `{}` must be declared directly in the \
+                             #[operation_group] module, not in nested module `{}`",
+                            nested.ident, m.ident
+                        ),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (operations, errors)
+}
+
+/// `#[operation]` structs inside a (recursively) nested inline module
+fn nested_operations(module: &ItemMod) -> Vec<&ItemStruct> {
+    let Some((_, items)) = &module.content else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .flat_map(|item| match item {
+            Item::Struct(s) if s.attrs.iter().any(is_operation_attr) => vec![s],
+            Item::Mod(m) => nested_operations(m),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
 /// Parse attribute arguments for operation
 struct OperationArgs {
     name: Option<String>,
@@ -175,6 +298,8 @@ struct OperationArgs {
     description: Option<String>,
     group: Option<String>,
     input: Option<Ident>,
+    /// Input field that receives a raw (non-JSON) request body
+    raw_body: Option<String>,
 }
 
 impl Parse for OperationArgs {
@@ -185,6 +310,7 @@ impl Parse for OperationArgs {
         let mut description = None;
         let mut group = None;
         let mut input_type = None;
+        let mut raw_body = None;
 
         while !input.is_empty() {
             let ident: Ident = input.parse()?;
@@ -204,6 +330,7 @@ impl Parse for OperationArgs {
                         "cli" => cli = Some(value.value()),
                         "description" => description = Some(value.value()),
                         "group" => group = Some(value.value()),
+                        "raw_body" => raw_body = Some(value.value()),
                         _ => return Err(syn::Error::new_spanned(ident, "Unknown attribute")),
                     }
                 }
@@ -221,6 +348,7 @@ impl Parse for OperationArgs {
             description,
             group,
             input: input_type,
+            raw_body,
         })
     }
 }
@@ -272,6 +400,7 @@ fn generate_http_route_method(
     http_method: &str,
     http_path: &str,
     input_type: &Option<Ident>,
+    raw_body: &Option<String>,
 ) -> proc_macro2::TokenStream {
     use proc_macro2::Span;
 
@@ -290,6 +419,23 @@ fn generate_http_route_method(
                     quote! { axum::extract::Query(input): axum::extract::Query<#input_ty> },
                     quote! { input },
                 )
+            } else if let Some(raw_field) = raw_body {
+                // JSON envelope, or a raw body placed into `raw_field`
+                (
+                    quote! {
+                        headers: axum::http::HeaderMap,
+                        axum::extract::RawQuery(query): axum::extract::RawQuery,
+                        body: axum::body::Bytes
+                    },
+                    quote! {
+                        crate::http::input_from_body::<#input_ty>(
+                            &headers,
+                            query.as_deref(),
+                            &body,
+                            #raw_field,
+                        )?
+                    },
+                )
             } else {
                 // For POST/PUT/PATCH, use JSON body
                 (
@@ -298,15 +444,20 @@ fn generate_http_route_method(
                 )
             }
         } else if path_params.len() == 1 && (http_method == "GET" || http_method == "DELETE") {
-            // Single path param with GET/DELETE - construct input from path param only
+            // Single path param with GET/DELETE - combine path param with optional query params
             let param = &path_params[0];
             let param_ident = Ident::new(param, Span::call_site());
             (
                 quote! {
-                    axum::extract::Path(#param_ident): axum::extract::Path<String>
+                    axum::extract::Path(#param_ident): axum::extract::Path<String>,
+                    axum::extract::RawQuery(query): axum::extract::RawQuery
                 },
                 quote! {
-                    #input_ty { #param_ident }
+                    crate::http::input_from_path_and_query::<#input_ty>(
+                        #param,
+                        &#param_ident,
+                        query.as_deref(),
+                    )?
                 },
             )
         } else {
@@ -383,7 +534,18 @@ fn generate_http_route_method(
 #[proc_macro_attribute]
 pub fn operation(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_macro_input!(attr as OperationArgs);
-    let input = parse_macro_input!(item as ItemStruct);
+    let input = match syn::parse::<ItemStruct>(item) {
+        Ok(input) => input,
+        Err(e) => {
+            return syn::Error::new(e.span(), "#[operation] can only be applied to a struct")
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    if let Err(e) = check_operation_fields(&input) {
+        return e.to_compile_error().into();
+    }
 
     let struct_name = &input.ident;
     let vis = &input.vis;
@@ -422,7 +584,8 @@ pub fn operation(attr: TokenStream, item: TokenStream) -> TokenStream {
     // Parse HTTP metadata and generate helper method
     let (http_method_const, http_path_const, http_route_method) = if let Some(http) = &args.http {
         let (method, path) = parse_http_route(http);
-        let http_method_helper = generate_http_route_method(&method, &path, &args.input);
+        let http_method_helper =
+            generate_http_route_method(&method, &path, &args.input, &args.raw_body);
         (
             quote! { Some(#method) },
             quote! { Some(#path) },
@@ -542,3 +705,139 @@ pub fn operation(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     TokenStream::from(expanded)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn collect(module: ItemMod) -> (Vec<String>, Option<String>) {
+        let (_, items) = module.content.as_ref().unwrap();
+        let (operations, errors) = collect_operations(items);
+        (
+            operations.iter().map(|i| i.to_string()).collect(),
+            errors.map(|e| {
+                e.into_iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }),
+        )
+    }
+
+    #[test]
+    fn test_non_operation_items_pass_through() {
+        let (operations, errors) = collect(parse_quote! {
+            pub mod things {
+                use super::*;
+
+                #[derive(Deserialize)]
+                pub struct GetInput { pub name: String }
+
+                enum Mode { Fast, Slow }
+
+                type Output = Vec<String>;
+
+                impl Mode {
+                    fn is_fast(&self) -> bool { matches!(self, Mode::Fast) }
+                }
+
+                mod helpers {
+                    pub struct Cache { pub size: usize }
+                }
+
+                #[operation(name = "get_thing", input = GetInput)]
+                pub struct Get { pub deps: Arc<Dependencies> }
+
+                #[beemflow_core_macros::operation(input = GetInput)]
+                pub struct ListThings { pub deps: Arc<Dependencies> }
+            }
+        });
+
+        assert_eq!(operations, vec!["Get", "ListThings"]);
+        assert!(errors.is_none(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_deps_struct_without_operation_attribute() {
+        let (operations, errors) = collect(parse_quote! {
+            mod things {
+                pub struct Helper { pub deps: Arc<Dependencies> }
+            }
+        });
+
+        assert!(operations.is_empty());
+        let errors = errors.unwrap();
+        assert!(errors.contains("struct `Helper` has a `deps` field but no #[operation]"));
+    }
+
+    #[test]
+    fn test_operation_in_nested_module() {
+        let (operations, errors) = collect(parse_quote! {
+            mod things {
+                mod inner {
+                    #[operation(name = "hidden")]
+                    pub struct Hidden { pub deps: Arc<Dependencies> }
+                }
+            }
+        });
+
+        assert!(operations.is_empty());
+        assert!(
+            errors
+                .unwrap()
+                .contains("operation `Hidden` must be declared directly in the #[operation_group] module, not in nested module `inner`")
+        );
+    }
+
+    #[test]
+    fn test_duplicate_operation_names() {
+        let (operations, errors) = collect(parse_quote! {
+            mod things {
+                #[operation(name = "get_thing")]
+                pub struct Get { pub deps: Arc<Dependencies> }
+
+                // Default name from the struct is also `get_thing`
+                #[operation]
+                pub struct GetThing { pub deps: Arc<Dependencies> }
+            }
+        });
+
+        assert_eq!(operations, vec!["Get"]);
+        assert!(
+            errors
+                .unwrap()
+                .contains("operation name `get_thing` is already used by `Get` in this group")
+        );
+    }
+
+    #[test]
+    fn test_malformed_operation_fields() {
+        let extra: ItemStruct = parse_quote! {
+            pub struct Get { pub deps: Arc<Dependencies>, pub cache: Cache }
+        };
+        let tuple: ItemStruct = parse_quote! {
+            pub struct Get(Arc<Dependencies>);
+        };
+        let unit: ItemStruct = parse_quote! {
+            pub struct Get;
+        };
+        for item in [extra, tuple, unit] {
+            let err = check_operation_fields(&item).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "operation `Get` must have exactly one field: `pub deps: Arc<Dependencies>`"
+            );
+        }
+
+        // Malformed operations are left to #[operation] to report
+        let (operations, errors) = collect(parse_quote! {
+            mod things {
+                #[operation]
+                pub struct Get(Arc<Dependencies>);
+            }
+        });
+        assert!(operations.is_empty());
+        assert!(errors.is_none());
+    }
+}
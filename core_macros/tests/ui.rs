@@ -0,0 +1,11 @@
+//! Compile-time diagnostics of `#[operation]` and `#[operation_group]`
+//!
+//! Run with `TRYBUILD=overwrite` to regenerate the `.stderr` files after an
+//! intentional change to a message.
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/*.rs");
+}
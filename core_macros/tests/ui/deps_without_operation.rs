@@ -0,0 +1,29 @@
+// A struct that looks like an operation must be annotated to be registered
+
+#![allow(unused_imports)]
+
+include!("support/mod.rs");
+
+mod ops {
+    use super::*;
+
+    pub struct Dependencies;
+
+    #[beemflow_core_macros::operation_group(flows)]
+    pub mod flows {
+        use super::*;
+        use beemflow_core_macros::operation;
+        use std::sync::Arc;
+
+        #[operation(name = "list_flows")]
+        pub struct List {
+            pub deps: Arc<Dependencies>,
+        }
+
+        pub struct Get {
+            pub deps: Arc<Dependencies>,
+        }
+    }
+}
+
+fn main() {}
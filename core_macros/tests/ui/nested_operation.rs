@@ -0,0 +1,29 @@
+// Operations in nested modules would never be registered
+
+#![allow(unused_imports)]
+
+include!("support/mod.rs");
+
+mod ops {
+    use super::*;
+
+    pub struct Dependencies;
+
+    #[beemflow_core_macros::operation_group(flows)]
+    pub mod flows {
+        use super::*;
+        use beemflow_core_macros::operation;
+        use std::sync::Arc;
+
+        pub mod admin {
+            use super::*;
+
+            #[operation(name = "purge_flows")]
+            pub struct Purge {
+                pub deps: Arc<Dependencies>,
+            }
+        }
+    }
+}
+
+fn main() {}
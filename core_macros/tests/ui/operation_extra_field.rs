@@ -0,0 +1,26 @@
+// The generated constructor only fills in `deps`
+
+#![allow(unused_imports)]
+
+include!("support/mod.rs");
+
+mod ops {
+    use super::*;
+
+    pub struct Dependencies;
+
+    #[beemflow_core_macros::operation_group(flows)]
+    pub mod flows {
+        use super::*;
+        use beemflow_core_macros::operation;
+        use std::sync::Arc;
+
+        #[operation(name = "get_flow")]
+        pub struct Get {
+            pub deps: Arc<Dependencies>,
+            pub cache: bool,
+        }
+    }
+}
+
+fn main() {}
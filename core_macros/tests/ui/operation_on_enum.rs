@@ -0,0 +1,26 @@
+// #[operation] only applies to structs
+
+#![allow(unused_imports)]
+
+include!("support/mod.rs");
+
+mod ops {
+    use super::*;
+
+    pub struct Dependencies;
+
+    #[beemflow_core_macros::operation_group(flows)]
+    pub mod flows {
+        use super::*;
+        use beemflow_core_macros::operation;
+        use std::sync::Arc;
+
+        #[operation(name = "flow_mode")]
+        pub enum Mode {
+            Fast,
+            Slow,
+        }
+    }
+}
+
+fn main() {}
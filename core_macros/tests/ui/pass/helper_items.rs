@@ -0,0 +1,53 @@
+// Helper items alongside operations pass through the group untouched
+
+include!("../support/mod.rs");
+
+mod ops {
+    use super::*;
+
+    pub struct Dependencies;
+
+    #[beemflow_core_macros::operation_group(flows)]
+    pub mod flows {
+        use super::*;
+        use beemflow_core_macros::operation;
+        use std::sync::Arc;
+
+        /// Lists flows
+        #[operation(name = "list_flows", cli = "flows list")]
+        pub struct List {
+            pub deps: Arc<Dependencies>,
+        }
+
+        pub enum Mode {
+            Fast,
+            Slow,
+        }
+
+        impl Mode {
+            pub fn is_fast(&self) -> bool {
+                matches!(self, Mode::Fast)
+            }
+        }
+
+        pub type Names = Vec<String>;
+
+        pub mod helpers {
+            pub fn name() -> &'static str {
+                "flows"
+            }
+        }
+    }
+}
+
+fn main() {
+    use ops::flows;
+
+    let mut registry = OperationRegistry;
+    flows::register_all(&mut registry, std::sync::Arc::new(ops::Dependencies));
+    let metadata = <flows::List as HasMetadata>::metadata();
+    assert_eq!(metadata.name, "list_flows");
+    assert_eq!(metadata.group, flows::GROUP_NAME);
+    assert!(flows::Mode::Fast.is_fast() && !flows::Mode::Slow.is_fast());
+    let _: flows::Names = vec![flows::helpers::name().to_string()];
+}
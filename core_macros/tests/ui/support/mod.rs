@@ -0,0 +1,23 @@
+// Stand-ins for the items in beemflow's `core` module that the expansions name.
+// UI tests mirror its layout: `mod ops { use super::*; pub struct Dependencies; .. }`
+// holding the #[operation_group] module, which itself starts with `use super::*`.
+
+pub struct OperationMetadata {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub group: &'static str,
+    pub http_method: Option<&'static str>,
+    pub http_path: Option<&'static str>,
+    pub cli_pattern: Option<&'static str>,
+    pub schema: serde_json::Map<String, serde_json::Value>,
+}
+
+pub trait HasMetadata {
+    fn metadata() -> OperationMetadata;
+}
+
+pub struct OperationRegistry;
+
+impl OperationRegistry {
+    pub fn register<T>(&mut self, _operation: T, _name: &'static str) {}
+}
@@ -0,0 +1,2 @@
+-- Persist rendered (secret-redacted) tool inputs for each step
+ALTER TABLE steps ADD COLUMN IF NOT EXISTS inputs JSONB;
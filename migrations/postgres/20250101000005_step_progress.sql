@@ -0,0 +1,4 @@
+-- Latest progress reported by a running step
+ALTER TABLE steps ADD COLUMN IF NOT EXISTS progress_percent DOUBLE PRECISION;
+ALTER TABLE steps ADD COLUMN IF NOT EXISTS progress_message TEXT;
+ALTER TABLE steps ADD COLUMN IF NOT EXISTS progress_updated_at TIMESTAMPTZ;
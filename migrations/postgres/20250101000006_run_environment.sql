@@ -0,0 +1,2 @@
+-- Environment a run was started in (flow `environments` overlay)
+ALTER TABLE runs ADD COLUMN IF NOT EXISTS environment TEXT;
@@ -0,0 +1,7 @@
+-- Backend locations of blobs behind stable beemflow://blobs/<id> URLs
+CREATE TABLE IF NOT EXISTS blobs (
+    id UUID PRIMARY KEY,
+    backend TEXT NOT NULL,
+    location TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,15 @@
+-- Ownership metadata indexed from each flow version at deploy time
+ALTER TABLE flow_versions ADD COLUMN description TEXT;
+ALTER TABLE flow_versions ADD COLUMN owner TEXT;
+ALTER TABLE flow_versions ADD COLUMN team TEXT;
+
+CREATE TABLE IF NOT EXISTS flow_tags (
+    flow_name TEXT NOT NULL,
+    version TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (flow_name, version, tag),
+    FOREIGN KEY (flow_name, version) REFERENCES flow_versions(flow_name, version) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_flow_tags_tag ON flow_tags(tag);
+CREATE INDEX IF NOT EXISTS idx_flow_versions_owner ON flow_versions(owner);
@@ -0,0 +1,19 @@
+-- Events written with the run/step updates that produce them, published by the outbox dispatcher
+CREATE TABLE IF NOT EXISTS event_outbox (
+    position BIGSERIAL PRIMARY KEY,
+    id UUID NOT NULL UNIQUE,
+    topic TEXT NOT NULL,
+    sequence BIGINT NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    sent_at TIMESTAMPTZ,
+    UNIQUE (topic, sequence)
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_outbox_unsent ON event_outbox(position) WHERE sent_at IS NULL;
+
+-- Last sequence number handed out per topic; the row lock orders concurrent writers
+CREATE TABLE IF NOT EXISTS event_topic_sequences (
+    topic TEXT PRIMARY KEY,
+    last_sequence BIGINT NOT NULL
+);
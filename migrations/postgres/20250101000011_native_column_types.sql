@@ -0,0 +1,58 @@
+-- Native types for the initial schema's columns, matching the values the storage binds
+-- (UUID ids, JSONB documents, TIMESTAMPTZ times)
+ALTER TABLE steps DROP CONSTRAINT IF EXISTS steps_run_id_fkey;
+
+ALTER TABLE runs
+    ALTER COLUMN id TYPE UUID USING id::UUID,
+    ALTER COLUMN event TYPE JSONB USING event::JSONB,
+    ALTER COLUMN vars TYPE JSONB USING vars::JSONB,
+    ALTER COLUMN started_at TYPE TIMESTAMPTZ USING to_timestamp(started_at),
+    ALTER COLUMN ended_at TYPE TIMESTAMPTZ USING to_timestamp(ended_at);
+
+ALTER TABLE steps
+    ALTER COLUMN id TYPE UUID USING id::UUID,
+    ALTER COLUMN run_id TYPE UUID USING run_id::UUID,
+    ALTER COLUMN started_at TYPE TIMESTAMPTZ USING to_timestamp(started_at),
+    ALTER COLUMN ended_at TYPE TIMESTAMPTZ USING to_timestamp(ended_at),
+    ALTER COLUMN outputs TYPE JSONB USING outputs::JSONB;
+
+ALTER TABLE steps
+    ADD CONSTRAINT steps_run_id_fkey FOREIGN KEY (run_id) REFERENCES runs(id) ON DELETE CASCADE;
+
+ALTER TABLE paused_runs
+    ALTER COLUMN data TYPE JSONB USING data::JSONB;
+
+ALTER TABLE flows
+    ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at),
+    ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING to_timestamp(updated_at);
+
+ALTER TABLE flow_versions
+    ALTER COLUMN deployed_at TYPE TIMESTAMPTZ USING to_timestamp(deployed_at);
+
+ALTER TABLE deployed_flows
+    ALTER COLUMN deployed_at TYPE TIMESTAMPTZ USING to_timestamp(deployed_at);
+
+ALTER TABLE oauth_credentials
+    ALTER COLUMN expires_at TYPE TIMESTAMPTZ USING to_timestamp(expires_at),
+    ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at),
+    ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING to_timestamp(updated_at);
+
+ALTER TABLE oauth_providers
+    ALTER COLUMN scopes TYPE JSONB USING scopes::JSONB,
+    ALTER COLUMN auth_params TYPE JSONB USING auth_params::JSONB,
+    ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at),
+    ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING to_timestamp(updated_at);
+
+ALTER TABLE oauth_clients
+    ALTER COLUMN redirect_uris TYPE JSONB USING redirect_uris::JSONB,
+    ALTER COLUMN grant_types TYPE JSONB USING grant_types::JSONB,
+    ALTER COLUMN response_types TYPE JSONB USING response_types::JSONB,
+    ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at),
+    ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING to_timestamp(updated_at);
+
+ALTER TABLE oauth_tokens
+    ALTER COLUMN code_create_at TYPE TIMESTAMPTZ USING to_timestamp(code_create_at),
+    ALTER COLUMN access_create_at TYPE TIMESTAMPTZ USING to_timestamp(access_create_at),
+    ALTER COLUMN refresh_create_at TYPE TIMESTAMPTZ USING to_timestamp(refresh_create_at),
+    ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at),
+    ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING to_timestamp(updated_at);
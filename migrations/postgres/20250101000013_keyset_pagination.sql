@@ -0,0 +1,3 @@
+-- Keyset pagination walks runs by (started_at, id) and versions by (deployed_at, version)
+CREATE INDEX IF NOT EXISTS idx_runs_started_id ON runs(started_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_flow_versions_keyset ON flow_versions(flow_name, deployed_at DESC, version DESC);
@@ -0,0 +1,4 @@
+-- Key/value labels from the flow and the caller that started each run
+ALTER TABLE runs ADD COLUMN IF NOT EXISTS labels JSONB;
+
+CREATE INDEX IF NOT EXISTS idx_runs_labels ON runs USING GIN (labels jsonb_path_ops);
@@ -0,0 +1,8 @@
+-- Step context checkpoints of in-progress runs (checkpointRuns), used to resume
+-- runs interrupted by a crash or restart
+CREATE TABLE IF NOT EXISTS run_checkpoints (
+    run_id UUID PRIMARY KEY,
+    data JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    FOREIGN KEY (run_id) REFERENCES runs(id) ON DELETE CASCADE
+);
@@ -0,0 +1,17 @@
+-- Full-text index of each flow version's searchable fields (search_flows),
+-- written at deploy time; searches join it to deployed_flows
+CREATE TABLE IF NOT EXISTS flow_search (
+    flow_name TEXT NOT NULL,
+    version TEXT NOT NULL,
+    field TEXT NOT NULL,
+    weight BIGINT NOT NULL,
+    text TEXT NOT NULL,
+    -- Punctuation splits words, so `slack.chat.postMessage` matches `slack`
+    document TSVECTOR GENERATED ALWAYS AS (
+        to_tsvector('simple', regexp_replace(text, '[^[:alnum:]]+', ' ', 'g'))
+    ) STORED,
+    PRIMARY KEY (flow_name, version, field),
+    FOREIGN KEY (flow_name, version) REFERENCES flow_versions(flow_name, version) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_flow_search_document ON flow_search USING GIN (document);
@@ -0,0 +1,14 @@
+-- Tenant isolation keys (see auth::tenant); NULL in single-tenant deployments
+ALTER TABLE runs ADD COLUMN IF NOT EXISTS tenant_id TEXT;
+ALTER TABLE oauth_credentials ADD COLUMN IF NOT EXISTS tenant_id TEXT;
+ALTER TABLE oauth_clients ADD COLUMN IF NOT EXISTS tenant_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_runs_tenant ON runs(tenant_id, started_at DESC) WHERE tenant_id IS NOT NULL;
+
+-- Tenant owning each flow name, claimed by its first tenant-scoped deploy
+CREATE TABLE IF NOT EXISTS flow_tenants (
+    flow_name TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_flow_tenants_tenant ON flow_tenants(tenant_id);
@@ -0,0 +1,2 @@
+-- What made a failed step fail: adapter, template, validation or timeout
+ALTER TABLE steps ADD COLUMN IF NOT EXISTS error_kind TEXT;
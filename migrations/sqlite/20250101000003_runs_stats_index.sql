@@ -0,0 +1,2 @@
+-- Covering index for run statistics: window scan by started_at, grouped by flow
+CREATE INDEX IF NOT EXISTS idx_runs_started_flow_status ON runs(started_at, flow_name, status, ended_at);
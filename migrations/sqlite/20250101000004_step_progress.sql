@@ -0,0 +1,4 @@
+-- Latest progress reported by a running step
+ALTER TABLE steps ADD COLUMN progress_percent REAL;
+ALTER TABLE steps ADD COLUMN progress_message TEXT;
+ALTER TABLE steps ADD COLUMN progress_updated_at INTEGER;
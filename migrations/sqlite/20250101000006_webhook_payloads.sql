@@ -0,0 +1,10 @@
+-- Recently received webhook payloads, kept for replay
+CREATE TABLE IF NOT EXISTS webhook_payloads (
+    id TEXT PRIMARY KEY,
+    provider TEXT NOT NULL,
+    headers TEXT NOT NULL,
+    body TEXT NOT NULL,
+    received_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_payloads_provider ON webhook_payloads(provider, received_at DESC);
@@ -0,0 +1,7 @@
+-- Backend locations of blobs behind stable beemflow://blobs/<id> URLs
+CREATE TABLE IF NOT EXISTS blobs (
+    id TEXT PRIMARY KEY,
+    backend TEXT NOT NULL,
+    location TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
@@ -0,0 +1,19 @@
+-- Events written with the run/step updates that produce them, published by the outbox dispatcher
+CREATE TABLE IF NOT EXISTS event_outbox (
+    position INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL UNIQUE,
+    topic TEXT NOT NULL,
+    sequence INTEGER NOT NULL,
+    payload TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    sent_at INTEGER,
+    UNIQUE (topic, sequence)
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_outbox_unsent ON event_outbox(position) WHERE sent_at IS NULL;
+
+-- Last sequence number handed out per topic
+CREATE TABLE IF NOT EXISTS event_topic_sequences (
+    topic TEXT PRIMARY KEY,
+    last_sequence INTEGER NOT NULL
+);
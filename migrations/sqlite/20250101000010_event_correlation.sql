@@ -0,0 +1,6 @@
+-- Correlation IDs linking runs and events back to the action that caused them
+ALTER TABLE runs ADD COLUMN correlation_id TEXT;
+ALTER TABLE event_outbox ADD COLUMN source TEXT;
+ALTER TABLE event_outbox ADD COLUMN correlation_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_runs_correlation ON runs(correlation_id) WHERE correlation_id IS NOT NULL;
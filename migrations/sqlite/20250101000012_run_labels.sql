@@ -0,0 +1,2 @@
+-- Key/value labels from the flow and the caller that started each run (JSON object)
+ALTER TABLE runs ADD COLUMN labels TEXT;
@@ -0,0 +1,2 @@
+-- Resources each run consumed: wall time, tool invocations, HTTP and MCP calls, LLM tokens (JSON object)
+ALTER TABLE runs ADD COLUMN usage TEXT;
@@ -0,0 +1,24 @@
+-- Tamper-evident run history (storage.integrityChain): every run and step write
+-- appends an entry chained to the previous entry of the same flow
+CREATE TABLE IF NOT EXISTS run_history (
+    flow_name TEXT NOT NULL,
+    sequence INTEGER NOT NULL,
+    record_kind TEXT NOT NULL,
+    record_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    prev_hash TEXT NOT NULL,
+    record_hash TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    PRIMARY KEY (flow_name, sequence)
+);
+
+-- Last entry of each flow's chain
+CREATE TABLE IF NOT EXISTS run_history_heads (
+    flow_name TEXT PRIMARY KEY,
+    last_sequence INTEGER NOT NULL,
+    last_hash TEXT NOT NULL
+);
+
+-- Hash of the latest history entry of each record
+ALTER TABLE runs ADD COLUMN record_hash TEXT;
+ALTER TABLE steps ADD COLUMN record_hash TEXT;
@@ -0,0 +1,8 @@
+-- Step context checkpoints of in-progress runs (checkpointRuns), used to resume
+-- runs interrupted by a crash or restart
+CREATE TABLE IF NOT EXISTS run_checkpoints (
+    run_id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    updated_at BIGINT NOT NULL,
+    FOREIGN KEY (run_id) REFERENCES runs(id) ON DELETE CASCADE
+);
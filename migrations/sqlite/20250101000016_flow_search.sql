@@ -0,0 +1,9 @@
+-- Full-text index of each flow version's searchable fields (search_flows),
+-- written at deploy time; searches join it to deployed_flows
+CREATE VIRTUAL TABLE IF NOT EXISTS flow_search USING fts5(
+    flow_name UNINDEXED,
+    version UNINDEXED,
+    field UNINDEXED,
+    weight UNINDEXED,
+    text
+);
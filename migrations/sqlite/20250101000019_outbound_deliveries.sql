@@ -0,0 +1,34 @@
+-- Outbound HTTP deliveries waiting to be retried (core.deliver)
+CREATE TABLE IF NOT EXISTS outbound_deliveries (
+    id TEXT PRIMARY KEY,
+    method TEXT NOT NULL,
+    url TEXT NOT NULL,
+    headers TEXT NOT NULL,
+    body TEXT,
+    attempts INTEGER NOT NULL,
+    max_attempts INTEGER NOT NULL,
+    last_error TEXT,
+    next_attempt_at INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    run_id TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbound_deliveries_due ON outbound_deliveries(next_attempt_at);
+
+-- Deliveries that failed every attempt
+CREATE TABLE IF NOT EXISTS dead_letter_deliveries (
+    id TEXT PRIMARY KEY,
+    method TEXT NOT NULL,
+    url TEXT NOT NULL,
+    headers TEXT NOT NULL,
+    body TEXT,
+    attempts INTEGER NOT NULL,
+    max_attempts INTEGER NOT NULL,
+    last_error TEXT,
+    next_attempt_at INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    dead_lettered_at INTEGER NOT NULL,
+    run_id TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_dead_letter_deliveries_time ON dead_letter_deliveries(dead_lettered_at DESC);
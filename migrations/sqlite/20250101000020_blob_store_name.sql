@@ -0,0 +1,2 @@
+-- Named blob store (from blob_stores) that holds each blob; NULL is the default store
+ALTER TABLE blobs ADD COLUMN store TEXT;
@@ -0,0 +1,7 @@
+-- Headers and body of deliveries, encrypted; the plain headers and body are left empty
+ALTER TABLE outbound_deliveries ADD COLUMN sealed TEXT;
+ALTER TABLE dead_letter_deliveries ADD COLUMN sealed TEXT;
+
+-- Pace of requests to the delivery's host (core.deliver rate_limit), as JSON
+ALTER TABLE outbound_deliveries ADD COLUMN rate_limit TEXT;
+ALTER TABLE dead_letter_deliveries ADD COLUMN rate_limit TEXT;
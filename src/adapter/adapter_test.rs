@@ -36,6 +36,9 @@ async fn test_lazy_load_tool_from_registry() {
             );
             p
         }),
+        body_format: None,
+        rate_limit: None,
+        stream: None,
         command: None,
         args: None,
         env: None,
@@ -121,6 +124,9 @@ async fn test_lazy_load_ignores_non_tools() {
         kind: None,
         version: None,
         registry: None,
+        body_format: None,
+        rate_limit: None,
+        stream: None,
         command: Some("node".to_string()),
         args: Some(vec!["server.js".to_string()]),
         endpoint: None,
@@ -253,6 +259,9 @@ async fn test_lazy_load_end_to_end_execution() {
             p.insert("type".to_string(), serde_json::json!("object"));
             p
         }),
+        body_format: None,
+        rate_limit: None,
+        stream: None,
         command: None,
         args: None,
         env: None,
@@ -308,17 +317,23 @@ async fn test_lazy_load_end_to_end_execution() {
         secrets_provider,
         config,
         oauth_client,
+        Arc::new(crate::event::InProcEventBus::new()),
         1000,
     );
 
     // Create a flow that uses the lazy-loaded tool
     let flow = crate::model::Flow {
-        name: "weather_test".to_string().into(),
+        name: crate::model::FlowName::new("weather_test").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(crate::model::Trigger::Single("manual".to_string())),
         cron: None,
         vars: None,
+        input_schema: None,
         steps: vec![crate::model::Step {
             id: "get_weather".to_string().into(),
             use_: Some("weather.get".to_string()), // This should trigger lazy loading
@@ -326,7 +341,12 @@ async fn test_lazy_load_end_to_end_execution() {
             ..Default::default()
         }],
         catch: None,
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     };
 
     // Execute the flow - this should lazy-load the tool and execute it
@@ -354,3 +374,498 @@ async fn test_lazy_load_end_to_end_execution() {
         "Output should contain weather data"
     );
 }
+
+fn manifest_with_parameters(parameters: serde_json::Value) -> ToolManifest {
+    ToolManifest {
+        name: "test.params".to_string(),
+        description: "Test parameter validation".to_string(),
+        kind: "task".to_string(),
+        version: None,
+        parameters: parameters
+            .as_object()
+            .unwrap()
+            .clone()
+            .into_iter()
+            .collect(),
+        endpoint: Some("https://api.example.com/items".to_string()),
+        method: Some("POST".to_string()),
+        headers: None,
+        body_format: BodyFormat::Json,
+        rate_limit: None,
+        stream: None,
+    }
+}
+
+#[test]
+fn test_normalize_inputs_rejects_unknown_key() {
+    let manifest = manifest_with_parameters(serde_json::json!({
+        "type": "object",
+        "properties": {
+            "body": {"type": "string"},
+            "url": {"type": "string"}
+        }
+    }));
+
+    let mut inputs = HashMap::new();
+    inputs.insert("boddy".to_string(), serde_json::json!("hello"));
+
+    let err = manifest.normalize_inputs(inputs).unwrap_err().to_string();
+    assert!(err.contains("unknown parameter 'boddy'"), "{}", err);
+    assert!(err.contains("body, url"), "{}", err);
+}
+
+#[test]
+fn test_normalize_inputs_applies_defaults_and_coerces() {
+    let manifest = manifest_with_parameters(serde_json::json!({
+        "type": "object",
+        "required": ["limit"],
+        "properties": {
+            "limit": {"type": "integer", "default": 10},
+            "verbose": {"type": "boolean"},
+            "api_key": {"type": "string", "default": "$env:TEST_PARAMS_API_KEY"}
+        }
+    }));
+
+    let mut inputs = HashMap::new();
+    inputs.insert("verbose".to_string(), serde_json::json!("true"));
+    inputs.insert("__use".to_string(), serde_json::json!("test.params"));
+
+    let normalized = manifest.normalize_inputs(inputs).unwrap();
+    assert_eq!(normalized.get("limit"), Some(&serde_json::json!(10)));
+    assert_eq!(normalized.get("verbose"), Some(&serde_json::json!(true)));
+    // $env: defaults are resolved by the adapter, not during validation
+    assert!(!normalized.contains_key("api_key"));
+}
+
+#[test]
+fn test_normalize_inputs_type_mismatch_and_escape_hatches() {
+    let manifest = manifest_with_parameters(serde_json::json!({
+        "type": "object",
+        "properties": {
+            "limit": {"type": "integer"}
+        }
+    }));
+
+    let mut inputs = HashMap::new();
+    inputs.insert("limit".to_string(), serde_json::json!("ten"));
+    let err = manifest.normalize_inputs(inputs).unwrap_err().to_string();
+    assert!(
+        err.contains("parameter 'limit' expected type \"integer\""),
+        "{}",
+        err
+    );
+
+    // Templated values are skipped statically
+    let mut templated = HashMap::new();
+    templated.insert("limit".to_string(), serde_json::json!("{{ vars.limit }}"));
+    assert!(manifest.check_static_inputs(&templated).is_ok());
+
+    // Manifests without declared properties accept anything
+    let open = manifest_with_parameters(serde_json::json!({}));
+    let mut inputs = HashMap::new();
+    inputs.insert("anything".to_string(), serde_json::json!(1));
+    assert!(open.normalize_inputs(inputs).is_ok());
+}
+
+async fn execution_context() -> ExecutionContext {
+    let storage: Arc<dyn crate::storage::Storage> = Arc::new(
+        crate::storage::SqliteStorage::new(":memory:")
+            .await
+            .expect("Failed to create storage"),
+    );
+    let secrets_provider: Arc<dyn crate::secrets::SecretsProvider> =
+        Arc::new(crate::secrets::EnvSecretsProvider::new());
+    let oauth_client =
+        crate::auth::create_test_oauth_client(storage.clone(), secrets_provider.clone());
+    ExecutionContext::new(storage, secrets_provider, oauth_client)
+}
+
+fn http_tool(
+    endpoint: String,
+    body_format: BodyFormat,
+    headers: Option<HashMap<String, String>>,
+) -> HttpAdapter {
+    HttpAdapter::new(
+        "test.submit".to_string(),
+        Some(ToolManifest {
+            name: "test.submit".to_string(),
+            description: "Submit to a legacy API".to_string(),
+            kind: "task".to_string(),
+            version: None,
+            parameters: HashMap::new(),
+            endpoint: Some(endpoint),
+            method: Some("POST".to_string()),
+            headers,
+            body_format,
+            rate_limit: None,
+            stream: None,
+        }),
+    )
+}
+
+#[tokio::test]
+async fn test_http_tool_sends_form_encoded_body() {
+    use wiremock::matchers::{body_string, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/messages/acct-1"))
+        .and(header("content-type", "application/x-www-form-urlencoded"))
+        .and(body_string(
+            "Body=Hello+%26+welcome%21&MediaUrl=a&MediaUrl=b&Priority=2&To=%2B15551234",
+        ))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({"sid": "SM1"})))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let tool = http_tool(
+        format!("{}/messages/{{account}}", mock_server.uri()),
+        BodyFormat::Form,
+        None,
+    );
+    let inputs = serde_json::json!({
+        "account": "acct-1",
+        "To": "+15551234",
+        "Body": "Hello & welcome!",
+        "Priority": 2,
+        "MediaUrl": ["a", "b"],
+        "StatusCallback": null
+    });
+    let outputs = tool
+        .execute(
+            serde_json::from_value(inputs).unwrap(),
+            &execution_context().await,
+        )
+        .await
+        .unwrap();
+    assert_eq!(outputs["sid"], "SM1");
+}
+
+#[tokio::test]
+async fn test_http_tool_records_usage() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let body = serde_json::json!({
+        "choices": [],
+        "usage": {"input_tokens": 12, "output_tokens": 30}
+    });
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&body))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let tool = http_tool(mock_server.uri(), BodyFormat::Json, None);
+    let ctx = execution_context().await;
+    for _ in 0..2 {
+        tool.execute(HashMap::new(), &ctx).await.unwrap();
+    }
+
+    let usage = ctx.usage.snapshot(std::time::Duration::ZERO);
+    assert_eq!(usage.http_requests, 2);
+    assert_eq!(
+        usage.http_response_bytes,
+        2 * serde_json::to_string(&body).unwrap().len() as u64
+    );
+    assert_eq!(usage.llm_tokens, 84);
+}
+
+#[tokio::test]
+async fn test_http_tool_sends_raw_body_verbatim() {
+    use wiremock::matchers::{body_string, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/soap"))
+        .and(header("content-type", "text/xml"))
+        .and(body_string("<Envelope><Ping/></Envelope>"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<Pong/>"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let tool = http_tool(
+        format!("{}/soap", mock_server.uri()),
+        BodyFormat::Raw,
+        Some(HashMap::from([(
+            "Content-Type".to_string(),
+            "text/xml".to_string(),
+        )])),
+    );
+    let ctx = execution_context().await;
+    let mut inputs = HashMap::new();
+    inputs.insert(
+        "body".to_string(),
+        serde_json::json!("<Envelope><Ping/></Envelope>"),
+    );
+    let outputs = tool.execute(inputs, &ctx).await.unwrap();
+    assert_eq!(outputs["body"], "<Pong/>");
+
+    // Raw bodies must already be text
+    let mut inputs = HashMap::new();
+    inputs.insert("body".to_string(), serde_json::json!({"ping": true}));
+    let err = tool.execute(inputs, &ctx).await.unwrap_err().to_string();
+    assert!(err.contains("raw body must be a string"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_http_tool_assembles_streamed_deltas() {
+    use wiremock::matchers::{header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let chunk = |delta: &str| {
+        format!(
+            "data: {}\n\n",
+            serde_json::json!({"choices": [{"delta": {"content": delta}}]})
+        )
+    };
+    let mut events: String = ["The", " quick", " brown", " fox"]
+        .iter()
+        .map(|delta| chunk(delta))
+        .collect();
+    events.push_str(&format!(
+        "data: {}\n\ndata: [DONE]\n\n",
+        serde_json::json!({"choices": [], "usage": {"total_tokens": 17}})
+    ));
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(header("accept", "text/event-stream"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(events.clone(), "text/event-stream"))
+        .mount(&mock_server)
+        .await;
+
+    let streaming_tool = |stream: StreamConfig| {
+        let manifest = http_tool(mock_server.uri(), BodyFormat::Json, None)
+            .manifest()
+            .unwrap();
+        HttpAdapter::new(
+            manifest.name.clone(),
+            Some(ToolManifest {
+                stream: Some(stream),
+                ..manifest
+            }),
+        )
+    };
+
+    let ctx = execution_context().await;
+    let outputs = streaming_tool(StreamConfig::default())
+        .execute(HashMap::new(), &ctx)
+        .await
+        .unwrap();
+    assert_eq!(outputs["text"], "The quick brown fox");
+    assert_eq!(outputs["events"], 5);
+    let usage = ctx.usage.snapshot(std::time::Duration::ZERO);
+    assert_eq!(usage.http_response_bytes, events.len() as u64);
+    assert_eq!(usage.llm_tokens, 17);
+
+    // Streams past the size limit are cut off
+    let err = streaming_tool(StreamConfig {
+        max_bytes: 64,
+        ..Default::default()
+    })
+    .execute(HashMap::new(), &ctx)
+    .await
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("exceeded 64 bytes"), "{}", err);
+}
+
+#[test]
+fn test_registry_entry_body_format() {
+    let entry: crate::registry::RegistryEntry = serde_json::from_value(serde_json::json!({
+        "type": "tool",
+        "name": "legacy.submit",
+        "body_format": "form"
+    }))
+    .unwrap();
+    assert_eq!(entry.body_format, Some(BodyFormat::Form));
+
+    let entry: crate::registry::RegistryEntry =
+        serde_json::from_value(serde_json::json!({"type": "tool", "name": "modern.submit"}))
+            .unwrap();
+    assert_eq!(entry.body_format, None);
+}
+
+#[tokio::test]
+async fn test_mock_adapter_follows_script_then_fallback() {
+    use mock::{MockAdapter, MockResponse};
+
+    let ctx = execution_context().await;
+    async fn call(
+        mock: &MockAdapter,
+        ctx: &ExecutionContext,
+        n: i64,
+    ) -> crate::Result<HashMap<String, serde_json::Value>> {
+        mock.execute(
+            HashMap::from([("n".to_string(), serde_json::json!(n))]),
+            ctx,
+        )
+        .await
+    }
+
+    let mock = MockAdapter::new("test.scripted")
+        .then(MockResponse::ok(serde_json::json!({"first": true})))
+        .then(MockResponse::err("second fails"));
+    assert_eq!(call(&mock, &ctx, 1).await.unwrap()["first"], true);
+    assert!(
+        call(&mock, &ctx, 2)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("second fails")
+    );
+    // An exhausted script without a fallback fails the call
+    let err = call(&mock, &ctx, 3).await.unwrap_err().to_string();
+    assert!(err.contains("no response scripted for call 3"), "{}", err);
+    assert_eq!(
+        mock.calls()
+            .iter()
+            .map(|inputs| inputs["n"].clone())
+            .collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+
+    let mock = MockAdapter::new("test.fallback")
+        .otherwise(MockResponse::ok(serde_json::json!({"same": "always"})));
+    for n in 0..3 {
+        assert_eq!(call(&mock, &ctx, n).await.unwrap()["same"], "always");
+    }
+    assert_eq!(mock.id(), "test.fallback");
+}
+
+#[tokio::test]
+async fn test_rate_limiter_allows_burst_then_paces() {
+    use std::time::{Duration, Instant};
+
+    let limiter = RateLimiter::default();
+    let limit = RateLimit {
+        per_second: 20.0,
+        burst: 2,
+    };
+
+    // The burst goes through at once
+    let started = Instant::now();
+    limiter.acquire("api.search", &limit).await.unwrap();
+    limiter.acquire("api.search", &limit).await.unwrap();
+    assert!(started.elapsed() < Duration::from_millis(40));
+
+    // Then each call waits for the bucket to refill
+    let paced = Instant::now();
+    limiter.acquire("api.search", &limit).await.unwrap();
+    limiter.acquire("api.search", &limit).await.unwrap();
+    assert!(
+        paced.elapsed() >= Duration::from_millis(90),
+        "{:?}",
+        paced.elapsed()
+    );
+
+    // Other keys have their own bucket
+    let other = Instant::now();
+    limiter.acquire("api.other", &limit).await.unwrap();
+    assert!(other.elapsed() < Duration::from_millis(40));
+
+    // Limits that allow no calls are rejected
+    for invalid in [
+        RateLimit {
+            per_second: 0.0,
+            burst: 1,
+        },
+        RateLimit {
+            per_second: 1.0,
+            burst: 0,
+        },
+    ] {
+        assert!(limiter.acquire("api.search", &invalid).await.is_err());
+    }
+}
+
+#[test]
+fn test_registry_entry_rate_limit() {
+    let entry: crate::registry::RegistryEntry = serde_json::from_value(serde_json::json!({
+        "type": "tool",
+        "name": "api.search",
+        "rate_limit": {"per_second": 0.5}
+    }))
+    .unwrap();
+    assert_eq!(
+        entry.rate_limit,
+        Some(RateLimit {
+            per_second: 0.5,
+            burst: 1
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_http_adapter_decompresses_gzip_responses() {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let gzip = |data: &[u8]| {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    };
+    let json = serde_json::json!({"items": [1, 2, 3], "status": "ok"}).to_string();
+    let compressed = |status: u16, body: Vec<u8>, encoding: &str| {
+        ResponseTemplate::new(status)
+            .insert_header("content-encoding", encoding)
+            .set_body_raw(body, "application/json")
+    };
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/gzip"))
+        .respond_with(compressed(200, gzip(json.as_bytes()), "gzip"))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/corrupt"))
+        .respond_with(compressed(200, b"not gzip at all".to_vec(), "gzip"))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/twice"))
+        .respond_with(compressed(200, gzip(&gzip(json.as_bytes())), "gzip"))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/stacked"))
+        .respond_with(compressed(200, gzip(&gzip(json.as_bytes())), "gzip, gzip"))
+        .mount(&mock_server)
+        .await;
+
+    let adapter = HttpAdapter::new(crate::constants::HTTP_ADAPTER_ID.to_string(), None);
+    let ctx = execution_context().await;
+    let fetch = |route: &str| {
+        HashMap::from([(
+            "url".to_string(),
+            serde_json::json!(format!("{}{}", mock_server.uri(), route)),
+        )])
+    };
+
+    let outputs = adapter.execute(fetch("/gzip"), &ctx).await.unwrap();
+    assert_eq!(outputs["status"], "ok");
+    assert_eq!(outputs["items"], serde_json::json!([1, 2, 3]));
+
+    for (route, expected) in [
+        ("/corrupt", "gzip"),
+        ("/twice", "content-encoding: gzip"),
+        ("/stacked", "content-encoding: gzip, gzip"),
+    ] {
+        let err = adapter.execute(fetch(route), &ctx).await.unwrap_err();
+        assert!(matches!(err, crate::BeemFlowError::Adapter(_)), "{}", err);
+        let err = err.to_string();
+        assert!(err.contains("decompress"), "{}: {}", route, err);
+        assert!(err.contains(expected), "{}: {}", route, err);
+    }
+}
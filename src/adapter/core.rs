@@ -94,6 +94,87 @@ impl CoreAdapter {
         Ok(result)
     }
 
+    /// Execute resume token tool - a token for a later `await_event` and its signed form
+    ///
+    /// Generates a 256-bit random token unless `token` is given. `signed` binds it
+    /// to the current run and expires after `expires_in` (default 24h); hand that to
+    /// third parties and match the run's `await_event` on `token`.
+    async fn execute_resume_token(
+        &self,
+        inputs: HashMap<String, Value>,
+        ctx: &super::ExecutionContext,
+    ) -> Result<HashMap<String, Value>> {
+        let run_id = ctx.run_id.ok_or_else(|| {
+            crate::BeemFlowError::adapter("core.resume_token can only be used in a run")
+        })?;
+        let token = match inputs.get("token") {
+            Some(Value::String(token)) if !token.trim().is_empty() => token.clone(),
+            Some(_) => {
+                return Err(crate::BeemFlowError::adapter(
+                    "core.resume_token 'token' must be a non-empty string",
+                ));
+            }
+            None => crate::model::ResumeToken::generate().into_inner(),
+        };
+        let expires_in = inputs
+            .get("expires_in")
+            .and_then(|v| v.as_str())
+            .unwrap_or(crate::engine::resume::DEFAULT_RESUME_TOKEN_TTL);
+        let expires_at = crate::utils::shift_time(
+            chrono::Utc::now(),
+            crate::utils::parse_duration(expires_in)?,
+            false,
+        )?;
+
+        let key = crate::engine::resume::signing_key(ctx.secrets_provider.as_ref()).await;
+        let signed = crate::engine::resume::sign(&key, &token, run_id, expires_at);
+
+        let mut result = HashMap::new();
+        result.insert("token".to_string(), Value::String(token));
+        result.insert("signed".to_string(), Value::String(signed));
+        result.insert("expires_at".to_string(), serde_json::to_value(expires_at)?);
+        Ok(result)
+    }
+
+    /// Execute publish tool - validates the event a `core.publish` step emits
+    ///
+    /// The executor enqueues the returned `topic` and `payload` in the event outbox
+    /// together with the step's record, so the event is published once the step
+    /// is durably complete.
+    async fn execute_publish(
+        &self,
+        inputs: HashMap<String, Value>,
+    ) -> Result<HashMap<String, Value>> {
+        let topic = inputs
+            .get("topic")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|topic| !topic.is_empty())
+            .ok_or_else(|| crate::BeemFlowError::adapter("core.publish requires a 'topic'"))?;
+        let payload = inputs
+            .get("payload")
+            .cloned()
+            .unwrap_or_else(|| Value::Object(Default::default()));
+
+        let mut result = HashMap::new();
+        result.insert("topic".to_string(), Value::String(topic.to_string()));
+        result.insert("payload".to_string(), payload);
+
+        Ok(result)
+    }
+
+    /// Execute deliver tool - send an HTTP request, queueing it for retry on failure
+    ///
+    /// See [`crate::engine::delivery`].
+    async fn execute_deliver(
+        &self,
+        inputs: HashMap<String, Value>,
+        ctx: &super::ExecutionContext,
+    ) -> Result<HashMap<String, Value>> {
+        let delivery = crate::engine::delivery::from_inputs(&inputs, ctx.run_id)?;
+        crate::engine::delivery::deliver(ctx, delivery).await
+    }
+
     /// Execute convert OpenAPI tool
     async fn execute_convert_openapi(
         &self,
@@ -406,14 +487,8 @@ impl Adapter for CoreAdapter {
     async fn execute(
         &self,
         inputs: HashMap<String, Value>,
-        _ctx: &super::ExecutionContext,
+        ctx: &super::ExecutionContext,
     ) -> Result<HashMap<String, Value>> {
-        // CoreAdapter doesn't currently use ExecutionContext, but it's available for
-        // future features like:
-        // - Persisting logs to storage (core.log)
-        // - Loading secrets for template expansion
-        // - User-specific rate limiting
-
         let use_field = inputs
             .get(PARAM_SPECIAL_USE)
             .and_then(|v| v.as_str())
@@ -424,6 +499,9 @@ impl Adapter for CoreAdapter {
             CORE_WAIT => self.execute_wait(inputs).await,
             CORE_LOG => self.execute_log(inputs).await,
             CORE_CONVERT_OPENAPI => self.execute_convert_openapi(inputs).await,
+            CORE_PUBLISH => self.execute_publish(inputs).await,
+            CORE_RESUME_TOKEN => self.execute_resume_token(inputs, ctx).await,
+            CORE_DELIVER => self.execute_deliver(inputs, ctx).await,
             _ => Err(crate::BeemFlowError::adapter(format!(
                 "unknown core tool: {}",
                 use_field
@@ -2,9 +2,19 @@
 
 use super::*;
 use crate::constants::*;
+use futures::StreamExt;
 use reqwest::{Client, Method};
 use std::str::FromStr;
 
+/// Transfers at least this large report progress
+const LARGE_TRANSFER_BYTES: u64 = 1024 * 1024;
+
+/// Bytes transferred between progress reports
+const PROGRESS_CHUNK_BYTES: u64 = 256 * 1024;
+
+/// Leading bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
 /// Type alias for HTTP request components (method, url, headers, body)
 type HttpRequestComponents = (String, String, HashMap<String, String>, Option<Value>);
 
@@ -17,11 +27,23 @@ pub struct HttpAdapter {
 
 impl HttpAdapter {
     /// Create a new HTTP adapter
+    ///
+    /// Responses compressed with gzip, brotli or deflate are decompressed
+    /// transparently.
     pub fn new(adapter_id: String, tool_manifest: Option<ToolManifest>) -> Self {
+        let client = Client::builder()
+            .gzip(true)
+            .brotli(true)
+            .deflate(true)
+            .build()
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to build HTTP client, using defaults: {}", e);
+                Client::new()
+            });
         Self {
             adapter_id,
             tool_manifest,
-            client: Client::new(),
+            client,
         }
     }
 
@@ -50,7 +72,7 @@ impl HttpAdapter {
         };
 
         // Expand OAuth tokens in headers with automatic refresh
-        self.expand_oauth_in_headers(&mut headers, &ctx.oauth_client)
+        self.expand_oauth_in_headers(&mut headers, &ctx.oauth_client, ctx.tenant_id.as_deref())
             .await;
 
         // Create request
@@ -60,6 +82,12 @@ impl HttpAdapter {
 
         let mut request = self.client.request(method, &url);
 
+        // Ask streaming tools' endpoints for an event stream
+        let stream = self.tool_manifest.as_ref().and_then(|m| m.stream.as_ref());
+        if stream.is_some() && !headers.keys().any(|k| k.eq_ignore_ascii_case("accept")) {
+            headers.insert("Accept".to_string(), CONTENT_TYPE_EVENT_STREAM.to_string());
+        }
+
         // Add headers with validation
         for (k, v) in &headers {
             // Validate header value - reqwest rejects invalid characters
@@ -67,16 +95,38 @@ impl HttpAdapter {
             request = request.header(k, v);
         }
 
-        // Add body if present
-        if let Some(body_val) = body {
-            if body_val.is_object() || body_val.is_array() {
-                request = request.json(&body_val);
-            } else if let Some(s) = body_val.as_str() {
-                request = request.body(s.to_string());
+        // Add body if present, encoded as the manifest asks
+        let body_format = self
+            .tool_manifest
+            .as_ref()
+            .map(|m| m.body_format)
+            .unwrap_or_default();
+        if let Some(body_val) = body
+            && let Some((payload, content_type)) = encode_body(body_format, &body_val)?
+        {
+            if let Some(content_type) = content_type
+                && !headers
+                    .keys()
+                    .any(|k| k.eq_ignore_ascii_case("content-type"))
+            {
+                request = request.header("content-type", content_type);
             }
+            request = Self::attach_body(request, payload, &ctx.progress);
+        }
+
+        // Wait for the tool's rate limit, if it declares one
+        if let Some(manifest) = &self.tool_manifest
+            && let Some(limit) = &manifest.rate_limit
+        {
+            ctx.rate_limiter.acquire(&manifest.name, limit).await?;
         }
 
         // Execute request
+        tracing::debug!(
+            request_id = ctx.request_id.as_deref(),
+            "Sending {} request",
+            self.adapter_id
+        );
         let response = request.send().await.map_err(|e| {
             crate::BeemFlowError::Network(crate::error::NetworkError::Http(e.to_string()))
         })?;
@@ -84,26 +134,100 @@ impl HttpAdapter {
         // Check status code
         let status = response.status();
 
+        // Assemble streamed deltas instead of buffering the events
+        if let Some(config) = stream
+            && status.is_success()
+            && super::stream::is_event_stream(&response)
+        {
+            let streamed =
+                super::stream::read_event_stream(response, config, &ctx.progress).await?;
+            ctx.usage.record_http_request(streamed.bytes);
+            if let Some(tokens) = streamed.last_event.as_ref().and_then(reported_llm_tokens) {
+                ctx.usage.record_llm_tokens(tokens);
+            }
+            let mut result = HashMap::new();
+            result.insert("text".to_string(), Value::String(streamed.text));
+            result.insert("events".to_string(), Value::from(streamed.events));
+            return Ok(result);
+        }
+
+        // Throttled responses may say when to retry
+        let retry_after = match status {
+            reqwest::StatusCode::TOO_MANY_REQUESTS | reqwest::StatusCode::SERVICE_UNAVAILABLE => {
+                response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| {
+                        crate::utils::retry::parse_retry_after(value, chrono::Utc::now())
+                    })
+            }
+            _ => None,
+        };
+
+        // The client strips Content-Encoding from responses it decompressed, so
+        // one still present wasn't decoded
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let content_encoding = header(reqwest::header::CONTENT_ENCODING);
+        let content_type = header(reqwest::header::CONTENT_TYPE);
+
         // Extract response body
-        let body_text = response.text().await.map_err(|e| {
-            crate::BeemFlowError::Network(crate::error::NetworkError::Http(e.to_string()))
-        })?;
+        let body = Self::read_body(response, &ctx.progress)
+            .await
+            .map_err(|e| {
+                if e.is_decode() {
+                    let cause = std::error::Error::source(&e)
+                        .map(ToString::to_string)
+                        .unwrap_or_else(|| e.to_string());
+                    crate::BeemFlowError::adapter(format!(
+                        "HTTP {} {}: could not decompress the response body: {}",
+                        method_str, url, cause
+                    ))
+                } else {
+                    crate::BeemFlowError::Network(crate::error::NetworkError::Http(e.to_string()))
+                }
+            })?;
+        let body_text = String::from_utf8_lossy(&body).into_owned();
+        ctx.usage.record_http_request(body_text.len() as u64);
 
         // Return error for non-2xx status codes
         if !status.is_success() {
-            return Err(crate::BeemFlowError::Network(
-                crate::error::NetworkError::Http(format!(
-                    "HTTP {} {}: status {}: {}",
-                    method_str,
-                    url,
-                    status.as_u16(),
-                    body_text
-                )),
-            ));
+            let message = format!(
+                "HTTP {} {}: status {}: {}",
+                method_str,
+                url,
+                status.as_u16(),
+                body_text
+            );
+            return Err(crate::BeemFlowError::Network(match retry_after {
+                Some(retry_after) => crate::error::NetworkError::RetryAfter {
+                    message,
+                    retry_after,
+                },
+                None => crate::error::NetworkError::Http(message),
+            }));
+        }
+
+        if let Some(reason) =
+            undecoded_body(&body, content_encoding.as_deref(), content_type.as_deref())
+        {
+            return Err(crate::BeemFlowError::adapter(format!(
+                "HTTP {} {}: {}",
+                method_str, url, reason
+            )));
         }
 
         // Try to parse as JSON
         if let Ok(json_value) = serde_json::from_str::<Value>(&body_text) {
+            if let Some(tokens) = reported_llm_tokens(&json_value) {
+                ctx.usage.record_llm_tokens(tokens);
+            }
             // For JSON objects, return the object directly (unwrapped)
             if let Some(obj) = json_value.as_object() {
                 return Ok(obj.clone().into_iter().collect());
@@ -120,6 +244,63 @@ impl HttpAdapter {
         Ok(result)
     }
 
+    /// Attach a request body, streaming large bodies to report upload progress
+    fn attach_body(
+        request: reqwest::RequestBuilder,
+        payload: Vec<u8>,
+        progress: &ProgressHandle,
+    ) -> reqwest::RequestBuilder {
+        let total = payload.len() as u64;
+        if total < LARGE_TRANSFER_BYTES || !progress.is_attached() {
+            return request.body(payload);
+        }
+
+        let progress = progress.clone();
+        let payload = bytes::Bytes::from(payload);
+        let chunks = (0..payload.len())
+            .step_by(PROGRESS_CHUNK_BYTES as usize)
+            .map(move |start| {
+                let end = (start + PROGRESS_CHUNK_BYTES as usize).min(payload.len());
+                progress.report(
+                    Some(end as f64 / total as f64 * 100.0),
+                    transfer_message("uploaded", end as u64, Some(total)),
+                );
+                Ok::<_, std::io::Error>(payload.slice(start..end))
+            });
+        request
+            .header(reqwest::header::CONTENT_LENGTH, total)
+            .body(reqwest::Body::wrap_stream(futures::stream::iter(chunks)))
+    }
+
+    /// Read the response body, reporting download progress for large responses
+    async fn read_body(
+        response: reqwest::Response,
+        progress: &ProgressHandle,
+    ) -> std::result::Result<Vec<u8>, reqwest::Error> {
+        let total = response.content_length();
+        let mut received: u64 = 0;
+        let mut next_report = LARGE_TRANSFER_BYTES;
+        let mut body = Vec::with_capacity(total.unwrap_or(0).min(LARGE_TRANSFER_BYTES) as usize);
+
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            received += chunk.len() as u64;
+            body.extend_from_slice(&chunk);
+
+            let large = total.unwrap_or(received) >= LARGE_TRANSFER_BYTES;
+            if large && (received >= next_report || Some(received) == total) {
+                next_report = received + PROGRESS_CHUNK_BYTES;
+                progress.report(
+                    total.map(|t| received as f64 / t as f64 * 100.0),
+                    transfer_message("downloaded", received, total),
+                );
+            }
+        }
+
+        Ok(body)
+    }
+
     async fn build_from_manifest(
         &self,
         manifest: &ToolManifest,
@@ -169,13 +350,16 @@ impl HttpAdapter {
         // Build body from inputs for non-GET requests
         let body = if method.to_uppercase() != HTTP_METHOD_GET {
             // Filter out path parameters from the body
-            let body_inputs: HashMap<String, Value> = inputs
+            let body_inputs: std::collections::BTreeMap<String, Value> = inputs
                 .iter()
                 .filter(|(k, _)| !path_params.contains(*k))
                 .map(|(k, v)| (k.clone(), v.clone()))
                 .collect();
 
-            if !body_inputs.is_empty() {
+            if manifest.body_format == BodyFormat::Raw {
+                // Raw bodies come from the `body` input alone
+                body_inputs.get("body").cloned()
+            } else if !body_inputs.is_empty() {
                 Some(serde_json::to_value(body_inputs)?)
             } else {
                 None
@@ -244,6 +428,7 @@ impl HttpAdapter {
         &self,
         headers: &mut HashMap<String, String>,
         oauth_client: &Arc<crate::auth::OAuthClientManager>,
+        tenant_id: Option<&str>,
     ) {
         let oauth_headers: Vec<_> = headers
             .iter()
@@ -252,7 +437,10 @@ impl HttpAdapter {
             .collect();
 
         for (key, value) in oauth_headers {
-            if let Some(token) = self.expand_oauth_token(&value, oauth_client).await {
+            if let Some(token) = self
+                .expand_oauth_token(&value, oauth_client, tenant_id)
+                .await
+            {
                 headers.insert(key, format!("Bearer {}", token));
             }
         }
@@ -271,12 +459,16 @@ impl HttpAdapter {
         &self,
         value: &str,
         oauth_client: &Arc<crate::auth::OAuthClientManager>,
+        tenant_id: Option<&str>,
     ) -> Option<String> {
         let oauth_ref = value.trim_start_matches("$oauth:");
         let mut parts = oauth_ref.split(':');
         let (provider, integration) = (parts.next()?, parts.next()?);
 
-        match oauth_client.get_token(provider, integration).await {
+        match oauth_client
+            .get_token_for_tenant(provider, integration, tenant_id)
+            .await
+        {
             Ok(token) => Some(token),
             Err(e) => {
                 tracing::error!(
@@ -423,3 +615,127 @@ impl Adapter for HttpAdapter {
         self
     }
 }
+
+/// Why a body read with transparent decompression is still compressed, if it is
+///
+/// `content_encoding` is what the client left on the response: an encoding it
+/// doesn't decode, or a stacked one such as `gzip, gzip`. A gzip stream in a
+/// JSON or text response means the server compressed the body twice but
+/// declared it once; other content types may legitimately be gzip files.
+fn undecoded_body(
+    body: &[u8],
+    content_encoding: Option<&str>,
+    content_type: Option<&str>,
+) -> Option<String> {
+    if body.is_empty() {
+        return None;
+    }
+    if let Some(encoding) = content_encoding
+        && !encoding.trim().eq_ignore_ascii_case("identity")
+    {
+        return Some(format!(
+            "response body could not be decompressed (content-encoding: {})",
+            encoding
+        ));
+    }
+    let textual = content_type.is_some_and(|value| {
+        let value = value.trim_start().to_ascii_lowercase();
+        value.starts_with("text/") || value.contains("json")
+    });
+    if textual && body.starts_with(&GZIP_MAGIC) {
+        return Some(
+            "response body is still gzip-compressed after decompression (content-encoding: gzip applied twice?)"
+                .to_string(),
+        );
+    }
+    None
+}
+
+/// Encode a request body, with the content type to send when none is set
+///
+/// JSON bodies that are neither objects, arrays nor strings are dropped, as
+/// before body formats existed. Form bodies are an object of fields (arrays
+/// repeat the key, nulls are skipped) or an already encoded string.
+fn encode_body(
+    format: BodyFormat,
+    body: &Value,
+) -> Result<Option<(Vec<u8>, Option<&'static str>)>> {
+    let encoded = match (format, body) {
+        (_, Value::Null) => None,
+        (BodyFormat::Json, Value::Object(_) | Value::Array(_)) => {
+            Some((serde_json::to_vec(body)?, Some(CONTENT_TYPE_JSON)))
+        }
+        (BodyFormat::Json | BodyFormat::Raw, Value::String(s)) => {
+            Some((s.as_bytes().to_vec(), None))
+        }
+        (BodyFormat::Json, _) => None,
+        (BodyFormat::Form, Value::Object(fields)) => {
+            let mut form = url::form_urlencoded::Serializer::new(String::new());
+            for (key, value) in fields {
+                match value {
+                    Value::Null => {}
+                    Value::Array(items) => {
+                        for item in items {
+                            form.append_pair(key, &form_value(item));
+                        }
+                    }
+                    value => {
+                        form.append_pair(key, &form_value(value));
+                    }
+                }
+            }
+            Some((form.finish().into_bytes(), Some(CONTENT_TYPE_FORM)))
+        }
+        (BodyFormat::Form, Value::String(s)) => {
+            Some((s.as_bytes().to_vec(), Some(CONTENT_TYPE_FORM)))
+        }
+        (BodyFormat::Form, _) => {
+            return Err(crate::BeemFlowError::adapter(
+                "form body must be an object of fields or an encoded string",
+            ));
+        }
+        (BodyFormat::Raw, _) => {
+            return Err(crate::BeemFlowError::adapter("raw body must be a string"));
+        }
+    };
+    Ok(encoded)
+}
+
+/// Tokens a model provider reports in a response's `usage` block
+///
+/// Reads `total_tokens` (OpenAI), falling back to `input_tokens + output_tokens`
+/// (Anthropic).
+fn reported_llm_tokens(response: &Value) -> Option<u64> {
+    let usage = response.get("usage")?.as_object()?;
+    if let Some(total) = usage.get("total_tokens").and_then(Value::as_u64) {
+        return Some(total);
+    }
+    let input = usage.get("input_tokens").and_then(Value::as_u64);
+    let output = usage.get("output_tokens").and_then(Value::as_u64);
+    match (input, output) {
+        (None, None) => None,
+        (input, output) => Some(input.unwrap_or(0) + output.unwrap_or(0)),
+    }
+}
+
+/// Form field value: strings as is, anything else as JSON
+fn form_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Describe transfer progress, e.g. "downloaded 1.5 MiB of 4.0 MiB"
+fn transfer_message(verb: &str, done: u64, total: Option<u64>) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    match total {
+        Some(total) => format!(
+            "{} {:.1} MiB of {:.1} MiB",
+            verb,
+            done as f64 / MIB,
+            total as f64 / MIB
+        ),
+        None => format!("{} {:.1} MiB", verb, done as f64 / MIB),
+    }
+}
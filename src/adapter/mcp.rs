@@ -24,10 +24,42 @@ impl McpAdapter {
         self.manager.register_server(name, config);
     }
 
+    /// Names of the MCP servers this adapter can route to
+    pub fn server_names(&self) -> Vec<String> {
+        self.manager.server_names()
+    }
+
+    /// Config to start a dedicated instance of `server_name` with, if the call needs one
+    ///
+    /// That is when the flow declares the server itself, or when the step sets
+    /// `env`, which is merged over the server's own.
+    fn server_override(
+        &self,
+        server_name: &str,
+        ctx: &ExecutionContext,
+    ) -> Option<McpServerConfig> {
+        let declared = ctx.mcp_servers.get(server_name).cloned();
+        if ctx.env.is_empty() {
+            return declared;
+        }
+        let mut config = declared.or_else(|| self.manager.server_config(server_name))?;
+        config
+            .env
+            .get_or_insert_with(HashMap::new)
+            .extend(ctx.env.clone());
+        Some(config)
+    }
+
+    /// Manager holding the downstream server connections
+    pub fn manager(&self) -> &Arc<McpManager> {
+        &self.manager
+    }
+
     async fn execute_mcp_call(
         &self,
         tool_use: &str,
         inputs: HashMap<String, Value>,
+        ctx: &ExecutionContext,
     ) -> Result<HashMap<String, Value>> {
         if !tool_use.starts_with(ADAPTER_PREFIX_MCP) {
             return Err(crate::BeemFlowError::adapter(format!(
@@ -56,10 +88,31 @@ impl McpAdapter {
             )));
         }
 
-        let result = self
-            .manager
-            .call_tool(server_name, tool_name, serde_json::to_value(&inputs)?)
-            .await?;
+        tracing::debug!(
+            request_id = ctx.request_id.as_deref(),
+            "Calling MCP tool {} on {}",
+            tool_name,
+            server_name
+        );
+        let arguments = serde_json::to_value(&inputs)?;
+        let result = match self.server_override(server_name, ctx) {
+            Some(config) => {
+                self.manager
+                    .call_tool_with_config(
+                        server_name,
+                        &config,
+                        tool_name,
+                        arguments,
+                        &ctx.progress,
+                    )
+                    .await?
+            }
+            None => {
+                self.manager
+                    .call_tool_with_progress(server_name, tool_name, arguments, &ctx.progress)
+                    .await?
+            }
+        };
 
         let mut outputs = HashMap::new();
         if let Some(content) = result.get("content") {
@@ -81,10 +134,11 @@ impl Adapter for McpAdapter {
     async fn execute(
         &self,
         inputs: HashMap<String, Value>,
-        _ctx: &super::ExecutionContext,
+        ctx: &super::ExecutionContext,
     ) -> Result<HashMap<String, Value>> {
-        // McpAdapter doesn't currently use ExecutionContext, but it's available for
-        // future features like:
+        // McpAdapter uses ExecutionContext to forward server progress
+        // notifications and to pick the server instance (flow-declared servers,
+        // step `env`); the rest is available for future features like:
         // - Passing OAuth credentials to MCP servers
         // - User-specific server instances (multi-tenancy)
         // - Rate limiting per user
@@ -95,7 +149,8 @@ impl Adapter for McpAdapter {
             .ok_or_else(|| crate::BeemFlowError::adapter("missing __use for MCPAdapter"))?
             .to_string();
 
-        self.execute_mcp_call(&tool_use, inputs).await
+        ctx.usage.record_mcp_call();
+        self.execute_mcp_call(&tool_use, inputs, ctx).await
     }
 
     fn manifest(&self) -> Option<ToolManifest> {
@@ -0,0 +1,177 @@
+//! Scripted adapters for testing flows (`testing` feature)
+//!
+//! A [`MockAdapter`] stands in for one tool: it is registered under the tool's
+//! name, which the executor resolves before any built-in adapter. Each call
+//! takes the next [`MockResponse`] from its script; once the script runs out,
+//! the `otherwise` response answers every further call, and without one the
+//! call fails. The inputs of every call are recorded for assertions.
+//!
+//! ```rust,ignore
+//! use beemflow::adapter::mock::{MockAdapter, MockResponse, mock_registry};
+//!
+//! let charge = Arc::new(
+//!     MockAdapter::new("payments.charge")
+//!         .then(MockResponse::err("gateway timeout"))
+//!         .then(MockResponse::ok(json!({"id": "ch_1"}))),
+//! );
+//! let engine = Engine::for_testing_with(mock_registry([charge.clone()])).await;
+//! engine.execute(&flow, event).await?;
+//! assert_eq!(charge.call_count(), 2);
+//! ```
+
+use super::{Adapter, AdapterRegistry, ExecutionContext, ToolManifest};
+use crate::{BeemFlowError, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// What a [`MockAdapter`] does when called
+#[derive(Debug, Clone)]
+pub enum MockResponse {
+    /// Return these outputs
+    Ok(HashMap<String, Value>),
+    /// Fail with an adapter error carrying this message
+    Err(String),
+    /// Wait, then respond with the inner response
+    Delay(Duration, Box<MockResponse>),
+    /// Panic with this message
+    Panic(String),
+}
+
+impl MockResponse {
+    /// Return `outputs`, which must be a JSON object
+    pub fn ok(outputs: Value) -> Self {
+        match outputs {
+            Value::Object(outputs) => Self::Ok(outputs.into_iter().collect()),
+            other => panic!("mock outputs must be a JSON object, got {}", other),
+        }
+    }
+
+    /// Fail with an adapter error
+    pub fn err(message: impl Into<String>) -> Self {
+        Self::Err(message.into())
+    }
+
+    /// Respond with `response` after `latency`
+    pub fn delayed(latency: Duration, response: MockResponse) -> Self {
+        Self::Delay(latency, Box::new(response))
+    }
+
+    /// Panic inside the adapter call
+    pub fn panic(message: impl Into<String>) -> Self {
+        Self::Panic(message.into())
+    }
+}
+
+/// Adapter answering calls to one tool from a script
+pub struct MockAdapter {
+    tool: String,
+    script: Mutex<VecDeque<MockResponse>>,
+    otherwise: Option<MockResponse>,
+    calls: Mutex<Vec<HashMap<String, Value>>>,
+}
+
+impl MockAdapter {
+    /// Mock for `tool` with an empty script
+    pub fn new(tool: impl Into<String>) -> Self {
+        Self {
+            tool: tool.into(),
+            script: Mutex::default(),
+            otherwise: None,
+            calls: Mutex::default(),
+        }
+    }
+
+    /// Append `response` to the script
+    pub fn then(self, response: MockResponse) -> Self {
+        self.lock_script().push_back(response);
+        self
+    }
+
+    /// Respond with `response` once the script is exhausted
+    pub fn otherwise(mut self, response: MockResponse) -> Self {
+        self.otherwise = Some(response);
+        self
+    }
+
+    /// Inputs of every call so far, in call order
+    pub fn calls(&self) -> Vec<HashMap<String, Value>> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Number of calls so far
+    pub fn call_count(&self) -> usize {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    fn lock_script(&self) -> std::sync::MutexGuard<'_, VecDeque<MockResponse>> {
+        self.script.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl Adapter for MockAdapter {
+    fn id(&self) -> &str {
+        &self.tool
+    }
+
+    async fn execute(
+        &self,
+        inputs: HashMap<String, Value>,
+        _ctx: &ExecutionContext,
+    ) -> Result<HashMap<String, Value>> {
+        self.calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(inputs);
+
+        let next = self.lock_script().pop_front();
+        let mut response = next.or_else(|| self.otherwise.clone()).ok_or_else(|| {
+            BeemFlowError::adapter(format!(
+                "mock '{}' has no response scripted for call {}",
+                self.tool,
+                self.call_count()
+            ))
+        })?;
+
+        loop {
+            match response {
+                MockResponse::Ok(outputs) => return Ok(outputs),
+                MockResponse::Err(message) => return Err(BeemFlowError::adapter(message)),
+                MockResponse::Delay(latency, inner) => {
+                    tokio::time::sleep(latency).await;
+                    response = *inner;
+                }
+                MockResponse::Panic(message) => panic!("{}", message),
+            }
+        }
+    }
+
+    fn manifest(&self) -> Option<ToolManifest> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// Adapter registry holding just `mocks`, for [`Engine::for_testing_with`]
+///
+/// Tools are never lazy loaded from a tool registry, so a step using a tool
+/// that isn't mocked or built in fails instead of reaching the network.
+///
+/// [`Engine::for_testing_with`]: crate::Engine::for_testing_with
+pub fn mock_registry(mocks: impl IntoIterator<Item = Arc<MockAdapter>>) -> AdapterRegistry {
+    let registry_manager = Arc::new(crate::registry::RegistryManager::new(
+        Vec::new(),
+        Arc::new(crate::secrets::EnvSecretsProvider::new()),
+    ));
+    let adapters = AdapterRegistry::new(registry_manager);
+    for mock in mocks {
+        adapters.register(mock);
+    }
+    adapters
+}
@@ -44,6 +44,12 @@
 pub mod core;
 pub mod http;
 pub mod mcp;
+#[cfg(any(test, feature = "testing"))]
+pub mod mock;
+pub mod progress;
+pub mod rate_limit;
+pub mod stream;
+pub mod usage;
 
 use crate::Result;
 use crate::storage::Storage;
@@ -81,9 +87,7 @@ use std::sync::Arc;
 ///     // Future additions (no trait changes needed!):
 ///     pub user_id: Option<String>,          // Who triggered this execution?
 ///     pub permissions: Arc<Permissions>,    // What can they access?
-///     pub rate_limiter: Arc<RateLimiter>,   // Prevent abuse
 ///     pub audit_log: Arc<AuditLogger>,      // Track all actions
-///     pub request_id: String,               // For tracing/debugging
 /// }
 /// ```
 ///
@@ -126,6 +130,59 @@ pub struct ExecutionContext {
     /// - HttpAdapter calls: `ctx.oauth_client.get_token("github", "default")`
     /// - Token is automatically refreshed if expired and injected into request headers
     pub oauth_client: Arc<crate::auth::OAuthClientManager>,
+
+    /// Progress reporter for the step being executed
+    ///
+    /// Long-running adapters call `ctx.progress.report(percent, message)`; the
+    /// executor persists the latest report on the running step and uses it to
+    /// reset the step's `timeout_idle` deadline.
+    pub progress: ProgressHandle,
+
+    /// Blob stores available to the step, see [`ExecutionContext::blob_store`]
+    pub blob_stores: Arc<crate::blob::BlobStores>,
+
+    /// Named blob store selected by the step or its flow (`None` for the default store)
+    pub blob_store_name: Option<String>,
+
+    /// Resource usage counters of the run the step belongs to
+    ///
+    /// HttpAdapter counts requests, response bytes and reported LLM tokens;
+    /// McpAdapter counts calls.
+    pub usage: UsageMeter,
+
+    /// Rate limiter shared by every tool call the engine makes
+    ///
+    /// HttpAdapter acquires a permit before calling a tool that declares a
+    /// `rate_limit` in its registry entry.
+    pub rate_limiter: RateLimiter,
+
+    /// Environment overrides from the step's `env`, templates rendered
+    pub env: HashMap<String, String>,
+
+    /// MCP servers declared by the flow, with templates in their `env` rendered
+    /// for the run
+    ///
+    /// McpAdapter prefers these over servers configured globally, so runs of
+    /// flows declaring the same server name don't share a server process.
+    pub mcp_servers: Arc<HashMap<String, crate::model::McpServerConfig>>,
+
+    /// Run the step belongs to, if any
+    ///
+    /// Used by `core.resume_token` to bind signed tokens to their run.
+    pub run_id: Option<uuid::Uuid>,
+
+    /// Tenant of the run the step belongs to (see [`crate::auth::tenant`])
+    ///
+    /// HttpAdapter only expands `$oauth:` references to credentials of this
+    /// tenant or shared ones.
+    pub tenant_id: Option<String>,
+
+    /// Request ID of the run the step belongs to (its correlation ID)
+    ///
+    /// For runs started over HTTP this is the request's `X-Request-Id`. The
+    /// executor's `tool` span carries it, and adapters include it in their
+    /// logs, so adapter logs correlate with the request's.
+    pub request_id: Option<String>,
     // Future fields will be added here as needed without breaking changes
 }
 
@@ -136,12 +193,109 @@ impl ExecutionContext {
         secrets_provider: Arc<dyn crate::secrets::SecretsProvider>,
         oauth_client: Arc<crate::auth::OAuthClientManager>,
     ) -> Self {
+        let blob_stores = Arc::new(crate::blob::BlobStores::new(
+            crate::blob::BlobConfig::default(),
+            HashMap::new(),
+            storage.clone(),
+        ));
         Self {
             storage,
             secrets_provider,
             oauth_client,
+            progress: ProgressHandle::default(),
+            blob_stores,
+            blob_store_name: None,
+            usage: UsageMeter::default(),
+            rate_limiter: RateLimiter::default(),
+            env: HashMap::new(),
+            mcp_servers: Arc::default(),
+            run_id: None,
+            tenant_id: None,
+            request_id: None,
         }
     }
+
+    /// Attach a progress handle observed by the executor
+    pub fn with_progress(mut self, progress: ProgressHandle) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Count resource usage on `usage`
+    pub fn with_usage(mut self, usage: UsageMeter) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Pace rate-limited tool calls with `rate_limiter`
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Override the environment of the process behind the tool with `env`
+    pub fn with_env(mut self, env: HashMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Route MCP calls to the servers the flow declares in `mcp_servers`
+    pub fn with_mcp_servers(
+        mut self,
+        mcp_servers: Arc<HashMap<String, crate::model::McpServerConfig>>,
+    ) -> Self {
+        self.mcp_servers = mcp_servers;
+        self
+    }
+
+    /// Attribute the step to the run `run_id`
+    pub fn with_run_id(mut self, run_id: Option<uuid::Uuid>) -> Self {
+        self.run_id = run_id;
+        self
+    }
+
+    /// Scope the step's OAuth credentials to the tenant `tenant_id`
+    pub fn with_tenant_id(mut self, tenant_id: Option<String>) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    /// Tag the step's logs with the request ID `request_id`
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// Use `blob_stores`, writing to the store named `name` (default store when `None`)
+    pub fn with_blob_store(
+        mut self,
+        blob_stores: Arc<crate::blob::BlobStores>,
+        name: Option<String>,
+    ) -> Self {
+        self.blob_stores = blob_stores;
+        self.blob_store_name = name;
+        self
+    }
+
+    /// Blob store the step writes to
+    ///
+    /// Fails when the step or flow names a store that isn't configured.
+    pub async fn blob_store(&self) -> Result<Arc<dyn crate::blob::BlobStore>> {
+        self.blob_stores.get(self.blob_store_name.as_deref()).await
+    }
+}
+
+/// How an HTTP tool encodes its request body
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyFormat {
+    /// JSON, sent as `application/json`
+    #[default]
+    Json,
+    /// Form fields, sent as `application/x-www-form-urlencoded`
+    Form,
+    /// The `body` input sent verbatim
+    Raw,
 }
 
 /// Tool manifest information
@@ -155,6 +309,180 @@ pub struct ToolManifest {
     pub endpoint: Option<String>,
     pub method: Option<String>,
     pub headers: Option<HashMap<String, String>>,
+    pub body_format: BodyFormat,
+    pub rate_limit: Option<RateLimit>,
+    pub stream: Option<StreamConfig>,
+}
+
+impl ToolManifest {
+    /// Validate and normalize rendered inputs against the declared `parameters` schema
+    ///
+    /// Rejects unknown keys and type mismatches, coerces scalar strings produced by
+    /// templates into their declared types, and applies literal defaults for missing
+    /// parameters (`$env:` defaults are left for the adapter to resolve). Manifests
+    /// without `properties` accept any inputs unchanged.
+    pub fn normalize_inputs(
+        &self,
+        mut inputs: HashMap<String, Value>,
+    ) -> Result<HashMap<String, Value>> {
+        self.check_inputs(&mut inputs, false)?;
+        Ok(inputs)
+    }
+
+    /// Statically check a step's `with` block against the declared `parameters` schema
+    ///
+    /// Templated values can only be checked once rendered, so their types are skipped.
+    pub fn check_static_inputs(&self, inputs: &HashMap<String, Value>) -> Result<()> {
+        self.check_inputs(&mut inputs.clone(), true)
+    }
+
+    fn check_inputs(&self, inputs: &mut HashMap<String, Value>, is_static: bool) -> Result<()> {
+        let Some(properties) = self
+            .parameters
+            .get("properties")
+            .and_then(|v| v.as_object())
+        else {
+            return Ok(());
+        };
+
+        let allows_additional = matches!(
+            self.parameters.get("additionalProperties"),
+            Some(Value::Bool(true)) | Some(Value::Object(_))
+        );
+
+        if !allows_additional {
+            let mut unknown: Vec<&String> = inputs
+                .keys()
+                .filter(|k| !k.starts_with("__") && !properties.contains_key(*k))
+                .collect();
+            unknown.sort();
+            if let Some(key) = unknown.first() {
+                let mut known: Vec<&String> = properties.keys().collect();
+                known.sort();
+                return Err(crate::BeemFlowError::validation(format!(
+                    "tool '{}': unknown parameter '{}' (expected one of: {})",
+                    self.name,
+                    key,
+                    known
+                        .iter()
+                        .map(|k| k.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )));
+            }
+        }
+
+        for (key, schema) in properties {
+            match inputs.get_mut(key) {
+                Some(value) => {
+                    if is_static && is_templated(value) {
+                        continue;
+                    }
+                    if let Some(expected) = schema.get("type") {
+                        coerce_to_type(value, expected);
+                        if !matches_type(value, expected) {
+                            return Err(crate::BeemFlowError::validation(format!(
+                                "tool '{}': parameter '{}' expected type {}, got {}",
+                                self.name,
+                                key,
+                                expected,
+                                json_type_name(value)
+                            )));
+                        }
+                    }
+                }
+                None => {
+                    if let Some(default) = schema.get("default") {
+                        let env_default = default.as_str().is_some_and(|s| s.starts_with("$env:"));
+                        if !is_static && !env_default {
+                            inputs.insert(key.clone(), default.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(required) = self.parameters.get("required").and_then(|v| v.as_array()) {
+            for key in required.iter().filter_map(|k| k.as_str()) {
+                let has_default = properties
+                    .get(key)
+                    .is_some_and(|schema| schema.get("default").is_some());
+                if !inputs.contains_key(key) && !has_default {
+                    return Err(crate::BeemFlowError::validation(format!(
+                        "tool '{}': missing required parameter '{}'",
+                        self.name, key
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Check whether a value still contains template syntax
+fn is_templated(value: &Value) -> bool {
+    match value {
+        Value::String(s) => s.contains("{{") || s.contains("{%"),
+        Value::Array(items) => items.iter().any(is_templated),
+        Value::Object(map) => map.values().any(is_templated),
+        _ => false,
+    }
+}
+
+/// Check a value against a JSON schema `type` (a single name or a list of names)
+fn matches_type(value: &Value, expected: &Value) -> bool {
+    match expected {
+        Value::String(name) => match name.as_str() {
+            "string" => value.is_string(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "boolean" => value.is_boolean(),
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "null" => value.is_null(),
+            _ => true,
+        },
+        Value::Array(names) => names.iter().any(|name| matches_type(value, name)),
+        _ => true,
+    }
+}
+
+/// Coerce a rendered template string into the declared scalar or JSON type when possible
+fn coerce_to_type(value: &mut Value, expected: &Value) {
+    let (Value::String(s), Value::String(name)) = (&*value, expected) else {
+        return;
+    };
+
+    let coerced = match name.as_str() {
+        "integer" => s.trim().parse::<i64>().ok().map(Value::from),
+        "number" => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(|n| serde_json::Number::from_f64(n).map(Value::Number)),
+        "boolean" => s.trim().parse::<bool>().ok().map(Value::Bool),
+        "object" | "array" => serde_json::from_str::<Value>(s)
+            .ok()
+            .filter(|v| matches_type(v, expected)),
+        _ => None,
+    };
+
+    if let Some(coerced) = coerced {
+        *value = coerced;
+    }
+}
+
+/// Human-readable JSON type name for error messages
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
 }
 
 /// Adapter trait for tool execution
@@ -205,6 +533,9 @@ pub trait Adapter: Send + Sync {
 pub struct AdapterRegistry {
     adapters: Arc<DashMap<String, Arc<dyn Adapter>>>,
     registry_manager: Arc<crate::registry::RegistryManager>,
+    rate_limiter: RateLimiter,
+    /// IDs of the adapters built from registry entries
+    registry_tools: Arc<dashmap::DashSet<String>>,
 }
 
 impl AdapterRegistry {
@@ -217,6 +548,8 @@ impl AdapterRegistry {
         Self {
             adapters: Arc::new(DashMap::new()),
             registry_manager,
+            rate_limiter: RateLimiter::default(),
+            registry_tools: Arc::default(),
         }
     }
 
@@ -225,6 +558,28 @@ impl AdapterRegistry {
         self.adapters.insert(adapter.id().to_string(), adapter);
     }
 
+    /// Register an adapter built from a registry entry
+    ///
+    /// Unlike [`AdapterRegistry::register`], the adapter is dropped by
+    /// [`AdapterRegistry::unload_registry_tools`].
+    pub fn register_registry_tool(&self, adapter: Arc<dyn Adapter>) {
+        self.registry_tools.insert(adapter.id().to_string());
+        self.register(adapter);
+    }
+
+    /// Drop every adapter built from a registry entry, returning how many
+    ///
+    /// They are loaded again from the registries on next use, picking up
+    /// changed entries.
+    pub fn unload_registry_tools(&self) -> usize {
+        let tools: Vec<String> = self.registry_tools.iter().map(|t| t.clone()).collect();
+        for tool in &tools {
+            self.adapters.remove(tool);
+            self.registry_tools.remove(tool);
+        }
+        tools.len()
+    }
+
     /// Get a registered adapter by ID (synchronous, no lazy loading)
     ///
     /// This is used internally for built-in adapters (core, mcp, http) during prefix matching.
@@ -265,6 +620,9 @@ impl AdapterRegistry {
                     endpoint: entry.endpoint,
                     method: entry.method,
                     headers: entry.headers,
+                    body_format: entry.body_format.unwrap_or_default(),
+                    rate_limit: entry.rate_limit,
+                    stream: entry.stream.as_ref().and_then(StreamSetting::config),
                 };
 
                 // Create HTTP adapter with this manifest
@@ -272,6 +630,7 @@ impl AdapterRegistry {
                     as Arc<dyn Adapter>;
 
                 // Cache for future use
+                self.registry_tools.insert(tool_name.to_string());
                 self.adapters.insert(tool_name.to_string(), adapter.clone());
 
                 tracing::info!("Loaded tool '{}' from registry", tool_name);
@@ -282,6 +641,16 @@ impl AdapterRegistry {
         None
     }
 
+    /// Registry manager used for lazy loading
+    pub fn registry_manager(&self) -> &Arc<crate::registry::RegistryManager> {
+        &self.registry_manager
+    }
+
+    /// Rate limiter shared by the tool calls of every run using this registry
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
     /// Get all adapters
     pub fn all(&self) -> Vec<Arc<dyn Adapter>> {
         self.adapters
@@ -295,6 +664,10 @@ pub use core::CoreAdapter;
 pub use http::HttpAdapter;
 
 pub use mcp::McpAdapter;
+pub use progress::ProgressHandle;
+pub use rate_limit::{RateLimit, RateLimiter};
+pub use stream::{StreamConfig, StreamSetting};
+pub use usage::UsageMeter;
 
 #[cfg(test)]
 mod adapter_test;
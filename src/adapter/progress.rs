@@ -0,0 +1,53 @@
+//! Progress reporting for long-running tool calls
+//!
+//! Adapters report progress through [`ExecutionContext::progress`](super::ExecutionContext).
+//! The executor watches the handle to persist the latest report onto the
+//! in-flight step and to reset a step's `timeout_idle` deadline.
+
+use crate::model::StepProgress;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Handle adapters use to report progress of the current step
+///
+/// The default handle is detached: reports are accepted and dropped, so adapters
+/// can report unconditionally.
+#[derive(Clone, Default)]
+pub struct ProgressHandle {
+    sender: Option<Arc<watch::Sender<Option<StepProgress>>>>,
+}
+
+impl ProgressHandle {
+    /// Create a handle together with a receiver observing its reports
+    pub fn channel() -> (Self, watch::Receiver<Option<StepProgress>>) {
+        let (sender, receiver) = watch::channel(None);
+        (
+            Self {
+                sender: Some(Arc::new(sender)),
+            },
+            receiver,
+        )
+    }
+
+    /// Report progress; `percent` is clamped to 0-100
+    ///
+    /// Only the latest report is kept, so calling this frequently is cheap.
+    pub fn report(&self, percent: Option<f64>, message: impl Into<String>) {
+        let Some(ref sender) = self.sender else {
+            return;
+        };
+        let message = message.into();
+        sender.send_replace(Some(StepProgress {
+            percent: percent
+                .filter(|p| p.is_finite())
+                .map(|p| p.clamp(0.0, 100.0)),
+            message: (!message.is_empty()).then_some(message),
+            updated_at: chrono::Utc::now(),
+        }));
+    }
+
+    /// Whether anyone is observing reports from this handle
+    pub fn is_attached(&self) -> bool {
+        self.sender.is_some()
+    }
+}
@@ -0,0 +1,112 @@
+//! Token-bucket rate limiting of tool calls
+//!
+//! Every [`ExecutionContext`](super::ExecutionContext) carries the engine's
+//! [`RateLimiter`], so calls from all runs, `parallel` blocks and `foreach`
+//! iterations draw from the same buckets. A tool opts in with `rate_limit` in
+//! its registry entry; adapters acquire a permit before each call and wait
+//! until the tool's bucket has one, which spaces out bursts instead of letting
+//! them hit the API's own limit.
+
+use crate::{BeemFlowError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Call rate allowed for a tool (`rate_limit: { per_second, burst }`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Sustained calls per second; fractions allow slower rates
+    pub per_second: f64,
+
+    /// Calls allowed back to back before pacing starts (default: 1)
+    #[serde(default = "default_burst", skip_serializing_if = "is_default_burst")]
+    pub burst: u32,
+}
+
+fn default_burst() -> u32 {
+    1
+}
+
+fn is_default_burst(burst: &u32) -> bool {
+    *burst == default_burst()
+}
+
+impl RateLimit {
+    /// Check the limit allows any calls at all
+    pub fn validate(&self) -> Result<()> {
+        if !self.per_second.is_finite() || self.per_second <= 0.0 {
+            return Err(BeemFlowError::validation(format!(
+                "rate_limit.per_second must be a positive number, got {}",
+                self.per_second
+            )));
+        }
+        if self.burst == 0 {
+            return Err(BeemFlowError::validation(
+                "rate_limit.burst must be at least 1",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Shared token buckets, one per key (a tool name or endpoint)
+///
+/// Clones share the same buckets.
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    /// Wait for a permit to call `key`, paced by `limit`
+    ///
+    /// The bucket for `key` is created full on first use and follows the latest
+    /// `limit` it is acquired with. Waiters are served in the order they arrive.
+    pub async fn acquire(&self, key: &str, limit: &RateLimit) -> Result<()> {
+        limit.validate()?;
+        let wait = {
+            let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            buckets
+                .entry(key.to_string())
+                .or_insert_with(|| Bucket::full(limit, now))
+                .reserve(limit, now)
+        };
+        if !wait.is_zero() {
+            tracing::debug!("Rate limited call to {}: waiting {:?}", key, wait);
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+}
+
+struct Bucket {
+    /// Permits available; negative when callers are already waiting
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn full(limit: &RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit.burst),
+            refilled_at: now,
+        }
+    }
+
+    /// Take a permit, returning how long to wait until it is due
+    fn reserve(&mut self, limit: &RateLimit, now: Instant) -> Duration {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.per_second).min(f64::from(limit.burst));
+        self.refilled_at = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / limit.per_second)
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Streamed (`text/event-stream`) responses of HTTP tools
+//!
+//! A tool with `stream` in its registry entry reads a `text/event-stream`
+//! response as Server-Sent Events instead of buffering one body. The `data:` of
+//! each event is parsed as JSON and the string at `delta_path` is appended to
+//! the result, the way OpenAI's `stream: true` chat completions send
+//! `choices.0.delta.content` a few tokens at a time. Events whose data isn't
+//! JSON are appended as is. The stream ends at `data: [DONE]` or when the
+//! server closes it, and fails once it passes `max_bytes` or `timeout_secs`.
+
+use super::ProgressHandle;
+use crate::error::NetworkError;
+use crate::{BeemFlowError, Result};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Delta path of OpenAI chat completion chunks
+pub const DEFAULT_DELTA_PATH: &str = "choices.0.delta.content";
+
+/// Default cap on the bytes read from a stream (10 MiB)
+pub const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Default cap on how long a stream may run, in seconds
+pub const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Data of the event that ends an OpenAI stream
+const DONE_MARKER: &str = "[DONE]";
+
+/// How an HTTP tool reads a streamed response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamConfig {
+    /// Dot-separated path of each event's delta, numbers indexing arrays
+    pub delta_path: String,
+
+    /// Bytes the stream may send before it is cut off
+    pub max_bytes: u64,
+
+    /// Seconds the stream may run before it is cut off
+    pub timeout_secs: u64,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            delta_path: DEFAULT_DELTA_PATH.to_string(),
+            max_bytes: DEFAULT_MAX_BYTES,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+        }
+    }
+}
+
+/// Registry form of `stream`: `true` for the defaults, or an object overriding
+/// any of them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StreamSetting {
+    Enabled(bool),
+    Custom(StreamOverrides),
+}
+
+/// Settings of a `stream` object; missing ones take their defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StreamOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+impl StreamSetting {
+    /// Stream handling to use, or none when streaming is off
+    pub fn config(&self) -> Option<StreamConfig> {
+        match self {
+            StreamSetting::Enabled(false) => None,
+            StreamSetting::Enabled(true) => Some(StreamConfig::default()),
+            StreamSetting::Custom(overrides) => {
+                let defaults = StreamConfig::default();
+                Some(StreamConfig {
+                    delta_path: overrides.delta_path.clone().unwrap_or(defaults.delta_path),
+                    max_bytes: overrides.max_bytes.unwrap_or(defaults.max_bytes),
+                    timeout_secs: overrides.timeout_secs.unwrap_or(defaults.timeout_secs),
+                })
+            }
+        }
+    }
+}
+
+/// Text assembled from a stream
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamedResponse {
+    /// Concatenated deltas
+    pub text: String,
+    /// Events received, not counting `[DONE]`
+    pub events: u64,
+    /// Bytes received
+    pub bytes: u64,
+    /// Data of the last JSON event, which carries `usage` and stop reasons
+    pub last_event: Option<Value>,
+}
+
+/// Whether a response is an event stream
+pub fn is_event_stream(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .trim_start()
+                .starts_with(crate::constants::CONTENT_TYPE_EVENT_STREAM)
+        })
+}
+
+/// Read an event stream to its end, assembling the deltas at `config.delta_path`
+pub async fn read_event_stream(
+    response: reqwest::Response,
+    config: &StreamConfig,
+    progress: &ProgressHandle,
+) -> Result<StreamedResponse> {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(config.timeout_secs);
+    let mut assembler = Assembler::new(config);
+    let mut stream = response.bytes_stream();
+
+    loop {
+        let chunk = match tokio::time::timeout_at(deadline, stream.next()).await {
+            Ok(Some(chunk)) => {
+                chunk.map_err(|e| BeemFlowError::Network(NetworkError::Http(e.to_string())))?
+            }
+            Ok(None) => break,
+            Err(_) => {
+                return Err(BeemFlowError::Network(NetworkError::Http(format!(
+                    "event stream still open after {}s",
+                    config.timeout_secs
+                ))));
+            }
+        };
+
+        let events = assembler.result.events;
+        if assembler.push(&chunk)? {
+            return Ok(assembler.finish());
+        }
+        if assembler.result.events > events {
+            progress.report(
+                None,
+                format!("received {} streamed events", assembler.result.events),
+            );
+        }
+    }
+
+    assembler.end();
+    Ok(assembler.finish())
+}
+
+/// Incremental Server-Sent Events parser that assembles deltas
+struct Assembler<'a> {
+    config: &'a StreamConfig,
+    path: Vec<&'a str>,
+    /// Bytes of the current, unterminated line
+    line: Vec<u8>,
+    /// `data:` lines of the current event
+    data: Option<String>,
+    result: StreamedResponse,
+}
+
+impl<'a> Assembler<'a> {
+    fn new(config: &'a StreamConfig) -> Self {
+        Self {
+            config,
+            path: config
+                .delta_path
+                .split('.')
+                .filter(|segment| !segment.is_empty())
+                .collect(),
+            line: Vec::new(),
+            data: None,
+            result: StreamedResponse::default(),
+        }
+    }
+
+    /// Feed received bytes; true once the stream signalled `[DONE]`
+    fn push(&mut self, chunk: &[u8]) -> Result<bool> {
+        self.result.bytes += chunk.len() as u64;
+        if self.result.bytes > self.config.max_bytes {
+            return Err(BeemFlowError::Network(NetworkError::Http(format!(
+                "event stream exceeded {} bytes",
+                self.config.max_bytes
+            ))));
+        }
+
+        for &byte in chunk {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+            if self.line.last() == Some(&b'\r') {
+                self.line.pop();
+            }
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.line)).into_owned();
+            if self.line_complete(&line) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Dispatch a final event the server didn't terminate with a blank line
+    fn end(&mut self) {
+        if !self.line.is_empty() {
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.line)).into_owned();
+            if self.line_complete(&line) {
+                return;
+            }
+        }
+        self.dispatch();
+    }
+
+    /// Handle one line; true once the stream signalled `[DONE]`
+    fn line_complete(&mut self, line: &str) -> bool {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        // Comments (`: keep-alive`) and fields other than data are ignored
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        if field == "data" {
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match self.data {
+                Some(ref mut data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            }
+        }
+        false
+    }
+
+    /// Append the current event's delta; true if it was `[DONE]`
+    fn dispatch(&mut self) -> bool {
+        let Some(data) = self.data.take() else {
+            return false;
+        };
+        if data.trim() == DONE_MARKER {
+            return true;
+        }
+
+        self.result.events += 1;
+        match serde_json::from_str::<Value>(&data) {
+            Ok(event) => {
+                if let Some(delta) = lookup(&event, &self.path).and_then(Value::as_str) {
+                    self.result.text.push_str(delta);
+                }
+                self.result.last_event = Some(event);
+            }
+            Err(_) => self.result.text.push_str(&data),
+        }
+        false
+    }
+
+    fn finish(self) -> StreamedResponse {
+        self.result
+    }
+}
+
+/// Value at a dot-separated path, numeric segments indexing arrays
+fn lookup<'v>(value: &'v Value, path: &[&str]) -> Option<&'v Value> {
+    path.iter().try_fold(value, |value, segment| match value {
+        Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        Value::Object(map) => map.get(*segment),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+#[path = "stream_test.rs"]
+mod tests;
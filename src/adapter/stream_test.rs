@@ -0,0 +1,87 @@
+use super::*;
+
+fn assemble(config: &StreamConfig, chunks: &[&str]) -> Result<StreamedResponse> {
+    let mut assembler = Assembler::new(config);
+    for chunk in chunks {
+        if assembler.push(chunk.as_bytes())? {
+            return Ok(assembler.finish());
+        }
+    }
+    assembler.end();
+    Ok(assembler.finish())
+}
+
+#[test]
+fn test_assembles_deltas_split_across_chunks() {
+    let config = StreamConfig::default();
+    let result = assemble(
+        &config,
+        &[
+            ": keep-alive\n\ndata: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\r\n\r\ndata: {\"choi",
+            "ces\":[{\"delta\":{\"content\":\"lo\"}}]}\n\ndata: [DONE]\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"ignored\"}}]}\n\n",
+        ],
+    )
+    .unwrap();
+    assert_eq!(result.text, "Hello");
+    assert_eq!(result.events, 3);
+}
+
+#[test]
+fn test_custom_path_plain_data_and_unterminated_event() {
+    let config = StreamSetting::Custom(StreamOverrides {
+        delta_path: Some("delta.text".to_string()),
+        ..Default::default()
+    })
+    .config()
+    .unwrap();
+    let result = assemble(
+        &config,
+        &[
+            "event: content_block_delta\ndata: {\"delta\":{\"text\":\"a\"}}\n\n",
+            "data: plain\ndata: text\n\n",
+            "data: {\"delta\":{\"text\":\"z\"}}",
+        ],
+    )
+    .unwrap();
+    assert_eq!(result.text, "aplain\ntextz");
+    assert_eq!(
+        result.last_event,
+        Some(serde_json::json!({"delta": {"text": "z"}}))
+    );
+}
+
+#[test]
+fn test_stream_over_max_bytes_fails() {
+    let config = StreamConfig {
+        max_bytes: 16,
+        ..Default::default()
+    };
+    let err = assemble(&config, &["data: 0123456789\n\n", "data: 0123456789\n\n"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("exceeded 16 bytes"), "{}", err);
+}
+
+#[test]
+fn test_stream_setting_forms() {
+    let parse = |value: Value| {
+        serde_json::from_value::<StreamSetting>(value)
+            .unwrap()
+            .config()
+    };
+    assert_eq!(parse(serde_json::json!(false)), None);
+    assert_eq!(
+        parse(serde_json::json!(true)),
+        Some(StreamConfig::default())
+    );
+    assert_eq!(
+        parse(serde_json::json!({"timeout_secs": 5})),
+        Some(StreamConfig {
+            timeout_secs: 5,
+            ..Default::default()
+        })
+    );
+    assert!(serde_json::from_value::<StreamSetting>(serde_json::json!({"delta": "x"})).is_err());
+}
@@ -0,0 +1,76 @@
+//! Resource usage accounting for runs
+//!
+//! The executor hands every tool call of a run the same [`UsageMeter`] through
+//! [`ExecutionContext::usage`](super::ExecutionContext), including calls made
+//! from `parallel` and `foreach` tasks. Adapters count what they consume on it,
+//! and the engine saves a snapshot on the run as its [`RunUsage`].
+
+use crate::model::RunUsage;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Shared counters of one run's resource usage
+///
+/// Clones count into the same totals. The default meter is unshared, so
+/// adapters can count unconditionally.
+#[derive(Clone, Default)]
+pub struct UsageMeter {
+    counters: Arc<UsageCounters>,
+}
+
+#[derive(Default)]
+struct UsageCounters {
+    tool_invocations: Mutex<BTreeMap<String, u64>>,
+    http_requests: AtomicU64,
+    http_response_bytes: AtomicU64,
+    mcp_calls: AtomicU64,
+    llm_tokens: AtomicU64,
+}
+
+impl UsageMeter {
+    /// Count one invocation of `tool`
+    pub fn record_tool_invocation(&self, tool: &str) {
+        if let Ok(mut tools) = self.counters.tool_invocations.lock() {
+            *tools.entry(tool.to_string()).or_default() += 1;
+        }
+    }
+
+    /// Count an HTTP request whose response body was `response_bytes` long
+    pub fn record_http_request(&self, response_bytes: u64) {
+        self.counters.http_requests.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .http_response_bytes
+            .fetch_add(response_bytes, Ordering::Relaxed);
+    }
+
+    /// Count a tool call to an MCP server
+    pub fn record_mcp_call(&self) {
+        self.counters.mcp_calls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count LLM tokens reported by a model provider
+    pub fn record_llm_tokens(&self, tokens: u64) {
+        self.counters
+            .llm_tokens
+            .fetch_add(tokens, Ordering::Relaxed);
+    }
+
+    /// Usage counted so far, with `wall_time` as the time spent executing
+    pub fn snapshot(&self, wall_time: Duration) -> RunUsage {
+        RunUsage {
+            wall_time_ms: wall_time.as_millis() as u64,
+            tool_invocations: self
+                .counters
+                .tool_invocations
+                .lock()
+                .map(|tools| tools.clone())
+                .unwrap_or_default(),
+            http_requests: self.counters.http_requests.load(Ordering::Relaxed),
+            http_response_bytes: self.counters.http_response_bytes.load(Ordering::Relaxed),
+            mcp_calls: self.counters.mcp_calls.load(Ordering::Relaxed),
+            llm_tokens: self.counters.llm_tokens.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -8,6 +8,7 @@ use crate::http::template::TemplateRenderer;
 use crate::model::{OAuthCredential, OAuthProvider};
 use crate::registry::RegistryManager;
 use crate::storage::Storage;
+use crate::utils::retry::{RetryPolicy, with_backoff};
 use crate::{BeemFlowError, Result};
 use axum::{
     Json, Router,
@@ -27,6 +28,22 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Time a provider's token endpoint has to answer [`OAuthClientManager::test_provider`]
+const PROVIDER_TEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Outcome of checking an OAuth provider's configuration
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OAuthProviderTest {
+    pub provider: String,
+    /// Whether the token endpoint issued a token for the client
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_url: Option<String>,
+    /// Why the check failed, with the provider's own error when it sent one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 /// OAuth client manager for handling tokens from external providers
 ///
 /// This manages OAuth credentials and automatically refreshes expired tokens.
@@ -262,6 +279,7 @@ impl OAuthClientManager {
             }),
             created_at: now,
             updated_at: now,
+            tenant_id: crate::auth::current_tenant(),
         };
 
         // Save credential
@@ -304,6 +322,19 @@ impl OAuthClientManager {
     /// # }
     /// ```
     pub async fn get_token(&self, provider: &str, integration: &str) -> Result<String> {
+        self.get_token_for_tenant(provider, integration, None).await
+    }
+
+    /// Get a valid OAuth access token usable by `tenant_id`
+    ///
+    /// A tenant may use its own credentials and shared ones (without a tenant);
+    /// another tenant's credential reads as not found. `None` may use any.
+    pub async fn get_token_for_tenant(
+        &self,
+        provider: &str,
+        integration: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<String> {
         let cred = self
             .storage
             .get_oauth_credential(provider, integration)
@@ -314,6 +345,11 @@ impl OAuthClientManager {
                     provider, integration, e
                 ))
             })?
+            .filter(|cred| {
+                tenant_id.is_none()
+                    || cred.tenant_id.is_none()
+                    || cred.tenant_id.as_deref() == tenant_id
+            })
             .ok_or_else(|| {
                 BeemFlowError::OAuth(format!(
                     "OAuth credential not found for {}:{}",
@@ -387,12 +423,20 @@ impl OAuthClientManager {
                     .map_err(|e| BeemFlowError::auth(format!("Invalid redirect URI: {}", e)))?,
             );
 
-        // Refresh the token using cached HTTP client
-        let token_result = client
-            .exchange_refresh_token(&RefreshToken::new(refresh_token_str.clone()))
-            .request_async(&self.http_client)
-            .await
-            .map_err(|e| BeemFlowError::OAuth(format!("Token refresh failed: {}", e)))?;
+        // Refresh the token using cached HTTP client, retrying transport failures;
+        // errors returned by the provider (e.g. a revoked refresh token) are final
+        let refresh_token = RefreshToken::new(refresh_token_str.clone());
+        let token_result = with_backoff(
+            &RetryPolicy::default(),
+            |e| matches!(e, oauth2::RequestTokenError::Request(_)),
+            || {
+                client
+                    .exchange_refresh_token(&refresh_token)
+                    .request_async(&self.http_client)
+            },
+        )
+        .await
+        .map_err(|e| BeemFlowError::OAuth(format!("Token refresh failed: {}", e)))?;
 
         // Extract new token info
         let new_access_token = token_result.access_token().secret().clone();
@@ -419,6 +463,73 @@ impl OAuthClientManager {
         Ok(())
     }
 
+    /// Check a provider's client ID, secret and URLs with a client-credentials
+    /// token request
+    ///
+    /// Nothing is stored; a token the provider issues is dropped. A provider
+    /// that is misconfigured or whose token endpoint rejects the request is
+    /// reported in the result, with the provider's error message; only an
+    /// unknown provider is an error.
+    pub async fn test_provider(&self, provider_id: &str) -> Result<OAuthProviderTest> {
+        let known = self
+            .registry_manager
+            .get_oauth_provider(provider_id)
+            .await?
+            .is_some()
+            || self
+                .storage
+                .get_oauth_provider(provider_id)
+                .await?
+                .is_some();
+        if !known {
+            return Err(BeemFlowError::not_found("OAuth provider", provider_id));
+        }
+
+        let mut result = OAuthProviderTest {
+            provider: provider_id.to_string(),
+            ok: false,
+            token_url: None,
+            error: None,
+        };
+        let config = match self.get_provider(provider_id).await {
+            Ok(config) => config,
+            Err(e) => {
+                result.error = Some(e.to_string());
+                return Ok(result);
+            }
+        };
+        result.token_url = Some(config.token_url.clone());
+        if let Err(e) = AuthUrl::new(config.auth_url) {
+            result.error = Some(format!("Invalid auth URL: {}", e));
+            return Ok(result);
+        }
+        let token_url = match TokenUrl::new(config.token_url) {
+            Ok(token_url) => token_url,
+            Err(e) => {
+                result.error = Some(format!("Invalid token URL: {}", e));
+                return Ok(result);
+            }
+        };
+
+        let client = BasicClient::new(ClientId::new(config.client_id))
+            .set_client_secret(ClientSecret::new(config.client_secret))
+            .set_token_uri(token_url);
+        let request = client
+            .exchange_client_credentials()
+            .request_async(&self.http_client);
+        match tokio::time::timeout(PROVIDER_TEST_TIMEOUT, request).await {
+            Ok(Ok(_)) => result.ok = true,
+            Ok(Err(e)) => result.error = Some(describe_token_error(e)),
+            Err(_) => {
+                result.error = Some(format!(
+                    "Token endpoint did not answer within {}s",
+                    PROVIDER_TEST_TIMEOUT.as_secs()
+                ))
+            }
+        }
+        Ok(result)
+    }
+
     /// Check if a credential needs token refresh
     fn needs_refresh(cred: &OAuthCredential) -> bool {
         if let Some(expires_at) = cred.expires_at {
@@ -431,6 +542,28 @@ impl OAuthClientManager {
     }
 }
 
+/// A failed token request as the provider described it, when it did
+fn describe_token_error<RE: std::error::Error + 'static>(
+    error: oauth2::basic::BasicRequestTokenError<RE>,
+) -> String {
+    match error {
+        oauth2::RequestTokenError::ServerResponse(response) => match response.error_description() {
+            Some(description) => format!("{}: {}", response.error(), description),
+            None => response.error().to_string(),
+        },
+        oauth2::RequestTokenError::Request(e) => format!("Token request failed: {}", e),
+        oauth2::RequestTokenError::Parse(e, body) => format!(
+            "Unexpected response from the token endpoint ({}): {}",
+            e,
+            String::from_utf8_lossy(&body)
+                .chars()
+                .take(200)
+                .collect::<String>()
+        ),
+        oauth2::RequestTokenError::Other(message) => message,
+    }
+}
+
 // ============================================================================
 // TEST UTILITIES
 // ============================================================================
@@ -500,6 +633,10 @@ pub fn create_oauth_client_routes(state: Arc<OAuthClientState>) -> Router {
             "/oauth/credentials/{id}/delete",
             delete(delete_oauth_credential_handler),
         )
+        .route(
+            "/oauth/credentials/prune",
+            post(prune_oauth_credentials_handler),
+        )
         .route(
             "/oauth/authorize/{provider}",
             get(authorize_oauth_provider_handler),
@@ -1125,15 +1262,20 @@ async fn delete_oauth_provider_handler(
 // OAUTH CREDENTIAL API HANDLERS
 // ============================================================================
 
-/// List all OAuth credentials
+/// List all OAuth credentials, or only the current tenant's
 async fn list_oauth_credentials_handler(
     State(state): State<Arc<OAuthClientState>>,
 ) -> std::result::Result<Json<Value>, StatusCode> {
-    let credentials = state
-        .storage
-        .list_oauth_credentials()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let credentials = match crate::auth::current_tenant() {
+        Some(tenant_id) => {
+            state
+                .storage
+                .list_oauth_credentials_for_tenant(&tenant_id)
+                .await
+        }
+        None => state.storage.list_oauth_credentials().await,
+    }
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     // Redact access tokens for security
     let safe_credentials: Vec<Value> = credentials
@@ -1160,6 +1302,17 @@ async fn delete_oauth_credential_handler(
     State(state): State<Arc<OAuthClientState>>,
     AxumPath(id): AxumPath<String>,
 ) -> std::result::Result<Json<Value>, StatusCode> {
+    // A tenant may only delete its own credentials
+    if let Some(tenant_id) = crate::auth::current_tenant() {
+        let owned = state
+            .storage
+            .list_oauth_credentials_for_tenant(&tenant_id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if !owned.iter().any(|credential| credential.id == id) {
+            return Err(StatusCode::NOT_FOUND);
+        }
+    }
     state
         .storage
         .delete_oauth_credential(&id)
@@ -1168,6 +1321,18 @@ async fn delete_oauth_credential_handler(
     Ok(Json(json!({ "success": true })))
 }
 
+/// Delete expired OAuth credentials that cannot be refreshed
+async fn prune_oauth_credentials_handler(
+    State(state): State<Arc<OAuthClientState>>,
+) -> std::result::Result<Json<Value>, StatusCode> {
+    let removed = state
+        .storage
+        .delete_expired_oauth_credentials(Utc::now())
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(json!({ "removed": removed })))
+}
+
 /// Initiate OAuth authorization flow for a provider
 async fn authorize_oauth_provider_handler(
     State(state): State<Arc<OAuthClientState>>,
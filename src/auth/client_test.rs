@@ -14,6 +14,7 @@ fn create_test_credential() -> OAuthCredential {
         scope: Some("https://www.googleapis.com/auth/spreadsheets".to_string()),
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        tenant_id: None,
     }
 }
 
@@ -82,3 +83,91 @@ async fn test_needs_refresh_no_expiry() {
 
     assert!(!OAuthClientManager::needs_refresh(&cred));
 }
+
+/// Client manager with a stored provider whose token endpoint is `server`
+async fn client_with_provider(
+    server: &wiremock::MockServer,
+) -> (OAuthClientManager, Arc<SqliteStorage>) {
+    let storage = Arc::new(SqliteStorage::new(":memory:").await.expect("Failed to create SQLite storage"));
+    storage
+        .save_oauth_provider(&OAuthProvider {
+            id: "acme".to_string(),
+            name: "Acme".to_string(),
+            client_id: "acme-client".to_string(),
+            client_secret: "acme-secret".to_string(),
+            auth_url: format!("{}/authorize", server.uri()),
+            token_url: format!("{}/token", server.uri()),
+            scopes: None,
+            auth_params: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+
+    let secrets_provider: Arc<dyn crate::secrets::SecretsProvider> =
+        Arc::new(crate::secrets::EnvSecretsProvider::new());
+    let registry_manager = Arc::new(RegistryManager::standard(None, secrets_provider));
+    let client = OAuthClientManager::new(
+        storage.clone(),
+        registry_manager,
+        "http://localhost:3000/callback".to_string(),
+    )
+    .expect("Failed to create OAuth client manager");
+    (client, storage)
+}
+
+#[tokio::test]
+async fn test_provider_issuing_a_token_passes() {
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(body_string_contains("grant_type=client_credentials"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "acme-token",
+            "token_type": "bearer",
+            "expires_in": 3600
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let (client, storage) = client_with_provider(&server).await;
+
+    let result = client.test_provider("acme").await.unwrap();
+    assert!(result.ok, "{:?}", result.error);
+    assert_eq!(result.error, None);
+    assert_eq!(result.token_url, Some(format!("{}/token", server.uri())));
+    // The token is only proof the configuration works
+    assert!(storage.list_oauth_credentials().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_provider_rejecting_the_client_reports_its_error() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "error": "invalid_client",
+            "error_description": "Client authentication failed"
+        })))
+        .mount(&server)
+        .await;
+    let (client, storage) = client_with_provider(&server).await;
+
+    let result = client.test_provider("acme").await.unwrap();
+    assert!(!result.ok);
+    assert_eq!(
+        result.error.as_deref(),
+        Some("invalid_client: Client authentication failed")
+    );
+    assert!(storage.list_oauth_credentials().await.unwrap().is_empty());
+
+    let err = client.test_provider("unknown").await.unwrap_err();
+    assert!(err.to_string().contains("OAuth provider"), "{}", err);
+}
@@ -8,13 +8,15 @@
 pub mod client;
 pub mod middleware;
 pub mod server;
+pub mod tenant;
 
-pub use client::{OAuthClientManager, create_test_oauth_client};
+pub use client::{OAuthClientManager, OAuthProviderTest, create_test_oauth_client};
 pub use middleware::{
     AuthenticatedUser, OAuthMiddlewareState, RequiredScopes, has_all_scopes, has_any_scope,
     has_scope, oauth_middleware, rate_limit_middleware, validate_token,
 };
 pub use server::{OAuthConfig, OAuthServerState, create_oauth_routes};
+pub use tenant::{current_tenant, inherit_tenant, with_tenant};
 
 use crate::{Result, model::*};
 use parking_lot::RwLock;
@@ -262,6 +262,8 @@ async fn handle_client_registration(
         logo_uri: req.logo_uri.clone(),
         created_at: now,
         updated_at: now,
+        // Self-registered clients can't pick a tenant; use `oauth create-client --tenant`
+        tenant_id: None,
     };
 
     // Save client to storage
@@ -0,0 +1,101 @@
+//! Tenant of the request being handled
+//!
+//! Multi-tenant deployments scope runs, deployed flows and OAuth credentials to
+//! a tenant. The tenant comes from the authenticated principal: an OAuth client
+//! registered with a `tenant_id` scopes every request made with its tokens. The
+//! HTTP operation routes and MCP set it for the handler with [`with_tenant`];
+//! operations read it with [`current_tenant`]. Without a tenant (the CLI, and
+//! HTTP and MCP requests of a single-tenant deployment) everything is visible.
+//!
+//! Once any OAuth client is registered to a tenant, the deployment is
+//! multi-tenant: [`resolve_tenant`] refuses HTTP and MCP requests that are
+//! unauthenticated or whose client has no tenant, so none of them run unscoped.
+
+use super::middleware::AuthenticatedUser;
+use crate::BeemFlowError;
+use crate::storage::Storage;
+use std::future::Future;
+use std::sync::Arc;
+
+tokio::task_local! {
+    static TENANT_ID: String;
+}
+
+/// Run `future` with `tenant_id` as the current tenant
+pub async fn with_tenant<F: Future>(tenant_id: String, future: F) -> F::Output {
+    TENANT_ID.scope(tenant_id, future).await
+}
+
+/// Run `future` under the caller's tenant, if any
+///
+/// Task-locals don't cross `tokio::spawn`; wrap spawned futures with this. The
+/// tenant is captured when this is called, not when the returned future runs.
+pub fn inherit_tenant<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let tenant_id = current_tenant();
+    async move {
+        match tenant_id {
+            Some(tenant_id) => with_tenant(tenant_id, future).await,
+            None => future.await,
+        }
+    }
+}
+
+/// Tenant the current request is scoped to, if any
+pub fn current_tenant() -> Option<String> {
+    TENANT_ID.try_with(Clone::clone).ok()
+}
+
+/// Whether a record owned by `owner` is visible to the current tenant
+///
+/// Unscoped callers see every record; a tenant sees only its own.
+pub fn visible_to_current_tenant(owner: Option<&str>) -> bool {
+    current_tenant().is_none_or(|tenant_id| owner == Some(tenant_id.as_str()))
+}
+
+/// Request extension carrying the tenant of a request's principal
+///
+/// Set by middleware that authenticated the request, for handlers that don't
+/// run inside it (MCP tool calls).
+#[derive(Debug, Clone, Default)]
+pub struct TenantScope(pub Option<String>);
+
+/// Tenant of an authenticated principal: the one its OAuth client is registered to
+pub async fn principal_tenant(
+    storage: &Arc<dyn Storage>,
+    user: &AuthenticatedUser,
+) -> crate::Result<Option<String>> {
+    Ok(storage
+        .get_oauth_client(&user.client_id)
+        .await?
+        .and_then(|client| client.tenant_id))
+}
+
+/// Whether the deployment is multi-tenant: some OAuth client is registered to a tenant
+pub async fn tenants_configured(storage: &Arc<dyn Storage>) -> crate::Result<bool> {
+    Ok(storage
+        .list_oauth_clients()
+        .await?
+        .iter()
+        .any(|client| client.tenant_id.is_some()))
+}
+
+/// Tenant to scope an HTTP or MCP request to, from its principal if authenticated
+///
+/// Requests without a tenant run unscoped only while no tenants are configured;
+/// after that they are refused with an auth error.
+pub async fn resolve_tenant(
+    storage: &Arc<dyn Storage>,
+    user: Option<&AuthenticatedUser>,
+) -> crate::Result<Option<String>> {
+    let tenant_id = match user {
+        Some(user) => principal_tenant(storage, user).await?,
+        None => None,
+    };
+    if tenant_id.is_none() && tenants_configured(storage).await? {
+        return Err(BeemFlowError::auth(match user {
+            Some(_) => "This token's client has no tenant; tenants are configured",
+            None => "Authentication required; tenants are configured",
+        }));
+    }
+    Ok(tenant_id)
+}
@@ -1,7 +1,27 @@
 //! Tests for blob
 
-use super::{BlobConfig, BlobStore, FilesystemBlobStore, new_default_blob_store};
+use super::{
+    BLOB_URL_PREFIX, BlobConfig, BlobStore, BlobStores, FilesystemBlobStore, StableBlobStore,
+    new_default_blob_store, parse_blob_url,
+};
+use crate::model::{FlowName, Run, RunStatus, StepRun, StepStatus};
+use crate::storage::{SqliteStorage, Storage};
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
 use tempfile::TempDir;
+use uuid::Uuid;
+
+async fn memory_storage() -> Arc<dyn Storage> {
+    Arc::new(SqliteStorage::new(":memory:").await.unwrap())
+}
+
+async fn stable_store(dir: &TempDir, storage: Arc<dyn Storage>) -> StableBlobStore {
+    let backend = FilesystemBlobStore::new(dir.path().to_string_lossy().to_string())
+        .await
+        .unwrap();
+    StableBlobStore::new("filesystem", Box::new(backend), storage)
+}
 
 #[tokio::test]
 async fn test_filesystem_put_and_get() {
@@ -125,7 +145,9 @@ async fn test_new_default_blob_store_filesystem() {
         region: None,
     };
 
-    let store = new_default_blob_store(Some(&config)).await.unwrap();
+    let store = new_default_blob_store(Some(&config), memory_storage().await)
+        .await
+        .unwrap();
 
     // Test that it works
     let data = b"Test data".to_vec();
@@ -148,7 +170,9 @@ async fn test_new_default_blob_store_none() {
         region: None,
     };
 
-    let store = new_default_blob_store(Some(&config)).await.unwrap();
+    let store = new_default_blob_store(Some(&config), memory_storage().await)
+        .await
+        .unwrap();
 
     // Test that it works
     let data = b"Test data".to_vec();
@@ -156,7 +180,7 @@ async fn test_new_default_blob_store_none() {
         .put(data.clone(), None, Some("test.txt"))
         .await
         .unwrap();
-    assert!(url.starts_with("file://"));
+    assert!(url.starts_with(BLOB_URL_PREFIX));
 }
 
 #[tokio::test]
@@ -199,3 +223,187 @@ async fn test_filesystem_atomic_write() {
     let retrieved = store.get(&url).await.unwrap();
     assert_eq!(retrieved, data);
 }
+
+#[tokio::test]
+async fn test_stable_urls_resolve_through_storage() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = memory_storage().await;
+    let store = stable_store(&temp_dir, storage.clone()).await;
+
+    // Blobs stored under the same filename get distinct URLs and objects
+    let first = store
+        .put(b"first".to_vec(), None, Some("report.csv"))
+        .await
+        .unwrap();
+    let second = store
+        .put(b"second".to_vec(), None, Some("report.csv"))
+        .await
+        .unwrap();
+    assert_ne!(first, second);
+    assert_eq!(store.get(&first).await.unwrap(), b"first");
+    assert_eq!(store.get(&second).await.unwrap(), b"second");
+
+    // The URL doesn't expose the backend location; storage records it
+    let id = parse_blob_url(&first).unwrap();
+    let record = storage.get_blob_record(id).await.unwrap().unwrap();
+    assert_eq!(record.backend, "filesystem");
+    assert!(record.location.starts_with("file://"));
+    assert!(!first.contains("report.csv"));
+
+    // Old-style backend URLs keep resolving
+    assert_eq!(store.get(&record.location).await.unwrap(), b"first");
+
+    // Deleting removes both the object and its record
+    store.delete(&first).await.unwrap();
+    assert!(store.get(&first).await.is_err());
+    assert!(storage.get_blob_record(id).await.unwrap().is_none());
+    assert!(store.get(&record.location).await.is_err());
+
+    // Unknown blob IDs are not found
+    let unknown = format!("{}{}", BLOB_URL_PREFIX, Uuid::new_v4());
+    assert!(store.get(&unknown).await.is_err());
+}
+
+#[tokio::test]
+async fn test_stable_url_from_other_backend_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = memory_storage().await;
+    let store = stable_store(&temp_dir, storage.clone()).await;
+    let url = store.put(b"data".to_vec(), None, None).await.unwrap();
+
+    let backend = FilesystemBlobStore::new(temp_dir.path().to_string_lossy().to_string())
+        .await
+        .unwrap();
+    let s3_configured = StableBlobStore::new("s3", Box::new(backend), storage);
+    assert!(s3_configured.get(&url).await.is_err());
+}
+
+#[tokio::test]
+async fn test_collect_garbage_keeps_referenced_and_recent_blobs() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = memory_storage().await;
+    let store = stable_store(&temp_dir, storage.clone()).await;
+
+    let in_output = store.put(b"a".to_vec(), None, None).await.unwrap();
+    let in_event = store.put(b"b".to_vec(), None, None).await.unwrap();
+    let in_paused = store.put(b"c".to_vec(), None, None).await.unwrap();
+    let orphan = store.put(b"d".to_vec(), None, None).await.unwrap();
+
+    let run = Run {
+        id: Uuid::new_v4(),
+        flow_name: FlowName::new("report").unwrap(),
+        event: HashMap::from([("file".to_string(), serde_json::json!(in_event))]),
+        vars: HashMap::new(),
+        status: RunStatus::Succeeded,
+        started_at: Utc::now(),
+        ended_at: Some(Utc::now()),
+        steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
+    };
+    storage.save_run(&run).await.unwrap();
+    storage
+        .save_step(&StepRun {
+            id: Uuid::new_v4(),
+            run_id: run.id,
+            step_name: "render".to_string().into(),
+            status: StepStatus::Succeeded,
+            started_at: Utc::now(),
+            ended_at: Some(Utc::now()),
+            inputs: None,
+            outputs: Some(HashMap::from([(
+                "links".to_string(),
+                serde_json::json!([format!("see {}", in_output)]),
+            )])),
+            error: None,
+            error_kind: None,
+            progress: None,
+        })
+        .await
+        .unwrap();
+    storage
+        .save_paused_run(
+            "token",
+            "webhook.test",
+            serde_json::json!({"outputs": {"file": in_paused}}),
+        )
+        .await
+        .unwrap();
+
+    // Blobs created after the cutoff are kept even when unreferenced
+    let collected = store
+        .collect_garbage(Utc::now() - Duration::hours(1), false)
+        .await
+        .unwrap();
+    assert!(collected.is_empty());
+
+    // Dry run reports the orphan without deleting it
+    let cutoff = Utc::now() + Duration::seconds(1);
+    let collected = store.collect_garbage(cutoff, true).await.unwrap();
+    assert_eq!(collected.len(), 1);
+    assert_eq!(Some(collected[0].id), parse_blob_url(&orphan));
+    assert!(store.get(&orphan).await.is_ok());
+
+    let collected = store.collect_garbage(cutoff, false).await.unwrap();
+    assert_eq!(collected.len(), 1);
+    assert!(store.get(&orphan).await.is_err());
+    for url in [&in_output, &in_event, &in_paused] {
+        assert!(store.get(url).await.is_ok());
+    }
+}
+
+#[tokio::test]
+async fn test_blob_stores_route_stable_urls_to_their_store() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = memory_storage().await;
+    let filesystem = |name: &str| BlobConfig {
+        driver: Some("filesystem".to_string()),
+        directory: Some(temp_dir.path().join(name).to_string_lossy().to_string()),
+        bucket: None,
+        region: None,
+    };
+    let stores = Arc::new(BlobStores::new(
+        filesystem("default"),
+        HashMap::from([("archive".to_string(), filesystem("archive"))]),
+        storage.clone(),
+    ));
+
+    let archive = stores.get(Some("archive")).await.unwrap();
+    let default = stores.get(None).await.unwrap();
+    let archived = archive.put(b"old".to_vec(), None, None).await.unwrap();
+    let current = default.put(b"new".to_vec(), None, None).await.unwrap();
+
+    let record = storage
+        .get_blob_record(parse_blob_url(&archived).unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.store.as_deref(), Some("archive"));
+
+    // Either store reads a stable URL from the store that wrote it
+    assert_eq!(default.get(&archived).await.unwrap(), b"old");
+    assert_eq!(archive.get(&current).await.unwrap(), b"new");
+
+    // Garbage collection covers every store, deleting through each blob's own store
+    let collected = stores
+        .collect_garbage(Utc::now() + Duration::seconds(1), false)
+        .await
+        .unwrap();
+    assert_eq!(collected.len(), 2);
+    assert!(
+        std::fs::read_dir(temp_dir.path().join("archive"))
+            .unwrap()
+            .next()
+            .is_none()
+    );
+    assert!(
+        std::fs::read_dir(temp_dir.path().join("default"))
+            .unwrap()
+            .next()
+            .is_none()
+    );
+    assert!(storage.list_blob_records().await.unwrap().is_empty());
+}
@@ -1,14 +1,52 @@
 //! Blob storage for large objects
 //!
-//! Provides filesystem and S3 blob storage backends.
+//! Provides filesystem and S3 blob storage backends. [`StableBlobStore`] wraps a
+//! backend and hands out `beemflow://blobs/<id>` URLs whose backend location is
+//! recorded in storage, so references survive backend or host changes.
+//! [`BlobStores`] selects between the default store and named `blob_stores`,
+//! and reads stable URLs from the store that recorded them.
 
 pub mod s3;
 
-use crate::{Result, constants};
+use crate::model::BlobRecord;
+use crate::storage::Storage;
+use crate::{BeemFlowError, Result, constants};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
 
 pub use s3::S3BlobStore;
 
+/// Prefix of stable blob URLs (`beemflow://blobs/<id>`)
+pub const BLOB_URL_PREFIX: &str = "beemflow://blobs/";
+
+/// Stable URL for a blob ID
+pub fn blob_url(id: Uuid) -> String {
+    format!("{}{}", BLOB_URL_PREFIX, id)
+}
+
+/// Blob ID of a stable `beemflow://blobs/<id>` URL
+pub fn parse_blob_url(url: &str) -> Option<Uuid> {
+    url.strip_prefix(BLOB_URL_PREFIX)
+        .and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// Add the IDs of all stable blob URLs appearing in `text` to `ids`
+fn collect_blob_ids(text: &str, ids: &mut HashSet<Uuid>) {
+    const ID_LEN: usize = 36;
+    for (start, _) in text.match_indices(BLOB_URL_PREFIX) {
+        let id_start = start + BLOB_URL_PREFIX.len();
+        if let Some(id) = text
+            .get(id_start..id_start + ID_LEN)
+            .and_then(|id| Uuid::parse_str(id).ok())
+        {
+            ids.insert(id);
+        }
+    }
+}
+
 /// Blob storage trait
 #[async_trait]
 pub trait BlobStore: Send + Sync {
@@ -23,6 +61,9 @@ pub trait BlobStore: Send + Sync {
 
     /// Retrieve a blob from a URL
     async fn get(&self, url: &str) -> Result<Vec<u8>>;
+
+    /// Delete a blob by URL; deleting a missing blob is not an error
+    async fn delete(&self, url: &str) -> Result<()>;
 }
 
 /// Filesystem blob storage
@@ -92,17 +133,215 @@ impl BlobStore for FilesystemBlobStore {
 
     /// Retrieve a blob from a file:// URL
     async fn get(&self, url: &str) -> Result<Vec<u8>> {
-        const PREFIX: &str = "file://";
-        if !url.starts_with(PREFIX) {
-            return Err(crate::BeemFlowError::validation(format!(
-                "invalid file URL: {}",
-                url
+        let data = tokio::fs::read(file_url_path(url)?).await?;
+        Ok(data)
+    }
+
+    /// Delete the file behind a file:// URL
+    async fn delete(&self, url: &str) -> Result<()> {
+        match tokio::fs::remove_file(file_url_path(url)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Path of a file:// URL
+fn file_url_path(url: &str) -> Result<&str> {
+    url.strip_prefix("file://")
+        .ok_or_else(|| BeemFlowError::validation(format!("invalid file URL: {}", url)))
+}
+
+/// Blob store handing out stable `beemflow://blobs/<id>` URLs
+///
+/// Objects are written to the wrapped backend and their backend location is
+/// recorded in storage; `get` and `delete` resolve stable URLs through that
+/// record. Backend-native URLs from before stable URLs (`file://`, `s3://`)
+/// are passed straight to the backend, so old references keep resolving.
+pub struct StableBlobStore {
+    driver: String,
+    store: Option<String>,
+    backend: Box<dyn BlobStore>,
+    storage: Arc<dyn Storage>,
+}
+
+/// Name of a blob store in messages
+fn store_label(store: Option<&str>) -> String {
+    store.map_or_else(|| "default".to_string(), |name| format!("'{}'", name))
+}
+
+impl StableBlobStore {
+    /// Wrap `backend`, whose driver name (`filesystem`, `s3`) is recorded with each blob
+    pub fn new(
+        driver: impl Into<String>,
+        backend: Box<dyn BlobStore>,
+        storage: Arc<dyn Storage>,
+    ) -> Self {
+        Self {
+            driver: driver.into(),
+            store: None,
+            backend,
+            storage,
+        }
+    }
+
+    /// Record blobs as held by the named store `store` (`None`: the default store)
+    pub fn named(mut self, store: Option<String>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Backend location of a stable blob URL
+    async fn resolve(&self, id: Uuid) -> Result<BlobRecord> {
+        let record = self
+            .storage
+            .get_blob_record(id)
+            .await?
+            .ok_or_else(|| BeemFlowError::not_found("Blob", id.to_string()))?;
+        if record.store != self.store {
+            return Err(BeemFlowError::validation(format!(
+                "blob {} is held by the {} blob store, not the {} one",
+                id,
+                store_label(record.store.as_deref()),
+                store_label(self.store.as_deref())
+            )));
+        }
+        if record.backend != self.driver {
+            return Err(BeemFlowError::validation(format!(
+                "blob {} is stored in the {} backend, but the configured blob driver is {}",
+                id, record.backend, self.driver
             )));
         }
+        Ok(record)
+    }
 
-        let path = &url[PREFIX.len()..];
-        let data = tokio::fs::read(path).await?;
-        Ok(data)
+    /// Delete blobs that no retained run references
+    ///
+    /// Scans every run's event and vars, its steps' inputs and outputs, and all
+    /// paused runs for stable blob URLs. Blobs created after `created_before`
+    /// are kept, since the step that stored them may not have been saved yet.
+    /// Blobs recorded in a different store or backend are skipped. Returns the
+    /// collected records; with `dry_run` nothing is deleted.
+    pub async fn collect_garbage(
+        &self,
+        created_before: DateTime<Utc>,
+        dry_run: bool,
+    ) -> Result<Vec<BlobRecord>> {
+        let referenced = referenced_blob_ids(self.storage.as_ref()).await?;
+        self.collect_unreferenced(&referenced, created_before, dry_run)
+            .await
+    }
+
+    /// Delete this store's blobs outside `referenced` created before `created_before`
+    async fn collect_unreferenced(
+        &self,
+        referenced: &HashSet<Uuid>,
+        created_before: DateTime<Utc>,
+        dry_run: bool,
+    ) -> Result<Vec<BlobRecord>> {
+        let mut collected = Vec::new();
+        for record in self.storage.list_blob_records().await? {
+            if referenced.contains(&record.id)
+                || record.created_at >= created_before
+                || record.store != self.store
+            {
+                continue;
+            }
+            if record.backend != self.driver {
+                tracing::warn!(
+                    "Skipping unreferenced blob {} in the {} backend (configured: {})",
+                    record.id,
+                    record.backend,
+                    self.driver
+                );
+                continue;
+            }
+            if !dry_run {
+                self.backend.delete(&record.location).await?;
+                self.storage.delete_blob_record(record.id).await?;
+            }
+            collected.push(record);
+        }
+        Ok(collected)
+    }
+}
+
+/// IDs of the stable blob URLs referenced by retained runs and paused runs
+async fn referenced_blob_ids(storage: &dyn Storage) -> Result<HashSet<Uuid>> {
+    const PAGE_SIZE: usize = 500;
+
+    let mut referenced = HashSet::new();
+    let mut offset = 0;
+    loop {
+        let runs = storage.list_runs(PAGE_SIZE, offset).await?;
+        for run in &runs {
+            collect_blob_ids(&serde_json::to_string(&run.event)?, &mut referenced);
+            collect_blob_ids(&serde_json::to_string(&run.vars)?, &mut referenced);
+            for step in storage.get_steps(run.id).await? {
+                collect_blob_ids(&serde_json::to_string(&step.inputs)?, &mut referenced);
+                collect_blob_ids(&serde_json::to_string(&step.outputs)?, &mut referenced);
+            }
+        }
+        if runs.len() < PAGE_SIZE {
+            break;
+        }
+        offset += runs.len();
+    }
+    for data in storage.load_paused_runs().await?.values() {
+        collect_blob_ids(&data.to_string(), &mut referenced);
+    }
+    Ok(referenced)
+}
+
+#[async_trait]
+impl BlobStore for StableBlobStore {
+    /// Store a blob in the backend and return its stable URL
+    ///
+    /// The backend object name is prefixed with the blob ID so blobs stored
+    /// under the same filename never overwrite each other.
+    async fn put(
+        &self,
+        data: Vec<u8>,
+        mime: Option<&str>,
+        filename: Option<&str>,
+    ) -> Result<String> {
+        let id = Uuid::new_v4();
+        let name = match filename {
+            Some(name) if !name.is_empty() => format!("{}-{}", id, name),
+            _ => id.to_string(),
+        };
+        let location = self.backend.put(data, mime, Some(&name)).await?;
+
+        self.storage
+            .save_blob_record(&BlobRecord {
+                id,
+                backend: self.driver.clone(),
+                store: self.store.clone(),
+                location,
+                created_at: Utc::now(),
+            })
+            .await?;
+        Ok(blob_url(id))
+    }
+
+    /// Retrieve a blob by stable URL, or by backend-native URL
+    async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        match parse_blob_url(url) {
+            Some(id) => self.backend.get(&self.resolve(id).await?.location).await,
+            None => self.backend.get(url).await,
+        }
+    }
+
+    /// Delete a blob and, for stable URLs, its location record
+    async fn delete(&self, url: &str) -> Result<()> {
+        match parse_blob_url(url) {
+            Some(id) => {
+                let record = self.resolve(id).await?;
+                self.backend.delete(&record.location).await?;
+                self.storage.delete_blob_record(id).await
+            }
+            None => self.backend.delete(url).await,
+        }
     }
 }
 
@@ -134,13 +373,194 @@ impl Default for BlobConfig {
     }
 }
 
+impl From<&crate::config::BlobConfig> for BlobConfig {
+    fn from(config: &crate::config::BlobConfig) -> Self {
+        Self {
+            driver: config.driver.clone(),
+            directory: config.directory.clone(),
+            bucket: config.bucket.clone(),
+            region: config.region.clone(),
+        }
+    }
+}
+
+/// The default blob store plus named stores from `blob_stores`
+///
+/// Flows and steps pick a store by name with `blob_store:`; unnamed writes go
+/// to the default store. Each blob's record names the store that wrote it, and
+/// stable URLs are read and deleted through that store whichever store the
+/// reader writes to. Stores are opened on first use and then reused, so
+/// configured stores that no flow uses are never created.
+pub struct BlobStores {
+    default: BlobConfig,
+    named: HashMap<String, BlobConfig>,
+    storage: Arc<dyn Storage>,
+    opened: tokio::sync::Mutex<HashMap<Option<String>, Arc<StableBlobStore>>>,
+}
+
+impl BlobStores {
+    /// Stores backed by `default` and the `named` configurations
+    pub fn new(
+        default: BlobConfig,
+        named: HashMap<String, BlobConfig>,
+        storage: Arc<dyn Storage>,
+    ) -> Self {
+        Self {
+            default,
+            named,
+            storage,
+            opened: tokio::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Stores configured by `blob` and `blob_stores` in the application config
+    pub fn from_config(config: &crate::config::Config, storage: Arc<dyn Storage>) -> Self {
+        let default = config
+            .blob
+            .as_ref()
+            .map(BlobConfig::from)
+            .unwrap_or_default();
+        let named = config
+            .blob_stores
+            .iter()
+            .flatten()
+            .map(|(name, blob)| (name.clone(), BlobConfig::from(blob)))
+            .collect();
+        Self::new(default, named, storage)
+    }
+
+    /// Store writing to `name`, or to the default store when unnamed
+    ///
+    /// Stable URLs are read and deleted through the store that recorded them.
+    pub async fn get(self: &Arc<Self>, name: Option<&str>) -> Result<Arc<dyn BlobStore>> {
+        self.open(name).await?;
+        Ok(Arc::new(RoutedBlobStore {
+            stores: self.clone(),
+            name: name.map(str::to_string),
+        }))
+    }
+
+    /// Delete unreferenced blobs from every configured store
+    ///
+    /// See [`StableBlobStore::collect_garbage`]. Blobs of stores no longer
+    /// configured are left alone.
+    pub async fn collect_garbage(
+        &self,
+        created_before: DateTime<Utc>,
+        dry_run: bool,
+    ) -> Result<Vec<BlobRecord>> {
+        let referenced = referenced_blob_ids(self.storage.as_ref()).await?;
+        let mut names: Vec<&str> = self.named.keys().map(String::as_str).collect();
+        names.sort_unstable();
+
+        let mut collected = Vec::new();
+        for name in std::iter::once(None).chain(names.into_iter().map(Some)) {
+            let store = self.open(name).await?;
+            collected.extend(
+                store
+                    .collect_unreferenced(&referenced, created_before, dry_run)
+                    .await?,
+            );
+        }
+        Ok(collected)
+    }
+
+    /// Open store `name`, or the default store when unnamed
+    async fn open(&self, name: Option<&str>) -> Result<Arc<StableBlobStore>> {
+        let config = match name {
+            None => &self.default,
+            Some(name) => self.named.get(name).ok_or_else(|| {
+                let mut known: Vec<&str> = self.named.keys().map(String::as_str).collect();
+                known.sort_unstable();
+                BeemFlowError::validation(format!(
+                    "unknown blob store '{}' (configured: {})",
+                    name,
+                    if known.is_empty() {
+                        "none".to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                ))
+            })?,
+        };
+
+        let mut opened = self.opened.lock().await;
+        let key = name.map(str::to_string);
+        if let Some(store) = opened.get(&key) {
+            return Ok(store.clone());
+        }
+        let store = Arc::new(
+            new_default_blob_store(Some(config), self.storage.clone())
+                .await?
+                .named(key.clone()),
+        );
+        opened.insert(key, store.clone());
+        Ok(store)
+    }
+}
+
+/// Blob store writing to one of [`BlobStores`]
+struct RoutedBlobStore {
+    stores: Arc<BlobStores>,
+    name: Option<String>,
+}
+
+impl RoutedBlobStore {
+    /// Store holding `url`: the one recorded for a stable URL, else the writing store
+    async fn holder(&self, url: &str) -> Result<Arc<StableBlobStore>> {
+        let recorded = match parse_blob_url(url) {
+            Some(id) => self.stores.storage.get_blob_record(id).await?,
+            None => None,
+        };
+        let name = recorded.map_or_else(|| self.name.clone(), |record| record.store);
+        self.stores.open(name.as_deref()).await
+    }
+}
+
+#[async_trait]
+impl BlobStore for RoutedBlobStore {
+    async fn put(
+        &self,
+        data: Vec<u8>,
+        mime: Option<&str>,
+        filename: Option<&str>,
+    ) -> Result<String> {
+        let store = self.stores.open(self.name.as_deref()).await?;
+        store.put(data, mime, filename).await
+    }
+
+    async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        self.holder(url).await?.get(url).await
+    }
+
+    async fn delete(&self, url: &str) -> Result<()> {
+        self.holder(url).await?.delete(url).await
+    }
+}
+
 /// Create a default blob store based on configuration
 ///
+/// The backend is wrapped in a [`StableBlobStore`] recording blob locations in
+/// `storage`, so every returned URL is a stable `beemflow://blobs/<id>` URL.
+pub async fn new_default_blob_store(
+    config: Option<&BlobConfig>,
+    storage: Arc<dyn Storage>,
+) -> Result<StableBlobStore> {
+    let driver = config
+        .and_then(|c| c.driver.as_deref())
+        .filter(|d| !d.is_empty())
+        .unwrap_or("filesystem");
+    let backend = new_backend_blob_store(config).await?;
+    Ok(StableBlobStore::new(driver, backend, storage))
+}
+
+/// Create the backend blob store named by the configuration
+///
 /// Matches Go's NewDefaultBlobStore behavior:
 /// - Returns FilesystemBlobStore if config is None, empty, or driver is "filesystem"
 /// - Returns S3BlobStore if driver is "s3"
 /// - Uses default directory (~/.beemflow/files) if not specified
-pub async fn new_default_blob_store(config: Option<&BlobConfig>) -> Result<Box<dyn BlobStore>> {
+pub async fn new_backend_blob_store(config: Option<&BlobConfig>) -> Result<Box<dyn BlobStore>> {
     // Determine driver and extract config values
     let driver = config
         .and_then(|c| c.driver.as_ref())
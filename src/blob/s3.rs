@@ -107,26 +107,58 @@ impl BlobStore for S3BlobStore {
     ///
     /// Expects url format: s3://bucket/key
     async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        let key = self.key_of(url)?;
+
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| BeemFlowError::storage(format!("Failed to get S3 object: {}", e)))?;
+
+        let data =
+            response.body.collect().await.map_err(|e| {
+                BeemFlowError::storage(format!("Failed to read S3 object body: {}", e))
+            })?;
+
+        Ok(data.to_vec())
+    }
+
+    /// Delete an S3 object by URL (S3 treats missing keys as deleted)
+    async fn delete(&self, url: &str) -> Result<()> {
+        let key = self.key_of(url)?;
+
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| BeemFlowError::storage(format!("Failed to delete S3 object: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+impl S3BlobStore {
+    /// Object key of an s3://bucket/key URL in the configured bucket
+    fn key_of<'a>(&self, url: &'a str) -> Result<&'a str> {
         // Parse s3://bucket/key URL
-        if !url.starts_with("s3://") {
+        let Some(url_parts) = url.strip_prefix("s3://") else {
             return Err(BeemFlowError::validation(format!(
                 "invalid S3 URL: {}",
                 url
             )));
-        }
-
-        let url_parts = &url[5..]; // Remove "s3://"
-        let parts: Vec<&str> = url_parts.splitn(2, '/').collect();
+        };
 
-        if parts.len() != 2 {
+        let Some((bucket, key)) = url_parts.split_once('/') else {
             return Err(BeemFlowError::validation(format!(
                 "invalid S3 URL format: {}",
                 url
             )));
-        }
-
-        let bucket = parts[0];
-        let key = parts[1];
+        };
 
         // Verify bucket matches configured bucket
         if bucket != self.bucket {
@@ -136,20 +168,6 @@ impl BlobStore for S3BlobStore {
             )));
         }
 
-        let response = self
-            .client
-            .get_object()
-            .bucket(&self.bucket)
-            .key(key)
-            .send()
-            .await
-            .map_err(|e| BeemFlowError::storage(format!("Failed to get S3 object: {}", e)))?;
-
-        let data =
-            response.body.collect().await.map_err(|e| {
-                BeemFlowError::storage(format!("Failed to read S3 object body: {}", e))
-            })?;
-
-        Ok(data.to_vec())
+        Ok(key)
     }
 }
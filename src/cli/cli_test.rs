@@ -0,0 +1,313 @@
+use super::*;
+use crate::core::HasMetadata;
+use clap::error::ErrorKind;
+
+/// Parse `args` against the CLI command generated for an operation
+fn parse<Op: HasMetadata>(args: &[&str]) -> std::result::Result<Value, clap::Error> {
+    let meta = Op::metadata();
+    let matches = build_operation_command(meta.name, &meta, "op")
+        .try_get_matches_from(std::iter::once("op").chain(args.iter().copied()))?;
+    Ok(extract_input_from_matches(&matches, &meta).unwrap())
+}
+
+#[test]
+fn test_enum_field_rejects_values_outside_schema() {
+    let err = parse::<crate::core::flows::flows::Get>(&["hello", "--format", "xml"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidValue);
+    let message = err.to_string();
+    assert!(
+        message.contains("[possible values: yaml, json, toml]"),
+        "{}",
+        message
+    );
+
+    let input = parse::<crate::core::flows::flows::Get>(&["hello", "--format", "json"]).unwrap();
+    assert_eq!(
+        input,
+        serde_json::json!({"name": "hello", "format": "json"})
+    );
+}
+
+#[test]
+fn test_enum_of_consts_behind_ref_is_validated() {
+    let err = parse::<crate::core::runs::runs::Start>(&["hello", "--run_id_strategy", "sometimes"])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidValue);
+    assert!(
+        err.to_string().contains("deterministic, random, client"),
+        "{}",
+        err
+    );
+
+    let input =
+        parse::<crate::core::runs::runs::Start>(&["hello", "--run_id_strategy", "random"]).unwrap();
+    assert_eq!(input["run_id_strategy"], "random");
+}
+
+#[test]
+fn test_numeric_bounds_and_pattern_are_validated() {
+    for limit in ["-1", "ten"] {
+        let err = parse::<crate::core::runs::runs::List>(&["--limit", limit]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation, "{}", err);
+    }
+    let err = parse::<crate::core::runs::runs::List>(&["--limit", "-1"]).unwrap_err();
+    assert!(err.to_string().contains("must be at least 0"), "{}", err);
+    let input = parse::<crate::core::runs::runs::List>(&["--limit", "10"]).unwrap();
+    assert_eq!(input["limit"], 10);
+
+    let err = parse::<crate::core::flows::flows::Get>(&["not a name"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ValueValidation);
+    assert!(
+        err.to_string().contains("must match the pattern"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_flow_patch_argument_is_parsed_as_json() {
+    for patch in [
+        r#"[{"op": "replace", "path": "/steps/0/with/text", "value": "hi"}]"#,
+        r#"{"description": "Daily"}"#,
+    ] {
+        let input =
+            parse::<crate::core::flows::flows::Update>(&["greeter", "--patch", patch]).unwrap();
+        assert_eq!(
+            input["patch"],
+            serde_json::from_str::<Value>(patch).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_repeated_flags_build_an_array() {
+    let meta = OperationMetadata {
+        name: "tag_things",
+        description: "Tag things",
+        group: "test",
+        http_method: None,
+        http_path: None,
+        cli_pattern: Some("tag [--tags <TAGS>]"),
+        schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "tags": {"type": ["array", "null"], "items": {"type": "string"}}
+            }
+        })
+        .as_object()
+        .unwrap()
+        .clone(),
+    };
+    let parse = |args: &[&str]| {
+        let matches = build_operation_command(meta.name, &meta, "op")
+            .try_get_matches_from(std::iter::once("op").chain(args.iter().copied()))
+            .unwrap();
+        extract_input_from_matches(&matches, &meta).unwrap()
+    };
+
+    assert_eq!(
+        parse(&["--tags", "a", "--tags", "b"]),
+        serde_json::json!({"tags": ["a", "b"]})
+    );
+    // String items stay strings even when they look like JSON
+    assert_eq!(
+        parse(&["--tags", "1", "--tags", "true"]),
+        serde_json::json!({"tags": ["1", "true"]})
+    );
+    assert_eq!(parse(&["--tags", "a"]), serde_json::json!({"tags": ["a"]}));
+    assert_eq!(
+        parse(&["--tags", r#"["a", "b"]"#]),
+        serde_json::json!({"tags": ["a", "b"]})
+    );
+    assert_eq!(parse(&[]), serde_json::json!({}));
+}
+
+#[test]
+fn test_repeated_patch_flags_build_patch_operations() {
+    let input = parse::<crate::core::flows::flows::Update>(&[
+        "greeter",
+        "--patch",
+        r#"{"op": "replace", "path": "/description", "value": "Daily"}"#,
+        "--patch",
+        r#"{"op": "remove", "path": "/tags"}"#,
+    ])
+    .unwrap();
+    assert_eq!(
+        input["patch"],
+        serde_json::json!([
+            {"op": "replace", "path": "/description", "value": "Daily"},
+            {"op": "remove", "path": "/tags"}
+        ])
+    );
+}
+
+#[test]
+fn test_event_is_loaded_from_input_file_and_overridden_by_fields() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let yaml = dir.path().join("event.yaml");
+    std::fs::write(
+        &yaml,
+        "customer: acme\nitems:\n  - sku: A1\n    qty: 2\nrush: false\n",
+    )
+    .unwrap();
+    let yaml = yaml.to_str().unwrap();
+
+    let input = parse::<crate::core::runs::runs::Start>(&["orders", "--input-file", yaml]).unwrap();
+    assert_eq!(
+        input["event"],
+        serde_json::json!({"customer": "acme", "items": [{"sku": "A1", "qty": 2}], "rush": false})
+    );
+
+    // Inline JSON wins over the file and explicit fields win over both
+    let input = parse::<crate::core::runs::runs::Start>(&[
+        "orders",
+        "--input-file",
+        yaml,
+        "--input",
+        r#"{"customer": "globex", "note": "inline"}"#,
+        "--field",
+        "rush=true",
+        "--field",
+        "note=by hand",
+    ])
+    .unwrap();
+    assert_eq!(
+        input["event"],
+        serde_json::json!({
+            "customer": "globex",
+            "items": [{"sku": "A1", "qty": 2}],
+            "rush": true,
+            "note": "by hand"
+        })
+    );
+
+    let json = dir.path().join("event.json");
+    std::fs::write(&json, r#"{"customer": "initech"}"#).unwrap();
+    let input = parse::<crate::core::runs::runs::Start>(&[
+        "orders",
+        "--input-file",
+        json.to_str().unwrap(),
+        "--event",
+        r#"{"priority": 1}"#,
+    ])
+    .unwrap();
+    assert_eq!(
+        input["event"],
+        serde_json::json!({"customer": "initech", "priority": 1})
+    );
+}
+
+#[test]
+fn test_event_flags_are_validated() {
+    let meta = crate::core::runs::runs::Start::metadata();
+    let extract = |args: &[&str]| {
+        let matches = build_operation_command(meta.name, &meta, "op")
+            .try_get_matches_from(std::iter::once("op").chain(args.iter().copied()))
+            .unwrap();
+        extract_input_from_matches(&matches, &meta)
+    };
+    for args in [
+        &["orders", "--field", "no-equals-sign"][..],
+        &["orders", "--input", "not json"],
+        &["orders", "--input", "[1, 2]", "--field", "a=1"],
+        &["orders", "--input-file", "/nonexistent/event.json"],
+    ] {
+        assert!(extract(args).is_err(), "{:?}", args);
+    }
+
+    // --input and --event are the same payload
+    let err = build_operation_command(meta.name, &meta, "op")
+        .try_get_matches_from(["op", "orders", "--input", "{}", "--event", "{}"])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+}
+
+async fn oauth_token_fixture() -> (
+    crate::auth::OAuthClientManager,
+    Arc<dyn crate::storage::Storage>,
+) {
+    let storage: Arc<dyn crate::storage::Storage> = Arc::new(
+        crate::storage::SqliteStorage::new(":memory:")
+            .await
+            .unwrap(),
+    );
+    let registry_manager = Arc::new(RegistryManager::standard(
+        None,
+        Arc::new(crate::secrets::EnvSecretsProvider::new()),
+    ));
+    let client = crate::auth::OAuthClientManager::new(
+        storage.clone(),
+        registry_manager,
+        "http://localhost:3000/callback".to_string(),
+    )
+    .unwrap();
+    (client, storage)
+}
+
+fn oauth_credential(
+    integration: &str,
+    expires_at: chrono::DateTime<Utc>,
+) -> crate::model::OAuthCredential {
+    crate::model::OAuthCredential {
+        id: format!("github-{}", integration),
+        provider: "github".to_string(),
+        integration: integration.to_string(),
+        access_token: "ghp_0123456789abcdef3f9a".to_string(),
+        refresh_token: None,
+        expires_at: Some(expires_at),
+        scope: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        tenant_id: None,
+    }
+}
+
+#[tokio::test]
+async fn test_oauth_token_returns_valid_credential() {
+    let (client, storage) = oauth_token_fixture().await;
+    let expires_at = Utc::now() + chrono::Duration::hours(1);
+    storage
+        .save_oauth_credential(&oauth_credential("default", expires_at))
+        .await
+        .unwrap();
+
+    let token = current_oauth_token(&client, &storage, "github", "default")
+        .await
+        .unwrap();
+    assert_eq!(token.access_token, "ghp_0123456789abcdef3f9a");
+    assert_eq!(
+        token.expires_at.map(|at| at.timestamp()),
+        Some(expires_at.timestamp())
+    );
+    assert_eq!(mask_token(&token.access_token), "ghp_…3f9a");
+    assert_eq!(mask_token("short"), crate::secrets::REDACTED);
+}
+
+#[tokio::test]
+async fn test_oauth_token_missing_or_unrecoverable_credential() {
+    let (client, storage) = oauth_token_fixture().await;
+
+    let err = current_oauth_token(&client, &storage, "github", "default")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("OAuth credential not found for github:default"),
+        "{}",
+        err
+    );
+
+    // Expired with no refresh token: get_token would return the stale token
+    storage
+        .save_oauth_credential(&oauth_credential(
+            "stale",
+            Utc::now() - chrono::Duration::hours(1),
+        ))
+        .await
+        .unwrap();
+    let err = current_oauth_token(&client, &storage, "github", "stale")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("could not be refreshed"), "{}", err);
+}
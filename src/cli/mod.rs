@@ -8,10 +8,21 @@ use crate::auth::server::generate_client_secret;
 use crate::config::Config;
 use crate::core::{OperationMetadata, OperationRegistry};
 use crate::model::OAuthClient;
+use crate::registry::RegistryManager;
 use chrono::Utc;
+use clap::builder::{PossibleValue, PossibleValuesParser, ValueParser};
 use clap::{Arg, ArgAction, ArgMatches, Command};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+/// Flag added to cursor-paginated operations to list every page
+const ALL_PAGES_FLAG: &str = "all";
+
+/// Flags added to operations taking an event, which assemble it in layers
+const INPUT_FILE_FLAG: &str = "input-file";
+const INPUT_FLAG: &str = "input";
+const FIELD_FLAG: &str = "field";
 
 /// Parse a comma-separated list from CLI arguments
 fn parse_comma_list(matches: &ArgMatches, key: &str) -> Vec<String> {
@@ -37,6 +48,164 @@ fn to_static_str(s: String) -> &'static str {
     s.leak()
 }
 
+/// Render `runs stats` output as a plain-text table
+fn format_runs_stats_table(result: &Value) -> String {
+    fn rate(v: &Value) -> String {
+        v.as_f64()
+            .map(|r| format!("{:.1}%", r * 100.0))
+            .unwrap_or_else(|| "-".to_string())
+    }
+    fn duration(v: &Value) -> String {
+        v.as_i64()
+            .map(|ms| format!("{}ms", ms))
+            .unwrap_or_else(|| "-".to_string())
+    }
+    fn count(v: &Value) -> String {
+        v.as_u64().unwrap_or(0).to_string()
+    }
+    fn tool_calls(usage: &Value) -> String {
+        let calls: u64 = usage["tool_invocations"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(_, n)| n.as_u64())
+            .sum();
+        calls.to_string()
+    }
+
+    let mut rows = vec![[
+        "FLOW".to_string(),
+        "RUNS".to_string(),
+        "SUCCEEDED".to_string(),
+        "FAILED".to_string(),
+        "SUCCESS".to_string(),
+        "P50".to_string(),
+        "P95".to_string(),
+        "TOOL CALLS".to_string(),
+        "HTTP".to_string(),
+    ]];
+    for flow in result["flows"].as_array().into_iter().flatten() {
+        rows.push([
+            flow["flow_name"].as_str().unwrap_or_default().to_string(),
+            count(&flow["total"]),
+            count(&flow["succeeded"]),
+            count(&flow["failed"]),
+            rate(&flow["success_rate"]),
+            duration(&flow["p50_duration_ms"]),
+            duration(&flow["p95_duration_ms"]),
+            tool_calls(&flow["usage"]),
+            count(&flow["usage"]["http_requests"]),
+        ]);
+    }
+    let totals = &result["totals"];
+    rows.push([
+        "TOTAL".to_string(),
+        count(&totals["total"]),
+        count(&totals["succeeded"]),
+        count(&totals["failed"]),
+        rate(&totals["success_rate"]),
+        String::new(),
+        String::new(),
+        tool_calls(&totals["usage"]),
+        count(&totals["usage"]["http_requests"]),
+    ]);
+
+    format!(
+        "Run statistics for the last {}\n{}",
+        result["window"].as_str().unwrap_or_default(),
+        format_table(&rows)
+    )
+}
+
+/// Render `system operations` output as a plain-text table
+fn format_operations_table(result: &Value) -> String {
+    let mut rows = vec![[
+        "OPERATION".to_string(),
+        "GROUP".to_string(),
+        "HTTP".to_string(),
+        "CLI".to_string(),
+        "MCP".to_string(),
+    ]];
+    for op in result["operations"].as_array().into_iter().flatten() {
+        let http = match (op["http_method"].as_str(), op["http_path"].as_str()) {
+            (Some(method), Some(path)) => format!("{} {}", method, path),
+            _ => "-".to_string(),
+        };
+        rows.push([
+            op["name"].as_str().unwrap_or_default().to_string(),
+            op["group"].as_str().unwrap_or_default().to_string(),
+            http,
+            op["cli_pattern"].as_str().unwrap_or("-").to_string(),
+            if op["mcp_tool"].as_bool().unwrap_or(false) {
+                "yes"
+            } else {
+                "no"
+            }
+            .to_string(),
+        ]);
+    }
+    format_table(&rows)
+}
+
+/// Render `flows list` output as a plain-text table
+fn format_flows_table(result: &Value) -> String {
+    fn cell(v: &Value) -> String {
+        v.as_str().unwrap_or("-").to_string()
+    }
+
+    let mut rows = vec![[
+        "NAME".to_string(),
+        "OWNER".to_string(),
+        "TEAM".to_string(),
+        "TAGS".to_string(),
+        "DEPLOYED".to_string(),
+        "STATUS".to_string(),
+    ]];
+    for flow in result["flows"].as_array().into_iter().flatten() {
+        let tags: Vec<&str> = flow["tags"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        rows.push([
+            cell(&flow["name"]),
+            cell(&flow["owner"]),
+            cell(&flow["team"]),
+            if tags.is_empty() {
+                "-".to_string()
+            } else {
+                tags.join(",")
+            },
+            cell(&flow["deployed_version"]),
+            cell(&flow["status"]),
+        ]);
+    }
+    format_table(&rows)
+}
+
+/// Left-align rows into columns separated by two spaces
+fn format_table<const N: usize>(rows: &[[String; N]]) -> String {
+    let mut widths = [0usize; N];
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut out = String::new();
+    for row in rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
 /// Create operation registry with dependencies
 async fn create_registry() -> Result<OperationRegistry> {
     let config = Config::load_and_inject(crate::constants::CONFIG_FILE_NAME)?;
@@ -61,16 +230,50 @@ pub async fn run() -> Result<()> {
         Some(("cron", _)) => {
             return run_cron_check().await;
         }
-        Some(("oauth", sub_matches)) => {
+        Some(("oauth", sub_matches)) if !is_operation_command(&matches, &registry) => {
             return handle_oauth_command(sub_matches).await;
         }
+        Some(("runs", sub_matches)) if sub_matches.subcommand_name() == Some("tail") => {
+            return tail_run(&registry, sub_matches).await;
+        }
         _ => {}
     }
 
     // Try to dispatch to an operation (uses registry.execute() like MCP does)
     if let Some((op_name, input)) = dispatch_to_operation(&matches, &registry)? {
+        let op_matches = operation_matches(&matches).1;
+        if let Ok(Some(true)) = op_matches.try_get_one::<bool>(ALL_PAGES_FLAG) {
+            return stream_all_pages(&registry, &op_name, input).await;
+        }
+        if op_name == "export_runs" {
+            return export_runs(&registry, input).await;
+        }
+        if op_name == "import_flow_bundle" {
+            return import_flow_bundle(&registry, input).await;
+        }
+        let input = if op_name == "render_flow" {
+            read_render_files(input).await?
+        } else {
+            input
+        };
         let result = registry.execute(&op_name, input).await?;
-        println!("{}", serde_json::to_string_pretty(&result)?);
+        if op_name == "runs_stats" {
+            print!("{}", format_runs_stats_table(&result));
+        } else if op_name == "list_flows" {
+            print!("{}", format_flows_table(&result));
+        } else if op_name == "list_operations" {
+            print!("{}", format_operations_table(&result));
+        } else if op_name == "render_flow" {
+            // Print the rendered flow (or its diff) as-is rather than JSON-escaped
+            let text = result
+                .get("diff")
+                .or_else(|| result.get("rendered"))
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            print!("{}", text);
+        } else {
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
         return Ok(());
     }
 
@@ -142,6 +345,12 @@ fn build_cli(registry: &OperationRegistry) -> Command {
                         .short('p')
                         .default_value("3330")
                         .help("Server port"),
+                )
+                .arg(
+                    Arg::new("reload")
+                        .long("reload")
+                        .action(ArgAction::SetTrue)
+                        .help("Watch the config, registry and flows directory and apply changes without restarting (development)"),
                 ),
         )
         .subcommand(Command::new("cron").about("Run cron checks"))
@@ -158,6 +367,11 @@ fn build_cli(registry: &OperationRegistry) -> Command {
                                 .default_value("client_credentials"),
                         )
                         .arg(Arg::new("scopes").long("scopes").default_value("mcp"))
+                        .arg(
+                            Arg::new("tenant")
+                                .long("tenant")
+                                .help("Scope the client's tokens to this tenant's runs, flows and credentials"),
+                        )
                         .arg(Arg::new("json").long("json").action(ArgAction::SetTrue)),
                 )
                 .subcommand(
@@ -169,55 +383,111 @@ fn build_cli(registry: &OperationRegistry) -> Command {
                     Command::new("revoke-client")
                         .about("Revoke OAuth client")
                         .arg(Arg::new("client-id").required(true).index(1)),
+                )
+                .subcommand(
+                    Command::new("prune-credentials")
+                        .about("Delete expired OAuth credentials that have no refresh token")
+                        .arg(Arg::new("json").long("json").action(ArgAction::SetTrue)),
+                )
+                .subcommand(
+                    Command::new("token")
+                        .about("Print the current access token of a credential, refreshing it if needed")
+                        .arg(Arg::new("provider").long("provider").required(true))
+                        .arg(Arg::new("integration").long("integration").required(true))
+                        .arg(
+                            Arg::new("reveal")
+                                .long("reveal")
+                                .action(ArgAction::SetTrue)
+                                .help("Print the whole token instead of masking it"),
+                        )
+                        .arg(Arg::new("json").long("json").action(ArgAction::SetTrue)),
                 ),
         );
 
     // Build operation commands from metadata
-    add_operation_commands(app, registry)
+    let app = add_operation_commands(app, registry);
+
+    // Streaming commands nested among the operations
+    app.mut_subcommand("runs", |runs| {
+        runs.subcommand(
+            Command::new("tail")
+                .about("Follow a run, printing each step as it starts and finishes, until the run ends")
+                .arg(Arg::new("run_id").required(true).index(1).help("UUID of the run"))
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .value_name("SECS")
+                        .value_parser(clap::value_parser!(f64))
+                        .default_value("1")
+                        .help("Seconds between reads of the run's records"),
+                ),
+        )
+    })
 }
 
-/// Build commands from operation metadata (mirrors HTTP route generation)
-fn add_operation_commands(mut app: Command, registry: &OperationRegistry) -> Command {
-    let metadata = registry.get_all_metadata();
-
-    // Group operations by CLI structure
-    let mut grouped: HashMap<String, Vec<(&String, &OperationMetadata)>> = HashMap::new();
+/// Leading command words of a CLI pattern (everything before the first argument)
+fn command_words(cli_pattern: &'static str) -> Vec<&'static str> {
+    cli_pattern
+        .split_whitespace()
+        .take_while(|w| !w.starts_with('<') && !w.starts_with('[') && !w.starts_with('-'))
+        .collect()
+}
 
-    for (op_name, meta) in metadata {
-        if let Some(cli_pattern) = meta.cli_pattern {
-            let words: Vec<&str> = cli_pattern
-                .split_whitespace()
-                .take_while(|w| !w.starts_with('<') && !w.starts_with('['))
-                .collect();
+/// Operation commands sharing a command-word prefix
+#[derive(Default)]
+struct CommandNode<'a> {
+    operation: Option<(&'a String, &'a OperationMetadata)>,
+    children: BTreeMap<&'static str, CommandNode<'a>>,
+}
 
-            let group = words.first().map(|s| s.to_string()).unwrap_or_default();
-            grouped.entry(group).or_default().push((op_name, meta));
-        }
+impl CommandNode<'_> {
+    /// Build the clap command for this node: the operation's own command if the
+    /// node has one, or a plain group, with child commands nested under it
+    fn into_command(self, name: &'static str) -> Command {
+        let cmd = match self.operation {
+            Some((op_name, meta)) => build_operation_command(op_name, meta, name),
+            None => Command::new(name).about(to_static_str(format!("{} operations", name))),
+        };
+        self.add_children(cmd)
     }
 
-    // Build subcommands for each group
-    for (group_name, ops) in grouped {
-        if group_name.is_empty() {
-            continue;
+    /// Nest this node's child commands under `cmd`
+    fn add_children(self, mut cmd: Command) -> Command {
+        for (child_name, child) in self.children {
+            cmd = cmd.subcommand(child.into_command(child_name));
         }
+        cmd
+    }
+}
 
-        // Use to_static_str to satisfy clap's 'static lifetime requirement
-        let group_name_static = to_static_str(group_name.clone());
-        let group_about = to_static_str(format!("{} operations", group_name));
-        let mut group_cmd = Command::new(group_name_static).about(group_about);
-
-        for (op_name, meta) in ops {
-            if let Some(cli_pattern) = meta.cli_pattern {
-                // cli_pattern has 'static lifetime, so words do too
-                let words: Vec<&'static str> = cli_pattern.split_whitespace().collect();
-                let subcmd_name = words.get(1).copied().unwrap_or(words[0]);
+/// Build commands from operation metadata (mirrors HTTP route generation)
+///
+/// Each pattern's command words form a path in the command tree: `spec` is a
+/// top-level command, `flows list` nests under `flows`, `system blobs gc`
+/// under `system blobs`.
+fn add_operation_commands(mut app: Command, registry: &OperationRegistry) -> Command {
+    let mut root = CommandNode::default();
 
-                let cmd = build_operation_command(op_name, meta, subcmd_name);
-                group_cmd = group_cmd.subcommand(cmd);
+    for (op_name, meta) in registry.get_all_metadata() {
+        if let Some(cli_pattern) = meta.cli_pattern {
+            let words = command_words(cli_pattern);
+            if words.is_empty() {
+                continue;
             }
+            let node = words.into_iter().fold(&mut root, |node, word| {
+                node.children.entry(word).or_default()
+            });
+            node.operation = Some((op_name, meta));
         }
+    }
 
-        app = app.subcommand(group_cmd);
+    for (name, node) in root.children {
+        // Operations may also nest under a hand-built command, as under `oauth`
+        app = if app.find_subcommand(name).is_some() {
+            app.mut_subcommand(name, |cmd| node.add_children(cmd))
+        } else {
+            app.subcommand(node.into_command(name))
+        };
     }
 
     app
@@ -249,6 +519,47 @@ fn build_operation_command(
     // Adjust schema for CLI: make "content" optional (convention)
     let cli_schema = adjust_schema_for_cli(&meta.schema);
 
+    // Cursor-paginated operations can follow every page
+    if let Some(properties) = cli_schema.get("properties").and_then(|p| p.as_object())
+        && properties.contains_key("cursor")
+        && !properties.contains_key(ALL_PAGES_FLAG)
+    {
+        cmd = cmd.arg(
+            Arg::new(ALL_PAGES_FLAG)
+                .long(ALL_PAGES_FLAG)
+                .action(ArgAction::SetTrue)
+                .conflicts_with("cursor")
+                .help("Follow cursors and print every item, one JSON document per line"),
+        );
+    }
+
+    // Event payloads can be loaded from a file and overridden field by field
+    if let Some(properties) = cli_schema.get("properties").and_then(|p| p.as_object())
+        && properties.contains_key("event")
+    {
+        cmd = cmd
+            .arg(
+                Arg::new(INPUT_FILE_FLAG)
+                    .long(INPUT_FILE_FLAG)
+                    .value_name("PATH")
+                    .help("Load the event from a JSON or YAML file"),
+            )
+            .arg(
+                Arg::new(INPUT_FLAG)
+                    .long(INPUT_FLAG)
+                    .value_name("JSON")
+                    .conflicts_with("event")
+                    .help("Event as inline JSON, merged over --input-file"),
+            )
+            .arg(
+                Arg::new(FIELD_FLAG)
+                    .long(FIELD_FLAG)
+                    .value_name("KEY=VALUE")
+                    .action(ArgAction::Append)
+                    .help("Set one event field, winning over --input-file and --input; VALUE is parsed as JSON if it can be"),
+            );
+    }
+
     // Extract field information from adjusted CLI schema
     if let Some(properties) = cli_schema.get("properties").and_then(|p| p.as_object()) {
         let required: Vec<&str> = cli_schema
@@ -257,6 +568,7 @@ fn build_operation_command(
             .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect())
             .unwrap_or_default();
 
+        let defs = cli_schema.get("$defs");
         let cli_pattern = meta.cli_pattern.unwrap_or("");
         let mut positional_index = 1;
 
@@ -292,35 +604,147 @@ fn build_operation_command(
             let field_name_static = to_static_str(field_name.clone());
             let description_static = to_static_str(description.to_string());
 
-            if is_positional {
-                cmd = cmd.arg(
-                    Arg::new(field_name_static)
-                        .required(is_required)
-                        .index(positional_index)
-                        .help(description_static),
-                );
+            let arg = if is_positional {
+                let arg = Arg::new(field_name_static)
+                    .required(is_required)
+                    .index(positional_index)
+                    .help(description_static);
                 positional_index += 1;
+                arg
             } else if field_type == "boolean" {
-                cmd = cmd.arg(
-                    Arg::new(field_name_static)
-                        .long(field_name_static)
-                        .action(ArgAction::SetTrue)
-                        .help(description_static),
-                );
+                Arg::new(field_name_static)
+                    .long(field_name_static)
+                    .action(ArgAction::SetTrue)
+                    .help(description_static)
+            } else if field_type == "array" {
+                // Repeated flags each add an item: --tag a --tag b
+                Arg::new(field_name_static)
+                    .long(field_name_static)
+                    .required(is_required)
+                    .action(ArgAction::Append)
+                    .help(description_static)
             } else {
-                cmd = cmd.arg(
-                    Arg::new(field_name_static)
-                        .long(field_name_static)
-                        .required(is_required)
-                        .help(description_static),
-                );
-            }
+                Arg::new(field_name_static)
+                    .long(field_name_static)
+                    .required(is_required)
+                    .allow_negative_numbers(matches!(field_type, "integer" | "number"))
+                    .help(description_static)
+            };
+
+            // Reject values the schema rules out before dispatch
+            cmd = cmd.arg(match schema_value_parser(field_schema, field_type, defs) {
+                Some(parser) => arg.value_parser(parser),
+                None => arg,
+            });
         }
     }
 
     cmd
 }
 
+/// Value parser enforcing a field's schema constraints, if it has any
+///
+/// String `enum`s, including `oneOf`/`anyOf` lists of `const`s behind `$ref`s,
+/// become possible values; integer and number fields must parse and respect
+/// `minimum`/`maximum`; strings must match `pattern`. Parsers yield the value as
+/// given, leaving conversion to `extract_input_from_matches`.
+fn schema_value_parser(
+    field_schema: &Value,
+    field_type: &str,
+    defs: Option<&Value>,
+) -> Option<ValueParser> {
+    if let Some(values) = schema_enum_values(field_schema, defs) {
+        let values = values
+            .into_iter()
+            .map(|value| PossibleValue::new(to_static_str(value)));
+        return Some(PossibleValuesParser::new(values).into());
+    }
+
+    match field_type {
+        "integer" | "number" => {
+            let integer = field_type == "integer";
+            let minimum = field_schema.get("minimum").and_then(Value::as_f64);
+            let maximum = field_schema.get("maximum").and_then(Value::as_f64);
+            Some(ValueParser::new(
+                move |value: &str| -> std::result::Result<String, String> {
+                    let number = if integer {
+                        value.parse::<i64>().map(|n| n as f64).ok()
+                    } else {
+                        value.parse::<f64>().ok()
+                    }
+                    .ok_or_else(|| {
+                        format!(
+                            "expected {}",
+                            if integer { "an integer" } else { "a number" }
+                        )
+                    })?;
+                    if let Some(minimum) = minimum
+                        && number < minimum
+                    {
+                        return Err(format!("must be at least {}", minimum));
+                    }
+                    if let Some(maximum) = maximum
+                        && number > maximum
+                    {
+                        return Err(format!("must be at most {}", maximum));
+                    }
+                    Ok(value.to_string())
+                },
+            ))
+        }
+        "string" => {
+            let pattern = field_schema.get("pattern").and_then(Value::as_str)?;
+            let regex = regex::Regex::new(pattern).ok()?;
+            Some(ValueParser::new(
+                move |value: &str| -> std::result::Result<String, String> {
+                    if regex.is_match(value) {
+                        Ok(value.to_string())
+                    } else {
+                        Err(format!("must match the pattern {}", regex.as_str()))
+                    }
+                },
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Values a field is limited to, if its schema enumerates them
+fn schema_enum_values(schema: &Value, defs: Option<&Value>) -> Option<Vec<String>> {
+    let schema = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix("#/$defs/"))
+        .and_then(|name| defs?.get(name))
+        .unwrap_or(schema);
+
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return Some(
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+        );
+    }
+    if let Some(value) = schema.get("const").and_then(Value::as_str) {
+        return Some(vec![value.to_string()]);
+    }
+
+    // Every non-null variant must itself be enumerated
+    let variants = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))?
+        .as_array()?;
+    let mut values = Vec::new();
+    for variant in variants {
+        if variant.get("type").and_then(Value::as_str) != Some("null") {
+            values.extend(schema_enum_values(variant, defs)?);
+        }
+    }
+    (!values.is_empty()).then_some(values)
+}
+
 /// Dispatch CLI matches to operation (uses registry.execute() like MCP)
 fn dispatch_to_operation(
     matches: &ArgMatches,
@@ -328,24 +752,214 @@ fn dispatch_to_operation(
 ) -> Result<Option<(String, Value)>> {
     let metadata = registry.get_all_metadata();
 
-    // Check for two-level subcommands (group subcmd)
-    if let Some((group, group_matches)) = matches.subcommand()
-        && let Some((subcmd, subcmd_matches)) = group_matches.subcommand()
+    let (words, op_matches) = operation_matches(matches);
+    if words.is_empty() {
+        return Ok(None);
+    }
+
+    // Find matching operation
+    for (op_name, meta) in metadata {
+        if let Some(cli_pattern) = meta.cli_pattern
+            && command_words(cli_pattern) == words
+        {
+            let input = extract_input_from_matches(op_matches, meta)?;
+            return Ok(Some((op_name.clone(), input)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Whether the matched command is an operation's rather than a hand-built one
+fn is_operation_command(matches: &ArgMatches, registry: &OperationRegistry) -> bool {
+    let words = operation_matches(matches).0;
+    registry.get_all_metadata().values().any(|meta| {
+        meta.cli_pattern
+            .is_some_and(|pattern| command_words(pattern) == words)
+    })
+}
+
+/// Follow the chain of matched subcommands down to the operation's command
+fn operation_matches(matches: &ArgMatches) -> (Vec<&str>, &ArgMatches) {
+    let mut words = Vec::new();
+    let mut op_matches = matches;
+    while let Some((name, sub_matches)) = op_matches.subcommand() {
+        words.push(name);
+        op_matches = sub_matches;
+    }
+    (words, op_matches)
+}
+
+/// Follow `next_cursor` through every page, printing each item as a JSON line
+///
+/// Pages are printed as they arrive, so listing everything never holds more
+/// than one page in memory.
+async fn stream_all_pages(
+    registry: &OperationRegistry,
+    op_name: &str,
+    mut input: Value,
+) -> Result<()> {
+    if input.get("offset").is_some() {
+        return Err(crate::BeemFlowError::validation(
+            "--all can't be combined with --offset",
+        ));
+    }
+    loop {
+        let page = registry.execute(op_name, input.clone()).await?;
+        for item in page["items"].as_array().into_iter().flatten() {
+            println!("{}", serde_json::to_string(item)?);
+        }
+        match page["next_cursor"].as_str() {
+            Some(cursor) => input["cursor"] = serde_json::json!(cursor),
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Read the local files `flows render` names into its input
+///
+/// The operation itself only renders inline content or flows in the flows
+/// directory, so reading arbitrary paths stays on the CLI.
+async fn read_render_files(mut input: Value) -> Result<Value> {
+    if let Some(file) = input
+        .get("file")
+        .and_then(Value::as_str)
+        .map(str::to_string)
     {
-        let prefix = format!("{} {}", group, subcmd);
-
-        // Find matching operation
-        for (op_name, meta) in metadata {
-            if let Some(cli_pattern) = meta.cli_pattern
-                && cli_pattern.starts_with(&prefix)
-            {
-                let input = extract_input_from_matches(subcmd_matches, meta)?;
-                return Ok(Some((op_name.clone(), input)));
+        input["content"] = Value::String(tokio::fs::read_to_string(&file).await?);
+    }
+    if let Some(vars_path) = input
+        .get("vars")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+    {
+        let raw = tokio::fs::read_to_string(&vars_path).await?;
+        input["vars"] = serde_json::from_str(&raw).map_err(|e| {
+            crate::BeemFlowError::validation(format!("Invalid vars file '{}': {}", vars_path, e))
+        })?;
+    }
+    Ok(input)
+}
+
+/// Import a flow bundle from `--file`
+///
+/// Unlike `import_flow_bundle` over HTTP and MCP, the CLI also registers MCP
+/// servers and OAuth providers, and replaces existing entries with `--overwrite`.
+async fn import_flow_bundle(registry: &OperationRegistry, input: Value) -> Result<()> {
+    use crate::core::flows::{BundleImport, flows::ImportBundleInput, import_bundle};
+
+    let input: ImportBundleInput = serde_json::from_value(input)
+        .map_err(|e| crate::BeemFlowError::validation(format!("Invalid input: {}", e)))?;
+    let bundle = match (input.bundle, input.file) {
+        (Some(bundle), _) => bundle,
+        (None, Some(file_path)) => {
+            let content = tokio::fs::read_to_string(&file_path).await?;
+            serde_json::from_str(&content).map_err(|e| {
+                crate::BeemFlowError::validation(format!("Invalid flow bundle: {}", e))
+            })?
+        }
+        (None, None) => {
+            return Err(crate::BeemFlowError::validation(
+                "Either 'bundle' or 'file' must be provided",
+            ));
+        }
+    };
+    let options = BundleImport {
+        all_entry_types: true,
+        overwrite: input.overwrite,
+    };
+    let result = import_bundle(&registry.get_dependencies(), bundle, options).await?;
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(())
+}
+
+/// Stream `runs export-data` rows to `--output` or stdout
+///
+/// With `--include_steps` and `--output runs.csv`, step rows go to
+/// `runs.steps.csv`; on stdout they follow the runs as a second section.
+async fn export_runs(registry: &OperationRegistry, input: Value) -> Result<()> {
+    use crate::core::export::write_export;
+
+    let input: crate::core::runs::runs::ExportInput = serde_json::from_value(input)
+        .map_err(|e| crate::BeemFlowError::validation(format!("Invalid input: {}", e)))?;
+    let options = input.options()?;
+    let storage = registry.get_dependencies().storage.clone();
+
+    let Some(output) = input.output else {
+        write_export(storage.as_ref(), &options, &mut tokio::io::stdout(), None).await?;
+        return Ok(());
+    };
+
+    let mut runs_file = tokio::fs::File::create(&output).await?;
+    if !options.include_steps {
+        let counts = write_export(storage.as_ref(), &options, &mut runs_file, None).await?;
+        eprintln!("Exported {} runs to {}", counts.runs, output);
+        return Ok(());
+    }
+
+    let path = std::path::Path::new(&output);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let steps_output =
+        path.with_file_name(format!("{}.steps.{}", stem, options.format.extension()));
+    let mut steps_file = tokio::fs::File::create(&steps_output).await?;
+    let counts = write_export(
+        storage.as_ref(),
+        &options,
+        &mut runs_file,
+        Some(&mut steps_file),
+    )
+    .await?;
+    eprintln!(
+        "Exported {} runs to {} and {} steps to {}",
+        counts.runs,
+        output,
+        counts.steps,
+        steps_output.display()
+    );
+    Ok(())
+}
+
+/// Names of operations whose CLI pattern parses and dispatches back to them
+///
+/// Each pattern is parsed against the built command tree with placeholder values
+/// for its required arguments; used by the `system operations` parity check.
+pub(crate) fn dispatchable_operations(registry: &OperationRegistry) -> HashSet<String> {
+    let app = build_cli(registry);
+    let mut reachable = HashSet::new();
+
+    for (op_name, meta) in registry.get_all_metadata() {
+        let Some(cli_pattern) = meta.cli_pattern else {
+            continue;
+        };
+        let words = command_words(cli_pattern);
+
+        // Walk the tree to the operation's command to learn its required arguments
+        let Some(cmd) = words
+            .iter()
+            .try_fold(&app, |cmd, word| cmd.find_subcommand(word))
+        else {
+            continue;
+        };
+        let mut argv: Vec<String> = std::iter::once("flow")
+            .chain(words.iter().copied())
+            .map(String::from)
+            .collect();
+        for arg in cmd.get_arguments().filter(|a| a.is_required_set()) {
+            if let Some(long) = arg.get_long() {
+                argv.push(format!("--{}", long));
             }
+            argv.push("placeholder".to_string());
+        }
+
+        if let Ok(matches) = app.clone().try_get_matches_from(argv)
+            && let Ok(Some((dispatched, _))) = dispatch_to_operation(&matches, registry)
+            && &dispatched == op_name
+        {
+            reachable.insert(op_name.clone());
         }
     }
 
-    Ok(None)
+    reachable
 }
 
 /// Extract operation input from CLI arguments using schema
@@ -371,6 +985,11 @@ fn extract_input_from_matches(matches: &ArgMatches, meta: &OperationMetadata) ->
                 if matches.get_flag(field_name.as_str()) {
                     input.insert(field_name.clone(), serde_json::json!(true));
                 }
+            } else if field_type == "array" {
+                if let Some(values) = matches.get_many::<String>(field_name.as_str()) {
+                    let values: Vec<&String> = values.collect();
+                    input.insert(field_name.clone(), array_from_values(&values, field_schema));
+                }
             } else if let Some(value_str) = matches.get_one::<String>(field_name.as_str()) {
                 let parsed = match field_type {
                     "integer" | "number" => value_str
@@ -386,6 +1005,10 @@ fn extract_input_from_matches(matches: &ArgMatches, meta: &OperationMetadata) ->
         }
     }
 
+    if let Some(event) = event_from_matches(matches, input.remove("event"))? {
+        input.insert("event".to_string(), event);
+    }
+
     // CLI-specific handling: inject empty string for "content" if missing but "file" is present
     // This allows "content" (required in schema for MCP) to be satisfied while using file
     if !input.contains_key("content") && input.contains_key("file") {
@@ -395,6 +1018,96 @@ fn extract_input_from_matches(matches: &ArgMatches, meta: &OperationMetadata) ->
     Ok(serde_json::json!(input))
 }
 
+/// Array input from the values of a repeated flag
+///
+/// A single value holding a JSON array or object is taken as the whole input,
+/// so `--tags '["a", "b"]'` keeps working. Otherwise each value is one item,
+/// parsed as JSON unless the schema says items are strings.
+fn array_from_values(values: &[&String], field_schema: &Value) -> Value {
+    if let [value] = values
+        && let Ok(parsed @ (Value::Array(_) | Value::Object(_))) =
+            serde_json::from_str::<Value>(value)
+    {
+        return parsed;
+    }
+
+    let string_items = field_schema
+        .get("items")
+        .and_then(|items| items.get("type"))
+        .and_then(Value::as_str)
+        == Some("string");
+    values
+        .iter()
+        .map(|value| {
+            if string_items {
+                Value::String(value.to_string())
+            } else {
+                serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+            }
+        })
+        .collect()
+}
+
+/// Assemble an event from `--input-file`, then `--input`/`--event`, then `--field`s
+///
+/// Later layers win key by key. Returns `inline` untouched for commands without
+/// the event flags.
+fn event_from_matches(matches: &ArgMatches, inline: Option<Value>) -> Result<Option<Value>> {
+    let arg = |id: &str| matches.try_get_one::<String>(id).ok().flatten();
+    let inline = match arg(INPUT_FLAG) {
+        Some(json) => Some(serde_json::from_str(json).map_err(|e| {
+            crate::BeemFlowError::validation(format!("--input is not valid JSON: {}", e))
+        })?),
+        None => inline,
+    };
+    let fields: Vec<&String> = matches
+        .try_get_many::<String>(FIELD_FLAG)
+        .ok()
+        .flatten()
+        .map(Iterator::collect)
+        .unwrap_or_default();
+    let file = arg(INPUT_FILE_FLAG);
+    if file.is_none() && fields.is_empty() {
+        return Ok(inline);
+    }
+
+    let mut event = serde_json::Map::new();
+    let mut merge = |layer: Value, source: &str| match layer {
+        Value::Object(layer) => {
+            event.extend(layer);
+            Ok(())
+        }
+        _ => Err(crate::BeemFlowError::validation(format!(
+            "{} must be an object",
+            source
+        ))),
+    };
+    if let Some(path) = file {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            crate::BeemFlowError::validation(format!("Failed to read --input-file {}: {}", path, e))
+        })?;
+        // YAML is a superset of JSON, so one parser reads both
+        let layer: Value = serde_yaml::from_str(&content).map_err(|e| {
+            crate::BeemFlowError::validation(format!(
+                "--input-file {} is not valid JSON or YAML: {}",
+                path, e
+            ))
+        })?;
+        merge(layer, path)?;
+    }
+    if let Some(layer) = inline {
+        merge(layer, "The inline event")?;
+    }
+    for field in fields {
+        let (key, value) = field.split_once('=').ok_or_else(|| {
+            crate::BeemFlowError::validation(format!("--field {} must be KEY=VALUE", field))
+        })?;
+        let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.into()));
+        event.insert(key.to_string(), value);
+    }
+    Ok(Some(Value::Object(event)))
+}
+
 // ============================================================================
 // Special Commands (not operations)
 // ============================================================================
@@ -503,6 +1216,10 @@ async fn handle_serve_command(matches: &ArgMatches) -> Result<()> {
             enable_oauth_server: false,
             oauth_issuer,
             public_url,
+            request_timeout_secs: crate::config::default_request_timeout_secs(),
+            mcp_bind: None,
+            metrics_bind: None,
+            reload_templates: false,
         });
     }
 
@@ -517,9 +1234,15 @@ async fn handle_serve_command(matches: &ArgMatches) -> Result<()> {
     if interfaces.oauth_server {
         println!("   ✓ OAuth authorization server enabled");
     }
+    let reload = matches
+        .get_flag("reload")
+        .then(|| std::path::PathBuf::from(crate::constants::CONFIG_FILE_NAME));
+    if reload.is_some() {
+        println!("   ✓ Reloading on config and flow changes");
+    }
     println!("   Press Ctrl+C to stop\n");
 
-    crate::http::start_server(config, interfaces).await?;
+    crate::http::start_server(config, interfaces, reload).await?;
 
     Ok(())
 }
@@ -533,7 +1256,7 @@ async fn serve_mcp_stdio() -> Result<()> {
 
     let config = Config::load_and_inject(crate::constants::CONFIG_FILE_NAME)?;
     let deps = crate::core::create_dependencies(&config).await?;
-    let registry = std::sync::Arc::new(OperationRegistry::new(deps));
+    let registry = Arc::new(OperationRegistry::new(deps));
     let mcp_server = crate::mcp::McpServer::new(registry);
 
     mcp_server.serve_stdio().await
@@ -545,6 +1268,41 @@ async fn run_cron_check() -> Result<()> {
     Ok(())
 }
 
+/// Follow a run until it ends (`runs tail`, special command - not an operation)
+async fn tail_run(registry: &OperationRegistry, matches: &ArgMatches) -> Result<()> {
+    let Some(("tail", sub)) = matches.subcommand() else {
+        return Ok(());
+    };
+    let run_id: uuid::Uuid = sub
+        .get_one::<String>("run_id")
+        .unwrap()
+        .parse()
+        .map_err(|e| crate::BeemFlowError::validation(format!("Invalid run ID: {}", e)))?;
+    let interval = *sub.get_one::<f64>("interval").unwrap();
+    if !(interval > 0.0 && interval.is_finite()) {
+        return Err(crate::BeemFlowError::validation(
+            "--interval must be a positive number of seconds",
+        ));
+    }
+
+    let deps = registry.get_dependencies();
+    // Events only help if the run's process publishes them where we listen
+    let event_bus = deps.engine.event_bus();
+    let status = crate::core::tail::tail_run(
+        deps.storage.as_ref(),
+        event_bus.is_shared().then_some(event_bus),
+        run_id,
+        std::time::Duration::from_secs_f64(interval),
+        &mut tokio::io::stdout(),
+    )
+    .await?;
+
+    if status == crate::model::RunStatus::Failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 /// Handle OAuth commands (special command - not an operation)
 async fn handle_oauth_command(matches: &ArgMatches) -> Result<()> {
     let config = Config::load_and_inject(crate::constants::CONFIG_FILE_NAME)?;
@@ -555,6 +1313,7 @@ async fn handle_oauth_command(matches: &ArgMatches) -> Result<()> {
             let name = sub.get_one::<String>("name").unwrap();
             let grant_types = parse_comma_list(sub, "grant-types");
             let scopes = parse_comma_list(sub, "scopes");
+            let tenant_id = sub.get_one::<String>("tenant").cloned();
             let json = sub.get_flag("json");
 
             let client_id = format!(
@@ -576,6 +1335,7 @@ async fn handle_oauth_command(matches: &ArgMatches) -> Result<()> {
                 logo_uri: None,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
+                tenant_id,
             };
 
             storage.save_oauth_client(&client).await?;
@@ -613,7 +1373,117 @@ async fn handle_oauth_command(matches: &ArgMatches) -> Result<()> {
             storage.delete_oauth_client(client_id).await?;
             println!("✅ Client '{}' revoked", client_id);
         }
+        Some(("prune-credentials", sub)) => {
+            let removed = storage.delete_expired_oauth_credentials(Utc::now()).await?;
+
+            if sub.get_flag("json") {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({ "removed": removed }))?
+                );
+            } else {
+                println!("✅ Pruned {} expired credential(s)", removed);
+            }
+        }
+        Some(("token", sub)) => {
+            let provider = sub.get_one::<String>("provider").unwrap();
+            let integration = sub.get_one::<String>("integration").unwrap();
+            let registry_manager = Arc::new(RegistryManager::standard(
+                Some(&config),
+                config.create_secrets_provider(),
+            ));
+            let client = crate::auth::OAuthClientManager::new(
+                storage.clone(),
+                registry_manager,
+                config.oauth_redirect_uri(),
+            )?;
+
+            let token = current_oauth_token(&client, &storage, provider, integration).await?;
+            let access_token = if sub.get_flag("reveal") {
+                token.access_token.clone()
+            } else {
+                mask_token(&token.access_token)
+            };
+
+            if sub.get_flag("json") {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "provider": provider,
+                        "integration": integration,
+                        "access_token": access_token,
+                        "expires_at": token.expires_at,
+                    }))?
+                );
+            } else {
+                println!("Provider:    {}", provider);
+                println!("Integration: {}", integration);
+                println!("Token:       {}", access_token);
+                match token.expires_at {
+                    Some(expires_at) => println!(
+                        "Expires:     {} (in {}m)",
+                        expires_at.to_rfc3339(),
+                        (expires_at - Utc::now()).num_minutes()
+                    ),
+                    None => println!("Expires:     never"),
+                }
+            }
+        }
         _ => {}
     }
     Ok(())
 }
+
+/// Access token of a stored credential and when it expires
+#[derive(Debug)]
+struct CurrentToken {
+    access_token: String,
+    expires_at: Option<chrono::DateTime<Utc>>,
+}
+
+/// Current access token of a credential, refreshed first if it's about to expire
+///
+/// Fails when there is no credential, or when its token has expired and can't
+/// be refreshed (where `get_token` would hand out the stale token).
+async fn current_oauth_token(
+    client: &crate::auth::OAuthClientManager,
+    storage: &Arc<dyn crate::storage::Storage>,
+    provider: &str,
+    integration: &str,
+) -> Result<CurrentToken> {
+    let access_token = client.get_token(provider, integration).await?;
+    let expires_at = storage
+        .get_oauth_credential(provider, integration)
+        .await?
+        .and_then(|cred| cred.expires_at);
+
+    if let Some(expires_at) = expires_at
+        && expires_at <= Utc::now()
+    {
+        return Err(crate::BeemFlowError::OAuth(format!(
+            "Access token for {}:{} expired at {} and could not be refreshed; reconnect the integration",
+            provider,
+            integration,
+            expires_at.to_rfc3339()
+        )));
+    }
+
+    Ok(CurrentToken {
+        access_token,
+        expires_at,
+    })
+}
+
+/// Mask all but the ends of a token, e.g. `ghp_…3f9a`
+fn mask_token(token: &str) -> String {
+    let chars: Vec<char> = token.chars().collect();
+    if chars.len() < 16 {
+        return crate::secrets::REDACTED.to_string();
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+#[cfg(test)]
+mod cli_test;
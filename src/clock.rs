@@ -0,0 +1,54 @@
+//! Time source of the engine
+//!
+//! The engine reads the time through a [`Clock`] rather than calling
+//! `Utc::now()` directly, so logic that depends on it (deterministic run ID
+//! windows, approval expiry) can be driven exactly in tests with a
+//! [`MockClock`]. Production uses [`SystemClock`].
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+
+/// Source of the current time
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system's wall clock
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock that stands still until it is set or advanced
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// Move the clock to `now`, which may be in its past
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock() = now;
+    }
+
+    /// Move the clock forward by `by`
+    pub fn advance(&self, by: chrono::Duration) {
+        *self.now.lock() += by;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock()
+    }
+}
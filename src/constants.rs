@@ -89,6 +89,15 @@ pub const ENV_SMITHERY_KEY: &str = "SMITHERY_API_KEY";
 /// Environment variable: Registry path
 pub const ENV_REGISTRY_PATH: &str = "BEEMFLOW_REGISTRY";
 
+/// Environment variable: key used to sign approval tokens
+pub const ENV_APPROVAL_SECRET: &str = "BEEMFLOW_APPROVAL_SECRET";
+
+/// Environment variable: key used to sign resume tokens handed to third parties
+pub const ENV_RESUME_SECRET: &str = "BEEMFLOW_RESUME_SECRET";
+
+/// Environment variable: key used to encrypt queued outbound deliveries
+pub const ENV_DELIVERY_SECRET: &str = "BEEMFLOW_DELIVERY_SECRET";
+
 // ============================================================================
 // ADAPTERS & TOOLS
 // ============================================================================
@@ -129,6 +138,34 @@ pub const CORE_LOG: &str = "core.log";
 /// Core tool: convert OpenAPI
 pub const CORE_CONVERT_OPENAPI: &str = "core.convert_openapi";
 
+/// Core tool: publish an event through the outbox
+pub const CORE_PUBLISH: &str = "core.publish";
+
+/// Core tool: human approval gate (handled by the executor, not the adapter)
+pub const CORE_APPROVAL: &str = "core.approval";
+
+/// Core tool: call a tool until its response matches (handled by the executor)
+pub const CORE_POLL: &str = "core.poll";
+
+/// Core tool: generate a resume token for a later `await_event`, with a signed copy
+pub const CORE_RESUME_TOKEN: &str = "core.resume_token";
+
+/// Core tool: send an HTTP request, retrying it in the background if it fails
+pub const CORE_DELIVER: &str = "core.deliver";
+
+/// Every built-in `core.*` tool
+pub const CORE_TOOLS: &[&str] = &[
+    CORE_ECHO,
+    CORE_WAIT,
+    CORE_LOG,
+    CORE_CONVERT_OPENAPI,
+    CORE_PUBLISH,
+    CORE_APPROVAL,
+    CORE_POLL,
+    CORE_RESUME_TOKEN,
+    CORE_DELIVER,
+];
+
 // ============================================================================
 // CLI COMMANDS & DESCRIPTIONS
 // ============================================================================
@@ -281,6 +318,9 @@ pub const CONTENT_TYPE_YAML: &str = "application/x-yaml";
 /// Content type: form
 pub const CONTENT_TYPE_FORM: &str = "application/x-www-form-urlencoded";
 
+/// Content type: server-sent events
+pub const CONTENT_TYPE_EVENT_STREAM: &str = "text/event-stream";
+
 /// HTTP status message: OK
 pub const HTTP_STATUS_OK: &str = "OK";
 
@@ -330,6 +370,9 @@ pub const MCP_PARAM_BASE_URL: &str = "base_url";
 /// Default HTTP port
 pub const DEFAULT_HTTP_PORT: u16 = 3330;
 
+/// Default limit on how long an HTTP operation request may take (seconds)
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 60;
+
 /// Default MCP port (over HTTP)
 pub const DEFAULT_MCP_PORT: u16 = 3331;
 
@@ -370,6 +413,12 @@ pub const TEMPLATE_FIELD_STEPS: &str = "steps";
 /// Template field: env
 pub const TEMPLATE_FIELD_ENV: &str = "env";
 
+/// Template field: loop (current foreach iteration)
+pub const TEMPLATE_FIELD_LOOP: &str = "loop";
+
+/// Output field: aggregated result of a parallel or foreach block
+pub const BLOCK_OUTPUT_FIELD: &str = "output";
+
 /// Error: await event pause
 pub const ERR_AWAIT_EVENT_PAUSE: &str = "step is waiting for event";
 
@@ -421,6 +470,33 @@ pub const MATCH_KEY_TOKEN: &str = "token";
 /// Event topic: resume prefix
 pub const EVENT_TOPIC_RESUME_PREFIX: &str = "resume.";
 
+/// Event topic: a run finished successfully
+pub const EVENT_TOPIC_RUN_SUCCEEDED: &str = "run.succeeded";
+
+/// Event topic: a run failed
+pub const EVENT_TOPIC_RUN_FAILED: &str = "run.failed";
+
+/// Event topic: a top-level step started, succeeded or failed
+pub const EVENT_TOPIC_STEP_STATUS: &str = "step.status";
+
+/// Event topic prefix: probe events published by `system check`
+pub const EVENT_TOPIC_SYSTEM_CHECK: &str = "system.check";
+
+/// Events the in-process bus holds for subscribers that fall behind
+pub const DEFAULT_EVENT_BUFFER_SIZE: usize = 100;
+
+/// Runs that may wait for an execution slot before new ones are rejected
+pub const DEFAULT_RUN_QUEUE_SIZE: usize = 1000;
+
+/// Flow runs webhooks may be starting at once before further starts wait
+pub const DEFAULT_WEBHOOK_CONCURRENT_STARTS: usize = 32;
+
+/// Accepted webhooks that may be dispatching at once before new ones are refused
+pub const DEFAULT_WEBHOOK_PENDING_DISPATCHES: usize = 256;
+
+/// Seconds a refused webhook sender is told to wait before retrying
+pub const WEBHOOK_RETRY_AFTER_SECS: u64 = 5;
+
 /// Adapter ID: MCP
 pub const ADAPTER_ID_MCP: &str = "mcp";
 
@@ -0,0 +1,110 @@
+//! Event operations module
+//!
+//! Operations for injecting events into BeemFlow by hand. Published events
+//! carry a `manual` source and the calling request's correlation ID, which the
+//! flows they trigger inherit.
+
+use super::*;
+use crate::model::FlowName;
+use beemflow_core_macros::{operation, operation_group};
+use schemars::JsonSchema;
+
+#[operation_group(events)]
+pub mod events {
+    use super::*;
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for publishing an event to a topic")]
+    pub struct PublishInput {
+        #[schemars(description = "Event topic (e.g. 'slack.message.received')")]
+        pub topic: String,
+        #[schemars(description = "Event payload passed to triggered flows")]
+        pub payload: Option<HashMap<String, Value>>,
+    }
+
+    #[derive(Serialize)]
+    pub struct TriggeredFlow {
+        pub flow_name: FlowName,
+        pub run_id: String,
+    }
+
+    #[derive(Serialize)]
+    pub struct PublishOutput {
+        pub topic: String,
+        pub triggered: Vec<TriggeredFlow>,
+    }
+
+    /// Publish an event to a topic
+    #[operation(
+        name = "publish_event",
+        input = PublishInput,
+        http = "POST /events/{topic}",
+        cli = "events publish <TOPIC>",
+        description = "Publish an event to a topic, triggering subscribed flows"
+    )]
+    pub struct Publish {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for Publish {
+        type Input = PublishInput;
+        type Output = PublishOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            if input.topic.trim().is_empty() {
+                return Err(BeemFlowError::validation("Event topic cannot be empty"));
+            }
+
+            let flow_names = self
+                .deps
+                .storage
+                .find_flow_names_by_topic(&input.topic)
+                .await?;
+
+            if flow_names.is_empty() {
+                tracing::debug!("No flows registered for topic: {}", input.topic);
+            }
+
+            let payload = input.payload.unwrap_or_default();
+            self.deps
+                .engine
+                .event_bus()
+                .publish(crate::event::EventEnvelope::new(
+                    &input.topic,
+                    serde_json::to_value(&payload)?,
+                ))
+                .await?;
+
+            let mut triggered = Vec::new();
+
+            // Same code path as webhooks: deployed flows only, failures don't block other flows
+            for flow_name in flow_names {
+                match self
+                    .deps
+                    .engine
+                    .start(&flow_name, payload.clone(), false)
+                    .await
+                {
+                    Ok(result) => triggered.push(TriggeredFlow {
+                        flow_name,
+                        run_id: result.run_id.to_string(),
+                    }),
+                    Err(e) => {
+                        tracing::error!(
+                            "Failed to trigger flow '{}' for topic '{}': {}",
+                            flow_name,
+                            input.topic,
+                            e
+                        );
+                    }
+                }
+            }
+
+            Ok(PublishOutput {
+                topic: input.topic,
+                triggered,
+            })
+        }
+    }
+}
@@ -0,0 +1,434 @@
+//! Flat exports of run history (`runs export-data`)
+//!
+//! Runs are read a page at a time with cursor pagination and each page is
+//! written out before the next is fetched, so an export of any size runs in
+//! constant memory. Rows are flat for loading into a warehouse: one per run,
+//! and optionally one per step record, as CSV or JSON lines.
+//!
+//! Step tools and run triggers are not recorded on runs; they are taken from
+//! the flow's deployed definition and left empty when it has none. Only the
+//! current tenant's runs are exported (see [`crate::auth::tenant`]).
+
+use crate::Result;
+use crate::model::{Flow, Run, RunStatus, StepRun, StepStatus};
+use crate::storage::{PageCursor, RunFilter, Storage};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use uuid::Uuid;
+
+/// Runs fetched per page
+const PAGE_SIZE: usize = 500;
+
+/// Longest error summary exported for a run
+const MAX_ERROR_SUMMARY: usize = 200;
+
+/// Row format of an export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+impl ExportFormat {
+    /// MIME type of an export in this format
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Jsonl => "application/x-ndjson",
+        }
+    }
+
+    /// File extension of an export in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// What to export
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Only runs started at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Also export a row per step record
+    pub include_steps: bool,
+}
+
+/// Number of rows an export wrote
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ExportCounts {
+    pub runs: u64,
+    pub steps: u64,
+}
+
+/// A row of one of the export's tables
+trait ExportRow: Serialize {
+    /// Column names, in order
+    const COLUMNS: &'static [&'static str];
+}
+
+/// One run
+#[derive(Debug, Serialize)]
+struct RunRow {
+    run_id: Uuid,
+    flow: String,
+    status: RunStatus,
+    started: DateTime<Utc>,
+    ended: Option<DateTime<Utc>>,
+    duration_ms: Option<i64>,
+    trigger: String,
+    error: Option<String>,
+}
+
+impl ExportRow for RunRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "run_id",
+        "flow",
+        "status",
+        "started",
+        "ended",
+        "duration_ms",
+        "trigger",
+        "error",
+    ];
+}
+
+/// One step record; a step re-run later gets a row per attempt
+#[derive(Debug, Serialize)]
+struct StepRow {
+    run_id: Uuid,
+    step: String,
+    tool: String,
+    status: StepStatus,
+    duration_ms: Option<i64>,
+    attempt: u32,
+}
+
+impl ExportRow for StepRow {
+    const COLUMNS: &'static [&'static str] =
+        &["run_id", "step", "tool", "status", "duration_ms", "attempt"];
+}
+
+/// Write an export of the runs in storage
+///
+/// Run rows go to `runs_out`. With `include_steps`, step rows go to `steps_out`
+/// when given, or else follow the runs in `runs_out` as a second section (after
+/// a blank line and their own header, for CSV). Runs started after the export
+/// began are left out, so both tables describe the same runs.
+pub async fn write_export<W: AsyncWrite + Unpin>(
+    storage: &dyn Storage,
+    options: &ExportOptions,
+    runs_out: &mut W,
+    mut steps_out: Option<&mut W>,
+) -> Result<ExportCounts> {
+    let until = Utc::now();
+    let mut flows = FlowDefinitions::default();
+    let mut counts = ExportCounts::default();
+    let steps_to_file = options.include_steps && steps_out.is_some();
+
+    write_chunk(runs_out, header::<RunRow>(options.format)).await?;
+    if steps_to_file && let Some(out) = steps_out.as_deref_mut() {
+        write_chunk(out, header::<StepRow>(options.format)).await?;
+    }
+
+    let mut pages = RunPages::new(options.since, until);
+    while let Some(runs) = pages.next(storage).await? {
+        let mut run_rows = String::new();
+        let mut step_rows = String::new();
+        for run in &runs {
+            let flow = flows.get(storage, &run.flow_name).await;
+            let steps = if steps_to_file || run.status == RunStatus::Failed {
+                storage.get_steps(run.id).await?
+            } else {
+                Vec::new()
+            };
+            run_rows.push_str(&format_row(options.format, &run_row(run, &steps, flow)));
+            counts.runs += 1;
+            if steps_to_file {
+                for row in step_rows_for(run.id, &steps, flow) {
+                    step_rows.push_str(&format_row(options.format, &row));
+                    counts.steps += 1;
+                }
+            }
+        }
+        write_chunk(runs_out, run_rows).await?;
+        if let Some(out) = steps_out.as_deref_mut().filter(|_| steps_to_file) {
+            write_chunk(out, step_rows).await?;
+        }
+    }
+
+    // Steps section: a second pass over the same runs
+    if options.include_steps && !steps_to_file {
+        let mut section = String::new();
+        if options.format == ExportFormat::Csv {
+            section.push_str("\r\n");
+        }
+        section.push_str(&header::<StepRow>(options.format));
+        write_chunk(runs_out, section).await?;
+
+        let mut pages = RunPages::new(options.since, until);
+        while let Some(runs) = pages.next(storage).await? {
+            let mut step_rows = String::new();
+            for run in &runs {
+                let flow = flows.get(storage, &run.flow_name).await;
+                let steps = storage.get_steps(run.id).await?;
+                for row in step_rows_for(run.id, &steps, flow) {
+                    step_rows.push_str(&format_row(options.format, &row));
+                    counts.steps += 1;
+                }
+            }
+            write_chunk(runs_out, step_rows).await?;
+        }
+    }
+
+    runs_out.flush().await?;
+    if let Some(out) = steps_out {
+        out.flush().await?;
+    }
+    Ok(counts)
+}
+
+/// Runs started within `[since, until]`, newest first, a page at a time
+struct RunPages {
+    since: Option<DateTime<Utc>>,
+    until: DateTime<Utc>,
+    after: Option<PageCursor<Uuid>>,
+    done: bool,
+}
+
+impl RunPages {
+    fn new(since: Option<DateTime<Utc>>, until: DateTime<Utc>) -> Self {
+        Self {
+            since,
+            until,
+            after: None,
+            done: false,
+        }
+    }
+
+    async fn next(&mut self, storage: &dyn Storage) -> Result<Option<Vec<Run>>> {
+        if self.done {
+            return Ok(None);
+        }
+        let filter = RunFilter {
+            tenant_id: crate::auth::current_tenant(),
+            ..Default::default()
+        };
+        let runs = storage
+            .list_runs_after(&filter, PAGE_SIZE, self.after.as_ref())
+            .await?;
+        self.done = runs.len() < PAGE_SIZE;
+        self.after = runs.last().map(|run| PageCursor {
+            at: run.started_at,
+            key: run.id,
+        });
+
+        let mut page = Vec::with_capacity(runs.len());
+        for run in runs {
+            if self.since.is_some_and(|since| run.started_at < since) {
+                self.done = true;
+                break;
+            }
+            if run.started_at <= self.until {
+                page.push(run);
+            }
+        }
+        Ok(Some(page))
+    }
+}
+
+/// Trigger and step tools of each flow's deployed definition, loaded on first use
+#[derive(Default)]
+struct FlowDefinitions {
+    flows: HashMap<String, Option<FlowInfo>>,
+}
+
+struct FlowInfo {
+    trigger: String,
+    tools: HashMap<String, String>,
+}
+
+impl FlowDefinitions {
+    async fn get(
+        &mut self,
+        storage: &dyn Storage,
+        name: &crate::model::FlowName,
+    ) -> Option<&FlowInfo> {
+        if !self.flows.contains_key(name.as_str()) {
+            let info = Self::load(storage, name).await;
+            self.flows.insert(name.to_string(), info);
+        }
+        self.flows.get(name.as_str()).and_then(Option::as_ref)
+    }
+
+    async fn load(storage: &dyn Storage, name: &crate::model::FlowName) -> Option<FlowInfo> {
+        let version = storage.get_deployed_version(name).await.ok()??;
+        let content = storage
+            .get_flow_version_content(name, &version)
+            .await
+            .ok()??;
+        let flow = crate::dsl::parse_string(&content, None).ok()?;
+        Some(FlowInfo {
+            trigger: trigger_names(&flow).join(" "),
+            tools: step_tools(&flow),
+        })
+    }
+}
+
+/// Event names a flow is triggered by
+fn trigger_names(flow: &Flow) -> Vec<String> {
+    let Some(on) = flow
+        .on
+        .as_ref()
+        .and_then(|on| serde_json::to_value(on).ok())
+    else {
+        return Vec::new();
+    };
+    let name = |value: &Value| {
+        value
+            .as_str()
+            .or_else(|| value.get("event").and_then(Value::as_str))
+            .map(str::to_string)
+    };
+    match &on {
+        Value::Array(values) => values.iter().filter_map(name).collect(),
+        value => name(value).into_iter().collect(),
+    }
+}
+
+/// Tool of every step in a flow, nested steps and catch steps included
+fn step_tools(flow: &Flow) -> HashMap<String, String> {
+    fn collect(steps: &[crate::model::Step], tools: &mut HashMap<String, String>) {
+        for step in steps {
+            if let Some(tool) = &step.use_ {
+                tools.insert(step.id.to_string(), tool.clone());
+            }
+            for nested in [&step.steps, &step.do_].into_iter().flatten() {
+                collect(nested, tools);
+            }
+        }
+    }
+
+    let mut tools = HashMap::new();
+    collect(&flow.steps, &mut tools);
+    for hook in [&flow.catch, &flow.on_success, &flow.on_failure]
+        .into_iter()
+        .flatten()
+    {
+        collect(hook, &mut tools);
+    }
+    tools
+}
+
+fn run_row(run: &Run, steps: &[StepRun], flow: Option<&FlowInfo>) -> RunRow {
+    RunRow {
+        run_id: run.id,
+        flow: run.flow_name.to_string(),
+        status: run.status,
+        started: run.started_at,
+        ended: run.ended_at,
+        duration_ms: run
+            .ended_at
+            .map(|ended| (ended - run.started_at).num_milliseconds()),
+        trigger: flow.map(|f| f.trigger.clone()).unwrap_or_default(),
+        error: error_summary(steps),
+    }
+}
+
+/// First line of the first failed step's error, prefixed with the step
+fn error_summary(steps: &[StepRun]) -> Option<String> {
+    let step = steps
+        .iter()
+        .filter(|step| step.status == StepStatus::Failed)
+        .min_by_key(|step| step.started_at)?;
+    let error = step.error.as_deref().unwrap_or("failed");
+    let summary = format!(
+        "{}: {}",
+        step.step_name,
+        error.lines().next().unwrap_or_default()
+    );
+    Some(match summary.char_indices().nth(MAX_ERROR_SUMMARY) {
+        Some((end, _)) => format!("{}...", &summary[..end]),
+        None => summary,
+    })
+}
+
+fn step_rows_for(run_id: Uuid, steps: &[StepRun], flow: Option<&FlowInfo>) -> Vec<StepRow> {
+    let mut steps: Vec<&StepRun> = steps.iter().collect();
+    steps.sort_by_key(|step| step.started_at);
+
+    let mut attempts: HashMap<&str, u32> = HashMap::new();
+    steps
+        .into_iter()
+        .map(|step| {
+            let attempt = attempts.entry(step.step_name.as_str()).or_default();
+            *attempt += 1;
+            StepRow {
+                run_id,
+                step: step.step_name.to_string(),
+                tool: flow
+                    .and_then(|f| f.tools.get(step.step_name.as_str()))
+                    .cloned()
+                    .unwrap_or_default(),
+                status: step.status,
+                duration_ms: step
+                    .ended_at
+                    .map(|ended| (ended - step.started_at).num_milliseconds()),
+                attempt: *attempt,
+            }
+        })
+        .collect()
+}
+
+/// Header of a table; JSON lines have none
+fn header<R: ExportRow>(format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => format!("{}\r\n", R::COLUMNS.join(",")),
+        ExportFormat::Jsonl => String::new(),
+    }
+}
+
+fn format_row<R: ExportRow>(format: ExportFormat, row: &R) -> String {
+    let value = serde_json::to_value(row).unwrap_or_default();
+    match format {
+        ExportFormat::Jsonl => format!("{}\n", value),
+        ExportFormat::Csv => {
+            let cells: Vec<String> = R::COLUMNS
+                .iter()
+                .map(|column| match &value[*column] {
+                    Value::Null => String::new(),
+                    Value::String(s) => csv_escape(s),
+                    other => csv_escape(&other.to_string()),
+                })
+                .collect();
+            format!("{}\r\n", cells.join(","))
+        }
+    }
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break (RFC 4180)
+pub(crate) fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+async fn write_chunk<W: AsyncWrite + Unpin>(out: &mut W, chunk: String) -> Result<()> {
+    if !chunk.is_empty() {
+        out.write_all(chunk.as_bytes()).await?;
+    }
+    Ok(())
+}
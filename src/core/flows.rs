@@ -3,9 +3,14 @@
 //! All operations for managing workflow definitions.
 
 use super::*;
-use crate::dsl::{Validator, parse_file, parse_string};
+use crate::dsl::{FlowFormat, Validator, parse_file, patch, serialize_flow};
+use crate::model::{Flow, FlowName, Step};
+use crate::registry::RegistryEntry;
+use crate::storage::{FlowFilter, FlowSummary};
+use crate::utils::mask_secrets;
 use beemflow_core_macros::{operation, operation_group};
 use schemars::JsonSchema;
+use std::collections::BTreeMap;
 
 #[operation_group(flows)]
 pub mod flows {
@@ -20,27 +25,125 @@ pub mod flows {
     #[schemars(description = "Input for retrieving a flow by name")]
     pub struct GetInput {
         #[schemars(description = "Name of the flow to retrieve")]
-        pub name: String,
+        pub name: FlowName,
+        #[serde(default)]
+        #[schemars(
+            description = "Re-serialize the flow as 'json' or 'yaml' (default: stored content as-is)"
+        )]
+        pub format: Option<FlowFormat>,
     }
 
     #[derive(Serialize)]
     pub struct GetOutput {
-        pub name: String,
+        pub name: FlowName,
         pub content: String,
         pub version: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub description: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub owner: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub team: Option<String>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pub tags: Vec<String>,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for listing flows, optionally filtered by ownership metadata")]
+    pub struct ListInput {
+        #[serde(default)]
+        #[schemars(description = "Only list flows carrying this tag")]
+        pub tag: Option<String>,
+        #[serde(default)]
+        #[schemars(description = "Only list flows owned by this owner")]
+        pub owner: Option<String>,
+        #[serde(default)]
+        #[schemars(description = "Only list flows belonging to this team")]
+        pub team: Option<String>,
     }
 
     #[derive(Serialize)]
     pub struct ListOutput {
-        pub flows: Vec<String>,
+        pub flows: Vec<ListedFlow>,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for searching deployed flows")]
+    pub struct SearchInput {
+        #[schemars(
+            description = "Words to find; a flow matches when each occurs in its name, description, tags, step ids, tools or literal step inputs"
+        )]
+        pub q: String,
+        #[schemars(description = "Maximum number of flows to return (default: 20, max: 1000)")]
+        pub limit: Option<usize>,
+    }
+
+    #[derive(Serialize)]
+    pub struct SearchOutput {
+        pub results: Vec<crate::storage::search::FlowSearchResult>,
+    }
+
+    /// A listed flow with how its draft compares to the deployed version
+    #[derive(Serialize)]
+    pub struct ListedFlow {
+        #[serde(flatten)]
+        pub summary: FlowSummary,
+        pub status: DriftStatus,
+    }
+
+    /// How a flow's draft file compares to its deployed version
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum DriftStatus {
+        /// The draft file matches the deployed version
+        InSync,
+        /// The draft file differs from the deployed version
+        DraftAhead,
+        /// Deployed, but there is no draft file
+        DeployedOnly,
+        /// A draft file that was never deployed
+        FileOnly,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for comparing draft flow files with their deployed versions")]
+    pub struct StatusInput {
+        #[serde(default)]
+        #[schemars(
+            description = "Flow to check (default: every flow with a file or a deployment)"
+        )]
+        pub name: Option<FlowName>,
+        #[serde(default)]
+        #[schemars(
+            description = "Fail if any checked flow is not in sync with its deployed version"
+        )]
+        pub fail_on_drift: Option<bool>,
+    }
+
+    #[derive(Serialize)]
+    pub struct StatusOutput {
+        pub flows: Vec<FlowStatus>,
+    }
+
+    /// Drift of one flow, with a SHA-256 of each side's content
+    #[derive(Debug, Serialize)]
+    pub struct FlowStatus {
+        pub name: String,
+        pub status: DriftStatus,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub deployed_version: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub file_hash: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub deployed_hash: Option<String>,
     }
 
     #[derive(Deserialize, JsonSchema)]
     #[schemars(description = "Input for saving or updating a flow definition")]
     pub struct SaveInput {
         #[schemars(description = "Name of the flow (optional, can be inferred from content)")]
-        pub name: Option<String>,
-        #[schemars(description = "YAML content of the flow definition")]
+        pub name: Option<FlowName>,
+        #[schemars(description = "YAML or JSON content of the flow definition")]
         pub content: String,
         /// Path to flow file (CLI only)
         #[serde(default)]
@@ -51,7 +154,7 @@ pub mod flows {
     #[derive(Serialize)]
     pub struct SaveOutput {
         pub status: String,
-        pub name: String,
+        pub name: FlowName,
         pub version: String,
     }
 
@@ -59,25 +162,72 @@ pub mod flows {
     #[schemars(description = "Input for deleting a flow definition")]
     pub struct DeleteInput {
         #[schemars(description = "Name of the flow to delete")]
-        pub name: String,
+        pub name: FlowName,
     }
 
     #[derive(Serialize)]
     pub struct DeleteOutput {
         pub status: String,
-        pub name: String,
+        pub name: FlowName,
     }
 
     #[derive(Deserialize, JsonSchema)]
     #[schemars(description = "Input for deploying a flow to production")]
     pub struct DeployInput {
         #[schemars(description = "Name of the flow to deploy")]
-        pub name: String,
+        pub name: FlowName,
+        #[schemars(
+            description = "Environment the deployment targets; must be defined by the flow"
+        )]
+        pub environment: Option<String>,
+        #[schemars(description = "Deploy even if some tools the flow uses cannot be resolved yet")]
+        pub skip_tool_check: Option<bool>,
+        #[schemars(
+            description = "Validate the draft and report whether it would deploy a new version or is unchanged, without deploying"
+        )]
+        pub dry_run: Option<bool>,
     }
 
     #[derive(Serialize)]
     pub struct DeployOutput {
-        pub flow: String,
+        pub flow: FlowName,
+        pub version: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub environment: Option<String>,
+        /// `deployed`, or for dry runs `would_deploy` or `unchanged`
+        pub status: String,
+        pub message: String,
+        pub dry_run: bool,
+        /// Version deployed before this deploy (dry runs only)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub deployed_version: Option<String>,
+        /// SHA-256 of the draft content (dry runs only)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub file_hash: Option<String>,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for patching the deployed version of a flow")]
+    pub struct UpdateInput {
+        #[schemars(description = "Name of the flow to update")]
+        pub name: FlowName,
+        #[schemars(
+            description = "JSON Patch operations (RFC 6902 array) or a merge patch (RFC 7396 object) applied to the deployed flow",
+            schema_with = "patch_schema"
+        )]
+        pub patch: Value,
+        #[schemars(description = "Deploy even if some tools the flow uses cannot be resolved yet")]
+        pub skip_tool_check: Option<bool>,
+    }
+
+    fn patch_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({"type": ["array", "object"]})
+    }
+
+    #[derive(Serialize)]
+    pub struct UpdateOutput {
+        pub flow: FlowName,
+        pub from_version: String,
         pub version: String,
         pub status: String,
         pub message: String,
@@ -87,14 +237,14 @@ pub mod flows {
     #[schemars(description = "Input for rolling back a flow to a specific version")]
     pub struct RollbackInput {
         #[schemars(description = "Name of the flow")]
-        pub name: String,
+        pub name: FlowName,
         #[schemars(description = "Version to rollback to")]
         pub version: String,
     }
 
     #[derive(Serialize)]
     pub struct RollbackOutput {
-        pub flow: String,
+        pub flow: FlowName,
         pub from_version: Option<String>,
         pub to_version: String,
         pub status: String,
@@ -105,12 +255,12 @@ pub mod flows {
     #[schemars(description = "Input for disabling a flow")]
     pub struct DisableInput {
         #[schemars(description = "Name of the flow to disable")]
-        pub name: String,
+        pub name: FlowName,
     }
 
     #[derive(Serialize)]
     pub struct DisableOutput {
-        pub flow_name: String,
+        pub flow_name: FlowName,
         pub version: String,
         pub message: String,
     }
@@ -119,12 +269,12 @@ pub mod flows {
     #[schemars(description = "Input for enabling a flow")]
     pub struct EnableInput {
         #[schemars(description = "Name of the flow to enable")]
-        pub name: String,
+        pub name: FlowName,
     }
 
     #[derive(Serialize)]
     pub struct EnableOutput {
-        pub flow_name: String,
+        pub flow_name: FlowName,
         pub version: String,
         pub message: String,
     }
@@ -133,7 +283,7 @@ pub mod flows {
     #[schemars(description = "Input for restoring a flow from deployment history")]
     pub struct RestoreInput {
         #[schemars(description = "Name of the flow to restore")]
-        pub name: String,
+        pub name: FlowName,
         #[schemars(
             description = "Specific version to restore (defaults to currently deployed or latest)"
         )]
@@ -142,7 +292,7 @@ pub mod flows {
 
     #[derive(Serialize)]
     pub struct RestoreOutput {
-        pub name: String,
+        pub name: FlowName,
         pub version: String,
         pub status: String,
         pub message: String,
@@ -152,18 +302,29 @@ pub mod flows {
     #[schemars(description = "Input for retrieving flow version history")]
     pub struct HistoryInput {
         #[schemars(description = "Name of the flow")]
-        pub name: String,
+        pub name: FlowName,
+        #[schemars(
+            description = "Maximum number of versions to return (default: 100, max: 10000)"
+        )]
+        pub limit: Option<usize>,
+        #[schemars(description = "next_cursor of the previous page; omit for the first page")]
+        pub cursor: Option<String>,
     }
 
     #[derive(Deserialize, JsonSchema)]
     #[schemars(description = "Input for validating a flow")]
     pub struct ValidateInput {
         #[schemars(description = "Name of the flow to load from storage")]
-        pub name: String,
+        pub name: FlowName,
         /// Path to flow file (CLI only)
         #[serde(default)]
         #[schemars(description = "Path to flow file (CLI only)")]
         pub file: Option<String>,
+        #[serde(default)]
+        #[schemars(
+            description = "Also fail on tools and $oauth providers that the registry, adapters, MCP servers and stored providers don't provide"
+        )]
+        pub strict: Option<bool>,
     }
 
     #[derive(Deserialize, JsonSchema)]
@@ -173,15 +334,97 @@ pub mod flows {
         pub file: String,
     }
 
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for previewing template expansion of a flow")]
+    pub struct RenderInput {
+        /// Flow to render: a local path on the CLI, a flow name otherwise
+        #[serde(default)]
+        #[schemars(
+            description = "Flow to render: a file path on the CLI, or the name of a flow in the flows directory"
+        )]
+        pub file: Option<String>,
+        #[serde(default)]
+        #[schemars(description = "Flow content to render (alternative to file)")]
+        pub content: Option<String>,
+        #[serde(default)]
+        #[schemars(
+            description = "Template variables: an object (the CLI also accepts a path to a JSON file)"
+        )]
+        pub vars: Option<Value>,
+        #[serde(default)]
+        #[schemars(description = "Include a unified diff against the raw flow")]
+        pub diff: bool,
+    }
+
+    #[derive(Serialize)]
+    pub struct RenderOutput {
+        pub rendered: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub diff: Option<String>,
+    }
+
+    /// Current flow bundle format version
+    pub const BUNDLE_VERSION: u32 = 1;
+
+    /// A flow plus the registry entries it depends on
+    #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+    pub struct FlowBundle {
+        #[schemars(description = "Bundle format version")]
+        pub bundle_version: u32,
+        #[schemars(description = "Name of the bundled flow")]
+        pub name: FlowName,
+        #[schemars(description = "Flow definition content")]
+        pub content: String,
+        #[schemars(
+            description = "Tools, MCP servers and OAuth providers the flow references",
+            with = "Vec<Value>"
+        )]
+        pub entries: Vec<RegistryEntry>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        #[schemars(description = "Referenced names that were not found in any registry")]
+        pub missing: Vec<String>,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for exporting a flow bundle")]
+    pub struct ExportBundleInput {
+        #[schemars(description = "Name of the flow to export")]
+        pub name: FlowName,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for importing a flow bundle")]
+    pub struct ImportBundleInput {
+        #[serde(default)]
+        #[schemars(description = "Bundle produced by export_flow_bundle")]
+        pub bundle: Option<FlowBundle>,
+        /// Path to bundle file (CLI only)
+        #[serde(default)]
+        #[schemars(description = "Path to bundle file (CLI only)")]
+        pub file: Option<String>,
+        #[serde(default)]
+        #[schemars(
+            description = "Replace registry entries that already exist with different content (CLI only)"
+        )]
+        pub overwrite: bool,
+    }
+
+    #[derive(Debug, Serialize)]
+    pub struct ImportBundleOutput {
+        pub status: String,
+        pub name: FlowName,
+        pub entries: Vec<String>,
+    }
+
     // Operations
 
     /// List all available flows
     #[operation(
         name = "list_flows",
-        input = EmptyInput,
+        input = ListInput,
         http = "GET /flows",
-        cli = "flows list",
-        description = "List all available workflow definitions"
+        cli = "flows list [--tag <TAG>] [--owner <OWNER>] [--team <TEAM>]",
+        description = "List workflow definitions with their owner, team and tags, optionally filtered by tag, owner or team"
     )]
     pub struct List {
         pub deps: Arc<Dependencies>,
@@ -189,13 +432,163 @@ pub mod flows {
 
     #[async_trait]
     impl Operation for List {
-        type Input = EmptyInput;
+        type Input = ListInput;
         type Output = ListOutput;
 
-        async fn execute(&self, _input: Self::Input) -> Result<Self::Output> {
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let filter = FlowFilter {
+                tag: input.tag,
+                owner: input.owner,
+                team: input.team,
+                tenant_id: crate::auth::current_tenant(),
+            };
             let flows_dir = crate::config::get_flows_dir(&self.deps.config);
-            let flows = crate::storage::flows::list_flows(&flows_dir).await?;
-            Ok(ListOutput { flows })
+            let names = crate::storage::flows::list_flows(&flows_dir).await?;
+
+            // Flow files are filtered in memory on their draft metadata
+            let mut flows = BTreeMap::new();
+            for name in names {
+                if let Some(ref tenant_id) = filter.tenant_id
+                    && self.deps.storage.get_flow_tenant(&name).await?.as_ref() != Some(tenant_id)
+                {
+                    continue;
+                }
+                let Some(content) = crate::storage::flows::get_flow(&flows_dir, &name).await?
+                else {
+                    continue;
+                };
+                let summary = match super::parse_flow_content(&self.deps.config, &content) {
+                    Ok(flow) => FlowSummary::of(&flow),
+                    Err(e) => {
+                        tracing::debug!("Listing unparseable flow '{}': {}", name, e);
+                        FlowSummary::default()
+                    }
+                };
+                if !filter.matches(&summary) {
+                    continue;
+                }
+                let status = super::flow_status(&self.deps, &name, Some(&content)).await?;
+                flows.insert(
+                    name.to_string(),
+                    ListedFlow {
+                        summary: FlowSummary {
+                            name: name.to_string(),
+                            deployed_version: status.deployed_version,
+                            ..summary
+                        },
+                        status: status.status,
+                    },
+                );
+            }
+
+            // Deployed flows without a file are filtered by storage
+            for summary in self
+                .deps
+                .storage
+                .list_deployed_flow_summaries(&filter)
+                .await?
+            {
+                flows.entry(summary.name.clone()).or_insert(ListedFlow {
+                    summary,
+                    status: DriftStatus::DeployedOnly,
+                });
+            }
+
+            Ok(ListOutput {
+                flows: flows.into_values().collect(),
+            })
+        }
+    }
+
+    /// Search deployed flows by name, description, tags and step content
+    #[operation(
+        name = "search_flows",
+        input = SearchInput,
+        http = "GET /flows/search",
+        cli = "flows search <Q> [--limit <LIMIT>]",
+        description = "Search deployed flows by name, description, tags, step ids, tools and literal step inputs; name and description matches rank first"
+    )]
+    pub struct Search {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for Search {
+        type Input = SearchInput;
+        type Output = SearchOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            if crate::storage::search::search_terms(&input.q).is_empty() {
+                return Err(BeemFlowError::validation(
+                    "Search query must contain at least one word",
+                ));
+            }
+            let limit = input.limit.unwrap_or(20).min(1000);
+            let results = self.deps.storage.search_flows(&input.q, limit).await?;
+            Ok(SearchOutput { results })
+        }
+    }
+
+    /// Compare draft flow files with their deployed versions
+    #[operation(
+        name = "flow_status",
+        input = StatusInput,
+        http = "GET /flows/status",
+        cli = "flows status [<NAME>] [--fail_on_drift]",
+        description = "Report whether each flow's draft file matches its deployed version (in-sync, draft-ahead, deployed-only or file-only)"
+    )]
+    pub struct Status {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for Status {
+        type Input = StatusInput;
+        type Output = StatusOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let flows_dir = crate::config::get_flows_dir(&self.deps.config);
+            let names: Vec<FlowName> = match input.name {
+                Some(name) => vec![name],
+                None => {
+                    let mut names = crate::storage::flows::list_flows(&flows_dir).await?;
+                    for (name, _) in self.deps.storage.list_all_deployed_flows().await? {
+                        names.push(FlowName::new(name)?);
+                    }
+                    names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+                    names.dedup();
+                    names
+                }
+            };
+
+            let mut flows = Vec::with_capacity(names.len());
+            for name in names {
+                let content = crate::storage::flows::get_flow(&flows_dir, &name).await?;
+                let status = super::flow_status(&self.deps, &name, content.as_deref()).await?;
+                if status.deployed_version.is_none() && status.file_hash.is_none() {
+                    return Err(not_found("Flow", &name));
+                }
+                flows.push(status);
+            }
+
+            if input.fail_on_drift.unwrap_or(false) {
+                let drifted: Vec<String> = flows
+                    .iter()
+                    .filter(|f| f.status != DriftStatus::InSync)
+                    .map(|f| {
+                        let status = serde_json::to_value(f.status).unwrap_or_default();
+                        format!("{} ({})", f.name, status.as_str().unwrap_or_default())
+                    })
+                    .collect();
+                if !drifted.is_empty() {
+                    return Err(BeemFlowError::validation(format!(
+                        "Flows not in sync with their deployed version: {}",
+                        drifted.join(", ")
+                    )));
+                }
+            }
+
+            Ok(StatusOutput { flows })
         }
     }
 
@@ -204,7 +597,7 @@ pub mod flows {
         name = "get_flow",
         input = GetInput,
         http = "GET /flows/{name}",
-        cli = "flows get <NAME>",
+        cli = "flows get <NAME> [--format <FORMAT>]",
         description = "Get a flow by name"
     )]
     pub struct Get {
@@ -217,18 +610,27 @@ pub mod flows {
         type Output = GetOutput;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            super::authorize_flow(&self.deps, &input.name, false).await?;
             let flows_dir = crate::config::get_flows_dir(&self.deps.config);
             let content = crate::storage::flows::get_flow(&flows_dir, &input.name)
                 .await?
                 .ok_or_else(|| not_found("Flow", &input.name))?;
 
             // Parse to get version
-            let flow = parse_string(&content, None)?;
+            let flow = super::parse_flow_content(&self.deps.config, &content)?;
+            let content = match input.format {
+                Some(format) => serialize_flow(&flow, format)?,
+                None => content,
+            };
 
             Ok(GetOutput {
                 name: input.name,
                 content,
                 version: flow.version,
+                description: flow.description,
+                owner: flow.owner,
+                team: flow.team,
+                tags: flow.tags.unwrap_or_default(),
             })
         }
     }
@@ -238,6 +640,7 @@ pub mod flows {
         name = "save_flow",
         input = SaveInput,
         http = "POST /flows",
+        raw_body = "content",
         cli = "flows save <NAME> --file <FILE> --content <CONTENT>",
         description = "Save or update a flow definition"
     )]
@@ -265,14 +668,12 @@ pub mod flows {
             };
 
             // Parse and validate the flow
-            let flow = parse_string(&content, None)?;
+            let flow = super::parse_flow_content(&self.deps.config, &content)?;
             Validator::validate(&flow)?;
 
             // Determine flow name
-            let name = input.name.unwrap_or_else(|| flow.name.to_string());
-            if name.is_empty() {
-                return Err(BeemFlowError::validation("Flow must have a name"));
-            }
+            let name = input.name.unwrap_or_else(|| flow.name.clone());
+            super::authorize_flow(&self.deps, &name, true).await?;
 
             let flows_dir = crate::config::get_flows_dir(&self.deps.config);
 
@@ -308,6 +709,7 @@ pub mod flows {
         type Output = DeleteOutput;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            super::authorize_flow(&self.deps, &input.name, false).await?;
             let flows_dir = crate::config::get_flows_dir(&self.deps.config);
             crate::storage::flows::delete_flow(&flows_dir, &input.name).await?;
 
@@ -323,8 +725,8 @@ pub mod flows {
         name = "deploy_flow",
         input = DeployInput,
         http = "POST /flows/{name}/deploy",
-        cli = "flows deploy <NAME>",
-        description = "Deploy flow to production"
+        cli = "flows deploy <NAME> [--environment <ENVIRONMENT>] [--skip_tool_check] [--dry_run]",
+        description = "Deploy flow to production, or with dry_run report what would be deployed"
     )]
     pub struct Deploy {
         pub deps: Arc<Dependencies>,
@@ -343,22 +745,205 @@ pub mod flows {
                 .ok_or_else(|| not_found("Flow", &input.name))?;
 
             // Parse to get version
-            let flow = parse_string(&content, None)?;
-            let version = flow.version.ok_or_else(|| {
+            let flow = super::parse_flow_content(&self.deps.config, &content)?;
+            if let Some(ref environment) = input.environment
+                && !flow.has_environment(environment)
+            {
+                return Err(BeemFlowError::validation(format!(
+                    "Flow '{}' has no environment '{}'",
+                    input.name, environment
+                )));
+            }
+            let version = flow.version.clone().ok_or_else(|| {
                 BeemFlowError::validation("Flow must have a version field to deploy")
             })?;
+            if !input.skip_tool_check.unwrap_or(false) {
+                check_tools(&self.deps, &flow).await?;
+            }
+            if input.dry_run.unwrap_or(false) {
+                return self.plan(input, version, &content).await;
+            }
 
-            // Deploy the version to database
+            // Deploy the full flow (all environments); overlays apply when runs start
+            super::authorize_flow(&self.deps, &input.name, true).await?;
             self.deps
                 .storage
                 .deploy_flow_version(&input.name, &version, &content)
                 .await?;
 
-            let message = format!("Flow '{}' v{} deployed to production", input.name, version);
+            let message = match input.environment {
+                Some(ref environment) => format!(
+                    "Flow '{}' v{} deployed to {}",
+                    input.name, version, environment
+                ),
+                None => format!("Flow '{}' v{} deployed to production", input.name, version),
+            };
+
+            Ok(DeployOutput {
+                flow: input.name,
+                version,
+                environment: input.environment,
+                status: "deployed".to_string(),
+                message,
+                dry_run: false,
+                deployed_version: None,
+                file_hash: None,
+            })
+        }
+    }
+
+    impl Deploy {
+        /// Report what deploying `content` as `version` would do, changing nothing
+        ///
+        /// A draft matching the deployed version is `unchanged`. A draft whose
+        /// version already exists with other content fails, as the deploy would.
+        async fn plan(
+            &self,
+            input: DeployInput,
+            version: String,
+            content: &str,
+        ) -> Result<DeployOutput> {
+            // The deploy would claim a flow no tenant owns yet; a dry run claims nothing
+            if self
+                .deps
+                .storage
+                .get_flow_tenant(&input.name)
+                .await?
+                .is_some()
+            {
+                super::authorize_flow(&self.deps, &input.name, false).await?;
+            }
+
+            let status = super::flow_status(&self.deps, &input.name, Some(content)).await?;
+            let (action, message) = if status.status == DriftStatus::InSync {
+                let message = format!(
+                    "Flow '{}' v{} is already deployed; nothing to deploy",
+                    input.name,
+                    status.deployed_version.as_deref().unwrap_or(&version)
+                );
+                ("unchanged", message)
+            } else if self
+                .deps
+                .storage
+                .get_flow_version_content(&input.name, &version)
+                .await?
+                .is_some()
+            {
+                return Err(BeemFlowError::validation(format!(
+                    "Version '{}' already exists for flow '{}'. Versions are immutable - use a new version number.",
+                    version, input.name
+                )));
+            } else {
+                let message = match status.deployed_version {
+                    Some(ref deployed) => format!(
+                        "Flow '{}' v{} would replace deployed v{}",
+                        input.name, version, deployed
+                    ),
+                    None => format!("Flow '{}' v{} would be deployed", input.name, version),
+                };
+                ("would_deploy", message)
+            };
 
             Ok(DeployOutput {
                 flow: input.name,
                 version,
+                environment: input.environment,
+                status: action.to_string(),
+                message,
+                dry_run: true,
+                deployed_version: status.deployed_version,
+                file_hash: status.file_hash,
+            })
+        }
+    }
+
+    /// Patch the deployed flow and deploy the result as a new version
+    #[operation(
+        name = "update_flow",
+        input = UpdateInput,
+        http = "PATCH /flows/{name}",
+        cli = "flows update <NAME> --patch <PATCH> [--skip_tool_check]",
+        description = "Apply a JSON Patch or merge patch to the deployed flow and deploy it as a new version"
+    )]
+    pub struct Update {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for Update {
+        type Input = UpdateInput;
+        type Output = UpdateOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            super::authorize_flow(&self.deps, &input.name, false).await?;
+            let storage = &self.deps.storage;
+            let from_version = storage
+                .get_deployed_version(&input.name)
+                .await?
+                .ok_or_else(|| not_found("Deployed flow", &input.name))?;
+            let content = storage
+                .get_flow_version_content(&input.name, &from_version)
+                .await?
+                .ok_or_else(|| not_found(&format!("Version {}", from_version), &input.name))?;
+
+            let (mut document, format) = patch::parse_document(&content)?;
+            patch::apply_patch(&mut document, &input.patch)?;
+
+            // Versions are immutable: an unchanged version moves to the next free one
+            let version = match document.get("version").and_then(Value::as_str) {
+                Some(version) if version != from_version => version.to_string(),
+                _ => {
+                    let mut version = from_version.clone();
+                    loop {
+                        version = next_version(&version).ok_or_else(|| {
+                            BeemFlowError::validation(format!(
+                                "Cannot derive a new version from '{}'; set /version in the patch",
+                                from_version
+                            ))
+                        })?;
+                        if storage
+                            .get_flow_version_content(&input.name, &version)
+                            .await?
+                            .is_none()
+                        {
+                            break version;
+                        }
+                    }
+                }
+            };
+            if let Some(fields) = document.as_object_mut() {
+                fields.insert("version".to_string(), Value::String(version.clone()));
+            }
+
+            // The result must be a valid flow under the same name
+            let patched = patch::serialize_document(&document, format)?;
+            let flow = super::parse_flow_content(&self.deps.config, &patched)
+                .and_then(|flow| Validator::validate(&flow).map(|_| flow))
+                .map_err(|e| {
+                    BeemFlowError::validation(format!("Patched flow is invalid: {}", e))
+                })?;
+            if flow.name != input.name {
+                return Err(BeemFlowError::validation(format!(
+                    "Patched flow is named '{}'; a patch cannot rename flow '{}'",
+                    flow.name, input.name
+                )));
+            }
+            if !input.skip_tool_check.unwrap_or(false) {
+                check_tools(&self.deps, &flow).await?;
+            }
+
+            storage
+                .deploy_flow_version(&input.name, &version, &patched)
+                .await?;
+
+            let message = format!(
+                "Flow '{}' patched from v{} and deployed as v{}",
+                input.name, from_version, version
+            );
+            Ok(UpdateOutput {
+                flow: input.name,
+                from_version,
+                version,
                 status: "deployed".to_string(),
                 message,
             })
@@ -383,6 +968,8 @@ pub mod flows {
         type Output = RollbackOutput;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            super::authorize_flow(&self.deps, &input.name, false).await?;
+
             // Get current deployed version
             let current_version = self.deps.storage.get_deployed_version(&input.name).await?;
 
@@ -423,6 +1010,8 @@ pub mod flows {
         type Output = DisableOutput;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            super::authorize_flow(&self.deps, &input.name, false).await?;
+
             // Check if flow is currently deployed
             let deployed_version = self.deps.storage.get_deployed_version(&input.name).await?;
 
@@ -470,6 +1059,8 @@ pub mod flows {
         type Output = EnableOutput;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            super::authorize_flow(&self.deps, &input.name, false).await?;
+
             // Check if already enabled
             if let Some(current) = self.deps.storage.get_deployed_version(&input.name).await? {
                 return Err(BeemFlowError::validation(format!(
@@ -528,6 +1119,8 @@ pub mod flows {
         type Output = RestoreOutput;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            super::authorize_flow(&self.deps, &input.name, false).await?;
+
             // Determine which version to restore
             let version = if let Some(v) = input.version {
                 // Specific version requested
@@ -588,8 +1181,8 @@ pub mod flows {
         name = "flow_history",
         input = HistoryInput,
         http = "GET /flows/{name}/history",
-        cli = "flows history <NAME>",
-        description = "Get flow version history"
+        cli = "flows history <NAME> [--limit <LIMIT>] [--cursor <CURSOR>] [--all]",
+        description = "Get flow version history, newest first, a page at a time"
     )]
     pub struct History {
         pub deps: Arc<Dependencies>,
@@ -601,9 +1194,21 @@ pub mod flows {
         type Output = Value;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
-            let history = self.deps.storage.list_flow_versions(&input.name).await?;
+            super::authorize_flow(&self.deps, &input.name, false).await?;
+            let limit = input.limit.unwrap_or(100).min(10_000);
+            let after = input.cursor.as_deref().map(decode_cursor).transpose()?;
+            let history = self
+                .deps
+                .storage
+                .list_flow_versions_after(&input.name, limit, after.as_ref())
+                .await?;
 
-            let result: Vec<_> = history
+            let page = Page::new(history, limit, |v| crate::storage::PageCursor {
+                at: v.deployed_at,
+                key: v.version.clone(),
+            });
+            let items: Vec<_> = page
+                .items
                 .iter()
                 .map(|v| {
                     serde_json::json!({
@@ -614,7 +1219,10 @@ pub mod flows {
                 })
                 .collect();
 
-            Ok(serde_json::to_value(result)?)
+            Ok(serde_json::to_value(Page {
+                items,
+                next_cursor: page.next_cursor,
+            })?)
         }
     }
 
@@ -623,8 +1231,8 @@ pub mod flows {
         name = "validate_flow",
         input = ValidateInput,
         http = "POST /flows/validate",
-        cli = "flows validate <FILE>",
-        description = "Validate a flow"
+        cli = "flows validate <FILE> [--strict]",
+        description = "Validate a flow; with strict, also check that the tools and OAuth providers it references exist"
     )]
     pub struct Validate {
         pub deps: Arc<Dependencies>,
@@ -643,10 +1251,15 @@ pub mod flows {
             )
             .await?;
             Validator::validate(&flow)?;
+            self.deps.engine.check_tool_params(&flow).await?;
+            if input.strict.unwrap_or(false) {
+                super::check_references(&self.deps, &flow).await?;
+            }
 
             Ok(serde_json::json!({
                 "status": "valid",
-                "message": "Validation OK: flow is valid!"
+                "message": "Validation OK: flow is valid!",
+                "warnings": Validator::warnings(&flow)
             }))
         }
     }
@@ -669,8 +1282,12 @@ pub mod flows {
         type Output = Value;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
-            let flow = parse_file(&input.file, None)?;
+            let flow = parse_file(
+                &input.file,
+                Some(self.deps.config.get_limits().max_flow_file_size),
+            )?;
             Validator::validate(&flow)?;
+            self.deps.engine.check_tools_resolve(&flow).await?;
 
             Ok(serde_json::json!({
                 "status": "valid",
@@ -679,6 +1296,73 @@ pub mod flows {
         }
     }
 
+    /// Preview a flow after template expansion
+    #[operation(
+        name = "render_flow",
+        input = RenderInput,
+        http = "POST /flows/render",
+        cli = "flows render <FILE> [--vars <VARS>] [--diff]",
+        description = "Render a flow's templates with sample variables, as load_flow does before parsing"
+    )]
+    pub struct Render {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for Render {
+        type Input = RenderInput;
+        type Output = RenderOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            // Only inline content or flows in the flows directory are read here;
+            // the CLI reads local files itself before calling the operation
+            let (content, label) = match (input.content, input.file) {
+                (Some(content), file) if !content.is_empty() => {
+                    (content, file.unwrap_or_else(|| "flow".to_string()))
+                }
+                (_, Some(file)) => {
+                    let name = FlowName::new(file.as_str()).map_err(|_| {
+                        BeemFlowError::validation(format!(
+                            "'file' must name a flow in the flows directory, got '{}'",
+                            file
+                        ))
+                    })?;
+                    super::authorize_flow(&self.deps, &name, false).await?;
+                    let flows_dir = crate::config::get_flows_dir(&self.deps.config);
+                    let content = crate::storage::flows::get_flow(&flows_dir, &name)
+                        .await?
+                        .ok_or_else(|| not_found("Flow", &name))?;
+                    (content, file)
+                }
+                _ => {
+                    return Err(BeemFlowError::validation(
+                        "Either 'content' or 'file' must be provided",
+                    ));
+                }
+            };
+
+            super::check_flow_size(&self.deps.config, &content)?;
+
+            let vars_value = input
+                .vars
+                .unwrap_or_else(|| Value::Object(Default::default()));
+            let vars: HashMap<String, Value> = serde_json::from_value(vars_value)
+                .map_err(|_| BeemFlowError::validation("'vars' must be a JSON object"))?;
+
+            let rendered = crate::dsl::render_template_strict(&content, vars)?;
+            let diff = input.diff.then(|| {
+                crate::utils::unified_diff(
+                    &content,
+                    &rendered,
+                    &label,
+                    &format!("{} (rendered)", label),
+                )
+            });
+
+            Ok(RenderOutput { rendered, diff })
+        }
+    }
+
     /// Test a flow
     #[operation(name = "test_flow", input = EmptyInput, description = "Test a flow")]
     pub struct Test {
@@ -697,4 +1381,400 @@ pub mod flows {
             }))
         }
     }
+
+    /// Export a flow with its registry dependencies
+    #[operation(
+        name = "export_flow_bundle",
+        input = ExportBundleInput,
+        http = "GET /flows/{name}/bundle",
+        cli = "flows export-bundle <NAME>",
+        description = "Export a flow with the tools, MCP servers and OAuth providers it uses"
+    )]
+    pub struct ExportBundle {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for ExportBundle {
+        type Input = ExportBundleInput;
+        type Output = FlowBundle;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let flows_dir = crate::config::get_flows_dir(&self.deps.config);
+            let content = crate::storage::flows::get_flow(&flows_dir, &input.name)
+                .await?
+                .ok_or_else(|| not_found("Flow", &input.name))?;
+            let flow = super::parse_flow_content(&self.deps.config, &content)?;
+
+            let (entries, missing) =
+                super::collect_bundle_entries(&flow, &self.deps.registry_manager).await?;
+            // Literal client secrets, header tokens and env values stay behind
+            let entries = entries
+                .into_iter()
+                .map(|entry| {
+                    serde_json::from_value(mask_secrets(&serde_json::to_value(entry)?))
+                        .map_err(BeemFlowError::from)
+                })
+                .collect::<Result<Vec<RegistryEntry>>>()?;
+
+            Ok(FlowBundle {
+                bundle_version: BUNDLE_VERSION,
+                name: input.name,
+                content,
+                entries,
+                missing,
+            })
+        }
+    }
+
+    /// Import a flow bundle
+    #[operation(
+        name = "import_flow_bundle",
+        input = ImportBundleInput,
+        http = "POST /flows/import",
+        cli = "flows import-bundle --file <FILE>",
+        description = "Import a flow bundle, saving the flow and registering its dependencies"
+    )]
+    pub struct ImportBundle {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for ImportBundle {
+        type Input = ImportBundleInput;
+        type Output = ImportBundleOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            // Bundle files, MCP servers, OAuth providers and overwrites are only
+            // accepted on the CLI, which calls `import_bundle` itself
+            if input.file.is_some() {
+                return Err(BeemFlowError::validation(
+                    "'file' is only accepted on the CLI; send the bundle inline",
+                ));
+            }
+            if input.overwrite {
+                return Err(BeemFlowError::validation(
+                    "'overwrite' is only accepted on the CLI",
+                ));
+            }
+            let bundle = input
+                .bundle
+                .ok_or_else(|| BeemFlowError::validation("'bundle' must be provided"))?;
+            super::import_bundle(&self.deps, bundle, super::BundleImport::default()).await
+        }
+    }
+}
+
+/// Check every tool a flow uses resolves before it is deployed
+async fn check_tools(deps: &Dependencies, flow: &Flow) -> Result<()> {
+    deps.engine
+        .check_tools_resolve(flow)
+        .await
+        .map_err(|e| match e {
+            BeemFlowError::Validation(msg) => BeemFlowError::validation(format!(
+                "{}\nInstall them first, or deploy with --skip_tool_check if they will be installed later",
+                msg
+            )),
+            other => other,
+        })
+}
+
+/// `$oauth:<provider>` reference, capturing the provider
+static OAUTH_REF: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+    regex::Regex::new(r"\$oauth:([A-Za-z0-9_-]+)").expect("valid regex")
+});
+
+/// Fail when a flow uses tools or `$oauth` providers that don't exist
+///
+/// Reports every unknown tool and provider with the steps referencing it.
+async fn check_references(deps: &Dependencies, flow: &Flow) -> Result<()> {
+    let mut problems = Vec::new();
+    match deps.engine.check_tools_resolve(flow).await {
+        Ok(()) => {}
+        Err(BeemFlowError::Validation(msg)) => problems.push(msg),
+        Err(e) => return Err(e),
+    }
+
+    // provider -> steps referencing it
+    let mut references: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let mut pending: Vec<&Step> = flow
+        .steps
+        .iter()
+        .chain(flow.catch.iter().flatten())
+        .chain(flow.on_success.iter().flatten())
+        .chain(flow.on_failure.iter().flatten())
+        .collect();
+    let mut texts = Vec::new();
+    while let Some(step) = pending.pop() {
+        // The step without its children, which are scanned on their own
+        let own = Step {
+            steps: None,
+            do_: None,
+            ..step.clone()
+        };
+        texts.push((step.id.as_str(), serde_json::to_string(&own)?));
+        pending.extend(step.steps.iter().chain(step.do_.iter()).flatten());
+    }
+    for (step_id, text) in &texts {
+        for provider in OAUTH_REF
+            .captures_iter(text)
+            .filter_map(|c| c.get(1).map(|m| m.as_str()))
+        {
+            let steps = references.entry(provider).or_default();
+            if !steps.contains(step_id) {
+                steps.push(step_id);
+            }
+        }
+    }
+
+    let mut unknown = Vec::new();
+    for (provider, mut steps) in references {
+        let configured = deps
+            .registry_manager
+            .get_oauth_provider(provider)
+            .await?
+            .is_some()
+            || deps.storage.get_oauth_provider(provider).await?.is_some();
+        if !configured {
+            steps.sort_unstable();
+            unknown.push(format!("{} (step '{}')", provider, steps.join("', '")));
+        }
+    }
+    if !unknown.is_empty() {
+        problems.push(format!(
+            "Flow '{}' references OAuth providers that are not configured:\n  - {}",
+            flow.name,
+            unknown.join("\n  - ")
+        ));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(BeemFlowError::validation(problems.join("\n")))
+    }
+}
+
+/// Version after `version`, incrementing its last number (`1.0.9` to `1.0.10`)
+fn next_version(version: &str) -> Option<String> {
+    let (prefix, last) = match version.rsplit_once('.') {
+        Some((prefix, last)) => (Some(prefix), last),
+        None => (None, version),
+    };
+    let next = last.parse::<u64>().ok()?.checked_add(1)?;
+    Some(match prefix {
+        Some(prefix) => format!("{}.{}", prefix, next),
+        None => next.to_string(),
+    })
+}
+
+/// Compare a flow's draft file content (if any) with its deployed version
+async fn flow_status(
+    deps: &Dependencies,
+    name: &FlowName,
+    file_content: Option<&str>,
+) -> Result<self::flows::FlowStatus> {
+    use self::flows::DriftStatus;
+    use sha2::{Digest, Sha256};
+
+    let hash = |content: &str| hex::encode(Sha256::digest(content.as_bytes()));
+
+    let deployed_version = deps.storage.get_deployed_version(name).await?;
+    let deployed_hash = match &deployed_version {
+        Some(version) => deps
+            .storage
+            .get_flow_version_content(name, version)
+            .await?
+            .map(|content| hash(&content)),
+        None => None,
+    };
+    let file_hash = file_content.map(hash);
+
+    let status = match (&file_hash, &deployed_version) {
+        (Some(_), None) | (None, None) => DriftStatus::FileOnly,
+        (None, Some(_)) => DriftStatus::DeployedOnly,
+        (Some(file), Some(_)) if Some(file) == deployed_hash.as_ref() => DriftStatus::InSync,
+        (Some(_), Some(_)) => DriftStatus::DraftAhead,
+    };
+
+    Ok(self::flows::FlowStatus {
+        name: name.to_string(),
+        status,
+        deployed_version,
+        file_hash,
+        deployed_hash,
+    })
+}
+
+/// What a bundle import may change in the registry
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BundleImport {
+    /// Register MCP servers and OAuth providers, not only tools
+    pub all_entry_types: bool,
+    /// Replace entries that already exist with different content
+    pub overwrite: bool,
+}
+
+/// Import a flow bundle: register its entries and save its flow
+///
+/// `import_flow_bundle` only allows tool entries and never overwrites; the CLI
+/// passes its `--overwrite` flag and allows every entry type.
+pub async fn import_bundle(
+    deps: &Dependencies,
+    bundle: flows::FlowBundle,
+    options: BundleImport,
+) -> Result<flows::ImportBundleOutput> {
+    if bundle.bundle_version != flows::BUNDLE_VERSION {
+        return Err(BeemFlowError::validation(format!(
+            "Unsupported bundle version {} (expected {})",
+            bundle.bundle_version,
+            flows::BUNDLE_VERSION
+        )));
+    }
+
+    // Check everything before writing anything
+    let flow = parse_flow_content(&deps.config, &bundle.content)?;
+    Validator::validate(&flow)?;
+    let mut to_register = Vec::with_capacity(bundle.entries.len());
+    for entry in bundle.entries {
+        let allowed = if options.all_entry_types {
+            matches!(
+                entry.entry_type.as_str(),
+                "tool" | "mcp_server" | "oauth_provider"
+            )
+        } else {
+            entry.entry_type == "tool"
+        };
+        if !allowed {
+            return Err(BeemFlowError::validation(format!(
+                "Registry entry type '{}' for '{}' can't be imported here{}",
+                entry.entry_type,
+                entry.name,
+                if options.all_entry_types {
+                    ""
+                } else {
+                    " (MCP servers and OAuth providers are imported with the CLI)"
+                }
+            )));
+        }
+        // An existing entry is kept as-is; one with different content is only
+        // replaced on request
+        match deps.registry_manager.get_server(&entry.name).await? {
+            Some(existing) if same_entry(&existing, &entry)? => continue,
+            Some(_) if !options.overwrite => {
+                return Err(BeemFlowError::validation(format!(
+                    "Registry entry '{}' already exists with different content; import with --overwrite to replace it",
+                    entry.name
+                )));
+            }
+            _ => to_register.push(entry),
+        }
+    }
+
+    let mut names = Vec::with_capacity(to_register.len());
+    for entry in to_register {
+        names.push(entry.name.clone());
+        deps.registry_manager.upsert_local_entry(entry).await?;
+    }
+
+    let flows_dir = crate::config::get_flows_dir(&deps.config);
+    let was_updated =
+        crate::storage::flows::save_flow(&flows_dir, &bundle.name, &bundle.content).await?;
+
+    Ok(flows::ImportBundleOutput {
+        status: if was_updated { "updated" } else { "created" }.to_string(),
+        name: bundle.name,
+        entries: names,
+    })
+}
+
+/// Whether an imported entry matches an existing one, ignoring the registry it
+/// came from and comparing secrets as they are exported (masked)
+fn same_entry(existing: &RegistryEntry, imported: &RegistryEntry) -> Result<bool> {
+    let comparable = |entry: &RegistryEntry| -> Result<Value> {
+        let mut value = mask_secrets(&serde_json::to_value(entry)?);
+        if let Some(map) = value.as_object_mut() {
+            map.remove("registry");
+        }
+        Ok(value)
+    };
+    Ok(comparable(existing)? == comparable(imported)?)
+}
+
+/// Find the registry entries a flow depends on
+///
+/// Every `use:` value is resolved against the registry (`mcp://server/tool` by
+/// server name, everything else by tool name; `core.*` is built in). OAuth
+/// providers are found through `$oauth:<provider>` references in the flow and in
+/// the headers of its tools. Returns the entries sorted by name, plus any
+/// referenced names that no registry knows about.
+async fn collect_bundle_entries(
+    flow: &Flow,
+    registry_manager: &RegistryManager,
+) -> Result<(Vec<RegistryEntry>, Vec<String>)> {
+    fn collect_uses<'a>(steps: &'a [Step], uses: &mut Vec<&'a str>) {
+        for step in steps {
+            if let Some(use_) = step.use_.as_deref() {
+                uses.push(use_);
+            }
+            for nested in [&step.steps, &step.do_].into_iter().flatten() {
+                collect_uses(nested, uses);
+            }
+        }
+    }
+
+    let mut uses = Vec::new();
+    collect_uses(&flow.steps, &mut uses);
+
+    // (name, expected entry type)
+    let mut wanted: Vec<(String, &str)> = Vec::new();
+    for use_ in uses {
+        if use_.starts_with("core.") {
+            continue;
+        }
+        let wanted_entry = match use_.strip_prefix("mcp://") {
+            Some(rest) => (
+                rest.split('/').next().unwrap_or(rest).to_string(),
+                "mcp_server",
+            ),
+            None => (use_.to_string(), "tool"),
+        };
+        if !wanted.contains(&wanted_entry) {
+            wanted.push(wanted_entry);
+        }
+    }
+
+    let mut oauth_text = serde_json::to_string(flow)?;
+    let mut entries: Vec<RegistryEntry> = Vec::new();
+    let mut missing = Vec::new();
+
+    for (name, entry_type) in wanted {
+        match registry_manager.get_server(&name).await? {
+            Some(entry) if entry.entry_type == entry_type => {
+                if let Some(headers) = &entry.headers {
+                    oauth_text.extend(headers.values().map(|v| format!(" {}", v)));
+                }
+                entries.push(entry);
+            }
+            _ => missing.push(name),
+        }
+    }
+
+    let mut providers: Vec<&str> = OAUTH_REF
+        .captures_iter(&oauth_text)
+        .filter_map(|c| c.get(1).map(|m| m.as_str()))
+        .collect();
+    providers.sort_unstable();
+    providers.dedup();
+
+    for provider in providers {
+        match registry_manager.get_server(provider).await? {
+            Some(entry) if entry.entry_type == "oauth_provider" => entries.push(entry),
+            _ => missing.push(provider.to_string()),
+        }
+    }
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    missing.sort();
+    Ok((entries, missing))
 }
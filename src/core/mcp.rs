@@ -3,6 +3,7 @@
 //! All operations for managing MCP servers.
 
 use super::*;
+use crate::utils::mask_secrets;
 use beemflow_core_macros::{operation, operation_group};
 use schemars::JsonSchema;
 
@@ -27,6 +28,20 @@ pub mod mcp {
         pub query: Option<String>,
     }
 
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for checking configured MCP servers")]
+    pub struct HealthInput {
+        #[schemars(
+            description = "Seconds each server has to start and list its tools (default: 10, max: 300)"
+        )]
+        pub timeout_secs: Option<u64>,
+    }
+
+    #[derive(Serialize)]
+    pub struct HealthOutput {
+        pub servers: Vec<crate::mcp::McpServerStatus>,
+    }
+
     #[derive(Deserialize, JsonSchema)]
     #[schemars(description = "Input for installing an MCP server")]
     pub struct InstallServerInput {
@@ -58,7 +73,7 @@ pub mod mcp {
             let servers: Vec<serde_json::Value> = entries
                 .into_iter()
                 .filter(|e| e.entry_type == "mcp_server")
-                .map(|e| serde_json::to_value(e).unwrap_or_default())
+                .map(|e| mask_secrets(&serde_json::to_value(e).unwrap_or_default()))
                 .collect();
 
             Ok(ListServersOutput { servers })
@@ -86,7 +101,7 @@ pub mod mcp {
             let entries = self.deps.registry_manager.list_all_servers().await?;
             let servers = filter_by_query(entries.into_iter(), "mcp_server", &input.query);
 
-            Ok(serde_json::to_value(servers)?)
+            Ok(mask_secrets(&serde_json::to_value(servers)?))
         }
     }
 
@@ -146,4 +161,43 @@ pub mod mcp {
             }))
         }
     }
+
+    /// Check which configured MCP servers are reachable
+    #[operation(
+        name = "check_mcp_servers",
+        input = HealthInput,
+        http = "GET /mcp/health",
+        cli = "mcp health [--timeout_secs <TIMEOUT_SECS>]",
+        description = "Check which MCP servers in mcpServers start and list their tools"
+    )]
+    pub struct CheckServers {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for CheckServers {
+        type Input = HealthInput;
+        type Output = HealthOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let timeout =
+                std::time::Duration::from_secs(input.timeout_secs.unwrap_or(10).clamp(1, 300));
+
+            // Registered the way the MCP proxy does, sharing the engine's connections
+            let mcp_adapter = self.deps.engine.mcp_adapter();
+            let mut names = Vec::new();
+            for (name, config) in self.deps.config.mcp_servers.iter().flatten() {
+                mcp_adapter.register_server(name.clone(), config.into());
+                names.push(name.clone());
+            }
+            names.sort();
+
+            let manager = mcp_adapter.manager();
+            let servers =
+                futures::future::join_all(names.iter().map(|name| manager.probe(name, timeout)))
+                    .await;
+
+            Ok(HealthOutput { servers })
+        }
+    }
 }
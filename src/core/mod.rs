@@ -3,11 +3,17 @@
 //! This module contains all BeemFlow operations organized by group.
 //! Each operation uses #[operation] and #[operation_group] macros for metadata.
 
+pub mod events;
+pub mod export;
 pub mod flows;
 pub mod mcp;
+pub mod oauth;
+pub mod reload;
 pub mod runs;
 pub mod system;
+pub mod tail;
 pub mod tools;
+pub mod webhooks;
 
 // Operation groups are available as modules
 // (not re-exported to avoid namespace pollution)
@@ -87,10 +93,13 @@ impl OperationRegistry {
         // The macro-generated register_all functions expect Arc<Dependencies>
         [
             flows::flows::register_all,
+            events::events::register_all,
             runs::runs::register_all,
             tools::tools::register_all,
             mcp::mcp::register_all,
+            oauth::oauth::register_all,
             system::system::register_all,
+            webhooks::webhooks::register_all,
         ]
         .into_iter()
         .for_each(|register_fn| register_fn(&mut registry, deps.clone()));
@@ -138,7 +147,9 @@ struct OperationWrapper<Op>(Op);
 #[async_trait]
 impl<Op: Operation + 'static> OperationExecutor for OperationWrapper<Op> {
     async fn execute_json(&self, input: Value) -> Result<Value> {
-        let typed_input: Op::Input = serde_json::from_value(input)?;
+        // Typed inputs (e.g. FlowName) validate while deserializing
+        let typed_input: Op::Input = serde_json::from_value(input)
+            .map_err(|e| BeemFlowError::validation(format!("Invalid input: {}", e)))?;
         let output = self.0.execute(typed_input).await?;
         Ok(serde_json::to_value(output)?)
     }
@@ -149,6 +160,31 @@ fn not_found(entity: &str, name: &str) -> BeemFlowError {
     BeemFlowError::not_found(entity, name)
 }
 
+/// Check that the current tenant, if any, may use flow `name`
+///
+/// Unscoped callers may use every flow and a tenant the flows it owns. With
+/// `claim`, a flow no tenant owns yet becomes the current tenant's. Flows of
+/// other tenants read as not found.
+async fn authorize_flow(
+    deps: &Dependencies,
+    name: &crate::model::FlowName,
+    claim: bool,
+) -> Result<()> {
+    let Some(tenant_id) = crate::auth::current_tenant() else {
+        return Ok(());
+    };
+    let owner = if claim {
+        Some(deps.storage.claim_flow_tenant(name, &tenant_id).await?)
+    } else {
+        deps.storage.get_flow_tenant(name).await?
+    };
+    if owner.as_deref() == Some(tenant_id.as_str()) {
+        Ok(())
+    } else {
+        Err(not_found("Flow", name))
+    }
+}
+
 fn type_mismatch(name: &str, expected_type: &str, actual_type: &str) -> BeemFlowError {
     BeemFlowError::validation(format!(
         "Entry '{}' is not a {}, found {}",
@@ -179,21 +215,143 @@ where
         .collect()
 }
 
+/// Smallest score for a registry entry to count as a fuzzy search match
+const MIN_SEARCH_SCORE: f64 = 0.8;
+
+/// Rank registry entries of `entry_type` against `query`, best match first
+///
+/// A name containing the whole query scores 1.0 and a description containing it
+/// 0.9. Otherwise each query term is compared with every word of the name (and,
+/// discounted, the description) by Jaro-Winkler similarity and the term scores
+/// are averaged, so near-misses like `githb` still find `github` tools. Entries
+/// scoring below [`MIN_SEARCH_SCORE`] are dropped; ties are ordered by name.
+fn rank_by_query<I>(
+    entries: I,
+    entry_type: &str,
+    query: &str,
+) -> Vec<(crate::registry::RegistryEntry, f64)>
+where
+    I: Iterator<Item = crate::registry::RegistryEntry>,
+{
+    fn words(text: &str) -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    let query = query.trim().to_lowercase();
+    let terms = words(&query);
+
+    let score = |entry: &crate::registry::RegistryEntry| -> f64 {
+        let name = entry.name.to_lowercase();
+        let description = entry.description.as_deref().unwrap_or("").to_lowercase();
+        if name.contains(&query) {
+            return 1.0;
+        }
+        if description.contains(&query) {
+            return 0.9;
+        }
+        if terms.is_empty() {
+            return 0.0;
+        }
+
+        let name_words = words(&name);
+        let description_words = words(&description);
+        let best = |term: &str| {
+            let in_name = name_words
+                .iter()
+                .map(|w| strsim::jaro_winkler(term, w))
+                .fold(0.0, f64::max);
+            let in_description = description_words
+                .iter()
+                .map(|w| strsim::jaro_winkler(term, w) * 0.9)
+                .fold(0.0, f64::max);
+            in_name.max(in_description)
+        };
+        terms.iter().map(|t| best(t)).sum::<f64>() / terms.len() as f64
+    };
+
+    let mut ranked: Vec<_> = entries
+        .filter(|e| e.entry_type == entry_type)
+        .map(|e| {
+            let s = score(&e);
+            (e, s)
+        })
+        .filter(|(_, s)| *s >= MIN_SEARCH_SCORE)
+        .collect();
+    ranked.sort_by(|(a, sa), (b, sb)| sb.total_cmp(sa).then_with(|| a.name.cmp(&b.name)));
+    ranked
+}
+
+/// Reject flow content over the configured `limits.maxFlowFileSize`
+///
+/// Every operation that accepts flow content goes through this (or
+/// [`parse_flow_content`]) before doing any work on it.
+fn check_flow_size(config: &Config, content: &str) -> Result<()> {
+    crate::dsl::check_content_size(content, config.get_limits().max_flow_file_size)
+}
+
+/// Parse flow content, enforcing the configured size limit
+fn parse_flow_content(config: &Config, content: &str) -> Result<crate::model::Flow> {
+    crate::dsl::parse_string(content, Some(config.get_limits().max_flow_file_size))
+}
+
+/// One page of a cursor-paginated list
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Pass back as `cursor` for the next page; `null` once the list is exhausted
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Page of `items` fetched with `limit`, continuing after the last item
+    ///
+    /// A full page may be followed by an empty one when the list ends exactly
+    /// at the page boundary.
+    fn new<K: Serialize>(
+        items: Vec<T>,
+        limit: usize,
+        position: impl Fn(&T) -> crate::storage::PageCursor<K>,
+    ) -> Self {
+        let next_cursor = match items.last() {
+            Some(last) if items.len() >= limit => Some(encode_cursor(&position(last))),
+            _ => None,
+        };
+        Self { items, next_cursor }
+    }
+}
+
+/// Opaque form of a keyset position handed to clients
+fn encode_cursor<K: Serialize>(cursor: &crate::storage::PageCursor<K>) -> String {
+    let json = serde_json::to_vec(cursor).unwrap_or_default();
+    base64::Engine::encode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, json)
+}
+
+fn decode_cursor<K: serde::de::DeserializeOwned>(
+    cursor: &str,
+) -> Result<crate::storage::PageCursor<K>> {
+    base64::Engine::decode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, cursor)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or_else(|| BeemFlowError::validation(format!("Invalid cursor '{}'", cursor)))
+}
+
 // Helper function for loading flows from name or file
 async fn load_flow_from_config(
     config: &Config,
-    name: Option<&str>,
+    name: Option<&crate::model::FlowName>,
     file: Option<&str>,
 ) -> Result<crate::model::Flow> {
-    use crate::dsl::{parse_file, parse_string};
     match (file, name) {
-        (Some(f), _) => parse_file(f, None),
+        (Some(f), _) => crate::dsl::parse_file(f, Some(config.get_limits().max_flow_file_size)),
         (None, Some(n)) => {
             let flows_dir = crate::config::get_flows_dir(config);
             let content = crate::storage::flows::get_flow(&flows_dir, n)
                 .await?
                 .ok_or_else(|| not_found("Flow", n))?;
-            parse_string(&content, None)
+            parse_flow_content(config, &content)
         }
         _ => Err(BeemFlowError::validation(
             "Either name or file must be provided",
@@ -210,8 +368,13 @@ pub async fn create_dependencies(config: &Config) -> Result<Dependencies> {
     // Arc config once at the start - all callers will reuse this Arc
     let config = Arc::new(config.clone());
 
-    // Create storage from config
-    let storage = crate::storage::create_storage_from_config(&config.storage).await?;
+    // Create storage from config, caching deployed flows if enabled
+    let mut storage = crate::storage::create_storage_from_config(&config.storage).await?;
+    if let Some(ref flow_cache) = config.storage.flow_cache {
+        storage = Arc::new(crate::storage::cached::CachedStorage::new(
+            storage, flow_cache,
+        ));
+    }
 
     // Create secrets provider from config (needed by multiple components)
     let secrets_provider = config.create_secrets_provider();
@@ -228,7 +391,9 @@ pub async fn create_dependencies(config: &Config) -> Result<Dependencies> {
     ));
 
     // Create remaining engine dependencies
-    let templater = Arc::new(crate::dsl::Templater::new());
+    let templater = Arc::new(crate::dsl::Templater::with_undefined_mode(
+        config.template_undefined_mode(),
+    ));
 
     // Register core adapters (built-in, not from registry)
     adapters.register(Arc::new(crate::adapter::CoreAdapter::new()));
@@ -264,6 +429,13 @@ pub async fn create_dependencies(config: &Config) -> Result<Dependencies> {
         secrets_provider.clone(),
         config.clone(),
         oauth_client.clone(),
+        Arc::new(crate::event::InProcEventBus::with_buffer_size(
+            config
+                .event
+                .as_ref()
+                .and_then(|event| event.buffer_size)
+                .unwrap_or(crate::constants::DEFAULT_EVENT_BUFFER_SIZE),
+        )),
         limits.max_concurrent_tasks,
     ));
 
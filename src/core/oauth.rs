@@ -0,0 +1,44 @@
+//! OAuth operations module
+//!
+//! Operations for checking the OAuth providers flows connect through.
+
+use super::*;
+use crate::auth::OAuthProviderTest;
+use beemflow_core_macros::{operation, operation_group};
+use schemars::JsonSchema;
+
+#[operation_group(oauth)]
+pub mod oauth {
+    use super::*;
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for testing an OAuth provider's configuration")]
+    pub struct TestProviderInput {
+        #[schemars(
+            description = "ID of the provider, from the registry or created through the API"
+        )]
+        pub provider: String,
+    }
+
+    /// Test an OAuth provider's configuration
+    #[operation(
+        name = "test_oauth_provider",
+        input = TestProviderInput,
+        http = "POST /oauth/providers/{provider}/test",
+        cli = "oauth test-provider <PROVIDER>",
+        description = "Check an OAuth provider's client ID, secret and token URL with a client-credentials token request, reporting the provider's error on failure; no credential is stored"
+    )]
+    pub struct TestProvider {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for TestProvider {
+        type Input = TestProviderInput;
+        type Output = OAuthProviderTest;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            self.deps.oauth_client.test_provider(&input.provider).await
+        }
+    }
+}
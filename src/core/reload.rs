@@ -0,0 +1,271 @@
+//! Hot reload for development (`serve --reload`)
+//!
+//! A [`Reloader`] polls the config file, the local registry file and the flows
+//! directory, and applies changes to the running server without touching its
+//! listeners:
+//!
+//! - A changed flow file is parsed and validated; if its flow is deployed and
+//!   the file carries a new version, that version is deployed. A deployed flow
+//!   edited without a version bump is reported as rejected. Drafts are read
+//!   from disk on every run already, so they need nothing.
+//! - A changed config or registry file reloads the registry tools. Other config
+//!   settings are wired into the running server and apply after a restart.
+//!
+//! Invalid changes are logged and skipped; the previous flow version and
+//! config stay in effect.
+
+use super::{Dependencies, OperationRegistry};
+use crate::config::Config;
+use crate::model::FlowName;
+use crate::storage::flows::FLOW_EXTENSION;
+use crate::{BeemFlowError, Result};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// How often watched files are checked for changes
+pub const DEFAULT_RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What one check applied
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReloadReport {
+    /// Flows deployed at a new version
+    pub deployed: Vec<String>,
+    /// Flow files that changed but were left as they were, with the reason
+    pub rejected: Vec<(String, String)>,
+    /// Whether the registry tools were reloaded
+    pub tools_reloaded: bool,
+}
+
+/// Watches the server's config and flows and applies changes
+pub struct Reloader {
+    deps: Dependencies,
+    operations: OperationRegistry,
+    config_path: PathBuf,
+    /// Config as last loaded from `config_path`
+    file_config: Value,
+    /// Content hash of every watched file
+    files: HashMap<PathBuf, u64>,
+}
+
+impl Reloader {
+    /// Watch `config_path` and the flows and local registry of `deps`
+    ///
+    /// Files present now are the baseline; only later changes are applied.
+    pub async fn new(deps: Dependencies, config_path: impl Into<PathBuf>) -> Self {
+        let config_path = config_path.into();
+        let file_config = load_file_config(&config_path)
+            .map(|config| config_value(&config))
+            .unwrap_or(Value::Null);
+        let mut reloader = Self {
+            operations: OperationRegistry::new(deps.clone()),
+            deps,
+            config_path,
+            file_config,
+            files: HashMap::new(),
+        };
+        reloader.files = reloader.scan().await;
+        reloader
+    }
+
+    /// Check for changes every `interval` until `cancel` fires
+    pub async fn run(mut self, interval: Duration, cancel: CancellationToken) {
+        tracing::info!(
+            "Watching {} and {} for changes",
+            self.config_path.display(),
+            self.flows_dir().display()
+        );
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep(interval) => {}
+            }
+            self.check().await;
+        }
+    }
+
+    /// Apply the changes made since the last check
+    pub async fn check(&mut self) -> ReloadReport {
+        let current = self.scan().await;
+        let changed: Vec<&PathBuf> = current
+            .iter()
+            .filter(|(path, hash)| self.files.get(*path) != Some(hash))
+            .map(|(path, _)| path)
+            .collect();
+
+        let mut report = ReloadReport::default();
+        let registry_path = self.deps.registry_manager.local_registry_path();
+        let config_changed = changed.contains(&&self.config_path)
+            || (self.files.contains_key(&self.config_path)
+                && !current.contains_key(&self.config_path));
+        if config_changed {
+            report.tools_reloaded = self.reload_config().await;
+        } else if changed.contains(&&registry_path) {
+            let unloaded = self.deps.engine.reload_registry_tools().await;
+            tracing::info!("Registry changed; reloaded tools ({} unloaded)", unloaded);
+            report.tools_reloaded = true;
+        }
+
+        let flows_dir = self.flows_dir();
+        for path in changed.iter().filter(|path| path.starts_with(&flows_dir)) {
+            let Some(name) = flow_name_of(path) else {
+                continue;
+            };
+            match self.reload_flow(path, &name).await {
+                Ok(Some(version)) => {
+                    tracing::info!("Reloaded flow '{}': deployed v{}", name, version);
+                    report.deployed.push(name.to_string());
+                }
+                Ok(None) => tracing::debug!("Flow '{}' changed", name),
+                Err(e) => {
+                    tracing::error!("Keeping previous version of flow '{}': {}", name, e);
+                    report.rejected.push((name.to_string(), e.to_string()));
+                }
+            }
+        }
+
+        self.files = current;
+        report
+    }
+
+    /// Re-read the config file, reloading registry tools if it is valid
+    async fn reload_config(&mut self) -> bool {
+        let config = match load_file_config(&self.config_path) {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::error!(
+                    "Ignoring invalid config {}: {}",
+                    self.config_path.display(),
+                    e
+                );
+                return false;
+            }
+        };
+
+        let value = config_value(&config);
+        let restart_needed = changed_keys(&self.file_config, &value);
+        self.file_config = value;
+
+        let unloaded = self.deps.engine.reload_registry_tools().await;
+        tracing::info!("Config reloaded; reloaded tools ({} unloaded)", unloaded);
+        if !restart_needed.is_empty() {
+            tracing::warn!(
+                "Config settings {} take effect after a restart",
+                restart_needed.into_iter().collect::<Vec<_>>().join(", ")
+            );
+        }
+        true
+    }
+
+    /// Validate a changed flow file and deploy it if it's deployed at another version
+    ///
+    /// Returns the version deployed, if any. Edits to the deployed version itself
+    /// are an error, so they show up as rejected rather than passing unnoticed.
+    async fn reload_flow(&self, path: &Path, name: &FlowName) -> Result<Option<String>> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let flow = super::parse_flow_content(&self.deps.config, &content)?;
+        crate::dsl::Validator::validate(&flow)?;
+
+        let Some(deployed) = self.deps.storage.get_deployed_version(name).await? else {
+            return Ok(None);
+        };
+        let version = flow
+            .version
+            .clone()
+            .ok_or_else(|| BeemFlowError::validation("Flow must have a version field to deploy"))?;
+        if version == deployed {
+            let current = self
+                .deps
+                .storage
+                .get_flow_version_content(name, &deployed)
+                .await?;
+            if current.as_deref() != Some(content.as_str()) {
+                return Err(BeemFlowError::validation(format!(
+                    "v{} is already deployed with other content; bump its version to redeploy",
+                    version
+                )));
+            }
+            return Ok(None);
+        }
+
+        self.operations
+            .execute("deploy_flow", serde_json::json!({ "name": name }))
+            .await?;
+        Ok(Some(version))
+    }
+
+    fn flows_dir(&self) -> PathBuf {
+        crate::config::get_flows_dir(&self.deps.config)
+    }
+
+    /// Content hash of every watched file that exists
+    async fn scan(&self) -> HashMap<PathBuf, u64> {
+        let mut paths = vec![
+            self.config_path.clone(),
+            self.deps.registry_manager.local_registry_path(),
+        ];
+        if let Ok(mut entries) = tokio::fs::read_dir(self.flows_dir()).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                if flow_name_of(&path).is_some() {
+                    paths.push(path);
+                }
+            }
+        }
+
+        let mut files = HashMap::new();
+        for path in paths {
+            if let Ok(content) = tokio::fs::read(&path).await {
+                let mut hasher = DefaultHasher::new();
+                content.hash(&mut hasher);
+                files.insert(path, hasher.finish());
+            }
+        }
+        files
+    }
+}
+
+/// Spawn a [`Reloader`] for `deps`, stopped by `cancel`
+pub async fn spawn_reloader(
+    deps: &Dependencies,
+    config_path: &Path,
+    cancel: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    let reloader = Reloader::new(deps.clone(), config_path).await;
+    tokio::spawn(reloader.run(DEFAULT_RELOAD_INTERVAL, cancel))
+}
+
+/// Name of the flow a `*.flow.yaml` file holds
+fn flow_name_of(path: &Path) -> Option<FlowName> {
+    let file_name = path.file_name()?.to_str()?;
+    FlowName::new(file_name.strip_suffix(FLOW_EXTENSION)?).ok()
+}
+
+/// Load and validate the config file
+fn load_file_config(path: &Path) -> Result<Config> {
+    let config = Config::load_and_inject(path)?;
+    config.validate()?;
+    Ok(config)
+}
+
+fn config_value(config: &Config) -> Value {
+    serde_json::to_value(config).unwrap_or(Value::Null)
+}
+
+/// Top-level settings that differ between two configs
+fn changed_keys(before: &Value, after: &Value) -> BTreeSet<String> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+    before
+        .keys()
+        .chain(after.keys())
+        .filter(|key| before.get(*key) != after.get(*key))
+        .filter(|key| key.as_str() != "registries")
+        .cloned()
+        .collect()
+}
@@ -3,9 +3,13 @@
 //! All operations for managing flow executions.
 
 use super::*;
+use crate::core::export::{ExportFormat, ExportOptions, write_export};
+use crate::engine::RunOptions;
+use crate::model::{FlowName, RunId, RunUsage};
+use crate::storage::{FlowRunStats, PageCursor, RunFilter};
 use beemflow_core_macros::{operation, operation_group};
+use chrono::{DateTime, Duration, Utc};
 use schemars::JsonSchema;
-use uuid::Uuid;
 
 #[operation_group(runs)]
 pub mod runs {
@@ -20,24 +24,48 @@ pub mod runs {
     pub struct ListInput {
         #[schemars(description = "Maximum number of runs to return (default: 100, max: 10000)")]
         pub limit: Option<usize>,
-        #[schemars(description = "Number of runs to skip (default: 0)")]
+        #[schemars(
+            description = "Deprecated, use cursor. Number of runs to skip; when set, a bare array of runs is returned"
+        )]
         pub offset: Option<usize>,
+        #[schemars(description = "next_cursor of the previous page; omit for the first page")]
+        pub cursor: Option<String>,
+        #[schemars(description = "Only runs carrying this label, as key=value")]
+        pub label: Option<String>,
     }
 
     #[derive(Deserialize, JsonSchema)]
     #[schemars(description = "Input for starting a new flow run")]
     pub struct StartInput {
         #[schemars(description = "Name of the flow to execute")]
-        pub flow_name: String,
+        pub flow_name: FlowName,
         #[schemars(description = "Event data to pass to the flow")]
         pub event: Option<HashMap<String, Value>>,
         #[schemars(description = "Whether this is a draft run")]
         pub draft: Option<bool>,
+        #[schemars(
+            description = "Environment whose vars overlay the flow's (default: config defaultEnvironment)"
+        )]
+        pub environment: Option<String>,
+        #[schemars(
+            description = "Run ID strategy: deterministic, random or client (default: config runIdStrategy, or client when idempotency_key is set)"
+        )]
+        pub run_id_strategy: Option<crate::engine::RunIdStrategy>,
+        #[schemars(
+            description = "Idempotency key for the client strategy; a flow runs at most once per key"
+        )]
+        pub idempotency_key: Option<String>,
+        #[schemars(description = "Labels to record on the run, merged over the flow's labels")]
+        pub labels: Option<HashMap<String, String>>,
+        #[schemars(
+            description = "Place in the run queue when limits.maxConcurrentRuns is reached; higher runs first (default: 0)"
+        )]
+        pub priority: Option<i32>,
     }
 
     #[derive(Serialize)]
     pub struct StartOutput {
-        pub run_id: String,
+        pub run_id: RunId,
         pub status: String,
         pub outputs: HashMap<String, Value>,
     }
@@ -45,8 +73,14 @@ pub mod runs {
     #[derive(Deserialize, JsonSchema)]
     #[schemars(description = "Input for retrieving run details")]
     pub struct GetInput {
-        #[schemars(description = "UUID of the run to retrieve")]
-        pub run_id: String,
+        #[schemars(description = "UUID of the run to retrieve", with = "String")]
+        pub run_id: RunId,
+        #[schemars(description = "Include the run's step records (default: false)")]
+        pub include_steps: Option<bool>,
+        #[schemars(
+            description = "Include the rendered (secret-redacted) tool inputs of each step; implies include_steps"
+        )]
+        pub include_inputs: Option<bool>,
     }
 
     #[derive(Deserialize, JsonSchema)]
@@ -58,12 +92,113 @@ pub mod runs {
         pub event: Option<HashMap<String, Value>>,
     }
 
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for re-executing one step of a finished run")]
+    pub struct RerunStepInput {
+        #[schemars(description = "UUID of the run", with = "String")]
+        pub run_id: RunId,
+        #[schemars(description = "ID of the top-level step to re-execute")]
+        pub step_id: String,
+        #[schemars(description = "Also re-execute the steps that depend on it")]
+        pub downstream: Option<bool>,
+        #[schemars(
+            description = "Load the flow from the filesystem instead of the deployed version"
+        )]
+        pub draft: Option<bool>,
+    }
+
+    #[derive(Serialize)]
+    pub struct RerunStepOutput {
+        pub run_id: RunId,
+        pub status: String,
+        /// Re-executed steps
+        pub steps: Vec<String>,
+        pub outputs: HashMap<String, Value>,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for aggregated run statistics")]
+    pub struct StatsInput {
+        #[schemars(
+            description = "Time window ending now, e.g. '24h', '7d', '30m' (default: '24h')"
+        )]
+        pub window: Option<String>,
+        #[schemars(description = "Only include runs of this flow")]
+        pub flow_name: Option<FlowName>,
+    }
+
+    #[derive(Serialize)]
+    pub struct FlowStatsRow {
+        #[serde(flatten)]
+        pub stats: FlowRunStats,
+        /// Succeeded / (succeeded + failed); None when no run has finished
+        pub success_rate: Option<f64>,
+    }
+
+    #[derive(Serialize)]
+    pub struct StatsTotals {
+        pub total: u64,
+        pub succeeded: u64,
+        pub failed: u64,
+        pub success_rate: Option<f64>,
+        pub usage: RunUsage,
+    }
+
+    #[derive(Serialize)]
+    pub struct StatsOutput {
+        pub window: String,
+        pub since: DateTime<Utc>,
+        pub flows: Vec<FlowStatsRow>,
+        pub totals: StatsTotals,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for exporting run history as flat rows")]
+    pub struct ExportInput {
+        #[schemars(description = "Row format: csv or jsonl (default: csv)")]
+        pub format: Option<ExportFormat>,
+        #[schemars(
+            description = "Only runs started within this window ending now, e.g. '30d' (default: all runs)"
+        )]
+        pub since: Option<String>,
+        #[schemars(
+            description = "Also export a row per step: to <output>.steps.<format>, or as a second section"
+        )]
+        pub include_steps: Option<bool>,
+        #[schemars(description = "File to write runs to (CLI only, default: stdout)")]
+        pub output: Option<String>,
+    }
+
+    impl ExportInput {
+        /// Export options, with the `since` window resolved against now
+        pub fn options(&self) -> Result<ExportOptions> {
+            Ok(ExportOptions {
+                format: self.format.unwrap_or_default(),
+                since: self
+                    .since
+                    .as_deref()
+                    .map(|since| crate::utils::shift_time(Utc::now(), parse_window(since)?, true))
+                    .transpose()?,
+                include_steps: self.include_steps.unwrap_or(false),
+            })
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct ExportOutput {
+        pub format: &'static str,
+        pub runs: u64,
+        pub steps: u64,
+        /// The exported rows, runs then any steps section
+        pub content: String,
+    }
+
     /// Start a new flow run
     #[operation(
         name = "start_run",
         input = StartInput,
         http = "POST /runs",
-        cli = "runs start <FLOW_NAME> [--event <JSON>] [--draft]",
+        cli = "runs start <FLOW_NAME> [--event <JSON>] [--draft] [--environment <ENVIRONMENT>] [--run_id_strategy <RUN_ID_STRATEGY>] [--idempotency_key <IDEMPOTENCY_KEY>] [--labels <JSON>] [--priority <PRIORITY>]",
         description = "Start a new flow run"
     )]
     pub struct Start {
@@ -80,16 +215,24 @@ pub mod runs {
             let result = self
                 .deps
                 .engine
-                .start(
+                .start_with(
                     &input.flow_name,
                     input.event.unwrap_or_default(),
                     input.draft.unwrap_or(false),
+                    &RunOptions {
+                        environment: input.environment,
+                        run_id_strategy: input.run_id_strategy,
+                        idempotency_key: input.idempotency_key,
+                        labels: input.labels.unwrap_or_default(),
+                        priority: input.priority.unwrap_or_default(),
+                        ..Default::default()
+                    },
                 )
                 .await?;
 
             // Format output for API
             Ok(StartOutput {
-                run_id: result.run_id.to_string(),
+                run_id: result.run_id,
                 status: "completed".to_string(),
                 outputs: result.outputs,
             })
@@ -101,7 +244,7 @@ pub mod runs {
         name = "get_run",
         input = GetInput,
         http = "GET /runs/{run_id}",
-        cli = "runs get <RUN_ID>",
+        cli = "runs get <RUN_ID> [--include_steps] [--include_inputs]",
         description = "Get run details by ID"
     )]
     pub struct Get {
@@ -114,19 +257,30 @@ pub mod runs {
         type Output = Value;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
-            let run_id = Uuid::parse_str(&input.run_id)
-                .map_err(|_| BeemFlowError::validation("Invalid run ID"))?;
+            let run_id = input.run_id;
 
             let mut run = self
                 .deps
                 .storage
                 .get_run(run_id)
                 .await?
-                .ok_or_else(|| not_found("Run", &input.run_id))?;
+                .filter(|run| {
+                    crate::auth::tenant::visible_to_current_tenant(run.tenant_id.as_deref())
+                })
+                .ok_or_else(|| not_found("Run", &run_id.to_string()))?;
 
-            // Fetch step execution details
-            let steps = self.deps.storage.get_steps(run_id).await?;
-            run.steps = if steps.is_empty() { None } else { Some(steps) };
+            // Step records only on request, as they can outweigh the run
+            let include_inputs = input.include_inputs.unwrap_or(false);
+            run.steps = None;
+            if input.include_steps.unwrap_or(false) || include_inputs {
+                let mut steps = self.deps.storage.get_steps(run_id).await?;
+                if !include_inputs {
+                    for step in &mut steps {
+                        step.inputs = None;
+                    }
+                }
+                run.steps = Some(steps);
+            }
 
             Ok(serde_json::to_value(run)?)
         }
@@ -137,8 +291,8 @@ pub mod runs {
         name = "list_runs",
         input = ListInput,
         http = "GET /runs",
-        cli = "runs list [--limit <LIMIT>] [--offset <OFFSET>]",
-        description = "List all runs with pagination"
+        cli = "runs list [--limit <LIMIT>] [--cursor <CURSOR>] [--label <LABEL>] [--all]",
+        description = "List runs, most recent first, a page at a time"
     )]
     pub struct List {
         pub deps: Arc<Dependencies>,
@@ -150,12 +304,182 @@ pub mod runs {
         type Output = Value;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
-            // Use provided values or defaults (limit: 100, offset: 0)
-            let limit = input.limit.unwrap_or(100);
-            let offset = input.offset.unwrap_or(0);
+            let limit = input.limit.unwrap_or(100).min(10_000);
+            let tenant_id = crate::auth::current_tenant();
+
+            // Offset pagination is kept for existing clients, with its old output
+            if let Some(offset) = input.offset {
+                if input.cursor.is_some() || input.label.is_some() {
+                    return Err(BeemFlowError::validation(
+                        "offset can't be combined with cursor or label",
+                    ));
+                }
+                let runs = match tenant_id {
+                    Some(ref tenant_id) => {
+                        self.deps
+                            .storage
+                            .list_runs_for_tenant(tenant_id, limit, offset)
+                            .await?
+                    }
+                    None => self.deps.storage.list_runs(limit, offset).await?,
+                };
+                return Ok(serde_json::to_value(runs)?);
+            }
+
+            let filter = RunFilter {
+                label: input.label.as_deref().map(parse_label).transpose()?,
+                tenant_id,
+            };
+            let after = input.cursor.as_deref().map(decode_cursor).transpose()?;
+            let runs = self
+                .deps
+                .storage
+                .list_runs_after(&filter, limit, after.as_ref())
+                .await?;
+            let page = Page::new(runs, limit, |run| PageCursor {
+                at: run.started_at,
+                key: run.id,
+            });
+            Ok(serde_json::to_value(page)?)
+        }
+    }
+
+    /// Aggregate run statistics per flow
+    #[operation(
+        name = "runs_stats",
+        input = StatsInput,
+        http = "GET /runs/stats",
+        cli = "runs stats [--window <WINDOW>] [--flow_name <FLOW_NAME>]",
+        description = "Per-flow run counts, success rate and p50/p95 duration over a time window"
+    )]
+    pub struct Stats {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for Stats {
+        type Input = StatsInput;
+        type Output = StatsOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let window = input.window.unwrap_or_else(|| "24h".to_string());
+            let since = crate::utils::shift_time(Utc::now(), parse_window(&window)?, true)?;
+
+            let stats = self
+                .deps
+                .storage
+                .run_stats(
+                    since,
+                    input.flow_name.as_deref(),
+                    crate::auth::current_tenant().as_deref(),
+                )
+                .await?;
+
+            let succeeded = stats.iter().map(|s| s.succeeded).sum();
+            let failed = stats.iter().map(|s| s.failed).sum();
+            let mut usage = RunUsage::default();
+            for flow in &stats {
+                usage.add(&flow.usage);
+            }
+            let totals = StatsTotals {
+                total: stats.iter().map(|s| s.total).sum(),
+                succeeded,
+                failed,
+                success_rate: success_rate(succeeded, failed),
+                usage,
+            };
+
+            let flows = stats
+                .into_iter()
+                .map(|stats| FlowStatsRow {
+                    success_rate: success_rate(stats.succeeded, stats.failed),
+                    stats,
+                })
+                .collect();
+
+            Ok(StatsOutput {
+                window,
+                since,
+                flows,
+                totals,
+            })
+        }
+    }
+
+    /// Re-execute one step of a finished run
+    #[operation(
+        name = "rerun_step",
+        input = RerunStepInput,
+        http = "POST /runs/{run_id}/steps/{step_id}/rerun",
+        cli = "runs rerun <RUN_ID> <STEP_ID> [--downstream] [--draft]",
+        description = "Re-execute one step of a finished run (and optionally its dependents) using the run's persisted outputs"
+    )]
+    pub struct RerunStep {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for RerunStep {
+        type Input = RerunStepInput;
+        type Output = RerunStepOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let result = self
+                .deps
+                .engine
+                .rerun_step(
+                    input.run_id,
+                    &input.step_id,
+                    input.downstream.unwrap_or(false),
+                    input.draft.unwrap_or(false),
+                )
+                .await?;
+
+            Ok(RerunStepOutput {
+                run_id: result.run_id,
+                status: "completed".to_string(),
+                steps: result.steps,
+                outputs: result.outputs,
+            })
+        }
+    }
+
+    /// Export run history as flat CSV or JSON lines
+    ///
+    /// Over HTTP, `GET /runs/export` streams the same rows as a download, and the
+    /// CLI streams them to `--output` or stdout.
+    #[operation(
+        name = "export_runs",
+        input = ExportInput,
+        cli = "runs export-data [--format <FORMAT>] [--since <SINCE>] [--include_steps] [--output <OUTPUT>]",
+        description = "Export runs (and optionally steps) as flat CSV or JSON lines for offline analysis"
+    )]
+    pub struct ExportRuns {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for ExportRuns {
+        type Input = ExportInput;
+        type Output = ExportOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            if input.output.is_some() {
+                return Err(BeemFlowError::validation(
+                    "output is only supported from the CLI",
+                ));
+            }
+            let options = input.options()?;
 
-            let runs = self.deps.storage.list_runs(limit, offset).await?;
-            Ok(serde_json::to_value(runs)?)
+            let mut content = Vec::new();
+            let counts =
+                write_export(self.deps.storage.as_ref(), &options, &mut content, None).await?;
+            Ok(ExportOutput {
+                format: options.format.extension(),
+                runs: counts.runs,
+                steps: counts.steps,
+                content: String::from_utf8_lossy(&content).into_owned(),
+            })
         }
     }
 
@@ -190,3 +514,29 @@ pub mod runs {
         }
     }
 }
+
+/// Parse a stats window like "30m", "24h" or "7d"
+fn parse_window(window: &str) -> Result<Duration> {
+    crate::utils::parse_duration(window).map_err(|_| {
+        BeemFlowError::validation(format!(
+            "Invalid window '{}': expected a number followed by m, h or d (e.g. '7d')",
+            window
+        ))
+    })
+}
+
+/// Parse a `key=value` label filter
+fn parse_label(label: &str) -> Result<(String, String)> {
+    match label.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(BeemFlowError::validation(format!(
+            "Invalid label '{}': expected key=value",
+            label
+        ))),
+    }
+}
+
+fn success_rate(succeeded: u64, failed: u64) -> Option<f64> {
+    let finished = succeeded + failed;
+    (finished > 0).then(|| succeeded as f64 / finished as f64)
+}
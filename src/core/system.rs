@@ -199,6 +199,531 @@ pub mod system {
         }
     }
 
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for deleting unreferenced blobs")]
+    pub struct GcBlobsInput {
+        #[schemars(description = "Report the blobs that would be deleted without deleting them")]
+        pub dry_run: Option<bool>,
+        #[schemars(
+            description = "Keep blobs younger than this many seconds, whose runs may not be saved yet (default: 3600)"
+        )]
+        pub min_age_secs: Option<u64>,
+    }
+
+    /// Delete blobs that no retained run references
+    #[operation(
+        name = "gc_blobs",
+        input = GcBlobsInput,
+        http = "POST /system/blobs/gc",
+        cli = "system blobs gc [--dry_run] [--min_age_secs <MIN_AGE_SECS>]",
+        description = "Delete blob objects no longer referenced by any retained run"
+    )]
+    pub struct GcBlobs {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for GcBlobs {
+        type Input = GcBlobsInput;
+        type Output = Value;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            const DEFAULT_MIN_AGE_SECS: u64 = 3600;
+
+            // Every configured store, so each blob is deleted through the store that wrote it
+            let stores =
+                crate::blob::BlobStores::from_config(&self.deps.config, self.deps.storage.clone());
+
+            let min_age = i64::try_from(input.min_age_secs.unwrap_or(DEFAULT_MIN_AGE_SECS))
+                .ok()
+                .and_then(chrono::TimeDelta::try_seconds)
+                .ok_or_else(|| BeemFlowError::validation("min_age_secs is too large"))?;
+            let older_than = crate::utils::shift_time(chrono::Utc::now(), min_age, true)?;
+            let dry_run = input.dry_run.unwrap_or(false);
+            let collected = stores.collect_garbage(older_than, dry_run).await?;
+
+            Ok(serde_json::json!({
+                "dry_run": dry_run,
+                "deleted": collected.len(),
+                "blobs": collected
+                    .iter()
+                    .map(|record| serde_json::json!({
+                        "url": crate::blob::blob_url(record.id),
+                        "store": record.store,
+                        "location": record.location,
+                        "created_at": record.created_at,
+                    }))
+                    .collect::<Vec<_>>(),
+            }))
+        }
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for cleaning up orphaned paused runs")]
+    pub struct SystemGcInput {
+        #[schemars(description = "Report what would be removed without removing it")]
+        pub dry_run: Option<bool>,
+    }
+
+    /// Remove paused runs and subscriptions that can never be resumed
+    #[operation(
+        name = "system_gc",
+        input = SystemGcInput,
+        http = "POST /system/gc",
+        cli = "system gc [--dry_run]",
+        description = "Remove paused runs whose flow no longer exists and paused entries whose run is gone"
+    )]
+    pub struct SystemGc {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for SystemGc {
+        type Input = SystemGcInput;
+        type Output = Value;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let dry_run = input.dry_run.unwrap_or(false);
+            let gc = self
+                .deps
+                .engine
+                .collect_orphaned_paused_runs(dry_run)
+                .await?;
+
+            Ok(serde_json::json!({
+                "dry_run": dry_run,
+                "paused_runs": gc.paused_runs.len(),
+                "subscriptions": gc.subscriptions.len(),
+                "run_ids": gc.paused_runs,
+            }))
+        }
+    }
+
+    /// Show the configuration this process loaded, secrets redacted
+    #[operation(
+        name = "system_config",
+        input = EmptyInput,
+        http = "GET /config",
+        cli = "system config",
+        description = "Show the effective configuration (after environment injection, with defaults) with secrets redacted"
+    )]
+    pub struct SystemConfig {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for SystemConfig {
+        type Input = EmptyInput;
+        type Output = Value;
+
+        async fn execute(&self, _input: Self::Input) -> Result<Self::Output> {
+            self.deps.config.redacted()
+        }
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for checking every subsystem")]
+    pub struct CheckInput {
+        #[schemars(description = "Seconds each check may take (default: 10, max: 300)")]
+        pub timeout_secs: Option<u64>,
+    }
+
+    /// Result of checking one subsystem
+    #[derive(Debug, Serialize)]
+    pub struct SubsystemCheck {
+        /// Subsystem checked: storage, blob, event_bus, mcp:{server} or registry:{name}
+        pub name: String,
+        /// Whether a failure fails the whole check
+        pub critical: bool,
+        pub ok: bool,
+        pub latency_ms: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    pub struct CheckOutput {
+        /// Whether a non-critical check failed
+        pub degraded: bool,
+        pub checks: Vec<SubsystemCheck>,
+    }
+
+    /// Run `check`, timing it and failing it after `timeout`
+    async fn timed_check(
+        name: String,
+        critical: bool,
+        timeout: std::time::Duration,
+        check: impl std::future::Future<Output = Result<()>>,
+    ) -> SubsystemCheck {
+        let started = std::time::Instant::now();
+        let result = tokio::time::timeout(timeout, check)
+            .await
+            .unwrap_or_else(|_| {
+                Err(BeemFlowError::internal(format!(
+                    "no response within {}s",
+                    timeout.as_secs()
+                )))
+            });
+        SubsystemCheck {
+            name,
+            critical,
+            ok: result.is_ok(),
+            latency_ms: started.elapsed().as_millis() as u64,
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+
+    /// Write, read back and delete a probe blob
+    async fn check_blob(config: Option<&crate::config::BlobConfig>) -> Result<()> {
+        let config = config.map(crate::blob::BlobConfig::from);
+        let store = crate::blob::new_backend_blob_store(config.as_ref()).await?;
+        let probe = uuid::Uuid::new_v4().as_bytes().to_vec();
+        let url = store.put(probe.clone(), None, None).await?;
+        let read = store.get(&url).await;
+        store.delete(&url).await?;
+        if read? != probe {
+            return Err(BeemFlowError::internal(
+                "blob read back differs from what was written",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Publish a probe event and wait for a subscriber to receive it
+    ///
+    /// Each check leaves its (idle) subscriber behind, as the bus can't unsubscribe.
+    async fn check_event_bus(bus: &Arc<dyn crate::event::EventBus>) -> Result<()> {
+        let topic = format!(
+            "{}.{}",
+            crate::constants::EVENT_TOPIC_SYSTEM_CHECK,
+            uuid::Uuid::new_v4()
+        );
+        let (sender, received) = tokio::sync::oneshot::channel();
+        let sender = parking_lot::Mutex::new(Some(sender));
+        bus.subscribe(
+            &topic,
+            Arc::new(move |_event| {
+                if let Some(sender) = sender.lock().take() {
+                    let _ = sender.send(());
+                }
+            }),
+        )
+        .await?;
+        bus.publish_value(&topic, serde_json::json!({})).await?;
+        received
+            .await
+            .map_err(|_| BeemFlowError::internal("probe event was not delivered"))
+    }
+
+    /// Deep check of every subsystem the server depends on
+    #[operation(
+        name = "system_check",
+        input = CheckInput,
+        http = "GET /system/check",
+        cli = "system check [--timeout_secs <TIMEOUT_SECS>]",
+        description = "Check storage, blob store, event bus, MCP servers and remote registries, failing if a critical one is down"
+    )]
+    pub struct SystemCheck {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for SystemCheck {
+        type Input = CheckInput;
+        type Output = CheckOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let timeout =
+                std::time::Duration::from_secs(input.timeout_secs.unwrap_or(10).clamp(1, 300));
+            let deps = &self.deps;
+
+            let mut checks = vec![
+                timed_check("storage".to_string(), true, timeout, async {
+                    deps.storage.list_runs(1, 0).await.map(|_| ())
+                })
+                .await,
+                timed_check(
+                    "blob".to_string(),
+                    true,
+                    timeout,
+                    check_blob(deps.config.blob.as_ref()),
+                )
+                .await,
+                timed_check(
+                    "event_bus".to_string(),
+                    true,
+                    timeout,
+                    check_event_bus(deps.engine.event_bus()),
+                )
+                .await,
+            ];
+
+            // MCP servers, registered the way the MCP proxy does
+            let mcp_adapter = deps.engine.mcp_adapter();
+            let mut servers: Vec<_> = deps.config.mcp_servers.iter().flatten().collect();
+            servers.sort_by_key(|(name, _)| name.as_str());
+            for (name, config) in servers {
+                mcp_adapter.register_server(name.clone(), config.into());
+                let started = std::time::Instant::now();
+                let status = mcp_adapter.manager().probe(name, timeout).await;
+                checks.push(SubsystemCheck {
+                    name: format!("mcp:{}", name),
+                    critical: false,
+                    ok: status.reachable,
+                    latency_ms: started.elapsed().as_millis() as u64,
+                    error: status.error,
+                });
+            }
+
+            for registry in deps.config.registries.iter().flatten() {
+                if registry.registry_type != "remote" {
+                    continue;
+                }
+                let Some(url) = registry.url.as_deref() else {
+                    continue;
+                };
+                let name = registry.name.as_deref().unwrap_or("remote");
+                let remote = crate::registry::RemoteRegistry::new(url, name);
+                checks.push(
+                    timed_check(format!("registry:{}", name), false, timeout, async {
+                        remote.list_servers().await.map(|_| ())
+                    })
+                    .await,
+                );
+            }
+
+            let failed: Vec<String> = checks
+                .iter()
+                .filter(|check| check.critical && !check.ok)
+                .map(|check| {
+                    format!(
+                        "{} ({})",
+                        check.name,
+                        check.error.as_deref().unwrap_or_default()
+                    )
+                })
+                .collect();
+            if !failed.is_empty() {
+                return Err(BeemFlowError::internal(format!(
+                    "Critical subsystem checks failed: {}",
+                    failed.join("; ")
+                )));
+            }
+
+            Ok(CheckOutput {
+                degraded: checks.iter().any(|check| !check.ok),
+                checks,
+            })
+        }
+    }
+
+    /// Stop accepting new runs ahead of a shutdown
+    ///
+    /// The flag lives in this server's engine, so there is no CLI command: a
+    /// CLI process would only drain itself.
+    #[operation(
+        name = "drain",
+        input = EmptyInput,
+        http = "POST /admin/drain",
+        description = "Reject new runs (503) and report not ready on /readyz while in-flight runs finish"
+    )]
+    pub struct Drain {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for Drain {
+        type Input = EmptyInput;
+        type Output = Value;
+
+        async fn execute(&self, _input: Self::Input) -> Result<Self::Output> {
+            let was_draining = self.deps.engine.set_draining(true);
+            if !was_draining {
+                tracing::info!("Draining: new runs are rejected until undrained");
+            }
+            Ok(serde_json::json!({
+                "draining": true,
+                "changed": !was_draining,
+            }))
+        }
+    }
+
+    /// Accept new runs again after a drain
+    #[operation(
+        name = "undrain",
+        input = EmptyInput,
+        http = "POST /admin/undrain",
+        description = "Accept new runs again after a drain"
+    )]
+    pub struct Undrain {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for Undrain {
+        type Input = EmptyInput;
+        type Output = Value;
+
+        async fn execute(&self, _input: Self::Input) -> Result<Self::Output> {
+            let was_draining = self.deps.engine.set_draining(false);
+            if was_draining {
+                tracing::info!("Undrained: accepting new runs");
+            }
+            Ok(serde_json::json!({
+                "draining": false,
+                "changed": was_draining,
+            }))
+        }
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for verifying the run history integrity chain")]
+    pub struct VerifyIntegrityInput {
+        #[schemars(description = "Only verify the chain of this flow")]
+        pub flow: Option<String>,
+    }
+
+    /// Walk the tamper-evident run history and report the first divergence
+    #[operation(
+        name = "verify_integrity",
+        input = VerifyIntegrityInput,
+        http = "GET /system/integrity",
+        cli = "system verify-integrity [--flow <FLOW>]",
+        description = "Verify the run history integrity chain (storage.integrityChain) and report the first divergence"
+    )]
+    pub struct VerifyIntegrity {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for VerifyIntegrity {
+        type Input = VerifyIntegrityInput;
+        type Output = Value;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            // The chains cover every tenant's runs, so only unscoped callers walk them
+            if crate::auth::current_tenant().is_some() {
+                return Err(BeemFlowError::auth(
+                    "verify_integrity covers every tenant's runs and is not available to tenant-scoped clients",
+                ));
+            }
+            let report = self
+                .deps
+                .storage
+                .verify_integrity(input.flow.as_deref())
+                .await?;
+
+            Ok(serde_json::json!({
+                "enabled": self.deps.config.storage.integrity_chain,
+                "intact": report.is_intact(),
+                "flows_checked": report.flows_checked,
+                "entries_checked": report.entries_checked,
+                "divergence": report.divergence,
+            }))
+        }
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for listing registered operations")]
+    pub struct ListOperationsInput {
+        #[schemars(
+            description = "Fail if any operation is not reachable on a surface it declares (HTTP route, CLI command, MCP tool)"
+        )]
+        pub check_parity: Option<bool>,
+    }
+
+    /// List every registered operation and the surfaces it is exposed on
+    #[operation(
+        name = "list_operations",
+        input = ListOperationsInput,
+        http = "GET /system/operations",
+        cli = "system operations [--check_parity]",
+        description = "List registered operations with their HTTP, CLI and MCP surfaces"
+    )]
+    pub struct ListOperations {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for ListOperations {
+        type Input = ListOperationsInput;
+        type Output = Value;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let registry = OperationRegistry::new((*self.deps).clone());
+            let metadata = registry.get_all_metadata();
+
+            let mcp_tools: std::collections::HashSet<String> =
+                crate::mcp::operation_tools(self.deps.clone())
+                    .into_iter()
+                    .map(|tool| tool.name.to_string())
+                    .collect();
+
+            // Resolve each declared surface against the routers and command tree
+            // the server and CLI actually build
+            let check_parity = input.check_parity.unwrap_or(false);
+            let (routed, dispatchable) = if check_parity {
+                (
+                    crate::http::routed_operations(self.deps.clone(), metadata).await,
+                    crate::cli::dispatchable_operations(&registry),
+                )
+            } else {
+                Default::default()
+            };
+
+            let mut names: Vec<&String> = metadata.keys().collect();
+            names.sort_by_key(|name| (metadata[*name].group, name.as_str()));
+
+            let mut operations = Vec::with_capacity(names.len());
+            let mut out_of_parity = Vec::new();
+            for name in names {
+                let meta = &metadata[name];
+                let mcp_tool = mcp_tools.contains(&format!("beemflow_{}", name));
+
+                let mut entry = serde_json::json!({
+                    "name": name,
+                    "group": meta.group,
+                    "description": meta.description,
+                    "http_method": meta.http_method,
+                    "http_path": meta.http_path,
+                    "cli_pattern": meta.cli_pattern,
+                    "mcp_tool": mcp_tool,
+                    "schema": meta.schema,
+                });
+
+                if check_parity {
+                    let mut missing = Vec::new();
+                    if meta.http_path.is_some() && !routed.contains(name) {
+                        missing.push("http");
+                    }
+                    if meta.cli_pattern.is_some() && !dispatchable.contains(name) {
+                        missing.push("cli");
+                    }
+                    if !mcp_tool {
+                        missing.push("mcp");
+                    }
+                    if !missing.is_empty() {
+                        out_of_parity.push(format!("{} ({})", name, missing.join(", ")));
+                    }
+                    entry["missing"] = serde_json::json!(missing);
+                }
+                operations.push(entry);
+            }
+
+            if !out_of_parity.is_empty() {
+                return Err(BeemFlowError::validation(format!(
+                    "Operations missing a declared surface: {}",
+                    out_of_parity.join("; ")
+                )));
+            }
+
+            Ok(serde_json::json!({
+                "operations": operations,
+                "total": operations.len(),
+            }))
+        }
+    }
+
     /// Generate OpenAPI 3.0 specification from all operations
     #[operation(
         name = "generate_openapi",
@@ -0,0 +1,233 @@
+//! Live view of a run's steps (`runs tail`)
+//!
+//! Each top-level step prints a line when it starts and when it finishes, with
+//! its status and how long it took; tailing stops once the run succeeds, fails
+//! or is skipped. Steps are followed through `step.status` events when the
+//! event bus is shared with the process executing the run. Otherwise, as with
+//! the CLI's in-process bus and a run executing in a server, the run's step
+//! records are polled. Storage is read on every poll either way, so a missed
+//! event only delays a line.
+
+use crate::constants::{
+    EVENT_TOPIC_RUN_FAILED, EVENT_TOPIC_RUN_SUCCEEDED, EVENT_TOPIC_STEP_STATUS,
+};
+use crate::event::{EventBus, EventEnvelope};
+use crate::model::{Run, RunStatus, StepRun, StepStatus};
+use crate::storage::Storage;
+use crate::{BeemFlowError, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Default time between reads of the run's records
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Follow run `run_id`, writing a line per step start and finish to `out`
+///
+/// Subscribes to `event_bus` when given; pass it only if the bus reaches the
+/// process executing the run. Returns the run's final status.
+pub async fn tail_run<W: AsyncWrite + Unpin>(
+    storage: &dyn Storage,
+    event_bus: Option<&Arc<dyn EventBus>>,
+    run_id: Uuid,
+    poll_interval: Duration,
+    out: &mut W,
+) -> Result<RunStatus> {
+    let mut events = match event_bus {
+        Some(event_bus) => Some(subscribe(event_bus.as_ref(), run_id).await?),
+        None => None,
+    };
+    let mut tail = Tail::default();
+
+    loop {
+        let run = storage
+            .get_run(run_id)
+            .await?
+            .ok_or_else(|| BeemFlowError::not_found("Run", run_id.to_string()))?;
+        let mut steps = storage.get_steps(run_id).await?;
+        steps.sort_by_key(|step| step.started_at);
+        for step in &steps {
+            tail.step_recorded(step, out).await?;
+        }
+        if matches!(
+            run.status,
+            RunStatus::Succeeded | RunStatus::Failed | RunStatus::Skipped
+        ) {
+            tail.run_finished(&run, out).await?;
+            return Ok(run.status);
+        }
+
+        let Some(ref mut receiver) = events else {
+            tokio::time::sleep(poll_interval).await;
+            continue;
+        };
+        // Print events as they arrive until the run ends or it's time to poll
+        let poll = tokio::time::sleep(poll_interval);
+        tokio::pin!(poll);
+        loop {
+            tokio::select! {
+                event = receiver.recv() => match event {
+                    Some(event) if event.topic == EVENT_TOPIC_STEP_STATUS => {
+                        tail.step_event(&event, out).await?;
+                    }
+                    // The run ended, or the subscription did; read where it stands
+                    _ => break,
+                },
+                _ = &mut poll => break,
+            }
+        }
+    }
+}
+
+/// Events about run `run_id`, from now on
+async fn subscribe(
+    event_bus: &dyn EventBus,
+    run_id: Uuid,
+) -> Result<mpsc::UnboundedReceiver<EventEnvelope>> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let run_id = run_id.to_string();
+    for (topic, run_key) in [
+        (EVENT_TOPIC_STEP_STATUS, "run_id"),
+        (EVENT_TOPIC_RUN_SUCCEEDED, "id"),
+        (EVENT_TOPIC_RUN_FAILED, "id"),
+    ] {
+        let sender = sender.clone();
+        let run_id = run_id.clone();
+        event_bus
+            .subscribe(
+                topic,
+                Arc::new(move |event: EventEnvelope| {
+                    if event.payload[run_key].as_str() == Some(run_id.as_str()) {
+                        let _ = sender.send(event);
+                    }
+                }),
+            )
+            .await?;
+    }
+    Ok(receiver)
+}
+
+/// Steps printed so far, so each start and finish is printed once
+#[derive(Default)]
+struct Tail {
+    started: HashMap<String, DateTime<Utc>>,
+    finished: HashSet<String>,
+}
+
+impl Tail {
+    async fn step_recorded<W: AsyncWrite + Unpin>(
+        &mut self,
+        step: &StepRun,
+        out: &mut W,
+    ) -> Result<()> {
+        let name = step.step_name.to_string();
+        self.start(&name, step.started_at, out).await?;
+        if let (true, Some(ended_at)) = (finished(step.status), step.ended_at) {
+            self.finish(&name, step.status, ended_at, step.error.as_deref(), out)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn step_event<W: AsyncWrite + Unpin>(
+        &mut self,
+        event: &EventEnvelope,
+        out: &mut W,
+    ) -> Result<()> {
+        let Some(name) = event.payload["step_id"].as_str() else {
+            return Ok(());
+        };
+        let Ok(status) = serde_json::from_value::<StepStatus>(event.payload["status"].clone())
+        else {
+            return Ok(());
+        };
+        self.start(name, event.timestamp, out).await?;
+        if finished(status) {
+            let error = event.payload["error"].as_str();
+            self.finish(name, status, event.timestamp, error, out)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn start<W: AsyncWrite + Unpin>(
+        &mut self,
+        name: &str,
+        at: DateTime<Utc>,
+        out: &mut W,
+    ) -> Result<()> {
+        if self.started.contains_key(name) {
+            return Ok(());
+        }
+        self.started.insert(name.to_string(), at);
+        write_line(out, at, &format!("{}  started", name)).await
+    }
+
+    async fn finish<W: AsyncWrite + Unpin>(
+        &mut self,
+        name: &str,
+        status: StepStatus,
+        at: DateTime<Utc>,
+        error: Option<&str>,
+        out: &mut W,
+    ) -> Result<()> {
+        if !self.finished.insert(name.to_string()) {
+            return Ok(());
+        }
+        let mut line = format!("{}  {}", name, status_name(status));
+        if let Some(started_at) = self.started.get(name) {
+            line.push_str(&format!(" ({})", elapsed(*started_at, at)));
+        }
+        if let Some(error) = error {
+            line.push_str(&format!(": {}", error));
+        }
+        write_line(out, at, &line).await
+    }
+
+    async fn run_finished<W: AsyncWrite + Unpin>(&self, run: &Run, out: &mut W) -> Result<()> {
+        let ended_at = run.ended_at.unwrap_or_else(Utc::now);
+        let line = format!(
+            "run {} ({})",
+            status_name(run.status),
+            elapsed(run.started_at, ended_at)
+        );
+        write_line(out, ended_at, &line).await
+    }
+}
+
+/// Whether a step with `status` is done
+fn finished(status: StepStatus) -> bool {
+    matches!(
+        status,
+        StepStatus::Succeeded | StepStatus::Failed | StepStatus::Skipped
+    )
+}
+
+/// Lowercase name of a status, as in `succeeded`
+fn status_name<S: serde::Serialize>(status: S) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_lowercase))
+        .unwrap_or_default()
+}
+
+/// Time between `from` and `to`, as in `1.25s`
+fn elapsed(from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    let millis = (to - from).num_milliseconds().max(0);
+    format!("{}.{:02}s", millis / 1000, millis % 1000 / 10)
+}
+
+async fn write_line<W: AsyncWrite + Unpin>(
+    out: &mut W,
+    at: DateTime<Utc>,
+    text: &str,
+) -> Result<()> {
+    let line = format!("{}  {}\n", at.format("%H:%M:%S%.3f"), text);
+    out.write_all(line.as_bytes()).await?;
+    out.flush().await?;
+    Ok(())
+}
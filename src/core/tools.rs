@@ -3,6 +3,8 @@
 //! All operations for managing tools and adapters.
 
 use super::*;
+use crate::registry::RegistryEntry;
+use crate::utils::mask_secrets;
 use beemflow_core_macros::{operation, operation_group};
 use schemars::JsonSchema;
 
@@ -10,10 +12,22 @@ use schemars::JsonSchema;
 pub mod tools {
     use super::*;
 
+    /// Default number of results returned by a tool search
+    const DEFAULT_SEARCH_LIMIT: usize = 20;
+
     #[derive(Deserialize, JsonSchema)]
     #[schemars(description = "Empty input (no parameters required)")]
     pub struct EmptyInput {}
 
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for listing tools")]
+    pub struct ListInput {
+        #[schemars(description = "Fuzzy search query (optional, lists all tools if omitted)")]
+        pub q: Option<String>,
+        #[schemars(description = "Maximum number of search results (default: 20)")]
+        pub limit: Option<usize>,
+    }
+
     #[derive(Serialize)]
     pub struct ListOutput {
         pub tools: Vec<serde_json::Value>,
@@ -26,11 +40,52 @@ pub mod tools {
         pub name: String,
     }
 
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for describing a tool")]
+    pub struct DescribeInput {
+        #[schemars(description = "Name of the tool or MCP server to describe")]
+        pub name: String,
+        #[serde(default)]
+        #[schemars(
+            description = "'manifest' returns the bare registry entry, as GET /tools/{name} did before describe_tool"
+        )]
+        pub format: Option<DescribeFormat>,
+    }
+
+    /// Response shape of `describe_tool`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+    #[serde(rename_all = "lowercase")]
+    pub enum DescribeFormat {
+        /// The entry plus derived fields such as `requires_oauth` (default)
+        Description,
+        /// The masked registry entry alone, same as `get_tool_manifest`
+        Manifest,
+    }
+
+    #[derive(Serialize)]
+    pub struct DescribeOutput {
+        /// Registry entry with secret header and env values masked
+        #[serde(flatten)]
+        pub entry: RegistryEntry,
+        /// Whether calls need an OAuth connection (`$oauth:` references)
+        pub requires_oauth: bool,
+    }
+
     #[derive(Deserialize, JsonSchema)]
     #[schemars(description = "Input for searching tools")]
     pub struct SearchInput {
         #[schemars(description = "Search query (optional, returns all if omitted)")]
         pub query: Option<String>,
+        #[schemars(description = "Maximum number of results (default: 20)")]
+        pub limit: Option<usize>,
+    }
+
+    #[derive(Serialize)]
+    pub struct SearchOutput {
+        /// Matching tools, best first, each with its match `score` (0-1)
+        pub tools: Vec<Value>,
+        /// Number of matches before `limit` was applied
+        pub total: usize,
     }
 
     #[derive(Deserialize, JsonSchema)]
@@ -40,6 +95,19 @@ pub mod tools {
         pub name: Option<String>,
         #[schemars(description = "Tool manifest as JSON (alternative to name)")]
         pub manifest: Option<Value>,
+        #[schemars(
+            description = "URL of a tool manifest or OpenAPI spec to install (alternative to name)"
+        )]
+        pub from_url: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    pub struct InstallOutput {
+        pub status: String,
+        /// Where the entries came from: registry, manifest, url or openapi
+        pub source: String,
+        /// Installed entries, with secret header and env values masked
+        pub entries: Vec<RegistryEntry>,
     }
 
     #[derive(Deserialize, JsonSchema)]
@@ -56,10 +124,10 @@ pub mod tools {
     /// List all tools
     #[operation(
         name = "list_tools",
-        input = EmptyInput,
+        input = ListInput,
         http = "GET /tools",
-        cli = "tools list",
-        description = "List all tools"
+        cli = "tools list [--q <Q>] [--limit <LIMIT>]",
+        description = "List all tools, or fuzzy-search them with q"
     )]
     pub struct List {
         pub deps: Arc<Dependencies>,
@@ -67,17 +135,24 @@ pub mod tools {
 
     #[async_trait]
     impl Operation for List {
-        type Input = EmptyInput;
+        type Input = ListInput;
         type Output = ListOutput;
 
-        async fn execute(&self, _input: Self::Input) -> Result<Self::Output> {
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            if let Some(query) = input.q {
+                let results = search(&self.deps, Some(query), input.limit).await?;
+                return Ok(ListOutput {
+                    tools: results.tools,
+                });
+            }
+
             let entries = self.deps.registry_manager.list_all_servers().await?;
 
             // Filter to just tools
             let tools: Vec<serde_json::Value> = entries
                 .into_iter()
                 .filter(|e| e.entry_type == "tool")
-                .map(|e| serde_json::to_value(e).unwrap_or_default())
+                .map(|e| mask_secrets(&serde_json::to_value(e).unwrap_or_default()))
                 .collect();
 
             Ok(ListOutput { tools })
@@ -88,7 +163,7 @@ pub mod tools {
     #[operation(
         name = "get_tool_manifest",
         input = GetManifestInput,
-        http = "GET /tools/{name}",
+        http = "GET /tools/{name}/manifest",
         cli = "tools get <NAME>",
         description = "Get tool manifest"
     )]
@@ -109,7 +184,51 @@ pub mod tools {
                 .await?
                 .ok_or_else(|| not_found("Tool", &input.name))?;
 
-            Ok(serde_json::to_value(entry)?)
+            Ok(mask_secrets(&serde_json::to_value(entry)?))
+        }
+    }
+
+    /// Describe a tool
+    #[operation(
+        name = "describe_tool",
+        input = DescribeInput,
+        http = "GET /tools/{name}",
+        cli = "tools describe <NAME>",
+        description = "Describe a tool's parameters, endpoint and auth requirements"
+    )]
+    pub struct Describe {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for Describe {
+        type Input = DescribeInput;
+        type Output = Value;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let entry = self
+                .deps
+                .registry_manager
+                .get_server(&input.name)
+                .await?
+                .ok_or_else(|| not_found("Tool", &input.name))?;
+
+            let requires_oauth = entry
+                .headers
+                .iter()
+                .chain(entry.env.iter())
+                .flat_map(|values| values.values())
+                .any(|v| v.contains("$oauth:"));
+
+            let masked = mask_secrets(&serde_json::to_value(entry)?);
+            if input.format == Some(DescribeFormat::Manifest) {
+                return Ok(masked);
+            }
+
+            Ok(serde_json::to_value(DescribeOutput {
+                entry: serde_json::from_value(masked)?,
+                requires_oauth,
+            })?)
         }
     }
 
@@ -118,8 +237,8 @@ pub mod tools {
         name = "search_tools",
         input = SearchInput,
         http = "GET /tools/search",
-        cli = "tools search [<QUERY>]",
-        description = "Search for tools"
+        cli = "tools search [<QUERY>] [--limit <LIMIT>]",
+        description = "Fuzzy-search tools by name and description"
     )]
     pub struct Search {
         pub deps: Arc<Dependencies>,
@@ -128,14 +247,219 @@ pub mod tools {
     #[async_trait]
     impl Operation for Search {
         type Input = SearchInput;
-        type Output = Value;
+        type Output = SearchOutput;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
-            let entries = self.deps.registry_manager.list_all_servers().await?;
-            let tools = filter_by_query(entries.into_iter(), "tool", &input.query);
+            search(&self.deps, input.query, input.limit).await
+        }
+    }
+
+    /// Rank tools against `query`, keeping the best `limit` (all tools if no query)
+    async fn search(
+        deps: &Dependencies,
+        query: Option<String>,
+        limit: Option<usize>,
+    ) -> Result<SearchOutput> {
+        let entries = deps.registry_manager.list_all_servers().await?;
+        let ranked = match query.as_deref().map(str::trim) {
+            Some(query) if !query.is_empty() => rank_by_query(entries.into_iter(), "tool", query),
+            _ => filter_by_query(entries.into_iter(), "tool", &None)
+                .into_iter()
+                .map(|e| (e, 1.0))
+                .collect(),
+        };
+
+        let total = ranked.len();
+        let tools = ranked
+            .into_iter()
+            .take(limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+            .map(|(entry, score)| {
+                let mut tool = mask_secrets(&serde_json::to_value(entry)?);
+                tool["score"] = serde_json::json!((score * 1000.0).round() / 1000.0);
+                Ok(tool)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(SearchOutput { tools, total })
+    }
 
-            Ok(serde_json::to_value(tools)?)
+    /// Convert an OpenAPI spec's operations into tool manifests
+    ///
+    /// Returns the API name and base URL used, from the spec unless given.
+    fn openapi_manifests(
+        openapi_spec: &Value,
+        api_name: Option<String>,
+        base_url: Option<String>,
+    ) -> (String, String, Vec<Value>) {
+        // Extract basic info
+        let api_name = api_name.unwrap_or_else(|| {
+            openapi_spec
+                .get("info")
+                .and_then(|info| info.get("title"))
+                .and_then(|title| title.as_str())
+                .unwrap_or("api")
+                .to_string()
+        });
+
+        let base_url = base_url.unwrap_or_else(|| {
+            openapi_spec
+                .get("servers")
+                .and_then(|servers| servers.as_array())
+                .and_then(|servers| servers.first())
+                .and_then(|server| server.get("url"))
+                .and_then(|url| url.as_str())
+                .unwrap_or("https://api.example.com")
+                .to_string()
+        });
+
+        // Convert to tool manifests (simplified implementation)
+        let mut manifests = Vec::new();
+
+        if let Some(paths) = openapi_spec.get("paths").and_then(|p| p.as_object()) {
+            for (path, path_item) in paths {
+                if let Some(path_obj) = path_item.as_object() {
+                    for (method, operation) in path_obj {
+                        if method != "parameters" {
+                            // Skip parameters key
+                            if let Some(op_obj) = operation.as_object() {
+                                // Generate tool name
+                                let tool_name = format!(
+                                    "{}.{}_{}",
+                                    api_name,
+                                    path.trim_start_matches('/').replace('/', "_"),
+                                    method
+                                );
+
+                                // Extract description
+                                let description_str = op_obj
+                                    .get("summary")
+                                    .or_else(|| op_obj.get("description"))
+                                    .and_then(|d| d.as_str())
+                                    .unwrap_or("API endpoint");
+
+                                // Create basic manifest
+                                let mut manifest = serde_json::Map::new();
+                                manifest.insert(
+                                    "type".to_string(),
+                                    serde_json::Value::String("tool".to_string()),
+                                );
+                                manifest.insert(
+                                    "name".to_string(),
+                                    serde_json::Value::String(tool_name.clone()),
+                                );
+                                manifest.insert(
+                                    "description".to_string(),
+                                    serde_json::Value::String(description_str.to_string()),
+                                );
+                                manifest.insert(
+                                    "kind".to_string(),
+                                    serde_json::Value::String("task".to_string()),
+                                );
+                                manifest.insert(
+                                    "endpoint".to_string(),
+                                    serde_json::Value::String(format!("{}{}", base_url, path)),
+                                );
+                                manifest.insert(
+                                    "method".to_string(),
+                                    serde_json::Value::String(method.to_uppercase()),
+                                );
+
+                                // Add basic parameters schema
+                                let mut properties = serde_json::Map::new();
+                                let mut required = Vec::new();
+
+                                // Add path parameters
+                                if let Some(params) =
+                                    path_obj.get("parameters").and_then(|p| p.as_array())
+                                {
+                                    for param in params {
+                                        if let Some(param_obj) = param.as_object()
+                                            && let Some(param_name) =
+                                                param_obj.get("name").and_then(|n| n.as_str())
+                                        {
+                                            let mut param_schema = serde_json::Map::new();
+                                            param_schema.insert(
+                                                "type".to_string(),
+                                                serde_json::Value::String("string".to_string()),
+                                            );
+
+                                            if let Some(param_desc) = param_obj
+                                                .get("description")
+                                                .and_then(|d| d.as_str())
+                                            {
+                                                param_schema.insert(
+                                                    "description".to_string(),
+                                                    serde_json::Value::String(
+                                                        param_desc.to_string(),
+                                                    ),
+                                                );
+                                            }
+
+                                            if param_obj
+                                                .get("required")
+                                                .and_then(|r| r.as_bool())
+                                                .unwrap_or(false)
+                                            {
+                                                required.push(serde_json::Value::String(
+                                                    param_name.to_string(),
+                                                ));
+                                            }
+
+                                            properties.insert(
+                                                param_name.to_string(),
+                                                serde_json::Value::Object(param_schema),
+                                            );
+                                        }
+                                    }
+                                }
+
+                                let mut parameters = serde_json::Map::new();
+                                parameters.insert(
+                                    "type".to_string(),
+                                    serde_json::Value::String("object".to_string()),
+                                );
+                                parameters.insert(
+                                    "properties".to_string(),
+                                    serde_json::Value::Object(properties),
+                                );
+                                parameters.insert(
+                                    "required".to_string(),
+                                    serde_json::Value::Array(required),
+                                );
+
+                                manifest.insert(
+                                    "parameters".to_string(),
+                                    serde_json::Value::Object(parameters),
+                                );
+
+                                manifests.push(serde_json::Value::Object(manifest));
+                            }
+                        }
+                    }
+                }
+            }
         }
+
+        (api_name, base_url, manifests)
+    }
+
+    /// Fetch a JSON document for `tools install --from_url`
+    async fn fetch_json(url: &str) -> Result<Value> {
+        let fetch_error = |e: reqwest::Error| {
+            let action = if e.is_decode() { "parse" } else { "fetch" };
+            BeemFlowError::Network(crate::error::NetworkError::Http(format!(
+                "Failed to {} {}: {}",
+                action, url, e
+            )))
+        };
+
+        reqwest::get(url)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(fetch_error)?
+            .json()
+            .await
+            .map_err(fetch_error)
     }
 
     /// Install a tool
@@ -143,21 +467,42 @@ pub mod tools {
         name = "install_tool",
         input = InstallInput,
         http = "POST /tools/install",
-        cli = "tools install <SOURCE>",
-        description = "Install a tool"
+        cli = "tools install [<NAME>] [--manifest <MANIFEST>] [--from_url <FROM_URL>]",
+        description = "Install a tool from the registry, an inline manifest, or a manifest or OpenAPI spec URL"
     )]
     pub struct Install {
         pub deps: Arc<Dependencies>,
     }
 
+    impl Install {
+        /// Validate `manifests` and write them all to the local registry
+        ///
+        /// Every manifest is checked before any is written.
+        async fn register(&self, manifests: Vec<Value>) -> Result<Vec<RegistryEntry>> {
+            if manifests.is_empty() {
+                return Err(BeemFlowError::validation("No tools to install"));
+            }
+
+            let mut entries = Vec::with_capacity(manifests.len());
+            for manifest in manifests {
+                let entry: RegistryEntry = serde_json::from_value(manifest).map_err(|e| {
+                    BeemFlowError::validation(format!("Invalid tool manifest: {}", e))
+                })?;
+                entries.push(entry);
+            }
+
+            self.deps.registry_manager.register_tools(entries).await
+        }
+    }
+
     #[async_trait]
     impl Operation for Install {
         type Input = InstallInput;
-        type Output = Value;
+        type Output = InstallOutput;
 
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
-            match (input.name, input.manifest) {
-                (Some(name), None) => {
+            let (source, entries) = match (input.name, input.manifest, input.from_url) {
+                (Some(name), None, None) => {
                     // Install from registry by name
                     let tool_entry = self
                         .deps
@@ -170,43 +515,44 @@ pub mod tools {
                         return Err(type_mismatch(&name, "tool", &tool_entry.entry_type));
                     }
 
-                    Ok(serde_json::json!({
-                        "status": "installed",
-                        "name": name,
-                        "type": "tool",
-                        "endpoint": tool_entry.endpoint
-                    }))
+                    ("registry", vec![tool_entry])
                 }
-                (None, Some(manifest)) => {
-                    // Install from manifest
-                    let tool_name = manifest
-                        .get("name")
-                        .and_then(|n| n.as_str())
-                        .ok_or_else(|| {
-                            BeemFlowError::validation("Tool manifest must have a 'name' field")
-                        })?
-                        .to_string();
-
-                    // Register the tool in the local registry
-                    self.deps
-                        .registry_manager
-                        .register_tool_from_manifest(manifest)
-                        .await?;
-
-                    Ok(serde_json::json!({
-                        "status": "installed",
-                        "name": tool_name,
-                        "type": "tool",
-                        "source": "manifest"
-                    }))
+                (None, Some(manifest), None) => ("manifest", self.register(vec![manifest]).await?),
+                (None, None, Some(url)) => {
+                    let document = fetch_json(&url).await?;
+                    if document.get("openapi").is_some() || document.get("swagger").is_some() {
+                        let (_, _, manifests) = openapi_manifests(&document, None, None);
+                        ("openapi", self.register(manifests).await?)
+                    } else {
+                        ("url", self.register(vec![document]).await?)
+                    }
                 }
-                (Some(_), Some(_)) => Err(BeemFlowError::validation(
-                    "Provide either 'name' or 'manifest', not both",
-                )),
-                (None, None) => Err(BeemFlowError::validation(
-                    "Either 'name' or 'manifest' must be provided",
-                )),
-            }
+                (None, None, None) => {
+                    return Err(BeemFlowError::validation(
+                        "One of 'name', 'manifest' or 'from_url' must be provided",
+                    ));
+                }
+                _ => {
+                    return Err(BeemFlowError::validation(
+                        "Provide only one of 'name', 'manifest' or 'from_url'",
+                    ));
+                }
+            };
+
+            let entries = entries
+                .into_iter()
+                .map(|entry| {
+                    Ok(serde_json::from_value(mask_secrets(
+                        &serde_json::to_value(entry)?,
+                    ))?)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(InstallOutput {
+                status: "installed".to_string(),
+                source: source.to_string(),
+                entries,
+            })
         }
     }
 
@@ -229,151 +575,8 @@ pub mod tools {
         async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
             // Parse OpenAPI spec
             let openapi_spec: serde_json::Value = serde_json::from_str(&input.openapi)?;
-
-            // Extract basic info
-            let api_name = input.api_name.unwrap_or_else(|| {
-                openapi_spec
-                    .get("info")
-                    .and_then(|info| info.get("title"))
-                    .and_then(|title| title.as_str())
-                    .unwrap_or("api")
-                    .to_string()
-            });
-
-            let base_url = input.base_url.unwrap_or_else(|| {
-                openapi_spec
-                    .get("servers")
-                    .and_then(|servers| servers.as_array())
-                    .and_then(|servers| servers.first())
-                    .and_then(|server| server.get("url"))
-                    .and_then(|url| url.as_str())
-                    .unwrap_or("https://api.example.com")
-                    .to_string()
-            });
-
-            // Convert to tool manifests (simplified implementation)
-            let mut manifests = Vec::new();
-
-            if let Some(paths) = openapi_spec.get("paths").and_then(|p| p.as_object()) {
-                for (path, path_item) in paths {
-                    if let Some(path_obj) = path_item.as_object() {
-                        for (method, operation) in path_obj {
-                            if method != "parameters" {
-                                // Skip parameters key
-                                if let Some(op_obj) = operation.as_object() {
-                                    // Generate tool name
-                                    let tool_name = format!(
-                                        "{}.{}_{}",
-                                        api_name,
-                                        path.trim_start_matches('/').replace('/', "_"),
-                                        method
-                                    );
-
-                                    // Extract description
-                                    let description_str = op_obj
-                                        .get("summary")
-                                        .or_else(|| op_obj.get("description"))
-                                        .and_then(|d| d.as_str())
-                                        .unwrap_or("API endpoint");
-
-                                    // Create basic manifest
-                                    let mut manifest = serde_json::Map::new();
-                                    manifest.insert(
-                                        "name".to_string(),
-                                        serde_json::Value::String(tool_name.clone()),
-                                    );
-                                    manifest.insert(
-                                        "description".to_string(),
-                                        serde_json::Value::String(description_str.to_string()),
-                                    );
-                                    manifest.insert(
-                                        "kind".to_string(),
-                                        serde_json::Value::String("task".to_string()),
-                                    );
-                                    manifest.insert(
-                                        "endpoint".to_string(),
-                                        serde_json::Value::String(format!("{}{}", base_url, path)),
-                                    );
-                                    manifest.insert(
-                                        "method".to_string(),
-                                        serde_json::Value::String(method.to_uppercase()),
-                                    );
-
-                                    // Add basic parameters schema
-                                    let mut properties = serde_json::Map::new();
-                                    let mut required = Vec::new();
-
-                                    // Add path parameters
-                                    if let Some(params) =
-                                        path_obj.get("parameters").and_then(|p| p.as_array())
-                                    {
-                                        for param in params {
-                                            if let Some(param_obj) = param.as_object()
-                                                && let Some(param_name) =
-                                                    param_obj.get("name").and_then(|n| n.as_str())
-                                            {
-                                                let mut param_schema = serde_json::Map::new();
-                                                param_schema.insert(
-                                                    "type".to_string(),
-                                                    serde_json::Value::String("string".to_string()),
-                                                );
-
-                                                if let Some(param_desc) = param_obj
-                                                    .get("description")
-                                                    .and_then(|d| d.as_str())
-                                                {
-                                                    param_schema.insert(
-                                                        "description".to_string(),
-                                                        serde_json::Value::String(
-                                                            param_desc.to_string(),
-                                                        ),
-                                                    );
-                                                }
-
-                                                if param_obj
-                                                    .get("required")
-                                                    .and_then(|r| r.as_bool())
-                                                    .unwrap_or(false)
-                                                {
-                                                    required.push(serde_json::Value::String(
-                                                        param_name.to_string(),
-                                                    ));
-                                                }
-
-                                                properties.insert(
-                                                    param_name.to_string(),
-                                                    serde_json::Value::Object(param_schema),
-                                                );
-                                            }
-                                        }
-                                    }
-
-                                    let mut parameters = serde_json::Map::new();
-                                    parameters.insert(
-                                        "type".to_string(),
-                                        serde_json::Value::String("object".to_string()),
-                                    );
-                                    parameters.insert(
-                                        "properties".to_string(),
-                                        serde_json::Value::Object(properties),
-                                    );
-                                    parameters.insert(
-                                        "required".to_string(),
-                                        serde_json::Value::Array(required),
-                                    );
-
-                                    manifest.insert(
-                                        "parameters".to_string(),
-                                        serde_json::Value::Object(parameters),
-                                    );
-
-                                    manifests.push(serde_json::Value::Object(manifest));
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+            let (api_name, base_url, manifests) =
+                openapi_manifests(&openapi_spec, input.api_name, input.base_url);
 
             Ok(serde_json::json!({
                 "status": "converted",
@@ -0,0 +1,137 @@
+//! Webhook operations module
+//!
+//! Operations for inspecting and replaying received webhook payloads, and for
+//! inspecting outbound deliveries that `core.deliver` gave up on.
+
+use super::*;
+use crate::http::webhook::{WebhookDispatch, WebhookManagerState, replay_payload};
+use crate::model::{OutboundDelivery, WebhookPayload};
+use beemflow_core_macros::{operation, operation_group};
+use schemars::JsonSchema;
+
+#[operation_group(webhooks)]
+pub mod webhooks {
+    use super::*;
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for listing stored webhook payloads")]
+    pub struct ListInput {
+        #[schemars(description = "Only list payloads received by this provider")]
+        pub provider: Option<String>,
+        #[schemars(description = "Maximum number of payloads to return (default: 50)")]
+        pub limit: Option<usize>,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for listing dead-lettered deliveries")]
+    pub struct DeadLettersInput {
+        #[schemars(description = "Maximum number of deliveries to return (default: 50)")]
+        pub limit: Option<usize>,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[schemars(description = "Input for replaying a stored webhook payload")]
+    pub struct ReplayInput {
+        #[schemars(description = "ID of the stored payload", with = "String")]
+        pub id: uuid::Uuid,
+    }
+
+    #[derive(Serialize)]
+    pub struct ReplayOutput {
+        pub id: uuid::Uuid,
+        pub provider: String,
+        #[serde(flatten)]
+        pub dispatch: WebhookDispatch,
+    }
+
+    /// List stored webhook payloads
+    #[operation(
+        name = "list_webhook_payloads",
+        input = ListInput,
+        http = "GET /webhook-payloads",
+        cli = "webhooks list [--provider <PROVIDER>] [--limit <LIMIT>]",
+        description = "List received webhook payloads kept for replay, newest first"
+    )]
+    pub struct List {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for List {
+        type Input = ListInput;
+        type Output = Vec<WebhookPayload>;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            self.deps
+                .storage
+                .list_webhook_payloads(input.provider.as_deref(), input.limit.unwrap_or(50))
+                .await
+        }
+    }
+
+    /// Replay a stored webhook payload
+    #[operation(
+        name = "replay_webhook",
+        input = ReplayInput,
+        http = "POST /webhook-payloads/{id}/replay",
+        cli = "webhooks replay <ID>",
+        description = "Re-dispatch a stored webhook payload, triggering and resuming matching flows"
+    )]
+    pub struct Replay {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for Replay {
+        type Input = ReplayInput;
+        type Output = ReplayOutput;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let state = WebhookManagerState::new(&self.deps);
+
+            let stored = self
+                .deps
+                .storage
+                .get_webhook_payload(input.id)
+                .await?
+                .ok_or_else(|| not_found("Webhook payload", &input.id.to_string()))?;
+            let dispatch = replay_payload(&state, &stored).await?;
+
+            Ok(ReplayOutput {
+                id: stored.id,
+                provider: stored.provider,
+                dispatch,
+            })
+        }
+    }
+
+    /// List dead-lettered outbound deliveries
+    #[operation(
+        name = "list_dead_letters",
+        input = DeadLettersInput,
+        http = "GET /deliveries/dead-letters",
+        cli = "webhooks dead-letters [--limit <LIMIT>]",
+        description = "List outbound deliveries that failed every attempt, newest first"
+    )]
+    pub struct DeadLetters {
+        pub deps: Arc<Dependencies>,
+    }
+
+    #[async_trait]
+    impl Operation for DeadLetters {
+        type Input = DeadLettersInput;
+        type Output = Vec<OutboundDelivery>;
+
+        async fn execute(&self, input: Self::Input) -> Result<Self::Output> {
+            let dead = self
+                .deps
+                .storage
+                .list_dead_letters(
+                    input.limit.unwrap_or(50),
+                    crate::auth::current_tenant().as_deref(),
+                )
+                .await?;
+            Ok(self.deps.engine.deliveries().reveal(dead).await)
+        }
+    }
+}
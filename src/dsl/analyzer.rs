@@ -39,8 +39,9 @@ impl DependencyAnalyzer {
             // - {{ steps.foo.output }}
             // - {{ steps['foo'] }}
             // - {{ steps["foo"] }}
+            // `steps` must not follow a `.`, so `runs.previous.steps.foo` is not a reference
             step_ref_regex: Regex::new(
-                r#"steps\.([a-zA-Z0-9_-]+)|steps\['([^']+)'\]|steps\["([^"]+)"\]"#,
+                r#"(?:^|[^\w.])(?:steps\.([a-zA-Z0-9_-]+)|steps\['([^']+)'\]|steps\["([^"]+)"\])"#,
             )
             .expect("step reference regex is valid"),
             max_recursion_depth,
@@ -206,6 +207,24 @@ impl DependencyAnalyzer {
         self.merge_dependencies(auto_deps, manual_deps)
     }
 
+    /// Steps that depend on `step_id`, directly or transitively
+    pub fn dependents(&self, flow: &Flow, step_id: &str) -> HashSet<String> {
+        let graph = self.build_dependency_graph(flow);
+        let mut found = HashSet::new();
+        let mut pending = vec![step_id.to_string()];
+
+        while let Some(current) = pending.pop() {
+            for (dependent, deps) in &graph {
+                if deps.contains(&current) && found.insert(dependent.clone()) {
+                    pending.push(dependent.clone());
+                }
+            }
+        }
+
+        found.remove(step_id);
+        found
+    }
+
     /// Validate that all referenced steps exist
     fn validate_references(
         &self,
@@ -1,7 +1,7 @@
 //! Tests for analyzer
 
 use super::*;
-use crate::model::{Flow, Step};
+use crate::model::{Flow, FlowName, Step};
 use serde_json::json;
 use std::collections::{HashMap, HashSet};
 
@@ -39,6 +39,15 @@ fn test_extract_multiple_refs() {
     assert!(refs.contains("c"));
 }
 
+#[test]
+fn test_extract_refs_ignores_nested_steps_field() {
+    let analyzer = DependencyAnalyzer::new();
+    let template = "{{ runs.flow('other').latest.steps.fetch.output }} {{ runs.previous.steps.x }} {{steps.own}}";
+    let refs = analyzer.extract_step_refs(template);
+    assert_eq!(refs.len(), 1);
+    assert!(refs.contains("own"));
+}
+
 #[test]
 fn test_analyze_step_with_template() {
     let analyzer = DependencyAnalyzer::new();
@@ -86,7 +95,7 @@ fn test_topological_sort_simple() {
     step_b.depends_on = Some(vec!["a".to_string()]);
 
     let flow = Flow {
-        name: "test".to_string().into(),
+        name: FlowName::new("test").unwrap(),
         steps: vec![step_b, step_a], // Intentionally out of order
         ..Default::default()
     };
@@ -107,7 +116,7 @@ fn test_topological_sort_with_template_deps() {
     step_b.with = Some(with);
 
     let flow = Flow {
-        name: "test".to_string().into(),
+        name: FlowName::new("test").unwrap(),
         steps: vec![step_b, step_a], // b before a in YAML
         ..Default::default()
     };
@@ -127,7 +136,7 @@ fn test_detect_circular_dependency() {
     step_b.depends_on = Some(vec!["a".to_string()]);
 
     let flow = Flow {
-        name: "test".to_string().into(),
+        name: FlowName::new("test").unwrap(),
         steps: vec![step_a, step_b],
         ..Default::default()
     };
@@ -150,7 +159,7 @@ fn test_invalid_reference() {
     step.depends_on = Some(vec!["nonexistent".to_string()]);
 
     let flow = Flow {
-        name: "test".to_string().into(),
+        name: FlowName::new("test").unwrap(),
         steps: vec![step],
         ..Default::default()
     };
@@ -181,7 +190,7 @@ fn test_detect_template_refs_in_with_params() {
     step_b.with = Some(with);
 
     let flow = Flow {
-        name: "test".to_string().into(),
+        name: FlowName::new("test").unwrap(),
         steps: vec![step_b, step_a], // Intentionally reversed
         ..Default::default()
     };
@@ -203,3 +212,31 @@ fn test_detect_template_refs_in_with_params() {
         "step_a should run before step_b"
     );
 }
+
+#[test]
+fn test_dependents_are_transitive() {
+    let analyzer = DependencyAnalyzer::new();
+
+    let step_a = create_step("a");
+    let mut step_b = create_step("b");
+    step_b.depends_on = Some(vec!["a".to_string()]);
+    let mut step_c = create_step("c");
+    step_c.with = Some(HashMap::from([(
+        "text".to_string(),
+        json!("{{ steps.b.text }}"),
+    )]));
+    let unrelated = create_step("d");
+
+    let flow = Flow {
+        name: FlowName::new("test").unwrap(),
+        steps: vec![step_a, step_b, step_c, unrelated],
+        ..Default::default()
+    };
+
+    let dependents = analyzer.dependents(&flow, "a");
+    assert_eq!(
+        dependents,
+        HashSet::from(["b".to_string(), "c".to_string()])
+    );
+    assert!(analyzer.dependents(&flow, "c").is_empty());
+}
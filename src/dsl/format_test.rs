@@ -0,0 +1,112 @@
+//! Tests for flow serialization formats
+
+use super::*;
+
+const YAML_FLOW: &str = r##"
+name: digest
+version: 1.0.0
+on: schedule.cron
+cron: "0 9 * * *"
+vars:
+  channel: "#general"
+  limit: 5
+steps:
+  - id: fetch
+    use: http.fetch
+    with:
+      url: "https://example.com/feed"
+  - id: post
+    use: core.echo
+    depends_on: [fetch]
+    with:
+      text: "{{ outputs.fetch.body }}"
+"##;
+
+const TOML_FLOW: &str = r##"
+# Same flow as YAML_FLOW
+name = "digest"
+version = "1.0.0"
+on = "schedule.cron"
+cron = "0 9 * * *"
+
+[vars]
+channel = "#general"
+limit = 5
+
+[[steps]]
+id = "fetch"
+use = "http.fetch"
+with = { url = "https://example.com/feed" }
+
+[[steps]]
+id = "post"
+use = "core.echo"
+depends_on = ["fetch"]
+
+[steps.with]
+text = "{{ outputs.fetch.body }}"
+"##;
+
+#[test]
+fn test_toml_parses_like_its_yaml_equivalent() {
+    assert_eq!(FlowFormat::detect(TOML_FLOW), FlowFormat::Toml);
+    assert_eq!(FlowFormat::detect(YAML_FLOW), FlowFormat::Yaml);
+    assert_eq!(
+        parse_string(TOML_FLOW, None).unwrap(),
+        parse_string(YAML_FLOW, None).unwrap()
+    );
+}
+
+#[test]
+fn test_braced_content_that_is_not_json_is_yaml() {
+    let json = r#"{"name": "digest", "steps": [{"id": "post", "use": "core.echo"}]}"#;
+    assert_eq!(FlowFormat::detect(json), FlowFormat::Json);
+
+    // A flow-style YAML mapping starts with `{` too
+    let yaml = "{name: digest, steps: [{id: post, use: core.echo}]}";
+    assert_eq!(FlowFormat::detect(yaml), FlowFormat::Yaml);
+    assert_eq!(
+        parse_string(yaml, None).unwrap(),
+        parse_string(json, None).unwrap()
+    );
+}
+
+#[test]
+fn test_flows_round_trip_through_toml() {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("flows/examples");
+    for entry in std::fs::read_dir(examples).unwrap() {
+        let path = entry.unwrap().path();
+        let flow = parse_file(&path, None).unwrap();
+        let toml = serialize_flow(&flow, FlowFormat::Toml).unwrap();
+        assert_eq!(FlowFormat::detect(&toml), FlowFormat::Toml, "{:?}", path);
+        assert_eq!(parse_string(&toml, None).unwrap(), flow, "{:?}", path);
+        assert_eq!(serialize_flow(&flow, FlowFormat::Toml).unwrap(), toml);
+    }
+}
+
+#[test]
+fn test_file_extension_picks_the_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("digest.toml");
+    std::fs::write(&path, TOML_FLOW).unwrap();
+    assert_eq!(parse_file(&path, None).unwrap().name.as_str(), "digest");
+
+    // An explicit extension wins over the content
+    let path = dir.path().join("digest.yaml");
+    std::fs::write(&path, TOML_FLOW).unwrap();
+    assert!(parse_file(&path, None).is_err());
+
+    assert_eq!(FlowFormat::from_path("a.flow.yml"), Some(FlowFormat::Yaml));
+    assert_eq!(FlowFormat::from_path("a.JSON"), Some(FlowFormat::Json));
+    assert_eq!(FlowFormat::from_path("a.flow"), None);
+}
+
+#[test]
+fn test_toml_documents_patch_in_place() {
+    let (mut document, format) = patch::parse_document(TOML_FLOW).unwrap();
+    assert_eq!(format, FlowFormat::Toml);
+    patch::apply_patch(&mut document, &serde_json::json!({"vars": {"limit": 10}})).unwrap();
+    let toml = patch::serialize_document(&document, format).unwrap();
+    let flow = parse_string(&toml, None).unwrap();
+    assert_eq!(flow.vars.unwrap()["limit"], 10);
+}
@@ -1,21 +1,31 @@
 //! DSL parsing, validation, and templating
 
 pub mod analyzer;
+pub mod patch;
 pub mod template;
 pub mod validator;
 
 use crate::{BeemFlowError, Flow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
 // Re-export main types
 pub use analyzer::DependencyAnalyzer;
-pub use template::Templater;
+pub use template::{EscapePolicy, Templater, UndefinedMode};
 pub use validator::Validator;
 
 /// Default maximum flow file size (10MB) - prevents memory exhaustion from large files
 const DEFAULT_MAX_FLOW_FILE_SIZE: u64 = 10 * 1024 * 1024;
 
+/// Start of the error message for flow content over the size limit
+///
+/// The HTTP layer answers errors starting with this with 413 instead of 400.
+pub const FLOW_TOO_LARGE: &str = "Flow content exceeds maximum size";
+
 // ============================================================================
 // Parser Functions (formerly parser.rs)
 // ============================================================================
@@ -38,34 +48,163 @@ fn validate_file_size<P: AsRef<Path>>(path: P, max_size: u64) -> Result<()> {
 
 /// Parse a flow from a file path
 ///
+/// The format comes from the file's extension (`.yaml`, `.yml`, `.json` or
+/// `.toml`), or is detected from the content for any other extension.
+///
 /// # Arguments
 /// * `path` - Path to the flow file
 /// * `max_file_size` - Optional maximum file size in bytes (default: 10MB)
 pub fn parse_file<P: AsRef<Path>>(path: P, max_file_size: Option<u64>) -> Result<Flow> {
     let max_size = max_file_size.unwrap_or(DEFAULT_MAX_FLOW_FILE_SIZE);
     validate_file_size(&path, max_size)?;
-    let content = std::fs::read_to_string(path)?;
-    parse_string(&content, Some(max_size))
+    let content = std::fs::read_to_string(&path)?;
+    let format = FlowFormat::from_path(&path).unwrap_or_else(|| FlowFormat::detect(&content));
+    parse_string_as(&content, format, Some(max_size))
+}
+
+/// Serialization format of a flow definition
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FlowFormat {
+    #[default]
+    Yaml,
+    Json,
+    Toml,
 }
 
-/// Parse a flow from a YAML string
+/// A TOML `key = value` line, with a bare, quoted or dotted key
+static TOML_KEY_VALUE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^[A-Za-z0-9_\-."']+\s*="#).expect("valid regex"));
+
+impl FlowFormat {
+    /// Detect the format of flow content
+    ///
+    /// Content starting with `{` is JSON if it parses as JSON, and otherwise YAML
+    /// written in flow style (`{name: x, steps: [...]}`). Content whose first line
+    /// other than blanks and `#` comments is a `[table]` header or a `key = value`
+    /// pair is TOML; everything else is YAML.
+    pub fn detect(content: &str) -> Self {
+        if content.trim_start().starts_with('{') {
+            return match serde_json::from_str::<serde::de::IgnoredAny>(content) {
+                Ok(_) => Self::Json,
+                Err(_) => Self::Yaml,
+            };
+        }
+        let first_line = content
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'));
+        match first_line {
+            Some(line) if line.starts_with('[') || TOML_KEY_VALUE.is_match(line) => Self::Toml,
+            _ => Self::Yaml,
+        }
+    }
+
+    /// Format named by a file's extension, if it names one
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a flow from a YAML, JSON or TOML string
+///
+/// The format is detected from the content; see [`FlowFormat::detect`].
 ///
 /// # Arguments
-/// * `content` - YAML content to parse
+/// * `content` - YAML, JSON or TOML content to parse
 /// * `max_size` - Optional maximum content size in bytes (default: 10MB)
 pub fn parse_string(content: &str, max_size: Option<u64>) -> Result<Flow> {
-    let size_limit = max_size.unwrap_or(DEFAULT_MAX_FLOW_FILE_SIZE);
+    parse_string_as(content, FlowFormat::detect(content), max_size)
+}
 
-    // Validate content size to prevent YAML bombs and memory exhaustion
-    if content.len() > size_limit as usize {
+/// Reject flow content larger than `max_size` bytes
+///
+/// Checked before parsing to prevent YAML bombs and memory exhaustion.
+pub fn check_content_size(content: &str, max_size: u64) -> Result<()> {
+    if content.len() as u64 > max_size {
         return Err(BeemFlowError::validation(format!(
-            "YAML content exceeds maximum size of {} MB ({} bytes)",
-            size_limit / (1024 * 1024),
-            size_limit
+            "{} of {} MB ({} bytes)",
+            FLOW_TOO_LARGE,
+            max_size / (1024 * 1024),
+            max_size
         )));
     }
+    Ok(())
+}
 
-    Ok(serde_yaml::from_str(content)?)
+/// Parse a flow from a string in an explicit format
+pub fn parse_string_as(content: &str, format: FlowFormat, max_size: Option<u64>) -> Result<Flow> {
+    check_content_size(content, max_size.unwrap_or(DEFAULT_MAX_FLOW_FILE_SIZE))?;
+
+    match format {
+        FlowFormat::Yaml => Ok(serde_yaml::from_str(content)?),
+        FlowFormat::Json => Ok(serde_json::from_str(content)?),
+        FlowFormat::Toml => toml::from_str(content)
+            .map_err(|e| BeemFlowError::validation(format!("TOML error: {}", e))),
+    }
+}
+
+/// Serialize a flow in the given format
+///
+/// Map keys are sorted so the output is stable across calls.
+pub fn serialize_flow(flow: &Flow, format: FlowFormat) -> Result<String> {
+    let value = sort_keys(serde_json::to_value(flow)?);
+    match format {
+        FlowFormat::Yaml => Ok(serde_yaml::to_string(&value)?),
+        FlowFormat::Json => Ok(serde_json::to_string_pretty(&value)?),
+        FlowFormat::Toml => to_toml(&value),
+    }
+}
+
+/// Write a JSON value as a TOML document
+///
+/// TOML has no null, so null fields are left out; a null inside an array is an
+/// error.
+pub(crate) fn to_toml(value: &serde_json::Value) -> Result<String> {
+    toml::to_string_pretty(&strip_nulls(value.clone()))
+        .map_err(|e| BeemFlowError::validation(format!("Flow cannot be written as TOML: {}", e)))
+}
+
+/// Recursively drop null object fields
+fn strip_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k, strip_nulls(v)))
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(strip_nulls).collect())
+        }
+        other => other,
+    }
+}
+
+/// Recursively sort object keys
+fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_keys(v)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(sort_keys).collect())
+        }
+        other => other,
+    }
 }
 
 /// Load a flow: read, render with vars, parse, and validate
@@ -122,12 +261,30 @@ pub fn load_flow<P: AsRef<Path>>(
 /// assert!(rendered.contains("test_flow"));
 /// ```
 pub fn render_template(template: &str, vars: HashMap<String, serde_json::Value>) -> Result<String> {
-    Templater::new()
+    // Lenient for backward compatibility: vars often cover only part of a file
+    Templater::lenient()
         .render(template, &vars)
         .map_err(|e| BeemFlowError::validation(format!("Template rendering failed: {}", e)))
 }
 
+/// Render a template string, failing on undefined variables
+///
+/// Like [`render_template`], but an undefined variable is an error that names its
+/// line and column instead of rendering as an empty string. Used by flow previews.
+pub fn render_template_strict(
+    template: &str,
+    vars: HashMap<String, serde_json::Value>,
+) -> Result<String> {
+    Templater::new().render(template, &vars)
+}
+
 #[cfg(test)]
 mod analyzer_test;
 #[cfg(test)]
+mod format_test;
+#[cfg(test)]
+mod patch_test;
+#[cfg(test)]
 mod template_test;
+#[cfg(test)]
+mod validator_test;
@@ -0,0 +1,220 @@
+//! Partial edits of flow documents
+//!
+//! `update_flow` edits a deployed flow without resending all of it. The flow
+//! content is parsed into a plain document (not a [`Flow`](crate::model::Flow),
+//! so keys the model would normalize are kept), patched, and written back in
+//! the format it came in. A patch is either an RFC 6902 JSON Patch, an array of
+//! operations, or an RFC 7396 merge patch, an object merged into the document.
+
+use super::FlowFormat;
+use crate::{BeemFlowError, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// An RFC 6902 operation
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase", deny_unknown_fields)]
+enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+/// Parse flow content into a document to patch
+pub fn parse_document(content: &str) -> Result<(Value, FlowFormat)> {
+    let format = FlowFormat::detect(content);
+    let document = match format {
+        FlowFormat::Yaml => serde_yaml::from_str(content)?,
+        FlowFormat::Json => serde_json::from_str(content)?,
+        FlowFormat::Toml => toml::from_str(content)
+            .map_err(|e| BeemFlowError::validation(format!("TOML error: {}", e)))?,
+    };
+    Ok((document, format))
+}
+
+/// Write a patched document back as flow content
+///
+/// YAML and TOML comments and formatting of the original content are not kept.
+pub fn serialize_document(document: &Value, format: FlowFormat) -> Result<String> {
+    match format {
+        FlowFormat::Yaml => Ok(serde_yaml::to_string(document)?),
+        FlowFormat::Json => Ok(serde_json::to_string_pretty(document)?),
+        FlowFormat::Toml => super::to_toml(document),
+    }
+}
+
+/// Apply a JSON Patch (array) or merge patch (object) to `document`
+///
+/// A JSON Patch is applied atomically: if any operation fails, `document` is
+/// left unchanged.
+pub fn apply_patch(document: &mut Value, patch: &Value) -> Result<()> {
+    match patch {
+        Value::Array(operations) => {
+            let mut patched = document.clone();
+            for (i, operation) in operations.iter().enumerate() {
+                let operation: PatchOperation =
+                    serde_json::from_value(operation.clone()).map_err(|e| {
+                        BeemFlowError::validation(format!("patch operation {}: {}", i, e))
+                    })?;
+                apply_operation(&mut patched, operation).map_err(|e| {
+                    BeemFlowError::validation(format!("patch operation {}: {}", i, e))
+                })?;
+            }
+            *document = patched;
+            Ok(())
+        }
+        Value::Object(_) => {
+            merge_patch(document, patch);
+            Ok(())
+        }
+        _ => Err(BeemFlowError::validation(
+            "patch must be a JSON Patch array (RFC 6902) or a merge patch object (RFC 7396)",
+        )),
+    }
+}
+
+/// Merge `patch` into `target` (RFC 7396): `null` removes a key, objects merge,
+/// anything else replaces
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    if let Value::Object(map) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                map.shift_remove(key);
+            } else {
+                merge_patch(map.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+fn apply_operation(document: &mut Value, operation: PatchOperation) -> Result<()> {
+    match operation {
+        PatchOperation::Add { path, value } => add(document, &path, value),
+        PatchOperation::Remove { path } => remove(document, &path).map(|_| ()),
+        PatchOperation::Replace { path, value } => {
+            let target = lookup_mut(document, &path)?;
+            *target = value;
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            if path.starts_with(&format!("{}/", from)) {
+                return Err(BeemFlowError::validation(format!(
+                    "cannot move '{}' into its own child '{}'",
+                    from, path
+                )));
+            }
+            let value = remove(document, &from)?;
+            add(document, &path, value)
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = lookup_mut(document, &from)?.clone();
+            add(document, &path, value)
+        }
+        PatchOperation::Test { path, value } => {
+            if *lookup_mut(document, &path)? == value {
+                Ok(())
+            } else {
+                Err(BeemFlowError::validation(format!(
+                    "test failed: '{}' is not {}",
+                    path, value
+                )))
+            }
+        }
+    }
+}
+
+/// Value at a JSON Pointer
+fn lookup_mut<'v>(document: &'v mut Value, path: &str) -> Result<&'v mut Value> {
+    check_pointer(path)?;
+    document
+        .pointer_mut(path)
+        .ok_or_else(|| BeemFlowError::validation(format!("path '{}' does not exist", path)))
+}
+
+/// Parent container of a JSON Pointer and the pointer's last (unescaped) token
+fn parent_mut<'v>(document: &'v mut Value, path: &str) -> Result<(&'v mut Value, String)> {
+    check_pointer(path)?;
+    let (parent, token) = path.rsplit_once('/').unwrap_or_default();
+    let token = token.replace("~1", "/").replace("~0", "~");
+    let parent = document.pointer_mut(parent).ok_or_else(|| {
+        BeemFlowError::validation(format!("parent of path '{}' does not exist", path))
+    })?;
+    Ok((parent, token))
+}
+
+fn add(document: &mut Value, path: &str, value: Value) -> Result<()> {
+    if path.is_empty() {
+        *document = value;
+        return Ok(());
+    }
+    let (parent, token) = parent_mut(document, path)?;
+    match parent {
+        Value::Object(map) => {
+            map.insert(token, value);
+            Ok(())
+        }
+        Value::Array(items) => {
+            let index = if token == "-" {
+                items.len()
+            } else {
+                array_index(&token, items.len() + 1, path)?
+            };
+            items.insert(index, value);
+            Ok(())
+        }
+        _ => Err(BeemFlowError::validation(format!(
+            "cannot add '{}': parent is not an object or array",
+            path
+        ))),
+    }
+}
+
+fn remove(document: &mut Value, path: &str) -> Result<Value> {
+    if path.is_empty() {
+        return Err(BeemFlowError::validation(
+            "cannot remove the whole document",
+        ));
+    }
+    let (parent, token) = parent_mut(document, path)?;
+    let removed = match parent {
+        Value::Object(map) => map.shift_remove(&token),
+        Value::Array(items) => {
+            let index = array_index(&token, items.len(), path)?;
+            Some(items.remove(index))
+        }
+        _ => None,
+    };
+    removed.ok_or_else(|| BeemFlowError::validation(format!("path '{}' does not exist", path)))
+}
+
+/// Index token of an array, which must be below `bound`
+fn array_index(token: &str, bound: usize, path: &str) -> Result<usize> {
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|index| *index < bound && (token == "0" || !token.starts_with('0')))
+        .ok_or_else(|| {
+            BeemFlowError::validation(format!("path '{}' is not a valid array index", path))
+        })
+}
+
+fn check_pointer(path: &str) -> Result<()> {
+    if path.is_empty() || path.starts_with('/') {
+        Ok(())
+    } else {
+        Err(BeemFlowError::validation(format!(
+            "path '{}' must be empty or start with '/'",
+            path
+        )))
+    }
+}
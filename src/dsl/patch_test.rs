@@ -0,0 +1,127 @@
+//! Tests for flow document patches
+
+use super::patch::{apply_patch, parse_document, serialize_document};
+use super::*;
+use serde_json::json;
+
+fn patched(document: serde_json::Value, patch: serde_json::Value) -> Result<serde_json::Value> {
+    let mut document = document;
+    apply_patch(&mut document, &patch)?;
+    Ok(document)
+}
+
+#[test]
+fn test_json_patch_operations() {
+    let document = json!({
+        "name": "digest",
+        "steps": [{"id": "a", "with": {"text": "hi"}}, {"id": "b"}],
+        "vars": {"a/b": 1, "m~n": 2}
+    });
+    let result = patched(
+        document,
+        json!([
+            {"op": "test", "path": "/steps/0/id", "value": "a"},
+            {"op": "replace", "path": "/steps/0/with/text", "value": "hello"},
+            {"op": "add", "path": "/steps/-", "value": {"id": "c"}},
+            {"op": "add", "path": "/steps/1", "value": {"id": "a2"}},
+            {"op": "remove", "path": "/vars/a~1b"},
+            {"op": "copy", "from": "/vars/m~0n", "path": "/vars/copied"},
+            {"op": "move", "from": "/name", "path": "/description"}
+        ]),
+    )
+    .unwrap();
+    assert_eq!(
+        result,
+        json!({
+            "description": "digest",
+            "steps": [{"id": "a", "with": {"text": "hello"}}, {"id": "a2"}, {"id": "b"}, {"id": "c"}],
+            "vars": {"m~n": 2, "copied": 2}
+        })
+    );
+}
+
+#[test]
+fn test_json_patch_is_atomic() {
+    let mut document = json!({"name": "digest", "steps": []});
+    let err = apply_patch(
+        &mut document,
+        &json!([
+            {"op": "replace", "path": "/name", "value": "changed"},
+            {"op": "test", "path": "/name", "value": "digest"}
+        ]),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("patch operation 1: "), "{}", err);
+    assert_eq!(document, json!({"name": "digest", "steps": []}));
+
+    for (patch, message) in [
+        (
+            json!([{"op": "remove", "path": "/missing"}]),
+            "does not exist",
+        ),
+        (
+            json!([{"op": "add", "path": "/steps/5", "value": 1}]),
+            "not a valid array index",
+        ),
+        (
+            json!([{"op": "replace", "path": "name", "value": 1}]),
+            "must be empty or start with '/'",
+        ),
+        (
+            json!([{"op": "rename", "path": "/name"}]),
+            "unknown variant",
+        ),
+        (json!("name: x"), "merge patch object"),
+    ] {
+        let err = patched(document.clone(), patch).unwrap_err().to_string();
+        assert!(err.contains(message), "{}", err);
+    }
+}
+
+#[test]
+fn test_merge_patch() {
+    let result = patched(
+        json!({"name": "digest", "vars": {"a": 1, "b": 2}, "tags": ["x"]}),
+        json!({"vars": {"a": null, "c": 3}, "tags": ["y"], "description": "Daily"}),
+    )
+    .unwrap();
+    assert_eq!(
+        result,
+        json!({"name": "digest", "vars": {"b": 2, "c": 3}, "tags": ["y"], "description": "Daily"})
+    );
+}
+
+#[test]
+fn test_removing_a_key_keeps_the_order_of_the_rest() {
+    let document =
+        json!({"name": "digest", "description": "Daily", "on": "cli.manual", "steps": []});
+    let keys = |value: &serde_json::Value| {
+        value
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    let removed = patched(document.clone(), json!([{"op": "remove", "path": "/name"}])).unwrap();
+    assert_eq!(keys(&removed), ["description", "on", "steps"]);
+
+    let merged = patched(document, json!({"name": null})).unwrap();
+    assert_eq!(keys(&merged), ["description", "on", "steps"]);
+}
+
+#[test]
+fn test_documents_round_trip_in_their_format() {
+    let (document, format) = parse_document("name: digest\non: cli.manual\n").unwrap();
+    assert_eq!(format, FlowFormat::Yaml);
+    assert_eq!(document["on"], "cli.manual");
+    let yaml = serialize_document(&document, format).unwrap();
+    assert_eq!(FlowFormat::detect(&yaml), FlowFormat::Yaml);
+
+    let (document, format) = parse_document(r#"{"name": "digest", "steps": []}"#).unwrap();
+    let json = serialize_document(&document, format).unwrap();
+    assert_eq!(FlowFormat::detect(&json), FlowFormat::Json);
+    assert_eq!(parse_string(&json, None).unwrap().name.as_str(), "digest");
+}
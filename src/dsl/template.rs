@@ -2,35 +2,158 @@
 //!
 //! This module provides Django/Jinja2-style templating using minijinja's native syntax.
 //! BeemFlow-specific extensions:
-//! - item_index/item_row: Available in foreach loops (set by executor)
+//! - item_index/item_row and loop.*: Available in foreach loops (set by executor)
 //! - defined/undefined tests: For checking if variables exist
+//! - json_escape filter: For embedding values inside hand-built JSON strings
+//!
+//! # Undefined variables
+//!
+//! Flow values render in [`UndefinedMode::Strict`] by default: printing an undefined
+//! variable fails with a validation error naming the missing path, instead of
+//! silently producing an empty string. Truthiness checks (`{% if x %}`) and
+//! `is defined` tests still accept undefined values. [`UndefinedMode::Lenient`]
+//! keeps the old empty-string behavior and is selected with
+//! `"templates": {"undefined": "lenient"}` in `flow.config.json`.
+//!
+//! # Autoescape policy
+//!
+//! Flow value templating never escapes ([`EscapePolicy::None`]): rendered values
+//! become tool inputs (HTTP bodies, shell arguments, MCP calls), so HTML entities
+//! would silently corrupt them. Escaping is the job of the adapter that knows the
+//! output format. HTML pages served by `http::template::TemplateRenderer` use
+//! [`EscapePolicy::Html`] because they render untrusted data into markup.
 
-use crate::Result;
 use crate::error::TemplateError;
+use crate::{BeemFlowError, Result};
 use minijinja::{Environment, Value};
+use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Autoescape policy applied to every `{{ }}` output of a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EscapePolicy {
+    /// Emit values verbatim (flow value templating)
+    #[default]
+    None,
+    /// HTML-escape values (web pages)
+    Html,
+}
+
+impl EscapePolicy {
+    /// Install this policy on a minijinja environment
+    pub fn apply(self, env: &mut Environment<'static>) {
+        let auto_escape = match self {
+            EscapePolicy::None => minijinja::AutoEscape::None,
+            EscapePolicy::Html => minijinja::AutoEscape::Html,
+        };
+        env.set_auto_escape_callback(move |_| auto_escape);
+    }
+}
+
+/// How templates treat references to undefined variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UndefinedMode {
+    /// Printing an undefined variable is an error naming the missing path
+    #[default]
+    Strict,
+    /// Undefined variables render as empty strings
+    Lenient,
+}
+
+impl UndefinedMode {
+    fn behavior(self) -> minijinja::UndefinedBehavior {
+        match self {
+            // Semi-strict still lets `{% if x %}` test for presence
+            UndefinedMode::Strict => minijinja::UndefinedBehavior::SemiStrict,
+            // Chainable allows {{nonexistent.field}} to return undefined instead of error
+            UndefinedMode::Lenient => minijinja::UndefinedBehavior::Chainable,
+        }
+    }
+}
+
+/// Describe a render error, pointing at the line and column it came from
+fn describe_error(template: &str, err: &minijinja::Error) -> String {
+    let message = match err.detail() {
+        Some(detail) => format!("{}: {}", err.kind(), detail),
+        None => err.kind().to_string(),
+    };
+
+    match error_location(template, err) {
+        Some(location) => format!("{} at {}", message, location),
+        None => message,
+    }
+}
+
+/// Describe an undefined-variable error by the expression that produced it
+fn describe_undefined(template: &str, err: &minijinja::Error) -> String {
+    let path = err
+        .range()
+        .and_then(|range| template.get(range))
+        .map(str::trim)
+        .filter(|path| !path.is_empty());
+
+    match (path, error_location(template, err)) {
+        (Some(path), Some(location)) => {
+            format!("Variable not found: '{}' at {}", path, location)
+        }
+        (Some(path), None) => format!("Variable not found: '{}'", path),
+        (None, Some(location)) => format!("Variable not found at {}", location),
+        (None, None) => "Variable not found".to_string(),
+    }
+}
+
+/// Line and column of an error, as `line L, column C`
+fn error_location(template: &str, err: &minijinja::Error) -> Option<String> {
+    match (err.line(), err.range()) {
+        (Some(line), Some(range)) if template.is_char_boundary(range.start) => {
+            let before = &template[..range.start];
+            let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+            let column = before[line_start..].chars().count() + 1;
+            Some(format!("line {}, column {}", line, column))
+        }
+        (Some(line), _) => Some(format!("line {}", line)),
+        _ => None,
+    }
+}
+
 /// Templater provides pure minijinja template rendering
 pub struct Templater {
     env: Arc<Environment<'static>>,
 }
 
 impl Templater {
-    /// Create a new templater with minijinja's built-in filters
+    /// Create a new templater for flow values (no autoescaping, strict undefined)
     pub fn new() -> Self {
+        Self::with_undefined_mode(UndefinedMode::Strict)
+    }
+
+    /// Create a templater for flow values where undefined variables render empty
+    pub fn lenient() -> Self {
+        Self::with_undefined_mode(UndefinedMode::Lenient)
+    }
+
+    /// Create a templater for flow values with an explicit undefined mode
+    pub fn with_undefined_mode(mode: UndefinedMode) -> Self {
+        Self::build(EscapePolicy::None, mode)
+    }
+
+    /// Create a new templater with an explicit autoescape policy (lenient undefined)
+    pub fn with_escape_policy(policy: EscapePolicy) -> Self {
+        Self::build(policy, UndefinedMode::Lenient)
+    }
+
+    fn build(policy: EscapePolicy, mode: UndefinedMode) -> Self {
         let mut env = Environment::new();
 
         // Register ONLY BeemFlow-specific extensions
         Self::register_beemflow_extensions(&mut env);
 
         // Configure environment for template rendering
-        env.set_auto_escape_callback(|_| minijinja::AutoEscape::None);
-
-        // Set undefined behavior to chainable (allow chaining on undefined)
-        // This allows {{nonexistent.field}} to return undefined instead of error
-        env.set_undefined_behavior(minijinja::UndefinedBehavior::Chainable);
+        policy.apply(&mut env);
+        env.set_undefined_behavior(mode.behavior());
 
         // Security: Environment variables are NOT exposed as a global "env" object.
         // All environment variable access must go through the "secrets" namespace
@@ -46,12 +169,21 @@ impl Templater {
     ///
     /// BeemFlow extensions:
     /// - defined/undefined tests: Check if variables exist
+    /// - json_escape filter: Escape a value for use inside a JSON string literal
+    /// - `runs.flow("name")` method: Another flow's run data, prefetched by the engine
     fn register_beemflow_extensions(env: &mut Environment<'static>) {
         // Add tests for checking if variables are defined
         // These are useful for workflow conditionals
         env.add_test("defined", |value: Value| !value.is_undefined());
         env.add_test("undefined", |value: Value| value.is_undefined());
 
+        // For authors building JSON bodies by hand: "{\"text\": \"{{ msg | json_escape }}\"}"
+        env.add_filter("json_escape", json_escape);
+
+        // Templates render synchronously, so the engine prefetches `runs.flows.<name>`
+        // for every literal `runs.flow("name")` in the flow; this just looks it up.
+        env.set_unknown_method_callback(runs_flow_method);
+
         // Note: item_index and item_row are NOT filters - they're variables
         // injected by the executor during foreach loop execution
         // (see executor.rs:216-217, 256-257)
@@ -82,7 +214,12 @@ impl Templater {
 
         self.env
             .render_str(template, context)
-            .map_err(|e| crate::BeemFlowError::from(TemplateError::Syntax(e.to_string())))
+            .map_err(|e| match e.kind() {
+                minijinja::ErrorKind::UndefinedError => {
+                    BeemFlowError::validation(describe_undefined(template, &e))
+                }
+                _ => TemplateError::Syntax(describe_error(template, &e)).into(),
+            })
     }
 
     /// Evaluate a template expression and return the actual value (not rendered as string)
@@ -200,6 +337,46 @@ impl Templater {
     }
 }
 
+/// Resolve `runs.flow("name")` against the prefetched `runs.flows` map
+///
+/// Unknown flows resolve to `{"latest": {}}` so `{% if runs.flow("x").latest.id %}`
+/// works without the flow ever having run.
+fn runs_flow_method(
+    _state: &minijinja::State,
+    value: &Value,
+    method: &str,
+    args: &[Value],
+) -> std::result::Result<Value, minijinja::Error> {
+    let flows = match value.get_attr("flows") {
+        Ok(flows) if method == "flow" && flows.as_object().is_some() => flows,
+        _ => return Err(minijinja::Error::from(minijinja::ErrorKind::UnknownMethod)),
+    };
+
+    let (name,): (&str,) = minijinja::value::from_args(args)?;
+    let entry = flows.get_attr(name)?;
+    if entry.is_undefined() {
+        return Ok(Value::from_serialize(serde_json::json!({ "latest": {} })));
+    }
+    Ok(entry)
+}
+
+/// Escape a value so it can be embedded between double quotes in a JSON document
+///
+/// Strings are escaped without surrounding quotes; other values are serialized as JSON
+/// and then escaped, so the result is always safe inside a JSON string literal.
+fn json_escape(value: Value) -> std::result::Result<String, minijinja::Error> {
+    let raw = match value.as_str() {
+        Some(s) => s.to_string(),
+        None => serde_json::to_string(&value).map_err(|e| {
+            minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, e.to_string())
+        })?,
+    };
+    let quoted = serde_json::to_string(&raw).map_err(|e| {
+        minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, e.to_string())
+    })?;
+    Ok(quoted[1..quoted.len() - 1].to_string())
+}
+
 impl Default for Templater {
     fn default() -> Self {
         Self::new()
@@ -450,3 +450,83 @@ fn test_runs_previous_structure() {
         .unwrap();
     assert_eq!(result3, "The time is alec's time");
 }
+
+#[test]
+fn test_flow_values_are_not_html_escaped() {
+    let templater = Templater::new();
+    let mut data = HashMap::new();
+    data.insert("html".to_string(), json!("<b>Tom & \"Jerry\"</b>"));
+
+    let result = templater.render("{{ html }}", &data).unwrap();
+    assert_eq!(result, "<b>Tom & \"Jerry\"</b>");
+}
+
+#[test]
+fn test_html_escape_policy() {
+    let templater = Templater::with_escape_policy(crate::dsl::EscapePolicy::Html);
+    let mut data = HashMap::new();
+    data.insert("html".to_string(), json!("<b>hi</b>"));
+
+    let result = templater.render("{{ html }}", &data).unwrap();
+    assert_eq!(result, "&lt;b&gt;hi&lt;&#x2f;b&gt;");
+}
+
+#[test]
+fn test_json_escape_filter() {
+    let templater = Templater::new();
+    let mut data = HashMap::new();
+    data.insert("msg".to_string(), json!("say \"hi\"\nback\\slash"));
+    data.insert("obj".to_string(), json!({"a": "b"}));
+
+    let body = templater
+        .render(r#"{"text": "{{ msg | json_escape }}"}"#, &data)
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(parsed["text"], "say \"hi\"\nback\\slash");
+
+    let body = templater
+        .render(r#"{"raw": "{{ obj | json_escape }}"}"#, &data)
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(parsed["raw"], r#"{"a":"b"}"#);
+}
+
+#[test]
+fn test_strict_undefined_names_missing_path() {
+    let templater = Templater::new();
+    let mut data = HashMap::new();
+    data.insert("event".to_string(), json!({"name": "beem"}));
+
+    let err = templater
+        .render("Hello {{ event.name }}!\n{{ event.missing }}", &data)
+        .unwrap_err();
+    assert!(
+        matches!(err, crate::BeemFlowError::Validation(_)),
+        "{:?}",
+        err
+    );
+    let message = err.to_string();
+    assert!(message.contains("'event.missing'"), "{}", message);
+    assert!(message.contains("line 2, column 4"), "{}", message);
+
+    // Presence checks still accept undefined values
+    let result = templater
+        .render(
+            "{% if event.missing %}yes{% else %}no{% endif %} {{ event.missing is defined }} \
+             {{ event.missing or 'a' }} {{ event.missing | default('b') }}",
+            &data,
+        )
+        .unwrap();
+    assert_eq!(result, "no false a b");
+}
+
+#[test]
+fn test_lenient_undefined_renders_empty() {
+    let templater = Templater::lenient();
+    let data = HashMap::new();
+
+    let result = templater
+        .render("[{{ missing }}][{{ missing.nested.field }}]", &data)
+        .unwrap();
+    assert_eq!(result, "[][]");
+}
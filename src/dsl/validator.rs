@@ -7,6 +7,7 @@
 use crate::{BeemFlowError, Flow, Result, Step};
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 
 /// Embedded BeemFlow JSON Schema
@@ -45,9 +46,38 @@ impl Validator {
         Self::detect_circular_dependencies(flow)?; // Detect cycles in dependency graph
         Self::validate_step_constraints(flow)?;
         Self::validate_nested_steps(flow)?;
+        Self::validate_input_schema(flow)?;
         Ok(())
     }
 
+    /// Collect non-fatal issues worth reporting to flow authors
+    ///
+    /// Currently flags environment overlays that set vars the base `vars` doesn't
+    /// define, which is usually a typo.
+    pub fn warnings(flow: &Flow) -> Vec<String> {
+        let base = flow.vars.as_ref();
+        let mut warnings: Vec<String> = flow
+            .environments
+            .iter()
+            .flatten()
+            .flat_map(|(environment, overlay)| {
+                overlay
+                    .vars
+                    .iter()
+                    .flatten()
+                    .filter(|(name, _)| !base.is_some_and(|vars| vars.contains_key(*name)))
+                    .map(move |(name, _)| {
+                        format!(
+                            "Environment '{}' sets var '{}' which is not defined in the base vars",
+                            environment, name
+                        )
+                    })
+            })
+            .collect();
+        warnings.sort();
+        warnings
+    }
+
     /// Validate flow against JSON Schema
     fn validate_schema(flow: &Flow) -> Result<()> {
         // Convert flow to JSON value for schema validation
@@ -71,6 +101,49 @@ impl Validator {
         Ok(())
     }
 
+    /// Check the flow's `input_schema` is itself a valid JSON Schema
+    fn validate_input_schema(flow: &Flow) -> Result<()> {
+        if let Some(schema) = &flow.input_schema {
+            jsonschema::validator_for(schema)
+                .map_err(|e| BeemFlowError::validation(format!("Invalid input_schema: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Validate a triggering event against the flow's `input_schema`
+    ///
+    /// Flows without an `input_schema` accept any event. Every violation is
+    /// listed in the returned error.
+    pub fn validate_event(flow: &Flow, event: &HashMap<String, Value>) -> Result<()> {
+        let Some(schema) = &flow.input_schema else {
+            return Ok(());
+        };
+        let validator = jsonschema::validator_for(schema)
+            .map_err(|e| BeemFlowError::validation(format!("Invalid input_schema: {}", e)))?;
+
+        let event = serde_json::to_value(event)?;
+        let violations: Vec<String> = validator
+            .iter_errors(&event)
+            .map(|e| {
+                let path = e.instance_path.to_string();
+                if path.is_empty() {
+                    e.to_string()
+                } else {
+                    format!("{}: {}", path, e)
+                }
+            })
+            .collect();
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        Err(BeemFlowError::validation(format!(
+            "Event for flow '{}' does not match its input_schema:\n  - {}",
+            flow.name,
+            violations.join("\n  - ")
+        )))
+    }
+
     fn validate_required_fields(flow: &Flow) -> Result<()> {
         if flow.name.is_empty() {
             return Err(BeemFlowError::validation("Flow name is required"));
@@ -124,11 +197,13 @@ impl Validator {
             Self::validate_single_step(step)?;
         }
 
-        // Also validate catch blocks if present
-        if let Some(catch_steps) = &flow.catch {
-            for step in catch_steps {
-                Self::validate_single_step(step)?;
-            }
+        // Also validate catch blocks and terminal hooks if present
+        for step in [&flow.catch, &flow.on_success, &flow.on_failure]
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            Self::validate_single_step(step)?;
         }
 
         Ok(())
@@ -190,10 +265,23 @@ impl Validator {
                     step.id
                 )));
             }
+
+            // Branch outputs sit beside the block's `output` map, so no branch may take its name
+            if let Some(branch) = step
+                .steps
+                .iter()
+                .flatten()
+                .find(|branch| branch.id.as_str() == crate::constants::BLOCK_OUTPUT_FIELD)
+            {
+                return Err(BeemFlowError::validation(format!(
+                    "Parallel step '{}' cannot have a branch with id '{}'; it is reserved for the block's combined output",
+                    step.id, branch.id
+                )));
+            }
         }
 
         // Foreach must have 'as' and 'do'
-        if step.foreach.is_some() {
+        if let Some(foreach_expr) = &step.foreach {
             if step.as_.is_none() {
                 return Err(BeemFlowError::validation(format!(
                     "Foreach step '{}' must have 'as' field",
@@ -208,8 +296,6 @@ impl Validator {
             }
 
             // Validate foreach expression is templated
-            // Safe: We already verified step.foreach.is_some() above
-            let foreach_expr = step.foreach.as_ref().unwrap();
             if !Self::is_template_syntax(foreach_expr) {
                 return Err(BeemFlowError::validation(format!(
                     "Foreach expression in step '{}' should use template syntax: {{ }} ",
@@ -267,6 +353,21 @@ impl Validator {
             )));
         }
 
+        // Timeouts must be durations like "30s" or "5m"
+        for (field, value) in [
+            ("timeout_total", &step.timeout_total),
+            ("timeout_idle", &step.timeout_idle),
+        ] {
+            if let Some(value) = value
+                && let Err(e) = crate::utils::parse_duration(value)
+            {
+                return Err(BeemFlowError::validation(format!(
+                    "Invalid {} in step '{}': {}",
+                    field, step.id, e
+                )));
+            }
+        }
+
         Ok(())
     }
 
@@ -276,6 +377,7 @@ impl Validator {
             if let Some(nested_steps) = &step.steps {
                 for nested in nested_steps {
                     Self::validate_single_step(nested)?;
+                    Self::validate_not_best_effort(nested)?;
                 }
             }
 
@@ -283,6 +385,7 @@ impl Validator {
             if let Some(do_steps) = &step.do_ {
                 for nested in do_steps {
                     Self::validate_single_step(nested)?;
+                    Self::validate_not_best_effort(nested)?;
                 }
             }
         }
@@ -290,6 +393,17 @@ impl Validator {
         Ok(())
     }
 
+    /// `continue_on_error` applies to the top-level steps a run records
+    fn validate_not_best_effort(step: &Step) -> Result<()> {
+        if step.continue_on_error.is_some() {
+            return Err(BeemFlowError::validation(format!(
+                "Step '{}': continue_on_error is only supported on top-level steps",
+                step.id
+            )));
+        }
+        Ok(())
+    }
+
     /// Validate that a string is a valid identifier (alphanumeric + underscore)
     fn validate_identifier(id: &str) -> Result<()> {
         if id.is_empty() {
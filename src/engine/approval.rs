@@ -0,0 +1,199 @@
+//! Human approval gates (`core.approval`)
+//!
+//! An approval step pauses the run like `await_event`, but BeemFlow generates the
+//! token itself: a random nonce signed with HMAC-SHA256, so forged or mistyped
+//! tokens are rejected before storage is touched. The paused run is stored under
+//! the token's hash (see [`super::resume`]) with source `approval.<hash>`, and
+//! resumed through [`Engine::decide_approval`], which the HTTP `/approvals/{token}`
+//! endpoints call. Tokens are single-use (the paused run is fetched and deleted
+//! atomically) and expire after the step's `timeout`.
+//!
+//! The approval token lets anyone holding it decide, without saying who they
+//! are. A step listing `approvers` also gets one approver link per approver:
+//! the approval URL with a `link` token signed for that approver and that
+//! approval, expiring with it. Deciding through a link records its approver;
+//! when the OAuth server is enabled, decisions must come through a link or with
+//! a bearer token.
+//!
+//! [`Engine::decide_approval`]: crate::Engine::decide_approval
+
+use crate::secrets::SecretsProvider;
+use crate::{BeemFlowError, Result};
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Paused-run source prefix for approval gates
+pub const APPROVAL_SOURCE_PREFIX: &str = "approval.";
+
+/// Default time an approval stays open
+pub const DEFAULT_APPROVAL_TIMEOUT: &str = "24h";
+
+/// Prefix of signed approver links, naming their format version
+const LINK_PREFIX: &str = "bfa1.";
+
+/// What a rejection does to the run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnReject {
+    /// Mark the approval step and the run as failed
+    #[default]
+    Fail,
+    /// Continue the run; later steps branch on `outputs.<step>.approved`
+    Continue,
+}
+
+/// A pending approval, stored with the paused run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub step_id: String,
+    /// Step record updated when the decision arrives
+    pub step_run_id: Uuid,
+    pub flow_name: String,
+    pub run_id: Uuid,
+    /// Message shown to the approver
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Rendered context fields shown to the approver
+    #[serde(default)]
+    pub context: HashMap<String, Value>,
+    #[serde(default)]
+    pub on_reject: OnReject,
+    pub requested_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A human decision on a pending approval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalDecision {
+    pub approved: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approver: Option<String>,
+}
+
+impl ApprovalDecision {
+    /// Step outputs exposed to the rest of the flow as `outputs.<step_id>`
+    pub fn to_outputs(&self) -> Value {
+        serde_json::json!({
+            "approved": self.approved,
+            "comment": self.comment,
+            "approver": self.approver,
+            "decided_at": Utc::now(),
+        })
+    }
+}
+
+/// Get the key used to sign approval tokens
+///
+/// Uses `BEEMFLOW_APPROVAL_SECRET` when set. Otherwise a random per-process key
+/// is used, which means pending approvals cannot be decided after a restart.
+pub(crate) async fn signing_key(secrets: &dyn SecretsProvider) -> Vec<u8> {
+    super::resume::server_key(secrets, crate::constants::ENV_APPROVAL_SECRET).await
+}
+
+/// Generate a new signed token: `<nonce>.<hex signature>`
+pub(crate) fn generate_token(key: &[u8]) -> String {
+    let nonce = Uuid::new_v4().simple().to_string();
+    format!("{}.{}", nonce, sign(key, &nonce))
+}
+
+/// Verify a token's signature and return its nonce
+pub(crate) fn verify_token(key: &[u8], token: &str) -> Result<Uuid> {
+    let invalid = || BeemFlowError::validation("Invalid approval token");
+
+    let (nonce, signature) = token.split_once('.').ok_or_else(invalid)?;
+    let signature = hex::decode(signature).map_err(|_| invalid())?;
+
+    let mut mac = HmacSha256::new_from_slice(key).map_err(|_| invalid())?;
+    mac.update(nonce.as_bytes());
+    mac.verify_slice(&signature).map_err(|_| invalid())?;
+
+    Uuid::parse_str(nonce).map_err(|_| invalid())
+}
+
+/// Paused-run source for an approval token, naming it by its storage key
+pub fn approval_source(token: &str) -> String {
+    format!(
+        "{}{}",
+        APPROVAL_SOURCE_PREFIX,
+        super::resume::storage_key(token)
+    )
+}
+
+/// Paused-run source of approvals paused before tokens were hashed
+pub(crate) fn legacy_approval_source(token: &str) -> String {
+    format!("{}{}", APPROVAL_SOURCE_PREFIX, token)
+}
+
+/// Claims of an approver link
+#[derive(Serialize, Deserialize)]
+struct LinkClaims {
+    /// Nonce of the approval the link decides
+    n: Uuid,
+    /// Approver
+    u: String,
+    /// Expiry, as a Unix timestamp
+    e: i64,
+}
+
+/// Sign a link letting `approver` decide the approval `nonce` until `expires_at`
+///
+/// Format: `bfa1.<base64url claims>.<hex signature>`.
+pub(crate) fn sign_link(
+    key: &[u8],
+    nonce: Uuid,
+    approver: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<String> {
+    let claims = serde_json::to_vec(&LinkClaims {
+        n: nonce,
+        u: approver.to_string(),
+        e: expires_at.timestamp(),
+    })?;
+    let body = format!("{}{}", LINK_PREFIX, URL_SAFE_NO_PAD.encode(claims));
+    let signature = sign(key, &body);
+    Ok(format!("{}.{}", body, signature))
+}
+
+/// Verify an approver link for the approval `nonce` at `now`, returning its approver
+pub(crate) fn verify_link(
+    key: &[u8],
+    nonce: Uuid,
+    link: &str,
+    now: DateTime<Utc>,
+) -> Result<String> {
+    let invalid = || BeemFlowError::auth("Invalid approval link");
+
+    let (body, signature) = link.rsplit_once('.').ok_or_else(invalid)?;
+    let claims = body.strip_prefix(LINK_PREFIX).ok_or_else(invalid)?;
+    let signature = hex::decode(signature).map_err(|_| invalid())?;
+    let mut mac = HmacSha256::new_from_slice(key).map_err(|_| invalid())?;
+    mac.update(body.as_bytes());
+    mac.verify_slice(&signature).map_err(|_| invalid())?;
+
+    let claims = URL_SAFE_NO_PAD.decode(claims).map_err(|_| invalid())?;
+    let claims: LinkClaims = serde_json::from_slice(&claims).map_err(|_| invalid())?;
+    if claims.n != nonce {
+        return Err(invalid());
+    }
+    if now.timestamp() > claims.e {
+        return Err(BeemFlowError::auth("Approval link has expired"));
+    }
+    Ok(claims.u)
+}
+
+fn sign(key: &[u8], nonce: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(nonce.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
@@ -1,15 +1,17 @@
 //! Step execution context
 //!
 //! Manages event data, variables, outputs, and secrets during workflow execution.
-//! Also provides template access to previous run outputs.
+//! Also provides template access to previous run outputs of this and other flows.
 //!
 //! Optimized for read-heavy workloads:
 //! - Event, vars, and secrets are immutable after creation (Arc<HashMap>)
 //! - Outputs use DashMap for lock-free concurrent writes
 
-use crate::model::{RunStatus, StepStatus};
+use crate::model::{Flow, RunStatus, StepStatus};
 use crate::storage::Storage;
 use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -21,6 +23,7 @@ pub struct StepContext {
     event: Arc<HashMap<String, Value>>,
     vars: Arc<HashMap<String, Value>>,
     outputs: Arc<DashMap<String, Value>>,
+    inputs: Arc<DashMap<String, HashMap<String, Value>>>,
     secrets: Arc<HashMap<String, Value>>,
 }
 
@@ -58,6 +61,7 @@ impl StepContext {
             event: Arc::new(event),
             vars: Arc::new(vars),
             outputs: Arc::new(DashMap::new()),
+            inputs: Arc::new(DashMap::new()),
             secrets: Arc::new(secrets),
         }
     }
@@ -72,6 +76,21 @@ impl StepContext {
         self.outputs.insert(key, value);
     }
 
+    /// Get the recorded (redacted) tool inputs for a step
+    pub fn get_inputs(&self, key: &str) -> Option<HashMap<String, Value>> {
+        self.inputs.get(key).map(|v| v.clone())
+    }
+
+    /// Record the (redacted) tool inputs sent for a step
+    pub fn set_inputs(&self, key: String, inputs: HashMap<String, Value>) {
+        self.inputs.insert(key, inputs);
+    }
+
+    /// Get a secret value
+    pub fn get_secret(&self, key: &str) -> Option<&Value> {
+        self.secrets.get(key)
+    }
+
     /// Get a snapshot of the context (cloned data)
     pub fn snapshot(&self) -> ContextSnapshot {
         ContextSnapshot {
@@ -217,10 +236,16 @@ pub fn is_valid_identifier(s: &str) -> bool {
 // Previous Run Access
 // ============================================================================
 
+/// Maximum number of other flows a single flow may read through `runs.flow(...)`
+///
+/// Each referenced flow costs one run query and one step query before execution.
+pub const MAX_CROSS_FLOW_REFS: usize = 10;
+
 /// Provides template access to previous run outputs
 ///
 /// This helper allows accessing outputs from the most recent previous run
-/// of the same workflow through the `runs.previous` template variable.
+/// of the same workflow through the `runs.previous` template variable, and the
+/// latest successful run of other workflows through `runs.flow("name").latest`.
 #[derive(Clone)]
 pub struct RunsAccess {
     storage: Arc<dyn Storage>,
@@ -243,16 +268,25 @@ impl RunsAccess {
     /// Returns a map with:
     /// - id: Run ID as string
     /// - outputs: Map of step outputs
+    /// - steps: Map of step name to `{output, status}`
     /// - status: Run status
     /// - flow: Flow name
     ///
     /// Returns empty map if no previous run found.
     pub async fn previous(&self) -> HashMap<String, Value> {
+        self.latest_successful(&self.flow_name).await
+    }
+
+    /// Get outputs from the most recent successful run of any workflow
+    ///
+    /// Same shape as [`RunsAccess::previous`]. The current run is never returned.
+    /// Returns empty map if the flow has no successful run.
+    pub async fn latest_successful(&self, flow_name: &str) -> HashMap<String, Value> {
         // Use optimized query to fetch only matching runs (database-level filtering)
         let runs = match self
             .storage
             .list_runs_by_flow_and_status(
-                &self.flow_name,
+                flow_name,
                 RunStatus::Succeeded,
                 self.current_run_id,
                 1, // Only need the most recent
@@ -261,7 +295,7 @@ impl RunsAccess {
         {
             Ok(runs) => runs,
             Err(e) => {
-                tracing::warn!("Failed to query previous runs: {}", e);
+                tracing::warn!("Failed to query runs for flow '{}': {}", flow_name, e);
                 return HashMap::new();
             }
         };
@@ -270,16 +304,13 @@ impl RunsAccess {
         let run = match runs.first() {
             Some(run) => run,
             None => {
-                tracing::debug!(
-                    "No previous successful run found for flow '{}'",
-                    self.flow_name
-                );
+                tracing::debug!("No successful run found for flow '{}'", flow_name);
                 return HashMap::new();
             }
         };
 
         tracing::debug!(
-            "Found previous successful run: id={}, flow={}",
+            "Found successful run: id={}, flow={}",
             run.id,
             run.flow_name
         );
@@ -295,26 +326,27 @@ impl RunsAccess {
 
         // Aggregate step outputs
         let mut outputs = HashMap::new();
+        let mut step_data = HashMap::new();
         for step in steps {
             if step.status == StepStatus::Succeeded
                 && let Some(ref step_outputs) = step.outputs
             {
-                outputs.insert(
-                    step.step_name.clone(),
-                    serde_json::to_value(step_outputs).unwrap_or(Value::Null),
+                let output = serde_json::to_value(step_outputs).unwrap_or(Value::Null);
+                step_data.insert(
+                    step.step_name.to_string(),
+                    serde_json::json!({ "output": output, "status": step.status }),
                 );
+                outputs.insert(step.step_name.to_string(), output);
             }
         }
 
-        tracing::debug!(
-            "Returning previous run data with {} step outputs",
-            outputs.len()
-        );
+        tracing::debug!("Returning run data with {} step outputs", outputs.len());
 
         // Return the run data
         serde_json::json!({
             "id": run.id.to_string(),
             "outputs": outputs,
+            "steps": step_data,
             "status": format!("{:?}", run.status),
             "flow": run.flow_name,
         })
@@ -322,4 +354,40 @@ impl RunsAccess {
         .map(|obj| obj.clone().into_iter().collect())
         .unwrap_or_default()
     }
+
+    /// Find the flow names a flow reads through `runs.flow("name")`
+    ///
+    /// Only literal names can be prefetched. Names are deduplicated, and at most
+    /// [`MAX_CROSS_FLOW_REFS`] are returned so a flow cannot trigger unbounded loads.
+    pub fn referenced_flows(flow: &Flow) -> Vec<String> {
+        static FLOW_REF_PATTERN: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r#"runs\.flow\(\s*["']([^"']+)["']\s*\)"#)
+                .expect("Invalid flow reference regex")
+        });
+
+        let Ok(source) = serde_json::to_string(flow) else {
+            return Vec::new();
+        };
+        // Serialized as JSON, so double quotes inside templates are escaped
+        let source = source.replace("\\\"", "\"");
+
+        let mut names: Vec<String> = Vec::new();
+        for cap in FLOW_REF_PATTERN.captures_iter(&source) {
+            let name = cap[1].to_string();
+            if names.contains(&name) {
+                continue;
+            }
+            if names.len() == MAX_CROSS_FLOW_REFS {
+                tracing::warn!(
+                    "Flow '{}' references more than {} other flows via runs.flow(); ignoring '{}'",
+                    flow.name,
+                    MAX_CROSS_FLOW_REFS,
+                    name
+                );
+                continue;
+            }
+            names.push(name);
+        }
+        names
+    }
 }
@@ -1,5 +1,7 @@
-use crate::engine::{RunsAccess, StepContext, context::is_valid_identifier};
-use crate::model::{Run, RunStatus, StepRun, StepStatus};
+use crate::dsl::parse_string;
+use crate::engine::context::{MAX_CROSS_FLOW_REFS, is_valid_identifier};
+use crate::engine::{RunsAccess, StepContext};
+use crate::model::{FlowName, Run, RunStatus, StepRun, StepStatus};
 use crate::storage::{SqliteStorage, Storage};
 use chrono::Utc;
 use serde_json::Value;
@@ -60,13 +62,18 @@ async fn test_runs_access_previous() {
     // Create a previous run
     let prev_run = Run {
         id: Uuid::new_v4(),
-        flow_name: "test_flow".to_string().into(),
+        flow_name: FlowName::new("test_flow").unwrap(),
         event: HashMap::new(),
         vars: HashMap::new(),
         status: RunStatus::Succeeded,
         started_at: Utc::now(),
         ended_at: Some(Utc::now()),
         steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
     };
 
     storage.save_run(&prev_run).await.unwrap();
@@ -80,6 +87,8 @@ async fn test_runs_access_previous() {
         started_at: Utc::now(),
         ended_at: Some(Utc::now()),
         error: None,
+        error_kind: None,
+        inputs: None,
         outputs: Some(
             serde_json::json!({"result": "hello"})
                 .as_object()
@@ -88,6 +97,7 @@ async fn test_runs_access_previous() {
                 .into_iter()
                 .collect(),
         ),
+        progress: None,
     };
 
     storage.save_step(&step).await.unwrap();
@@ -102,3 +112,79 @@ async fn test_runs_access_previous() {
     assert!(previous.contains_key("id"));
     assert!(previous.contains_key("outputs"));
 }
+
+#[tokio::test]
+async fn test_runs_access_latest_successful_other_flow() {
+    let storage = Arc::new(
+        SqliteStorage::new(":memory:")
+            .await
+            .expect("Failed to create SQLite storage"),
+    ) as Arc<dyn Storage>;
+
+    for (status, text) in [(RunStatus::Succeeded, "good"), (RunStatus::Failed, "bad")] {
+        let run = Run {
+            id: Uuid::new_v4(),
+            flow_name: FlowName::new("daily_import").unwrap(),
+            event: HashMap::new(),
+            vars: HashMap::new(),
+            status,
+            started_at: Utc::now(),
+            ended_at: Some(Utc::now()),
+            steps: None,
+            environment: None,
+            correlation_id: None,
+            labels: HashMap::new(),
+            usage: None,
+            tenant_id: None,
+        };
+        storage.save_run(&run).await.unwrap();
+        storage
+            .save_step(&StepRun {
+                id: Uuid::new_v4(),
+                run_id: run.id,
+                step_name: "fetch".to_string().into(),
+                status: StepStatus::Succeeded,
+                started_at: Utc::now(),
+                ended_at: Some(Utc::now()),
+                error: None,
+                error_kind: None,
+                inputs: None,
+                outputs: Some(HashMap::from([(
+                    "text".to_string(),
+                    Value::String(text.to_string()),
+                )])),
+                progress: None,
+            })
+            .await
+            .unwrap();
+    }
+
+    let runs_access = RunsAccess::new(storage, None, "daily_report".to_string());
+
+    // The report flow has never run; the import flow's failed run is ignored
+    assert!(runs_access.previous().await.is_empty());
+    let latest = runs_access.latest_successful("daily_import").await;
+    assert_eq!(latest["flow"], "daily_import");
+    assert_eq!(latest["steps"]["fetch"]["output"]["text"], "good");
+    assert_eq!(latest["outputs"]["fetch"]["text"], "good");
+
+    assert!(runs_access.latest_successful("missing").await.is_empty());
+}
+
+#[test]
+fn test_runs_access_referenced_flows() {
+    // Both quote styles, one repeated name, and more flows than the limit
+    let mut steps = String::new();
+    for i in 0..MAX_CROSS_FLOW_REFS + 2 {
+        steps.push_str(&format!("  - id: s{i}\n    use: core.echo\n    with:\n"));
+        steps.push_str(&format!(
+            "      text: '{{{{ runs.flow(\"f{i}\").latest.id }}}} {{{{ runs.flow(\"f0\").latest.id }}}}'\n"
+        ));
+    }
+    let flow = parse_string(&format!("name: report\nsteps:\n{}", steps), None).unwrap();
+
+    let names = RunsAccess::referenced_flows(&flow);
+    assert_eq!(names.len(), MAX_CROSS_FLOW_REFS);
+    assert_eq!(names[0], "f0");
+    assert!(names.contains(&"f1".to_string()));
+}
@@ -0,0 +1,510 @@
+//! Durable outbound deliveries (`core.deliver`)
+//!
+//! A `core.deliver` step sends its HTTP request once, through the HTTP adapter,
+//! so `$oauth:` header references are expanded for the run's tenant and an
+//! optional `rate_limit` paces requests to the URL's host. If the request fails
+//! (a connection error or a non-2xx response), the delivery is queued in storage
+//! and the step still succeeds, with `queued: true`. [`DeliveryWorker`] retries
+//! queued deliveries on a background tick, backing off exponentially from
+//! [`RETRY_BASE_DELAY`] up to [`RETRY_MAX_DELAY`]. A delivery that fails
+//! `max_attempts` times in all is moved to the dead-letter queue, listed by
+//! the `list_dead_letters` operation with secrets masked.
+//!
+//! Headers and body are rendered, so they may hold secrets: they are stored
+//! sealed with AES-256-GCM under a key derived from `BEEMFLOW_DELIVERY_SECRET`.
+//! Without that secret the key is random per process, and deliveries queued
+//! before a restart can no longer be opened; they are dead-lettered.
+//!
+//! Workers claim due deliveries with a lease ([`CLAIM_LEASE`]), so instances
+//! sharing the storage don't send the same delivery at once. Delivery is still
+//! at least once: a crash between a successful attempt and its removal from
+//! the queue sends the request again once the lease ends.
+
+use crate::adapter::{Adapter, ExecutionContext, HttpAdapter};
+use crate::clock::Clock;
+use crate::constants::{CORE_DELIVER, ENV_DELIVERY_SECRET};
+use crate::model::OutboundDelivery;
+use crate::secrets::SecretsProvider;
+use crate::storage::Storage;
+use crate::{BeemFlowError, Result};
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Attempts made before a delivery is dead-lettered, unless the step sets `max_attempts`
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry; doubles with every failed attempt
+pub const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+
+/// Longest delay between two attempts
+pub const RETRY_MAX_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// How often the background loop looks for due deliveries
+pub const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a claimed delivery is hidden from other workers
+///
+/// Covers a whole batch of attempts timing out, with room to spare.
+pub const CLAIM_LEASE: Duration = Duration::from_secs(10 * 60);
+
+/// Time allowed for one attempt
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Due deliveries claimed from storage per batch
+const RETRY_BATCH_SIZE: usize = 10;
+
+/// Prefix of sealed headers and bodies, naming the sealing scheme
+const SEALED_PREFIX: &str = "v1.";
+
+static HTTP: Lazy<HttpAdapter> = Lazy::new(|| HttpAdapter::new(CORE_DELIVER.to_string(), None));
+
+/// Build the delivery described by a `core.deliver` step's inputs
+///
+/// Takes `url` (required), `method` (default `POST`), `headers`, a JSON `body`,
+/// `max_attempts` and `rate_limit`.
+pub fn from_inputs(
+    inputs: &HashMap<String, Value>,
+    run_id: Option<Uuid>,
+) -> Result<OutboundDelivery> {
+    let url = inputs
+        .get("url")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .ok_or_else(|| BeemFlowError::adapter("core.deliver requires a 'url'"))?;
+    let parsed = url::Url::parse(url)
+        .map_err(|e| BeemFlowError::adapter(format!("core.deliver 'url' is invalid: {}", e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(BeemFlowError::adapter(
+            "core.deliver 'url' must be an http or https URL",
+        ));
+    }
+
+    let method = inputs
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or("POST")
+        .to_uppercase();
+    if reqwest::Method::from_bytes(method.as_bytes()).is_err() {
+        return Err(BeemFlowError::adapter(format!(
+            "core.deliver 'method' is invalid: {}",
+            method
+        )));
+    }
+
+    let headers = match inputs.get("headers") {
+        None | Some(Value::Null) => HashMap::new(),
+        Some(Value::Object(headers)) => headers
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    Value::String(value) => value.clone(),
+                    other => other.to_string(),
+                };
+                (name.clone(), value)
+            })
+            .collect(),
+        Some(_) => {
+            return Err(BeemFlowError::adapter(
+                "core.deliver 'headers' must be an object",
+            ));
+        }
+    };
+
+    let max_attempts = match inputs.get("max_attempts") {
+        None => DEFAULT_MAX_ATTEMPTS,
+        Some(value) => value
+            .as_u64()
+            .filter(|attempts| (1..=u32::MAX as u64).contains(attempts))
+            .ok_or_else(|| {
+                BeemFlowError::adapter("core.deliver 'max_attempts' must be a positive integer")
+            })? as u32,
+    };
+
+    let rate_limit = match inputs.get("rate_limit") {
+        None | Some(Value::Null) => None,
+        Some(value) => {
+            let limit: crate::adapter::RateLimit =
+                serde_json::from_value(value.clone()).map_err(|e| {
+                    BeemFlowError::adapter(format!("core.deliver 'rate_limit' is invalid: {}", e))
+                })?;
+            limit.validate()?;
+            Some(limit)
+        }
+    };
+
+    let now = Utc::now();
+    Ok(OutboundDelivery {
+        id: Uuid::new_v4(),
+        method,
+        url: url.to_string(),
+        headers,
+        body: inputs.get("body").filter(|body| !body.is_null()).cloned(),
+        sealed: None,
+        rate_limit,
+        attempts: 0,
+        max_attempts,
+        last_error: None,
+        next_attempt_at: now,
+        created_at: now,
+        dead_lettered_at: None,
+        run_id,
+    })
+}
+
+/// Make the first attempt of `delivery`, queueing it for retry if it fails
+///
+/// Returns the outputs of the `core.deliver` step.
+pub async fn deliver(
+    ctx: &ExecutionContext,
+    mut delivery: OutboundDelivery,
+) -> Result<HashMap<String, Value>> {
+    let mut result = HashMap::new();
+    result.insert(
+        "delivery_id".to_string(),
+        Value::String(delivery.id.to_string()),
+    );
+
+    match attempt(&mut delivery, ctx).await {
+        Ok(response) => {
+            result.insert("delivered".to_string(), Value::Bool(true));
+            result.insert("queued".to_string(), Value::Bool(false));
+            result.insert("response".to_string(), Value::Object(response));
+        }
+        Err(error) => {
+            tracing::warn!(
+                "Delivery {} to {} failed, queueing for retry: {}",
+                delivery.id,
+                delivery.url,
+                error
+            );
+            delivery.last_error = Some(error.clone());
+            let key = sealing_key(ctx.secrets_provider.as_ref()).await;
+            let queued =
+                settle_failure(ctx.storage.as_ref(), &key, &mut delivery, Utc::now()).await?;
+            result.insert("delivered".to_string(), Value::Bool(false));
+            result.insert("queued".to_string(), Value::Bool(queued));
+            result.insert("error".to_string(), Value::String(error));
+        }
+    }
+    Ok(result)
+}
+
+/// Delay before the attempt following `attempts` failed ones
+pub fn retry_delay(attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(31);
+    RETRY_BASE_DELAY
+        .saturating_mul(1 << exponent)
+        .min(RETRY_MAX_DELAY)
+}
+
+/// Send `delivery` once through the HTTP adapter, counting the attempt
+///
+/// Returns the adapter's outputs, or why the attempt failed.
+async fn attempt(
+    delivery: &mut OutboundDelivery,
+    ctx: &ExecutionContext,
+) -> std::result::Result<serde_json::Map<String, Value>, String> {
+    delivery.attempts += 1;
+    if let Some(limit) = &delivery.rate_limit {
+        let host = url::Url::parse(&delivery.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default();
+        ctx.rate_limiter
+            .acquire(&format!("{}:{}", CORE_DELIVER, host), limit)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut inputs = HashMap::from([
+        ("url".to_string(), Value::String(delivery.url.clone())),
+        ("method".to_string(), Value::String(delivery.method.clone())),
+        (
+            "headers".to_string(),
+            serde_json::to_value(&delivery.headers).map_err(|e| e.to_string())?,
+        ),
+    ]);
+    if let Some(ref body) = delivery.body {
+        inputs.insert("body".to_string(), body.clone());
+    }
+
+    match tokio::time::timeout(REQUEST_TIMEOUT, HTTP.execute(inputs, ctx)).await {
+        Ok(Ok(outputs)) => Ok(outputs.into_iter().collect()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("timed out after {:?}", REQUEST_TIMEOUT)),
+    }
+}
+
+/// Record a failed attempt: schedule the next one, or dead-letter the delivery
+///
+/// The delivery is stored sealed under `key`. Returns whether it is still
+/// queued.
+async fn settle_failure(
+    storage: &dyn Storage,
+    key: &LessSafeKey,
+    delivery: &mut OutboundDelivery,
+    now: chrono::DateTime<Utc>,
+) -> Result<bool> {
+    if delivery.attempts >= delivery.max_attempts {
+        tracing::error!(
+            "Delivery {} to {} failed {} times, dead-lettering it",
+            delivery.id,
+            delivery.url,
+            delivery.attempts
+        );
+        delivery.dead_lettered_at = Some(now);
+        storage.dead_letter_delivery(&seal(key, delivery)?).await?;
+        return Ok(false);
+    }
+
+    let delay = chrono::Duration::from_std(retry_delay(delivery.attempts))
+        .map_err(|e| BeemFlowError::internal(format!("Invalid retry delay: {}", e)))?;
+    delivery.next_attempt_at = now + delay;
+    storage.save_delivery(&seal(key, delivery)?).await?;
+    Ok(true)
+}
+
+/// Headers and body of a delivery, as sealed for storage
+#[derive(Serialize, Deserialize)]
+struct SealedRequest {
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: Option<Value>,
+}
+
+/// Get the key sealing stored deliveries (`BEEMFLOW_DELIVERY_SECRET`)
+async fn sealing_key(secrets: &dyn SecretsProvider) -> LessSafeKey {
+    let secret = super::resume::server_key(secrets, ENV_DELIVERY_SECRET).await;
+    let key = UnboundKey::new(&AES_256_GCM, &Sha256::digest(&secret))
+        .expect("a SHA-256 digest is a valid AES-256 key");
+    LessSafeKey::new(key)
+}
+
+/// Copy `delivery` with its headers and body moved into `sealed`
+///
+/// The ciphertext is bound to the delivery's ID, so it can't be swapped
+/// between deliveries.
+fn seal(key: &LessSafeKey, delivery: &OutboundDelivery) -> Result<OutboundDelivery> {
+    if delivery.sealed.is_some() {
+        return Ok(delivery.clone());
+    }
+    let mut payload = serde_json::to_vec(&SealedRequest {
+        headers: delivery.headers.clone(),
+        body: delivery.body.clone(),
+    })?;
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(delivery.id.as_bytes()),
+        &mut payload,
+    )
+    .map_err(|_| BeemFlowError::internal("Failed to seal delivery"))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&payload);
+    Ok(OutboundDelivery {
+        headers: HashMap::new(),
+        body: None,
+        sealed: Some(format!(
+            "{}{}",
+            SEALED_PREFIX,
+            URL_SAFE_NO_PAD.encode(sealed)
+        )),
+        ..delivery.clone()
+    })
+}
+
+/// Restore the headers and body of a delivery read from storage
+///
+/// Deliveries stored before sealing existed are returned as they are.
+fn open(key: &LessSafeKey, mut delivery: OutboundDelivery) -> Result<OutboundDelivery> {
+    let Some(sealed) = delivery.sealed.take() else {
+        return Ok(delivery);
+    };
+    let unreadable = || {
+        BeemFlowError::internal(format!(
+            "Delivery {} can't be opened; {} is unset or has changed since it was queued",
+            delivery.id, ENV_DELIVERY_SECRET
+        ))
+    };
+    let mut sealed = sealed
+        .strip_prefix(SEALED_PREFIX)
+        .and_then(|encoded| URL_SAFE_NO_PAD.decode(encoded).ok())
+        .filter(|sealed| sealed.len() >= NONCE_LEN)
+        .ok_or_else(unreadable)?;
+    let mut payload = sealed.split_off(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(&sealed).map_err(|_| unreadable())?;
+    let plain = key
+        .open_in_place(nonce, Aad::from(delivery.id.as_bytes()), &mut payload)
+        .map_err(|_| unreadable())?;
+    let request: SealedRequest = serde_json::from_slice(plain)?;
+
+    delivery.headers = request.headers;
+    delivery.body = request.body;
+    Ok(delivery)
+}
+
+/// Outcome of one pass over the due deliveries
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct RetryReport {
+    /// Deliveries that succeeded and left the queue
+    pub delivered: usize,
+    /// Deliveries that failed again and were rescheduled
+    pub rescheduled: usize,
+    /// Deliveries that failed their last attempt, or couldn't be opened, and
+    /// were dead-lettered
+    pub dead_lettered: usize,
+}
+
+/// Retries queued deliveries as they fall due
+pub struct DeliveryWorker {
+    /// Context retries are sent with; each gets the run ID and tenant of its delivery
+    ctx: ExecutionContext,
+    clock: Arc<dyn Clock>,
+    /// Serializes passes within the process; claims keep other processes out
+    retrying: Mutex<()>,
+}
+
+impl DeliveryWorker {
+    /// Create a worker retrying the queued deliveries in `ctx`'s storage, due by `clock`
+    ///
+    /// Retries use `ctx`'s secrets, OAuth client and rate limiter.
+    pub fn new(ctx: ExecutionContext, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            ctx,
+            clock,
+            retrying: Mutex::new(()),
+        }
+    }
+
+    /// Create a worker like this one, with deliveries due by `clock`
+    pub fn with_clock(&self, clock: Arc<dyn Clock>) -> Self {
+        Self::new(self.ctx.clone(), clock)
+    }
+
+    /// Attempt every delivery that is due, longest overdue first
+    pub async fn retry_due(&self) -> Result<RetryReport> {
+        let _guard = self.retrying.lock().await;
+        let storage = self.ctx.storage.as_ref();
+        let key = sealing_key(self.ctx.secrets_provider.as_ref()).await;
+        let lease = chrono::Duration::from_std(CLAIM_LEASE)
+            .map_err(|e| BeemFlowError::internal(format!("Invalid claim lease: {}", e)))?;
+        let mut report = RetryReport::default();
+
+        loop {
+            let now = self.clock.now();
+            let claimed = storage
+                .claim_due_deliveries(now, now + lease, RETRY_BATCH_SIZE)
+                .await?;
+            let batch_len = claimed.len();
+
+            for stored in claimed {
+                let mut delivery = match open(&key, stored.clone()) {
+                    Ok(delivery) => delivery,
+                    Err(e) => {
+                        tracing::error!("{}; dead-lettering it", e);
+                        let dead = OutboundDelivery {
+                            last_error: Some(e.to_string()),
+                            dead_lettered_at: Some(self.clock.now()),
+                            ..stored
+                        };
+                        storage.dead_letter_delivery(&dead).await?;
+                        report.dead_lettered += 1;
+                        continue;
+                    }
+                };
+
+                let ctx = self.context_for(&delivery).await?;
+                match attempt(&mut delivery, &ctx).await {
+                    Ok(_) => {
+                        tracing::info!(
+                            "Delivery {} to {} succeeded on attempt {}",
+                            delivery.id,
+                            delivery.url,
+                            delivery.attempts
+                        );
+                        storage.delete_delivery(delivery.id).await?;
+                        report.delivered += 1;
+                    }
+                    Err(error) => {
+                        delivery.last_error = Some(error);
+                        let now = self.clock.now();
+                        if settle_failure(storage, &key, &mut delivery, now).await? {
+                            report.rescheduled += 1;
+                        } else {
+                            report.dead_lettered += 1;
+                        }
+                    }
+                }
+            }
+
+            if batch_len < RETRY_BATCH_SIZE {
+                return Ok(report);
+            }
+        }
+    }
+
+    /// Context to retry `delivery` with, scoped to the run that made it
+    async fn context_for(&self, delivery: &OutboundDelivery) -> Result<ExecutionContext> {
+        let tenant_id = match delivery.run_id {
+            Some(run_id) => self
+                .ctx
+                .storage
+                .get_run(run_id)
+                .await?
+                .and_then(|run| run.tenant_id),
+            None => None,
+        };
+        Ok(self
+            .ctx
+            .clone()
+            .with_run_id(delivery.run_id)
+            .with_tenant_id(tenant_id))
+    }
+
+    /// Open dead-lettered deliveries for display, with secrets masked
+    ///
+    /// Deliveries that can't be opened are returned still sealed.
+    pub async fn reveal(&self, deliveries: Vec<OutboundDelivery>) -> Vec<OutboundDelivery> {
+        let key = sealing_key(self.ctx.secrets_provider.as_ref()).await;
+        deliveries
+            .into_iter()
+            .map(|stored| match open(&key, stored.clone()) {
+                Ok(mut delivery) => {
+                    let headers = serde_json::to_value(&delivery.headers).unwrap_or_default();
+                    delivery.headers = serde_json::from_value(crate::utils::mask_secrets(&headers))
+                        .unwrap_or_default();
+                    delivery.body = delivery.body.as_ref().map(crate::utils::mask_secrets);
+                    delivery
+                }
+                Err(_) => stored,
+            })
+            .collect()
+    }
+
+    /// Retry due deliveries every `interval` until `cancel` fires
+    pub async fn run(self: Arc<Self>, interval: Duration, cancel: CancellationToken) {
+        loop {
+            if let Err(e) = self.retry_due().await {
+                tracing::warn!("Failed to retry outbound deliveries: {}", e);
+            }
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+    }
+}
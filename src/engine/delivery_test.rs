@@ -0,0 +1,263 @@
+use super::Engine;
+use super::delivery::{RetryReport, retry_delay};
+use crate::clock::{Clock, MockClock};
+use chrono::Utc;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Endpoint answering the first `failures` requests with 503, then 200
+async fn flaky_endpoint(failures: usize) -> (MockServer, Arc<AtomicUsize>) {
+    let server = MockServer::start().await;
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = calls.clone();
+    Mock::given(wiremock::matchers::method("POST"))
+        .respond_with(move |_: &wiremock::Request| {
+            if counted.fetch_add(1, Ordering::SeqCst) < failures {
+                ResponseTemplate::new(503)
+            } else {
+                ResponseTemplate::new(200)
+            }
+        })
+        .mount(&server)
+        .await;
+    (server, calls)
+}
+
+async fn engine_with_clock() -> (Engine, Arc<MockClock>) {
+    let clock = Arc::new(MockClock::new(Utc::now()));
+    let engine = Engine::for_testing().await.with_clock(clock.clone());
+    (engine, clock)
+}
+
+/// Run a flow with one `core.deliver` step, returning the step's outputs
+async fn deliver(engine: &Engine, url: &str, max_attempts: u32) -> serde_json::Value {
+    let yaml = r#"
+name: notify
+on: cli.manual
+steps:
+  - id: notify
+    use: core.deliver
+    with:
+      url: "{{ event.url }}"
+      headers:
+        X-Order: "{{ event.order }}"
+        Authorization: "Bearer {{ event.token }}"
+      body:
+        order: "{{ event.order }}"
+      max_attempts: MAX_ATTEMPTS
+"#
+    .replace("MAX_ATTEMPTS", &max_attempts.to_string());
+    let flow = crate::dsl::parse_string(&yaml, None).unwrap();
+    let event = HashMap::from([
+        ("url".to_string(), json!(url)),
+        ("order".to_string(), json!("o-1")),
+        ("token".to_string(), json!("partner-token-123")),
+    ]);
+    let result = engine.execute(&flow, event).await.unwrap();
+    result.outputs["notify"].clone()
+}
+
+#[tokio::test]
+async fn test_transient_failures_are_retried_until_delivered() {
+    let (server, calls) = flaky_endpoint(2).await;
+    let (engine, clock) = engine_with_clock().await;
+
+    let outputs = deliver(&engine, &server.uri(), 5).await;
+    assert_eq!(outputs["delivered"], false);
+    assert_eq!(outputs["queued"], true);
+    assert!(
+        outputs["error"].as_str().unwrap().contains("status 503"),
+        "{}",
+        outputs["error"]
+    );
+
+    // Not due until the backoff has passed
+    let worker = engine.deliveries();
+    assert_eq!(worker.retry_due().await.unwrap(), RetryReport::default());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    clock.advance(chrono::Duration::seconds(31));
+    let report = worker.retry_due().await.unwrap();
+    assert_eq!(report.rescheduled, 1);
+    let queued = engine
+        .storage()
+        .list_due_deliveries(clock.now() + chrono::Duration::hours(1), 10)
+        .await
+        .unwrap();
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].attempts, 2);
+    // Stored sealed: no header or body in the clear
+    assert!(queued[0].headers.is_empty());
+    assert!(queued[0].body.is_none());
+    let sealed = queued[0].sealed.as_deref().unwrap();
+    assert!(!sealed.contains("partner-token-123") && !sealed.contains("o-1"));
+
+    clock.advance(chrono::Duration::seconds(61));
+    let report = worker.retry_due().await.unwrap();
+    assert_eq!(report.delivered, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    // Delivered: gone from the queue, never dead-lettered
+    let storage = engine.storage();
+    assert!(
+        storage
+            .list_due_deliveries(clock.now() + chrono::Duration::hours(24), 10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        storage
+            .list_dead_letters(10, None)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(
+        requests[2].body_json::<serde_json::Value>().unwrap(),
+        json!({"order": "o-1"})
+    );
+    assert_eq!(
+        requests[2].headers["authorization"],
+        "Bearer partner-token-123"
+    );
+    assert_eq!(requests[2].headers["x-order"], "o-1");
+}
+
+#[tokio::test]
+async fn test_exhausted_deliveries_are_dead_lettered() {
+    let (server, calls) = flaky_endpoint(usize::MAX).await;
+    let (engine, clock) = engine_with_clock().await;
+
+    let outputs = deliver(&engine, &server.uri(), 3).await;
+    let delivery_id = outputs["delivery_id"].as_str().unwrap().to_string();
+    let worker = engine.deliveries();
+    for expected in [
+        RetryReport {
+            rescheduled: 1,
+            ..Default::default()
+        },
+        RetryReport {
+            dead_lettered: 1,
+            ..Default::default()
+        },
+    ] {
+        clock.advance(chrono::Duration::hours(1));
+        assert_eq!(worker.retry_due().await.unwrap(), expected);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let dead = engine.storage().list_dead_letters(10, None).await.unwrap();
+    assert_eq!(dead.len(), 1);
+    assert!(dead[0].sealed.is_some());
+
+    // Opened for display, with the credential masked
+    let dead = worker.reveal(dead).await;
+    assert_eq!(dead[0].id.to_string(), delivery_id);
+    assert_eq!(dead[0].attempts, 3);
+    assert_eq!(dead[0].body, Some(json!({"order": "o-1"})));
+    assert_eq!(dead[0].headers["X-Order"], "o-1");
+    assert!(!dead[0].headers["Authorization"].contains("partner-token-123"));
+    assert!(dead[0].sealed.is_none());
+    assert!(
+        dead[0]
+            .last_error
+            .as_deref()
+            .unwrap()
+            .contains("status 503")
+    );
+    assert!(dead[0].dead_lettered_at.is_some());
+
+    // Dead letters are not retried
+    clock.advance(chrono::Duration::hours(24));
+    assert_eq!(worker.retry_due().await.unwrap(), RetryReport::default());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_single_attempt_deliveries_dead_letter_at_once() {
+    let (server, _) = flaky_endpoint(usize::MAX).await;
+    let (engine, _) = engine_with_clock().await;
+
+    let outputs = deliver(&engine, &server.uri(), 1).await;
+    assert_eq!(outputs["queued"], false);
+    assert_eq!(
+        engine
+            .storage()
+            .list_dead_letters(10, None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_retry_delay_doubles_up_to_the_cap() {
+    assert_eq!(retry_delay(1), Duration::from_secs(30));
+    assert_eq!(retry_delay(2), Duration::from_secs(60));
+    assert_eq!(retry_delay(4), Duration::from_secs(240));
+    assert_eq!(retry_delay(8), Duration::from_secs(3600));
+    assert_eq!(retry_delay(u32::MAX), Duration::from_secs(3600));
+}
+
+#[tokio::test]
+async fn test_claimed_deliveries_are_not_retried_by_another_worker() {
+    let (server, calls) = flaky_endpoint(1).await;
+    let (engine, clock) = engine_with_clock().await;
+    deliver(&engine, &server.uri(), 5).await;
+    clock.advance(chrono::Duration::seconds(31));
+
+    // Another instance claimed the delivery and is still sending it
+    let storage = engine.storage();
+    let lease_until = clock.now() + chrono::Duration::minutes(10);
+    let claimed = storage
+        .claim_due_deliveries(clock.now(), lease_until, 10)
+        .await
+        .unwrap();
+    assert_eq!(claimed.len(), 1);
+    let worker = engine.deliveries();
+    assert_eq!(worker.retry_due().await.unwrap(), RetryReport::default());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // A lease that ran out means the claimant died: the delivery is retried
+    clock.advance(chrono::Duration::minutes(10));
+    assert_eq!(worker.retry_due().await.unwrap().delivered, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_deliveries_that_cannot_be_opened_are_dead_lettered() {
+    let (server, calls) = flaky_endpoint(usize::MAX).await;
+    let (engine, clock) = engine_with_clock().await;
+    deliver(&engine, &server.uri(), 5).await;
+
+    // Sealed under another key, as after a restart without BEEMFLOW_DELIVERY_SECRET
+    let storage = engine.storage();
+    let mut queued = storage
+        .list_due_deliveries(clock.now() + chrono::Duration::hours(1), 10)
+        .await
+        .unwrap()
+        .remove(0);
+    queued.sealed = Some("v1.AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA".to_string());
+    storage.delete_delivery(queued.id).await.unwrap();
+    storage.save_delivery(&queued).await.unwrap();
+
+    clock.advance(chrono::Duration::seconds(31));
+    let report = engine.deliveries().retry_due().await.unwrap();
+    assert_eq!(report.dead_lettered, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    let dead = storage.list_dead_letters(10, None).await.unwrap();
+    assert!(
+        dead[0]
+            .last_error
+            .as_deref()
+            .unwrap()
+            .contains("BEEMFLOW_DELIVERY_SECRET")
+    );
+}
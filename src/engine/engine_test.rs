@@ -1,5 +1,6 @@
 use super::*;
-use crate::model::{Flow, Step, Trigger};
+use crate::clock::Clock;
+use crate::model::{Flow, FlowName, Step, Trigger};
 use std::collections::HashMap;
 
 #[tokio::test]
@@ -80,12 +81,17 @@ async fn test_adapter_registration() {
 async fn test_execute_minimal_valid_flow() {
     let engine = Engine::for_testing().await;
     let flow = Flow {
-        name: "test".to_string().into(),
+        name: FlowName::new("test").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(Trigger::Single("cli.manual".to_string())),
         cron: None,
         vars: None,
+        input_schema: None,
         steps: vec![Step {
             id: "s1".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -97,7 +103,12 @@ async fn test_execute_minimal_valid_flow() {
             ..Default::default()
         }],
         catch: None,
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     };
 
     let result = engine.execute(&flow, HashMap::new()).await;
@@ -111,15 +122,25 @@ async fn test_execute_minimal_valid_flow() {
 async fn test_execute_empty_steps() {
     let engine = Engine::for_testing().await;
     let flow = Flow {
-        name: "empty".to_string().into(),
+        name: FlowName::new("empty").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(Trigger::Single("cli.manual".to_string())),
         cron: None,
         vars: None,
+        input_schema: None,
         steps: vec![],
         catch: None,
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     };
 
     let result = engine.execute(&flow, HashMap::new()).await;
@@ -135,12 +156,17 @@ async fn test_execute_empty_steps() {
 async fn test_execute_with_event_data() {
     let engine = Engine::for_testing().await;
     let flow = Flow {
-        name: "event_test".to_string().into(),
+        name: FlowName::new("event_test").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(Trigger::Single("cli.manual".to_string())),
         cron: None,
         vars: None,
+        input_schema: None,
         steps: vec![Step {
             id: "echo_event".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -155,7 +181,12 @@ async fn test_execute_with_event_data() {
             ..Default::default()
         }],
         catch: None,
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     };
 
     let mut event = HashMap::new();
@@ -169,8 +200,12 @@ async fn test_execute_with_event_data() {
 async fn test_execute_with_vars() {
     let engine = Engine::for_testing().await;
     let flow = Flow {
-        name: "vars_test".to_string().into(),
+        name: FlowName::new("vars_test").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(Trigger::Single("cli.manual".to_string())),
         cron: None,
@@ -180,6 +215,7 @@ async fn test_execute_with_vars() {
             m.insert("name".to_string(), serde_json::json!("World"));
             m
         }),
+        input_schema: None,
         steps: vec![Step {
             id: "echo_vars".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -194,7 +230,12 @@ async fn test_execute_with_vars() {
             ..Default::default()
         }],
         catch: None,
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     };
 
     let result = engine.execute(&flow, HashMap::new()).await;
@@ -210,12 +251,17 @@ async fn test_execute_with_vars() {
 async fn test_execute_step_output_chaining() {
     let engine = Engine::for_testing().await;
     let flow = Flow {
-        name: "chaining_test".to_string().into(),
+        name: FlowName::new("chaining_test").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(Trigger::Single("cli.manual".to_string())),
         cron: None,
         vars: None,
+        input_schema: None,
         steps: vec![
             Step {
                 id: "step1".to_string().into(),
@@ -242,7 +288,12 @@ async fn test_execute_step_output_chaining() {
             },
         ],
         catch: None,
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     };
 
     let result = engine.execute(&flow, HashMap::new()).await;
@@ -261,12 +312,17 @@ async fn test_execute_step_output_chaining() {
 async fn test_execute_concurrent_flows() {
     let engine = Arc::new(Engine::for_testing().await);
     let flow = Arc::new(Flow {
-        name: "concurrent".to_string().into(),
+        name: FlowName::new("concurrent").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(Trigger::Single("cli.manual".to_string())),
         cron: None,
         vars: None,
+        input_schema: None,
         steps: vec![Step {
             id: "s1".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -278,7 +334,12 @@ async fn test_execute_concurrent_flows() {
             ..Default::default()
         }],
         catch: None,
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     });
 
     // Spawn 5 concurrent executions
@@ -305,12 +366,17 @@ async fn test_execute_concurrent_flows() {
 async fn test_execute_catch_block() {
     let engine = Engine::for_testing().await;
     let flow = Flow {
-        name: "catch_test".to_string().into(),
+        name: FlowName::new("catch_test").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(Trigger::Single("cli.manual".to_string())),
         cron: None,
         vars: None,
+        input_schema: None,
         steps: vec![Step {
             id: "fail".to_string().into(),
             use_: Some("nonexistent.adapter".to_string()),
@@ -339,7 +405,12 @@ async fn test_execute_catch_block() {
                 ..Default::default()
             },
         ]),
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     };
 
     let result = engine.execute(&flow, HashMap::new()).await;
@@ -409,12 +480,17 @@ async fn test_execute_catch_block() {
 async fn test_execute_secrets_injection() {
     let engine = Engine::for_testing().await;
     let flow = Flow {
-        name: "secrets_test".to_string().into(),
+        name: FlowName::new("secrets_test").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(Trigger::Single("cli.manual".to_string())),
         cron: None,
         vars: None,
+        input_schema: None,
         steps: vec![Step {
             id: "s1".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -429,7 +505,12 @@ async fn test_execute_secrets_injection() {
             ..Default::default()
         }],
         catch: None,
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     };
 
     let mut event = HashMap::new();
@@ -453,12 +534,17 @@ async fn test_execute_secrets_injection() {
 async fn test_execute_secrets_dot_access() {
     let engine = Engine::for_testing().await;
     let flow = Flow {
-        name: "secrets_dot".to_string().into(),
+        name: FlowName::new("secrets_dot").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(Trigger::Single("cli.manual".to_string())),
         cron: None,
         vars: None,
+        input_schema: None,
         steps: vec![Step {
             id: "s1".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -473,7 +559,12 @@ async fn test_execute_secrets_dot_access() {
             ..Default::default()
         }],
         catch: None,
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     };
 
     let mut event = HashMap::new();
@@ -502,16 +593,93 @@ async fn test_execute_secrets_dot_access() {
     );
 }
 
+#[tokio::test]
+async fn test_persisted_step_inputs_are_redacted() {
+    let engine = Engine::for_testing().await;
+    let flow = Flow {
+        name: FlowName::new("inputs_redaction").unwrap(),
+        description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
+        version: None,
+        on: Some(Trigger::Single("cli.manual".to_string())),
+        steps: vec![Step {
+            id: "s1".to_string().into(),
+            use_: Some("core.echo".to_string()),
+            with: Some({
+                let mut m = HashMap::new();
+                m.insert(
+                    "text".to_string(),
+                    serde_json::json!("Bearer {{ secrets.API_KEY }} for {{ event.user }}"),
+                );
+                m
+            }),
+            ..Default::default()
+        }],
+        cron: None,
+        vars: None,
+        input_schema: None,
+        catch: None,
+        on_success: None,
+        on_failure: None,
+        mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
+    };
+
+    let mut event = HashMap::new();
+    event.insert("user".to_string(), serde_json::json!("alice"));
+    event.insert(
+        "secrets".to_string(),
+        serde_json::json!({
+            "API_KEY": "secret123"
+        }),
+    );
+
+    let result = engine
+        .execute(&flow, event)
+        .await
+        .expect("Flow should execute");
+
+    let steps = engine
+        .storage()
+        .get_steps(result.run_id)
+        .await
+        .expect("Failed to get steps");
+    let inputs = steps[0]
+        .inputs
+        .as_ref()
+        .expect("Rendered inputs should be persisted");
+
+    assert_eq!(
+        inputs.get("text").and_then(|v| v.as_str()),
+        Some(format!("Bearer {} for alice", crate::secrets::REDACTED).as_str()),
+        "Secret values must be redacted before persistence"
+    );
+    assert!(
+        !inputs.contains_key(crate::constants::PARAM_SPECIAL_USE),
+        "Internal __use parameter should not be persisted"
+    );
+}
+
 #[tokio::test]
 async fn test_execute_array_access_in_template() {
     let engine = Engine::for_testing().await;
     let flow = Flow {
-        name: "array_access".to_string().into(),
+        name: FlowName::new("array_access").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(Trigger::Single("cli.manual".to_string())),
         cron: None,
         vars: None,
+        input_schema: None,
         steps: vec![Step {
             id: "s1".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -528,7 +696,12 @@ async fn test_execute_array_access_in_template() {
             ..Default::default()
         }],
         catch: None,
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     };
 
     let mut event = HashMap::new();
@@ -562,12 +735,17 @@ async fn test_execute_array_access_in_template() {
 async fn test_adapter_error_propagation() {
     let engine = Engine::for_testing().await;
     let flow = Flow {
-        name: "adapter_error".to_string().into(),
+        name: FlowName::new("adapter_error").unwrap(),
         description: None,
+        owner: None,
+        team: None,
+        tags: None,
+        labels: None,
         version: None,
         on: Some(Trigger::Single("cli.manual".to_string())),
         cron: None,
         vars: None,
+        input_schema: None,
         steps: vec![Step {
             id: "s1".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -575,7 +753,12 @@ async fn test_adapter_error_propagation() {
             ..Default::default()
         }],
         catch: None,
+        on_success: None,
+        on_failure: None,
         mcp_servers: None,
+        strict_params: None,
+        environments: None,
+        blob_store: None,
     };
 
     let result = engine.execute(&flow, HashMap::new()).await;
@@ -661,26 +844,232 @@ async fn test_generate_deterministic_run_id() {
     );
 }
 
+/// Engine reading the time from a mock clock set to `at`
+async fn engine_at(at: &str) -> (Engine, Arc<crate::clock::MockClock>) {
+    let clock = Arc::new(crate::clock::MockClock::new(at.parse().unwrap()));
+    let engine = Engine::for_testing().await.with_clock(clock.clone());
+    (engine, clock)
+}
+
 #[tokio::test]
 async fn test_generate_deterministic_run_id_time_window() {
-    let engine = Engine::for_testing().await;
-    // Verify that UUIDs within the same minute window are identical
+    let (engine, clock) = engine_at("2026-03-01T12:00:00Z").await;
     let flow_name = "test-flow";
-    let event: HashMap<String, serde_json::Value> = {
-        let mut m = HashMap::new();
-        m.insert("key".to_string(), serde_json::json!("value"));
-        m
+    let event = HashMap::from([("key".to_string(), serde_json::json!("value"))]);
+
+    // One ID for the whole minute
+    let id = engine.generate_deterministic_run_id(flow_name, &event);
+    clock.set("2026-03-01T12:00:59.999Z".parse().unwrap());
+    assert_eq!(engine.generate_deterministic_run_id(flow_name, &event), id);
+
+    // The next minute starts a new window
+    clock.advance(chrono::Duration::milliseconds(1));
+    let next = engine.generate_deterministic_run_id(flow_name, &event);
+    assert_ne!(next, id);
+
+    clock.set("2026-03-01T12:00:30Z".parse().unwrap());
+    assert_eq!(engine.generate_deterministic_run_id(flow_name, &event), id);
+}
+
+#[tokio::test]
+async fn test_deterministic_dedup_window_boundary() {
+    let (engine, clock) = engine_at("2026-03-01T12:00:30Z").await;
+    let flow = echo_flow("dedup_window");
+    let event = HashMap::from([("n".to_string(), serde_json::json!(1))]);
+
+    let first = engine.execute(&flow, event.clone()).await.unwrap();
+    let run = engine
+        .storage()
+        .get_run(first.run_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.started_at, clock.now());
+    assert_eq!(run.ended_at, Some(clock.now()));
+
+    // Last instant of the window: still a duplicate
+    clock.set("2026-03-01T12:00:59.999Z".parse().unwrap());
+    let err = engine.execute(&flow, event.clone()).await.unwrap_err();
+    assert!(
+        matches!(err, BeemFlowError::DuplicateRun { run_id, .. } if run_id == first.run_id),
+        "{:?}",
+        err
+    );
+
+    // First instant of the next window: a new run
+    clock.advance(chrono::Duration::milliseconds(1));
+    let second = engine.execute(&flow, event).await.unwrap();
+    assert_ne!(second.run_id, first.run_id);
+}
+
+#[tokio::test]
+async fn test_approval_expiry_follows_clock() {
+    let (engine, clock) = engine_at("2026-03-01T12:00:00Z").await;
+    let flow = crate::dsl::parse_string(
+        "name: clocked_approval\nsteps:\n  - id: approve\n    use: core.approval\n    with:\n      message: Ship it?\n      timeout: 1h\n",
+        None,
+    )
+    .unwrap();
+    assert!(engine.execute(&flow, HashMap::new()).await.is_err());
+
+    let run = engine.storage().list_runs(1, 0).await.unwrap().remove(0);
+    let steps = engine.storage().get_steps(run.id).await.unwrap();
+    let token = steps[0].outputs.as_ref().unwrap()["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let request = engine.get_approval(&token).await.unwrap();
+    assert_eq!(request.expires_at, clock.now() + chrono::Duration::hours(1));
+
+    // Valid through the last instant of the timeout, expired right after
+    clock.advance(chrono::Duration::hours(1));
+    assert!(engine.get_approval(&token).await.is_ok());
+    clock.advance(chrono::Duration::milliseconds(1));
+    let err = engine.get_approval(&token).await.unwrap_err();
+    assert!(err.to_string().contains("expired"), "{}", err);
+}
+
+fn echo_flow(name: &str) -> Flow {
+    let mut flow = Flow::test(name);
+    flow.steps = vec![Step {
+        id: "s1".to_string().into(),
+        use_: Some("core.echo".to_string()),
+        with: Some(HashMap::from([(
+            "text".to_string(),
+            serde_json::json!("{{ event.n }}"),
+        )])),
+        ..Default::default()
+    }];
+    flow
+}
+
+#[tokio::test]
+async fn test_run_id_strategy_deterministic_dedups_identical_events() {
+    let engine = Engine::for_testing().await;
+    let flow = echo_flow("dedup_deterministic");
+    let event = HashMap::from([("n".to_string(), serde_json::json!(1))]);
+
+    let first = engine.execute(&flow, event.clone()).await.unwrap();
+    let err = engine.execute(&flow, event).await.unwrap_err();
+    assert!(err.to_string().contains("Duplicate run"), "{}", err);
+    assert!(
+        matches!(err, BeemFlowError::DuplicateRun { run_id, .. } if run_id == first.run_id),
+        "{:?}",
+        err
+    );
+
+    // A different event is a different run
+    let other = HashMap::from([("n".to_string(), serde_json::json!(2))]);
+    assert!(engine.execute(&flow, other).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_run_id_strategy_random_never_dedups() {
+    let engine = Engine::for_testing().await;
+    let flow = echo_flow("dedup_random");
+    let event = HashMap::from([("n".to_string(), serde_json::json!(1))]);
+    let options = RunOptions {
+        run_id_strategy: Some(RunIdStrategy::Random),
+        ..Default::default()
     };
 
-    let id1 = engine.generate_deterministic_run_id(flow_name, &event);
+    let first = engine
+        .execute_with(&flow, event.clone(), &options)
+        .await
+        .unwrap();
+    let second = engine.execute_with(&flow, event, &options).await.unwrap();
+    assert_ne!(first.run_id, second.run_id);
+}
+
+#[tokio::test]
+async fn test_run_id_strategy_client_dedups_by_key() {
+    let engine = Engine::for_testing().await;
+    let flow = echo_flow("dedup_client");
+    let keyed = |key: &str| RunOptions {
+        idempotency_key: Some(key.to_string()),
+        ..Default::default()
+    };
 
-    // Sleep a tiny bit
-    std::thread::sleep(std::time::Duration::from_millis(5));
+    // Same key dedups even when the event differs
+    let first = engine
+        .execute_with(
+            &flow,
+            HashMap::from([("n".to_string(), serde_json::json!(1))]),
+            &keyed("order-42"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        first.run_id,
+        Engine::generate_client_run_id("dedup_client", "order-42")
+    );
+    let err = engine
+        .execute_with(
+            &flow,
+            HashMap::from([("n".to_string(), serde_json::json!(2))]),
+            &keyed("order-42"),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, BeemFlowError::DuplicateRun { run_id, .. } if run_id == first.run_id),
+        "{:?}",
+        err
+    );
 
-    let id2 = engine.generate_deterministic_run_id(flow_name, &event);
+    // Same event under a new key runs again
+    engine
+        .execute_with(
+            &flow,
+            HashMap::from([("n".to_string(), serde_json::json!(1))]),
+            &keyed("order-43"),
+        )
+        .await
+        .unwrap();
+
+    // Keys are scoped per flow
+    assert_ne!(
+        Engine::generate_client_run_id("other_flow", "order-42"),
+        first.run_id
+    );
+
+    // The client strategy without a key is rejected
+    let options = RunOptions {
+        run_id_strategy: Some(RunIdStrategy::Client),
+        ..Default::default()
+    };
+    assert!(
+        engine
+            .execute_with(&flow, HashMap::new(), &options)
+            .await
+            .is_err()
+    );
+}
 
-    // Should still be the same (within 1 min window)
-    assert_eq!(id1, id2, "UUIDs within same minute should be identical");
+#[tokio::test]
+async fn test_run_id_strategy_config_default() {
+    let mut engine = Engine::for_testing().await;
+    engine.config = Arc::new(crate::config::Config {
+        run_id_strategy: Some(RunIdStrategy::Random),
+        ..Default::default()
+    });
+    let flow = echo_flow("dedup_config");
+    let event = HashMap::from([("n".to_string(), serde_json::json!(1))]);
+
+    engine.execute(&flow, event.clone()).await.unwrap();
+    engine.execute(&flow, event.clone()).await.unwrap();
+
+    // An explicit strategy overrides the config
+    let options = RunOptions {
+        run_id_strategy: Some(RunIdStrategy::Deterministic),
+        ..Default::default()
+    };
+    engine
+        .execute_with(&flow, event.clone(), &options)
+        .await
+        .unwrap();
+    assert!(engine.execute_with(&flow, event, &options).await.is_err());
 }
 
 #[tokio::test]
@@ -720,9 +1109,10 @@ async fn test_await_event_resume_roundtrip() {
         .await
         .expect("Should load paused runs");
     assert_eq!(paused_runs.len(), 1, "Should have one paused run");
+    let key = resume::storage_key("abc123");
     assert!(
-        paused_runs.contains_key("abc123"),
-        "Should have token abc123"
+        paused_runs.contains_key(&key),
+        "Should be stored under the hash of token abc123"
     );
 
     // Verify we can query by source (webhook architecture)
@@ -732,7 +1122,7 @@ async fn test_await_event_resume_roundtrip() {
         .await
         .expect("Should query by source");
     assert_eq!(source_runs.len(), 1, "Should find paused run by source");
-    assert_eq!(source_runs[0].0, "abc123");
+    assert_eq!(source_runs[0].0, key);
 
     // Simulate resume by calling engine.resume() directly
     let mut resume_event = HashMap::new();
@@ -765,3 +1155,1121 @@ async fn test_await_event_resume_roundtrip() {
         "Source query should be empty after resume"
     );
 }
+
+const SIGNED_RESUME_FLOW: &str = r#"
+name: signed_resume
+on: cli.manual
+steps:
+  - id: callback
+    use: core.resume_token
+    with:
+      expires_in: 1h
+  - id: wait
+    await_event:
+      source: partner
+      match:
+        token: "{{ outputs.callback.token }}"
+  - id: done
+    use: core.echo
+    with:
+      text: "resumed with {{ event.status }}"
+"#;
+
+/// Start the signed resume flow and return its run ID and `core.resume_token` outputs
+async fn pause_signed_resume_flow(engine: &Engine) -> (Uuid, serde_json::Value) {
+    let flow = crate::dsl::parse_string(SIGNED_RESUME_FLOW, None).unwrap();
+    let err = engine.execute(&flow, HashMap::new()).await.unwrap_err();
+    assert!(err.to_string().contains("waiting for event"), "{}", err);
+
+    let run = engine.storage().list_runs(10, 0).await.unwrap()[0].clone();
+    let callback = engine
+        .storage()
+        .get_steps(run.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|step| step.step_name.as_str() == "callback")
+        .unwrap();
+    (run.id, serde_json::to_value(callback.outputs).unwrap())
+}
+
+#[tokio::test]
+async fn test_resume_token_is_stored_hashed_and_signed_form_resumes() {
+    let engine = Engine::for_testing().await;
+    let (run_id, callback) = pause_signed_resume_flow(&engine).await;
+    let token = callback["token"].as_str().unwrap();
+    let signed = callback["signed"].as_str().unwrap();
+    assert_eq!(token.len(), 64, "256-bit hex token: {}", token);
+
+    // The paused run is keyed by the token's hash
+    let paused = engine.storage().load_paused_runs().await.unwrap();
+    let key = resume::storage_key(token);
+    assert_eq!(paused.keys().collect::<Vec<_>>(), [&key]);
+    assert_eq!(paused[&key]["token"], key);
+
+    // A tampered signature is rejected without consuming the paused run
+    let flipped = if signed.ends_with('0') { "1" } else { "0" };
+    let tampered = format!("{}{}", &signed[..signed.len() - 1], flipped);
+    let err = engine.resume(&tampered, HashMap::new()).await.unwrap_err();
+    assert!(matches!(err, BeemFlowError::Validation(_)), "{}", err);
+    assert_eq!(engine.storage().load_paused_runs().await.unwrap().len(), 1);
+
+    let event = HashMap::from([("status".to_string(), serde_json::json!("ok"))]);
+    engine.resume(signed, event).await.unwrap();
+    let run = engine.storage().get_run(run_id).await.unwrap().unwrap();
+    assert_eq!(run.status, crate::model::RunStatus::Succeeded);
+    assert!(
+        engine
+            .storage()
+            .load_paused_runs()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_signed_resume_token_is_bound_to_run_and_expiry() {
+    let mut engine = Engine::for_testing().await;
+    engine.config = Arc::new(crate::config::Config {
+        require_signed_resume_tokens: Some(true),
+        ..Default::default()
+    });
+    let (run_id, callback) = pause_signed_resume_flow(&engine).await;
+    let token = callback["token"].as_str().unwrap();
+    let signed = callback["signed"].as_str().unwrap();
+    let key = resume::signing_key(engine.secrets_provider.as_ref()).await;
+
+    let expired = resume::sign(
+        &key,
+        token,
+        run_id,
+        chrono::Utc::now() - chrono::Duration::minutes(1),
+    );
+    let err = engine.resume(&expired, HashMap::new()).await.unwrap_err();
+    assert!(err.to_string().contains("expired"), "{}", err);
+
+    let other_run = resume::sign(
+        &key,
+        token,
+        Uuid::new_v4(),
+        chrono::Utc::now() + chrono::Duration::hours(1),
+    );
+    let err = engine.resume(&other_run, HashMap::new()).await.unwrap_err();
+    assert!(
+        err.to_string().contains("not issued for this run"),
+        "{}",
+        err
+    );
+
+    // Unsigned tokens are refused with requireSignedResumeTokens
+    let err = engine.resume(token, HashMap::new()).await.unwrap_err();
+    assert!(err.to_string().contains("must be signed"), "{}", err);
+
+    // Still paused, and still resumable with its own token
+    let paused = engine.storage().find_paused_runs_by_source("partner").await;
+    assert_eq!(paused.unwrap().len(), 1);
+    engine.resume(signed, HashMap::new()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_rehash_paused_runs_moves_plaintext_tokens_to_hashed_keys() {
+    let engine = Engine::for_testing().await;
+    let (_, callback) = pause_signed_resume_flow(&engine).await;
+    let token = callback["token"].as_str().unwrap();
+    let signed = callback["signed"].as_str().unwrap();
+
+    // Move the paused run to its plaintext token, as stored by earlier versions
+    let storage = engine.storage();
+    let data = storage
+        .fetch_and_delete_paused_run(&resume::storage_key(token))
+        .await
+        .unwrap()
+        .unwrap();
+    storage
+        .save_paused_run(token, "partner", data)
+        .await
+        .unwrap();
+
+    assert_eq!(engine.rehash_paused_runs().await.unwrap(), 1);
+    let paused = storage.load_paused_runs().await.unwrap();
+    assert_eq!(
+        paused.keys().collect::<Vec<_>>(),
+        [&resume::storage_key(token)]
+    );
+    assert_eq!(engine.rehash_paused_runs().await.unwrap(), 0);
+
+    engine.resume(signed, HashMap::new()).await.unwrap();
+    assert!(storage.load_paused_runs().await.unwrap().is_empty());
+}
+
+fn strict_params_flow(strict_params: Option<bool>) -> Flow {
+    Flow {
+        name: FlowName::new("strict_params_test").unwrap(),
+        on: Some(Trigger::Single("cli.manual".to_string())),
+        steps: vec![Step {
+            id: "create".to_string().into(),
+            use_: Some("test.items".to_string()),
+            with: Some({
+                let mut m = HashMap::new();
+                m.insert("count".to_string(), serde_json::json!("{{ event.count }}"));
+                m
+            }),
+            ..Default::default()
+        }],
+        strict_params,
+        ..Default::default()
+    }
+}
+
+async fn engine_with_items_tool() -> Engine {
+    let engine = Engine::for_testing().await;
+    let manifest = crate::adapter::ToolManifest {
+        name: "test.items".to_string(),
+        description: "Create items".to_string(),
+        kind: "task".to_string(),
+        version: None,
+        parameters: serde_json::json!({
+            "type": "object",
+            "properties": {"count": {"type": "integer"}}
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+        .into_iter()
+        .collect(),
+        // Unroutable endpoint: validation must fail before any request is made
+        endpoint: Some("http://127.0.0.1:9/items".to_string()),
+        method: Some("POST".to_string()),
+        headers: None,
+        body_format: crate::adapter::BodyFormat::Json,
+        rate_limit: None,
+        stream: None,
+    };
+    engine
+        .adapters
+        .register(Arc::new(crate::adapter::HttpAdapter::new(
+            "test.items".to_string(),
+            Some(manifest),
+        )));
+    engine
+}
+
+#[tokio::test]
+async fn test_strict_params_templated_value_checked_at_runtime() {
+    let engine = engine_with_items_tool().await;
+    let flow = strict_params_flow(None);
+
+    // Templated values can't be checked statically
+    engine
+        .check_tool_params(&flow)
+        .await
+        .expect("Templated value should pass static check");
+
+    let mut event = HashMap::new();
+    event.insert("count".to_string(), serde_json::json!("many"));
+    let err = engine.execute(&flow, event).await.unwrap_err();
+    assert!(
+        matches!(err, BeemFlowError::Validation(ref msg)
+            if msg.contains("step 'create'") && msg.contains("parameter 'count'")),
+        "Expected parameter validation error, got: {}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_strict_params_static_unknown_key_and_opt_out() {
+    let engine = engine_with_items_tool().await;
+    let mut flow = strict_params_flow(None);
+    flow.steps[0]
+        .with
+        .as_mut()
+        .unwrap()
+        .insert("cuont".to_string(), serde_json::json!(3));
+
+    let err = engine.check_tool_params(&flow).await.unwrap_err();
+    assert!(
+        err.to_string().contains("unknown parameter 'cuont'"),
+        "{}",
+        err
+    );
+
+    // Flow-level opt-out keeps legacy flows working
+    flow.strict_params = Some(false);
+    assert!(engine.check_tool_params(&flow).await.is_ok());
+
+    // Step-level setting overrides the flow
+    flow.steps[0].strict_params = Some(true);
+    assert!(engine.check_tool_params(&flow).await.is_err());
+}
+
+fn echo_step(id: &str, text: &str) -> Step {
+    Step {
+        id: id.to_string().into(),
+        use_: Some("core.echo".to_string()),
+        with: Some(HashMap::from([(
+            "text".to_string(),
+            serde_json::json!(text),
+        )])),
+        ..Default::default()
+    }
+}
+
+/// Recorded steps of the (single) run of `flow_name`, keyed by step name
+async fn recorded_steps(
+    engine: &Engine,
+    flow_name: &str,
+) -> HashMap<String, crate::model::StepRun> {
+    let runs = engine.storage().list_runs(1000, 0).await.unwrap();
+    let run = runs
+        .iter()
+        .find(|r| r.flow_name.as_str() == flow_name)
+        .expect("run should be recorded");
+    engine
+        .storage()
+        .get_steps(run.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|step| (step.step_name.to_string(), step))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_on_success_hook_runs_only_on_success() {
+    let engine = Engine::for_testing().await;
+    let mut flow = echo_flow("hooks_success");
+    flow.on_success = Some(vec![echo_step(
+        "notify_ok",
+        "{{ run.status }}: {{ outputs.s1.text }}",
+    )]);
+    flow.on_failure = Some(vec![echo_step("notify_failed", "failed")]);
+
+    let event = HashMap::from([("n".to_string(), serde_json::json!(7))]);
+    engine.execute(&flow, event).await.unwrap();
+
+    let steps = recorded_steps(&engine, "hooks_success").await;
+    let hook = &steps["notify_ok"];
+    assert_eq!(hook.status, crate::model::StepStatus::Succeeded);
+    assert_eq!(
+        hook.outputs.as_ref().unwrap()["text"],
+        serde_json::json!("SUCCEEDED: 7")
+    );
+    assert!(!steps.contains_key("notify_failed"));
+}
+
+#[tokio::test]
+async fn test_on_failure_hook_runs_only_on_failure_after_catch() {
+    let engine = Engine::for_testing().await;
+    let mut flow = Flow::test("hooks_failure");
+    flow.steps = vec![
+        echo_step("first", "done"),
+        Step {
+            id: "boom".to_string().into(),
+            use_: Some("nonexistent.adapter".to_string()),
+            depends_on: Some(vec!["first".to_string()]),
+            ..Default::default()
+        },
+    ];
+    flow.catch = Some(vec![echo_step("cleanup", "cleaned")]);
+    flow.on_success = Some(vec![echo_step("notify_ok", "ok")]);
+    flow.on_failure = Some(vec![echo_step(
+        "notify_failed",
+        "{{ outputs.first.text }}/{{ outputs.cleanup.text }}/{{ error.message != '' }}",
+    )]);
+
+    assert!(engine.execute(&flow, HashMap::new()).await.is_err());
+
+    let steps = recorded_steps(&engine, "hooks_failure").await;
+    let hook = &steps["notify_failed"];
+    assert_eq!(hook.status, crate::model::StepStatus::Succeeded);
+    assert_eq!(
+        hook.outputs.as_ref().unwrap()["text"],
+        serde_json::json!("done/cleaned/true")
+    );
+    assert!(hook.started_at >= steps["cleanup"].started_at);
+    assert!(!steps.contains_key("notify_ok"));
+}
+
+fn order_flow() -> Flow {
+    let mut flow = Flow::test("typed_event");
+    flow.input_schema = Some(serde_json::json!({
+        "type": "object",
+        "required": ["order_id", "amount"],
+        "properties": {
+            "order_id": {"type": "string"},
+            "amount": {"type": "number"}
+        }
+    }));
+    flow.steps = vec![echo_step("s1", "{{ event.order_id }}")];
+    flow
+}
+
+#[tokio::test]
+async fn test_input_schema_accepts_valid_event() {
+    let engine = Engine::for_testing().await;
+    let event = HashMap::from([
+        ("order_id".to_string(), serde_json::json!("A-1")),
+        ("amount".to_string(), serde_json::json!(12.5)),
+    ]);
+
+    let result = engine.execute(&order_flow(), event).await.unwrap();
+    assert_eq!(result.outputs["s1"]["text"], serde_json::json!("A-1"));
+}
+
+#[tokio::test]
+async fn test_input_schema_rejects_event_before_steps_run() {
+    let engine = Engine::for_testing().await;
+    let event = HashMap::from([("amount".to_string(), serde_json::json!("lots"))]);
+
+    let err = engine.execute(&order_flow(), event).await.unwrap_err();
+    assert!(matches!(err, BeemFlowError::Validation(_)), "{:?}", err);
+    let message = err.to_string();
+    assert!(message.contains("order_id"), "{}", message);
+    assert!(message.contains("/amount"), "{}", message);
+
+    // No run was started
+    let runs = engine.storage().list_runs(1000, 0).await.unwrap();
+    assert!(runs.iter().all(|r| r.flow_name.as_str() != "typed_event"));
+}
+
+#[tokio::test]
+async fn test_published_events_reach_the_bus_after_commit() {
+    let engine = Engine::for_testing().await;
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    for topic in [
+        "orders.created",
+        crate::constants::EVENT_TOPIC_RUN_SUCCEEDED,
+    ] {
+        let tx = tx.clone();
+        engine
+            .event_bus()
+            .subscribe(
+                topic,
+                Arc::new(move |envelope| {
+                    let _ = tx.send(envelope);
+                }),
+            )
+            .await
+            .unwrap();
+    }
+
+    let mut flow = Flow::test("publisher");
+    flow.steps = vec![Step {
+        id: "announce".to_string().into(),
+        use_: Some(crate::constants::CORE_PUBLISH.to_string()),
+        with: Some(HashMap::from([
+            ("topic".to_string(), serde_json::json!("orders.created")),
+            (
+                "payload".to_string(),
+                serde_json::json!({"order_id": "A-1"}),
+            ),
+        ])),
+        ..Default::default()
+    }];
+    engine.execute(&flow, HashMap::new()).await.unwrap();
+
+    let mut received = Vec::new();
+    for _ in 0..2 {
+        let envelope = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("event should be dispatched")
+            .unwrap();
+        received.push(envelope);
+    }
+
+    let published = received
+        .iter()
+        .find(|e| e.topic == "orders.created")
+        .unwrap();
+    assert_eq!(published.payload, serde_json::json!({"order_id": "A-1"}));
+    assert_eq!(published.sequence, Some(1));
+    let finished = received
+        .iter()
+        .find(|e| e.topic == crate::constants::EVENT_TOPIC_RUN_SUCCEEDED)
+        .unwrap();
+    assert_eq!(finished.payload["flow"], "publisher");
+
+    // Both events come from the same run and share its correlation ID
+    for event in [published, finished] {
+        assert!(
+            matches!(&event.source, crate::event::EventSource::Run { flow, .. } if flow.as_str() == "publisher")
+        );
+        assert!(event.correlation_id.is_some());
+    }
+    assert_eq!(published.correlation_id, finished.correlation_id);
+
+    // Nothing is left in the outbox once the run has finished
+    assert!(
+        engine
+            .storage()
+            .list_unsent_events(10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+/// `a` -> `b` -> `c`, each echoing the previous step's text with a suffix
+fn chain_flow(name: &str) -> Flow {
+    let mut flow = Flow::test(name);
+    flow.steps = vec![
+        echo_step("a", "{{ event.seed }}"),
+        echo_step("b", "{{ steps.a.text }}-b"),
+        echo_step("c", "{{ steps.b.text }}-c"),
+    ];
+    flow
+}
+
+/// Recorded runs of `step` in `run_id`, oldest first
+async fn step_history(
+    engine: &Engine,
+    run_id: uuid::Uuid,
+    step: &str,
+) -> Vec<crate::model::StepRun> {
+    let mut history: Vec<_> = engine
+        .storage()
+        .get_steps(run_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|s| s.step_name.as_str() == step)
+        .collect();
+    history.sort_by_key(|s| s.started_at);
+    history
+}
+
+#[tokio::test]
+async fn test_rerun_failed_step_reuses_persisted_outputs() {
+    let engine = Engine::for_testing().await;
+    let event = HashMap::from([("seed".to_string(), serde_json::json!("x"))]);
+
+    // `b` fails: core.publish without a topic
+    let mut broken = chain_flow("rerun_failed");
+    broken.steps[1] = Step {
+        id: "b".to_string().into(),
+        use_: Some(crate::constants::CORE_PUBLISH.to_string()),
+        ..Default::default()
+    };
+    assert!(engine.execute(&broken, event).await.is_err());
+    let run_id = engine.storage().list_runs(1000, 0).await.unwrap()[0].id;
+
+    let result = engine
+        .rerun(&chain_flow("rerun_failed"), run_id, "b", false)
+        .await
+        .unwrap();
+    assert_eq!(result.run_id, run_id);
+    assert_eq!(result.steps, vec!["b"]);
+    assert_eq!(result.outputs["b"]["text"], "x-b");
+
+    // `a` was not executed again and `c` was not selected
+    assert_eq!(step_history(&engine, run_id, "a").await.len(), 1);
+    assert!(step_history(&engine, run_id, "c").await.is_empty());
+
+    let run = engine.storage().get_run(run_id).await.unwrap().unwrap();
+    assert_eq!(run.status, crate::model::RunStatus::Succeeded);
+    assert_eq!(engine.storage().list_runs(1000, 0).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_rerun_with_dependents_updates_downstream_steps() {
+    let engine = Engine::for_testing().await;
+    let event = HashMap::from([("seed".to_string(), serde_json::json!("x"))]);
+    let run_id = engine
+        .execute(&chain_flow("rerun_downstream"), event)
+        .await
+        .unwrap()
+        .run_id;
+
+    let mut changed = chain_flow("rerun_downstream");
+    changed.steps[1] = echo_step("b", "{{ steps.a.text }}-B");
+
+    let result = engine.rerun(&changed, run_id, "b", true).await.unwrap();
+    assert_eq!(result.steps, vec!["b", "c"]);
+    assert_eq!(result.outputs["c"]["text"], "x-B-c");
+
+    let history = step_history(&engine, run_id, "c").await;
+    assert_eq!(history.len(), 2);
+    assert_eq!(
+        history[1].outputs.as_ref().unwrap()["text"],
+        serde_json::json!("x-B-c")
+    );
+    assert_eq!(step_history(&engine, run_id, "a").await.len(), 1);
+}
+
+#[tokio::test]
+async fn test_rerun_rejects_active_runs_and_unknown_steps() {
+    let engine = Engine::for_testing().await;
+    let flow = chain_flow("rerun_guard");
+    let run = crate::model::Run {
+        id: uuid::Uuid::new_v4(),
+        flow_name: flow.name.clone(),
+        event: HashMap::new(),
+        vars: HashMap::new(),
+        status: crate::model::RunStatus::Running,
+        started_at: chrono::Utc::now(),
+        ended_at: None,
+        steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
+    };
+    engine.storage().save_run(&run).await.unwrap();
+
+    let err = engine.rerun(&flow, run.id, "b", false).await.unwrap_err();
+    assert!(matches!(err, BeemFlowError::Validation(_)), "{:?}", err);
+    assert!(step_history(&engine, run.id, "b").await.is_empty());
+
+    let finished = crate::model::Run {
+        status: crate::model::RunStatus::Failed,
+        ..run
+    };
+    engine.storage().save_run(&finished).await.unwrap();
+    let err = engine
+        .rerun(&flow, finished.id, "missing", false)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("missing"), "{}", err);
+}
+
+fn tool_step(id: &str, use_: &str) -> Step {
+    Step {
+        id: id.to_string().into(),
+        use_: Some(use_.to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_find_unresolved_tools_with_suggestions() {
+    let engine = Engine::for_testing().await;
+    let mut flow = Flow::test("tool_check");
+    flow.mcp_servers = Some(HashMap::from([(
+        "local".to_string(),
+        crate::model::McpServerConfig {
+            command: "local-mcp".to_string(),
+            args: None,
+            env: None,
+            port: None,
+            transport: None,
+            endpoint: None,
+        },
+    )]));
+    flow.steps = vec![
+        tool_step("fetch", "http.fetch"),
+        tool_step("echo", "core.echo"),
+        tool_step("search", "mcp://local/search"),
+        tool_step("chat", "openai.chat_complection"),
+        tool_step("shout", "core.ecko"),
+        tool_step("lookup", "mcp://locl/search"),
+        Step {
+            id: "each".to_string().into(),
+            foreach: Some("{{ event.items }}".to_string()),
+            as_: Some("item".to_string()),
+            do_: Some(vec![tool_step("page", "notion.create_page")]),
+            ..Default::default()
+        },
+    ];
+    flow.catch = Some(vec![tool_step("alert", "notion.create_page")]);
+
+    let unresolved = engine.find_unresolved_tools(&flow).await;
+    let by_tool: HashMap<&str, &UnresolvedTool> =
+        unresolved.iter().map(|u| (u.tool.as_str(), u)).collect();
+    assert_eq!(by_tool.len(), 4, "{:?}", unresolved);
+
+    assert_eq!(
+        by_tool["openai.chat_complection"].suggestions[0],
+        "openai.chat_completion"
+    );
+    assert_eq!(by_tool["core.ecko"].suggestions[0], "core.echo");
+    assert_eq!(
+        by_tool["mcp://locl/search"].suggestions,
+        vec!["mcp://local/search"]
+    );
+    assert_eq!(by_tool["notion.create_page"].steps, vec!["alert", "page"]);
+
+    let err = engine.check_tools_resolve(&flow).await.unwrap_err();
+    assert!(matches!(err, BeemFlowError::Validation(_)));
+    assert!(
+        err.to_string()
+            .contains("core.ecko (step 'shout'); did you mean core.echo?"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_run_usage_counts_tools_across_foreach_and_parallel() {
+    let engine = Engine::for_testing().await;
+    let echo = |id: &str| Step {
+        with: Some(HashMap::from([("text".to_string(), serde_json::json!(id))])),
+        ..tool_step(id, "core.echo")
+    };
+    let mut flow = Flow::test("usage_flow");
+    flow.steps = vec![
+        echo("first"),
+        Step {
+            id: "each".to_string().into(),
+            foreach: Some("{{ event.items }}".to_string()),
+            as_: Some("item".to_string()),
+            parallel: Some(true),
+            do_: Some(vec![echo("page")]),
+            ..Default::default()
+        },
+        Step {
+            id: "both".to_string().into(),
+            parallel: Some(true),
+            steps: Some(vec![echo("left"), echo("right")]),
+            ..Default::default()
+        },
+    ];
+
+    let event = HashMap::from([("items".to_string(), serde_json::json!([1, 2, 3]))]);
+    let result = engine.execute(&flow, event).await.unwrap();
+
+    let run = engine
+        .storage()
+        .get_run(result.run_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let usage = run.usage.expect("finished runs record their usage");
+    assert_eq!(usage.tool_invocations["core.echo"], 6);
+    assert_eq!(usage.http_requests, 0);
+}
+
+#[tokio::test]
+async fn test_execute_until_stops_after_target_step() {
+    let engine = Engine::for_testing().await;
+    let echo = |id: &str, text: &str| Step {
+        with: Some(HashMap::from([(
+            "text".to_string(),
+            serde_json::json!(text),
+        )])),
+        ..tool_step(id, "core.echo")
+    };
+    let mut flow = Flow::test("partial_flow");
+    // YAML order differs from dependency order: fetch -> transform -> publish
+    flow.steps = vec![
+        echo("publish", "{{ steps.transform.text }}!"),
+        echo("transform", "{{ steps.fetch.text }} data"),
+        echo("fetch", "raw"),
+    ];
+    flow.on_success = Some(vec![echo("notify", "done")]);
+
+    let result = engine
+        .execute_until(&flow, HashMap::new(), "transform")
+        .await
+        .unwrap();
+    assert_eq!(result.outputs["transform"]["text"], "raw data");
+    assert!(!result.outputs.contains_key("publish"));
+
+    let run = engine
+        .storage()
+        .get_run(result.run_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.status, crate::model::RunStatus::Succeeded);
+    let mut ran: Vec<String> = engine
+        .storage()
+        .get_steps(result.run_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|step| step.step_name.to_string())
+        .collect();
+    ran.sort();
+    assert_eq!(ran, ["fetch", "transform"]);
+
+    let err = engine
+        .execute_until(&flow, HashMap::new(), "missing")
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            BeemFlowError::Storage(crate::error::StorageError::NotFound { .. })
+        ),
+        "{}",
+        err
+    );
+}
+
+/// Engine whose config scans step outputs for secrets with `action`
+async fn engine_with_output_scan(action: &str) -> Engine {
+    let mut engine = Engine::for_testing().await;
+    engine.config = Arc::new(
+        serde_json::from_value(serde_json::json!({
+            "storage": {"driver": "sqlite", "dsn": ":memory:"},
+            "secrets": {"outputScan": {"action": action, "names": ["EXTRA_SECRET"]}}
+        }))
+        .unwrap(),
+    );
+    engine
+}
+
+fn secret_echo_flow() -> Flow {
+    let echo = |id: &str, text: &str| Step {
+        with: Some(HashMap::from([(
+            "text".to_string(),
+            serde_json::json!(text),
+        )])),
+        ..tool_step(id, "core.echo")
+    };
+    let mut flow = Flow::test("secret_echo");
+    flow.steps = vec![
+        echo("leak", "token={{ secrets.API_KEY }}"),
+        Step {
+            id: "fan".to_string().into(),
+            parallel: Some(true),
+            steps: Some(vec![echo("nested", "{{ event.pasted }}")]),
+            ..Default::default()
+        },
+    ];
+    flow
+}
+
+fn secret_echo_event() -> HashMap<String, serde_json::Value> {
+    HashMap::from([
+        (
+            "secrets".to_string(),
+            serde_json::json!({"API_KEY": "sk-live-123", "EXTRA_SECRET": "hunter2"}),
+        ),
+        ("pasted".to_string(), serde_json::json!("pw is hunter2")),
+    ])
+}
+
+#[tokio::test]
+async fn test_output_scan_redacts_secrets_from_step_outputs() {
+    let engine = engine_with_output_scan("redact").await;
+    let result = engine
+        .execute(&secret_echo_flow(), secret_echo_event())
+        .await
+        .unwrap();
+
+    let redacted = crate::secrets::REDACTED;
+    assert_eq!(
+        result.outputs["leak"]["text"],
+        format!("token={}", redacted)
+    );
+    // Configured names are scanned for even when the flow doesn't reference them
+    assert_eq!(
+        result.outputs["nested"]["text"],
+        format!("pw is {}", redacted)
+    );
+
+    let steps = engine.storage().get_steps(result.run_id).await.unwrap();
+    let leak = steps
+        .iter()
+        .find(|s| s.step_name.as_str() == "leak")
+        .unwrap();
+    assert_eq!(
+        leak.outputs.as_ref().unwrap()["text"],
+        format!("token={}", redacted)
+    );
+}
+
+#[tokio::test]
+async fn test_output_scan_fail_fails_step_echoing_a_secret() {
+    let engine = engine_with_output_scan("fail").await;
+    let err = engine
+        .execute(&secret_echo_flow(), secret_echo_event())
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("output contains a secret value"),
+        "{}",
+        err
+    );
+    assert!(!err.to_string().contains("sk-live-123"), "{}", err);
+
+    let run = engine.storage().list_runs(10, 0).await.unwrap()[0].clone();
+    assert_eq!(run.status, crate::model::RunStatus::Failed);
+    let steps = engine.storage().get_steps(run.id).await.unwrap();
+    assert!(
+        steps.iter().all(|s| s.outputs.is_none()),
+        "No output is stored: {:?}",
+        steps
+    );
+}
+
+#[tokio::test]
+async fn test_output_scan_is_off_by_default() {
+    let engine = Engine::for_testing().await;
+    let result = engine
+        .execute(&secret_echo_flow(), secret_echo_event())
+        .await
+        .unwrap();
+    assert_eq!(result.outputs["leak"]["text"], "token=sk-live-123");
+}
+
+#[tokio::test]
+async fn test_rate_limited_tool_paces_parallel_foreach() {
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // Arrival time of every request at the API
+    let arrivals = Arc::new(Mutex::new(Vec::new()));
+    let server = MockServer::start().await;
+    let recorded = arrivals.clone();
+    Mock::given(wiremock::matchers::method("POST"))
+        .respond_with(move |_: &wiremock::Request| {
+            recorded.lock().unwrap().push(Instant::now());
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"ok": true}))
+        })
+        .expect(4)
+        .mount(&server)
+        .await;
+
+    let engine = Engine::for_testing().await;
+    engine
+        .adapters
+        .register(Arc::new(crate::adapter::HttpAdapter::new(
+            "test.limited".to_string(),
+            Some(crate::adapter::ToolManifest {
+                name: "test.limited".to_string(),
+                description: "Rate limited API".to_string(),
+                kind: "task".to_string(),
+                version: None,
+                parameters: HashMap::new(),
+                endpoint: Some(server.uri()),
+                method: Some("POST".to_string()),
+                headers: None,
+                body_format: crate::adapter::BodyFormat::Json,
+                rate_limit: Some(crate::adapter::RateLimit {
+                    per_second: 10.0,
+                    burst: 1,
+                }),
+                stream: None,
+            }),
+        )));
+
+    let flow = crate::dsl::parse_string(
+        r#"
+name: rate_limited
+on: cli.manual
+steps:
+  - id: each
+    foreach: "{{ event.items }}"
+    as: item
+    parallel: true
+    do:
+      - id: call_{{ item }}
+        use: test.limited
+        with:
+          item: "{{ item }}"
+"#,
+        None,
+    )
+    .unwrap();
+    let mut event = HashMap::new();
+    event.insert("items".to_string(), serde_json::json!([1, 2, 3, 4]));
+    engine.execute(&flow, event).await.unwrap();
+
+    // All four iterations start at once, but reach the API 100ms apart
+    let mut arrivals = arrivals.lock().unwrap().clone();
+    arrivals.sort();
+    assert_eq!(arrivals.len(), 4);
+    let min_spacing = arrivals
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .min()
+        .unwrap();
+    assert!(
+        min_spacing >= Duration::from_millis(90),
+        "calls were {:?} apart",
+        min_spacing
+    );
+}
+
+#[tokio::test]
+async fn test_draining_rejects_new_runs_but_finishes_in_flight_ones() {
+    let engine = Arc::new(Engine::for_testing().await);
+    let mut slow = Flow::test("drain_slow");
+    slow.steps = vec![
+        Step {
+            id: "pause".to_string().into(),
+            wait: Some(crate::model::WaitSpec {
+                seconds: Some(1),
+                until: None,
+            }),
+            ..Default::default()
+        },
+        Step {
+            id: "done".to_string().into(),
+            use_: Some("core.echo".to_string()),
+            with: Some(HashMap::from([(
+                "text".to_string(),
+                serde_json::json!("finished"),
+            )])),
+            ..Default::default()
+        },
+    ];
+
+    let in_flight = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.execute(&slow, HashMap::new()).await }
+    });
+    // Let the run get past its start before draining
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    assert!(!engine.set_draining(true));
+    assert!(engine.is_draining());
+    let err = engine
+        .execute(&echo_flow("drain_new"), HashMap::new())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, BeemFlowError::Draining), "{}", err);
+
+    let result = in_flight.await.unwrap().unwrap();
+    assert_eq!(result.outputs["done"]["text"], "finished");
+
+    // Undraining accepts runs again
+    assert!(engine.set_draining(false));
+    engine
+        .execute(
+            &echo_flow("drain_new"),
+            HashMap::from([("n".to_string(), serde_json::json!(1))]),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_run_queue_admits_runs() {
+    let mut engine = Engine::for_testing().await;
+    assert!(engine.run_queue().is_none());
+    engine.run_queue = Some(Arc::new(super::queue::RunQueue::new(1, 0)));
+    let event = HashMap::from([("n".to_string(), serde_json::json!(1))]);
+
+    // Runs go through while a slot is free, and give it back when done
+    engine
+        .execute(&echo_flow("queued"), event.clone())
+        .await
+        .unwrap();
+    let slot = engine.run_queue().unwrap().acquire(0).await.unwrap();
+
+    // With the only slot taken and no room to wait, the run is rejected
+    let options = RunOptions {
+        priority: 10,
+        run_id_strategy: Some(RunIdStrategy::Random),
+        ..Default::default()
+    };
+    let err = engine
+        .execute_with(&echo_flow("queued"), event.clone(), &options)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, BeemFlowError::QueueFull { capacity: 0 }),
+        "{}",
+        err
+    );
+
+    drop(slot);
+    engine
+        .execute_with(&echo_flow("queued"), event, &options)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_downstream_steps_read_aggregated_block_outputs() {
+    let engine = Engine::for_testing().await;
+
+    for parallel in [false, true] {
+        let flow = crate::dsl::parse_string(
+            &r#"
+name: aggregate_flow
+on: cli.manual
+steps:
+  - id: summary
+    use: core.echo
+    depends_on: [each, branches]
+    with:
+      text: "{% for it in steps.each.output %}{{ it.double.text }},{% endfor %} {{ steps.branches.output.left.text }}"
+  - id: each
+    foreach: "{{ event.items }}"
+    as: item
+    parallel: PARALLEL
+    do:
+      - id: double
+        use: core.echo
+        with:
+          text: "{{ item }}{{ item }}"
+  - id: branches
+    parallel: true
+    steps:
+      - id: left
+        use: core.echo
+        with:
+          text: L
+      - id: right
+        use: core.echo
+        with:
+          text: R
+"#
+            .replace("PARALLEL", &parallel.to_string()),
+            None,
+        )
+        .unwrap();
+
+        // Events differ per pass so the second run isn't deduplicated
+        let event = HashMap::from([
+            ("items".to_string(), serde_json::json!(["a", "b", "c"])),
+            ("parallel".to_string(), serde_json::json!(parallel)),
+        ]);
+        let result = engine.execute(&flow, event).await.unwrap();
+
+        assert_eq!(result.outputs["summary"]["text"], "aa,bb,cc, L");
+        // One entry per iteration, in list order, keyed by inner step ID
+        let iterations = result.outputs["each"]["output"].as_array().unwrap();
+        assert_eq!(iterations.len(), 3);
+        assert_eq!(iterations[2]["double"]["text"], "cc");
+        // Branch outputs stay reachable directly too
+        assert_eq!(result.outputs["branches"]["output"]["right"]["text"], "R");
+        assert_eq!(result.outputs["branches"]["right"]["text"], "R");
+    }
+}
+
+#[tokio::test]
+async fn test_parallel_block_keeps_top_level_branch_outputs_for_older_flows() {
+    let engine = Engine::for_testing().await;
+    let flow = crate::dsl::parse_string(
+        r#"
+name: legacy_parallel_flow
+on: cli.manual
+steps:
+  - id: branches
+    parallel: true
+    steps:
+      - id: left
+        use: core.echo
+        with:
+          text: L
+      - id: right
+        use: core.echo
+        with:
+          text: R
+  - id: summary
+    use: core.echo
+    depends_on: [branches]
+    with:
+      text: "{{ steps.branches.left.text }}{{ steps.branches.right.text }}"
+"#,
+        None,
+    )
+    .unwrap();
+
+    let result = engine.execute(&flow, HashMap::new()).await.unwrap();
+
+    // Flows written before `output` existed read branches off the block directly
+    assert_eq!(result.outputs["summary"]["text"], "LR");
+    // The top-level keys are exactly the branches in `output`, nothing more
+    let mut block = result.outputs["branches"].as_object().unwrap().clone();
+    let output = block.shift_remove("output").unwrap();
+    assert_eq!(serde_json::Value::Object(block), output);
+}
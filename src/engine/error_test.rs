@@ -3,9 +3,11 @@
 //! Tests various error scenarios and recovery mechanisms.
 
 use super::*;
-use crate::model::{Flow, RetrySpec, Step, StepId};
+use crate::adapter::mock::{MockAdapter, MockResponse, mock_registry};
+use crate::model::{Flow, FlowName, RetrySpec, RunStatus, Step, StepId};
 use serde_json::json;
 use std::collections::HashMap;
+use std::time::Duration;
 
 fn create_step(id: &str, use_tool: &str, text: &str) -> Step {
     let mut with = HashMap::new();
@@ -19,12 +21,17 @@ fn create_step(id: &str, use_tool: &str, text: &str) -> Step {
     }
 }
 
+/// Engine whose only tools besides `core.*` are `mocks`
+async fn engine_with_mocks<const N: usize>(mocks: [&Arc<MockAdapter>; N]) -> Engine {
+    Engine::for_testing_with(mock_registry(mocks.map(Arc::clone))).await
+}
+
 #[tokio::test]
 async fn test_missing_adapter() {
     let engine = Engine::for_testing().await;
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![create_step("step1", "nonexistent.adapter", "test")],
         ..Default::default()
     };
@@ -56,7 +63,7 @@ async fn test_invalid_step_configuration() {
     with.insert("wrong_field".to_string(), json!("value"));
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: "step1".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -68,8 +75,7 @@ async fn test_invalid_step_configuration() {
 
     let result = engine.execute(&flow, HashMap::new()).await;
     // Engine may tolerate missing/wrong fields
-    if result.is_ok() {
-        let outputs = result.unwrap();
+    if let Ok(outputs) = result {
         assert!(outputs.outputs.contains_key("step1"));
     }
 }
@@ -82,7 +88,7 @@ async fn test_template_rendering_error() {
     with.insert("text".to_string(), json!("{{ undefined_variable }}"));
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: "step1".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -92,9 +98,9 @@ async fn test_template_rendering_error() {
         ..Default::default()
     };
 
-    let result = engine.execute(&flow, HashMap::new()).await;
-    // Should handle template errors gracefully
-    assert!(result.is_ok());
+    // Strict mode fails the step, naming the missing variable
+    let err = engine.execute(&flow, HashMap::new()).await.unwrap_err();
+    assert!(err.to_string().contains("'undefined_variable'"), "{}", err);
 }
 
 #[tokio::test]
@@ -108,7 +114,7 @@ async fn test_circular_dependency() {
     with2.insert("text".to_string(), json!("{{ steps.step1.output }}"));
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![
             Step {
                 id: "step1".to_string().into(),
@@ -136,7 +142,7 @@ async fn test_error_in_catch_block() {
     let engine = Engine::for_testing().await;
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: "step1".to_string().into(),
             use_: Some("nonexistent.tool".to_string()),
@@ -163,7 +169,7 @@ async fn test_foreach_with_invalid_expression() {
     let engine = Engine::for_testing().await;
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: "step1".to_string().into(),
             foreach: Some("not_an_array".to_string()),
@@ -180,44 +186,145 @@ async fn test_foreach_with_invalid_expression() {
 
 #[tokio::test]
 async fn test_retry_exhaustion() {
-    let engine = Engine::for_testing().await;
+    let flaky = Arc::new(MockAdapter::new("test.flaky").otherwise(MockResponse::err("boom")));
+    let engine = engine_with_mocks([&flaky]).await;
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: "step1".to_string().into(),
-            use_: Some("nonexistent.tool".to_string()),
+            use_: Some("test.flaky".to_string()),
             with: Some(HashMap::new()),
             retry: Some(RetrySpec {
                 attempts: 2,
                 delay_sec: 0,
+                max_delay_sec: None,
             }),
             ..Default::default()
         }],
         ..Default::default()
     };
 
-    let result = engine.execute(&flow, HashMap::new()).await;
-    // Should fail after retries exhausted
-    assert!(result.is_err());
+    let err = engine.execute(&flow, HashMap::new()).await.unwrap_err();
+    // Should fail after retries exhausted, with the last attempt's error
+    assert!(err.to_string().contains("boom"), "{}", err);
+    assert_eq!(flaky.call_count(), 2);
 }
 
 #[tokio::test]
-async fn test_parallel_block_partial_failure() {
+async fn test_retry_succeeds_after_transient_failures() {
+    let flaky = Arc::new(
+        MockAdapter::new("test.flaky")
+            .then(MockResponse::err("503"))
+            .then(MockResponse::err("503"))
+            .then(MockResponse::ok(json!({"status": "ok"}))),
+    );
+    let engine = engine_with_mocks([&flaky]).await;
+
+    let flow = Flow {
+        name: FlowName::new("test-flow").unwrap(),
+        steps: vec![Step {
+            id: "step1".to_string().into(),
+            use_: Some("test.flaky".to_string()),
+            with: Some(HashMap::from([("id".to_string(), json!(7))])),
+            retry: Some(RetrySpec {
+                attempts: 3,
+                delay_sec: 0,
+                max_delay_sec: None,
+            }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let result = engine.execute(&flow, HashMap::new()).await.unwrap();
+    assert_eq!(result.outputs["step1"]["status"], "ok");
+    // Every attempt got the same inputs
+    let calls = flaky.calls();
+    assert_eq!(calls.len(), 3);
+    assert!(calls.iter().all(|inputs| inputs["id"] == 7));
+}
+
+#[tokio::test]
+async fn test_retry_waits_for_retry_after() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "2"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"status": "ok"})))
+        .mount(&server)
+        .await;
+
+    let flow = |max_delay_sec: Option<u64>| Flow {
+        name: FlowName::new("throttled").unwrap(),
+        steps: vec![Step {
+            id: "fetch".to_string().into(),
+            use_: Some("http".to_string()),
+            with: Some(HashMap::from([("url".to_string(), json!(server.uri()))])),
+            retry: Some(RetrySpec {
+                attempts: 2,
+                delay_sec: 0,
+                max_delay_sec,
+            }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
     let engine = Engine::for_testing().await;
 
+    // The zero-second backoff is replaced by the two seconds the server asked for
+    let started = std::time::Instant::now();
+    let result = engine.execute(&flow(None), HashMap::new()).await.unwrap();
+    let waited = started.elapsed();
+    assert_eq!(result.outputs["fetch"]["status"], "ok");
+    assert!(
+        waited >= Duration::from_millis(1900) && waited < Duration::from_secs(4),
+        "{:?}",
+        waited
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+    // max_delay_sec caps what the server asks for
+    server.reset().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "120"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"status": "ok"})))
+        .mount(&server)
+        .await;
+    let started = std::time::Instant::now();
+    // A different event, so the run isn't a duplicate of the first
+    let event = HashMap::from([("attempt".to_string(), json!(2))]);
+    engine.execute(&flow(Some(1)), event).await.unwrap();
+    let waited = started.elapsed();
+    assert!(
+        waited >= Duration::from_millis(900) && waited < Duration::from_secs(3),
+        "{:?}",
+        waited
+    );
+}
+
+#[tokio::test]
+async fn test_parallel_block_partial_failure() {
+    let broken = Arc::new(MockAdapter::new("test.broken").then(MockResponse::err("broken")));
+    let engine = engine_with_mocks([&broken]).await;
+
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: "parallel1".to_string().into(),
             steps: Some(vec![
                 create_step("p1", "core.echo", "success"),
-                Step {
-                    id: "p2".to_string().into(),
-                    use_: Some("nonexistent.tool".to_string()),
-                    with: Some(HashMap::new()),
-                    ..Default::default()
-                },
+                create_step("p2", "test.broken", "failure"),
             ]),
             parallel: Some(true),
             ..Default::default()
@@ -225,9 +332,211 @@ async fn test_parallel_block_partial_failure() {
         ..Default::default()
     };
 
-    let result = engine.execute(&flow, HashMap::new()).await;
-    // Should handle partial failures in parallel blocks
-    assert!(result.is_err());
+    let err = engine.execute(&flow, HashMap::new()).await.unwrap_err();
+    // A failed branch fails the whole parallel block
+    assert!(err.to_string().contains("broken"), "{}", err);
+    assert_eq!(broken.calls()[0]["text"], "failure");
+}
+
+#[tokio::test]
+async fn test_parallel_block_panicking_task_fails_run() {
+    let panicky = Arc::new(MockAdapter::new("test.panicky").then(MockResponse::panic("kaboom")));
+    let engine = engine_with_mocks([&panicky]).await;
+
+    let flow = Flow {
+        name: FlowName::new("test-flow").unwrap(),
+        steps: vec![Step {
+            id: "parallel1".to_string().into(),
+            steps: Some(vec![
+                create_step("p1", "core.echo", "success"),
+                create_step("p2", "test.panicky", "panic"),
+            ]),
+            parallel: Some(true),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let err = engine.execute(&flow, HashMap::new()).await.unwrap_err();
+    assert!(
+        err.to_string().contains("parallel task panicked"),
+        "{}",
+        err
+    );
+    let runs = engine.storage().list_runs(10, 0).await.unwrap();
+    assert_eq!(runs[0].status, RunStatus::Failed);
+}
+
+#[tokio::test]
+async fn test_continue_on_error_records_failure_and_keeps_running() {
+    let broken = Arc::new(MockAdapter::new("test.broken").then(MockResponse::err("smtp down")));
+    let engine = engine_with_mocks([&broken]).await;
+
+    let flow = Flow {
+        name: FlowName::new("best-effort").unwrap(),
+        steps: vec![
+            Step {
+                continue_on_error: Some(true),
+                ..create_step("notify", "test.broken", "hello")
+            },
+            Step {
+                depends_on: Some(vec!["notify".to_string()]),
+                ..create_step("report", "core.echo", "{{ steps.notify.error }}")
+            },
+        ],
+        ..Default::default()
+    };
+
+    let result = engine.execute(&flow, HashMap::new()).await.unwrap();
+    let report = result.outputs["report"]["text"].as_str().unwrap();
+    assert!(report.contains("smtp down"), "{}", report);
+
+    let run = engine
+        .storage()
+        .get_run(result.run_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.status, RunStatus::Succeeded);
+    let steps = engine.storage().get_steps(result.run_id).await.unwrap();
+    let notify = steps
+        .iter()
+        .find(|s| s.step_name.as_str() == "notify")
+        .unwrap();
+    assert_eq!(notify.status, crate::model::StepStatus::Failed);
+    assert!(notify.error.as_deref().unwrap().contains("smtp down"));
+    let report = steps
+        .iter()
+        .find(|s| s.step_name.as_str() == "report")
+        .unwrap();
+    assert_eq!(report.status, crate::model::StepStatus::Succeeded);
+
+    // Without the flag the same failure aborts the run
+    let flow = Flow {
+        name: FlowName::new("strict").unwrap(),
+        steps: vec![create_step("notify", "test.broken", "hello")],
+        ..Default::default()
+    };
+    assert!(engine.execute(&flow, HashMap::new()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_slow_tool_hits_timeout_total() {
+    let slow = Arc::new(MockAdapter::new("test.slow").then(MockResponse::delayed(
+        Duration::from_secs(30),
+        MockResponse::ok(json!({"late": true})),
+    )));
+    let engine = engine_with_mocks([&slow]).await;
+
+    let flow = Flow {
+        name: FlowName::new("test-flow").unwrap(),
+        steps: vec![Step {
+            timeout_total: Some("1s".to_string()),
+            ..create_step("slow", "test.slow", "wait")
+        }],
+        ..Default::default()
+    };
+
+    let started = std::time::Instant::now();
+    let err = engine.execute(&flow, HashMap::new()).await.unwrap_err();
+    assert!(err.to_string().contains("timeout_total"), "{}", err);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_failed_steps_record_their_error_kind() {
+    let slow = Arc::new(MockAdapter::new("test.slow").then(MockResponse::delayed(
+        Duration::from_secs(30),
+        MockResponse::ok(json!({"late": true})),
+    )));
+    let broken = Arc::new(MockAdapter::new("test.broken").then(MockResponse::err("smtp down")));
+    let engine = engine_with_mocks([&slow, &broken]).await;
+
+    let best_effort = |step: Step| Step {
+        continue_on_error: Some(true),
+        ..step
+    };
+    let flow = Flow {
+        name: FlowName::new("kinds").unwrap(),
+        steps: vec![
+            best_effort(Step {
+                timeout_total: Some("1s".to_string()),
+                ..create_step("slow", "test.slow", "wait")
+            }),
+            best_effort(create_step("broken", "test.broken", "hello")),
+            best_effort(create_step("render", "core.echo", "{{ event.missing }}")),
+            create_step(
+                "report",
+                "core.echo",
+                "{{ steps.slow.error_kind }}/{{ steps.broken.error_kind }}",
+            ),
+        ],
+        ..Default::default()
+    };
+
+    let result = engine.execute(&flow, HashMap::new()).await.unwrap();
+    assert_eq!(result.outputs["report"]["text"], "timeout/adapter");
+
+    let steps = engine.storage().get_steps(result.run_id).await.unwrap();
+    let kind_of = |name: &str| {
+        steps
+            .iter()
+            .find(|s| s.step_name.as_str() == name)
+            .unwrap()
+            .error_kind
+    };
+    assert_eq!(kind_of("slow"), Some(crate::StepErrorKind::Timeout));
+    assert_eq!(kind_of("broken"), Some(crate::StepErrorKind::Adapter));
+    assert_eq!(kind_of("render"), Some(crate::StepErrorKind::Template));
+    assert_eq!(kind_of("report"), None);
+}
+
+#[tokio::test]
+async fn test_step_failures_keep_their_cause() {
+    let broken = Arc::new(MockAdapter::new("test.broken").then(MockResponse::err("smtp down")));
+    let engine = engine_with_mocks([&broken]).await;
+    let flow = Flow {
+        name: FlowName::new("strict").unwrap(),
+        steps: vec![create_step("notify", "test.broken", "hello")],
+        on_failure: Some(vec![create_step(
+            "alert",
+            "core.echo",
+            "{{ error.kind }}: {{ error.message }}",
+        )]),
+        ..Default::default()
+    };
+    let err = engine.execute(&flow, HashMap::new()).await.unwrap_err();
+    assert_eq!(err.step_kind(), Some(crate::StepErrorKind::Adapter));
+
+    let runs = engine.storage().list_runs(10, 0).await.unwrap();
+    let steps = engine.storage().get_steps(runs[0].id).await.unwrap();
+    let alert = steps
+        .iter()
+        .find(|s| s.step_name.as_str() == "alert")
+        .unwrap();
+    let text = alert.outputs.as_ref().unwrap()["text"].as_str().unwrap();
+    assert!(text.starts_with("adapter: "), "{}", text);
+
+    // Wrapped as a step failure, the cause stays reachable
+    let wrapped = err.into_step_failure("notify");
+    let BeemFlowError::StepExecution {
+        ref step_id,
+        kind,
+        ref source,
+        ..
+    } = wrapped
+    else {
+        panic!("expected a step execution error: {}", wrapped);
+    };
+    assert_eq!(step_id, "notify");
+    assert_eq!(kind, crate::StepErrorKind::Adapter);
+    assert!(source.is_some());
+    assert!(
+        wrapped
+            .to_string()
+            .starts_with("Step execution failed: notify: ")
+    );
+    assert!(std::error::Error::source(&wrapped).is_some());
 }
 
 #[tokio::test]
@@ -238,7 +547,7 @@ async fn test_empty_step_id() {
     with.insert("text".to_string(), json!("test"));
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: StepId::unchecked("".to_string()), // Empty ID - use unchecked for testing error handling
             use_: Some("core.echo".to_string()),
@@ -258,7 +567,7 @@ async fn test_duplicate_step_ids() {
     let engine = Engine::for_testing().await;
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![
             create_step("duplicate", "core.echo", "first"),
             create_step("duplicate", "core.echo", "second"),
@@ -268,8 +577,7 @@ async fn test_duplicate_step_ids() {
 
     let result = engine.execute(&flow, HashMap::new()).await;
     // Engine should handle duplicate IDs (may overwrite or error)
-    if result.is_ok() {
-        let outputs = result.unwrap();
+    if let Ok(outputs) = result {
         // Should have output for the duplicate key
         assert!(outputs.outputs.contains_key("duplicate"));
     }
@@ -280,7 +588,7 @@ async fn test_condition_evaluation() {
     let engine = Engine::for_testing().await;
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: "step1".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -305,7 +613,7 @@ async fn test_step_without_use_field() {
     let engine = Engine::for_testing().await;
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: "step1".to_string().into(),
             use_: None, // No use field
@@ -329,7 +637,7 @@ async fn test_deeply_nested_steps() {
     let engine = Engine::for_testing().await;
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: "outer".to_string().into(),
             steps: Some(vec![Step {
@@ -360,7 +668,7 @@ async fn test_large_output_handling() {
     with.insert("text".to_string(), json!(large_text));
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: "step1".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -386,7 +694,7 @@ async fn test_null_values_in_context() {
     with.insert("text".to_string(), json!("{{ event.null_value }}"));
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![Step {
             id: "step1".to_string().into(),
             use_: Some("core.echo".to_string()),
@@ -403,16 +711,12 @@ async fn test_null_values_in_context() {
 
 #[tokio::test]
 async fn test_error_recovery_with_catch() {
-    let engine = Engine::for_testing().await;
+    let failing = Arc::new(MockAdapter::new("test.failing").then(MockResponse::err("down")));
+    let engine = engine_with_mocks([&failing]).await;
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
-        steps: vec![Step {
-            id: "step1".to_string().into(),
-            use_: Some("nonexistent.tool".to_string()),
-            with: Some(HashMap::new()),
-            ..Default::default()
-        }],
+        name: FlowName::new("test-flow").unwrap(),
+        steps: vec![create_step("step1", "test.failing", "input")],
         // Flow-level catch for error recovery
         catch: Some(vec![create_step("recovery", "core.echo", "recovered")]),
         ..Default::default()
@@ -420,38 +724,31 @@ async fn test_error_recovery_with_catch() {
 
     let result = engine.execute(&flow, HashMap::new()).await;
     // Should recover using catch block
-    if result.is_ok() {
-        let outputs = result.unwrap();
+    if let Ok(outputs) = result {
         assert!(outputs.outputs.contains_key("recovery"));
     }
+    assert_eq!(failing.call_count(), 1);
 }
 
 #[tokio::test]
 async fn test_multiple_errors_sequentially() {
-    let engine = Engine::for_testing().await;
+    let fail1 = Arc::new(MockAdapter::new("test.fail1").then(MockResponse::err("first")));
+    let fail2 = Arc::new(MockAdapter::new("test.fail2").then(MockResponse::err("second")));
+    let engine = engine_with_mocks([&fail1, &fail2]).await;
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![
-            Step {
-                id: "fail1".to_string().into(),
-                use_: Some("nonexistent1".to_string()),
-                with: Some(HashMap::new()),
-                ..Default::default()
-            },
-            Step {
-                id: "fail2".to_string().into(),
-                use_: Some("nonexistent2".to_string()),
-                with: Some(HashMap::new()),
-                ..Default::default()
-            },
+            create_step("fail1", "test.fail1", "a"),
+            create_step("fail2", "test.fail2", "b"),
         ],
         ..Default::default()
     };
 
-    let result = engine.execute(&flow, HashMap::new()).await;
+    let err = engine.execute(&flow, HashMap::new()).await.unwrap_err();
     // Should fail on first error
-    assert!(result.is_err());
+    assert!(err.to_string().contains("first"), "{}", err);
+    assert_eq!(fail2.call_count(), 0);
 }
 
 #[tokio::test]
@@ -459,7 +756,7 @@ async fn test_step_depends_on_failed_step() {
     let engine = Engine::for_testing().await;
 
     let flow = Flow {
-        name: "test-flow".to_string().into(),
+        name: FlowName::new("test-flow").unwrap(),
         steps: vec![
             Step {
                 id: "fail_step".to_string().into(),
@@ -486,3 +783,102 @@ async fn test_step_depends_on_failed_step() {
     // Should fail because dependency failed
     assert!(result.is_err());
 }
+
+/// Flow of three mocked steps, each taking the previous step's `n`
+fn checkpointed_flow() -> Flow {
+    Flow {
+        name: FlowName::new("checkpointed").unwrap(),
+        steps: vec![
+            create_step("step1", "ckpt.one", "start"),
+            create_step("step2", "ckpt.two", "{{ steps.step1.n }}"),
+            create_step("step3", "ckpt.three", "{{ steps.step2.n }}"),
+        ],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_checkpointed_run_resumes_after_crash() {
+    let one = Arc::new(MockAdapter::new("ckpt.one").otherwise(MockResponse::ok(json!({"n": 1}))));
+    let two = Arc::new(MockAdapter::new("ckpt.two").otherwise(MockResponse::ok(json!({"n": 2}))));
+    // Step 3 hangs until the process "crashes", and succeeds once recovered
+    let three = Arc::new(
+        MockAdapter::new("ckpt.three")
+            .then(MockResponse::delayed(
+                Duration::from_secs(3600),
+                MockResponse::ok(json!({})),
+            ))
+            .otherwise(MockResponse::ok(json!({"n": 3}))),
+    );
+    let mut engine = engine_with_mocks([&one, &two, &three]).await;
+    engine.config = Arc::new(crate::config::Config {
+        checkpoint_runs: Some(true),
+        ..Default::default()
+    });
+    let engine = Arc::new(engine);
+
+    // Abort the run while step 3 executes: nothing past the checkpoint survives
+    let running = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.execute(&checkpointed_flow(), HashMap::new()).await }
+    });
+    while three.call_count() == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    running.abort();
+    assert!(running.await.unwrap_err().is_cancelled());
+
+    let checkpoints = engine.storage().load_run_checkpoints().await.unwrap();
+    assert_eq!(checkpoints.len(), 1);
+    let (run_id, data) = checkpoints.into_iter().next().unwrap();
+    let checkpoint: RunCheckpoint = serde_json::from_value(data).unwrap();
+    assert_eq!(checkpoint.completed_steps, vec!["step1", "step2"]);
+    assert_eq!(checkpoint.outputs["step2"], json!({"n": 2}));
+    let run = engine.storage().get_run(run_id).await.unwrap().unwrap();
+    assert_eq!(run.status, RunStatus::Running);
+
+    // The recovery scan continues from step 3 instead of restarting the run
+    let recovered = engine.recover_checkpointed_runs().await.unwrap();
+    assert_eq!(recovered, vec![run_id]);
+    assert_eq!(one.call_count(), 1);
+    assert_eq!(two.call_count(), 1);
+    assert_eq!(three.call_count(), 2);
+    assert_eq!(three.calls()[1]["text"], "2");
+
+    let run = engine.storage().get_run(run_id).await.unwrap().unwrap();
+    assert_eq!(run.status, RunStatus::Succeeded);
+    assert!(
+        engine
+            .storage()
+            .load_run_checkpoints()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // A second scan finds nothing left to recover
+    assert!(engine.recover_checkpointed_runs().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_runs_are_not_checkpointed_by_default() {
+    let one = Arc::new(MockAdapter::new("ckpt.one").otherwise(MockResponse::ok(json!({"n": 1}))));
+    let two = Arc::new(MockAdapter::new("ckpt.two").otherwise(MockResponse::err("boom")));
+    let three = Arc::new(MockAdapter::new("ckpt.three"));
+    let engine = engine_with_mocks([&one, &two, &three]).await;
+
+    assert!(
+        engine
+            .execute(&checkpointed_flow(), HashMap::new())
+            .await
+            .is_err()
+    );
+    assert!(
+        engine
+            .storage()
+            .load_run_checkpoints()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -2,17 +2,33 @@
 //!
 //! Handles execution of individual steps, parallel blocks, loops, and conditionals.
 
-use super::{PausedRun, StepContext};
-use crate::adapter::{Adapter, AdapterRegistry};
+use super::{PausedRun, RunCheckpoint, StepContext, approval, poll};
+use crate::adapter::{Adapter, AdapterRegistry, ExecutionContext, ProgressHandle, UsageMeter};
+use crate::constants::{BLOCK_OUTPUT_FIELD, EVENT_TOPIC_STEP_STATUS};
 use crate::dsl::{DependencyAnalyzer, Templater};
-use crate::storage::Storage;
+use crate::error::NetworkError;
+use crate::event::{EventBus, EventEnvelope, EventSource};
+use crate::model::{McpServerConfig, PendingEvent, StepProgress, StepRun, StepStatus};
+use crate::secrets::OutputScanner;
+use crate::storage::{Storage, WriteBatch};
 use crate::{BeemFlowError, Flow, Result, Step};
 use serde_json::Value;
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::Semaphore;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, watch};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 use uuid::Uuid;
 
+/// Minimum interval between progress writes for a running step
+const PROGRESS_PERSIST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest delay between retries of a step without `max_delay_sec` (5 minutes)
+const MAX_RETRY_DELAY_SEC: u64 = 300;
+
 // ============================================================================
 // Helper Functions (used by both main executor and parallel tasks)
 // ============================================================================
@@ -84,11 +100,32 @@ fn prepare_inputs(
                 .map(|(k, v)| {
                     render_value(templater, v, &template_data).map(|rendered| (k.clone(), rendered))
                 })
-                .collect()
+                .collect::<Result<_>>()
+                .map_err(|e| e.into_step_failure_as(&step.id, crate::StepErrorKind::Template))
         },
     )
 }
 
+/// Render the step's `env` overrides
+fn prepare_env(
+    templater: &Arc<Templater>,
+    step: &Step,
+    step_ctx: &StepContext,
+    runs_data: Option<&HashMap<String, Value>>,
+) -> Result<HashMap<String, String>> {
+    let Some(env) = &step.env else {
+        return Ok(HashMap::new());
+    };
+    let template_data = if let Some(runs) = runs_data {
+        step_ctx.template_data_with_runs(Some(runs.clone()))
+    } else {
+        step_ctx.template_data()
+    };
+    env.iter()
+        .map(|(k, v)| Ok((k.clone(), templater.render(v, &template_data)?)))
+        .collect()
+}
+
 /// Render a JSON value recursively, expanding templates
 fn render_value(
     templater: &Arc<Templater>,
@@ -111,6 +148,72 @@ fn render_value(
     }
 }
 
+/// Validate and normalize rendered inputs against the adapter's tool manifest
+///
+/// Skipped when the step (or its flow) opts out with `strict_params: false`.
+fn normalize_inputs(
+    adapter: &Arc<dyn Adapter>,
+    step: &Step,
+    strict_params: bool,
+    inputs: HashMap<String, Value>,
+) -> Result<HashMap<String, Value>> {
+    if !step.strict_params.unwrap_or(strict_params) {
+        return Ok(inputs);
+    }
+
+    match adapter.manifest() {
+        Some(manifest) => manifest.normalize_inputs(inputs).map_err(|e| match e {
+            BeemFlowError::Validation(msg) => {
+                BeemFlowError::validation(format!("step '{}': {}", step.id, msg))
+            }
+            other => other,
+        }),
+        None => Ok(inputs),
+    }
+}
+
+/// Redact secret values referenced by a step's `with` block from its rendered inputs
+///
+/// Only secrets the step actually references are considered, so unrelated values
+/// that happen to match an environment variable are left untouched.
+fn redact_inputs(
+    step: &Step,
+    step_ctx: &StepContext,
+    inputs: &HashMap<String, Value>,
+) -> HashMap<String, Value> {
+    let raw = step
+        .with
+        .as_ref()
+        .and_then(|with| serde_json::to_string(with).ok())
+        .unwrap_or_default();
+
+    let secret_values: Vec<String> = crate::secrets::referenced_secret_names(&raw)
+        .iter()
+        .filter_map(|name| step_ctx.get_secret(name))
+        .map(|value| match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .collect();
+
+    inputs
+        .iter()
+        .map(|(k, v)| (k.clone(), crate::secrets::redact_value(v, &secret_values)))
+        .collect()
+}
+
+/// Run a tool's outputs through the secret scan, when enabled
+fn scan_outputs(
+    output_scan: Option<&OutputScanner>,
+    step_id: &str,
+    outputs: HashMap<String, Value>,
+) -> Result<HashMap<String, Value>> {
+    match output_scan {
+        Some(scanner) => scanner.scan(step_id, outputs),
+        None => Ok(outputs),
+    }
+}
+
 /// Add special __use parameter for core and MCP tools
 fn add_special_use_param(inputs: &mut HashMap<String, Value>, use_: &str) {
     if use_.starts_with(crate::constants::ADAPTER_PREFIX_CORE)
@@ -123,20 +226,267 @@ fn add_special_use_param(inputs: &mut HashMap<String, Value>, use_: &str) {
     }
 }
 
+/// Call `tool` through `adapter`, counting the invocation towards the run's usage
+async fn invoke_tool(
+    adapter: &Arc<dyn Adapter>,
+    tool: &str,
+    inputs: HashMap<String, Value>,
+    ctx: &ExecutionContext,
+) -> Result<HashMap<String, Value>> {
+    ctx.usage.record_tool_invocation(tool);
+    let span = tracing::info_span!(
+        "tool",
+        tool,
+        run_id = ctx.run_id.map(tracing::field::display),
+        request_id = ctx.request_id.as_deref(),
+    );
+    adapter.execute(inputs, ctx).instrument(span).await
+}
+
 /// Create loop variables for foreach iterations
+///
+/// Besides the item under `as_var`, `loop` describes the iteration:
+/// `index` (1-based), `index0`, `length`, `first`, `last` and `item`.
 fn create_loop_vars(
     base_vars: HashMap<String, Value>,
     as_var: &str,
     item: Value,
     index: usize,
+    length: usize,
 ) -> HashMap<String, Value> {
     let mut vars = base_vars;
+    vars.insert(
+        crate::constants::TEMPLATE_FIELD_LOOP.to_string(),
+        serde_json::json!({
+            "index": index + 1,
+            "index0": index,
+            "length": length,
+            "first": index == 0,
+            "last": index + 1 == length,
+            "item": item,
+        }),
+    );
     vars.insert(as_var.to_string(), item);
     vars.insert(format!("{}_index", as_var), Value::Number(index.into()));
     vars.insert(format!("{}_row", as_var), Value::Number((index + 1).into()));
     vars
 }
 
+/// Acquire a concurrency permit for a parallel/foreach task
+///
+/// Records a waiter when `max_concurrent_tasks` is exhausted, and keeps the task
+/// counted as in flight until the returned permit is dropped.
+async fn acquire_task_permit(
+    semaphore: &Arc<Semaphore>,
+    kind: &str,
+) -> Result<(OwnedSemaphorePermit, crate::telemetry::TaskInFlightGuard)> {
+    let permit = match semaphore.clone().try_acquire_owned() {
+        Ok(permit) => permit,
+        Err(_) => {
+            crate::telemetry::record_task_waited(kind);
+            semaphore.clone().acquire_owned().await.map_err(|e| {
+                BeemFlowError::adapter(format!("Failed to acquire semaphore: {}", e))
+            })?
+        }
+    };
+    Ok((permit, crate::telemetry::track_task_in_flight(kind)))
+}
+
+/// Parse a step's `timeout_total` / `timeout_idle` value
+fn parse_step_timeout(step: &Step, field: &str, value: Option<&str>) -> Result<Option<Duration>> {
+    value
+        .map(|v| {
+            crate::utils::parse_duration(v)
+                .ok()
+                .and_then(|d| d.to_std().ok())
+                .ok_or_else(|| {
+                    BeemFlowError::validation(format!(
+                        "step '{}': invalid {} '{}'",
+                        step.id, field, v
+                    ))
+                })
+        })
+        .transpose()
+}
+
+/// Run a tool call under the step's `timeout_total` and `timeout_idle`
+///
+/// Every progress report pushes the idle deadline back; the total deadline is fixed.
+async fn with_step_timeouts<T>(
+    step: &Step,
+    mut progress: watch::Receiver<Option<StepProgress>>,
+    call: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let total = parse_step_timeout(step, "timeout_total", step.timeout_total.as_deref())?;
+    let idle = parse_step_timeout(step, "timeout_idle", step.timeout_idle.as_deref())?;
+    if total.is_none() && idle.is_none() {
+        return call.await;
+    }
+
+    let started = Instant::now();
+    let total_deadline = total.map(|d| started + d);
+    let mut idle_deadline = idle.map(|d| started + d);
+    let mut reporting = idle.is_some();
+    tokio::pin!(call);
+
+    loop {
+        let (deadline, kind) = match (total_deadline, idle_deadline) {
+            (Some(t), Some(i)) if i < t => (i, "timeout_idle"),
+            (Some(t), _) => (t, "timeout_total"),
+            (None, Some(i)) => (i, "timeout_idle"),
+            (None, None) => unreachable!("at least one timeout is set"),
+        };
+
+        tokio::select! {
+            result = &mut call => return result,
+            changed = progress.changed(), if reporting => match changed {
+                Ok(()) => idle_deadline = idle.map(|d| Instant::now() + d),
+                Err(_) => reporting = false,
+            },
+            _ = tokio::time::sleep_until(deadline) => {
+                let limit = match kind {
+                    "timeout_idle" => step.timeout_idle.as_deref(),
+                    _ => step.timeout_total.as_deref(),
+                };
+                return Err(BeemFlowError::step_execution(
+                    step.id.to_string(),
+                    crate::StepErrorKind::Timeout,
+                    format!("{} of {} exceeded", kind, limit.unwrap_or_default()),
+                ));
+            }
+        }
+    }
+}
+
+/// Identity of a top-level step while it runs
+///
+/// Progress writes and the final result share one `StepRun` record.
+struct InFlightStep {
+    id: Uuid,
+    run_id: Uuid,
+    started_at: chrono::DateTime<chrono::Utc>,
+    progress: Mutex<Option<StepProgress>>,
+    persisted: Mutex<bool>,
+}
+
+impl InFlightStep {
+    fn new(run_id: Uuid, started_at: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            run_id,
+            started_at,
+            progress: Mutex::new(None),
+            persisted: Mutex::new(false),
+        }
+    }
+
+    fn record(&self, step: &Step, status: StepStatus) -> StepRun {
+        StepRun {
+            id: self.id,
+            run_id: self.run_id,
+            step_name: step.id.clone(),
+            status,
+            started_at: self.started_at,
+            ended_at: None,
+            error: None,
+            error_kind: None,
+            inputs: None,
+            outputs: None,
+            progress: self.progress.lock().ok().and_then(|p| p.clone()),
+        }
+    }
+}
+
+/// Source and correlation ID stamped on the events a run publishes
+#[derive(Debug, Clone, Default)]
+struct EventOrigin {
+    source: EventSource,
+    correlation_id: Option<String>,
+}
+
+impl EventOrigin {
+    /// The run executing, when the events come from one
+    fn run_id(&self) -> Option<Uuid> {
+        match self.source {
+            EventSource::Run { run_id, .. } => Some(run_id),
+            _ => None,
+        }
+    }
+}
+
+/// The event a `core.publish` step emits, taken from its outputs
+fn published_event(
+    step: &Step,
+    outputs: Option<&HashMap<String, Value>>,
+    origin: &EventOrigin,
+) -> Option<PendingEvent> {
+    if step.use_.as_deref() != Some(crate::constants::CORE_PUBLISH) {
+        return None;
+    }
+    let outputs = outputs?;
+    Some(PendingEvent {
+        topic: outputs.get("topic")?.as_str()?.to_string(),
+        payload: outputs.get("payload").cloned().unwrap_or(Value::Null),
+        source: origin.source.clone(),
+        correlation_id: origin.correlation_id.clone(),
+    })
+}
+
+/// Enqueue the event of a `core.publish` step that has no step record to commit with
+async fn enqueue_published_event(
+    storage: &dyn Storage,
+    step: &Step,
+    outputs: &HashMap<String, Value>,
+    origin: &EventOrigin,
+) -> Result<()> {
+    if let Some(event) = published_event(step, Some(outputs), origin) {
+        storage
+            .commit(&WriteBatch {
+                events: vec![event],
+                ..Default::default()
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+/// Persist progress reports onto the running step, at most once per interval
+///
+/// The first report is written immediately. Stops when `cancel` fires.
+async fn persist_progress(
+    storage: Arc<dyn Storage>,
+    step: Step,
+    in_flight: Arc<InFlightStep>,
+    mut progress: watch::Receiver<Option<StepProgress>>,
+    cancel: CancellationToken,
+) {
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => return,
+            changed = progress.changed() => if changed.is_err() { return },
+        }
+
+        let latest = progress.borrow_and_update().clone();
+        if let Ok(mut current) = in_flight.progress.lock() {
+            *current = latest;
+        }
+        let record = in_flight.record(&step, StepStatus::Running);
+        match storage.save_step(&record).await {
+            Ok(()) => {
+                if let Ok(mut persisted) = in_flight.persisted.lock() {
+                    *persisted = true;
+                }
+            }
+            Err(e) => tracing::warn!("Failed to persist progress for step {}: {}", step.id, e),
+        }
+
+        tokio::select! {
+            _ = cancel.cancelled() => return,
+            _ = tokio::time::sleep(PROGRESS_PERSIST_INTERVAL) => {}
+        }
+    }
+}
+
 /// Step executor
 pub struct Executor {
     adapters: Arc<AdapterRegistry>,
@@ -146,6 +496,18 @@ pub struct Executor {
     oauth_client: Arc<crate::auth::OAuthClientManager>,
     runs_data: Option<HashMap<String, Value>>,
     max_concurrent_tasks: usize,
+    strict_params: bool,
+    blob_stores: Arc<crate::blob::BlobStores>,
+    blob_store: Option<String>,
+    event_origin: EventOrigin,
+    event_bus: Option<Arc<dyn EventBus>>,
+    usage: UsageMeter,
+    stop_after: Option<String>,
+    output_scan: Option<OutputScanner>,
+    mcp_servers: Arc<HashMap<String, McpServerConfig>>,
+    checkpoints: bool,
+    tenant_id: Option<String>,
+    clock: Arc<dyn crate::clock::Clock>,
 }
 
 impl Executor {
@@ -159,6 +521,11 @@ impl Executor {
         runs_data: Option<HashMap<String, Value>>,
         max_concurrent_tasks: usize,
     ) -> Self {
+        let blob_stores = Arc::new(crate::blob::BlobStores::new(
+            crate::blob::BlobConfig::default(),
+            HashMap::new(),
+            storage.clone(),
+        ));
         Self {
             adapters,
             templater,
@@ -167,9 +534,109 @@ impl Executor {
             oauth_client,
             runs_data,
             max_concurrent_tasks,
+            strict_params: true,
+            blob_stores,
+            blob_store: None,
+            event_origin: EventOrigin::default(),
+            event_bus: None,
+            usage: UsageMeter::default(),
+            stop_after: None,
+            output_scan: None,
+            mcp_servers: Arc::default(),
+            checkpoints: false,
+            tenant_id: None,
+            clock: Arc::new(crate::clock::SystemClock),
         }
     }
 
+    /// Set the flow-level default for validating inputs against tool manifests
+    pub fn with_strict_params(mut self, strict_params: bool) -> Self {
+        self.strict_params = strict_params;
+        self
+    }
+
+    /// Set the blob stores steps write to and the flow-level store name
+    pub fn with_blob_stores(
+        mut self,
+        blob_stores: Arc<crate::blob::BlobStores>,
+        blob_store: Option<String>,
+    ) -> Self {
+        self.blob_stores = blob_stores;
+        self.blob_store = blob_store;
+        self
+    }
+
+    /// Stamp the events `core.publish` steps emit with `source` and `correlation_id`
+    ///
+    /// The correlation ID is also saved with runs paused by this executor.
+    pub fn with_event_origin(
+        mut self,
+        source: EventSource,
+        correlation_id: Option<String>,
+    ) -> Self {
+        self.event_origin = EventOrigin {
+            source,
+            correlation_id,
+        };
+        self
+    }
+
+    /// Attribute tool calls to the tenant `tenant_id`, which scopes the OAuth
+    /// credentials they can use
+    ///
+    /// The tenant is also saved with runs paused by this executor.
+    pub fn with_tenant_id(mut self, tenant_id: Option<String>) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    /// Read the time from `clock` (see [`crate::clock`])
+    pub fn with_clock(mut self, clock: Arc<dyn crate::clock::Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Set the event bus that step lifecycle events are published to
+    pub fn with_event_bus(mut self, event_bus: Arc<dyn EventBus>) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Count the run's resource usage on `usage`, shared by every tool call
+    pub fn with_usage(mut self, usage: UsageMeter) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Scan tool outputs for secret values before they reach the step context
+    pub fn with_output_scan(mut self, output_scan: Option<OutputScanner>) -> Self {
+        self.output_scan = output_scan;
+        self
+    }
+
+    /// Route `mcp://` calls to the servers the flow declares, env rendered for the run
+    pub fn with_mcp_servers(mut self, mcp_servers: Arc<HashMap<String, McpServerConfig>>) -> Self {
+        self.mcp_servers = mcp_servers;
+        self
+    }
+
+    /// Stop [`Executor::execute_steps`] once the top-level step `step_id` has run
+    pub fn with_stop_after(mut self, step_id: Option<String>) -> Self {
+        self.stop_after = step_id;
+        self
+    }
+
+    /// Save a [`RunCheckpoint`] after every top-level step (`checkpointRuns`)
+    pub fn with_checkpoints(mut self, checkpoints: bool) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+
+    /// Blob store name for `step`: its own setting, else the flow's
+    fn blob_store_for(&self, step: &Step) -> Option<String> {
+        step.blob_store.clone().or_else(|| self.blob_store.clone())
+    }
+
     /// Get template data with runs context if available
     fn get_template_data(&self, step_ctx: &StepContext) -> HashMap<String, Value> {
         if let Some(ref runs) = self.runs_data {
@@ -196,10 +663,6 @@ impl Executor {
         let analyzer = DependencyAnalyzer::new();
         let sorted_ids = analyzer.topological_sort(flow)?;
 
-        // Create lookup map for steps
-        let step_map: HashMap<String, &Step> =
-            flow.steps.iter().map(|s| (s.id.to_string(), s)).collect();
-
         // Determine which step to start from
         // For fresh runs (start_idx=0), execute all steps in sorted order
         // For resumed runs, find the resume point in sorted order
@@ -217,8 +680,49 @@ impl Executor {
             return Ok(step_ctx.snapshot().outputs);
         };
 
-        // Execute steps in dependency order (starting from start_idx)
-        for step_id in sorted_ids.iter().skip(sorted_start_idx) {
+        let (completed, pending) = sorted_ids.split_at(sorted_start_idx);
+        self.execute_sorted(flow, step_ctx, completed.to_vec(), pending, run_id)
+            .await
+    }
+
+    /// Execute the top-level steps a checkpoint doesn't list as completed
+    ///
+    /// `step_ctx` should hold the checkpoint's outputs. The remaining steps run in
+    /// dependency order.
+    pub async fn resume_from_checkpoint(
+        &self,
+        flow: &Flow,
+        step_ctx: &StepContext,
+        completed_steps: &[String],
+        run_id: Uuid,
+    ) -> Result<HashMap<String, Value>> {
+        let pending: Vec<String> = DependencyAnalyzer::new()
+            .topological_sort(flow)?
+            .into_iter()
+            .filter(|id| !completed_steps.contains(id))
+            .collect();
+
+        self.execute_sorted(flow, step_ctx, completed_steps.to_vec(), &pending, run_id)
+            .await
+    }
+
+    /// Execute the `pending` steps in order, after the already `completed` ones
+    ///
+    /// Pauses the run at `await_event` and approval steps.
+    async fn execute_sorted(
+        &self,
+        flow: &Flow,
+        step_ctx: &StepContext,
+        mut completed: Vec<String>,
+        pending: &[String],
+        run_id: Uuid,
+    ) -> Result<HashMap<String, Value>> {
+        // Create lookup map for steps
+        let step_map: HashMap<String, &Step> =
+            flow.steps.iter().map(|s| (s.id.to_string(), s)).collect();
+
+        // Execute steps in dependency order
+        for step_id in pending {
             let step = step_map
                 .get(step_id)
                 .ok_or_else(|| BeemFlowError::adapter(format!("step not found: {}", step_id)))?;
@@ -236,22 +740,200 @@ impl Executor {
                     .await;
             }
 
-            // Execute regular step
-            self.execute_single_step(step, step_ctx, &step.id).await?;
+            // Handle approval gates (pause like await_event, with a generated token)
+            if step.use_.as_deref() == Some(crate::constants::CORE_APPROVAL) {
+                if let Some(ref condition) = step.if_
+                    && !self.evaluate_condition(condition, step_ctx).await?
+                {
+                    continue;
+                }
+                let idx = flow
+                    .steps
+                    .iter()
+                    .position(|s| s.id.as_str() == step_id)
+                    .unwrap();
+                return self
+                    .handle_approval(step, flow, step_ctx, idx, run_id)
+                    .await;
+            }
 
-            // Persist step result
-            self.persist_step_result(step, step_ctx, run_id).await?;
+            self.execute_top_level_step(step, step_ctx, run_id).await?;
+            if self.checkpoints {
+                completed.push(step_id.clone());
+                self.save_checkpoint(flow, step_ctx, &completed, run_id)
+                    .await;
+            }
+            if self.stop_after.as_ref() == Some(step_id) {
+                break;
+            }
         }
 
         Ok(step_ctx.snapshot().outputs)
     }
 
+    /// Checkpoint the run after its `completed` steps; a failed write is logged,
+    /// not fatal to the run
+    async fn save_checkpoint(
+        &self,
+        flow: &Flow,
+        step_ctx: &StepContext,
+        completed: &[String],
+        run_id: Uuid,
+    ) {
+        let snapshot = step_ctx.snapshot();
+        let checkpoint = RunCheckpoint {
+            flow: flow.clone(),
+            run_id,
+            completed_steps: completed.to_vec(),
+            event: snapshot.event,
+            vars: snapshot.vars,
+            outputs: snapshot.outputs,
+            correlation_id: self.event_origin.correlation_id.clone(),
+            stop_after: self.stop_after.clone(),
+        };
+        let saved = match serde_json::to_value(&checkpoint) {
+            Ok(data) => self.storage.save_run_checkpoint(run_id, data).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = saved {
+            tracing::warn!("Failed to checkpoint run {}: {}", run_id, e);
+        }
+    }
+
+    /// Re-execute the named top-level steps of an existing run
+    ///
+    /// Steps run in dependency order against `step_ctx`, which should already hold
+    /// the outputs of the steps that are not re-executed. Each step gets a new
+    /// `StepRun` record under `run_id`. Steps that pause a run (`await_event`,
+    /// `core.approval`) cannot be re-executed.
+    pub async fn rerun_steps(
+        &self,
+        flow: &Flow,
+        step_ctx: &StepContext,
+        run_id: Uuid,
+        step_ids: &HashSet<String>,
+    ) -> Result<HashMap<String, Value>> {
+        let sorted_ids = DependencyAnalyzer::new().topological_sort(flow)?;
+        let steps: Vec<&Step> = sorted_ids
+            .iter()
+            .filter(|id| step_ids.contains(*id))
+            .filter_map(|id| flow.steps.iter().find(|s| s.id.as_str() == id))
+            .collect();
+
+        if let Some(step) = steps.iter().find(|s| {
+            s.await_event.is_some() || s.use_.as_deref() == Some(crate::constants::CORE_APPROVAL)
+        }) {
+            return Err(BeemFlowError::validation(format!(
+                "Step '{}' pauses its run and cannot be re-executed",
+                step.id
+            )));
+        }
+
+        for step in steps {
+            self.execute_top_level_step(step, step_ctx, run_id).await?;
+        }
+
+        Ok(step_ctx.snapshot().outputs)
+    }
+
+    /// Execute one top-level step and persist its `StepRun`
+    async fn execute_top_level_step(
+        &self,
+        step: &Step,
+        step_ctx: &StepContext,
+        run_id: Uuid,
+    ) -> Result<()> {
+        let in_flight = Arc::new(InFlightStep::new(run_id, self.clock.now()));
+        self.publish_step_status(step, run_id, StepStatus::Running, None)
+            .await;
+
+        let result = match self
+            .execute_step(step, step_ctx, &step.id, Some(&in_flight))
+            .await
+        {
+            Ok(()) => self.persist_step_result(step, step_ctx, &in_flight).await,
+            Err(e) if step.continue_on_error == Some(true) => {
+                // Best effort: record the failure and hand the error to later steps
+                tracing::warn!("Step {} failed, continuing: {}", step.id, e);
+                self.persist_step_failure(step, &in_flight, &e, true).await;
+                self.publish_step_status(step, run_id, StepStatus::Failed, Some(&e))
+                    .await;
+                step_ctx.set_output(
+                    step.id.to_string(),
+                    serde_json::json!({ "error": e.to_string(), "error_kind": e.step_kind() }),
+                );
+                return Ok(());
+            }
+            Err(e) => {
+                self.persist_step_failure(step, &in_flight, &e, false).await;
+                Err(e)
+            }
+        };
+
+        match &result {
+            Ok(()) => {
+                self.publish_step_status(step, run_id, StepStatus::Succeeded, None)
+                    .await
+            }
+            Err(e) => {
+                self.publish_step_status(step, run_id, StepStatus::Failed, Some(e))
+                    .await
+            }
+        }
+        result
+    }
+
+    /// Announce a top-level step's status on the event bus (`step.status`)
+    ///
+    /// These are live progress signals, so they skip the outbox: a subscriber
+    /// that misses one is not redelivered it.
+    async fn publish_step_status(
+        &self,
+        step: &Step,
+        run_id: Uuid,
+        status: StepStatus,
+        error: Option<&BeemFlowError>,
+    ) {
+        let Some(ref event_bus) = self.event_bus else {
+            return;
+        };
+        let mut payload = serde_json::json!({
+            "run_id": run_id,
+            "step_id": step.id,
+            "status": status,
+        });
+        if let Some(error) = error {
+            payload["error"] = Value::String(error.to_string());
+        }
+        let event = EventEnvelope::new(EVENT_TOPIC_STEP_STATUS, payload)
+            .with_source(self.event_origin.source.clone())
+            .with_correlation_id(self.event_origin.correlation_id.clone());
+        if let Err(e) = event_bus.publish(event).await {
+            tracing::warn!("Failed to publish status of step {}: {}", step.id, e);
+        }
+    }
+
     /// Execute a single step (boxed to handle recursion)
     pub fn execute_single_step<'a>(
         &'a self,
         step: &'a Step,
         step_ctx: &'a StepContext,
         step_id: &'a str,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            self.execute_step(step, step_ctx, step_id, None)
+                .await
+                .map_err(|e| e.into_step_failure(step_id))
+        })
+    }
+
+    /// Execute a step, persisting progress onto `in_flight` for top-level tool calls
+    fn execute_step<'a>(
+        &'a self,
+        step: &'a Step,
+        step_ctx: &'a StepContext,
+        step_id: &'a str,
+        in_flight: Option<&'a Arc<InFlightStep>>,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
         Box::pin(async move {
             // Check condition first
@@ -280,7 +962,9 @@ impl Executor {
             }
 
             if let Some(ref use_) = step.use_ {
-                return self.execute_tool_call(use_, step, step_ctx, step_id).await;
+                return self
+                    .execute_tool_call(use_, step, step_ctx, step_id, in_flight)
+                    .await;
             }
 
             Ok(())
@@ -312,9 +996,15 @@ impl Executor {
             let storage = self.storage.clone();
             let secrets_provider = self.secrets_provider.clone();
             let oauth_client = self.oauth_client.clone();
-            let permit = semaphore.clone().acquire_owned().await.map_err(|e| {
-                BeemFlowError::adapter(format!("Failed to acquire semaphore: {}", e))
-            })?;
+            let blob_stores = self.blob_stores.clone();
+            let blob_store = self.blob_store_for(&child);
+            let event_origin = self.event_origin.clone();
+            let usage = self.usage.clone();
+            let output_scan = self.output_scan.clone();
+            let mcp_servers = self.mcp_servers.clone();
+            let tenant_id = self.tenant_id.clone();
+            let strict_params = step.strict_params.unwrap_or(self.strict_params);
+            let permit = acquire_task_permit(&semaphore, "parallel").await?;
 
             let handle = tokio::spawn(async move {
                 let _permit = permit; // Hold permit until task completes
@@ -322,18 +1012,46 @@ impl Executor {
                 // Execute tool call directly for parallel steps (no nesting)
                 if let Some(ref use_) = child.use_ {
                     let adapter = resolve_adapter(&adapters, use_).await?;
-                    let mut inputs =
+                    let inputs =
                         prepare_inputs(&templater, &child, &step_ctx_clone, runs_data.as_ref())?;
+                    let mut inputs = normalize_inputs(&adapter, &child, strict_params, inputs)?;
+                    step_ctx_clone.set_inputs(
+                        child.id.to_string(),
+                        redact_inputs(&child, &step_ctx_clone, &inputs),
+                    );
                     add_special_use_param(&mut inputs, use_);
 
                     // Create execution context for OAuth and secrets expansion
+                    let (progress, progress_rx) = ProgressHandle::channel();
                     let exec_ctx = crate::adapter::ExecutionContext::new(
-                        storage,
+                        storage.clone(),
                         secrets_provider.clone(),
                         oauth_client.clone(),
-                    );
-
-                    let outputs = adapter.execute(inputs, &exec_ctx).await?;
+                    )
+                    .with_progress(progress)
+                    .with_blob_store(blob_stores, blob_store)
+                    .with_usage(usage)
+                    .with_rate_limiter(adapters.rate_limiter().clone())
+                    .with_env(prepare_env(
+                        &templater,
+                        &child,
+                        &step_ctx_clone,
+                        runs_data.as_ref(),
+                    )?)
+                    .with_mcp_servers(mcp_servers)
+                    .with_run_id(event_origin.run_id())
+                    .with_request_id(event_origin.correlation_id.clone())
+                    .with_tenant_id(tenant_id);
+
+                    let outputs = with_step_timeouts(
+                        &child,
+                        progress_rx,
+                        invoke_tool(&adapter, use_, inputs, &exec_ctx),
+                    )
+                    .await?;
+                    let outputs = scan_outputs(output_scan.as_ref(), &child.id, outputs)?;
+                    enqueue_published_event(storage.as_ref(), &child, &outputs, &event_origin)
+                        .await?;
                     step_ctx_clone.set_output(child.id.to_string(), serde_json::to_value(outputs)?);
                 }
                 Ok::<_, BeemFlowError>((child.id.to_string(), step_ctx_clone.get_output(&child.id)))
@@ -367,7 +1085,15 @@ impl Executor {
             }
         }
 
-        step_ctx.set_output(step_id.to_string(), serde_json::to_value(outputs)?);
+        // `output` is the canonical shape. The branches are repeated at the top
+        // level only for backward compatibility: flows written before `output`
+        // existed read `steps.<block>.<branch>`, so dropping them would break those
+        let mut block_output = serde_json::Map::from_iter(outputs.clone());
+        block_output.insert(
+            BLOCK_OUTPUT_FIELD.to_string(),
+            Value::Object(serde_json::Map::from_iter(outputs)),
+        );
+        step_ctx.set_output(step_id.to_string(), Value::Object(block_output));
         Ok(())
     }
 
@@ -400,36 +1126,44 @@ impl Executor {
             ))
         })?;
 
-        if list.is_empty() {
-            step_ctx.set_output(step_id.to_string(), Value::Object(serde_json::Map::new()));
-            return Ok(());
-        }
-
         // Execute in parallel or sequential
-        if step.parallel == Some(true) {
+        let iterations = if list.is_empty() {
+            Vec::new()
+        } else if step.parallel == Some(true) {
             self.execute_foreach_parallel(list, as_var, do_steps, step_ctx)
-                .await?;
+                .await?
         } else {
             self.execute_foreach_sequential(list, as_var, do_steps, step_ctx)
-                .await?;
-        }
+                .await?
+        };
 
-        step_ctx.set_output(step_id.to_string(), Value::Object(serde_json::Map::new()));
+        let mut block_output = serde_json::Map::new();
+        block_output.insert(BLOCK_OUTPUT_FIELD.to_string(), Value::Array(iterations));
+        step_ctx.set_output(step_id.to_string(), Value::Object(block_output));
         Ok(())
     }
 
     /// Execute foreach sequentially
+    ///
+    /// Returns each iteration's outputs, keyed by inner step ID.
     async fn execute_foreach_sequential(
         &self,
         list: &[Value],
         as_var: &str,
         do_steps: &[Step],
         step_ctx: &StepContext,
-    ) -> Result<()> {
+    ) -> Result<Vec<Value>> {
+        let mut iterations = Vec::with_capacity(list.len());
         for (index, item) in list.iter().enumerate() {
             // Create child context with loop variables
             let snapshot = step_ctx.snapshot();
-            let iter_vars = create_loop_vars(snapshot.vars.clone(), as_var, item.clone(), index);
+            let iter_vars = create_loop_vars(
+                snapshot.vars.clone(),
+                as_var,
+                item.clone(),
+                index,
+                list.len(),
+            );
             let iter_ctx = StepContext::new(snapshot.event, iter_vars, snapshot.secrets);
 
             // Copy outputs to child context
@@ -439,6 +1173,7 @@ impl Executor {
                 .for_each(|(k, v)| iter_ctx.set_output(k, v));
 
             // Execute all steps for this iteration
+            let mut iteration = serde_json::Map::new();
             for inner_step in do_steps {
                 // Render step ID
                 let template_data = self.get_template_data(&iter_ctx);
@@ -453,7 +1188,11 @@ impl Executor {
 
                 self.execute_single_step(inner_step, &iter_ctx, &rendered_id)
                     .await?;
+                if let Some(output) = iter_ctx.get_output(&rendered_id) {
+                    iteration.insert(rendered_id, output);
+                }
             }
+            iterations.push(Value::Object(iteration));
 
             // Copy outputs back to parent context
             let iter_snapshot = iter_ctx.snapshot();
@@ -462,21 +1201,24 @@ impl Executor {
             }
         }
 
-        Ok(())
+        Ok(iterations)
     }
 
     /// Execute foreach in parallel
+    ///
+    /// Returns each iteration's outputs, keyed by inner step ID, in list order.
     async fn execute_foreach_parallel(
         &self,
         list: &[Value],
         as_var: &str,
         do_steps: &[Step],
         step_ctx: &StepContext,
-    ) -> Result<()> {
+    ) -> Result<Vec<Value>> {
         // Create semaphore to limit concurrent tasks
         let semaphore = Arc::new(Semaphore::new(self.max_concurrent_tasks));
         let mut handles = Vec::new();
 
+        let length = list.len();
         for (index, item) in list.iter().enumerate() {
             let item = item.clone();
             let as_var = as_var.to_string();
@@ -488,15 +1230,22 @@ impl Executor {
             let storage = self.storage.clone();
             let secrets_provider = self.secrets_provider.clone();
             let oauth_client = self.oauth_client.clone();
-            let permit = semaphore.clone().acquire_owned().await.map_err(|e| {
-                BeemFlowError::adapter(format!("Failed to acquire semaphore: {}", e))
-            })?;
+            let blob_stores = self.blob_stores.clone();
+            let blob_store_names: Vec<Option<String>> =
+                do_steps.iter().map(|s| self.blob_store_for(s)).collect();
+            let event_origin = self.event_origin.clone();
+            let usage = self.usage.clone();
+            let output_scan = self.output_scan.clone();
+            let mcp_servers = self.mcp_servers.clone();
+            let tenant_id = self.tenant_id.clone();
+            let permit = acquire_task_permit(&semaphore, "foreach").await?;
 
             let handle = tokio::spawn(async move {
                 let _permit = permit; // Hold permit until task completes
 
                 // Create iteration context with loop variables
-                let iter_vars = create_loop_vars(snapshot.vars.clone(), &as_var, item, index);
+                let iter_vars =
+                    create_loop_vars(snapshot.vars.clone(), &as_var, item, index, length);
                 let iter_ctx = StepContext::new(snapshot.event, iter_vars, snapshot.secrets);
 
                 // Copy existing outputs using iterator
@@ -506,35 +1255,78 @@ impl Executor {
                     .for_each(|(k, v)| iter_ctx.set_output(k, v));
 
                 // Create execution context for OAuth expansion
+                let (progress, progress_rx) = ProgressHandle::channel();
                 let exec_ctx = crate::adapter::ExecutionContext::new(
-                    storage,
+                    storage.clone(),
                     secrets_provider.clone(),
                     oauth_client.clone(),
-                );
+                )
+                .with_progress(progress)
+                .with_usage(usage)
+                .with_rate_limiter(adapters.rate_limiter().clone())
+                .with_mcp_servers(mcp_servers)
+                .with_run_id(event_origin.run_id())
+                .with_request_id(event_origin.correlation_id.clone())
+                .with_tenant_id(tenant_id);
 
                 // Execute steps - simple tool calls only in parallel foreach
-                for inner_step in &do_steps {
+                let mut iteration = serde_json::Map::new();
+                for (inner_step, blob_store) in do_steps.iter().zip(blob_store_names) {
                     if let Some(ref use_) = inner_step.use_ {
                         let adapter = resolve_adapter(&adapters, use_).await?;
                         let mut inputs =
                             prepare_inputs(&templater, inner_step, &iter_ctx, runs_data.as_ref())?;
                         add_special_use_param(&mut inputs, use_);
-
-                        let outputs = adapter.execute(inputs, &exec_ctx).await?;
-                        iter_ctx
-                            .set_output(inner_step.id.to_string(), serde_json::to_value(outputs)?);
+                        let exec_ctx = exec_ctx
+                            .clone()
+                            .with_blob_store(blob_stores.clone(), blob_store)
+                            .with_env(prepare_env(
+                                &templater,
+                                inner_step,
+                                &iter_ctx,
+                                runs_data.as_ref(),
+                            )?);
+
+                        let outputs = with_step_timeouts(
+                            inner_step,
+                            progress_rx.clone(),
+                            invoke_tool(&adapter, use_, inputs, &exec_ctx),
+                        )
+                        .await?;
+                        let outputs = scan_outputs(output_scan.as_ref(), &inner_step.id, outputs)?;
+                        enqueue_published_event(
+                            storage.as_ref(),
+                            inner_step,
+                            &outputs,
+                            &event_origin,
+                        )
+                        .await?;
+                        // Templated IDs render per iteration, as in sequential foreach
+                        let template_data = iter_ctx.template_data_with_runs(runs_data.clone());
+                        let rendered_id = render_value(
+                            &templater,
+                            &Value::String(inner_step.id.to_string()),
+                            &template_data,
+                        )?
+                        .as_str()
+                        .unwrap_or(inner_step.id.as_str())
+                        .to_string();
+                        let outputs = serde_json::to_value(outputs)?;
+                        iteration.insert(rendered_id.clone(), outputs.clone());
+                        iter_ctx.set_output(rendered_id, outputs);
                     }
                 }
 
-                Ok::<_, BeemFlowError>(iter_ctx.snapshot())
+                Ok::<_, BeemFlowError>((iter_ctx.snapshot(), Value::Object(iteration)))
             });
 
             handles.push(handle);
         }
 
         // Wait for all iterations
+        let mut iterations = Vec::with_capacity(handles.len());
         for handle in handles {
-            let snapshot = match handle.await {
+            let (snapshot, iteration) = match handle.await {
                 Ok(result) => result?,
                 Err(e) if e.is_panic() => {
                     tracing::error!("Foreach parallel task panicked: {:?}", e);
@@ -557,9 +1349,10 @@ impl Executor {
                 .outputs
                 .into_iter()
                 .for_each(|(k, v)| step_ctx.set_output(k, v));
+            iterations.push(iteration);
         }
 
-        Ok(())
+        Ok(iterations)
     }
 
     /// Execute a tool call
@@ -569,26 +1362,91 @@ impl Executor {
         step: &Step,
         step_ctx: &StepContext,
         step_id: &str,
+        in_flight: Option<&Arc<InFlightStep>>,
     ) -> Result<()> {
         let adapter = resolve_adapter(&self.adapters, use_).await?;
-        let mut inputs = prepare_inputs(&self.templater, step, step_ctx, self.runs_data.as_ref())?;
+        // A poll step renders its `until` condition against each response instead
+        let (render_step, poll_until) = if use_ == crate::constants::CORE_POLL {
+            let (render_step, until) = poll::split_until(step);
+            (render_step, Some(until))
+        } else {
+            (std::borrow::Cow::Borrowed(step), None)
+        };
+        let inputs = prepare_inputs(
+            &self.templater,
+            &render_step,
+            step_ctx,
+            self.runs_data.as_ref(),
+        )?;
+        let mut inputs = normalize_inputs(&adapter, step, self.strict_params, inputs)?;
+        step_ctx.set_inputs(step_id.to_string(), redact_inputs(step, step_ctx, &inputs));
         add_special_use_param(&mut inputs, use_);
 
         // Create execution context with storage for OAuth and secrets expansion
+        let (progress, progress_rx) = ProgressHandle::channel();
         let ctx = crate::adapter::ExecutionContext::new(
             self.storage.clone(),
             self.secrets_provider.clone(),
             self.oauth_client.clone(),
-        );
+        )
+        .with_progress(progress)
+        .with_blob_store(self.blob_stores.clone(), self.blob_store_for(step))
+        .with_usage(self.usage.clone())
+        .with_rate_limiter(self.adapters.rate_limiter().clone())
+        .with_env(prepare_env(
+            &self.templater,
+            step,
+            step_ctx,
+            self.runs_data.as_ref(),
+        )?)
+        .with_mcp_servers(self.mcp_servers.clone())
+        .with_run_id(self.event_origin.run_id())
+        .with_request_id(self.event_origin.correlation_id.clone())
+        .with_tenant_id(self.tenant_id.clone());
+
+        let cancel = CancellationToken::new();
+        let persister = in_flight.map(|in_flight| {
+            tokio::spawn(persist_progress(
+                self.storage.clone(),
+                step.clone(),
+                in_flight.clone(),
+                progress_rx.clone(),
+                cancel.clone(),
+            ))
+        });
 
         // Execute with retry if configured
-        let outputs = if let Some(ref retry) = step.retry {
-            self.execute_with_retry(&adapter, inputs, &ctx, retry)
-                .await?
-        } else {
-            adapter.execute(inputs, &ctx).await?
+        let call = async {
+            if let Some(until) = poll_until {
+                let spec = poll::PollSpec::parse(&step.id, &inputs, until.as_ref())?;
+                self.execute_poll(step, step_ctx, spec, &ctx).await
+            } else if let Some(ref retry) = step.retry {
+                self.execute_with_retry(&adapter, use_, inputs, &ctx, retry)
+                    .await
+            } else {
+                invoke_tool(&adapter, use_, inputs, &ctx).await
+            }
         };
+        let result = with_step_timeouts(step, progress_rx.clone(), call).await;
 
+        // Stop persisting and keep the final report for the step record
+        cancel.cancel();
+        if let Some(persister) = persister {
+            let _ = persister.await;
+        }
+        if let Some(in_flight) = in_flight
+            && let Ok(mut current) = in_flight.progress.lock()
+        {
+            *current = progress_rx.borrow().clone();
+        }
+
+        let outputs = scan_outputs(self.output_scan.as_ref(), step_id, result?)?;
+        // Top-level steps commit their event with the step record; nested steps
+        // have no record of their own
+        if in_flight.is_none() {
+            enqueue_published_event(self.storage.as_ref(), step, &outputs, &self.event_origin)
+                .await?;
+        }
         step_ctx.set_output(step_id.to_string(), serde_json::to_value(outputs)?);
         Ok(())
     }
@@ -597,15 +1455,16 @@ impl Executor {
     async fn execute_with_retry(
         &self,
         adapter: &Arc<dyn Adapter>,
+        tool: &str,
         inputs: HashMap<String, Value>,
-        ctx: &crate::adapter::ExecutionContext,
+        ctx: &ExecutionContext,
         retry: &crate::model::RetrySpec,
     ) -> Result<HashMap<String, Value>> {
         let mut attempts = 0;
         let mut last_error = None;
 
         while attempts < retry.attempts {
-            match adapter.execute(inputs.clone(), ctx).await {
+            match invoke_tool(adapter, tool, inputs.clone(), ctx).await {
                 Ok(outputs) => {
                     if attempts > 0 {
                         tracing::info!(
@@ -618,18 +1477,30 @@ impl Executor {
                 }
                 Err(e) => {
                     attempts += 1;
-                    last_error = Some(e);
 
                     if attempts < retry.attempts {
-                        let delay = self.calculate_retry_delay(attempts, retry.delay_sec);
+                        // A server's Retry-After replaces the backoff, within the same cap
+                        let max_delay =
+                            Duration::from_secs(retry.max_delay_sec.unwrap_or(MAX_RETRY_DELAY_SEC));
+                        let delay = match &e {
+                            BeemFlowError::Network(NetworkError::RetryAfter {
+                                retry_after,
+                                ..
+                            }) => *retry_after,
+                            _ => Duration::from_secs(
+                                self.calculate_retry_delay(attempts, retry.delay_sec),
+                            ),
+                        }
+                        .min(max_delay);
                         tracing::debug!(
-                            "Retrying step in {} seconds (attempt {} of {})",
+                            "Retrying step in {:?} (attempt {} of {})",
                             delay,
                             attempts + 1,
                             retry.attempts
                         );
-                        tokio::time::sleep(tokio::time::Duration::from_secs(delay)).await;
+                        tokio::time::sleep(delay).await;
                     }
+                    last_error = Some(e);
                 }
             }
         }
@@ -638,6 +1509,74 @@ impl Executor {
         Err(last_error.unwrap_or_else(|| BeemFlowError::adapter("retry failed")))
     }
 
+    /// Call a poll step's inner tool until its `until` condition holds
+    ///
+    /// Returns the matching response and the number of attempts. Errors from the
+    /// inner tool fail the step immediately; running out of attempts or time
+    /// fails it with the last response in the message.
+    async fn execute_poll(
+        &self,
+        step: &Step,
+        step_ctx: &StepContext,
+        spec: poll::PollSpec,
+        ctx: &ExecutionContext,
+    ) -> Result<HashMap<String, Value>> {
+        let adapter = resolve_adapter(&self.adapters, &spec.tool).await?;
+        let mut inputs = normalize_inputs(&adapter, step, self.strict_params, spec.with)?;
+        add_special_use_param(&mut inputs, &spec.tool);
+
+        let max_attempts = spec.policy.max_attempts.max(1);
+        let deadline = spec.max_duration.map(|d| Instant::now() + d);
+        let mut attempt = 1;
+        loop {
+            ctx.progress
+                .report(None, format!("poll attempt {} of {}", attempt, spec.tool));
+            let response = invoke_tool(&adapter, &spec.tool, inputs.clone(), ctx)
+                .await
+                .map_err(|e| {
+                    BeemFlowError::adapter(format!(
+                        "step '{}': core.poll attempt {} of '{}' failed: {}",
+                        step.id, attempt, spec.tool, e
+                    ))
+                })?;
+            let response = serde_json::to_value(response)?;
+
+            let mut data = self.get_template_data(step_ctx);
+            data.insert("response".to_string(), response.clone());
+            data.insert("attempt".to_string(), Value::from(attempt));
+            if self.condition_holds(&spec.until, &data)? {
+                return Ok(HashMap::from([
+                    ("response".to_string(), response),
+                    ("attempts".to_string(), Value::from(attempt)),
+                ]));
+            }
+
+            let delay = spec.policy.backoff(attempt);
+            let exhausted = if attempt >= max_attempts {
+                Some(format!("max_attempts {}", max_attempts))
+            } else if deadline.is_some_and(|deadline| Instant::now() + delay > deadline) {
+                spec.max_duration.map(|d| format!("max_duration {:?}", d))
+            } else {
+                None
+            };
+            if let Some(limit) = exhausted {
+                return Err(BeemFlowError::adapter(format!(
+                    "step '{}': core.poll condition not met after {} attempts ({} reached); last response: {}",
+                    step.id, attempt, limit, response
+                )));
+            }
+
+            tracing::debug!(
+                "Poll step {} not done after attempt {}, polling again in {:?}",
+                step.id,
+                attempt,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
     /// Calculate retry delay with exponential backoff
     fn calculate_retry_delay(&self, attempt: u32, base_delay: u64) -> u64 {
         // Exponential backoff: base_delay * 2^(attempt-1)
@@ -645,9 +1584,8 @@ impl Executor {
         // For attempt 2: base_delay * 2
         // For attempt 3: base_delay * 4
         // etc.
-        // Cap at 5 minutes maximum
-        let delay = base_delay * (2_u64.pow(attempt - 1));
-        delay.min(300) // Max 5 minutes
+        // The caller caps it at the step's max_delay_sec
+        base_delay.saturating_mul(2_u64.saturating_pow(attempt - 1))
     }
 
     /// Execute a wait step
@@ -703,20 +1641,24 @@ impl Executor {
             await_spec.source
         );
 
-        // Create paused run
+        // Create paused run, keyed by the token's hash so storage holds no usable token
+        let key = super::resume::storage_key(token);
         let paused = PausedRun {
             flow: flow.clone(),
             step_idx,
             context: step_ctx.clone(),
             outputs: step_ctx.snapshot().outputs,
-            token: token.to_string(),
+            token: key.clone(),
             run_id,
+            approval: None,
+            correlation_id: self.event_origin.correlation_id.clone(),
+            tenant_id: self.tenant_id.clone(),
         };
 
         // Store paused run in storage with source metadata for webhook queries
         let paused_value = serde_json::to_value(&paused)?;
         self.storage
-            .save_paused_run(token, &await_spec.source, paused_value)
+            .save_paused_run(&key, &await_spec.source, paused_value)
             .await?;
 
         Err(BeemFlowError::AwaitEventPause(format!(
@@ -725,12 +1667,175 @@ impl Executor {
         )))
     }
 
+    /// Handle a `core.approval` step
+    ///
+    /// Renders the approval request, stores the paused run under a signed token and
+    /// records the step as waiting so the token can be found via the run's steps.
+    async fn handle_approval(
+        &self,
+        step: &Step,
+        flow: &Flow,
+        step_ctx: &StepContext,
+        step_idx: usize,
+        run_id: Uuid,
+    ) -> Result<HashMap<String, Value>> {
+        let inputs = prepare_inputs(&self.templater, step, step_ctx, self.runs_data.as_ref())?;
+        let field = |name: &str| inputs.get(name).cloned();
+
+        let timeout = field("timeout")
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_else(|| approval::DEFAULT_APPROVAL_TIMEOUT.to_string());
+        let requested_at = self.clock.now();
+        let expires_at =
+            crate::utils::shift_time(requested_at, crate::utils::parse_duration(&timeout)?, false)?;
+
+        let on_reject = match field("on_reject") {
+            Some(value) => serde_json::from_value(value).map_err(|_| {
+                BeemFlowError::validation(format!(
+                    "step '{}': on_reject must be 'fail' or 'continue'",
+                    step.id
+                ))
+            })?,
+            None => approval::OnReject::default(),
+        };
+
+        let context = match field("context") {
+            Some(Value::Object(map)) => map.into_iter().collect(),
+            Some(_) => {
+                return Err(BeemFlowError::validation(format!(
+                    "step '{}': approval context must be an object",
+                    step.id
+                )));
+            }
+            None => HashMap::new(),
+        };
+
+        let approvers = match field("approvers") {
+            Some(Value::Array(approvers)) => approvers
+                .iter()
+                .map(|approver| {
+                    approver
+                        .as_str()
+                        .map(str::trim)
+                        .filter(|approver| !approver.is_empty())
+                        .map(str::to_string)
+                        .ok_or_else(|| {
+                            BeemFlowError::validation(format!(
+                                "step '{}': approvers must be non-empty strings",
+                                step.id
+                            ))
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?,
+            Some(_) => {
+                return Err(BeemFlowError::validation(format!(
+                    "step '{}': approvers must be a list",
+                    step.id
+                )));
+            }
+            None => Vec::new(),
+        };
+
+        let step_run_id = Uuid::new_v4();
+        let request = approval::ApprovalRequest {
+            step_id: step.id.to_string(),
+            step_run_id,
+            flow_name: flow.name.to_string(),
+            run_id,
+            message: field("message").and_then(|v| v.as_str().map(str::to_string)),
+            context,
+            on_reject,
+            requested_at,
+            expires_at,
+        };
+
+        let signing_key = approval::signing_key(self.secrets_provider.as_ref()).await;
+        let token = approval::generate_token(&signing_key);
+        let nonce = approval::verify_token(&signing_key, &token)?;
+
+        tracing::info!(
+            "Pausing run {} at step '{}' for approval (expires {})",
+            run_id,
+            step.id,
+            expires_at
+        );
+
+        let key = super::resume::storage_key(&token);
+        let paused = PausedRun {
+            flow: flow.clone(),
+            step_idx,
+            context: step_ctx.clone(),
+            outputs: step_ctx.snapshot().outputs,
+            token: key.clone(),
+            run_id,
+            approval: Some(request),
+            correlation_id: self.event_origin.correlation_id.clone(),
+            tenant_id: self.tenant_id.clone(),
+        };
+
+        self.storage
+            .save_paused_run(
+                &key,
+                &approval::approval_source(&token),
+                serde_json::to_value(&paused)?,
+            )
+            .await?;
+        self.storage
+            .register_wait(nonce, Some(expires_at.timestamp()))
+            .await?;
+
+        let mut outputs = HashMap::new();
+        outputs.insert("token".to_string(), Value::String(token.clone()));
+        outputs.insert(
+            "approval_url".to_string(),
+            Value::String(format!("/approvals/{}", token)),
+        );
+        outputs.insert("expires_at".to_string(), serde_json::to_value(expires_at)?);
+        if !approvers.is_empty() {
+            let mut urls = serde_json::Map::new();
+            for approver in &approvers {
+                let link = approval::sign_link(&signing_key, nonce, approver, expires_at)?;
+                urls.insert(
+                    approver.clone(),
+                    Value::String(format!("/approvals/{}?link={}", token, link)),
+                );
+            }
+            outputs.insert("approver_urls".to_string(), Value::Object(urls));
+        }
+
+        self.storage
+            .save_step(&crate::model::StepRun {
+                id: step_run_id,
+                run_id,
+                step_name: step.id.clone(),
+                status: crate::model::StepStatus::Waiting,
+                started_at: requested_at,
+                ended_at: None,
+                error: None,
+                error_kind: None,
+                inputs: None,
+                outputs: Some(outputs),
+                progress: None,
+            })
+            .await?;
+
+        Err(BeemFlowError::AwaitEventPause(format!(
+            "step '{}' is waiting for approval",
+            step.id
+        )))
+    }
+
     /// Evaluate a conditional expression
     pub async fn evaluate_condition(
         &self,
         condition: &str,
         step_ctx: &StepContext,
     ) -> Result<bool> {
+        self.condition_holds(condition, &self.get_template_data(step_ctx))
+    }
+
+    /// Evaluate a `{{ expression }}` condition against `data` for truthiness
+    fn condition_holds(&self, condition: &str, data: &HashMap<String, Value>) -> Result<bool> {
         // Condition must be in {{ }} format
         let trimmed = condition.trim();
         if !trimmed.starts_with("{{") || !trimmed.ends_with("}}") {
@@ -741,10 +1846,7 @@ impl Executor {
         }
 
         // Use templater's evaluate_expression to get the actual value
-        let template_data = self.get_template_data(step_ctx);
-        let value = self
-            .templater
-            .evaluate_expression(condition, &template_data)?;
+        let value = self.templater.evaluate_expression(condition, data)?;
 
         // Check if it's a boolean
         if let Some(b) = value.as_bool() {
@@ -782,24 +1884,60 @@ impl Executor {
         &self,
         step: &Step,
         step_ctx: &StepContext,
-        run_id: Uuid,
+        in_flight: &InFlightStep,
     ) -> Result<()> {
         let outputs = step_ctx
             .get_output(&step.id)
             .and_then(|v| serde_json::from_value::<HashMap<String, Value>>(v).ok());
 
-        let step_run = crate::model::StepRun {
-            id: Uuid::new_v4(),
-            run_id,
-            step_name: step.id.clone(),
-            status: crate::model::StepStatus::Succeeded,
-            started_at: chrono::Utc::now(),
-            ended_at: Some(chrono::Utc::now()),
-            error: None,
+        let events: Vec<PendingEvent> = published_event(step, outputs.as_ref(), &self.event_origin)
+            .into_iter()
+            .collect();
+        let step_run = StepRun {
+            ended_at: Some(self.clock.now()),
+            inputs: step_ctx.get_inputs(&step.id),
             outputs,
+            ..in_flight.record(step, StepStatus::Succeeded)
         };
 
-        self.storage.save_step(&step_run).await?;
+        // A published event is committed with the step that published it
+        if events.is_empty() {
+            self.storage.save_step(&step_run).await?;
+        } else {
+            self.storage
+                .commit(&WriteBatch {
+                    steps: vec![step_run],
+                    events,
+                    ..Default::default()
+                })
+                .await?;
+        }
         Ok(())
     }
+
+    /// Close out a step left in `running` by progress writes
+    ///
+    /// Steps that never reported progress have no record, matching prior behavior,
+    /// unless `always` is set.
+    async fn persist_step_failure(
+        &self,
+        step: &Step,
+        in_flight: &InFlightStep,
+        err: &BeemFlowError,
+        always: bool,
+    ) {
+        if !always && !in_flight.persisted.lock().map(|p| *p).unwrap_or(false) {
+            return;
+        }
+
+        let step_run = StepRun {
+            ended_at: Some(self.clock.now()),
+            error: Some(err.to_string()),
+            error_kind: err.step_kind(),
+            ..in_flight.record(step, StepStatus::Failed)
+        };
+        if let Err(e) = self.storage.save_step(&step_run).await {
+            tracing::error!("Failed to persist failure of step {}: {}", step.id, e);
+        }
+    }
 }
@@ -1,14 +1,28 @@
 use super::*;
-use crate::adapter::{AdapterRegistry, CoreAdapter};
+use crate::adapter::mock::{MockAdapter, MockResponse};
+use crate::adapter::{Adapter, AdapterRegistry, CoreAdapter, ExecutionContext};
 use crate::dsl::Templater;
 use crate::engine::Executor;
-use crate::model::Step;
+use crate::model::{Run, RunStatus, Step, StepStatus};
 use crate::storage::{SqliteStorage, Storage};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 
 async fn setup_executor() -> Executor {
+    setup_executor_with_limit(1000).await
+}
+
+async fn setup_executor_with_limit(max_concurrent_tasks: usize) -> Executor {
+    setup_executor_with_adapters(max_concurrent_tasks, vec![])
+        .await
+        .0
+}
+
+async fn setup_executor_with_adapters(
+    max_concurrent_tasks: usize,
+    extra_adapters: Vec<Arc<dyn Adapter>>,
+) -> (Executor, Arc<dyn Storage>) {
     // Create secrets provider for testing
     let secrets_provider: Arc<dyn crate::secrets::SecretsProvider> =
         Arc::new(crate::secrets::EnvSecretsProvider::new());
@@ -25,6 +39,9 @@ async fn setup_executor() -> Executor {
 
     let adapters = Arc::new(AdapterRegistry::new(registry_manager));
     adapters.register(Arc::new(CoreAdapter::new()));
+    for adapter in extra_adapters {
+        adapters.register(adapter);
+    }
     let templater = Arc::new(Templater::new());
     let storage: Arc<dyn Storage> = Arc::new(
         SqliteStorage::new(":memory:")
@@ -35,15 +52,16 @@ async fn setup_executor() -> Executor {
     let oauth_client =
         crate::auth::create_test_oauth_client(storage.clone(), secrets_provider.clone());
 
-    Executor::new(
+    let executor = Executor::new(
         adapters,
         templater,
-        storage,
+        storage.clone(),
         secrets_provider,
         oauth_client,
         None,
-        1000,
-    )
+        max_concurrent_tasks,
+    );
+    (executor, storage)
 }
 
 #[tokio::test]
@@ -226,6 +244,54 @@ async fn test_foreach_parallel() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_foreach_over_limit_records_waiters() {
+    let executor = setup_executor_with_limit(1).await;
+
+    let mut vars = HashMap::new();
+    vars.insert(
+        "items".to_string(),
+        Value::Array(vec![
+            Value::String("a".to_string()),
+            Value::String("b".to_string()),
+            Value::String("c".to_string()),
+        ]),
+    );
+    let step_ctx = StepContext::new(HashMap::new(), vars, HashMap::new());
+
+    let step = Step {
+        id: "foreach_limited".to_string().into(),
+        foreach: Some("{{ vars.items }}".to_string()),
+        as_: Some("item".to_string()),
+        parallel: Some(true),
+        do_: Some(vec![Step {
+            id: "echo_{{ item_index }}".to_string().into(),
+            use_: Some("core.echo".to_string()),
+            with: Some({
+                let mut map = HashMap::new();
+                map.insert("text".to_string(), Value::String("{{ item }}".to_string()));
+                map
+            }),
+            ..Step::test("default")
+        }]),
+        ..Step::test("default")
+    };
+
+    let waited_before = crate::telemetry::tasks_waited("foreach");
+    executor
+        .execute_foreach_block(&step, &step_ctx, "foreach_limited")
+        .await
+        .unwrap();
+
+    // With one permit, the second and third items had to wait
+    assert!(crate::telemetry::tasks_waited("foreach") - waited_before >= 2.0);
+    assert!(
+        crate::telemetry::get_metrics()
+            .unwrap()
+            .contains("beemflow_executor_tasks_waited_total")
+    );
+}
+
 #[tokio::test]
 async fn test_foreach_empty_list() {
     let executor = setup_executor().await;
@@ -257,6 +323,93 @@ async fn test_foreach_empty_list() {
     assert!(output.is_some());
 }
 
+#[tokio::test]
+async fn test_foreach_exposes_loop_metadata() {
+    let executor = setup_executor().await;
+
+    for parallel in [false, true] {
+        let mut vars = HashMap::new();
+        vars.insert(
+            "items".to_string(),
+            serde_json::json!(["alpha", "beta", "gamma"]),
+        );
+        let step_ctx = StepContext::new(HashMap::new(), vars, HashMap::new());
+
+        let step = Step {
+            id: "foreach_loop".to_string().into(),
+            foreach: Some("{{ vars.items }}".to_string()),
+            as_: Some("item".to_string()),
+            parallel: Some(parallel),
+            do_: Some(vec![Step {
+                id: "echo_{{ loop.index0 }}".to_string().into(),
+                use_: Some("core.echo".to_string()),
+                with: Some(HashMap::from([(
+                    "text".to_string(),
+                    Value::String(
+                        "{{ loop.index }}/{{ loop.length }} {{ loop.item }} \
+                         first={{ loop.first }} last={{ loop.last }}"
+                            .to_string(),
+                    ),
+                )])),
+                ..Step::test("default")
+            }]),
+            ..Step::test("default")
+        };
+
+        executor
+            .execute_foreach_block(&step, &step_ctx, "foreach_loop")
+            .await
+            .unwrap();
+
+        let text = |id: &str| step_ctx.get_output(id).unwrap()["text"].clone();
+        assert_eq!(text("echo_0"), "1/3 alpha first=true last=false");
+        assert_eq!(text("echo_1"), "2/3 beta first=false last=false");
+        assert_eq!(text("echo_2"), "3/3 gamma first=false last=true");
+    }
+}
+
+#[tokio::test]
+async fn test_parallel_foreach_renders_templated_step_ids() {
+    let executor = setup_executor().await;
+    let mut vars = HashMap::new();
+    vars.insert("items".to_string(), serde_json::json!(["alpha", "beta"]));
+    let step_ctx = StepContext::new(HashMap::new(), vars, HashMap::new());
+
+    let echo = |id: &str, text: &str| Step {
+        id: id.to_string().into(),
+        use_: Some("core.echo".to_string()),
+        with: Some(HashMap::from([(
+            "text".to_string(),
+            Value::String(text.to_string()),
+        )])),
+        ..Step::test("default")
+    };
+    let step = Step {
+        id: "foreach_loop".to_string().into(),
+        foreach: Some("{{ vars.items }}".to_string()),
+        as_: Some("item".to_string()),
+        parallel: Some(true),
+        do_: Some(vec![
+            echo("fetch_{{ item }}", "{{ item }}"),
+            // A later step in the iteration reads the earlier one by its rendered ID
+            echo("shout_{{ item }}", "{{ outputs['fetch_' ~ item].text }}!"),
+        ]),
+        ..Step::test("default")
+    };
+
+    executor
+        .execute_foreach_block(&step, &step_ctx, "foreach_loop")
+        .await
+        .unwrap();
+
+    assert_eq!(step_ctx.get_output("fetch_alpha").unwrap()["text"], "alpha");
+    assert_eq!(step_ctx.get_output("shout_beta").unwrap()["text"], "beta!");
+    assert!(step_ctx.get_output("fetch_{{ item }}").is_none());
+    let output = step_ctx.get_output("foreach_loop").unwrap();
+    assert_eq!(output["output"][0]["shout_alpha"]["text"], "alpha!");
+    assert_eq!(output["output"][1]["fetch_beta"]["text"], "beta");
+}
+
 #[tokio::test]
 async fn test_retry_logic() {
     let executor = setup_executor().await;
@@ -269,6 +422,7 @@ async fn test_retry_logic() {
         retry: Some(crate::model::RetrySpec {
             attempts: 3,
             delay_sec: 1,
+            max_delay_sec: None,
         }),
         ..Step::test("default")
     };
@@ -333,3 +487,504 @@ async fn test_wait_seconds() {
 
     assert!(duration.as_secs() >= 1);
 }
+
+/// Adapter that reports progress `reports` times, `interval_ms` apart
+///
+/// With `run_id` set, it also returns the status of its own step as persisted
+/// while it was still running.
+struct ProgressAdapter;
+
+#[async_trait::async_trait]
+impl Adapter for ProgressAdapter {
+    fn id(&self) -> &str {
+        "test.progress"
+    }
+
+    async fn execute(
+        &self,
+        inputs: HashMap<String, Value>,
+        ctx: &ExecutionContext,
+    ) -> crate::Result<HashMap<String, Value>> {
+        let reports = inputs.get("reports").and_then(|v| v.as_u64()).unwrap_or(0);
+        let interval = inputs
+            .get("interval_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+
+        for i in 1..=reports {
+            tokio::time::sleep(std::time::Duration::from_millis(interval)).await;
+            ctx.progress.report(
+                Some(i as f64 / reports as f64 * 100.0),
+                format!("chunk {} of {}", i, reports),
+            );
+        }
+
+        let mut outputs = HashMap::new();
+        if let Some(run_id) = inputs.get("run_id").and_then(|v| v.as_str()) {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            let steps = ctx.storage.get_steps(run_id.parse().unwrap()).await?;
+            outputs.insert("observed".to_string(), serde_json::to_value(&steps)?);
+        }
+        Ok(outputs)
+    }
+
+    fn manifest(&self) -> Option<crate::adapter::ToolManifest> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+fn progress_step(id: &str, with: Value) -> Step {
+    Step {
+        use_: Some("test.progress".to_string()),
+        with: serde_json::from_value(with).unwrap(),
+        ..Step::test(id)
+    }
+}
+
+#[tokio::test]
+async fn test_progress_persisted_on_running_step() {
+    let (executor, storage) =
+        setup_executor_with_adapters(10, vec![Arc::new(ProgressAdapter)]).await;
+
+    let run_id = uuid::Uuid::new_v4();
+    storage
+        .save_run(&Run {
+            id: run_id,
+            flow_name: crate::model::FlowName::new("progress_flow").unwrap(),
+            event: HashMap::new(),
+            vars: HashMap::new(),
+            status: RunStatus::Running,
+            started_at: chrono::Utc::now(),
+            ended_at: None,
+            steps: None,
+            environment: None,
+            correlation_id: None,
+            labels: HashMap::new(),
+            usage: None,
+            tenant_id: None,
+        })
+        .await
+        .unwrap();
+
+    let flow = Flow {
+        steps: vec![progress_step(
+            "download",
+            serde_json::json!({"reports": 2, "interval_ms": 10, "run_id": run_id.to_string()}),
+        )],
+        ..Flow::test("progress_flow")
+    };
+    let step_ctx = StepContext::new(HashMap::new(), HashMap::new(), HashMap::new());
+    executor
+        .execute_steps(&flow, &step_ctx, 0, run_id)
+        .await
+        .unwrap();
+
+    // While running, the step was visible with its latest progress
+    let observed = step_ctx.get_output("download").unwrap()["observed"].clone();
+    let observed: Vec<crate::model::StepRun> = serde_json::from_value(observed).unwrap();
+    assert_eq!(observed.len(), 1);
+    assert_eq!(observed[0].status, StepStatus::Running);
+    assert!(observed[0].progress.is_some());
+
+    // The final record replaces the running one and keeps the last report
+    let steps = storage.get_steps(run_id).await.unwrap();
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].id, observed[0].id);
+    assert_eq!(steps[0].status, StepStatus::Succeeded);
+    let progress = steps[0].progress.as_ref().unwrap();
+    assert_eq!(progress.percent, Some(100.0));
+    assert_eq!(progress.message.as_deref(), Some("chunk 2 of 2"));
+}
+
+#[tokio::test]
+async fn test_timeout_idle_reset_by_progress() {
+    let (executor, _) = setup_executor_with_adapters(10, vec![Arc::new(ProgressAdapter)]).await;
+    let step_ctx = StepContext::new(HashMap::new(), HashMap::new(), HashMap::new());
+
+    // Reports every 400ms for 1.6s: never idle for a full second
+    let step = Step {
+        timeout_idle: Some("1s".to_string()),
+        ..progress_step(
+            "chatty",
+            serde_json::json!({"reports": 4, "interval_ms": 400}),
+        )
+    };
+    executor
+        .execute_single_step(&step, &step_ctx, "chatty")
+        .await
+        .unwrap();
+
+    // Silent for 1.5s: idle timeout fires
+    let step = Step {
+        timeout_idle: Some("1s".to_string()),
+        ..progress_step(
+            "silent",
+            serde_json::json!({"reports": 1, "interval_ms": 1500}),
+        )
+    };
+    let err = executor
+        .execute_single_step(&step, &step_ctx, "silent")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("timeout_idle"), "{}", err);
+
+    // Progress does not extend the total timeout
+    let step = Step {
+        timeout_total: Some("1s".to_string()),
+        ..progress_step(
+            "long",
+            serde_json::json!({"reports": 4, "interval_ms": 400}),
+        )
+    };
+    let err = executor
+        .execute_single_step(&step, &step_ctx, "long")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("timeout_total"), "{}", err);
+}
+
+/// Adapter that writes its `content` input to the step's blob store
+struct BlobWriterAdapter;
+
+#[async_trait::async_trait]
+impl Adapter for BlobWriterAdapter {
+    fn id(&self) -> &str {
+        "test.blob_put"
+    }
+
+    async fn execute(
+        &self,
+        inputs: HashMap<String, Value>,
+        ctx: &ExecutionContext,
+    ) -> crate::Result<HashMap<String, Value>> {
+        let content = inputs.get("content").and_then(|v| v.as_str()).unwrap_or("");
+        let url = ctx
+            .blob_store()
+            .await?
+            .put(content.as_bytes().to_vec(), None, Some("out.txt"))
+            .await?;
+        Ok(HashMap::from([("url".to_string(), Value::String(url))]))
+    }
+
+    fn manifest(&self) -> Option<crate::adapter::ToolManifest> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+fn blob_put_step(id: &str, blob_store: Option<&str>) -> Step {
+    Step {
+        use_: Some("test.blob_put".to_string()),
+        with: Some(HashMap::from([(
+            "content".to_string(),
+            Value::String(id.to_string()),
+        )])),
+        blob_store: blob_store.map(str::to_string),
+        ..Step::test(id)
+    }
+}
+
+/// Save a running run for `blob_flow` so its steps can be recorded
+async fn save_blob_run(storage: &Arc<dyn Storage>) -> uuid::Uuid {
+    let run_id = uuid::Uuid::new_v4();
+    storage
+        .save_run(&Run {
+            id: run_id,
+            flow_name: crate::model::FlowName::new("blob_flow").unwrap(),
+            event: HashMap::new(),
+            vars: HashMap::new(),
+            status: RunStatus::Running,
+            started_at: chrono::Utc::now(),
+            ended_at: None,
+            steps: None,
+            environment: None,
+            correlation_id: None,
+            labels: HashMap::new(),
+            usage: None,
+            tenant_id: None,
+        })
+        .await
+        .unwrap();
+    run_id
+}
+
+/// Contents of the blob files in `dir`
+fn blob_contents(dir: &std::path::Path) -> Vec<String> {
+    let mut contents: Vec<String> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .map(|e| std::fs::read_to_string(e.unwrap().path()).unwrap())
+                .collect()
+        })
+        .unwrap_or_default();
+    contents.sort();
+    contents
+}
+
+#[tokio::test]
+async fn test_steps_write_to_named_blob_stores() {
+    let (executor, storage) =
+        setup_executor_with_adapters(10, vec![Arc::new(BlobWriterAdapter)]).await;
+    let temp = tempfile::tempdir().unwrap();
+    let dir = |name: &str| temp.path().join(name);
+    let filesystem = |name: &str| crate::blob::BlobConfig {
+        driver: Some("filesystem".to_string()),
+        directory: Some(dir(name).to_str().unwrap().to_string()),
+        bucket: None,
+        region: None,
+    };
+    let blob_stores = Arc::new(crate::blob::BlobStores::new(
+        filesystem("default"),
+        HashMap::from([
+            ("public".to_string(), filesystem("public")),
+            ("private".to_string(), filesystem("private")),
+        ]),
+        storage.clone(),
+    ));
+    let executor = executor.with_blob_stores(blob_stores.clone(), Some("public".to_string()));
+    let run_id = save_blob_run(&storage).await;
+
+    let flow = Flow {
+        steps: vec![
+            blob_put_step("flow_default", None),
+            blob_put_step("step_override", Some("private")),
+        ],
+        blob_store: Some("public".to_string()),
+        ..Flow::test("blob_flow")
+    };
+    let step_ctx = StepContext::new(HashMap::new(), HashMap::new(), HashMap::new());
+    executor
+        .execute_steps(&flow, &step_ctx, 0, run_id)
+        .await
+        .unwrap();
+
+    assert_eq!(blob_contents(&dir("public")), vec!["flow_default"]);
+    assert_eq!(blob_contents(&dir("private")), vec!["step_override"]);
+    assert!(blob_contents(&dir("default")).is_empty());
+
+    // Stable URLs resolve through the store that wrote them
+    let url = step_ctx.get_output("step_override").unwrap()["url"].clone();
+    let private = blob_stores.get(Some("private")).await.unwrap();
+    assert_eq!(
+        private.get(url.as_str().unwrap()).await.unwrap(),
+        b"step_override"
+    );
+}
+
+#[tokio::test]
+async fn test_unnamed_blob_store_uses_default() {
+    let (executor, storage) =
+        setup_executor_with_adapters(10, vec![Arc::new(BlobWriterAdapter)]).await;
+    let temp = tempfile::tempdir().unwrap();
+    let default_dir = temp.path().join("default");
+    let blob_stores = Arc::new(crate::blob::BlobStores::new(
+        crate::blob::BlobConfig {
+            driver: Some("filesystem".to_string()),
+            directory: Some(default_dir.to_str().unwrap().to_string()),
+            bucket: None,
+            region: None,
+        },
+        HashMap::new(),
+        storage.clone(),
+    ));
+    let executor = executor.with_blob_stores(blob_stores, None);
+    let run_id = save_blob_run(&storage).await;
+
+    let flow = Flow {
+        steps: vec![blob_put_step("plain", None)],
+        ..Flow::test("blob_flow")
+    };
+    let step_ctx = StepContext::new(HashMap::new(), HashMap::new(), HashMap::new());
+    executor
+        .execute_steps(&flow, &step_ctx, 0, run_id)
+        .await
+        .unwrap();
+    assert_eq!(blob_contents(&default_dir), vec!["plain"]);
+
+    // Naming a store that isn't configured fails the step
+    let flow = Flow {
+        steps: vec![blob_put_step("missing", Some("archive"))],
+        ..Flow::test("blob_flow")
+    };
+    let err = executor
+        .execute_steps(&flow, &step_ctx, 0, run_id)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown blob store 'archive'"));
+}
+
+/// Job status tool reporting `running` for its first `running_calls` calls, then `done`
+fn job_status(running_calls: usize) -> Arc<MockAdapter> {
+    let mut mock = MockAdapter::new("test.job_status");
+    for _ in 0..running_calls {
+        mock = mock.then(MockResponse::ok(serde_json::json!({"state": "running"})));
+    }
+    Arc::new(mock.otherwise(MockResponse::ok(serde_json::json!({"state": "done"}))))
+}
+
+/// Job status tool that never reports the job done
+fn stuck_job_status() -> Arc<MockAdapter> {
+    Arc::new(
+        MockAdapter::new("test.job_status")
+            .otherwise(MockResponse::ok(serde_json::json!({"state": "running"}))),
+    )
+}
+
+async fn setup_poll_executor(job_status: &Arc<MockAdapter>) -> Executor {
+    setup_executor_with_adapters(10, vec![job_status.clone()])
+        .await
+        .0
+}
+
+fn poll_step(id: &str, with: Value) -> Step {
+    Step {
+        use_: Some("core.poll".to_string()),
+        with: serde_json::from_value(with).unwrap(),
+        ..Step::test(id)
+    }
+}
+
+#[tokio::test]
+async fn test_poll_until_condition_matches() {
+    let job_status = job_status(2);
+    let executor = setup_poll_executor(&job_status).await;
+    let mut vars = HashMap::new();
+    vars.insert("job_id".to_string(), Value::from("export-7"));
+    let step_ctx = StepContext::new(HashMap::new(), vars, HashMap::new());
+
+    // The inner `with` is rendered once; `until` sees each response
+    let step = poll_step(
+        "wait_export",
+        serde_json::json!({
+            "use": "test.job_status",
+            "with": {"job": "{{ vars.job_id }}"},
+            "until": "{{ response.state == 'done' }}",
+            "interval": 0.01,
+            "backoff": 2
+        }),
+    );
+    executor
+        .execute_single_step(&step, &step_ctx, "wait_export")
+        .await
+        .unwrap();
+
+    let output = step_ctx.get_output("wait_export").unwrap();
+    assert_eq!(output["attempts"], 3);
+    assert_eq!(output["response"]["state"], "done");
+    let calls = job_status.calls();
+    assert_eq!(calls.len(), 3);
+    assert!(calls.iter().all(|inputs| inputs["job"] == "export-7"));
+}
+
+#[tokio::test]
+async fn test_poll_fails_with_last_response_when_exhausted() {
+    let job_status = stuck_job_status();
+    let executor = setup_poll_executor(&job_status).await;
+    let step_ctx = StepContext::new(HashMap::new(), HashMap::new(), HashMap::new());
+
+    let step = poll_step(
+        "wait_export",
+        serde_json::json!({
+            "use": "test.job_status",
+            "until": "{{ response.state == 'done' }}",
+            "interval": 0.01,
+            "max_attempts": 2
+        }),
+    );
+    let err = executor
+        .execute_single_step(&step, &step_ctx, "wait_export")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("not met after 2 attempts"), "{}", err);
+    assert!(err.contains("max_attempts 2"), "{}", err);
+    assert!(err.contains(r#""state":"running""#), "{}", err);
+    assert_eq!(job_status.call_count(), 2);
+
+    // max_duration stops before an attempt would start past the deadline
+    let step = poll_step(
+        "wait_export",
+        serde_json::json!({
+            "use": "test.job_status",
+            "until": "{{ response.state == 'done' }}",
+            "interval": 0.05,
+            "max_duration": 0.12
+        }),
+    );
+    let started = std::time::Instant::now();
+    let err = executor
+        .execute_single_step(&step, &step_ctx, "wait_export")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("max_duration"), "{}", err);
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_poll_cancelled_by_timeout_total() {
+    let executor = setup_poll_executor(&stuck_job_status()).await;
+    let step_ctx = StepContext::new(HashMap::new(), HashMap::new(), HashMap::new());
+
+    let step = Step {
+        timeout_total: Some("1s".to_string()),
+        ..poll_step(
+            "wait_export",
+            serde_json::json!({
+                "use": "test.job_status",
+                    "until": "{{ response.state == 'done' }}",
+                "interval": "30s"
+            }),
+        )
+    };
+    let started = std::time::Instant::now();
+    let err = executor
+        .execute_single_step(&step, &step_ctx, "wait_export")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("timeout_total"), "{}", err);
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_poll_rejects_invalid_specs() {
+    let executor = setup_poll_executor(&job_status(0)).await;
+    let step_ctx = StepContext::new(HashMap::new(), HashMap::new(), HashMap::new());
+
+    for (with, expected) in [
+        (
+            serde_json::json!({"until": "{{ response.done }}"}),
+            "requires 'use'",
+        ),
+        (
+            serde_json::json!({"use": "test.job_status"}),
+            "requires an 'until' condition",
+        ),
+        (
+            serde_json::json!({"use": "core.approval", "until": "{{ response.done }}"}),
+            "cannot poll 'core.approval'",
+        ),
+        (
+            serde_json::json!({"use": "test.job_status", "until": "{{ response.done }}", "interval": "soon"}),
+            "invalid core.poll interval",
+        ),
+        (
+            serde_json::json!({"use": "test.job_status", "until": "{{ response.done }}", "backoff": 0.5}),
+            "invalid core.poll backoff",
+        ),
+    ] {
+        let err = executor
+            .execute_single_step(&poll_step("bad", with), &step_ctx, "bad")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(expected), "{}", err);
+    }
+}
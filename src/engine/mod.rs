@@ -3,11 +3,17 @@
 //! The engine handles step execution, parallel processing, loops, conditionals,
 //! state management, and durable waits.
 
+pub mod approval;
 pub mod context;
+pub mod delivery;
 pub mod executor;
+pub mod poll;
+pub mod queue;
+pub mod resume;
 
-use crate::adapter::AdapterRegistry;
+use crate::adapter::{AdapterRegistry, UsageMeter};
 use crate::dsl::Templater;
+use crate::model::{FlowName, McpServerConfig};
 use crate::storage::Storage;
 use crate::{BeemFlowError, Flow, Result};
 use std::collections::HashMap;
@@ -24,6 +30,26 @@ pub struct ExecutionResult {
     pub outputs: HashMap<String, serde_json::Value>,
 }
 
+/// Result of re-executing steps of an existing run
+#[derive(Debug, Clone)]
+pub struct RerunResult {
+    pub run_id: Uuid,
+    /// Names of the re-executed steps
+    pub steps: Vec<String>,
+    pub outputs: HashMap<String, serde_json::Value>,
+}
+
+/// A tool referenced by a flow that nothing can resolve
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UnresolvedTool {
+    /// The `use:` value
+    pub tool: String,
+    /// Steps using it
+    pub steps: Vec<String>,
+    /// Closest known tool names
+    pub suggestions: Vec<String>,
+}
+
 /// Paused run information for await_event
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PausedRun {
@@ -31,8 +57,97 @@ pub struct PausedRun {
     pub step_idx: usize,
     pub context: StepContext,
     pub outputs: HashMap<String, serde_json::Value>,
+    /// Key the run is stored under: its token's hash (see [`resume::storage_key`])
     pub token: String,
     pub run_id: Uuid,
+    /// Set when the run is paused at a `core.approval` step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<approval::ApprovalRequest>,
+    /// Correlation ID of the run, carried over to its events after resuming
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    /// Tenant of the run, whose credentials its steps keep using after resuming
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+/// Step context of an in-progress run, saved after each top-level step when
+/// `checkpointRuns` is set
+///
+/// Secrets are left out and collected again when the run is recovered.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RunCheckpoint {
+    /// The flow as it runs, with any environment applied
+    pub flow: Flow,
+    pub run_id: Uuid,
+    /// Top-level steps already executed, in execution order
+    pub completed_steps: Vec<String>,
+    pub event: HashMap<String, serde_json::Value>,
+    pub vars: HashMap<String, serde_json::Value>,
+    pub outputs: HashMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    /// Step the run stops after (see [`Engine::execute_until`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_after: Option<String>,
+}
+
+/// What [`Engine::collect_orphaned_paused_runs`] removed
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct PausedRunGc {
+    /// Runs paused on a flow that no longer exists
+    pub paused_runs: Vec<Uuid>,
+    /// Tokens of paused entries whose run is gone or unreadable
+    pub subscriptions: Vec<String>,
+}
+
+/// How a new run's ID is chosen, which decides when a start is a duplicate
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum RunIdStrategy {
+    /// Hash of flow name, event and the current minute: identical events within a
+    /// minute are duplicates
+    #[default]
+    Deterministic,
+    /// Fresh random ID: never a duplicate
+    Random,
+    /// Hash of flow name and the caller's idempotency key: a key is used at most once
+    Client,
+}
+
+/// Per-run options for [`Engine::execute_with`] and [`Engine::start_with`]
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Environment whose vars overlay the flow's (default: config `defaultEnvironment`)
+    pub environment: Option<String>,
+    /// Run ID strategy (default: config `runIdStrategy`, or `client` when an
+    /// idempotency key is given)
+    pub run_id_strategy: Option<RunIdStrategy>,
+    /// Caller-supplied key for the `client` strategy
+    pub idempotency_key: Option<String>,
+    /// Correlation ID to record on the run and its events (default: the current
+    /// request's, else the run ID)
+    pub correlation_id: Option<String>,
+    /// Labels recorded on the run, merged over the flow's `labels`
+    pub labels: HashMap<String, String>,
+    /// Top-level step after which the run stops (see [`Engine::execute_until`])
+    pub stop_after: Option<String>,
+    /// Tenant the run belongs to (default: the current request's, see
+    /// [`crate::auth::tenant`])
+    pub tenant_id: Option<String>,
+    /// Place in the run queue when `limits.maxConcurrentRuns` is reached; higher
+    /// runs first (default: 0)
+    pub priority: i32,
 }
 
 /// BeemFlow execution engine
@@ -47,7 +162,16 @@ pub struct Engine {
     secrets_provider: Arc<dyn crate::secrets::SecretsProvider>,
     config: Arc<crate::config::Config>,
     oauth_client: Arc<crate::auth::OAuthClientManager>,
+    event_bus: Arc<dyn crate::event::EventBus>,
+    outbox: Arc<crate::event::OutboxDispatcher>,
+    deliveries: Arc<delivery::DeliveryWorker>,
+    blob_stores: Arc<crate::blob::BlobStores>,
     max_concurrent_tasks: usize,
+    /// Set while draining: new runs are rejected, in-flight ones finish
+    draining: std::sync::atomic::AtomicBool,
+    /// Admits new runs when `limits.maxConcurrentRuns` is set
+    run_queue: Option<Arc<queue::RunQueue>>,
+    clock: Arc<dyn crate::clock::Clock>,
 }
 
 impl Engine {
@@ -65,8 +189,31 @@ impl Engine {
         secrets_provider: Arc<dyn crate::secrets::SecretsProvider>,
         config: Arc<crate::config::Config>,
         oauth_client: Arc<crate::auth::OAuthClientManager>,
+        event_bus: Arc<dyn crate::event::EventBus>,
         max_concurrent_tasks: usize,
     ) -> Self {
+        let outbox = Arc::new(crate::event::OutboxDispatcher::new(
+            storage.clone(),
+            event_bus.clone(),
+        ));
+        let clock: Arc<dyn crate::clock::Clock> = Arc::new(crate::clock::SystemClock);
+        let deliveries = Arc::new(delivery::DeliveryWorker::new(
+            crate::adapter::ExecutionContext::new(
+                storage.clone(),
+                secrets_provider.clone(),
+                oauth_client.clone(),
+            )
+            .with_rate_limiter(adapters.rate_limiter().clone()),
+            clock.clone(),
+        ));
+        let blob_stores = Arc::new(crate::blob::BlobStores::from_config(
+            &config,
+            storage.clone(),
+        ));
+        let limits = config.get_limits();
+        let run_queue = limits
+            .max_concurrent_runs
+            .map(|max| Arc::new(queue::RunQueue::new(max, limits.run_queue_size)));
         Self {
             adapters,
             mcp_adapter,
@@ -75,7 +222,88 @@ impl Engine {
             secrets_provider,
             config,
             oauth_client,
+            event_bus,
+            outbox,
+            deliveries,
+            blob_stores,
             max_concurrent_tasks,
+            draining: std::sync::atomic::AtomicBool::new(false),
+            run_queue,
+            clock,
+        }
+    }
+
+    /// Read the time from `clock` instead of the system clock
+    ///
+    /// Run and step timestamps, deterministic run ID windows, approval
+    /// expiry and when queued deliveries fall due all follow it.
+    pub fn with_clock(mut self, clock: Arc<dyn crate::clock::Clock>) -> Self {
+        self.deliveries = Arc::new(self.deliveries.with_clock(clock.clone()));
+        self.clock = clock;
+        self
+    }
+
+    /// Start or stop draining; returns whether the engine was draining before
+    ///
+    /// While draining, [`Engine::execute_with`] rejects new runs with
+    /// [`BeemFlowError::Draining`]. Runs already executing, and paused runs
+    /// resuming, are not affected.
+    pub fn set_draining(&self, draining: bool) -> bool {
+        self.draining
+            .swap(draining, std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Whether new runs are being rejected
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Queue admitting new runs, when `limits.maxConcurrentRuns` is set
+    pub fn run_queue(&self) -> Option<&Arc<queue::RunQueue>> {
+        self.run_queue.as_ref()
+    }
+
+    /// Event bus that outbox events are published to
+    pub fn event_bus(&self) -> &Arc<dyn crate::event::EventBus> {
+        &self.event_bus
+    }
+
+    /// Dispatcher publishing this engine's outbox events
+    pub fn outbox(&self) -> &Arc<crate::event::OutboxDispatcher> {
+        &self.outbox
+    }
+
+    /// Worker retrying outbound deliveries queued by `core.deliver`
+    pub fn deliveries(&self) -> &Arc<delivery::DeliveryWorker> {
+        &self.deliveries
+    }
+
+    /// Adapter routing `mcp://` tool calls to downstream MCP servers
+    pub fn mcp_adapter(&self) -> &Arc<crate::adapter::McpAdapter> {
+        &self.mcp_adapter
+    }
+
+    /// Reload the tools built from registry entries
+    ///
+    /// Cached tools are dropped and the default registry's tools loaded again;
+    /// other tools are loaded from the registries on next use.
+    pub async fn reload_registry_tools(&self) -> usize {
+        let unloaded = self.adapters.unload_registry_tools();
+        Self::load_default_registry_tools(
+            &self.adapters,
+            &self.mcp_adapter,
+            &self.secrets_provider,
+        )
+        .await;
+        unloaded
+    }
+
+    /// Publish queued outbox events now rather than at the next background sweep
+    ///
+    /// Failures are logged; the events stay queued.
+    async fn dispatch_outbox(&self) {
+        if let Err(e) = self.outbox.dispatch_pending().await {
+            tracing::warn!("Failed to dispatch outbox events: {}", e);
         }
     }
 
@@ -110,13 +338,21 @@ impl Engine {
                                 endpoint: entry.endpoint,
                                 method: entry.method,
                                 headers: entry.headers,
+                                body_format: entry.body_format.unwrap_or_default(),
+                                rate_limit: entry.rate_limit,
+                                stream: entry
+                                    .stream
+                                    .as_ref()
+                                    .and_then(crate::adapter::StreamSetting::config),
                             };
 
                             // Register as HTTP adapter
-                            adapters.register(Arc::new(crate::adapter::HttpAdapter::new(
-                                entry.name.clone(),
-                                Some(manifest),
-                            )));
+                            adapters.register_registry_tool(Arc::new(
+                                crate::adapter::HttpAdapter::new(
+                                    entry.name.clone(),
+                                    Some(manifest),
+                                ),
+                            ));
 
                             tracing::debug!("Registered tool: {}", entry.name);
                         }
@@ -177,11 +413,88 @@ impl Engine {
     }
 
     /// Execute a flow with event data
+    ///
+    /// Runs in the configured `defaultEnvironment` when the flow defines it.
     pub async fn execute(
         &self,
         flow: &Flow,
         event: HashMap<String, serde_json::Value>,
     ) -> Result<ExecutionResult> {
+        self.execute_with(flow, event, &RunOptions::default()).await
+    }
+
+    /// Execute a flow up to and including the top-level step `target_step_id`
+    ///
+    /// Steps run in dependency order as usual, and the run stops once the target
+    /// has run. The run is recorded as succeeded with the outputs of the steps
+    /// executed so far; `on_success` hooks don't run for it. Useful for debugging
+    /// and stepwise development of a flow.
+    pub async fn execute_until(
+        &self,
+        flow: &Flow,
+        event: HashMap<String, serde_json::Value>,
+        target_step_id: &str,
+    ) -> Result<ExecutionResult> {
+        let options = RunOptions {
+            stop_after: Some(target_step_id.to_string()),
+            ..Default::default()
+        };
+        self.execute_with(flow, event, &options).await
+    }
+
+    /// Execute a flow with explicit run options
+    ///
+    /// Without an explicit environment, the configured `defaultEnvironment` is
+    /// used if the flow knows it. An explicitly requested environment the flow
+    /// doesn't know is a validation error. Stage-keyed vars resolve for the
+    /// environment the run starts in (their `default` without one). Fields
+    /// the event leaves out are taken from the trigger's `defaults`.
+    pub async fn execute_with(
+        &self,
+        flow: &Flow,
+        event: HashMap<String, serde_json::Value>,
+        options: &RunOptions,
+    ) -> Result<ExecutionResult> {
+        if self.is_draining() {
+            return Err(BeemFlowError::Draining);
+        }
+
+        let environment = options.environment.as_deref().or_else(|| {
+            self.config
+                .default_environment
+                .as_deref()
+                .filter(|name| flow.has_environment(name))
+        });
+        let flow = match environment {
+            Some(name) => flow.in_environment(name)?,
+            None => {
+                let mut flow = flow.clone();
+                flow.resolve_stage_vars(None)?;
+                flow
+            }
+        };
+        let environment = environment.map(str::to_string);
+        let flow = &flow;
+
+        // Trigger defaults fill fields the event leaves out, before validation
+        let mut event = event;
+        if let Some(trigger) = &flow.on {
+            for (key, value) in trigger.event_defaults() {
+                event.entry(key).or_insert(value);
+            }
+        }
+
+        // Reject events the flow doesn't accept before anything runs
+        crate::dsl::Validator::validate_event(flow, &event)?;
+        if let Some(ref target) = options.stop_after
+            && !flow.steps.iter().any(|s| s.id.as_str() == target)
+        {
+            return Err(BeemFlowError::not_found(
+                "Step",
+                format!("{} in flow {}", target, flow.name),
+            ));
+        }
+
         if flow.steps.is_empty() {
             return Ok(ExecutionResult {
                 run_id: Uuid::nil(),
@@ -189,21 +502,24 @@ impl Engine {
             });
         }
 
-        // Configure MCP servers if present in flow
-        if let Some(ref mcp_servers) = flow.mcp_servers {
-            for (name, config) in mcp_servers {
-                self.mcp_adapter
-                    .register_server(name.clone(), config.clone());
-            }
-        }
+        // Wait for a slot behind higher-priority runs (held until the run returns)
+        let _slot = match self.run_queue {
+            Some(ref run_queue) => Some(run_queue.acquire(options.priority).await?),
+            None => None,
+        };
 
         // Setup execution context (returns error if duplicate run detected)
-        let (step_ctx, run_id) = self.setup_execution_context(flow, event.clone()).await?;
+        let (step_ctx, mut run) = self
+            .setup_execution_context(flow, event, environment, options)
+            .await?;
+        let run_id = run.id;
 
         // Fetch previous run data for template access
-        let runs_data = self.fetch_previous_run_data(&flow.name, run_id).await;
+        let runs_data = self.fetch_previous_run_data(flow, run_id).await;
 
         // Create executor
+        let usage = UsageMeter::default();
+        let started = std::time::Instant::now();
         let executor = Executor::new(
             self.adapters.clone(),
             self.templater.clone(),
@@ -212,13 +528,29 @@ impl Engine {
             self.oauth_client.clone(),
             runs_data,
             self.max_concurrent_tasks,
-        );
+        )
+        .with_strict_params(flow.strict_params.unwrap_or(true))
+        .with_blob_stores(self.blob_stores.clone(), flow.blob_store.clone())
+        .with_event_origin(
+            run_event_source(run_id, &flow.name),
+            run.correlation_id.clone(),
+        )
+        .with_tenant_id(run.tenant_id.clone())
+        .with_clock(self.clock.clone())
+        .with_event_bus(self.event_bus.clone())
+        .with_usage(usage.clone())
+        .with_stop_after(options.stop_after.clone())
+        .with_output_scan(self.output_scanner(flow, &step_ctx))
+        .with_mcp_servers(self.flow_mcp_servers(flow, &step_ctx)?)
+        .with_checkpoints(self.checkpoint_runs());
 
         // Execute steps
         let result = executor.execute_steps(flow, &step_ctx, 0, run_id).await;
+        run.usage = Some(execution_usage(&flow.name, &usage, started));
 
         // Finalize execution and return result with run_id
-        let outputs = self.finalize_execution(flow, event, result, run_id).await?;
+        let partial = options.stop_after.is_some();
+        let outputs = self.finalize_execution(flow, result, run, partial).await?;
 
         Ok(ExecutionResult { run_id, outputs })
     }
@@ -244,9 +576,23 @@ impl Engine {
     /// - Execution errors
     pub async fn start(
         &self,
-        flow_name: &str,
+        flow_name: &FlowName,
+        event: HashMap<String, serde_json::Value>,
+        is_draft: bool,
+    ) -> Result<ExecutionResult> {
+        self.start_with(flow_name, event, is_draft, &RunOptions::default())
+            .await
+    }
+
+    /// Start a flow execution by name with explicit run options
+    ///
+    /// Like [`Engine::start`], see [`Engine::execute_with`].
+    pub async fn start_with(
+        &self,
+        flow_name: &FlowName,
         event: HashMap<String, serde_json::Value>,
         is_draft: bool,
+        options: &RunOptions,
     ) -> Result<ExecutionResult> {
         // Load flow content
         let content = self.load_flow_content(flow_name, is_draft).await?;
@@ -255,13 +601,216 @@ impl Engine {
         let flow = crate::dsl::parse_string(&content, None)?;
 
         // Execute flow (delegate to existing low-level method)
-        self.execute(&flow, event).await
+        self.execute_with(&flow, event, options).await
+    }
+
+    /// Statically check step inputs against tool manifests
+    ///
+    /// Catches unknown keys and literal type mismatches before a run starts.
+    /// Templated values are skipped here and validated at runtime once rendered.
+    pub async fn check_tool_params(&self, flow: &Flow) -> Result<()> {
+        let flow_strict = flow.strict_params.unwrap_or(true);
+        let mut pending: Vec<(&crate::Step, bool)> = flow
+            .steps
+            .iter()
+            .chain(flow.catch.iter().flatten())
+            .chain(flow.on_success.iter().flatten())
+            .chain(flow.on_failure.iter().flatten())
+            .map(|step| (step, flow_strict))
+            .collect();
+
+        while let Some((step, inherited)) = pending.pop() {
+            let strict = step.strict_params.unwrap_or(inherited);
+
+            if strict
+                && let Some(ref use_) = step.use_
+                && let Some(adapter) = self.adapters.get_or_load(use_).await
+                && let Some(manifest) = adapter.manifest()
+            {
+                manifest
+                    .check_static_inputs(&step.with.clone().unwrap_or_default())
+                    .map_err(|e| match e {
+                        BeemFlowError::Validation(msg) => {
+                            BeemFlowError::validation(format!("step '{}': {}", step.id, msg))
+                        }
+                        other => other,
+                    })?;
+            }
+
+            for child in step.steps.iter().chain(step.do_.iter()).flatten() {
+                pending.push((child, strict));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find `use:` values that no built-in adapter, MCP server or registry resolves
+    ///
+    /// `mcp://server/tool` resolves when the server is configured by the flow, the
+    /// config file or a registry; the tool itself is only known once the server
+    /// runs. Each unresolved tool is reported once, with the steps using it and the
+    /// closest known names.
+    pub async fn find_unresolved_tools(&self, flow: &Flow) -> Vec<UnresolvedTool> {
+        let mut uses: Vec<(&str, &str)> = Vec::new();
+        let mut pending: Vec<&crate::Step> = flow
+            .steps
+            .iter()
+            .chain(flow.catch.iter().flatten())
+            .chain(flow.on_success.iter().flatten())
+            .chain(flow.on_failure.iter().flatten())
+            .collect();
+        while let Some(step) = pending.pop() {
+            if let Some(ref use_) = step.use_ {
+                uses.push((use_, &step.id));
+            }
+            pending.extend(step.steps.iter().chain(step.do_.iter()).flatten());
+        }
+
+        let registry_entries = self
+            .adapters
+            .registry_manager()
+            .list_all_servers()
+            .await
+            .unwrap_or_default();
+        let mut mcp_servers: Vec<String> = self.mcp_adapter.server_names();
+        mcp_servers.extend(
+            flow.mcp_servers
+                .iter()
+                .flatten()
+                .map(|(name, _)| name.clone()),
+        );
+        mcp_servers.extend(
+            self.config
+                .mcp_servers
+                .iter()
+                .flatten()
+                .map(|(name, _)| name.clone()),
+        );
+        mcp_servers.extend(
+            registry_entries
+                .iter()
+                .filter(|e| e.entry_type == "mcp_server")
+                .map(|e| e.name.clone()),
+        );
+
+        let mut unresolved: Vec<UnresolvedTool> = Vec::new();
+        for (tool, step) in uses {
+            if let Some(existing) = unresolved.iter_mut().find(|u| u.tool == tool) {
+                existing.steps.push(step.to_string());
+                continue;
+            }
+
+            let suggestions =
+                if let Some(rest) = tool.strip_prefix(crate::constants::ADAPTER_PREFIX_MCP) {
+                    let (server, name) = rest.split_once('/').unwrap_or((rest, ""));
+                    if mcp_servers.iter().any(|s| s == server)
+                        && !name.is_empty()
+                        && !name.contains('/')
+                    {
+                        continue;
+                    }
+                    closest_names(server, mcp_servers.iter().cloned())
+                        .into_iter()
+                        .map(|s| format!("{}{}/{}", crate::constants::ADAPTER_PREFIX_MCP, s, name))
+                        .collect()
+                } else {
+                    let resolved = if tool.starts_with(crate::constants::ADAPTER_PREFIX_CORE) {
+                        crate::constants::CORE_TOOLS.contains(&tool)
+                    } else {
+                        self.adapters.get_or_load(tool).await.is_some()
+                    };
+                    if resolved {
+                        continue;
+                    }
+                    let known = crate::constants::CORE_TOOLS
+                        .iter()
+                        .map(|t| t.to_string())
+                        .chain(
+                            self.adapters
+                                .all()
+                                .into_iter()
+                                .map(|a| a.id().to_string())
+                                .filter(|id| {
+                                    id != crate::constants::ADAPTER_ID_CORE
+                                        && id != crate::constants::ADAPTER_ID_MCP
+                                }),
+                        )
+                        .chain(
+                            registry_entries
+                                .iter()
+                                .filter(|e| e.entry_type == "tool")
+                                .map(|e| e.name.clone()),
+                        );
+                    closest_names(tool, known)
+                };
+
+            unresolved.push(UnresolvedTool {
+                tool: tool.to_string(),
+                steps: vec![step.to_string()],
+                suggestions,
+            });
+        }
+
+        for u in &mut unresolved {
+            u.steps.sort();
+        }
+        unresolved.sort_by(|a, b| a.tool.cmp(&b.tool));
+        unresolved
+    }
+
+    /// Fail when the flow uses tools that cannot be resolved
+    ///
+    /// See [`Engine::find_unresolved_tools`].
+    pub async fn check_tools_resolve(&self, flow: &Flow) -> Result<()> {
+        let unresolved = self.find_unresolved_tools(flow).await;
+        if unresolved.is_empty() {
+            return Ok(());
+        }
+
+        let lines: Vec<String> = unresolved
+            .iter()
+            .map(|u| {
+                let mut line = format!("{} (step '{}')", u.tool, u.steps.join("', '"));
+                if !u.suggestions.is_empty() {
+                    line.push_str(&format!("; did you mean {}?", u.suggestions.join(", ")));
+                }
+                line
+            })
+            .collect();
+        Err(BeemFlowError::validation(format!(
+            "Flow '{}' uses tools that cannot be resolved:\n  - {}",
+            flow.name,
+            lines.join("\n  - ")
+        )))
     }
 
     /// Load flow content from storage or filesystem
     ///
     /// Helper method that encapsulates the draft vs. deployed logic.
-    async fn load_flow_content(&self, flow_name: &str, is_draft: bool) -> Result<String> {
+    async fn load_flow_content(&self, flow_name: &FlowName, is_draft: bool) -> Result<String> {
+        // A tenant runs only the flows it owns (see `crate::auth::tenant`)
+        if let Some(tenant_id) = crate::auth::current_tenant() {
+            if is_draft {
+                if self.storage.get_flow_tenant(flow_name).await?.as_deref()
+                    != Some(tenant_id.as_str())
+                {
+                    return Err(crate::BeemFlowError::not_found(
+                        "Flow",
+                        format!("{} (filesystem)", flow_name),
+                    ));
+                }
+            } else {
+                return self
+                    .storage
+                    .get_flow_for_tenant(flow_name, &tenant_id)
+                    .await?
+                    .ok_or_else(|| {
+                        crate::BeemFlowError::not_found("Deployed flow", flow_name.as_str())
+                    });
+            }
+        }
+
         if is_draft {
             // Draft mode: load from filesystem
             let flows_dir = crate::config::get_flows_dir(&self.config);
@@ -300,29 +849,236 @@ impl Engine {
     }
 
     /// Resume a paused run
+    ///
+    /// `token` is the token the run paused with, or a signed token from
+    /// `core.resume_token`, which is verified before storage is touched and only
+    /// resumes the run it was issued for.
     pub async fn resume(
         &self,
         token: &str,
         resume_event: HashMap<String, serde_json::Value>,
     ) -> Result<()> {
-        tracing::debug!(
-            "Resume called for token {} with event: {:?}",
-            token,
-            resume_event
-        );
+        let opened = self.open_resume_token(token).await?;
+        tracing::debug!("Resume called with event: {:?}", resume_event);
 
         // Atomically fetch and delete paused run from storage
+        let (key, paused_json) = self
+            .take_paused_run(&opened.token)
+            .await?
+            .ok_or_else(|| BeemFlowError::config("No paused run found for token"))?;
+
+        // Deserialize paused run from JSON
+        let paused: PausedRun = serde_json::from_value(paused_json.clone())?;
+        if let Some(run_id) = opened.run_id
+            && run_id != paused.run_id
+        {
+            // Not this run's token: put the paused run back untouched
+            self.storage
+                .save_paused_run(&key, &paused_source(&key, &paused), paused_json)
+                .await?;
+            return Err(BeemFlowError::validation(
+                "Resume token was not issued for this run",
+            ));
+        }
+
+        self.continue_paused_run(paused, resume_event, None).await
+    }
+
+    /// Resume the paused run stored under `key` (see [`resume::storage_key`])
+    ///
+    /// For callers that found the paused run in storage, like webhook matching.
+    pub async fn resume_stored(
+        &self,
+        key: &str,
+        resume_event: HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
         let paused_json = self
             .storage
-            .fetch_and_delete_paused_run(token)
+            .fetch_and_delete_paused_run(key)
             .await?
-            .ok_or_else(|| {
-                crate::BeemFlowError::config(format!("No paused run found for token: {}", token))
-            })?;
+            .ok_or_else(|| BeemFlowError::config("No paused run found for token"))?;
+        let paused: PausedRun = serde_json::from_value(paused_json)?;
 
-        // Deserialize paused run from JSON
+        self.continue_paused_run(paused, resume_event, None).await
+    }
+
+    /// Verify a presented resume token, see [`resume::open`]
+    ///
+    /// With `requireSignedResumeTokens`, plain tokens are refused.
+    pub async fn open_resume_token(&self, token: &str) -> Result<resume::OpenedToken> {
+        let key = resume::signing_key(self.secrets_provider.as_ref()).await;
+        let opened = resume::open(&key, token)?;
+        if opened.run_id.is_none() && self.config.require_signed_resume_tokens() {
+            return Err(BeemFlowError::validation(
+                "Resume token must be signed (requireSignedResumeTokens is on)",
+            ));
+        }
+        Ok(opened)
+    }
+
+    /// Atomically fetch and delete the paused run for `token`, with its key
+    async fn take_paused_run(&self, token: &str) -> Result<Option<(String, serde_json::Value)>> {
+        let key = resume::storage_key(token);
+        let paused = self.storage.fetch_and_delete_paused_run(&key).await?;
+        Ok(paused.map(|paused| (key, paused)))
+    }
+
+    /// Move paused runs stored under plaintext tokens by earlier versions to
+    /// the hash of their token
+    ///
+    /// Returns how many paused runs were moved.
+    pub async fn rehash_paused_runs(&self) -> Result<usize> {
+        let mut moved = 0;
+        for (token, data) in self.storage.load_paused_runs().await? {
+            if resume::is_storage_key(&token) {
+                continue;
+            }
+            let paused: PausedRun = match serde_json::from_value(data) {
+                Ok(paused) => paused,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable paused run: {}", e);
+                    continue;
+                }
+            };
+            // Taken first, so a concurrent resume can't also get it
+            let Some(data) = self.storage.fetch_and_delete_paused_run(&token).await? else {
+                continue;
+            };
+            let key = resume::storage_key(&token);
+            self.storage
+                .save_paused_run(&key, &paused_source(&key, &paused), data)
+                .await?;
+            moved += 1;
+        }
+        Ok(moved)
+    }
+
+    /// Look up a pending approval without consuming its token
+    pub async fn get_approval(&self, token: &str) -> Result<approval::ApprovalRequest> {
+        let key = approval::signing_key(self.secrets_provider.as_ref()).await;
+        approval::verify_token(&key, token)?;
+
+        let mut paused = None;
+        for source in [
+            approval::approval_source(token),
+            approval::legacy_approval_source(token),
+        ] {
+            paused = self
+                .storage
+                .find_paused_runs_by_source(&source)
+                .await?
+                .into_iter()
+                .next();
+            if paused.is_some() {
+                break;
+            }
+        }
+        let paused = paused.ok_or_else(|| BeemFlowError::not_found("Approval", token))?;
+        let paused: PausedRun = serde_json::from_value(paused.1)?;
+
+        let request = paused
+            .approval
+            .ok_or_else(|| BeemFlowError::not_found("Approval", token))?;
+        if self.clock.now() > request.expires_at {
+            return Err(BeemFlowError::validation(format!(
+                "Approval expired at {}",
+                request.expires_at
+            )));
+        }
+
+        Ok(request)
+    }
+
+    /// Get the approver an approver link for the approval `token` was signed for
+    ///
+    /// Fails when the link is forged, expired, or signed for another approval.
+    pub async fn approval_link_approver(&self, token: &str, link: &str) -> Result<String> {
+        let key = approval::signing_key(self.secrets_provider.as_ref()).await;
+        let nonce = approval::verify_token(&key, token)?;
+        approval::verify_link(&key, nonce, link, self.clock.now())
+    }
+
+    /// Record a human decision on a pending approval and resume the run
+    ///
+    /// The token is consumed even if it turns out to be expired, which fails the run.
+    /// Returns the ID of the affected run.
+    pub async fn decide_approval(
+        &self,
+        token: &str,
+        decision: approval::ApprovalDecision,
+    ) -> Result<Uuid> {
+        let key = approval::signing_key(self.secrets_provider.as_ref()).await;
+        let nonce = approval::verify_token(&key, token)?;
+
+        let (_, paused_json) = self
+            .take_paused_run(token)
+            .await?
+            .ok_or_else(|| BeemFlowError::not_found("Approval", token))?;
         let paused: PausedRun = serde_json::from_value(paused_json)?;
+        let request = paused
+            .approval
+            .clone()
+            .ok_or_else(|| BeemFlowError::not_found("Approval", token))?;
+        self.storage.resolve_wait(nonce).await?;
+
+        let run_id = paused.run_id;
+
+        if self.clock.now() > request.expires_at {
+            let message = format!("Approval expired at {}", request.expires_at);
+            self.record_approval_step(
+                &request,
+                crate::model::StepStatus::Failed,
+                Some(message.clone()),
+                None,
+            )
+            .await?;
+            self.update_run_status(run_id, crate::model::RunStatus::Failed, None)
+                .await?;
+            return Err(BeemFlowError::validation(message));
+        }
+
+        let outputs = decision.to_outputs();
+
+        if !decision.approved && request.on_reject == approval::OnReject::Fail {
+            tracing::info!("Approval for run {} rejected", run_id);
+            self.record_approval_step(
+                &request,
+                crate::model::StepStatus::Failed,
+                Some(format!(
+                    "Rejected by {}",
+                    decision.approver.as_deref().unwrap_or("unknown approver")
+                )),
+                Some(outputs),
+            )
+            .await?;
+            self.update_run_status(run_id, crate::model::RunStatus::Failed, None)
+                .await?;
+            return Ok(run_id);
+        }
+
+        self.record_approval_step(
+            &request,
+            crate::model::StepStatus::Succeeded,
+            None,
+            Some(outputs.clone()),
+        )
+        .await?;
+
+        self.continue_paused_run(paused, HashMap::new(), Some(outputs))
+            .await?;
+
+        Ok(run_id)
+    }
 
+    /// Continue executing a paused run after its pausing step
+    ///
+    /// `step_output` becomes the output of the pausing step (e.g. an approval decision).
+    async fn continue_paused_run(
+        &self,
+        paused: PausedRun,
+        resume_event: HashMap<String, serde_json::Value>,
+        step_output: Option<serde_json::Value>,
+    ) -> Result<()> {
         // Merge resume event with existing event data and create new context
         let snapshot = paused.context.snapshot();
         let mut merged_event = snapshot.event;
@@ -334,13 +1090,20 @@ impl Engine {
         for (k, v) in snapshot.outputs {
             updated_ctx.set_output(k, v);
         }
+        if let Some(output) = step_output
+            && let Some(step) = paused.flow.steps.get(paused.step_idx)
+        {
+            updated_ctx.set_output(step.id.to_string(), output);
+        }
 
         // Fetch previous run data for template access
         let runs_data = self
-            .fetch_previous_run_data(&paused.flow.name, paused.run_id)
+            .fetch_previous_run_data(&paused.flow, paused.run_id)
             .await;
 
         // Create executor
+        let usage = UsageMeter::default();
+        let started = std::time::Instant::now();
         let executor = Executor::new(
             self.adapters.clone(),
             self.templater.clone(),
@@ -349,30 +1112,209 @@ impl Engine {
             self.oauth_client.clone(),
             runs_data,
             self.max_concurrent_tasks,
-        );
+        )
+        .with_strict_params(paused.flow.strict_params.unwrap_or(true))
+        .with_blob_stores(self.blob_stores.clone(), paused.flow.blob_store.clone())
+        .with_event_origin(
+            run_event_source(paused.run_id, &paused.flow.name),
+            paused.correlation_id.clone(),
+        )
+        .with_tenant_id(paused.tenant_id.clone())
+        .with_clock(self.clock.clone())
+        .with_event_bus(self.event_bus.clone())
+        .with_usage(usage.clone())
+        .with_output_scan(self.output_scanner(&paused.flow, &updated_ctx))
+        .with_mcp_servers(self.flow_mcp_servers(&paused.flow, &updated_ctx)?)
+        .with_checkpoints(self.checkpoint_runs());
 
         // Continue execution
-        let _outputs = executor
+        let result = executor
             .execute_steps(
                 &paused.flow,
                 &updated_ctx,
                 paused.step_idx + 1,
                 paused.run_id,
             )
-            .await
-            .unwrap_or_else(|_| HashMap::new());
+            .await;
+
+        let status = match &result {
+            Ok(_) => crate::model::RunStatus::Succeeded,
+            Err(BeemFlowError::AwaitEventPause(_)) => crate::model::RunStatus::Waiting,
+            Err(e) => {
+                tracing::error!("Resumed run {} failed: {}", paused.run_id, e);
+                crate::model::RunStatus::Failed
+            }
+        };
+        let usage = execution_usage(&paused.flow.name, &usage, started);
+        self.update_run_status(paused.run_id, status, Some(usage))
+            .await?;
+        self.clear_checkpoint(paused.run_id).await;
 
         // Note: Outputs are tracked in storage via StepContext, not in-memory
         Ok(())
     }
 
+    /// Resume runs interrupted mid-execution from their last checkpoint
+    ///
+    /// Meant to run once at startup with `checkpointRuns` set: every checkpointed
+    /// run still marked running is taken to have lost its executor, so only one
+    /// engine per storage should scan. Checkpoints of runs that have since
+    /// finished, paused or been deleted are dropped. Returns the IDs of the
+    /// runs resumed; their outcome is recorded on the runs.
+    pub async fn recover_checkpointed_runs(&self) -> Result<Vec<Uuid>> {
+        let mut recovered = Vec::new();
+        for (run_id, data) in self.storage.load_run_checkpoints().await? {
+            let run = match self.storage.get_run(run_id).await? {
+                Some(run) if run.status == crate::model::RunStatus::Running => run,
+                _ => {
+                    self.storage.delete_run_checkpoint(run_id).await?;
+                    continue;
+                }
+            };
+            let checkpoint: RunCheckpoint = match serde_json::from_value(data) {
+                Ok(checkpoint) => checkpoint,
+                Err(e) => {
+                    tracing::error!("Dropping unreadable checkpoint of run {}: {}", run_id, e);
+                    self.storage.delete_run_checkpoint(run_id).await?;
+                    continue;
+                }
+            };
+
+            tracing::info!(
+                "Resuming run {} of flow '{}' after {} completed steps",
+                run_id,
+                checkpoint.flow.name,
+                checkpoint.completed_steps.len()
+            );
+            if let Err(e) = self.resume_checkpoint(checkpoint, run).await {
+                tracing::error!("Recovered run {} failed: {}", run_id, e);
+            }
+            recovered.push(run_id);
+        }
+        Ok(recovered)
+    }
+
+    /// Execute the steps of `run` its checkpoint hasn't completed, then finalize it
+    async fn resume_checkpoint(
+        &self,
+        checkpoint: RunCheckpoint,
+        mut run: crate::model::Run,
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        let flow = &checkpoint.flow;
+        let secrets = self.collect_secrets(&checkpoint.event).await;
+        let step_ctx = StepContext::new(checkpoint.event.clone(), checkpoint.vars.clone(), secrets);
+        for (k, v) in checkpoint.outputs.clone() {
+            step_ctx.set_output(k, v);
+        }
+
+        // Fetch previous run data for template access
+        let runs_data = self.fetch_previous_run_data(flow, run.id).await;
+
+        // Create executor
+        let usage = UsageMeter::default();
+        let started = std::time::Instant::now();
+        let executor = Executor::new(
+            self.adapters.clone(),
+            self.templater.clone(),
+            self.storage.clone(),
+            self.secrets_provider.clone(),
+            self.oauth_client.clone(),
+            runs_data,
+            self.max_concurrent_tasks,
+        )
+        .with_strict_params(flow.strict_params.unwrap_or(true))
+        .with_blob_stores(self.blob_stores.clone(), flow.blob_store.clone())
+        .with_event_origin(
+            run_event_source(run.id, &flow.name),
+            checkpoint.correlation_id.clone(),
+        )
+        .with_tenant_id(run.tenant_id.clone())
+        .with_clock(self.clock.clone())
+        .with_event_bus(self.event_bus.clone())
+        .with_usage(usage.clone())
+        .with_stop_after(checkpoint.stop_after.clone())
+        .with_output_scan(self.output_scanner(flow, &step_ctx))
+        .with_mcp_servers(self.flow_mcp_servers(flow, &step_ctx)?)
+        .with_checkpoints(true);
+
+        let result = executor
+            .resume_from_checkpoint(flow, &step_ctx, &checkpoint.completed_steps, run.id)
+            .await;
+        run.usage
+            .get_or_insert_default()
+            .add(&execution_usage(&flow.name, &usage, started));
+
+        let partial = checkpoint.stop_after.is_some();
+        self.finalize_execution(flow, result, run, partial).await
+    }
+
+    /// Whether runs are checkpointed after every top-level step (`checkpointRuns`)
+    fn checkpoint_runs(&self) -> bool {
+        self.config.checkpoint_runs.unwrap_or(false)
+    }
+
+    /// Drop the checkpoint of a run that finished or paused
+    async fn clear_checkpoint(&self, run_id: Uuid) {
+        if !self.checkpoint_runs() {
+            return;
+        }
+        if let Err(e) = self.storage.delete_run_checkpoint(run_id).await {
+            tracing::warn!("Failed to delete checkpoint of run {}: {}", run_id, e);
+        }
+    }
+
+    /// Update the persisted status of an existing run, adding `usage` to its usage
+    async fn update_run_status(
+        &self,
+        run_id: Uuid,
+        status: crate::model::RunStatus,
+        usage: Option<crate::model::RunUsage>,
+    ) -> Result<()> {
+        if let Some(mut run) = self.storage.get_run(run_id).await? {
+            run.status = status;
+            if let Some(usage) = usage {
+                run.usage.get_or_insert_default().add(&usage);
+            }
+            run.ended_at = match status {
+                crate::model::RunStatus::Waiting => None,
+                _ => Some(self.clock.now()),
+            };
+            self.storage.save_run(&run).await?;
+        }
+        Ok(())
+    }
+
+    /// Persist the outcome of an approval step
+    async fn record_approval_step(
+        &self,
+        request: &approval::ApprovalRequest,
+        status: crate::model::StepStatus,
+        error: Option<String>,
+        outputs: Option<serde_json::Value>,
+    ) -> Result<()> {
+        let step_run = crate::model::StepRun {
+            id: request.step_run_id,
+            run_id: request.run_id,
+            step_name: request.step_id.clone().into(),
+            status,
+            started_at: request.requested_at,
+            ended_at: Some(self.clock.now()),
+            error,
+            error_kind: None,
+            inputs: None,
+            outputs: outputs.and_then(|v| serde_json::from_value(v).ok()),
+            progress: None,
+        };
+        self.storage.save_step(&step_run).await
+    }
+
     /// Handle resume events (called when resume events are received)
     pub async fn handle_resume_event(
         &self,
         token: &str,
         event_data: serde_json::Value,
     ) -> Result<()> {
-        tracing::info!("Handling resume event for token: {}", token);
+        tracing::info!("Handling resume event");
 
         // Extract event data into HashMap
         let resume_event = if let Some(obj) = event_data.as_object() {
@@ -385,12 +1327,218 @@ impl Engine {
         self.resume(token, resume_event).await
     }
 
+    /// Remove paused runs that can never be resumed
+    ///
+    /// A paused run is orphaned when its flow is neither deployed nor saved as a
+    /// draft; its run is marked failed. An entry whose run no longer exists, or
+    /// whose data can't be read, is a dangling subscription and is dropped. With
+    /// `dry_run`, nothing is changed. Safe to run repeatedly.
+    pub async fn collect_orphaned_paused_runs(&self, dry_run: bool) -> Result<PausedRunGc> {
+        let flows_dir = crate::config::get_flows_dir(&self.config);
+        let mut gc = PausedRunGc::default();
+        let mut flow_exists = HashMap::new();
+
+        for (token, data) in self.storage.load_paused_runs().await? {
+            let paused = match serde_json::from_value::<PausedRun>(data) {
+                Ok(paused) => paused,
+                Err(e) => {
+                    tracing::debug!("Unreadable paused run '{}': {}", token, e);
+                    gc.subscriptions.push(token);
+                    continue;
+                }
+            };
+            if self.storage.get_run(paused.run_id).await?.is_none() {
+                gc.subscriptions.push(token);
+                continue;
+            }
+
+            let exists = match flow_exists.get(&paused.flow.name) {
+                Some(exists) => *exists,
+                None => {
+                    let exists = self
+                        .storage
+                        .get_deployed_version(&paused.flow.name)
+                        .await?
+                        .is_some()
+                        || crate::storage::flows::flow_exists(&flows_dir, &paused.flow.name)
+                            .await?;
+                    flow_exists.insert(paused.flow.name.clone(), exists);
+                    exists
+                }
+            };
+            if !exists {
+                gc.paused_runs.push(paused.run_id);
+                if !dry_run {
+                    self.storage.delete_paused_run(&token).await?;
+                    self.update_run_status(paused.run_id, crate::model::RunStatus::Failed, None)
+                        .await?;
+                }
+            }
+        }
+
+        if !dry_run {
+            for token in &gc.subscriptions {
+                self.storage.delete_paused_run(token).await?;
+            }
+        }
+        Ok(gc)
+    }
+
+    /// Re-execute one step of a finished run, loading the flow like [`Engine::start`]
+    ///
+    /// See [`Engine::rerun`].
+    pub async fn rerun_step(
+        &self,
+        run_id: Uuid,
+        step_id: &str,
+        with_dependents: bool,
+        is_draft: bool,
+    ) -> Result<RerunResult> {
+        let run = self
+            .storage
+            .get_run(run_id)
+            .await?
+            .ok_or_else(|| BeemFlowError::not_found("Run", run_id.to_string()))?;
+        let content = self.load_flow_content(&run.flow_name, is_draft).await?;
+        let flow = crate::dsl::parse_string(&content, None)?;
+
+        self.rerun(&flow, run_id, step_id, with_dependents).await
+    }
+
+    /// Re-execute one top-level step of a finished run against `flow`
+    ///
+    /// The step sees the run's original event and vars plus the latest successful
+    /// output of every step that is not re-executed. With `with_dependents`, steps
+    /// that depend on it (directly or transitively) are re-executed too, in
+    /// dependency order. New `StepRun`s are recorded under the same run and the
+    /// run's status is set from the outcome; no new run is created.
+    ///
+    /// Runs that are still pending, running or waiting are rejected.
+    pub async fn rerun(
+        &self,
+        flow: &Flow,
+        run_id: Uuid,
+        step_id: &str,
+        with_dependents: bool,
+    ) -> Result<RerunResult> {
+        use crate::model::RunStatus;
+
+        let mut run = self
+            .storage
+            .get_run(run_id)
+            .await?
+            .ok_or_else(|| BeemFlowError::not_found("Run", run_id.to_string()))?;
+        if matches!(
+            run.status,
+            RunStatus::Pending | RunStatus::Running | RunStatus::Waiting
+        ) {
+            return Err(BeemFlowError::validation(format!(
+                "Run {} is still active ({:?}); only finished runs can be re-executed",
+                run_id, run.status
+            )));
+        }
+        if !flow.steps.iter().any(|s| s.id.as_str() == step_id) {
+            return Err(BeemFlowError::not_found(
+                "Step",
+                format!("{} in flow {}", step_id, flow.name),
+            ));
+        }
+
+        let mut selected = std::collections::HashSet::from([step_id.to_string()]);
+        if with_dependents {
+            selected.extend(crate::dsl::DependencyAnalyzer::new().dependents(flow, step_id));
+        }
+
+        // Rebuild the context from the run's latest successful step outputs
+        let secrets = self.collect_secrets(&run.event).await;
+        let step_ctx = StepContext::new(run.event.clone(), run.vars.clone(), secrets);
+        let mut latest: HashMap<String, crate::model::StepRun> = HashMap::new();
+        for step in self.storage.get_steps(run_id).await? {
+            if step.status != crate::model::StepStatus::Succeeded
+                || selected.contains(step.step_name.as_str())
+            {
+                continue;
+            }
+            match latest.get(step.step_name.as_str()) {
+                Some(existing) if existing.started_at >= step.started_at => {}
+                _ => {
+                    latest.insert(step.step_name.to_string(), step);
+                }
+            }
+        }
+        for (name, step) in latest {
+            if let Some(outputs) = step.outputs {
+                step_ctx.set_output(
+                    name,
+                    serde_json::Value::Object(outputs.into_iter().collect()),
+                );
+            }
+        }
+
+        // Mark the run active so it can't be re-executed twice at once
+        run.status = RunStatus::Running;
+        run.ended_at = None;
+        self.storage.save_run(&run).await?;
+
+        let runs_data = self.fetch_previous_run_data(flow, run_id).await;
+        let usage = UsageMeter::default();
+        let started = std::time::Instant::now();
+        let executor = Executor::new(
+            self.adapters.clone(),
+            self.templater.clone(),
+            self.storage.clone(),
+            self.secrets_provider.clone(),
+            self.oauth_client.clone(),
+            runs_data,
+            self.max_concurrent_tasks,
+        )
+        .with_strict_params(flow.strict_params.unwrap_or(true))
+        .with_blob_stores(self.blob_stores.clone(), flow.blob_store.clone())
+        .with_event_origin(
+            run_event_source(run_id, &flow.name),
+            run.correlation_id.clone(),
+        )
+        .with_tenant_id(run.tenant_id.clone())
+        .with_clock(self.clock.clone())
+        .with_event_bus(self.event_bus.clone())
+        .with_usage(usage.clone())
+        .with_output_scan(self.output_scanner(flow, &step_ctx))
+        .with_mcp_servers(self.flow_mcp_servers(flow, &step_ctx)?);
+
+        let result = executor
+            .rerun_steps(flow, &step_ctx, run_id, &selected)
+            .await;
+        let usage = execution_usage(&flow.name, &usage, started);
+        run.usage.get_or_insert_default().add(&usage);
+
+        run.status = match &result {
+            Ok(_) => RunStatus::Succeeded,
+            Err(e) => {
+                tracing::error!("Re-execution of run {} failed: {}", run_id, e);
+                RunStatus::Failed
+            }
+        };
+        run.ended_at = Some(self.clock.now());
+        self.save_run_outcome(&run).await?;
+        self.dispatch_outbox().await;
+
+        let mut steps: Vec<String> = selected.into_iter().collect();
+        steps.sort();
+        Ok(RerunResult {
+            run_id,
+            steps,
+            outputs: result?,
+        })
+    }
+
     /// Setup execution context
     async fn setup_execution_context(
         &self,
         flow: &Flow,
         event: HashMap<String, serde_json::Value>,
-    ) -> Result<(StepContext, Uuid)> {
+        environment: Option<String>,
+        options: &RunOptions,
+    ) -> Result<(StepContext, crate::model::Run)> {
         // Collect secrets from event and secrets provider
         let secrets = self.collect_secrets(&event).await;
 
@@ -401,8 +1549,26 @@ impl Engine {
             secrets,
         );
 
-        // Generate deterministic run ID
-        let run_id = self.generate_deterministic_run_id(&flow.name, &event);
+        let strategy = self.run_id_strategy(options);
+        let run_id = match strategy {
+            RunIdStrategy::Deterministic => self.generate_deterministic_run_id(&flow.name, &event),
+            RunIdStrategy::Random => Uuid::new_v4(),
+            RunIdStrategy::Client => {
+                let key = options.idempotency_key.as_deref().ok_or_else(|| {
+                    BeemFlowError::validation(
+                        "Run ID strategy 'client' requires an idempotency key",
+                    )
+                })?;
+                Self::generate_client_run_id(&flow.name, key)
+            }
+        };
+
+        // A run started outside any request roots its own correlation chain
+        let correlation_id = options
+            .correlation_id
+            .clone()
+            .or_else(crate::event::current_correlation_id)
+            .unwrap_or_else(|| run_id.to_string());
 
         // Create run
         let run = crate::model::Run {
@@ -411,94 +1577,187 @@ impl Engine {
             event: event.clone(),
             vars: flow.vars.clone().unwrap_or_default(),
             status: crate::model::RunStatus::Running,
-            started_at: chrono::Utc::now(),
+            started_at: self.clock.now(),
             ended_at: None,
             steps: None,
+            environment,
+            correlation_id: Some(correlation_id),
+            labels: flow
+                .labels
+                .iter()
+                .flatten()
+                .chain(&options.labels)
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            usage: None,
+            tenant_id: options
+                .tenant_id
+                .clone()
+                .or_else(crate::auth::current_tenant),
         };
 
         // Try to atomically insert run - returns false if already exists
         // Note: Deterministic UUID includes time bucket, so duplicates within
         // the same minute window will have the same ID
         if !self.storage.try_insert_run(&run).await? {
+            let reason = match strategy {
+                RunIdStrategy::Client => "A run with the same idempotency key already exists.",
+                _ => {
+                    "A run with the same event data was already executed within the current time window."
+                }
+            };
             tracing::info!(
-                "Duplicate run detected for {}, run_id: {}",
+                "Duplicate run detected for {}, run_id: {}. {}",
                 flow.name,
-                run_id
+                run_id,
+                reason
             );
-            return Err(crate::BeemFlowError::validation(format!(
-                "Duplicate run detected for flow '{}' (run_id: {}). A run with the same event data was already executed within the current time window.",
-                flow.name, run_id
-            )));
+            return Err(crate::BeemFlowError::DuplicateRun {
+                flow: flow.name.to_string(),
+                run_id,
+            });
         }
 
-        Ok((step_ctx, run_id))
+        Ok((step_ctx, run))
     }
 
     /// Finalize execution and update run status
+    ///
+    /// A `partial` run (stopped before its last step) skips the `on_success` hooks.
     async fn finalize_execution(
         &self,
         flow: &Flow,
-        event: HashMap<String, serde_json::Value>,
         result: std::result::Result<HashMap<String, serde_json::Value>, BeemFlowError>,
-        run_id: Uuid,
+        mut run: crate::model::Run,
+        partial: bool,
     ) -> Result<HashMap<String, serde_json::Value>> {
         let (_outputs, status) = match &result {
             Ok(outputs) => (outputs.clone(), crate::model::RunStatus::Succeeded),
-            Err(e)
-                if e.to_string()
-                    .contains(crate::constants::ERR_AWAIT_EVENT_PAUSE) =>
-            {
+            Err(BeemFlowError::AwaitEventPause(_)) => {
                 (HashMap::new(), crate::model::RunStatus::Waiting)
             }
             Err(_) => (HashMap::new(), crate::model::RunStatus::Failed),
         };
 
-        // Clone event before moving
-        let event_clone = event.clone();
+        let event = run.event.clone();
 
         // Update run with final status
-        let run = crate::model::Run {
-            id: run_id,
-            flow_name: flow.name.clone(),
-            event,
-            vars: flow.vars.clone().unwrap_or_default(),
-            status,
-            started_at: chrono::Utc::now(),
-            ended_at: Some(chrono::Utc::now()),
-            steps: None,
-        };
+        let run_id = run.id;
+        run.status = status;
+        run.ended_at = Some(self.clock.now());
 
-        self.storage.save_run(&run).await?;
+        self.save_run_outcome(&run).await?;
+        self.clear_checkpoint(run_id).await;
 
-        // Handle catch blocks if there was an error
-        if result.is_err() && flow.catch.is_some() {
-            self.execute_catch_blocks(flow, &event_clone, run_id)
+        // Handle catch blocks if there was an error (a pause is not a failure)
+        if status == crate::model::RunStatus::Failed
+            && let Some(ref catch_steps) = flow.catch
+        {
+            self.execute_handler_steps(flow, catch_steps, &event, &run, HashMap::new())
                 .await?;
         }
 
+        // Terminal hooks see the run's outputs, after any catch steps
+        match (&result, status) {
+            (Ok(outputs), crate::model::RunStatus::Succeeded) if !partial => {
+                if let Some(ref hook_steps) = flow.on_success {
+                    let mut context = outputs.clone();
+                    context.insert("run".to_string(), run_summary(&run));
+                    self.execute_handler_steps(flow, hook_steps, &event, &run, context)
+                        .await?;
+                }
+            }
+            (Err(e), crate::model::RunStatus::Failed) => {
+                if let Some(ref hook_steps) = flow.on_failure {
+                    let mut context = self.completed_step_outputs(run_id).await;
+                    context.insert("run".to_string(), run_summary(&run));
+                    context.insert(
+                        "error".to_string(),
+                        serde_json::json!({"message": e.to_string(), "kind": e.step_kind()}),
+                    );
+                    self.execute_handler_steps(flow, hook_steps, &event, &run, context)
+                        .await?;
+                }
+            }
+            _ => {}
+        }
+
+        // Publish the run's events now; the background sweep catches any left over
+        self.dispatch_outbox().await;
+
         result
     }
 
-    /// Execute catch blocks on error
-    async fn execute_catch_blocks(
+    /// Persist a run's status, publishing `run.succeeded` / `run.failed` for
+    /// terminal runs
+    ///
+    /// The event goes through the outbox, atomically with the status write.
+    async fn save_run_outcome(&self, run: &crate::model::Run) -> Result<()> {
+        let topic = match run.status {
+            crate::model::RunStatus::Succeeded => crate::constants::EVENT_TOPIC_RUN_SUCCEEDED,
+            crate::model::RunStatus::Failed => crate::constants::EVENT_TOPIC_RUN_FAILED,
+            _ => return self.storage.save_run(run).await,
+        };
+        self.storage
+            .commit(&crate::storage::WriteBatch {
+                runs: vec![run.clone()],
+                events: vec![crate::model::PendingEvent {
+                    topic: topic.to_string(),
+                    payload: run_summary(run),
+                    source: run_event_source(run.id, &run.flow_name),
+                    correlation_id: run.correlation_id.clone(),
+                }],
+                ..Default::default()
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Outputs of a run's succeeded steps, keyed by step name
+    async fn completed_step_outputs(&self, run_id: Uuid) -> HashMap<String, serde_json::Value> {
+        match self.storage.get_steps(run_id).await {
+            Ok(steps) => steps
+                .into_iter()
+                .filter(|step| step.status == crate::model::StepStatus::Succeeded)
+                .filter_map(|step| {
+                    let outputs = step.outputs?;
+                    Some((
+                        step.step_name.to_string(),
+                        serde_json::Value::Object(outputs.into_iter().collect()),
+                    ))
+                })
+                .collect(),
+            Err(e) => {
+                tracing::error!("Failed to load step outputs for run {}: {}", run_id, e);
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Execute catch or terminal hook steps after the main steps finish
+    ///
+    /// `outputs` seeds the steps' `outputs` context. Failures are recorded on the
+    /// step records but don't change the run's result.
+    async fn execute_handler_steps(
         &self,
         flow: &Flow,
+        steps: &[crate::Step],
         event: &HashMap<String, serde_json::Value>,
-        run_id: Uuid,
+        run: &crate::model::Run,
+        outputs: HashMap<String, serde_json::Value>,
     ) -> Result<HashMap<String, serde_json::Value>> {
-        let catch_steps = flow
-            .catch
-            .as_ref()
-            .ok_or_else(|| crate::BeemFlowError::validation("no catch blocks defined"))?;
-
+        let run_id = run.id;
         let secrets = self.collect_secrets(event).await;
         let step_ctx = StepContext::new(
             event.clone(),
             flow.vars.clone().unwrap_or_default(),
             secrets,
         );
+        for (key, value) in outputs {
+            step_ctx.set_output(key, value);
+        }
 
-        // Catch blocks don't have access to previous runs
+        // Handler steps don't have access to previous runs
         let executor = Executor::new(
             self.adapters.clone(),
             self.templater.clone(),
@@ -507,14 +1766,25 @@ impl Engine {
             self.oauth_client.clone(),
             None,
             self.max_concurrent_tasks,
-        );
-
-        // Execute catch steps and collect step records
-        let mut catch_outputs = HashMap::new();
+        )
+        .with_strict_params(flow.strict_params.unwrap_or(true))
+        .with_blob_stores(self.blob_stores.clone(), flow.blob_store.clone())
+        .with_event_origin(
+            run_event_source(run_id, &flow.name),
+            run.correlation_id.clone(),
+        )
+        .with_tenant_id(run.tenant_id.clone())
+        .with_clock(self.clock.clone())
+        .with_event_bus(self.event_bus.clone())
+        .with_output_scan(self.output_scanner(flow, &step_ctx))
+        .with_mcp_servers(self.flow_mcp_servers(flow, &step_ctx)?);
+
+        // Execute handler steps and collect step records
+        let mut handler_outputs = HashMap::new();
         let mut step_records = Vec::new();
 
-        for step in catch_steps {
-            let step_start = chrono::Utc::now();
+        for step in steps {
+            let step_start = self.clock.now();
 
             match executor
                 .execute_single_step(step, &step_ctx, &step.id)
@@ -523,7 +1793,7 @@ impl Engine {
                 Ok(_) => {
                     let output = step_ctx.get_output(&step.id);
                     if let Some(ref output_value) = output {
-                        catch_outputs.insert(step.id.to_string(), output_value.clone());
+                        handler_outputs.insert(step.id.to_string(), output_value.clone());
                     }
 
                     // Create successful step record
@@ -533,8 +1803,10 @@ impl Engine {
                         step_name: step.id.clone(),
                         status: crate::model::StepStatus::Succeeded,
                         started_at: step_start,
-                        ended_at: Some(chrono::Utc::now()),
+                        ended_at: Some(self.clock.now()),
                         error: None,
+                        error_kind: None,
+                        inputs: step_ctx.get_inputs(&step.id),
                         outputs: output.and_then(|v| {
                             if let serde_json::Value::Object(map) = v {
                                 Some(map.into_iter().collect())
@@ -542,10 +1814,11 @@ impl Engine {
                                 None
                             }
                         }),
+                        progress: None,
                     });
                 }
                 Err(e) => {
-                    tracing::error!("Catch block step {} failed: {}", step.id, e);
+                    tracing::error!("Handler step {} failed: {}", step.id, e);
 
                     // Create failed step record
                     step_records.push(crate::model::StepRun {
@@ -554,26 +1827,29 @@ impl Engine {
                         step_name: step.id.clone(),
                         status: crate::model::StepStatus::Failed,
                         started_at: step_start,
-                        ended_at: Some(chrono::Utc::now()),
+                        ended_at: Some(self.clock.now()),
                         error: Some(e.to_string()),
+                        error_kind: e.step_kind(),
+                        inputs: step_ctx.get_inputs(&step.id),
                         outputs: None,
+                        progress: None,
                     });
                 }
             }
         }
 
-        // Save catch block step records to storage
+        // Save handler step records to storage
         for step_record in step_records {
             if let Err(e) = self.storage.save_step(&step_record).await {
                 tracing::error!(
-                    "Failed to save catch block step {}: {}",
+                    "Failed to save handler step {}: {}",
                     step_record.step_name,
                     e
                 );
             }
         }
 
-        Ok(catch_outputs)
+        Ok(handler_outputs)
     }
 
     /// Collect secrets from event data and secrets provider
@@ -618,6 +1894,87 @@ impl Engine {
         secrets
     }
 
+    /// Secret scan of step outputs for a run of `flow`, when `secrets.outputScan` is set
+    ///
+    /// Scans for the values of every secret the flow references, plus the
+    /// configured `names`.
+    fn output_scanner(
+        &self,
+        flow: &Flow,
+        step_ctx: &StepContext,
+    ) -> Option<crate::secrets::OutputScanner> {
+        let config = self.config.secrets.as_ref()?.output_scan.as_ref()?;
+        let flow_text = serde_json::to_string(flow).unwrap_or_default();
+        let mut names = crate::secrets::referenced_secret_names(&flow_text);
+        names.extend(config.names.iter().cloned());
+
+        let values = names
+            .iter()
+            .filter_map(|name| step_ctx.get_secret(name))
+            .map(|value| match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect();
+        Some(crate::secrets::OutputScanner::new(config.action, values))
+    }
+
+    /// MCP servers the flow declares, with templates in their `env` rendered
+    ///
+    /// They are handed to the run's tool calls instead of being registered on
+    /// the shared McpAdapter, so concurrent runs can start the same server name
+    /// with different env.
+    fn flow_mcp_servers(
+        &self,
+        flow: &Flow,
+        step_ctx: &StepContext,
+    ) -> Result<Arc<HashMap<String, McpServerConfig>>> {
+        let Some(servers) = &flow.mcp_servers else {
+            return Ok(Arc::default());
+        };
+        let data = step_ctx.template_data();
+        servers
+            .iter()
+            .map(|(name, config)| {
+                let mut config = config.clone();
+                for value in config.env.iter_mut().flat_map(|env| env.values_mut()) {
+                    *value = self.templater.render(value, &data)?;
+                }
+                Ok((name.clone(), config))
+            })
+            .collect::<Result<HashMap<_, _>>>()
+            .map(Arc::new)
+    }
+
+    /// Resolve the run ID strategy for a start
+    ///
+    /// An explicit strategy wins; an idempotency key alone implies `client`.
+    fn run_id_strategy(&self, options: &RunOptions) -> RunIdStrategy {
+        options
+            .run_id_strategy
+            .or_else(|| {
+                options
+                    .idempotency_key
+                    .as_ref()
+                    .map(|_| RunIdStrategy::Client)
+            })
+            .or(self.config.run_id_strategy)
+            .unwrap_or_default()
+    }
+
+    /// Generate a run ID from a caller-supplied idempotency key
+    ///
+    /// Keys are scoped per flow and never expire.
+    fn generate_client_run_id(flow_name: &str, key: &str) -> Uuid {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        hasher.update(flow_name.as_bytes());
+        hasher.update([0]);
+        hasher.update(key.as_bytes());
+        Uuid::new_v5(&Uuid::NAMESPACE_DNS, &hasher.finalize())
+    }
+
     /// Generate deterministic run ID for deduplication
     fn generate_deterministic_run_id(
         &self,
@@ -633,7 +1990,7 @@ impl Engine {
         hasher.update(flow_name.as_bytes());
 
         // Add time bucket (1 minute windows)
-        let now = chrono::Utc::now();
+        let now = self.clock.now();
         let time_bucket = now.timestamp() / 60 * 60; // truncate to minute
         hasher.update(time_bucket.to_string().as_bytes());
 
@@ -652,11 +2009,15 @@ impl Engine {
     }
 
     /// Fetch previous run data for template access
+    ///
+    /// Provides `runs.previous` (this flow's last successful run) and, for each flow
+    /// named literally in a `runs.flow("name")` call, `runs.flows.<name>.latest`.
     async fn fetch_previous_run_data(
         &self,
-        flow_name: &str,
+        flow: &Flow,
         current_run_id: Uuid,
     ) -> Option<HashMap<String, serde_json::Value>> {
+        let flow_name = flow.name.as_str();
         tracing::debug!(
             "Fetching previous run data for flow '{}', current run: {}",
             flow_name,
@@ -669,6 +2030,8 @@ impl Engine {
             flow_name.to_string(),
         );
 
+        let mut wrapped = HashMap::new();
+
         let prev_data = runs_access.previous().await;
         if !prev_data.is_empty() {
             tracing::debug!(
@@ -676,17 +2039,29 @@ impl Engine {
                 flow_name,
                 prev_data.len()
             );
-            // Wrap in "previous" key for template access as runs.previous.id, etc.
-            let mut wrapped = HashMap::new();
-            wrapped.insert(
-                "previous".to_string(),
-                serde_json::to_value(&prev_data).unwrap_or(serde_json::Value::Null),
-            );
-            Some(wrapped)
         } else {
             tracing::debug!("No previous run data found for '{}'", flow_name);
-            None
         }
+        // Wrap in "previous" key for template access as runs.previous.id, etc.
+        // Always present (empty on first run) so `{% if runs.previous.id %}` works
+        // with strict undefined handling.
+        wrapped.insert(
+            "previous".to_string(),
+            serde_json::to_value(&prev_data).unwrap_or(serde_json::Value::Null),
+        );
+
+        // Cross-flow access: resolved by the `runs.flow(name)` template method
+        let referenced = RunsAccess::referenced_flows(flow);
+        if !referenced.is_empty() {
+            let mut flows = serde_json::Map::new();
+            for name in referenced {
+                let latest = runs_access.latest_successful(&name).await;
+                flows.insert(name, serde_json::json!({ "latest": latest }));
+            }
+            wrapped.insert("flows".to_string(), serde_json::Value::Object(flows));
+        }
+
+        (!wrapped.is_empty()).then_some(wrapped)
     }
 
     /// Create an engine for testing with in-memory SQLite storage
@@ -696,17 +2071,10 @@ impl Engine {
     ///
     /// For tests that need isolated environments, use `beemflow::utils::TestEnvironment` instead.
     pub async fn for_testing() -> Self {
-        let storage = crate::storage::SqliteStorage::new(":memory:")
-            .await
-            .expect("Failed to create in-memory SQLite storage");
-
         // Create secrets provider
         let secrets_provider: Arc<dyn crate::secrets::SecretsProvider> =
             Arc::new(crate::secrets::EnvSecretsProvider::new());
 
-        // Create minimal config for testing
-        let config = Arc::new(crate::config::Config::default());
-
         // Create registry manager for testing
         let registry_manager = Arc::new(crate::registry::RegistryManager::standard(
             None,
@@ -730,6 +2098,40 @@ impl Engine {
         // Load tools and MCP servers from default registry
         Self::load_default_registry_tools(&adapters, &mcp_adapter, &secrets_provider).await;
 
+        Self::testing_engine(adapters, mcp_adapter, secrets_provider).await
+    }
+
+    /// Create an engine for testing that resolves tools from `adapters`
+    ///
+    /// Meant for registries of scripted adapters (see `adapter::mock`, behind the
+    /// `testing` feature). The core adapter and an MCP adapter without servers are
+    /// added unless the registry already has them; nothing is loaded from the
+    /// default tool registry, so the engine never calls a real tool it wasn't given.
+    pub async fn for_testing_with(adapters: AdapterRegistry) -> Self {
+        let secrets_provider: Arc<dyn crate::secrets::SecretsProvider> =
+            Arc::new(crate::secrets::EnvSecretsProvider::new());
+
+        if adapters.get(crate::constants::ADAPTER_ID_CORE).is_none() {
+            adapters.register(Arc::new(crate::adapter::CoreAdapter::new()));
+        }
+        let mcp_adapter = Arc::new(crate::adapter::McpAdapter::new(secrets_provider.clone()));
+        if adapters.get(crate::constants::ADAPTER_ID_MCP).is_none() {
+            adapters.register(mcp_adapter.clone());
+        }
+
+        Self::testing_engine(Arc::new(adapters), mcp_adapter, secrets_provider).await
+    }
+
+    /// Engine over in-memory SQLite storage and the given adapters
+    async fn testing_engine(
+        adapters: Arc<AdapterRegistry>,
+        mcp_adapter: Arc<crate::adapter::McpAdapter>,
+        secrets_provider: Arc<dyn crate::secrets::SecretsProvider>,
+    ) -> Self {
+        let storage = crate::storage::SqliteStorage::new(":memory:")
+            .await
+            .expect("Failed to create in-memory SQLite storage");
+
         // Wrap storage in Arc first for sharing between engine and oauth_client
         let storage_arc = Arc::new(storage);
 
@@ -743,24 +2145,84 @@ impl Engine {
             Arc::new(Templater::new()),
             storage_arc,
             secrets_provider,
-            config,
+            Arc::new(crate::config::Config::default()),
             oauth_client,
+            Arc::new(crate::event::InProcEventBus::new()),
             1000, // Default max concurrent tasks for testing
         )
     }
 
     /// Get storage reference (for testing only)
-    #[cfg(test)]
+    #[cfg(any(test, feature = "testing"))]
     pub fn storage(&self) -> &Arc<dyn Storage> {
         &self.storage
     }
 }
 
+/// `run` context given to terminal hook steps
+/// Up to three candidates within a small edit distance of `name`, closest first
+fn closest_names(name: &str, candidates: impl Iterator<Item = String>) -> Vec<String> {
+    let max_distance = (name.len() / 3).max(2);
+    let mut scored: Vec<(usize, String)> = candidates
+        .filter_map(|candidate| {
+            let distance = strsim::levenshtein(name, &candidate);
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+    scored.sort();
+    scored.dedup();
+    scored.into_iter().take(3).map(|(_, name)| name).collect()
+}
+
+/// Usage of one execution of a run's steps, also added to the flow's metrics
+fn execution_usage(
+    flow_name: &str,
+    usage: &UsageMeter,
+    started: std::time::Instant,
+) -> crate::model::RunUsage {
+    let usage = usage.snapshot(started.elapsed());
+    crate::telemetry::record_run_usage(flow_name, &usage);
+    usage
+}
+
+/// Source a paused run stored under `key` was saved with
+fn paused_source(key: &str, paused: &PausedRun) -> String {
+    match paused
+        .flow
+        .steps
+        .get(paused.step_idx)
+        .and_then(|step| step.await_event.as_ref())
+    {
+        Some(await_spec) => await_spec.source.clone(),
+        None => format!("{}{}", approval::APPROVAL_SOURCE_PREFIX, key),
+    }
+}
+
+/// Source of the events a run emits
+fn run_event_source(run_id: Uuid, flow: &FlowName) -> crate::event::EventSource {
+    crate::event::EventSource::Run {
+        run_id,
+        flow: flow.clone(),
+    }
+}
+
+fn run_summary(run: &crate::model::Run) -> serde_json::Value {
+    serde_json::json!({
+        "id": run.id,
+        "flow": run.flow_name,
+        "status": run.status,
+    })
+}
+
 #[cfg(test)]
 mod context_test;
 #[cfg(test)]
+mod delivery_test;
+#[cfg(test)]
 mod engine_test;
 #[cfg(test)]
 mod error_test;
 #[cfg(test)]
 mod executor_test;
+#[cfg(test)]
+mod queue_test;
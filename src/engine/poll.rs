@@ -0,0 +1,157 @@
+//! Polling a tool until its response matches (`core.poll`)
+//!
+//! A poll step calls an inner tool (`use` + `with`) repeatedly and evaluates its
+//! `until` condition against each response, exposed to the template as
+//! `response` alongside the current `attempt`. The step's `with` block is
+//! rendered once, except for `until`, which is rendered per response.
+//!
+//! The loop runs in memory inside the step's tool call, so `timeout_total`
+//! cancels it (mid-attempt or mid-sleep), and each attempt reports progress,
+//! which keeps `timeout_idle` from firing between polls.
+
+use crate::utils::retry::RetryPolicy;
+use crate::{BeemFlowError, Result, Step};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Poll interval when the step sets none
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Attempts allowed when the step sets neither `max_attempts` nor `max_duration`
+pub const DEFAULT_POLL_MAX_ATTEMPTS: u32 = 60;
+
+/// Longest interval `backoff` grows to when the step sets no `max_interval`
+pub const DEFAULT_POLL_MAX_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Key of the unrendered condition in a poll step's `with` block
+const UNTIL: &str = "until";
+
+/// A `core.poll` step's rendered inputs
+#[derive(Debug, Clone)]
+pub struct PollSpec {
+    /// Tool called on every attempt
+    pub tool: String,
+    /// Inputs of the inner tool
+    pub with: HashMap<String, Value>,
+    /// Condition evaluated against each response
+    pub until: String,
+    /// Attempts and the delays between them (`interval`, `backoff`, `max_interval`)
+    pub policy: RetryPolicy,
+    /// Time after which no further attempt is started
+    pub max_duration: Option<Duration>,
+}
+
+impl PollSpec {
+    /// Parse a poll step's rendered inputs and its unrendered `until` condition
+    pub fn parse(
+        step_id: &str,
+        inputs: &HashMap<String, Value>,
+        until: Option<&Value>,
+    ) -> Result<Self> {
+        let invalid =
+            |msg: String| BeemFlowError::validation(format!("step '{}': {}", step_id, msg));
+
+        let tool = inputs
+            .get("use")
+            .and_then(|v| v.as_str())
+            .filter(|tool| !tool.is_empty())
+            .ok_or_else(|| invalid("core.poll requires 'use', the tool to poll".to_string()))?;
+        if tool == crate::constants::CORE_POLL || tool == crate::constants::CORE_APPROVAL {
+            return Err(invalid(format!("core.poll cannot poll '{}'", tool)));
+        }
+
+        let with = match inputs.get("with") {
+            None | Some(Value::Null) => HashMap::new(),
+            Some(Value::Object(with)) => with.clone().into_iter().collect(),
+            Some(_) => return Err(invalid("core.poll 'with' must be an object".to_string())),
+        };
+
+        let until = until
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| invalid("core.poll requires an 'until' condition".to_string()))?
+            .to_string();
+
+        let duration = |field: &str| -> Result<Option<Duration>> {
+            inputs
+                .get(field)
+                .map(|value| {
+                    parse_poll_duration(value)
+                        .ok_or_else(|| invalid(format!("invalid core.poll {} '{}'", field, value)))
+                })
+                .transpose()
+        };
+        let interval = duration("interval")?.unwrap_or(DEFAULT_POLL_INTERVAL);
+        let max_interval = duration("max_interval")?.unwrap_or(DEFAULT_POLL_MAX_INTERVAL);
+        let max_duration = duration("max_duration")?;
+
+        let max_attempts = match inputs.get("max_attempts") {
+            Some(value) => value
+                .as_u64()
+                .filter(|n| *n > 0)
+                .map(|n| n.min(u32::MAX as u64) as u32)
+                .ok_or_else(|| invalid(format!("invalid core.poll max_attempts '{}'", value)))?,
+            None if max_duration.is_some() => u32::MAX,
+            None => DEFAULT_POLL_MAX_ATTEMPTS,
+        };
+
+        let multiplier = match inputs.get("backoff") {
+            Some(value) => value
+                .as_f64()
+                .filter(|b| b.is_finite() && *b >= 1.0)
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "invalid core.poll backoff '{}': expected a number >= 1",
+                        value
+                    ))
+                })?,
+            None => 1.0,
+        };
+
+        Ok(Self {
+            tool: tool.to_string(),
+            with,
+            until,
+            policy: RetryPolicy {
+                max_attempts,
+                initial_backoff: interval,
+                multiplier,
+                max_backoff: max_interval.max(interval),
+                jitter: 0.0,
+            },
+            max_duration,
+        })
+    }
+}
+
+/// A poll step without its `until` condition, for rendering the rest of `with`
+///
+/// Returns the condition separately; it is rendered against each response.
+pub fn split_until(step: &Step) -> (Cow<'_, Step>, Option<Value>) {
+    match step.with.as_ref().and_then(|with| with.get(UNTIL)) {
+        Some(until) => {
+            let until = until.clone();
+            let mut step = step.clone();
+            if let Some(with) = step.with.as_mut() {
+                with.remove(UNTIL);
+            }
+            (Cow::Owned(step), Some(until))
+        }
+        None => (Cow::Borrowed(step), None),
+    }
+}
+
+/// Parse a poll duration: seconds as a number, or a string like `30s` or `5m`
+fn parse_poll_duration(value: &Value) -> Option<Duration> {
+    match value {
+        Value::Number(n) => n
+            .as_f64()
+            .filter(|secs| secs.is_finite() && *secs > 0.0)
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok()),
+        Value::String(s) => crate::utils::parse_duration(s)
+            .ok()
+            .and_then(|d| d.to_std().ok()),
+        _ => None,
+    }
+}
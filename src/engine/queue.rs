@@ -0,0 +1,166 @@
+//! Admission of new runs by priority
+//!
+//! With `limits.maxConcurrentRuns` set, at most that many new runs execute at
+//! once. Runs started beyond it wait in a bounded queue and are admitted
+//! highest `priority` first, in arrival order within a priority; once
+//! `limits.runQueueSize` runs are waiting, further runs are rejected with
+//! [`BeemFlowError::QueueFull`]. Resumed and re-executed runs are not queued.
+
+use crate::{BeemFlowError, Result};
+use parking_lot::Mutex;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+
+/// Bounded priority queue in front of run execution
+pub struct RunQueue {
+    max_running: usize,
+    capacity: usize,
+    state: Mutex<QueueState>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    running: usize,
+    waiting: BinaryHeap<Waiter>,
+    next_seq: u64,
+}
+
+/// A run waiting for a slot; handed one through `slot`
+struct Waiter {
+    priority: i32,
+    seq: u64,
+    slot: oneshot::Sender<()>,
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Max-heap: higher priority first, then earlier arrival
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl RunQueue {
+    /// Queue admitting `max_running` runs at once, with up to `capacity` waiting
+    pub fn new(max_running: usize, capacity: usize) -> Self {
+        Self {
+            max_running,
+            capacity,
+            state: Mutex::new(QueueState::default()),
+        }
+    }
+
+    /// Wait for a slot to execute a run with `priority`
+    ///
+    /// Returns at once while fewer than `max_running` runs hold slots and none
+    /// are waiting. The slot is given back when the returned [`RunSlot`] is
+    /// dropped.
+    pub async fn acquire(self: &Arc<Self>, priority: i32) -> Result<RunSlot> {
+        let receiver = {
+            let mut state = self.state.lock();
+            if state.running < self.max_running && state.waiting.is_empty() {
+                state.running += 1;
+                return Ok(RunSlot {
+                    queue: self.clone(),
+                });
+            }
+
+            // Waiters that gave up don't take up room
+            let before = state.waiting.len();
+            state.waiting.retain(|waiter| !waiter.slot.is_closed());
+            crate::telemetry::record_run_queue_depth(-((before - state.waiting.len()) as i64));
+            if state.waiting.len() >= self.capacity {
+                return Err(BeemFlowError::QueueFull {
+                    capacity: self.capacity,
+                });
+            }
+
+            let (slot, receiver) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiting.push(Waiter {
+                priority,
+                seq,
+                slot,
+            });
+            crate::telemetry::record_run_queue_depth(1);
+            receiver
+        };
+
+        let mut wait = Wait {
+            queue: self,
+            receiver,
+        };
+        (&mut wait.receiver)
+            .await
+            .map_err(|_| BeemFlowError::internal("Run queue closed while waiting"))?;
+        Ok(RunSlot {
+            queue: self.clone(),
+        })
+    }
+
+    /// Runs waiting for a slot
+    pub fn waiting(&self) -> usize {
+        let state = self.state.lock();
+        state
+            .waiting
+            .iter()
+            .filter(|waiter| !waiter.slot.is_closed())
+            .count()
+    }
+
+    /// Hand a freed slot to the next waiter, or free it
+    fn release(&self) {
+        let mut state = self.state.lock();
+        while let Some(waiter) = state.waiting.pop() {
+            crate::telemetry::record_run_queue_depth(-1);
+            if waiter.slot.send(()).is_ok() {
+                return;
+            }
+        }
+        state.running -= 1;
+    }
+}
+
+/// A run's place among the executing runs, given back when dropped
+pub struct RunSlot {
+    queue: Arc<RunQueue>,
+}
+
+impl Drop for RunSlot {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+/// Gives back a slot handed to a waiter that stopped waiting before taking it
+struct Wait<'a> {
+    queue: &'a Arc<RunQueue>,
+    receiver: oneshot::Receiver<()>,
+}
+
+impl Drop for Wait<'_> {
+    fn drop(&mut self) {
+        self.receiver.close();
+        if self.receiver.try_recv().is_ok() {
+            self.queue.release();
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use super::queue::RunQueue;
+use crate::BeemFlowError;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Wait until `count` runs are queued
+async fn until_waiting(queue: &RunQueue, count: usize) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while queue.waiting() < count {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("runs should queue");
+}
+
+#[tokio::test]
+async fn test_higher_priority_runs_are_dequeued_first() {
+    let queue = Arc::new(RunQueue::new(1, 10));
+    let running = queue.acquire(0).await.unwrap();
+
+    let order = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let mut handles = Vec::new();
+    for (i, priority) in [1, 5, 3, 5, -2].into_iter().enumerate() {
+        handles.push(tokio::spawn({
+            let (queue, order) = (queue.clone(), order.clone());
+            async move {
+                let _slot = queue.acquire(priority).await.unwrap();
+                order.lock().push((priority, i));
+            }
+        }));
+        until_waiting(&queue, i + 1).await;
+    }
+
+    drop(running);
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    // Equal priorities keep arrival order
+    assert_eq!(*order.lock(), vec![(5, 1), (5, 3), (3, 2), (1, 0), (-2, 4)]);
+    assert_eq!(queue.waiting(), 0);
+}
+
+#[tokio::test]
+async fn test_full_queue_rejects_runs() {
+    let queue = Arc::new(RunQueue::new(1, 1));
+    let running = queue.acquire(0).await.unwrap();
+    let waiting = tokio::spawn({
+        let queue = queue.clone();
+        async move { queue.acquire(0).await.map(drop) }
+    });
+    until_waiting(&queue, 1).await;
+
+    let err = queue.acquire(10).await.err().unwrap();
+    assert!(
+        matches!(err, BeemFlowError::QueueFull { capacity: 1 }),
+        "{}",
+        err
+    );
+
+    drop(running);
+    waiting.await.unwrap().unwrap();
+    // Both slots are free again
+    let _a = queue.acquire(0).await.unwrap();
+    assert_eq!(queue.waiting(), 0);
+}
+
+#[tokio::test]
+async fn test_runs_that_stop_waiting_give_up_their_place() {
+    let queue = Arc::new(RunQueue::new(1, 1));
+    let running = queue.acquire(0).await.unwrap();
+
+    let abandoned = tokio::spawn({
+        let queue = queue.clone();
+        async move { queue.acquire(0).await.map(drop) }
+    });
+    until_waiting(&queue, 1).await;
+    abandoned.abort();
+    let _ = abandoned.await;
+
+    // The abandoned run no longer counts against the queue, nor takes the slot
+    let next = tokio::spawn({
+        let queue = queue.clone();
+        async move { queue.acquire(0).await.map(drop) }
+    });
+    until_waiting(&queue, 1).await;
+    drop(running);
+    tokio::time::timeout(Duration::from_secs(5), next)
+        .await
+        .expect("freed slot should go to the next run")
+        .unwrap()
+        .unwrap();
+}
@@ -0,0 +1,152 @@
+//! Resume tokens for paused runs (`await_event`)
+//!
+//! A paused run is stored under [`storage_key`], a SHA-256 hash of its token, so
+//! reading the `paused_runs` table doesn't yield tokens that can resume runs.
+//! Runs paused before tokens were hashed are stored under the plaintext token;
+//! `flow serve` rehashes them at startup.
+//!
+//! Tokens handed to third parties (e.g. in a webhook callback URL) can be wrapped
+//! by [`sign`]: the token, the run it belongs to and an expiry, signed with
+//! HMAC-SHA256 under `BEEMFLOW_RESUME_SECRET`. [`open`] checks the signature and
+//! expiry before storage is touched, and the resumed run must be the bound one.
+//! With `requireSignedResumeTokens`, plain tokens are refused.
+//! `core.resume_token` generates a random token and its signed form for a flow.
+
+use crate::secrets::SecretsProvider;
+use crate::{BeemFlowError, Result};
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Prefix of signed resume tokens
+const SIGNED_PREFIX: &str = "bfr1.";
+
+/// Prefix of hashed storage keys
+const KEY_PREFIX: &str = "sha256:";
+
+/// Default time a signed resume token stays valid
+pub const DEFAULT_RESUME_TOKEN_TTL: &str = "24h";
+
+/// Key a paused run with `token` is stored under
+pub fn storage_key(token: &str) -> String {
+    format!(
+        "{}{}",
+        KEY_PREFIX,
+        hex::encode(Sha256::digest(token.as_bytes()))
+    )
+}
+
+/// Whether `key` is a hashed storage key rather than a plaintext token
+pub fn is_storage_key(key: &str) -> bool {
+    key.starts_with(KEY_PREFIX)
+}
+
+/// A presented resume token, after verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenedToken {
+    /// The token the run paused with
+    pub token: String,
+    /// Run the token was signed for; `None` for plain tokens
+    pub run_id: Option<Uuid>,
+}
+
+/// Signed payload: token, bound run and expiry (unix seconds)
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    t: String,
+    r: Uuid,
+    e: i64,
+}
+
+/// Wrap `token` as a signed value valid for `run_id` until `expires_at`
+pub(crate) fn sign(key: &[u8], token: &str, run_id: Uuid, expires_at: DateTime<Utc>) -> String {
+    let claims = Claims {
+        t: token.to_string(),
+        r: run_id,
+        e: expires_at.timestamp(),
+    };
+    let payload = serde_json::to_vec(&claims).expect("claims serialize to JSON");
+    let body = format!("{}{}", SIGNED_PREFIX, URL_SAFE_NO_PAD.encode(payload));
+    let signature = hex::encode(mac(key, &body).finalize().into_bytes());
+    format!("{}.{}", body, signature)
+}
+
+/// Verify a presented token
+///
+/// Signed tokens must carry a valid signature and be unexpired; anything else is
+/// taken as a plain token.
+pub(crate) fn open(key: &[u8], presented: &str) -> Result<OpenedToken> {
+    if !presented.starts_with(SIGNED_PREFIX) {
+        return Ok(OpenedToken {
+            token: presented.to_string(),
+            run_id: None,
+        });
+    }
+
+    let invalid = || BeemFlowError::validation("Invalid resume token");
+    let (body, signature) = presented.rsplit_once('.').ok_or_else(invalid)?;
+    let signature = hex::decode(signature).map_err(|_| invalid())?;
+    mac(key, body)
+        .verify_slice(&signature)
+        .map_err(|_| invalid())?;
+
+    let payload = URL_SAFE_NO_PAD
+        .decode(&body[SIGNED_PREFIX.len()..])
+        .map_err(|_| invalid())?;
+    let claims: Claims = serde_json::from_slice(&payload).map_err(|_| invalid())?;
+    if Utc::now().timestamp() > claims.e {
+        return Err(BeemFlowError::validation("Resume token has expired"));
+    }
+
+    Ok(OpenedToken {
+        token: claims.t,
+        run_id: Some(claims.r),
+    })
+}
+
+/// Get the key used to sign resume tokens (`BEEMFLOW_RESUME_SECRET`)
+pub(crate) async fn signing_key(secrets: &dyn SecretsProvider) -> Vec<u8> {
+    server_key(secrets, crate::constants::ENV_RESUME_SECRET).await
+}
+
+/// Get a signing key from the secret `name`
+///
+/// When the secret is unset, a random per-process key is used instead, which
+/// means tokens signed with it stop verifying after a restart.
+pub(crate) async fn server_key(secrets: &dyn SecretsProvider, name: &str) -> Vec<u8> {
+    static FALLBACK_KEYS: OnceLock<Mutex<HashMap<String, Vec<u8>>>> = OnceLock::new();
+
+    if let Ok(Some(secret)) = secrets.get_secret(name).await
+        && !secret.is_empty()
+    {
+        return secret.into_bytes();
+    }
+
+    let mut keys = FALLBACK_KEYS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    keys.entry(name.to_string())
+        .or_insert_with(|| {
+            tracing::warn!(
+                "{} is not set; tokens signed with it will not survive a restart",
+                name
+            );
+            rand::random::<[u8; 32]>().to_vec()
+        })
+        .clone()
+}
+
+fn mac(key: &[u8], body: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(body.as_bytes());
+    mac
+}
@@ -33,7 +33,22 @@ pub enum BeemFlowError {
     Mcp(String),
 
     #[error("Step execution failed: {step_id}: {message}")]
-    StepExecution { step_id: String, message: String },
+    StepExecution {
+        step_id: String,
+        message: String,
+        kind: StepErrorKind,
+        #[source]
+        source: Option<Box<BeemFlowError>>,
+    },
+
+    #[error("Duplicate run detected for flow '{flow}' (run_id: {run_id})")]
+    DuplicateRun { flow: String, run_id: uuid::Uuid },
+
+    #[error("Server is draining and not accepting new runs")]
+    Draining,
+
+    #[error("Run queue is full ({capacity} runs waiting); try again later")]
+    QueueFull { capacity: usize },
 
     #[error("Await event pause: {0}")]
     AwaitEventPause(String),
@@ -51,6 +66,48 @@ pub enum BeemFlowError {
     Internal(String),
 }
 
+/// What made a step fail, for `on_failure` steps and API consumers to branch on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepErrorKind {
+    /// The tool call failed: adapter, MCP server, network or anything else
+    Adapter,
+    /// A template in the step's inputs or condition could not be rendered
+    Template,
+    /// The step's inputs or outputs were rejected
+    Validation,
+    /// `timeout_total` or `timeout_idle` ran out
+    Timeout,
+}
+
+impl StepErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Adapter => "adapter",
+            Self::Template => "template",
+            Self::Validation => "validation",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+impl std::str::FromStr for StepErrorKind {
+    type Err = BeemFlowError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "adapter" => Ok(Self::Adapter),
+            "template" => Ok(Self::Template),
+            "validation" => Ok(Self::Validation),
+            "timeout" => Ok(Self::Timeout),
+            other => Err(BeemFlowError::validation(format!(
+                "unknown step error kind '{}'",
+                other
+            ))),
+        }
+    }
+}
+
 /// Template-specific errors
 #[derive(Error, Debug)]
 pub enum TemplateError {
@@ -115,6 +172,13 @@ pub enum NetworkError {
     #[error("Connection timeout")]
     Timeout,
 
+    /// A `429` or `503` response whose `Retry-After` says when to try again
+    #[error("HTTP request failed: {message}")]
+    RetryAfter {
+        message: String,
+        retry_after: std::time::Duration,
+    },
+
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
 
@@ -167,10 +231,51 @@ impl BeemFlowError {
 
     /// Create a step execution error
     #[inline]
-    pub fn step_execution<S: Into<String>>(step_id: S, message: S) -> Self {
+    pub fn step_execution<S: Into<String>>(step_id: S, kind: StepErrorKind, message: S) -> Self {
         BeemFlowError::StepExecution {
             step_id: step_id.into(),
             message: message.into(),
+            kind,
+            source: None,
+        }
+    }
+
+    /// What kind of step failure this error is
+    ///
+    /// None for errors that don't fail a step, such as a pause to await an event.
+    pub fn step_kind(&self) -> Option<StepErrorKind> {
+        match self {
+            Self::StepExecution { kind, .. } => Some(*kind),
+            Self::Template(_) => Some(StepErrorKind::Template),
+            Self::Validation(_) => Some(StepErrorKind::Validation),
+            Self::AwaitEventPause(_)
+            | Self::Draining
+            | Self::QueueFull { .. }
+            | Self::DuplicateRun { .. } => None,
+            _ => Some(StepErrorKind::Adapter),
+        }
+    }
+
+    /// Wrap this error as the failure of step `step_id`, keeping it as the source
+    ///
+    /// Step execution errors and errors that don't fail a step are returned as is.
+    pub fn into_step_failure(self, step_id: &str) -> Self {
+        match self.step_kind() {
+            Some(kind) => self.into_step_failure_as(step_id, kind),
+            None => self,
+        }
+    }
+
+    /// Like [`BeemFlowError::into_step_failure`], with the kind given by the caller
+    pub fn into_step_failure_as(self, step_id: &str, kind: StepErrorKind) -> Self {
+        match self {
+            Self::StepExecution { .. } | Self::AwaitEventPause(_) => self,
+            cause => Self::StepExecution {
+                step_id: step_id.to_string(),
+                message: cause.to_string(),
+                kind,
+                source: Some(Box::new(cause)),
+            },
         }
     }
 
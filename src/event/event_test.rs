@@ -0,0 +1,243 @@
+use super::*;
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn test_subscribers_receive_only_their_topic() {
+    let bus = InProcEventBus::new();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    bus.subscribe(
+        "orders.created",
+        Arc::new(move |payload| {
+            let _ = tx.send(payload);
+        }),
+    )
+    .await
+    .unwrap();
+
+    bus.publish_value("orders.deleted", json!({"id": 1}))
+        .await
+        .unwrap();
+    bus.publish_value("orders.created", json!({"id": 2}))
+        .await
+        .unwrap();
+
+    let received = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(received.topic, "orders.created");
+    assert_eq!(received.payload, json!({"id": 2}));
+    assert_eq!(received.source, EventSource::Manual);
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_publish_without_subscribers_succeeds() {
+    let bus = InProcEventBus::new();
+    assert!(bus.publish_value("nobody.listens", json!({})).await.is_ok());
+}
+
+/// Publish `count` events on `topic` without yielding, so a subscriber can't
+/// handle any of them before the burst ends
+async fn publish_burst(bus: &InProcEventBus, topic: &str, count: i64) {
+    for n in 0..count {
+        bus.publish_value(topic, json!({"n": n})).await.unwrap();
+    }
+}
+
+/// Payload numbers received on `rx` until it goes quiet
+async fn drain(rx: &mut mpsc::UnboundedReceiver<EventEnvelope>) -> Vec<i64> {
+    let mut received = Vec::new();
+    while let Ok(Some(event)) = tokio::time::timeout(Duration::from_millis(200), rx.recv()).await {
+        received.push(event.payload["n"].as_i64().unwrap());
+    }
+    received
+}
+
+#[tokio::test]
+async fn test_burst_within_the_buffer_reaches_a_slow_subscriber() {
+    let topic = "burst.within_buffer";
+    let bus = InProcEventBus::with_buffer_size(64);
+    let mut rx = collect_topic(&bus, topic);
+
+    publish_burst(&bus, topic, 50).await;
+
+    assert_eq!(drain(&mut rx).await, (0..50).collect::<Vec<_>>());
+    assert_eq!(crate::telemetry::events_dropped(topic), 0.0);
+}
+
+#[tokio::test]
+async fn test_burst_past_the_buffer_counts_dropped_events() {
+    let topic = "burst.past_buffer";
+    let bus = InProcEventBus::with_buffer_size(4);
+    let mut rx = collect_topic(&bus, topic);
+
+    publish_burst(&bus, topic, 50).await;
+
+    // The subscriber skips to the newest events and the gap is counted
+    assert_eq!(drain(&mut rx).await, (46..50).collect::<Vec<_>>());
+    assert_eq!(crate::telemetry::events_dropped(topic), 46.0);
+}
+
+async fn outbox_storage() -> Arc<dyn crate::storage::Storage> {
+    Arc::new(
+        crate::storage::SqliteStorage::new(":memory:")
+            .await
+            .expect("SQLite creation failed"),
+    )
+}
+
+fn pending(topic: &str, n: i64) -> crate::model::PendingEvent {
+    crate::model::PendingEvent {
+        topic: topic.to_string(),
+        payload: json!({"n": n}),
+        source: EventSource::Manual,
+        correlation_id: Some("req-1".to_string()),
+    }
+}
+
+fn collect_topic(bus: &InProcEventBus, topic: &str) -> mpsc::UnboundedReceiver<EventEnvelope> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let handler: EventHandler = Arc::new(move |payload| {
+        let _ = tx.send(payload);
+    });
+    let topic = topic.to_string();
+    futures::executor::block_on(bus.subscribe(&topic, handler)).unwrap();
+    rx
+}
+
+#[tokio::test]
+async fn test_dispatcher_publishes_events_left_by_a_crash() {
+    let storage = outbox_storage().await;
+
+    // The process "dies" right after committing: nothing was dispatched
+    let queued = storage
+        .commit(&crate::storage::WriteBatch {
+            events: vec![pending("orders", 1), pending("orders", 2)],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    // After restart a fresh dispatcher publishes what was left behind
+    let bus = Arc::new(InProcEventBus::new());
+    let mut received = collect_topic(&bus, "orders");
+    let dispatcher = OutboxDispatcher::new(storage.clone(), bus.clone());
+    assert_eq!(dispatcher.dispatch_pending().await.unwrap(), 2);
+
+    for (expected, n) in queued.iter().zip([1, 2]) {
+        let envelope = tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(envelope.id, expected.id);
+        assert_eq!(envelope.sequence, Some(n));
+        assert_eq!(envelope.payload, json!({"n": n}));
+        assert_eq!(envelope.correlation_id.as_deref(), Some("req-1"));
+    }
+
+    assert!(storage.list_unsent_events(10).await.unwrap().is_empty());
+    assert_eq!(dispatcher.dispatch_pending().await.unwrap(), 0);
+}
+
+/// Bus whose publishes fail once `remaining` successes are used up
+struct FlakyBus {
+    remaining: std::sync::atomic::AtomicUsize,
+}
+
+#[async_trait]
+impl EventBus for FlakyBus {
+    async fn publish(&self, _event: EventEnvelope) -> crate::Result<()> {
+        use std::sync::atomic::Ordering;
+        match self
+            .remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+        {
+            Ok(_) => Ok(()),
+            Err(_) => Err(crate::BeemFlowError::internal("bus unavailable")),
+        }
+    }
+
+    async fn subscribe(&self, _topic: &str, _handler: EventHandler) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_failed_publish_keeps_remaining_events_queued() {
+    let storage = outbox_storage().await;
+    storage
+        .commit(&crate::storage::WriteBatch {
+            events: vec![pending("orders", 1), pending("orders", 2)],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let flaky = OutboxDispatcher::new(
+        storage.clone(),
+        Arc::new(FlakyBus {
+            remaining: 1.into(),
+        }),
+    );
+    assert!(flaky.dispatch_pending().await.is_err());
+
+    let unsent = storage.list_unsent_events(10).await.unwrap();
+    assert_eq!(unsent.len(), 1);
+    assert_eq!(unsent[0].sequence, 2);
+
+    let dispatcher = OutboxDispatcher::new(storage.clone(), Arc::new(InProcEventBus::new()));
+    assert_eq!(dispatcher.dispatch_pending().await.unwrap(), 1);
+}
+
+#[test]
+fn test_envelope_deserializes_without_newer_fields() {
+    let id = uuid::Uuid::new_v4();
+    let envelope: EventEnvelope = serde_json::from_value(json!({
+        "id": id,
+        "topic": "orders",
+        "timestamp": "2025-01-01T00:00:00Z",
+        "payload": {"n": 1},
+    }))
+    .unwrap();
+    assert_eq!(envelope.id, id);
+    assert_eq!(envelope.source, EventSource::Manual);
+    assert_eq!(envelope.correlation_id, None);
+    assert_eq!(envelope.version, EVENT_ENVELOPE_VERSION);
+    assert_eq!(envelope.sequence, None);
+
+    let source = json!({"type": "webhook", "provider": "github"});
+    assert_eq!(
+        serde_json::from_value::<EventSource>(source).unwrap(),
+        EventSource::Webhook {
+            provider: "github".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_from_value_wraps_bare_payloads() {
+    let envelope = EventEnvelope::new("orders", json!({"n": 1}));
+    let round_trip = EventEnvelope::from_value("orders", serde_json::to_value(&envelope).unwrap());
+    assert_eq!(round_trip, envelope);
+
+    let wrapped = EventEnvelope::from_value("orders", json!({"n": 2}));
+    assert_eq!(wrapped.topic, "orders");
+    assert_eq!(wrapped.payload, json!({"n": 2}));
+}
+
+#[tokio::test]
+async fn test_envelopes_pick_up_the_scoped_correlation_id() {
+    assert_eq!(EventEnvelope::new("orders", json!({})).correlation_id, None);
+
+    let envelope = with_correlation_id("req-1".to_string(), async {
+        // Spawned work keeps the correlation only when it inherits it
+        let inherited = tokio::spawn(inherit_correlation_id(async { current_correlation_id() }));
+        assert_eq!(inherited.await.unwrap().as_deref(), Some("req-1"));
+        EventEnvelope::new("orders", json!({}))
+    })
+    .await;
+    assert_eq!(envelope.correlation_id.as_deref(), Some("req-1"));
+}
@@ -0,0 +1,264 @@
+//! Event bus
+//!
+//! Topics carry [`EventEnvelope`]s from publishers to in-process subscribers.
+//! Events produced by runs (`core.publish` steps and run completion) are not
+//! published directly: they are written to the storage outbox with the run or
+//! step update that produced them, and [`OutboxDispatcher`] publishes them
+//! afterwards. Step status events (`step.status`) are the exception: they are
+//! live progress signals, published straight to the bus without a redelivery
+//! guarantee.
+//!
+//! Outbox events are delivered at least once. Their envelope reuses the outbox
+//! `id` and carries the topic `sequence`; consumers needing exactly-once
+//! processing dedupe on `id`.
+//!
+//! Every envelope records where it came from ([`EventSource`]) and a
+//! correlation ID shared by everything one inbound action caused: the HTTP
+//! request ID, copied onto the runs it starts and the events those runs emit.
+
+pub mod outbox;
+
+use crate::Result;
+use crate::model::FlowName;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+pub use outbox::OutboxDispatcher;
+
+/// Current [`EventEnvelope`] format version
+pub const EVENT_ENVELOPE_VERSION: u32 = 1;
+
+/// What emitted an event
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventSource {
+    /// A run, through a `core.publish` step or its completion
+    Run { run_id: Uuid, flow: FlowName },
+    /// An inbound webhook from `provider`
+    Webhook { provider: String },
+    /// Published by hand (`publish_event`), or unknown
+    #[default]
+    Manual,
+}
+
+/// An event as delivered on the bus
+///
+/// Fields added after version 1 must be `#[serde(default)]` so stored
+/// envelopes keep deserializing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// Event identifier; outbox redeliveries keep it
+    pub id: Uuid,
+
+    /// Topic the event is published on
+    pub topic: String,
+
+    /// When the event was emitted
+    pub timestamp: DateTime<Utc>,
+
+    /// What emitted the event
+    #[serde(default)]
+    pub source: EventSource,
+
+    /// Shared by the events and runs caused by one inbound action
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+
+    /// Envelope format version
+    #[serde(default = "default_envelope_version")]
+    pub version: u32,
+
+    /// Position within the topic, for events published through the outbox
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<i64>,
+
+    /// Event payload
+    pub payload: Value,
+}
+
+fn default_envelope_version() -> u32 {
+    EVENT_ENVELOPE_VERSION
+}
+
+impl EventEnvelope {
+    /// Envelope for `payload` emitted now, correlated with the current request
+    pub fn new(topic: impl Into<String>, payload: Value) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            topic: topic.into(),
+            timestamp: Utc::now(),
+            source: EventSource::Manual,
+            correlation_id: current_correlation_id(),
+            version: EVENT_ENVELOPE_VERSION,
+            sequence: None,
+            payload,
+        }
+    }
+
+    /// Set what emitted the event
+    pub fn with_source(mut self, source: EventSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Set the correlation ID, keeping the current one when `None`
+    pub fn with_correlation_id(mut self, correlation_id: Option<String>) -> Self {
+        if correlation_id.is_some() {
+            self.correlation_id = correlation_id;
+        }
+        self
+    }
+
+    /// Compatibility shim for publishers of bare values
+    ///
+    /// A value that is already a serialized envelope is used as is; anything else
+    /// becomes the payload of a new envelope on `topic`.
+    pub fn from_value(topic: &str, value: Value) -> Self {
+        match serde_json::from_value::<Self>(value.clone()) {
+            Ok(envelope) if envelope.topic == topic => envelope,
+            _ => Self::new(topic, value),
+        }
+    }
+}
+
+impl From<crate::model::OutboxEvent> for EventEnvelope {
+    fn from(event: crate::model::OutboxEvent) -> Self {
+        Self {
+            id: event.id,
+            topic: event.topic,
+            timestamp: event.created_at,
+            source: event.source,
+            correlation_id: event.correlation_id,
+            version: EVENT_ENVELOPE_VERSION,
+            sequence: Some(event.sequence),
+            payload: event.payload,
+        }
+    }
+}
+
+tokio::task_local! {
+    static CORRELATION_ID: String;
+}
+
+/// Run `future` with `correlation_id` as the current correlation ID
+pub async fn with_correlation_id<F: Future>(correlation_id: String, future: F) -> F::Output {
+    CORRELATION_ID.scope(correlation_id, future).await
+}
+
+/// Run `future` under the caller's correlation ID, if any
+///
+/// Task-locals don't cross `tokio::spawn`; wrap spawned futures with this. The
+/// ID is captured when this is called, not when the returned future runs.
+pub fn inherit_correlation_id<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let correlation_id = current_correlation_id();
+    async move {
+        match correlation_id {
+            Some(correlation_id) => with_correlation_id(correlation_id, future).await,
+            None => future.await,
+        }
+    }
+}
+
+/// Correlation ID of the inbound action being handled, if any
+pub fn current_correlation_id() -> Option<String> {
+    CORRELATION_ID.try_with(Clone::clone).ok()
+}
+
+/// Callback invoked with each event on a subscribed topic
+pub type EventHandler = Arc<dyn Fn(EventEnvelope) + Send + Sync>;
+
+/// Publish/subscribe event bus
+#[async_trait]
+pub trait EventBus: Send + Sync {
+    /// Publish an event on its topic
+    async fn publish(&self, event: EventEnvelope) -> Result<()>;
+
+    /// Call `handler` for every event published on `topic` from now on
+    async fn subscribe(&self, topic: &str, handler: EventHandler) -> Result<()>;
+
+    /// Whether events published by other processes reach this bus's subscribers
+    fn is_shared(&self) -> bool {
+        false
+    }
+
+    /// Publish a bare value, wrapping it with [`EventEnvelope::from_value`]
+    async fn publish_value(&self, topic: &str, payload: Value) -> Result<()> {
+        self.publish(EventEnvelope::from_value(topic, payload))
+            .await
+    }
+}
+
+/// Event bus delivering to subscribers in the same process
+///
+/// Each subscriber has room for `buffer_size` events it hasn't handled yet.
+/// When a burst overruns that, the oldest are dropped for that subscriber,
+/// which logs a warning and counts them in `beemflow_events_dropped_total`.
+pub struct InProcEventBus {
+    sender: broadcast::Sender<EventEnvelope>,
+}
+
+impl InProcEventBus {
+    /// Create an event bus with no subscribers and the default buffer
+    pub fn new() -> Self {
+        Self::with_buffer_size(crate::constants::DEFAULT_EVENT_BUFFER_SIZE)
+    }
+
+    /// Create an event bus holding up to `buffer_size` events per subscriber
+    ///
+    /// # Panics
+    ///
+    /// If `buffer_size` is 0.
+    pub fn with_buffer_size(buffer_size: usize) -> Self {
+        let (sender, _) = broadcast::channel(buffer_size);
+        Self { sender }
+    }
+}
+
+impl Default for InProcEventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventBus for InProcEventBus {
+    async fn publish(&self, event: EventEnvelope) -> Result<()> {
+        // Sending only fails when nobody is subscribed, which is not an error
+        let _ = self.sender.send(event);
+        Ok(())
+    }
+
+    async fn subscribe(&self, topic: &str, handler: EventHandler) -> Result<()> {
+        let topic = topic.to_string();
+        let mut receiver = self.sender.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if event.topic == topic => handler(event),
+                    Ok(_) => {}
+                    // Dropped events may be on any topic; the subscriber's is
+                    // the one that fell behind
+                    Err(broadcast::error::RecvError::Lagged(dropped)) => {
+                        tracing::warn!(
+                            "Subscriber to '{}' fell behind; {} events dropped (raise event.bufferSize)",
+                            topic,
+                            dropped
+                        );
+                        crate::telemetry::record_events_dropped(&topic, dropped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod event_test;
@@ -0,0 +1,120 @@
+//! Outbox dispatcher
+//!
+//! Publishes events recorded by [`OutboxStorage::commit`] and marks them sent.
+//! A crash between commit and publish leaves the rows unsent, and the next
+//! dispatch publishes them, so delivery is at least once. Sent events are
+//! kept for [`SENT_EVENT_RETENTION`] and then pruned.
+
+use super::EventBus;
+use crate::Result;
+use crate::storage::Storage;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+#[cfg(doc)]
+use crate::storage::OutboxStorage;
+
+/// Unsent events read from storage per batch
+const DISPATCH_BATCH_SIZE: usize = 100;
+
+/// How often the background loop sweeps the outbox
+pub const DEFAULT_DISPATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long sent events stay in the outbox before they are pruned
+pub const SENT_EVENT_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How often the background loop prunes sent events
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Publishes outbox events to the event bus
+pub struct OutboxDispatcher {
+    storage: Arc<dyn Storage>,
+    bus: Arc<dyn EventBus>,
+    /// Serializes dispatches within the process so events aren't published twice
+    dispatching: Mutex<()>,
+}
+
+impl OutboxDispatcher {
+    /// Create a dispatcher publishing `storage`'s outbox to `bus`
+    pub fn new(storage: Arc<dyn Storage>, bus: Arc<dyn EventBus>) -> Self {
+        Self {
+            storage,
+            bus,
+            dispatching: Mutex::new(()),
+        }
+    }
+
+    /// Publish every unsent event, oldest first
+    ///
+    /// Returns the number of events published. Events are marked sent after
+    /// they are published; if publishing fails, the events published so far are
+    /// marked and the rest stay queued for the next dispatch.
+    pub async fn dispatch_pending(&self) -> Result<usize> {
+        let _guard = self.dispatching.lock().await;
+        let mut published = 0;
+
+        loop {
+            let events = self.storage.list_unsent_events(DISPATCH_BATCH_SIZE).await?;
+            let batch_len = events.len();
+
+            let mut sent = Vec::with_capacity(batch_len);
+            let mut failure = None;
+            for event in events {
+                let id = event.id;
+                match self.bus.publish(event.into()).await {
+                    Ok(()) => sent.push(id),
+                    Err(e) => {
+                        failure = Some(e);
+                        break;
+                    }
+                }
+            }
+
+            if !sent.is_empty() {
+                self.storage.mark_events_sent(&sent).await?;
+                published += sent.len();
+            }
+            if let Some(e) = failure {
+                return Err(e);
+            }
+            if batch_len < DISPATCH_BATCH_SIZE {
+                return Ok(published);
+            }
+        }
+    }
+
+    /// Delete events sent more than [`SENT_EVENT_RETENTION`] ago
+    ///
+    /// Returns the number of events deleted.
+    pub async fn prune_sent(&self) -> Result<u64> {
+        let retention = chrono::Duration::from_std(SENT_EVENT_RETENTION)
+            .expect("retention fits in chrono::Duration");
+        self.storage
+            .prune_sent_events(chrono::Utc::now() - retention)
+            .await
+    }
+
+    /// Dispatch every `interval`, and prune sent events hourly, until `cancel` fires
+    pub async fn run(self: Arc<Self>, interval: Duration, cancel: CancellationToken) {
+        let mut last_prune: Option<Instant> = None;
+        loop {
+            if let Err(e) = self.dispatch_pending().await {
+                tracing::warn!("Failed to dispatch outbox events: {}", e);
+            }
+            if last_prune.is_none_or(|at| at.elapsed() >= PRUNE_INTERVAL) {
+                last_prune = Some(Instant::now());
+                match self.prune_sent().await {
+                    Ok(0) => {}
+                    Ok(deleted) => tracing::debug!("Pruned {} sent outbox events", deleted),
+                    Err(e) => tracing::warn!("Failed to prune outbox events: {}", e),
+                }
+            }
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+    }
+}
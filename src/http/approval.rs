@@ -0,0 +1,177 @@
+//! Approval gate endpoints
+//!
+//! `GET /approvals/{token}` renders a small page describing a pending `core.approval`
+//! step; `POST /approvals/{token}` records the decision (JSON or form body) and
+//! resumes the run. The approver comes from the `?link=` approver link the page
+//! was opened with, or from a bearer token; never from the request body. When
+//! the OAuth server is enabled, decisions without either are refused.
+
+use super::AppError;
+use super::template::TemplateRenderer;
+use crate::engine::Engine;
+use crate::engine::approval::ApprovalDecision;
+use crate::storage::Storage;
+use crate::{BeemFlowError, Result};
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, header},
+    response::{Html, IntoResponse, Response},
+    routing::get,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Approval endpoint state
+#[derive(Clone)]
+pub struct ApprovalState {
+    pub engine: Arc<Engine>,
+    pub storage: Arc<dyn Storage>,
+    pub template_renderer: Arc<TemplateRenderer>,
+    /// Require an approver link or a valid bearer token to decide (set when the
+    /// OAuth server is enabled)
+    pub require_auth: bool,
+}
+
+/// Query string of approval URLs
+#[derive(Debug, Default, Deserialize)]
+struct ApprovalQuery {
+    /// Approver link, see [`crate::engine::approval`]
+    link: Option<String>,
+}
+
+/// Create approval routes
+pub fn create_approval_routes() -> Router<ApprovalState> {
+    Router::new().route(
+        "/approvals/{token}",
+        get(show_approval).post(decide_approval),
+    )
+}
+
+/// Render the approval page for a pending approval
+async fn show_approval(
+    State(state): State<ApprovalState>,
+    Path(token): Path<String>,
+    Query(query): Query<ApprovalQuery>,
+) -> std::result::Result<Html<String>, AppError> {
+    let request = state.engine.get_approval(&token).await?;
+    let approver = match &query.link {
+        Some(link) => Some(state.engine.approval_link_approver(&token, link).await?),
+        None => None,
+    };
+
+    let page = state.template_renderer.render_json(
+        "approval",
+        &json!({
+            "decided": false,
+            "flow_name": request.flow_name,
+            "step_id": request.step_id,
+            "message": request.message,
+            "context": request.context,
+            "expires_at": request.expires_at.to_rfc3339(),
+            "approver": approver,
+        }),
+    )?;
+
+    Ok(Html(page))
+}
+
+/// Record an approve/reject decision and resume the run
+async fn decide_approval(
+    State(state): State<ApprovalState>,
+    Path(token): Path<String>,
+    Query(query): Query<ApprovalQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> std::result::Result<Response, AppError> {
+    let is_form = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/x-www-form-urlencoded"));
+
+    let mut decision = if is_form {
+        parse_form_decision(&body)?
+    } else {
+        serde_json::from_slice::<ApprovalDecision>(&body)
+            .map_err(|e| BeemFlowError::validation(format!("Invalid decision: {}", e)))?
+    };
+
+    decision.approver = authenticated_approver(&state, &token, &query, &headers).await?;
+
+    let run_id = state
+        .engine
+        .decide_approval(&token, decision.clone())
+        .await?;
+
+    if is_form {
+        let page = state.template_renderer.render_json(
+            "approval",
+            &json!({ "decided": true, "approved": decision.approved }),
+        )?;
+        return Ok(Html(page).into_response());
+    }
+
+    Ok(Json(json!({
+        "run_id": run_id.to_string(),
+        "approved": decision.approved,
+        "approver": decision.approver,
+    }))
+    .into_response())
+}
+
+/// Who is deciding: the approver link's approver, else the bearer token's user
+///
+/// Without either, the decision is anonymous, unless auth is required.
+async fn authenticated_approver(
+    state: &ApprovalState,
+    token: &str,
+    query: &ApprovalQuery,
+    headers: &HeaderMap,
+) -> Result<Option<String>> {
+    if let Some(link) = &query.link {
+        return Ok(Some(
+            state.engine.approval_link_approver(token, link).await?,
+        ));
+    }
+    if !state.require_auth {
+        return Ok(None);
+    }
+
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or_else(|| {
+            BeemFlowError::auth("Authentication required: open your approver link to decide")
+        })?;
+    let user = crate::auth::middleware::validate_token(&state.storage, bearer).await?;
+    Ok(Some(user.user_id))
+}
+
+/// Parse the HTML form submission (`approved=true|false&comment=...`)
+fn parse_form_decision(body: &[u8]) -> Result<ApprovalDecision> {
+    let mut decision = ApprovalDecision {
+        approved: false,
+        comment: None,
+        approver: None,
+    };
+    let mut has_approved = false;
+
+    for (key, value) in url::form_urlencoded::parse(body) {
+        match key.as_ref() {
+            "approved" => {
+                decision.approved = value == "true";
+                has_approved = true;
+            }
+            "comment" if !value.trim().is_empty() => decision.comment = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+
+    if !has_approved {
+        return Err(BeemFlowError::validation("Missing 'approved' field"));
+    }
+    Ok(decision)
+}
@@ -0,0 +1,300 @@
+//! Tests for approval gate endpoints
+
+use crate::dsl::parse_string;
+use crate::http::approval::{ApprovalState, create_approval_routes};
+use crate::http::template::TemplateRenderer;
+use crate::model::{RunStatus, StepStatus};
+use crate::utils::TestEnvironment;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+const PAYMENT_FLOW: &str = r#"
+name: payment_approval
+steps:
+  - id: approve
+    use: core.approval
+    with:
+      message: "Approve payment of {{ event.amount }}?"
+      context:
+        amount: "{{ event.amount }}"
+      timeout: 1h
+      on_reject: ON_REJECT
+      approvers: [alice]
+  - id: pay
+    use: core.echo
+    if: "{{ outputs.approve.approved }}"
+    with:
+      text: "Approved by {{ outputs.approve.approver }}: {{ outputs.approve.comment }}"
+"#;
+
+async fn approval_app(env: &TestEnvironment) -> axum::Router {
+    approval_app_with_auth(env, false).await
+}
+
+async fn approval_app_with_auth(env: &TestEnvironment, require_auth: bool) -> axum::Router {
+    let mut renderer = TemplateRenderer::new("static");
+    renderer.load_approval_templates().await.unwrap();
+
+    create_approval_routes().with_state(ApprovalState {
+        engine: env.deps.engine.clone(),
+        storage: env.deps.storage.clone(),
+        template_renderer: Arc::new(renderer),
+        require_auth,
+    })
+}
+
+/// Start the payment flow and return (run_id, approval token)
+async fn start_paused_run(env: &TestEnvironment, on_reject: &str) -> (Uuid, String) {
+    let (run_id, token, _) = start_paused_run_with_link(env, on_reject).await;
+    (run_id, token)
+}
+
+/// Start the payment flow and return (run_id, approval token, alice's approver URL)
+async fn start_paused_run_with_link(
+    env: &TestEnvironment,
+    on_reject: &str,
+) -> (Uuid, String, String) {
+    let flow = parse_string(&PAYMENT_FLOW.replace("ON_REJECT", on_reject), None).unwrap();
+    let mut event = HashMap::new();
+    event.insert("amount".to_string(), json!("$1,200"));
+
+    let result = env.deps.engine.execute(&flow, event).await;
+    assert!(result.is_err(), "Run should pause at the approval step");
+
+    let storage = &env.deps.storage;
+    let run = storage.list_runs(10, 0).await.unwrap().remove(0);
+    assert_eq!(run.status, RunStatus::Waiting);
+
+    let steps = storage.get_steps(run.id).await.unwrap();
+    let approve = steps
+        .iter()
+        .find(|s| s.step_name.as_str() == "approve")
+        .unwrap();
+    assert_eq!(approve.status, StepStatus::Waiting);
+    let outputs = approve.outputs.as_ref().unwrap();
+    let token = outputs["token"].as_str().unwrap().to_string();
+    let alice_url = outputs["approver_urls"]["alice"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    (run.id, token, alice_url)
+}
+
+fn post_decision(token: &str, body: serde_json::Value) -> Request<Body> {
+    post_decision_to(&format!("/approvals/{}", token), body)
+}
+
+fn post_decision_to(uri: &str, body: serde_json::Value) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_approval_end_to_end_via_http() {
+    let env = TestEnvironment::new().await;
+    let app = approval_app(&env).await;
+    let (run_id, token, alice_url) = start_paused_run_with_link(&env, "fail").await;
+
+    // The approval page shows the rendered message and context (HTML-escaped)
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(alice_url.as_str())
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Approve payment of $1,200?"));
+    assert!(html.contains("payment_approval"));
+    assert!(html.contains("Deciding as <strong>alice</strong>"));
+
+    // Approve through alice's link; an approver in the body is ignored
+    let response = app
+        .clone()
+        .oneshot(post_decision_to(
+            &alice_url,
+            json!({"approved": true, "comment": "ok", "approver": "mallory"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let storage = &env.deps.storage;
+    let run = storage.get_run(run_id).await.unwrap().unwrap();
+    assert_eq!(run.status, RunStatus::Succeeded);
+
+    let steps = storage.get_steps(run_id).await.unwrap();
+    let approve = steps
+        .iter()
+        .find(|s| s.step_name.as_str() == "approve")
+        .unwrap();
+    assert_eq!(approve.status, StepStatus::Succeeded);
+    assert_eq!(approve.outputs.as_ref().unwrap()["approved"], true);
+    let pay = steps
+        .iter()
+        .find(|s| s.step_name.as_str() == "pay")
+        .unwrap();
+    assert_eq!(
+        pay.outputs.as_ref().unwrap()["text"],
+        "Approved by alice: ok"
+    );
+
+    // Tokens are single-use
+    let response = app
+        .oneshot(post_decision(&token, json!({"approved": true})))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_approval_rejection_fails_run() {
+    let env = TestEnvironment::new().await;
+    let app = approval_app(&env).await;
+    let (run_id, token) = start_paused_run(&env, "fail").await;
+
+    let response = app
+        .oneshot(post_decision(
+            &token,
+            json!({"approved": false, "comment": "too much"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let storage = &env.deps.storage;
+    let run = storage.get_run(run_id).await.unwrap().unwrap();
+    assert_eq!(run.status, RunStatus::Failed);
+
+    let steps = storage.get_steps(run_id).await.unwrap();
+    let approve = steps
+        .iter()
+        .find(|s| s.step_name.as_str() == "approve")
+        .unwrap();
+    assert_eq!(approve.status, StepStatus::Failed);
+    assert!(steps.iter().all(|s| s.step_name.as_str() != "pay"));
+}
+
+#[tokio::test]
+async fn test_approval_rejection_can_continue() {
+    let env = TestEnvironment::new().await;
+    let app = approval_app(&env).await;
+    let (run_id, token) = start_paused_run(&env, "continue").await;
+
+    let response = app
+        .oneshot(post_decision(&token, json!({"approved": false})))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Run completes; the gated step is skipped via its `if`
+    let run = env.deps.storage.get_run(run_id).await.unwrap().unwrap();
+    assert_eq!(run.status, RunStatus::Succeeded);
+}
+
+#[tokio::test]
+async fn test_approval_rejects_forged_token() {
+    let env = TestEnvironment::new().await;
+    let app = approval_app(&env).await;
+    let (_run_id, token) = start_paused_run(&env, "fail").await;
+
+    let (nonce, _) = token.split_once('.').unwrap();
+    let forged = format!("{}.{}", nonce, "00".repeat(32));
+
+    let response = app
+        .oneshot(post_decision(&forged, json!({"approved": true})))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+fn post_form(uri: &str, body: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_approval_form_with_auth_enabled_needs_an_approver_link() {
+    let env = TestEnvironment::new().await;
+    let app = approval_app_with_auth(&env, true).await;
+    let (run_id, token, alice_url) = start_paused_run_with_link(&env, "fail").await;
+
+    // The form can't carry a bearer token, and naming an approver doesn't help
+    let response = app
+        .clone()
+        .oneshot(post_form(
+            &format!("/approvals/{}", token),
+            "approved=true&approver=alice",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // A link signed for another approval is refused
+    let other_env = TestEnvironment::new().await;
+    let (_, other_token, _) = start_paused_run_with_link(&other_env, "fail").await;
+    let (_, link) = alice_url.split_once('?').unwrap();
+    let response = app
+        .clone()
+        .oneshot(post_form(
+            &format!("/approvals/{}?{}", other_token, link),
+            "approved=true",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // So is a tampered one
+    let response = app
+        .clone()
+        .oneshot(post_form(&format!("{}00", alice_url), "approved=true"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Posting the form from alice's link decides as alice
+    let response = app
+        .clone()
+        .oneshot(post_form(
+            &alice_url,
+            "approved=true&comment=ok&approver=mallory",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let steps = env.deps.storage.get_steps(run_id).await.unwrap();
+    let approve = steps
+        .iter()
+        .find(|s| s.step_name.as_str() == "approve")
+        .unwrap();
+    assert_eq!(approve.outputs.as_ref().unwrap()["approver"], "alice");
+
+    // The link is spent with the approval
+    let response = app
+        .oneshot(post_form(&alice_url, "approved=true"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,68 @@
+//! Run history download
+//!
+//! `GET /runs/export?format=csv&since=30d&include_steps=true` streams the rows of
+//! the `export_runs` operation as an attachment. Rows are written to the response
+//! as each page of runs is read, so the route is not subject to the request
+//! timeout of the operation routes. Like them, it is scoped to the tenant of a
+//! bearer token.
+
+use super::AppError;
+use crate::BeemFlowError;
+use crate::core::export::write_export;
+use crate::core::runs::runs::ExportInput;
+use crate::storage::Storage;
+use axum::{
+    Router,
+    body::Body,
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use std::sync::Arc;
+
+/// Bytes buffered between the export task and the response body
+const EXPORT_BUFFER: usize = 64 * 1024;
+
+/// Create the run export route
+pub fn create_export_routes() -> Router<Arc<dyn Storage>> {
+    Router::new().route("/runs/export", get(export_runs))
+}
+
+/// Stream an export of the runs as a file download
+async fn export_runs(
+    State(storage): State<Arc<dyn Storage>>,
+    Query(input): Query<ExportInput>,
+) -> std::result::Result<Response, AppError> {
+    if input.output.is_some() {
+        return Err(BeemFlowError::validation("output is only supported from the CLI").into());
+    }
+    let options = input.options()?;
+    let format = options.format;
+
+    let (mut writer, reader) = tokio::io::duplex(EXPORT_BUFFER);
+    // The export reads only the requesting tenant's runs
+    tokio::spawn(crate::auth::inherit_tenant(async move {
+        // An error ends the body early; the client sees a truncated download
+        if let Err(e) = write_export(storage.as_ref(), &options, &mut writer, None).await {
+            tracing::error!("Run export failed: {}", e);
+        }
+    }));
+
+    let filename = format!(
+        "runs-{}.{}",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+        format.extension()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        Body::from_stream(tokio_util::io::ReaderStream::new(reader)),
+    )
+        .into_response())
+}
@@ -3,12 +3,19 @@ use crate::core::OperationRegistry;
 use crate::utils::TestEnvironment;
 use axum::http::StatusCode;
 
-async fn create_test_state() -> AppState {
+/// Returns the test environment alongside the state so its temp directory
+/// (and SQLite database) stays alive for the duration of the test.
+async fn create_test_state() -> (AppState, TestEnvironment) {
     let env = TestEnvironment::new().await;
-    let storage = env.deps.storage.clone();
-    let registry_manager = env.deps.registry_manager.clone();
+    let state = test_state(env.deps.clone());
+    (state, env)
+}
 
-    let registry = Arc::new(OperationRegistry::new(env.deps));
+fn test_state(deps: crate::core::Dependencies) -> AppState {
+    let storage = deps.storage.clone();
+    let registry_manager = deps.registry_manager.clone();
+
+    let registry = Arc::new(OperationRegistry::new(deps));
     let session_store = Arc::new(session::SessionStore::new());
     let oauth_client = Arc::new(
         crate::auth::OAuthClientManager::new(
@@ -38,14 +45,14 @@ async fn test_health_endpoint() {
 
 #[tokio::test]
 async fn test_root_handler() {
-    let state = create_test_state().await;
+    let (state, _env) = create_test_state().await;
     let result = state.registry.execute("root", json!({})).await;
     assert!(result.is_ok());
 }
 
 #[tokio::test]
 async fn test_list_flows_empty() {
-    let state = create_test_state().await;
+    let (state, _env) = create_test_state().await;
     let result = state.registry.execute("list_flows", json!({})).await;
     assert!(result.is_ok());
     let result_val = result.unwrap();
@@ -54,7 +61,7 @@ async fn test_list_flows_empty() {
 
 #[tokio::test]
 async fn test_save_and_get_flow() {
-    let state = create_test_state().await;
+    let (state, _env) = create_test_state().await;
 
     // Save a flow
     let flow_content = r#"
@@ -92,7 +99,7 @@ steps:
 
 #[tokio::test]
 async fn test_delete_flow() {
-    let state = create_test_state().await;
+    let (state, _env) = create_test_state().await;
 
     // Save a flow first
     let flow_content = r#"
@@ -137,7 +144,7 @@ steps:
 
 #[tokio::test]
 async fn test_validate_flow_valid() {
-    let state = create_test_state().await;
+    let (state, _env) = create_test_state().await;
 
     let flow_content = r#"
 name: valid-flow
@@ -161,7 +168,7 @@ steps:
 
 #[tokio::test]
 async fn test_validate_flow_invalid() {
-    let state = create_test_state().await;
+    let (state, _env) = create_test_state().await;
 
     // Invalid YAML
     let invalid_content = "invalid: yaml: syntax: [[[";
@@ -176,7 +183,7 @@ async fn test_validate_flow_invalid() {
 
 #[tokio::test]
 async fn test_start_run() {
-    let state = create_test_state().await;
+    let (state, _env) = create_test_state().await;
 
     // First save a flow
     let flow_content = r#"
@@ -214,7 +221,7 @@ steps:
 
 #[tokio::test]
 async fn test_get_run() {
-    let state = create_test_state().await;
+    let (state, _env) = create_test_state().await;
 
     // Create and start a run
     let flow_content = r#"
@@ -253,21 +260,21 @@ steps:
 
 #[tokio::test]
 async fn test_list_runs() {
-    let state = create_test_state().await;
+    let (state, _env) = create_test_state().await;
     let result = state.registry.execute("list_runs", json!({})).await;
     assert!(result.is_ok());
 }
 
 #[tokio::test]
 async fn test_list_tools() {
-    let state = create_test_state().await;
+    let (state, _env) = create_test_state().await;
     let result = state.registry.execute("list_tools", json!({})).await;
     assert!(result.is_ok());
 }
 
 #[tokio::test]
 async fn test_get_nonexistent_flow() {
-    let state = create_test_state().await;
+    let (state, _env) = create_test_state().await;
     let input = json!({
         "name": "nonexistent-flow"
     });
@@ -301,3 +308,946 @@ async fn test_metrics_endpoint() {
     assert!(metrics_body.contains("beemflow_flow_executions_total"));
     assert!(!metrics_body.is_empty());
 }
+
+#[test]
+fn test_input_from_path_and_query() {
+    let run_id = uuid::Uuid::new_v4();
+    let input: crate::core::runs::runs::GetInput = input_from_path_and_query(
+        "run_id",
+        &run_id.to_string(),
+        Some("include_inputs=true&run_id=ignored"),
+    )
+    .unwrap();
+    assert_eq!(input.run_id, run_id);
+    assert_eq!(input.include_inputs, Some(true));
+
+    let input: crate::core::runs::runs::GetInput =
+        input_from_path_and_query("run_id", &run_id.to_string(), None).unwrap();
+    assert_eq!(input.include_inputs, None);
+
+    // Run IDs are parsed at the boundary
+    let result: Result<crate::core::runs::runs::GetInput> =
+        input_from_path_and_query("run_id", "abc", None);
+    assert!(matches!(result, Err(BeemFlowError::Validation(_))));
+}
+
+#[tokio::test]
+async fn test_flow_routes_reject_path_traversal() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let (state, env) = create_test_state().await;
+    let app = build_operation_routes(&state);
+
+    // A flow file just outside flows_dir that traversal would reach
+    let flows_dir = crate::config::get_flows_dir(&env.deps.config);
+    let outside = std::path::Path::new(&flows_dir)
+        .parent()
+        .unwrap()
+        .join("secret.flow.yaml");
+    std::fs::write(&outside, "name: secret\nversion: 1.0.0\nsteps: []").unwrap();
+
+    let requests = [
+        ("GET", "/flows/..%2Fsecret"),
+        ("GET", "/flows/..%2F..%2Fetc%2Fpasswd"),
+        ("GET", "/flows/flow.name"),
+        ("DELETE", "/flows/..%2Fsecret"),
+        ("POST", "/flows/..%2Fsecret/deploy"),
+        ("POST", "/flows/..%2F..%2Fetc%2Fcron.d%2Fevil/deploy"),
+        ("GET", "/runs/not-a-uuid"),
+    ];
+
+    for (method, uri) in requests {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(
+            response.status(),
+            StatusCode::BAD_REQUEST,
+            "{} {} should be rejected",
+            method,
+            uri
+        );
+    }
+
+    // Names in JSON bodies are validated as well
+    let request = Request::builder()
+        .method("POST")
+        .uri("/flows")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({"name": "../secret", "content": "name: secret\nsteps: []"}).to_string(),
+        ))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert!(response.status().is_client_error());
+
+    assert!(outside.exists());
+    let deployed = env.deps.storage.list_all_deployed_flows().await.unwrap();
+    assert!(deployed.is_empty());
+}
+
+#[tokio::test]
+async fn test_unauthenticated_requests_are_refused_once_tenants_exist() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let (state, env) = create_test_state().await;
+    let storage = env.deps.storage.clone();
+    let app = build_operation_routes(&state);
+    let list_runs = |token: Option<&str>| {
+        let mut request = Request::builder().method("GET").uri("/runs");
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        request.body(Body::empty()).unwrap()
+    };
+
+    // Single-tenant: no token needed
+    let response = app.clone().oneshot(list_runs(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Registering a client to a tenant makes the deployment multi-tenant
+    let now = chrono::Utc::now();
+    for (client_id, tenant_id) in [("acme-client", Some("acme")), ("bare-client", None)] {
+        let client = crate::model::OAuthClient {
+            id: client_id.to_string(),
+            secret: "hash".to_string(),
+            name: client_id.to_string(),
+            redirect_uris: vec![],
+            grant_types: vec!["client_credentials".to_string()],
+            response_types: vec![],
+            scope: "runs:read".to_string(),
+            client_uri: None,
+            logo_uri: None,
+            created_at: now,
+            updated_at: now,
+            tenant_id: tenant_id.map(str::to_string),
+        };
+        storage.save_oauth_client(&client).await.unwrap();
+        let token = crate::model::OAuthToken {
+            id: uuid::Uuid::new_v4().to_string(),
+            client_id: client_id.to_string(),
+            user_id: "user".to_string(),
+            redirect_uri: String::new(),
+            scope: "runs:read".to_string(),
+            code: None,
+            code_create_at: None,
+            code_expires_in: None,
+            code_challenge: None,
+            code_challenge_method: None,
+            access: Some(format!("{}-token", client_id)),
+            access_create_at: Some(now),
+            access_expires_in: Some(std::time::Duration::from_secs(3600)),
+            refresh: None,
+            refresh_create_at: None,
+            refresh_expires_in: None,
+        };
+        storage.save_oauth_token(&token).await.unwrap();
+    }
+    let run = crate::model::Run {
+        id: uuid::Uuid::new_v4(),
+        flow_name: crate::model::FlowName::new("acme_flow").unwrap(),
+        event: HashMap::new(),
+        vars: HashMap::new(),
+        status: crate::model::RunStatus::Succeeded,
+        started_at: now,
+        ended_at: None,
+        steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: Some("acme".to_string()),
+    };
+    storage.save_run(&run).await.unwrap();
+
+    // Neither dropping the token nor using a tenantless client lists acme's runs
+    for token in [None, Some("bare-client-token")] {
+        let response = app.clone().oneshot(list_runs(token)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{:?}", token);
+    }
+
+    let response = app
+        .oneshot(list_runs(Some("acme-client-token")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let runs: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(runs["items"][0]["id"], run.id.to_string());
+}
+
+#[tokio::test]
+async fn test_flow_json_yaml_round_trip() {
+    use crate::dsl::{FlowFormat, parse_string, parse_string_as};
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let (state, _env) = create_test_state().await;
+    let app = build_operation_routes(&state);
+
+    async fn send(app: &axum::Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    let get = |format: &str| {
+        Request::builder()
+            .uri(format!("/flows/round_trip?format={}", format))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let json_flow = json!({
+        "name": "round_trip",
+        "version": "1.0.0",
+        "on": "cli.manual",
+        "vars": {"zeta": 1, "alpha": "a", "mid": [1, 2]},
+        "steps": [{
+            "id": "greet",
+            "use": "core.echo",
+            "with": {"text": "Hello {{ vars.alpha }}", "extra": {"b": 2, "a": 1}}
+        }]
+    })
+    .to_string();
+    let original = parse_string(&json_flow, None).unwrap();
+
+    // Deploy as JSON inside the JSON envelope
+    let (status, _) = send(
+        &app,
+        Request::builder()
+            .method("POST")
+            .uri("/flows")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({ "content": json_flow }).to_string()))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // JSON drafts deploy like YAML ones
+    let (status, body) = send(
+        &app,
+        Request::builder()
+            .method("POST")
+            .uri("/flows/round_trip/deploy")
+            .header("content-type", "application/json")
+            .body(Body::from("{}"))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["version"], "1.0.0");
+
+    // Fetch as YAML; output is stable across calls
+    let (status, body) = send(&app, get("yaml")).await;
+    assert_eq!(status, StatusCode::OK);
+    let yaml = body["content"].as_str().unwrap().to_string();
+    assert_eq!(send(&app, get("yaml")).await.1["content"], yaml.as_str());
+    let from_yaml = parse_string_as(&yaml, FlowFormat::Yaml, None).unwrap();
+    assert_eq!(from_yaml, original);
+
+    // Re-deploy the YAML as a raw body
+    let (status, body) = send(
+        &app,
+        Request::builder()
+            .method("POST")
+            .uri("/flows")
+            .header("content-type", "application/yaml")
+            .body(Body::from(yaml))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "updated");
+
+    let (status, body) = send(&app, get("json")).await;
+    assert_eq!(status, StatusCode::OK);
+    let content = body["content"].as_str().unwrap();
+    assert_eq!(FlowFormat::detect(content), FlowFormat::Json);
+    assert_eq!(parse_string(content, None).unwrap(), original);
+
+    // Other content types are rejected
+    let (status, _) = send(
+        &app,
+        Request::builder()
+            .method("POST")
+            .uri("/flows")
+            .header("content-type", "text/plain")
+            .body(Body::from("name: x\nsteps: []"))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_render_flow_preview() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let (state, _env) = create_test_state().await;
+    let app = build_operation_routes(&state);
+
+    let render = |body: Value| {
+        Request::builder()
+            .method("POST")
+            .uri("/flows/render")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let content = "name: {{ flow_name }}\nsteps:\n  - id: greet\n    use: core.echo\n    with:\n      text: \"{{ greeting }}\"\n";
+
+    let response = app
+        .clone()
+        .oneshot(render(json!({
+            "content": content,
+            "vars": {"flow_name": "hello", "greeting": "hi"},
+            "diff": true
+        })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let result: Value = serde_json::from_slice(&body).unwrap();
+    let rendered = result["rendered"].as_str().unwrap();
+    assert!(rendered.starts_with("name: hello\n"));
+    assert!(rendered.contains("text: \"hi\""));
+    let diff = result["diff"].as_str().unwrap();
+    assert!(diff.contains("-name: {{ flow_name }}\n+name: hello\n"));
+    assert!(diff.contains("+      text: \"hi\"\n"));
+    assert!(!diff.contains("-  - id: greet"));
+
+    // Undefined variables are reported with their position instead of rendering empty
+    let response = app
+        .oneshot(render(json!({
+            "content": content,
+            "vars": {"flow_name": "hello"}
+        })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let error: Value = serde_json::from_slice(&body).unwrap();
+    let message = error["error"]["message"].as_str().unwrap();
+    assert!(message.contains("Variable not found"), "{}", message);
+    assert!(message.contains("line 6, column 17"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_render_flow_reads_only_the_flows_dir() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let (state, env) = create_test_state().await;
+    let app = build_operation_routes(&state);
+    let flows_dir = crate::config::get_flows_dir(&env.deps.config);
+    std::fs::create_dir_all(&flows_dir).unwrap();
+    std::fs::write(
+        std::path::Path::new(&flows_dir).join("greet.flow.yaml"),
+        "name: greet\nsteps:\n  - id: greet\n    use: core.echo\n    with:\n      text: \"{{ greeting }}\"\n",
+    )
+    .unwrap();
+
+    let render = |body: Value| {
+        Request::builder()
+            .method("POST")
+            .uri("/flows/render")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    let response = app
+        .clone()
+        .oneshot(render(json!({"file": "greet", "vars": {"greeting": "hi"}})))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let result: Value = serde_json::from_slice(&body).unwrap();
+    assert!(
+        result["rendered"]
+            .as_str()
+            .unwrap()
+            .contains("text: \"hi\"")
+    );
+
+    // Paths, and vars given as a file, are never read from disk
+    for body in [
+        json!({"file": "/etc/passwd"}),
+        json!({"file": "../greet.flow.yaml"}),
+        json!({"file": "greet", "vars": "/etc/passwd"}),
+    ] {
+        let response = app.clone().oneshot(render(body.clone())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", body);
+    }
+}
+
+#[tokio::test]
+async fn test_oversized_inline_flow_is_rejected() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let env = TestEnvironment::new().await;
+    let config = crate::config::Config {
+        limits: Some(crate::config::LimitsConfig {
+            max_flow_file_size: 256,
+            ..Default::default()
+        }),
+        ..(*env.deps.config).clone()
+    };
+    let state = test_state(crate::core::Dependencies {
+        config: Arc::new(config),
+        ..env.deps.clone()
+    });
+    let app = build_operation_routes(&state);
+
+    let small = "name: small\non: cli.manual\nsteps:\n  - id: s\n    use: core.echo\n    with:\n      text: hi\n";
+    let large = format!(
+        "name: large\nsteps:\n  - id: s\n    use: core.echo\n    with:\n      text: \"{}\"\n",
+        "x".repeat(512)
+    );
+    let bundle = |content: &str| json!({"bundle": {"bundle_version": 1, "name": "large", "content": content, "entries": []}});
+
+    for (uri, body) in [
+        ("/flows", json!({"content": large})),
+        ("/flows/render", json!({"content": large})),
+        ("/flows/import", bundle(&large)),
+    ] {
+        let request = Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE, "{}", uri);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["error"]["type"], "payload_too_large", "{}", uri);
+        let message = error["error"]["message"].as_str().unwrap();
+        assert!(message.contains("(256 bytes)"), "{}: {}", uri, message);
+    }
+
+    // Nothing oversized was written, and content under the limit still saves
+    assert!(
+        state
+            .registry
+            .execute("get_flow", json!({"name": "large"}))
+            .await
+            .is_err()
+    );
+    let request = Request::builder()
+        .method("POST")
+        .uri("/flows")
+        .header("content-type", "application/json")
+        .body(Body::from(json!({"content": small}).to_string()))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_slow_operation_times_out_without_orphaning_run() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let (state, env) = create_test_state().await;
+    let app = with_request_timeout(build_operation_routes(&state), 1);
+
+    let flow_content = r#"
+name: slow_flow
+on: cli.manual
+steps:
+  - id: pause
+    wait:
+      seconds: 2
+  - id: done
+    use: core.echo
+    with:
+      text: "finished"
+"#;
+    state
+        .registry
+        .execute(
+            "save_flow",
+            json!({"name": "slow_flow", "content": flow_content}),
+        )
+        .await
+        .unwrap();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/runs")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({"flow_name": "slow_flow", "draft": true}).to_string(),
+        ))
+        .unwrap();
+    let started = std::time::Instant::now();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let error: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["error"]["type"], "timeout");
+
+    // The run keeps executing and its final state is recorded
+    let mut status = None;
+    for _ in 0..50 {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let runs = env.deps.storage.list_runs(10, 0).await.unwrap();
+        status = runs.first().map(|run| run.status);
+        if status == Some(crate::model::RunStatus::Succeeded) {
+            break;
+        }
+    }
+    assert_eq!(status, Some(crate::model::RunStatus::Succeeded));
+
+    // Fast requests are unaffected
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/flows")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+/// Boot every listener on ephemeral ports and return each surface's address
+async fn serve_ephemeral(
+    http_config: HttpConfig,
+) -> (
+    HashMap<&'static str, SocketAddr>,
+    tokio::sync::oneshot::Sender<()>,
+    TestEnvironment,
+) {
+    let env = TestEnvironment::new().await;
+    let routers = build_routers(&env.deps, &http_config, ServerInterfaces::default())
+        .await
+        .unwrap();
+    let listeners = bind_listeners(&http_config, routers).await.unwrap();
+    let addrs = listeners
+        .iter()
+        .map(|l| (l.surface, l.listener.local_addr().unwrap()))
+        .collect();
+
+    let (stop, stopped) = tokio::sync::oneshot::channel();
+    tokio::spawn(serve_listeners(listeners, async {
+        let _ = stopped.await;
+    }));
+    (addrs, stop, env)
+}
+
+async fn status_of(addr: SocketAddr, method: reqwest::Method, path: &str) -> u16 {
+    reqwest::Client::new()
+        .request(method, format!("http://{}{}", addr, path))
+        .header("content-type", "application/json")
+        .header("accept", "application/json, text/event-stream")
+        .body("{}")
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+fn ephemeral_http_config() -> HttpConfig {
+    HttpConfig {
+        port: 0,
+        ..crate::config::Config::default().http.unwrap()
+    }
+}
+
+#[tokio::test]
+async fn test_separate_binds_route_each_surface_to_its_listener() {
+    use reqwest::Method;
+
+    let http_config = HttpConfig {
+        mcp_bind: Some("127.0.0.1:0".to_string()),
+        metrics_bind: Some("127.0.0.1:0".to_string()),
+        ..ephemeral_http_config()
+    };
+    let (addrs, stop, _env) = serve_ephemeral(http_config).await;
+    assert_eq!(addrs.len(), 3);
+    let (main, mcp, metrics) = (addrs["main"], addrs["mcp"], addrs["metrics"]);
+
+    // Operations only on the main bind
+    assert_eq!(status_of(main, Method::GET, "/flows").await, 200);
+    assert_eq!(status_of(mcp, Method::GET, "/flows").await, 404);
+    assert_eq!(status_of(metrics, Method::GET, "/flows").await, 404);
+
+    // MCP only on its bind
+    assert_ne!(status_of(mcp, Method::POST, "/mcp").await, 404);
+    // GET /mcp on the main bind is the list_mcp_servers operation, not the transport
+    assert_eq!(status_of(main, Method::POST, "/mcp").await, 405);
+    assert_eq!(status_of(metrics, Method::POST, "/mcp").await, 404);
+
+    // Health and metrics only on the metrics bind
+    for path in ["/healthz", "/readyz", "/metrics"] {
+        assert_eq!(status_of(metrics, Method::GET, path).await, 200, "{}", path);
+        assert_eq!(status_of(main, Method::GET, path).await, 404, "{}", path);
+        assert_eq!(status_of(mcp, Method::GET, path).await, 404, "{}", path);
+    }
+
+    stop.send(()).unwrap();
+}
+
+#[tokio::test]
+async fn test_default_binds_serve_everything_on_one_listener() {
+    use reqwest::Method;
+
+    let (addrs, stop, _env) = serve_ephemeral(ephemeral_http_config()).await;
+    assert_eq!(addrs.len(), 1);
+    let main = addrs["main"];
+
+    assert_eq!(status_of(main, Method::GET, "/flows").await, 200);
+    assert_ne!(status_of(main, Method::POST, "/mcp").await, 404);
+    assert_eq!(status_of(main, Method::GET, "/healthz").await, 200);
+    assert_eq!(status_of(main, Method::GET, "/metrics").await, 200);
+
+    stop.send(()).unwrap();
+}
+
+#[tokio::test]
+async fn test_request_id_correlates_runs() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let (state, env) = create_test_state().await;
+    let app =
+        build_operation_routes(&state).layer(axum::middleware::from_fn(request_id_middleware));
+    state
+        .registry
+        .execute(
+            "save_flow",
+            json!({"name": "echo_flow", "content": "name: echo_flow\non: cli.manual\nsteps:\n  - id: s\n    use: core.echo\n    with:\n      text: hi\n"}),
+        )
+        .await
+        .unwrap();
+
+    let start_run = |request_id: Option<&str>| {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/runs")
+            .header("content-type", "application/json");
+        if let Some(id) = request_id {
+            request = request.header(REQUEST_ID_HEADER, id);
+        }
+        request
+            .body(Body::from(
+                json!({"flow_name": "echo_flow", "draft": true}).to_string(),
+            ))
+            .unwrap()
+    };
+
+    // A client-supplied ID is echoed and recorded on the run it starts
+    let response = app
+        .clone()
+        .oneshot(start_run(Some("req-abc")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-abc");
+    let runs = env.deps.storage.list_runs(10, 0).await.unwrap();
+    assert_eq!(runs[0].correlation_id.as_deref(), Some("req-abc"));
+
+    // Otherwise one is generated
+    for request_id in [None, Some("has spaces")] {
+        let response = app.clone().oneshot(start_run(request_id)).await.unwrap();
+        let generated = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(generated).is_ok(), "{}", generated);
+    }
+}
+
+/// Log output captured by a test's tracing subscriber
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<parking_lot::Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_request_id_reaches_step_logs() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    // The current-thread test runtime keeps spawned run tasks on this thread
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::registry().with(
+        tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            }),
+    );
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let (state, env) = create_test_state().await;
+    let app =
+        build_operation_routes(&state).layer(axum::middleware::from_fn(request_id_middleware));
+    state
+        .registry
+        .execute(
+            "save_flow",
+            json!({"name": "log_flow", "content": "name: log_flow\non: cli.manual\nsteps:\n  - id: s\n    use: core.echo\n    with:\n      text: logged-by-step\n"}),
+        )
+        .await
+        .unwrap();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/runs")
+                .header("content-type", "application/json")
+                .header(REQUEST_ID_HEADER, "req-logs-42")
+                .body(Body::from(
+                    json!({"flow_name": "log_flow", "draft": true}).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-logs-42");
+
+    let runs = env.deps.storage.list_runs(10, 0).await.unwrap();
+    assert_eq!(runs[0].status, crate::model::RunStatus::Succeeded);
+
+    let logs = String::from_utf8(logs.0.lock().clone()).unwrap();
+    let step_log = logs
+        .lines()
+        .find(|line| line.contains("echo: logged-by-step"))
+        .unwrap_or_else(|| panic!("no step log in:\n{}", logs));
+    // The tool span carries the request ID, as well as the request span around it
+    assert!(step_log.contains("tool{"), "{}", step_log);
+    assert!(
+        step_log
+            .split("tool{")
+            .nth(1)
+            .is_some_and(|tool| tool.contains("req-logs-42")),
+        "{}",
+        step_log
+    );
+}
+
+#[tokio::test]
+async fn test_duplicate_run_is_conflict_with_run_id() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let (state, _env) = create_test_state().await;
+    let app = build_operation_routes(&state);
+    state
+        .registry
+        .execute(
+            "save_flow",
+            json!({"name": "dup_flow", "content": "name: dup_flow\non: cli.manual\nsteps:\n  - id: s\n    use: core.echo\n    with:\n      text: hi\n"}),
+        )
+        .await
+        .unwrap();
+
+    let start_run = || {
+        Request::builder()
+            .method("POST")
+            .uri("/runs")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"flow_name": "dup_flow", "draft": true, "idempotency_key": "order-42"})
+                    .to_string(),
+            ))
+            .unwrap()
+    };
+    let read = |response: axum::response::Response| async move {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice::<Value>(&body).unwrap()
+    };
+
+    let response = app.clone().oneshot(start_run()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let first = read(response).await;
+
+    // Triggering again with the same key points at the run that already exists
+    let response = app.oneshot(start_run()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let error = read(response).await;
+    assert_eq!(error["error"]["type"], "duplicate_run");
+    assert_eq!(error["error"]["status"], 409);
+    assert_eq!(error["error"]["run_id"], first["run_id"]);
+}
+
+#[tokio::test]
+async fn test_drain_rejects_runs_and_fails_readiness() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let (state, _env) = create_test_state().await;
+    let app = build_operation_routes(&state);
+    state
+        .registry
+        .execute(
+            "save_flow",
+            json!({"name": "drain_flow", "content": "name: drain_flow\non: cli.manual\nsteps:\n  - id: s\n    use: core.echo\n    with:\n      text: \"{{ event.n }}\"\n"}),
+        )
+        .await
+        .unwrap();
+
+    let post = |uri: &str, body: Value| {
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let start_run = |n: u32| {
+        post(
+            "/runs",
+            json!({"flow_name": "drain_flow", "draft": true, "event": {"n": n}}),
+        )
+    };
+    let readiness = |state: AppState| async move {
+        match readiness_handler(State(state)).await {
+            Ok(_) => StatusCode::OK,
+            Err((status, _)) => status,
+        }
+    };
+
+    let response = app
+        .clone()
+        .oneshot(post("/admin/drain", json!({})))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        readiness(state.clone()).await,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    let response = app.clone().oneshot(start_run(1)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let error: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["error"]["type"], "draining");
+
+    let response = app
+        .clone()
+        .oneshot(post("/admin/undrain", json!({})))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(readiness(state.clone()).await, StatusCode::OK);
+    let response = app.oneshot(start_run(2)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_runs_export_streams_attachment() {
+    let (addrs, stop, env) = serve_ephemeral(ephemeral_http_config()).await;
+    let main = addrs["main"];
+
+    let run = crate::model::Run {
+        id: uuid::Uuid::new_v4(),
+        flow_name: crate::model::FlowName::new("export_flow").unwrap(),
+        event: HashMap::new(),
+        vars: HashMap::new(),
+        status: crate::model::RunStatus::Succeeded,
+        started_at: chrono::Utc::now(),
+        ended_at: None,
+        steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
+    };
+    env.deps.storage.save_run(&run).await.unwrap();
+
+    let response = reqwest::get(format!(
+        "http://{}/runs/export?format=jsonl&include_steps=true",
+        main
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+    let disposition = response.headers()["content-disposition"].to_str().unwrap();
+    assert!(disposition.starts_with("attachment; filename=\"runs-"));
+    assert!(disposition.ends_with(".jsonl\""), "{}", disposition);
+    let body = response.text().await.unwrap();
+    let row: serde_json::Value = serde_json::from_str(body.trim_end()).unwrap();
+    assert_eq!(row["run_id"], run.id.to_string());
+    assert_eq!(row["status"], "SUCCEEDED");
+
+    // Other run routes still resolve alongside the export
+    assert_eq!(
+        status_of(main, reqwest::Method::GET, &format!("/runs/{}", run.id)).await,
+        200
+    );
+    // Unknown formats are rejected before streaming starts
+    assert_eq!(
+        status_of(main, reqwest::Method::GET, "/runs/export?format=xml").await,
+        400
+    );
+    // Bearer tokens scope the export to their tenant, so a bad one is refused
+    let response = reqwest::Client::new()
+        .get(format!("http://{}/runs/export", main))
+        .bearer_auth("not-a-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+
+    stop.send(()).unwrap();
+}
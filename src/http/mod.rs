@@ -3,10 +3,14 @@
 //! Provides REST API for all BeemFlow operations with complete parity
 //! with CLI and MCP interfaces.
 
+pub mod approval;
+pub mod export;
 pub mod session;
 pub mod template;
 pub mod webhook;
 
+use self::approval::{ApprovalState, create_approval_routes};
+use self::export::create_export_routes;
 use self::webhook::{WebhookManagerState, create_webhook_routes};
 use crate::auth::{
     OAuthConfig, OAuthServerState,
@@ -27,7 +31,7 @@ use axum::{
 };
 use parking_lot::RwLock;
 use serde_json::{Value, json};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tower::ServiceBuilder;
@@ -36,6 +40,7 @@ use tower_http::{
     cors::CorsLayer,
     trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer},
 };
+use tracing::Instrument;
 
 /// Application state shared across handlers
 #[derive(Clone)]
@@ -71,7 +76,21 @@ pub struct AppError(BeemFlowError);
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        // A step failure wrapping a cause answers as the cause would
+        if let BeemFlowError::StepExecution {
+            source: Some(source),
+            ..
+        } = self.0
+        {
+            return AppError(*source).into_response();
+        }
+
         let (status, error_type, message) = match &self.0 {
+            BeemFlowError::Validation(msg) if msg.starts_with(crate::dsl::FLOW_TOO_LARGE) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "payload_too_large",
+                msg.clone(),
+            ),
             BeemFlowError::Validation(msg) => {
                 (StatusCode::BAD_REQUEST, "validation_error", msg.clone())
             }
@@ -91,7 +110,9 @@ impl IntoResponse for AppError {
                     )
                 }
             },
-            BeemFlowError::StepExecution { step_id, message } => {
+            BeemFlowError::StepExecution {
+                step_id, message, ..
+            } => {
                 // Log full error details internally
                 tracing::error!("Step execution failed: {} - {}", step_id, message);
                 (
@@ -100,6 +121,19 @@ impl IntoResponse for AppError {
                     "A step execution error occurred".to_string(),
                 )
             }
+            BeemFlowError::DuplicateRun { .. } => {
+                (StatusCode::CONFLICT, "duplicate_run", self.0.to_string())
+            }
+            BeemFlowError::Draining => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "draining",
+                self.0.to_string(),
+            ),
+            BeemFlowError::QueueFull { .. } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "queue_full",
+                self.0.to_string(),
+            ),
             BeemFlowError::OAuth(msg) => (StatusCode::UNAUTHORIZED, "auth_error", msg.clone()),
             BeemFlowError::Adapter(msg) => (StatusCode::BAD_GATEWAY, "adapter_error", msg.clone()),
             BeemFlowError::Mcp(msg) => (StatusCode::BAD_GATEWAY, "mcp_error", msg.clone()),
@@ -131,13 +165,17 @@ impl IntoResponse for AppError {
             "HTTP request error response"
         );
 
-        let body = json!({
+        let mut body = json!({
             "error": {
                 "type": error_type,
                 "message": message,
                 "status": status.as_u16(),
             }
         });
+        // Point clients at the run that already exists
+        if let BeemFlowError::DuplicateRun { run_id, .. } = &self.0 {
+            body["error"]["run_id"] = json!(run_id);
+        }
 
         (status, Json(body)).into_response()
     }
@@ -152,6 +190,221 @@ where
     }
 }
 
+/// Build an operation input from a single path parameter plus the request query string
+///
+/// Used by generated GET/DELETE routes so optional flags (e.g. `?include_inputs=true`)
+/// can accompany the path parameter. The path value always wins over a query
+/// parameter of the same name.
+pub(crate) fn input_from_path_and_query<T: serde::de::DeserializeOwned>(
+    param: &str,
+    value: &str,
+    query: Option<&str>,
+) -> Result<T> {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, val) in url::form_urlencoded::parse(query.unwrap_or_default().as_bytes()) {
+        if key != param {
+            serializer.append_pair(&key, &val);
+        }
+    }
+    serializer.append_pair(param, value);
+
+    let uri: axum::http::Uri = format!("/?{}", serializer.finish())
+        .parse()
+        .map_err(|e| BeemFlowError::validation(format!("Invalid query string: {}", e)))?;
+
+    axum::extract::Query::<T>::try_from_uri(&uri)
+        .map(|axum::extract::Query(input)| input)
+        .map_err(|e| BeemFlowError::validation(format!("Invalid input: {}", e)))
+}
+
+/// Content types accepted as raw flow definitions
+const RAW_BODY_CONTENT_TYPES: &[&str] = &[
+    "application/yaml",
+    "application/x-yaml",
+    "text/yaml",
+    "text/x-yaml",
+    "application/toml",
+];
+
+/// Build an operation input from a request body that is either a JSON envelope or raw text
+///
+/// Used by generated routes declared with `raw_body = "<field>"`. JSON bodies (or
+/// bodies without a content type) are deserialized as the input directly. YAML
+/// and TOML bodies are placed in `raw_field` verbatim, and query parameters supply the
+/// remaining fields (e.g. `POST /flows?name=hello`).
+pub(crate) fn input_from_body<T: serde::de::DeserializeOwned>(
+    headers: &axum::http::HeaderMap,
+    query: Option<&str>,
+    body: &[u8],
+    raw_field: &str,
+) -> Result<T> {
+    let content_type = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| {
+            v.split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        });
+
+    let value = match content_type.as_deref() {
+        None | Some("application/json") => serde_json::from_slice::<Value>(body)
+            .map_err(|e| BeemFlowError::validation(format!("Invalid JSON body: {}", e)))?,
+        Some(ct) if RAW_BODY_CONTENT_TYPES.contains(&ct) => {
+            let text = std::str::from_utf8(body)
+                .map_err(|_| BeemFlowError::validation("Request body is not valid UTF-8"))?;
+            let mut fields = serde_json::Map::new();
+            for (key, val) in url::form_urlencoded::parse(query.unwrap_or_default().as_bytes()) {
+                fields.insert(key.into_owned(), Value::String(val.into_owned()));
+            }
+            fields.insert(raw_field.to_string(), Value::String(text.to_string()));
+            Value::Object(fields)
+        }
+        Some(ct) => {
+            return Err(BeemFlowError::validation(format!(
+                "Unsupported content type '{}'; expected application/json, application/yaml or application/toml",
+                ct
+            )));
+        }
+    };
+
+    serde_json::from_value(value)
+        .map_err(|e| BeemFlowError::validation(format!("Invalid input: {}", e)))
+}
+
+/// Answer with 504 when a request takes longer than `timeout_secs` (0 disables)
+///
+/// Only operation routes are wrapped; MCP's streaming transport and webhooks are
+/// left alone.
+fn with_request_timeout(router: Router, timeout_secs: u64) -> Router {
+    if timeout_secs == 0 {
+        return router;
+    }
+    let timeout = std::time::Duration::from_secs(timeout_secs);
+    router.layer(axum::middleware::from_fn(move |req, next| {
+        request_timeout_middleware(timeout, req, next)
+    }))
+}
+
+/// Run the handler on its own task and stop waiting for it after `timeout`
+///
+/// The task is detached rather than cancelled, so a run that is still executing
+/// finishes and records its state instead of being left half-written.
+async fn request_timeout_middleware(
+    timeout: std::time::Duration,
+    req: Request,
+    next: Next,
+) -> Response {
+    let handler = tokio::spawn(crate::event::inherit_correlation_id(next.run(req)));
+
+    let (status, error_type, message) = match tokio::time::timeout(timeout, handler).await {
+        Ok(Ok(response)) => return response,
+        Ok(Err(e)) => {
+            tracing::error!("Operation handler failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal_error",
+                "An internal error occurred".to_string(),
+            )
+        }
+        Err(_) => {
+            tracing::warn!(
+                "Operation request exceeded {}s; continuing in the background",
+                timeout.as_secs()
+            );
+            (
+                StatusCode::GATEWAY_TIMEOUT,
+                "timeout",
+                format!(
+                    "Request did not complete within {}s; any run it started continues in the background",
+                    timeout.as_secs()
+                ),
+            )
+        }
+    };
+
+    let body = json!({
+        "error": {
+            "type": error_type,
+            "message": message,
+            "status": status.as_u16(),
+        }
+    });
+    (status, Json(body)).into_response()
+}
+
+/// Scope an operation request carrying a bearer token to its principal's tenant
+///
+/// Requests without an `Authorization` header run unscoped until tenants are
+/// configured, and are refused after that; an invalid token is always rejected
+/// rather than silently widening what the request can see. See
+/// [`crate::auth::tenant`].
+async fn tenant_middleware(
+    State(storage): State<Arc<dyn crate::storage::Storage>>,
+    req: Request,
+    next: Next,
+) -> std::result::Result<Response, AppError> {
+    let user = match req.headers().get(axum::http::header::AUTHORIZATION) {
+        Some(header) => {
+            let token = header
+                .to_str()
+                .ok()
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .ok_or_else(|| BeemFlowError::auth("Invalid Authorization header format"))?;
+            Some(crate::auth::validate_token(&storage, token).await?)
+        }
+        None => None,
+    };
+
+    match crate::auth::tenant::resolve_tenant(&storage, user.as_ref()).await? {
+        Some(tenant_id) => Ok(crate::auth::with_tenant(tenant_id, next.run(req)).await),
+        None => Ok(next.run(req).await),
+    }
+}
+
+/// Header carrying a request's ID, echoed on every response
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied request ID that is kept
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// ID of the request being handled, in the request's extensions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Middleware tagging each request with an ID, echoed in `X-Request-Id`
+///
+/// A client-supplied `X-Request-Id` is kept if it is short and printable;
+/// otherwise a new one is generated. The ID is stored as a [`RequestId`]
+/// extension, recorded on a `request` span around the handler, and becomes the
+/// correlation ID of the runs and events the request causes (see
+/// [`crate::event`]), so their steps' logs carry it too.
+async fn request_id_middleware(mut req: Request, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LEN
+                && id.bytes().all(|b| b.is_ascii_graphic())
+        })
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+    let span = tracing::info_span!("request", request_id = %request_id);
+    let mut response = crate::event::with_correlation_id(request_id.clone(), next.run(req))
+        .instrument(span)
+        .await;
+    if let Ok(value) = axum::http::HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
 /// Marker to indicate the request is over HTTPS (from X-Forwarded-Proto)
 #[derive(Clone, Copy, Debug)]
 pub struct IsHttps(pub bool);
@@ -199,7 +452,14 @@ async fn proxy_headers_middleware(
 // in build_operation_routes() from operation metadata
 
 /// Start the HTTP server with configurable interfaces
-pub async fn start_server(config: Config, interfaces: ServerInterfaces) -> Result<()> {
+///
+/// With `reload` set to the config file path, changes to the config, the local
+/// registry and the flows directory are applied while serving.
+pub async fn start_server(
+    config: Config,
+    interfaces: ServerInterfaces,
+    reload: Option<std::path::PathBuf>,
+) -> Result<()> {
     // Initialize telemetry
     crate::telemetry::init(config.tracing.as_ref())?;
 
@@ -215,11 +475,83 @@ pub async fn start_server(config: Config, interfaces: ServerInterfaces) -> Resul
         enable_oauth_server: false,
         oauth_issuer: None,
         public_url: None,
+        request_timeout_secs: crate::config::default_request_timeout_secs(),
+        mcp_bind: None,
+        metrics_bind: None,
+        reload_templates: false,
     });
 
     // Use centralized dependency creation from core module
     let dependencies = crate::core::create_dependencies(&config).await?;
 
+    let routers = build_routers(&dependencies, &http_config, interfaces).await?;
+    let listeners = bind_listeners(&http_config, routers).await?;
+
+    // Publish outbox events left by runs that finished while no dispatcher ran
+    let dispatcher_cancel = tokio_util::sync::CancellationToken::new();
+    let dispatcher = tokio::spawn(dependencies.engine.outbox().clone().run(
+        crate::event::outbox::DEFAULT_DISPATCH_INTERVAL,
+        dispatcher_cancel.clone(),
+    ));
+    // Retry outbound deliveries queued by this or a previous process
+    let deliveries = tokio::spawn(dependencies.engine.deliveries().clone().run(
+        crate::engine::delivery::DEFAULT_RETRY_INTERVAL,
+        dispatcher_cancel.clone(),
+    ));
+    let reloader = match reload {
+        Some(config_path) => Some(
+            crate::core::reload::spawn_reloader(
+                &dependencies,
+                &config_path,
+                dispatcher_cancel.clone(),
+            )
+            .await,
+        ),
+        None => None,
+    };
+
+    // Paused runs stored under plaintext tokens by earlier versions
+    match dependencies.engine.rehash_paused_runs().await {
+        Ok(0) => {}
+        Ok(moved) => tracing::info!("Rehashed {} paused runs stored under plain tokens", moved),
+        Err(e) => tracing::error!("Failed to rehash paused run tokens: {}", e),
+    }
+
+    // Resume runs a previous process was executing when it stopped
+    if config.checkpoint_runs.unwrap_or(false) {
+        let engine = dependencies.engine.clone();
+        tokio::spawn(async move {
+            match engine.recover_checkpointed_runs().await {
+                Ok(recovered) if !recovered.is_empty() => {
+                    tracing::info!("Recovered {} interrupted runs", recovered.len())
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("Failed to recover interrupted runs: {}", e),
+            }
+        });
+    }
+
+    // Run every listener until SIGTERM/SIGINT, then drain them together
+    tracing::info!("Server ready to accept connections");
+    let served = serve_listeners(listeners, shutdown_signal()).await;
+    dispatcher_cancel.cancel();
+    let _ = dispatcher.await;
+    let _ = deliveries.await;
+    if let Some(reloader) = reloader {
+        let _ = reloader.await;
+    }
+    served?;
+
+    tracing::info!("Server shutdown complete");
+    Ok(())
+}
+
+/// Build the routers for every listener from shared application state
+async fn build_routers(
+    dependencies: &crate::core::Dependencies,
+    http_config: &HttpConfig,
+    interfaces: ServerInterfaces,
+) -> Result<ServerRouters> {
     // Create registry (takes ownership, so we clone dependencies to keep using them below)
     let registry = Arc::new(OperationRegistry::new(dependencies.clone()));
 
@@ -227,8 +559,10 @@ pub async fn start_server(config: Config, interfaces: ServerInterfaces) -> Resul
     let session_store = Arc::new(session::SessionStore::new());
 
     // Initialize template renderer
-    let mut template_renderer = template::TemplateRenderer::new("static");
+    let mut template_renderer =
+        template::TemplateRenderer::new("static").with_reload(http_config.reload_templates);
     template_renderer.load_oauth_templates().await?;
+    template_renderer.load_approval_templates().await?;
     let template_renderer = Arc::new(template_renderer);
 
     let state = AppState {
@@ -256,92 +590,158 @@ pub async fn start_server(config: Config, interfaces: ServerInterfaces) -> Resul
     });
 
     // Create webhook manager state
-    let webhook_state = WebhookManagerState {
-        registry_manager: dependencies.registry_manager.clone(),
-        secrets_provider: dependencies.config.create_secrets_provider(),
-        storage: dependencies.storage.clone(),
-        engine: dependencies.engine.clone(),
-        config: dependencies.config.clone(),
-    };
+    let webhook_state = WebhookManagerState::new(dependencies);
 
-    // Build router with config for CORS
     // Note: All static assets are embedded in the binary - no file system access needed
-    let app = build_router(
+    Ok(build_router(
         state,
         webhook_state,
         oauth_server_state,
-        &http_config,
+        http_config,
         interfaces,
-        &dependencies,
-    );
+        dependencies,
+    ))
+}
 
-    // Determine bind address
-    let addr = format!("{}:{}", http_config.host, http_config.port);
-    let socket_addr: SocketAddr = addr
-        .parse()
-        .map_err(|e| BeemFlowError::config(format!("Invalid address {}: {}", addr, e)))?;
+/// Routers served by `start_server`, one per listener
+///
+/// `mcp` and `metrics` are only split out when `mcpBind` / `metricsBind` are set;
+/// otherwise their routes are part of `main`.
+struct ServerRouters {
+    main: Router,
+    mcp: Option<Router>,
+    metrics: Option<Router>,
+}
 
-    tracing::info!("Starting HTTP server on {}", socket_addr);
+/// A bound listener and the router it serves
+struct Listener {
+    /// Which surface this listener serves ("main", "mcp" or "metrics")
+    surface: &'static str,
+    listener: tokio::net::TcpListener,
+    router: Router,
+}
 
-    // Create TCP listener
-    let listener = tokio::net::TcpListener::bind(socket_addr).await?;
+/// Bind the main listener plus any per-interface overrides
+async fn bind_listeners(http_config: &HttpConfig, routers: ServerRouters) -> Result<Vec<Listener>> {
+    let main_addr = format!("{}:{}", http_config.host, http_config.port);
+    let mut listeners = vec![Listener {
+        surface: "main",
+        listener: bind(&main_addr).await?,
+        router: routers.main,
+    }];
 
-    // Create server with graceful shutdown
-    let server = axum::serve(listener, app);
+    let overrides = [
+        ("mcp", &http_config.mcp_bind, routers.mcp),
+        ("metrics", &http_config.metrics_bind, routers.metrics),
+    ];
+    for (surface, addr, router) in overrides {
+        if let (Some(addr), Some(router)) = (addr, router) {
+            listeners.push(Listener {
+                surface,
+                listener: bind(addr).await?,
+                router,
+            });
+        }
+    }
 
-    // Set up graceful shutdown signal handler
-    let shutdown_signal = async {
-        // Wait for SIGTERM (Docker/Kubernetes) or SIGINT (Ctrl+C)
-        let ctrl_c = async {
-            tokio::signal::ctrl_c()
-                .await
-                .expect("failed to install Ctrl+C signal handler");
-        };
+    for listener in &listeners {
+        tracing::info!(
+            "Starting HTTP server ({}) on {}",
+            listener.surface,
+            listener.listener.local_addr()?
+        );
+    }
+    Ok(listeners)
+}
 
-        #[cfg(unix)]
-        let terminate = async {
-            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
-                .expect("failed to install SIGTERM signal handler")
-                .recv()
-                .await;
-        };
+/// Bind a TCP listener to a `host:port` address
+async fn bind(addr: &str) -> Result<tokio::net::TcpListener> {
+    let socket_addr: SocketAddr = addr
+        .parse()
+        .map_err(|e| BeemFlowError::config(format!("Invalid address {}: {}", addr, e)))?;
+    tokio::net::TcpListener::bind(socket_addr)
+        .await
+        .map_err(|e| BeemFlowError::config(format!("Failed to bind {}: {}", addr, e)))
+}
 
-        #[cfg(not(unix))]
-        let terminate = std::future::pending::<()>();
+/// Serve every listener until `shutdown` resolves, then shut them all down gracefully
+async fn serve_listeners(
+    listeners: Vec<Listener>,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+) -> Result<()> {
+    let token = tokio_util::sync::CancellationToken::new();
+    let trigger = token.clone();
+    tokio::spawn(async move {
+        shutdown.await;
+        trigger.cancel();
+    });
 
-        tokio::select! {
-            _ = ctrl_c => {
-                tracing::info!("Received SIGINT (Ctrl+C), initiating graceful shutdown...");
-            }
-            _ = terminate => {
-                tracing::info!("Received SIGTERM, initiating graceful shutdown...");
-            }
+    let servers = listeners.into_iter().map(|listener| {
+        let shutdown = token.clone().cancelled_owned();
+        async move {
+            axum::serve(listener.listener, listener.router)
+                .with_graceful_shutdown(shutdown)
+                .await
+                .map_err(|e| BeemFlowError::config(format!("Server error: {}", e)))
         }
+    });
+    // If one listener fails the others are dropped with it
+    futures::future::try_join_all(servers).await?;
+    Ok(())
+}
+
+/// Resolve on SIGTERM (Docker/Kubernetes) or SIGINT (Ctrl+C)
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C signal handler");
     };
 
-    // Run server with graceful shutdown
-    tracing::info!("Server ready to accept connections");
-    server
-        .with_graceful_shutdown(shutdown_signal)
-        .await
-        .map_err(|e| BeemFlowError::config(format!("Server error: {}", e)))?;
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM signal handler")
+            .recv()
+            .await;
+    };
 
-    tracing::info!("Server shutdown complete");
-    Ok(())
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {
+            tracing::info!("Received SIGINT (Ctrl+C), initiating graceful shutdown...");
+        }
+        _ = terminate => {
+            tracing::info!("Received SIGTERM, initiating graceful shutdown...");
+        }
+    }
 }
 
 /// Auto-generate routes from operation metadata using macro-generated registration functions
+///
+/// Requests are scoped to the tenant of their bearer token, if any.
 fn build_operation_routes(state: &AppState) -> Router {
-    let deps = state.registry.get_dependencies();
+    operation_routes(state.registry.get_dependencies()).layer(axum::middleware::from_fn_with_state(
+        state.storage.clone(),
+        tenant_middleware,
+    ))
+}
 
+/// Router with every operation's HTTP route, as mounted by the server
+pub(crate) fn operation_routes(deps: Arc<crate::core::Dependencies>) -> Router {
     // Use generated registration functions from each operation group
     // These functions call the http_route() method on each operation
     [
         crate::core::flows::flows::register_http_routes,
+        crate::core::events::events::register_http_routes,
         crate::core::runs::runs::register_http_routes,
         crate::core::tools::tools::register_http_routes,
         crate::core::mcp::mcp::register_http_routes,
+        crate::core::oauth::oauth::register_http_routes,
         crate::core::system::system::register_http_routes,
+        crate::core::webhooks::webhooks::register_http_routes,
     ]
     .into_iter()
     .fold(Router::new(), |router, register_fn| {
@@ -349,6 +749,67 @@ fn build_operation_routes(state: &AppState) -> Router {
     })
 }
 
+/// Names of operations whose declared `METHOD /path` routes to their own route
+///
+/// Requests are answered by a route layer before any handler runs, so checking
+/// has no side effects; used by the `system operations` parity check.
+pub(crate) async fn routed_operations(
+    deps: Arc<crate::core::Dependencies>,
+    metadata: &HashMap<String, crate::core::OperationMetadata>,
+) -> HashSet<String> {
+    use axum::extract::MatchedPath;
+    use tower::ServiceExt;
+
+    const MATCHED_HEADER: &str = "x-matched-path";
+
+    let router = operation_routes(deps).route_layer(axum::middleware::from_fn(
+        |request: Request, _next: Next| async move {
+            let matched = request
+                .extensions()
+                .get::<MatchedPath>()
+                .map(|path| path.as_str().to_string())
+                .unwrap_or_default();
+            (StatusCode::NO_CONTENT, [(MATCHED_HEADER, matched)]).into_response()
+        },
+    ));
+
+    let mut routed = HashSet::new();
+    for (op_name, meta) in metadata {
+        let (Some(method), Some(path)) = (meta.http_method, meta.http_path) else {
+            continue;
+        };
+        // Fill path parameters with placeholder segments
+        let uri: String = path
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with('{') {
+                    "placeholder"
+                } else {
+                    segment
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let Ok(request) = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(axum::body::Body::empty())
+        else {
+            continue;
+        };
+
+        if let Ok(response) = router.clone().oneshot(request).await
+            && response
+                .headers()
+                .get(MATCHED_HEADER)
+                .is_some_and(|matched| matched == path)
+        {
+            routed.insert(op_name.clone());
+        }
+    }
+    routed
+}
+
 /// Build the router with all endpoints
 fn build_router(
     state: AppState,
@@ -357,8 +818,9 @@ fn build_router(
     http_config: &HttpConfig,
     interfaces: ServerInterfaces,
     deps: &crate::core::Dependencies,
-) -> Router {
+) -> ServerRouters {
     let mut app = Router::new();
+    let mut mcp = Router::new();
 
     // Always serve static assets
     app = app.route("/static/{*path}", get(serve_static_asset));
@@ -377,7 +839,10 @@ fn build_router(
         session_store: state.session_store.clone(),
         template_renderer: state.template_renderer.clone(),
     });
-    let oauth_client_routes = create_oauth_client_routes(oauth_client_state);
+    // Credential listings are scoped to the tenant of a bearer token
+    let oauth_client_routes = create_oauth_client_routes(oauth_client_state).layer(
+        axum::middleware::from_fn_with_state(state.storage.clone(), tenant_middleware),
+    );
     app = app.merge(oauth_client_routes);
 
     // MCP routes (conditionally enabled)
@@ -400,23 +865,49 @@ fn build_router(
         });
 
         let mcp_routes = create_mcp_routes(mcp_state);
-        app = app.merge(mcp_routes);
+        mcp = mcp.merge(mcp_routes);
 
         // Add MCP metadata routes if OAuth is enabled
         if let Some(issuer) = oauth_issuer {
-            let base_url = http_config
-                .oauth_issuer
-                .clone()
-                .unwrap_or_else(|| format!("http://{}:{}", http_config.host, http_config.port));
+            let base_url =
+                http_config
+                    .oauth_issuer
+                    .clone()
+                    .unwrap_or_else(|| match &http_config.mcp_bind {
+                        Some(bind) => format!("http://{}", bind),
+                        None => format!("http://{}:{}", http_config.host, http_config.port),
+                    });
             let metadata_routes = create_mcp_metadata_routes(issuer, base_url);
-            app = app.merge(metadata_routes);
+            mcp = mcp.merge(metadata_routes);
         }
     }
 
     // HTTP API routes (conditionally enabled)
     if interfaces.http_api {
-        let operation_routes = build_operation_routes(&state);
+        let operation_routes = with_request_timeout(
+            build_operation_routes(&state),
+            http_config.request_timeout_secs,
+        );
         app = app.merge(operation_routes);
+
+        // Approval gates for core.approval steps
+        let approval_state = ApprovalState {
+            engine: deps.engine.clone(),
+            storage: deps.storage.clone(),
+            template_renderer: state.template_renderer.clone(),
+            require_auth: interfaces.oauth_server,
+        };
+        app = app.merge(create_approval_routes().with_state(approval_state));
+
+        // Run history download, streamed outside the request timeout
+        app = app.merge(
+            create_export_routes()
+                .layer(axum::middleware::from_fn_with_state(
+                    deps.storage.clone(),
+                    tenant_middleware,
+                ))
+                .with_state(deps.storage.clone()),
+        );
     }
 
     // Webhooks (always enabled)
@@ -430,11 +921,34 @@ fn build_router(
         .route("/healthz", get(health_handler))
         .route("/readyz", get(readiness_handler))
         .route("/metrics", get(metrics_handler))
-        .with_state(state.storage.clone());
-    app = app.merge(system_routes);
+        .with_state(state.clone());
+
+    // MCP and system endpoints get their own listener when a bind override is set
+    let mcp = match http_config.mcp_bind {
+        Some(_) => Some(with_middleware(mcp, http_config)),
+        None => {
+            app = app.merge(mcp);
+            None
+        }
+    };
+    let metrics = match http_config.metrics_bind {
+        Some(_) => Some(with_middleware(system_routes, http_config)),
+        None => {
+            app = app.merge(system_routes);
+            None
+        }
+    };
 
-    // Add comprehensive middleware stack
-    app.layer(
+    ServerRouters {
+        main: with_middleware(app, http_config),
+        mcp,
+        metrics,
+    }
+}
+
+/// Apply the middleware stack shared by every listener
+fn with_middleware(router: Router, http_config: &HttpConfig) -> Router {
+    router.layer(
         ServiceBuilder::new()
             // Proxy headers middleware (must come first to detect HTTPS)
             // Wrap config in Arc to avoid cloning on every request
@@ -445,6 +959,8 @@ fn build_router(
                     async move { proxy_headers_middleware(config, req, next).await }
                 }
             }))
+            // Request IDs, which also correlate the runs and events a request causes
+            .layer(axum::middleware::from_fn(request_id_middleware))
             // Session middleware for OAuth flows and authenticated requests
             .layer(axum::middleware::from_fn(session::session_middleware))
             // Tracing layer for request/response logging
@@ -500,7 +1016,11 @@ fn build_router(
                         axum::http::header::CONTENT_TYPE,
                         axum::http::header::AUTHORIZATION,
                         axum::http::header::HeaderName::from_static("x-requested-with"),
+                        axum::http::header::HeaderName::from_static(REQUEST_ID_HEADER),
                     ])
+                    .expose_headers([axum::http::header::HeaderName::from_static(
+                        REQUEST_ID_HEADER,
+                    )])
                     .allow_credentials(true)
             }),
     )
@@ -568,14 +1088,29 @@ async fn health_handler() -> Json<Value> {
 /// - Service is running
 /// - Database is accessible
 /// - All critical dependencies are healthy
+/// - The server is not draining (`POST /admin/drain`)
 ///
 /// Use this for Kubernetes readiness probes and load balancer health checks.
 async fn readiness_handler(
-    State(storage): State<Arc<dyn crate::storage::Storage>>,
+    State(state): State<AppState>,
 ) -> std::result::Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Draining servers take no new work, so load balancers should route around them
+    if state.registry.get_dependencies().engine.is_draining() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({
+                "status": "draining",
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "checks": {
+                    "draining": true
+                }
+            })),
+        ));
+    }
+
     // Check database connectivity by attempting a simple query
     // We use list_runs(1, 0) as a canary - if it succeeds, the database is accessible
-    match storage.list_runs(1, 0).await {
+    match state.storage.list_runs(1, 0).await {
         Ok(_) => {
             // Database is accessible
             Ok(Json(json!({
@@ -608,6 +1143,8 @@ async fn metrics_handler() -> std::result::Result<(StatusCode, String), AppError
     Ok((StatusCode::OK, metrics))
 }
 
+#[cfg(test)]
+mod approval_test;
 #[cfg(test)]
 mod http_test;
 #[cfg(test)]
@@ -2,7 +2,12 @@
 //!
 //! Provides template rendering using minijinja with full Jinja2 syntax support
 //! including loops, conditionals, filters, etc.
+//!
+//! Page templates are built into the binary. In reload mode
+//! (`http.reloadTemplates`), each render reads the template's file from the
+//! template directory instead, so pages can be edited without a rebuild.
 
+use crate::dsl::template::EscapePolicy;
 use crate::{BeemFlowError, Result};
 use minijinja::Environment;
 use std::collections::HashMap;
@@ -15,24 +20,49 @@ pub struct TemplateRenderer {
     template_dir: PathBuf,
     env: Arc<Environment<'static>>,
     templates: HashMap<String, String>,
+    /// File of each template, relative to `template_dir`
+    files: HashMap<String, String>,
+    reload: bool,
 }
 
 impl TemplateRenderer {
     /// Create a new template renderer with minijinja
+    ///
+    /// Pages render request and provider data into markup, so values are HTML-escaped.
     pub fn new<P: AsRef<Path>>(template_dir: P) -> Self {
+        Self::with_escape_policy(template_dir, EscapePolicy::Html)
+    }
+
+    /// Create a new template renderer with an explicit autoescape policy
+    pub fn with_escape_policy<P: AsRef<Path>>(template_dir: P, policy: EscapePolicy) -> Self {
         let mut env = Environment::new();
-        // Auto-escape HTML for security
-        env.set_auto_escape_callback(|_| minijinja::AutoEscape::Html);
+        policy.apply(&mut env);
 
         Self {
             template_dir: template_dir.as_ref().to_path_buf(),
             env: Arc::new(env),
             templates: HashMap::new(),
+            files: HashMap::new(),
+            reload: false,
         }
     }
 
+    /// Read templates from their files on every render when `reload` is set
+    pub fn with_reload(mut self, reload: bool) -> Self {
+        self.reload = reload;
+        self
+    }
+
     /// Load a template from file into cache
     pub async fn load_template(&mut self, name: &str, filename: &str) -> Result<()> {
+        let content = self.read_file(filename)?;
+        self.files.insert(name.to_string(), filename.to_string());
+        self.templates.insert(name.to_string(), content);
+        Ok(())
+    }
+
+    /// Read a template file from the template directory
+    fn read_file(&self, filename: &str) -> Result<String> {
         // Validate filename to prevent path traversal attacks
         if filename.contains("..") || filename.starts_with('/') || filename.contains('\\') {
             return Err(BeemFlowError::validation(
@@ -43,12 +73,10 @@ impl TemplateRenderer {
         let path = self.template_dir.join(filename);
 
         // Canonicalize paths to prevent traversal after join
-        let canonical_path = tokio::fs::canonicalize(&path)
-            .await
+        let canonical_path = std::fs::canonicalize(&path)
             .map_err(|e| BeemFlowError::config(format!("Invalid template path: {}", e)))?;
 
-        let canonical_template_dir = tokio::fs::canonicalize(&self.template_dir)
-            .await
+        let canonical_template_dir = std::fs::canonicalize(&self.template_dir)
             .map_err(|e| BeemFlowError::config(format!("Invalid template directory: {}", e)))?;
 
         // Ensure the resolved path is within the template directory
@@ -58,36 +86,50 @@ impl TemplateRenderer {
             ));
         }
 
-        let content = tokio::fs::read_to_string(&canonical_path)
-            .await
-            .map_err(|e| {
-                BeemFlowError::config(format!("Failed to load template {}: {}", filename, e))
-            })?;
-
-        // Cache the template content
-        self.templates.insert(name.to_string(), content);
+        std::fs::read_to_string(&canonical_path).map_err(|e| {
+            BeemFlowError::config(format!("Failed to load template {}: {}", filename, e))
+        })
+    }
 
-        Ok(())
+    /// Cache a built-in template, recording the file it was built from
+    fn insert_embedded(&mut self, name: &str, filename: &str, content: &str) {
+        self.files.insert(name.to_string(), filename.to_string());
+        self.templates.insert(name.to_string(), content.to_string());
     }
 
     /// Load all OAuth templates (embedded in binary)
     pub async fn load_oauth_templates(&mut self) -> Result<()> {
         // Embed templates in binary for portability
-        self.templates.insert(
-            "consent".to_string(),
-            include_str!("../../static/oauth/consent.html").to_string(),
+        self.insert_embedded(
+            "consent",
+            "oauth/consent.html",
+            include_str!("../../static/oauth/consent.html"),
+        );
+        self.insert_embedded(
+            "provider_auth",
+            "oauth/provider_auth.html",
+            include_str!("../../static/oauth/provider_auth.html"),
         );
-        self.templates.insert(
-            "provider_auth".to_string(),
-            include_str!("../../static/oauth/provider_auth.html").to_string(),
+        self.insert_embedded(
+            "success",
+            "oauth/success.html",
+            include_str!("../../static/oauth/success.html"),
         );
-        self.templates.insert(
-            "success".to_string(),
-            include_str!("../../static/oauth/success.html").to_string(),
+        self.insert_embedded(
+            "providers",
+            "oauth/providers.html",
+            include_str!("../../static/oauth/providers.html"),
         );
-        self.templates.insert(
-            "providers".to_string(),
-            include_str!("../../static/oauth/providers.html").to_string(),
+
+        Ok(())
+    }
+
+    /// Load the approval gate page (embedded in binary)
+    pub async fn load_approval_templates(&mut self) -> Result<()> {
+        self.insert_embedded(
+            "approval",
+            "approval/approval.html",
+            include_str!("../../static/approval/approval.html"),
         );
 
         Ok(())
@@ -99,6 +141,11 @@ impl TemplateRenderer {
             .templates
             .get(name)
             .ok_or_else(|| BeemFlowError::config(format!("Template '{}' not found", name)))?;
+        let reloaded = match self.files.get(name) {
+            Some(filename) if self.reload => Some(self.read_file(filename)?),
+            _ => None,
+        };
+        let template_content = reloaded.as_deref().unwrap_or(template_content);
 
         self.env.render_str(template_content, data).map_err(|e| {
             BeemFlowError::config(format!("Failed to render template '{}': {}", name, e))
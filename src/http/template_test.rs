@@ -14,3 +14,50 @@ async fn test_minijinja_rendering() {
     let result = tmpl.render(serde_json::json!({"name": "World"})).unwrap();
     assert_eq!(result, "Hello World!");
 }
+
+#[tokio::test]
+async fn test_renderer_escapes_html() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("page.html"), "<p>{{ name }}</p>").unwrap();
+
+    let mut renderer = TemplateRenderer::new(dir.path());
+    renderer.load_template("page", "page.html").await.unwrap();
+
+    let result = renderer
+        .render_json(
+            "page",
+            &serde_json::json!({"name": "<script>alert(1)</script>"}),
+        )
+        .unwrap();
+    assert_eq!(result, "<p>&lt;script&gt;alert(1)&lt;&#x2f;script&gt;</p>");
+}
+
+#[tokio::test]
+async fn test_reload_mode_reads_changed_template_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("approval")).unwrap();
+    let page = dir.path().join("approval/approval.html");
+    std::fs::write(&page, "<h1>{{ flow }}</h1>").unwrap();
+
+    let mut renderer = TemplateRenderer::new(dir.path()).with_reload(true);
+    renderer.load_approval_templates().await.unwrap();
+    let data = serde_json::json!({"flow": "payout"});
+    assert_eq!(
+        renderer.render_json("approval", &data).unwrap(),
+        "<h1>payout</h1>"
+    );
+
+    std::fs::write(&page, "<h2>Approve {{ flow }}</h2>").unwrap();
+    assert_eq!(
+        renderer.render_json("approval", &data).unwrap(),
+        "<h2>Approve payout</h2>"
+    );
+
+    // Without reload, the built-in page is rendered
+    let mut renderer = TemplateRenderer::new(dir.path());
+    renderer.load_approval_templates().await.unwrap();
+    assert_ne!(
+        renderer.render_json("approval", &data).unwrap(),
+        "<h2>Approve payout</h2>"
+    );
+}
@@ -1,16 +1,21 @@
 //! Webhook management system
 //!
 //! Handles dynamic webhook registration, signature verification, and event parsing.
+//!
+//! With `webhooks.historySize` configured, verified payloads are kept per provider
+//! (secret headers redacted) so [`replay_payload`] can re-dispatch them.
 
-use crate::Result;
-use crate::engine::{Engine, PausedRun};
+use crate::engine::{Engine, PausedRun, RunIdStrategy, RunOptions};
+use crate::event::{EventEnvelope, EventSource};
+use crate::model::WebhookPayload;
 use crate::registry::{RegistryManager, WebhookConfig};
 use crate::storage::Storage;
+use crate::{BeemFlowError, Result};
 use axum::{
     Router,
     body::Bytes,
     extract::{Path, State},
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
     routing::post,
 };
@@ -20,6 +25,8 @@ use sha2::Sha256;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
+use tracing::Instrument;
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -31,6 +38,27 @@ pub struct WebhookManagerState {
     pub storage: Arc<dyn Storage>,
     pub engine: Arc<Engine>,
     pub config: Arc<crate::config::Config>,
+    /// Bounds the flow runs webhooks are starting at once
+    pub start_permits: Arc<Semaphore>,
+    /// Bounds the accepted webhooks still dispatching their events
+    pub dispatch_permits: Arc<Semaphore>,
+}
+
+impl WebhookManagerState {
+    /// Create webhook state over `deps`, sizing start permits from its config
+    pub fn new(deps: &crate::core::Dependencies) -> Self {
+        Self {
+            registry_manager: deps.registry_manager.clone(),
+            secrets_provider: deps.config.create_secrets_provider(),
+            storage: deps.storage.clone(),
+            engine: deps.engine.clone(),
+            config: deps.config.clone(),
+            start_permits: Arc::new(Semaphore::new(deps.config.webhook_max_concurrent_starts())),
+            dispatch_permits: Arc::new(Semaphore::new(
+                deps.config.webhook_max_pending_dispatches(),
+            )),
+        }
+    }
 }
 
 /// Parsed webhook event
@@ -83,6 +111,24 @@ async fn handle_webhook(
         }
     }
 
+    // Refuse rather than queue without bound; the permit is held until the
+    // payload's events have been dispatched
+    let Ok(dispatch_permit) = state.dispatch_permits.clone().try_acquire_owned() else {
+        tracing::warn!(
+            "Too many pending webhooks; refusing delivery for {}",
+            provider
+        );
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(
+                header::RETRY_AFTER,
+                crate::constants::WEBHOOK_RETRY_AFTER_SECS.to_string(),
+            )],
+            "Too many pending webhooks",
+        )
+            .into_response();
+    };
+
     // Parse webhook payload
     let payload: Value = match serde_json::from_slice(&body) {
         Ok(v) => v,
@@ -92,7 +138,26 @@ async fn handle_webhook(
         }
     };
 
-    // Extract events from payload
+    // Keep the payload for replay; failing to record it must not drop the webhook
+    let history_size = state.config.webhook_history_size();
+    if history_size > 0 {
+        let record = WebhookPayload {
+            id: uuid::Uuid::new_v4(),
+            provider: provider.clone(),
+            headers: redact_headers(&webhook_config, &headers),
+            body: payload.clone(),
+            received_at: chrono::Utc::now(),
+        };
+        if let Err(e) = state
+            .storage
+            .save_webhook_payload(&record, history_size)
+            .await
+        {
+            tracing::warn!("Failed to record webhook payload for {}: {}", provider, e);
+        }
+    }
+
+    // Malformed payloads are rejected now; runs start once the response is sent
     let events = match parse_webhook_events(&webhook_config, &payload) {
         Ok(events) => events,
         Err(e) => {
@@ -101,15 +166,108 @@ async fn handle_webhook(
         }
     };
 
+    tokio::spawn(
+        crate::event::inherit_correlation_id(async move {
+            dispatch_events(&state, &provider, &events, &RunOptions::default()).await;
+            drop(dispatch_permit);
+        })
+        .instrument(tracing::Span::current()),
+    );
+
+    (StatusCode::ACCEPTED, "Accepted").into_response()
+}
+
+/// Outcome of dispatching a webhook payload
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct WebhookDispatch {
+    /// Events extracted from the payload
+    pub events: usize,
+    /// New flow runs started
+    pub triggered: usize,
+    /// Paused runs resumed
+    pub resumed: usize,
+}
+
+/// Re-dispatch a stored webhook payload through the normal matching path
+///
+/// Signatures are not re-verified: the payload was verified when it was received.
+/// Triggered runs get fresh random IDs so a replay is never deduplicated against
+/// the original delivery.
+pub async fn replay_payload(
+    state: &WebhookManagerState,
+    stored: &WebhookPayload,
+) -> Result<WebhookDispatch> {
+    let webhook_config = find_webhook_config(&state.registry_manager, &stored.provider)
+        .await
+        .ok_or_else(|| {
+            BeemFlowError::not_found(
+                "Webhook",
+                format!("{} (no longer configured)", stored.provider),
+            )
+        })?;
+
+    tracing::info!(
+        "Replaying webhook payload {} for provider {}",
+        stored.id,
+        stored.provider
+    );
+    let options = RunOptions {
+        run_id_strategy: Some(RunIdStrategy::Random),
+        ..Default::default()
+    };
+    dispatch_payload(
+        state,
+        &stored.provider,
+        &webhook_config,
+        &stored.body,
+        &options,
+    )
+    .await
+}
+
+/// Extract events from a payload, then dispatch them (see [`dispatch_events`])
+async fn dispatch_payload(
+    state: &WebhookManagerState,
+    provider: &str,
+    webhook_config: &WebhookConfig,
+    payload: &Value,
+    options: &RunOptions,
+) -> Result<WebhookDispatch> {
+    let events = parse_webhook_events(webhook_config, payload)?;
+    Ok(dispatch_events(state, provider, &events, options).await)
+}
+
+/// Publish events, then trigger and resume matching flows
+///
+/// Runs started here take the current correlation ID.
+async fn dispatch_events(
+    state: &WebhookManagerState,
+    provider: &str,
+    events: &[ParsedEvent],
+    options: &RunOptions,
+) -> WebhookDispatch {
     // Process webhook events - both trigger new flows AND resume paused runs
     let mut triggered_count = 0;
     let mut resumed_count = 0;
 
-    for event in &events {
+    for event in events {
         tracing::info!("Processing webhook event: {}", event.topic);
 
+        let envelope = match serde_json::to_value(&event.data) {
+            Ok(data) => EventEnvelope::new(&event.topic, data).with_source(EventSource::Webhook {
+                provider: provider.to_string(),
+            }),
+            Err(e) => {
+                tracing::error!("Failed to encode webhook event {}: {}", event.topic, e);
+                continue;
+            }
+        };
+        if let Err(e) = state.engine.event_bus().publish(envelope).await {
+            tracing::warn!("Failed to publish webhook event {}: {}", event.topic, e);
+        }
+
         // Use Case 1: Trigger new workflow executions
-        match trigger_flows_for_event(&state, event).await {
+        match trigger_flows_for_event(state, event, options).await {
             Ok(count) => {
                 triggered_count += count;
                 tracing::info!("Event {} triggered {} new flow(s)", event.topic, count);
@@ -121,7 +279,7 @@ async fn handle_webhook(
         }
 
         // Use Case 2: Resume paused workflow executions
-        match resume_paused_runs_for_event(&state, event).await {
+        match resume_paused_runs_for_event(state, event).await {
             Ok(count) => {
                 resumed_count += count;
                 tracing::info!("Event {} resumed {} paused run(s)", event.topic, count);
@@ -140,13 +298,57 @@ async fn handle_webhook(
         resumed_count
     );
 
-    (StatusCode::OK, "OK").into_response()
+    WebhookDispatch {
+        events: events.len(),
+        triggered: triggered_count,
+        resumed: resumed_count,
+    }
+}
+
+/// Copy request headers, redacting any that may carry a secret
+///
+/// Covers credential-looking names and the provider's configured signature header.
+pub(crate) fn redact_headers(
+    config: &WebhookConfig,
+    headers: &HeaderMap,
+) -> HashMap<String, String> {
+    const SENSITIVE: &[&str] = &[
+        "auth",
+        "token",
+        "secret",
+        "key",
+        "password",
+        "cookie",
+        "signature",
+        "credential",
+    ];
+
+    let signature_header = config
+        .signature
+        .as_ref()
+        .map(|signature| signature.header.to_lowercase());
+
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let name = name.as_str().to_lowercase();
+            let secret = SENSITIVE.iter().any(|s| name.contains(s))
+                || signature_header.as_deref() == Some(name.as_str());
+            let value = if secret {
+                crate::secrets::REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name, value)
+        })
+        .collect()
 }
 
 /// Trigger new flow executions for matching deployed flows (Use Case 1)
 async fn trigger_flows_for_event(
     state: &WebhookManagerState,
     event: &ParsedEvent,
+    options: &RunOptions,
 ) -> Result<usize> {
     // Fast O(log N) lookup: Query only flow names (not content)
     let flow_names = state.storage.find_flow_names_by_topic(&event.topic).await?;
@@ -156,10 +358,12 @@ async fn trigger_flows_for_event(
         return Ok(0);
     }
 
-    let mut triggered = 0;
-
-    // Use engine.start() - same code path as HTTP/CLI/MCP operations
-    for flow_name in flow_names {
+    // Use engine.start() - same code path as HTTP/CLI/MCP operations. Starts
+    // run concurrently, each holding a start permit until its run returns.
+    let starts = flow_names.into_iter().map(|flow_name| async move {
+        let Ok(_permit) = state.start_permits.acquire().await else {
+            return false;
+        };
         tracing::info!(
             "Triggering flow '{}' for webhook topic '{}'",
             flow_name,
@@ -168,28 +372,45 @@ async fn trigger_flows_for_event(
 
         match state
             .engine
-            .start(&flow_name, event.data.clone(), false)
+            .start_with(&flow_name, event.data.clone(), false, options)
             .await
         {
             Ok(_) => {
-                triggered += 1;
                 tracing::info!("Successfully triggered flow '{}'", flow_name);
+                true
             }
             Err(e) => {
                 // Log but don't fail - flow execution errors shouldn't block webhook
                 tracing::error!("Failed to trigger flow '{}': {}", flow_name, e);
+                false
             }
         }
-    }
+    });
 
-    Ok(triggered)
+    Ok(futures::future::join_all(starts)
+        .await
+        .into_iter()
+        .filter(|started| *started)
+        .count())
 }
 
 /// Resume paused runs for matching paused workflows (Use Case 2)
+///
+/// An event carrying a `token` only resumes the run paused with it. The token is
+/// verified (when signed) and hashed before storage is queried.
 async fn resume_paused_runs_for_event(
     state: &WebhookManagerState,
     event: &ParsedEvent,
 ) -> Result<usize> {
+    let event_value = serde_json::to_value(&event.data).unwrap_or_default();
+    let presented = match event_value
+        .get(crate::constants::MATCH_KEY_TOKEN)
+        .and_then(|v| v.as_str())
+    {
+        Some(token) => Some(state.engine.open_resume_token(token).await?),
+        None => None,
+    };
+
     // Query paused runs by source (event topic)
     let paused_runs = state
         .storage
@@ -210,6 +431,14 @@ async fn resume_paused_runs_for_event(
     let mut resumed = 0;
 
     for (token, paused_data) in paused_runs {
+        // Stored under the token's hash, or the plaintext token for older runs
+        if let Some(ref presented) = presented
+            && token != crate::engine::resume::storage_key(&presented.token)
+            && token != presented.token
+        {
+            continue;
+        }
+
         // Deserialize paused run
         let paused: PausedRun = match serde_json::from_value(paused_data) {
             Ok(p) => p,
@@ -240,8 +469,13 @@ async fn resume_paused_runs_for_event(
             }
         };
 
+        if let Some(run_id) = presented.as_ref().and_then(|p| p.run_id)
+            && run_id != paused.run_id
+        {
+            continue;
+        }
+
         // Check if event matches the await criteria
-        let event_value = serde_json::to_value(&event.data).unwrap_or_default();
         if !matches_criteria(&event_value, &await_spec.match_) {
             tracing::debug!(
                 "Event does not match criteria for token {}, skipping",
@@ -256,7 +490,7 @@ async fn resume_paused_runs_for_event(
         // Convert event data to HashMap for resume
         let resume_event = event.data.clone();
 
-        match state.engine.resume(&token, resume_event).await {
+        match state.engine.resume_stored(&token, resume_event).await {
             Ok(_) => {
                 resumed += 1;
                 tracing::info!("Successfully resumed run with token: {}", token);
@@ -18,13 +18,7 @@ async fn test_webhook_route_registration() {
     let env = TestEnvironment::new().await;
 
     // Create WebhookManagerState with storage and engine
-    let webhook_state = WebhookManagerState {
-        registry_manager: env.deps.registry_manager.clone(),
-        secrets_provider: env.deps.config.create_secrets_provider(),
-        storage: env.deps.storage.clone(),
-        engine: env.deps.engine.clone(),
-        config: env.deps.config.clone(),
-    };
+    let webhook_state = WebhookManagerState::new(&env.deps);
 
     // Build webhook router
     let app = create_webhook_routes().with_state(webhook_state);
@@ -55,13 +49,7 @@ async fn test_webhook_state_has_storage_and_engine() {
     // Verify that WebhookManagerState can be created with storage and engine
     let env = TestEnvironment::new().await;
 
-    let webhook_state = WebhookManagerState {
-        registry_manager: env.deps.registry_manager.clone(),
-        secrets_provider: env.deps.config.create_secrets_provider(),
-        storage: env.deps.storage.clone(),
-        engine: env.deps.engine.clone(),
-        config: env.deps.config.clone(),
-    };
+    let webhook_state = WebhookManagerState::new(&env.deps);
 
     // Verify state fields are accessible
     use std::sync::Arc;
@@ -256,3 +244,310 @@ fn test_parse_webhook_events_multiple_events() {
     assert_eq!(events[0].data.get("channel"), Some(&json!("C123")));
     assert_eq!(events[0].data.get("text"), Some(&json!("Hello world")));
 }
+
+#[tokio::test]
+async fn test_webhook_payload_recorded_and_replayed() {
+    use crate::core::OperationRegistry;
+    use crate::registry::RegistryEntry;
+
+    let env = TestEnvironment::new().await;
+    let entry: RegistryEntry = serde_json::from_value(json!({
+        "type": "oauth_provider",
+        "name": "oauth_acme",
+        "webhook": {
+            "enabled": true,
+            "signature": {"header": "X-Acme-Sig"},
+            "events": [{
+                "type": "order.created",
+                "topic": "acme.order.created",
+                "match": {"type": "order.created"},
+                "extract": {"order_id": "order.id"}
+            }]
+        }
+    }))
+    .unwrap();
+    env.deps
+        .registry_manager
+        .upsert_local_entry(entry)
+        .await
+        .unwrap();
+
+    let registry = OperationRegistry::new(env.deps.clone());
+    let flow = "name: on_order\nversion: \"1.0.0\"\non: acme.order.created\nsteps:\n  - id: log\n    use: core.echo\n    with:\n      text: \"{{ event.order_id }}\"\n";
+    registry
+        .execute("save_flow", json!({"name": "on_order", "content": flow}))
+        .await
+        .unwrap();
+    registry
+        .execute("deploy_flow", json!({"name": "on_order"}))
+        .await
+        .unwrap();
+
+    // Keep the last two payloads per provider
+    let mut config = (*env.deps.config).clone();
+    config.webhooks = Some(crate::config::WebhooksConfig {
+        history_size: 2,
+        ..Default::default()
+    });
+    let mut deps = env.deps.clone();
+    deps.config = std::sync::Arc::new(config);
+    let app = create_webhook_routes().with_state(WebhookManagerState::new(&deps));
+
+    for order in ["o-1", "o-2", "o-3"] {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/acme")
+            .header("content-type", "application/json")
+            .header("authorization", "Bearer live-token")
+            .header("x-acme-sig", "abc123")
+            .header("x-request-id", "req-7")
+            .body(Body::from(
+                json!({"type": "order.created", "order": {"id": order}}).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+    wait_for_finished_runs(&env, 3).await;
+
+    // Oldest payload was trimmed; secret headers were redacted before storing
+    let payloads = registry
+        .execute("list_webhook_payloads", json!({"provider": "acme"}))
+        .await
+        .unwrap();
+    let payloads = payloads.as_array().unwrap();
+    assert_eq!(payloads.len(), 2);
+    assert_eq!(payloads[0]["body"]["order"]["id"], "o-3");
+    assert_eq!(payloads[1]["body"]["order"]["id"], "o-2");
+    let headers = &payloads[0]["headers"];
+    assert_eq!(headers["authorization"], crate::secrets::REDACTED);
+    assert_eq!(headers["x-acme-sig"], crate::secrets::REDACTED);
+    assert_eq!(headers["x-request-id"], "req-7");
+
+    // Replay triggers the flow again, bypassing run-ID dedup
+    let replayed = registry
+        .execute("replay_webhook", json!({"id": payloads[0]["id"]}))
+        .await
+        .unwrap();
+    assert_eq!(replayed["provider"], "acme");
+    assert_eq!(replayed["events"], 1);
+    assert_eq!(replayed["triggered"], 1);
+
+    let runs = env.deps.storage.list_runs(100, 0).await.unwrap();
+    assert_eq!(runs.len(), 4);
+    assert_eq!(
+        runs.iter()
+            .filter(|run| run.event.get("order_id") == Some(&json!("o-3")))
+            .count(),
+        2
+    );
+
+    // Unknown payload IDs are not found
+    let missing = registry
+        .execute(
+            "replay_webhook",
+            json!({"id": "00000000-0000-0000-0000-000000000000"}),
+        )
+        .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_webhook_refused_with_retry_after_when_dispatches_are_pending() {
+    use std::sync::Arc;
+    use tokio::sync::Notify;
+
+    // Endpoint the flow calls, held until the test releases it
+    let release = Arc::new(Notify::new());
+    let slow = {
+        let release = release.clone();
+        axum::Router::new().route(
+            "/slow",
+            axum::routing::get(move || {
+                let release = release.clone();
+                async move {
+                    release.notified().await;
+                    axum::Json(json!({"ok": true}))
+                }
+            }),
+        )
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, slow).await });
+
+    let env = TestEnvironment::new().await;
+    deploy_order_flows(&env, addr, &["bill_order"]).await;
+
+    let mut config = (*env.deps.config).clone();
+    config.webhooks = Some(crate::config::WebhooksConfig {
+        max_pending_dispatches: Some(1),
+        ..Default::default()
+    });
+    let mut deps = env.deps.clone();
+    deps.config = Arc::new(config);
+    let app = create_webhook_routes().with_state(WebhookManagerState::new(&deps));
+    let post_order = |order: u32| {
+        Request::builder()
+            .method("POST")
+            .uri("/acme")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"type": "order.created", "order": {"id": order}}).to_string(),
+            ))
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(post_order(1)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    // The first delivery is still dispatching, so the next is refused
+    let response = app.clone().oneshot(post_order(2)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        response.headers()[axum::http::header::RETRY_AFTER],
+        crate::constants::WEBHOOK_RETRY_AFTER_SECS.to_string()
+    );
+
+    // Once it finishes, deliveries are accepted again
+    release.notify_one();
+    wait_for_finished_runs(&env, 1).await;
+    let mut accepted = false;
+    for _ in 0..50 {
+        let response = app.clone().oneshot(post_order(3)).await.unwrap();
+        if response.status() == StatusCode::ACCEPTED {
+            accepted = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(
+        accepted,
+        "delivery still refused after the dispatch finished"
+    );
+    release.notify_one();
+    wait_for_finished_runs(&env, 2).await;
+}
+
+/// Register the `acme` webhook and deploy flows that call `addr`'s `/slow` on each order
+async fn deploy_order_flows(env: &TestEnvironment, addr: std::net::SocketAddr, names: &[&str]) {
+    use crate::core::OperationRegistry;
+    use crate::registry::RegistryEntry;
+
+    let entry: RegistryEntry = serde_json::from_value(json!({
+        "type": "oauth_provider",
+        "name": "oauth_acme",
+        "webhook": {
+            "enabled": true,
+            "events": [{
+                "type": "order.created",
+                "topic": "acme.order.created",
+                "match": {"type": "order.created"},
+                "extract": {"order_id": "order.id"}
+            }]
+        }
+    }))
+    .unwrap();
+    env.deps
+        .registry_manager
+        .upsert_local_entry(entry)
+        .await
+        .unwrap();
+
+    let registry = OperationRegistry::new(env.deps.clone());
+    for name in names {
+        let flow = format!(
+            "name: {}\nversion: \"1.0.0\"\non: acme.order.created\nsteps:\n  - id: call\n    use: http.fetch\n    with:\n      url: \"http://{}/slow\"\n",
+            name, addr
+        );
+        registry
+            .execute("save_flow", json!({"name": name, "content": flow}))
+            .await
+            .unwrap();
+        registry
+            .execute("deploy_flow", json!({"name": name}))
+            .await
+            .unwrap();
+    }
+}
+
+/// Wait until `count` runs have finished, panicking after 10 seconds
+async fn wait_for_finished_runs(env: &TestEnvironment, count: usize) {
+    for _ in 0..100 {
+        let runs = env.deps.storage.list_runs(1000, 0).await.unwrap();
+        let finished = runs
+            .iter()
+            .filter(|run| run.status != crate::model::RunStatus::Running)
+            .count();
+        if finished >= count {
+            assert_eq!(finished, count, "more runs finished than expected");
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    panic!("timed out waiting for {} runs to finish", count);
+}
+
+#[tokio::test]
+async fn test_webhook_burst_starts_stay_within_bound() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Endpoint the flows call, recording the most calls in flight at once
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let slow = {
+        let (in_flight, max_in_flight) = (in_flight.clone(), max_in_flight.clone());
+        axum::Router::new().route(
+            "/slow",
+            axum::routing::get(move || {
+                let (in_flight, max_in_flight) = (in_flight.clone(), max_in_flight.clone());
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    axum::Json(json!({"ok": true}))
+                }
+            }),
+        )
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, slow).await });
+
+    // Two flows fan out from the topic
+    let env = TestEnvironment::new().await;
+    deploy_order_flows(&env, addr, &["bill_order", "ship_order"]).await;
+
+    let mut config = (*env.deps.config).clone();
+    config.webhooks = Some(crate::config::WebhooksConfig {
+        max_concurrent_starts: Some(2),
+        ..Default::default()
+    });
+    let mut deps = env.deps.clone();
+    deps.config = Arc::new(config);
+    let app = create_webhook_routes().with_state(WebhookManagerState::new(&deps));
+
+    // The burst is accepted without waiting for any run
+    for order in 0..5 {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/acme")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"type": "order.created", "order": {"id": order}}).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    wait_for_finished_runs(&env, 10).await;
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+    let runs = env.deps.storage.list_runs(100, 0).await.unwrap();
+    assert!(
+        runs.iter()
+            .all(|run| run.status == crate::model::RunStatus::Succeeded)
+    );
+}
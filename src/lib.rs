@@ -55,6 +55,7 @@ pub mod engine;
 // Infrastructure
 pub mod blob;
 pub mod config;
+pub mod event;
 pub mod registry;
 pub mod secrets;
 pub mod storage;
@@ -66,11 +67,12 @@ pub mod http;
 pub mod mcp;
 
 // Utilities
+pub mod clock;
 pub mod utils;
 
 // Re-exports for convenience
 pub use engine::Engine;
-pub use error::{BeemFlowError, Result};
+pub use error::{BeemFlowError, Result, StepErrorKind};
 pub use model::{Flow, FlowName, ResumeToken, Run, RunId, Step, StepId};
 
 /// Initialize logging for the application
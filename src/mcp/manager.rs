@@ -1,19 +1,43 @@
 //! MCP Manager - Uses rmcp client instead of custom JSON-RPC
 
+use crate::adapter::ProgressHandle;
 use crate::{BeemFlowError, Result, model::McpServerConfig};
+use futures::StreamExt;
 use parking_lot::RwLock;
 use rmcp::{
-    model::{CallToolRequestParam, Tool},
-    service::{RoleClient, RunningService, ServiceExt},
+    ClientHandler,
+    handler::client::progress::ProgressDispatcher,
+    model::{
+        CallToolRequestParam, ClientRequest, ProgressNotificationParam, Request, ServerResult, Tool,
+    },
+    service::{NotificationContext, PeerRequestOptions, RoleClient, RunningService, ServiceExt},
     transport::TokioChildProcess,
 };
+use serde::Serialize;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::process::Command;
 
+/// Client handler routing server progress notifications to their tool calls
+#[derive(Default)]
+struct ProgressClient {
+    dispatcher: ProgressDispatcher,
+}
+
+impl ClientHandler for ProgressClient {
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.dispatcher.handle_notification(params).await;
+    }
+}
+
 pub struct McpServer {
-    service: RunningService<RoleClient, ()>,
+    service: RunningService<RoleClient, ProgressClient>,
     tools: Arc<RwLock<HashMap<String, Tool>>>,
 }
 
@@ -48,9 +72,12 @@ impl McpServer {
             BeemFlowError::adapter(format!("Failed to create transport for '{}': {}", name, e))
         })?;
 
-        let service = ().serve(transport).await.map_err(|e| {
-            BeemFlowError::adapter(format!("Failed to connect to '{}': {}", name, e))
-        })?;
+        let service = ProgressClient::default()
+            .serve(transport)
+            .await
+            .map_err(|e| {
+                BeemFlowError::adapter(format!("Failed to connect to '{}': {}", name, e))
+            })?;
 
         let server = Self {
             service,
@@ -82,15 +109,68 @@ impl McpServer {
         Ok(())
     }
 
-    pub async fn call_tool(&self, tool_name: &str, arguments: Value) -> Result<Value> {
-        let result = self
+    /// Ask the server for its tools now, returning how many it has
+    async fn count_tools(&self) -> Result<usize> {
+        let tools_result = self
             .service
-            .call_tool(CallToolRequestParam {
-                name: tool_name.to_string().into(),
-                arguments: arguments.as_object().cloned(),
-            })
+            .list_tools(Default::default())
             .await
-            .map_err(|e| BeemFlowError::adapter(format!("Tool '{}' failed: {}", tool_name, e)))?;
+            .map_err(|e| BeemFlowError::adapter(format!("Failed to list tools: {}", e)))?;
+        Ok(tools_result.tools.len())
+    }
+
+    /// Tools discovered when the server started, sorted by name
+    pub fn tools(&self) -> Vec<Tool> {
+        let mut tools: Vec<Tool> = self.tools.read().values().cloned().collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        tools
+    }
+
+    /// Call a tool, forwarding the server's progress notifications to `progress`
+    pub async fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: Value,
+        progress: &ProgressHandle,
+    ) -> Result<Value> {
+        let failed = |e: rmcp::ServiceError| {
+            BeemFlowError::adapter(format!("Tool '{}' failed: {}", tool_name, e))
+        };
+
+        let request = ClientRequest::CallToolRequest(Request::new(CallToolRequestParam {
+            name: tool_name.to_string().into(),
+            arguments: arguments.as_object().cloned(),
+        }));
+        let handle = self
+            .service
+            .send_cancellable_request(request, PeerRequestOptions::no_options())
+            .await
+            .map_err(failed)?;
+        let mut updates = self
+            .service
+            .service()
+            .dispatcher
+            .subscribe(handle.progress_token.clone())
+            .await;
+
+        let response = handle.await_response();
+        tokio::pin!(response);
+        let response = loop {
+            tokio::select! {
+                response = &mut response => break response.map_err(failed)?,
+                Some(update) = updates.next() => progress.report(
+                    update.total.filter(|t| *t > 0.0).map(|t| update.progress / t * 100.0),
+                    update.message.unwrap_or_default(),
+                ),
+            }
+        };
+
+        let ServerResult::CallToolResult(result) = response else {
+            return Err(BeemFlowError::adapter(format!(
+                "Tool '{}' returned an unexpected response",
+                tool_name
+            )));
+        };
 
         // Return the full result structure
         serde_json::to_value(&result)
@@ -98,9 +178,24 @@ impl McpServer {
     }
 }
 
+/// Outcome of probing a downstream server
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct McpServerStatus {
+    pub name: String,
+    pub reachable: bool,
+    /// Tools the server listed, when reachable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 pub struct McpManager {
     servers: Arc<RwLock<HashMap<String, Arc<McpServer>>>>,
     configs: Arc<RwLock<HashMap<String, McpServerConfig>>>,
+    /// Held while an instance starts, so concurrent callers wait for it instead
+    /// of starting a second process
+    starting: parking_lot::Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
     secrets_provider: Arc<dyn crate::secrets::SecretsProvider>,
 }
 
@@ -109,6 +204,7 @@ impl McpManager {
         Self {
             servers: Arc::new(RwLock::new(HashMap::new())),
             configs: Arc::new(RwLock::new(HashMap::new())),
+            starting: parking_lot::Mutex::default(),
             secrets_provider,
         }
     }
@@ -117,12 +213,19 @@ impl McpManager {
         self.configs.write().insert(name, config);
     }
 
+    /// Names of all registered servers
+    pub fn server_names(&self) -> Vec<String> {
+        self.configs.read().keys().cloned().collect()
+    }
+
+    /// Config registered for `server_name`
+    pub fn server_config(&self, server_name: &str) -> Option<McpServerConfig> {
+        self.configs.read().get(server_name).cloned()
+    }
+
     pub async fn get_or_start_server(&self, server_name: &str) -> Result<Arc<McpServer>> {
-        {
-            let servers = self.servers.read();
-            if let Some(server) = servers.get(server_name) {
-                return Ok(server.clone());
-            }
+        if let Some(server) = self.servers.read().get(server_name) {
+            return Ok(server.clone());
         }
 
         let config = self
@@ -137,23 +240,139 @@ impl McpManager {
                 ))
             })?;
 
-        let server =
-            Arc::new(McpServer::start(server_name, &config, &self.secrets_provider).await?);
-        self.servers
-            .write()
-            .insert(server_name.to_string(), server.clone());
+        self.start_instance(server_name.to_string(), server_name, &config)
+            .await
+    }
+
+    /// Get or start an instance of `server_name` running with `config`
+    ///
+    /// Instances are keyed by the server name and a hash of the config, so
+    /// callers passing different config (e.g. env) never share a process, while
+    /// callers passing the same config do.
+    pub async fn get_or_start_instance(
+        &self,
+        server_name: &str,
+        config: &McpServerConfig,
+    ) -> Result<Arc<McpServer>> {
+        let key = instance_key(server_name, config);
+        if let Some(server) = self.servers.read().get(&key) {
+            return Ok(server.clone());
+        }
+        self.start_instance(key, server_name, config).await
+    }
+
+    async fn start_instance(
+        &self,
+        key: String,
+        server_name: &str,
+        config: &McpServerConfig,
+    ) -> Result<Arc<McpServer>> {
+        let start_lock = self.starting.lock().entry(key.clone()).or_default().clone();
+        let _starting = start_lock.lock().await;
+        if let Some(server) = self.servers.read().get(&key) {
+            return Ok(server.clone());
+        }
+
+        let server = Arc::new(McpServer::start(server_name, config, &self.secrets_provider).await?);
+        self.servers.write().insert(key, server.clone());
         Ok(server)
     }
 
+    /// Check that `server_name` answers `tools/list` within `timeout`
+    ///
+    /// Starts the server if it isn't running. A running server that fails the
+    /// check is dropped, so the next call starts it afresh.
+    pub async fn probe(&self, server_name: &str, timeout: Duration) -> McpServerStatus {
+        let check = async {
+            let server = self.get_or_start_server(server_name).await?;
+            server.count_tools().await
+        };
+        let result = tokio::time::timeout(timeout, check)
+            .await
+            .unwrap_or_else(|_| {
+                Err(BeemFlowError::adapter(format!(
+                    "MCP server '{}' did not respond within {}s",
+                    server_name,
+                    timeout.as_secs_f64()
+                )))
+            });
+
+        match result {
+            Ok(tools) => McpServerStatus {
+                name: server_name.to_string(),
+                reachable: true,
+                tools: Some(tools),
+                error: None,
+            },
+            Err(e) => {
+                self.servers.write().remove(server_name);
+                McpServerStatus {
+                    name: server_name.to_string(),
+                    reachable: false,
+                    tools: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        }
+    }
+
     pub async fn call_tool(
         &self,
         server_name: &str,
         tool_name: &str,
         arguments: Value,
+    ) -> Result<Value> {
+        self.call_tool_with_progress(
+            server_name,
+            tool_name,
+            arguments,
+            &ProgressHandle::default(),
+        )
+        .await
+    }
+
+    /// Call a tool, forwarding the server's progress notifications to `progress`
+    pub async fn call_tool_with_progress(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        arguments: Value,
+        progress: &ProgressHandle,
     ) -> Result<Value> {
         let server = self.get_or_start_server(server_name).await?;
-        server.call_tool(tool_name, arguments).await
+        server.call_tool(tool_name, arguments, progress).await
     }
+
+    /// Call a tool on the instance of `server_name` running with `config`
+    pub async fn call_tool_with_config(
+        &self,
+        server_name: &str,
+        config: &McpServerConfig,
+        tool_name: &str,
+        arguments: Value,
+        progress: &ProgressHandle,
+    ) -> Result<Value> {
+        let server = self.get_or_start_instance(server_name, config).await?;
+        server.call_tool(tool_name, arguments, progress).await
+    }
+}
+
+/// Key of a server instance: `name#hash`, hashing the config with env sorted
+fn instance_key(server_name: &str, config: &McpServerConfig) -> String {
+    use sha2::{Digest, Sha256};
+
+    let env: Option<std::collections::BTreeMap<&String, &String>> =
+        config.env.as_ref().map(|env| env.iter().collect());
+    let canonical = serde_json::json!({
+        "command": config.command,
+        "args": config.args,
+        "env": env,
+        "port": config.port,
+        "transport": config.transport,
+        "endpoint": config.endpoint,
+    });
+    let digest = Sha256::digest(canonical.to_string().as_bytes());
+    format!("{}#{}", server_name, &hex::encode(digest)[..16])
 }
 
 #[cfg(test)]
@@ -285,3 +285,145 @@ fn test_jsonrpc_response_preserve_extra_fields() {
     let response: Result<JsonRpcResponse, _> = serde_json::from_str(json_str);
     assert!(response.is_ok());
 }
+
+#[test]
+fn test_instance_key_depends_on_config_not_env_order() {
+    use super::instance_key;
+    use crate::model::McpServerConfig;
+    use std::collections::HashMap;
+
+    let config = |env: &[(&str, &str)]| McpServerConfig {
+        command: "npx".to_string(),
+        args: Some(vec!["airtable-mcp-server".to_string()]),
+        env: Some(
+            env.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+        ),
+        port: None,
+        transport: None,
+        endpoint: None,
+    };
+
+    let base_a = config(&[("AIRTABLE_BASE_ID", "appA"), ("TOKEN", "t")]);
+    let base_a_reordered = config(&[("TOKEN", "t"), ("AIRTABLE_BASE_ID", "appA")]);
+    let base_b = config(&[("AIRTABLE_BASE_ID", "appB"), ("TOKEN", "t")]);
+
+    let key = instance_key("airtable", &base_a);
+    assert!(key.starts_with("airtable#"));
+    assert_eq!(key, instance_key("airtable", &base_a_reordered));
+    assert_ne!(key, instance_key("airtable", &base_b));
+    assert_ne!(key, instance_key("other", &base_a));
+}
+
+/// Stdio MCP server that lists two tools, or hangs after `initialize` with `--hang`
+const LISTING_SERVER: &str = r#"
+import json, sys, time
+
+def reply(id, result):
+    sys.stdout.write(json.dumps({"jsonrpc": "2.0", "id": id, "result": result}) + "\n")
+    sys.stdout.flush()
+
+for line in sys.stdin:
+    message = json.loads(line)
+    method, id = message.get("method"), message.get("id")
+    if method == "initialize":
+        reply(id, {
+            "protocolVersion": message["params"]["protocolVersion"],
+            "capabilities": {"tools": {}},
+            "serverInfo": {"name": "listing", "version": "1.0.0"},
+        })
+    elif method == "tools/list":
+        if "--hang" in sys.argv:
+            time.sleep(60)
+        schema = {"type": "object"}
+        reply(id, {"tools": [
+            {"name": "one", "inputSchema": schema},
+            {"name": "two", "inputSchema": schema},
+        ]})
+"#;
+
+#[tokio::test]
+async fn test_check_mcp_servers_reports_reachable_and_unreachable() {
+    use crate::config::{Config, McpServerConfig};
+    use crate::core::{Dependencies, OperationRegistry};
+    use crate::utils::TestEnvironment;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    if !std::process::Command::new("python3")
+        .arg("--version")
+        .output()
+        .is_ok_and(|o| o.status.success())
+    {
+        eprintln!("python3 not available, skipping MCP health test");
+        return;
+    }
+
+    let env = TestEnvironment::new().await;
+    let dir = tempfile::TempDir::new().unwrap();
+    let script = dir.path().join("listing_mcp_server.py");
+    std::fs::write(&script, LISTING_SERVER).unwrap();
+    let python = |args: &[&str]| McpServerConfig {
+        command: "python3".to_string(),
+        args: Some(
+            std::iter::once(script.to_str().unwrap())
+                .chain(args.iter().copied())
+                .map(str::to_string)
+                .collect(),
+        ),
+        ..Default::default()
+    };
+    let config = Config {
+        mcp_servers: Some(HashMap::from([
+            ("up".to_string(), python(&[])),
+            ("hung".to_string(), python(&["--hang"])),
+            (
+                "missing".to_string(),
+                McpServerConfig {
+                    command: "/nonexistent/mcp-server".to_string(),
+                    ..Default::default()
+                },
+            ),
+        ])),
+        ..(*env.deps.config).clone()
+    };
+    let registry = OperationRegistry::new(Dependencies {
+        config: Arc::new(config),
+        ..env.deps.clone()
+    });
+
+    let report = registry
+        .execute("check_mcp_servers", json!({"timeout_secs": 2}))
+        .await
+        .unwrap();
+    let servers = report["servers"].as_array().unwrap();
+    let names: Vec<&str> = servers
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["hung", "missing", "up"]);
+
+    assert_eq!(
+        servers[2],
+        json!({"name": "up", "reachable": true, "tools": 2})
+    );
+    for server in &servers[..2] {
+        assert_eq!(server["reachable"], false, "{}", server);
+        assert!(server.get("tools").is_none(), "{}", server);
+    }
+    let error = |i: usize| servers[i]["error"].as_str().unwrap().to_string();
+    assert!(
+        error(0).contains("did not respond within 2s"),
+        "{}",
+        error(0)
+    );
+    assert!(error(1).contains("missing"), "{}", error(1));
+
+    // A second check reuses the running server
+    let report = registry
+        .execute("check_mcp_servers", json!({"timeout_secs": 2}))
+        .await
+        .unwrap();
+    assert_eq!(report["servers"][2]["reachable"], true);
+}
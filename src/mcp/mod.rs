@@ -1,7 +1,11 @@
 //! MCP (Model Context Protocol) server and client manager
 
 pub mod manager;
+mod progress;
+mod proxy;
 mod server;
+mod stdio;
 
-pub use manager::McpManager;
+pub use manager::{McpManager, McpServerStatus};
+pub(crate) use server::operation_tools;
 pub use server::{McpServer, McpServerState, create_mcp_metadata_routes, create_mcp_routes};
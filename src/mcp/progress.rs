@@ -0,0 +1,126 @@
+//! MCP progress notifications for tool calls that run flows
+//!
+//! A `tools/call` carrying a progress token runs its operation under a fresh
+//! correlation ID. Runs started by the operation stamp that ID on their
+//! `step.status` events, and [`ProgressRouter`] hands the ones matching an
+//! in-flight call to it, to be sent as `notifications/progress` until the call
+//! completes.
+
+use crate::constants::EVENT_TOPIC_STEP_STATUS;
+use crate::event::{EventBus, EventEnvelope};
+use rmcp::{
+    model::{ProgressNotificationParam, ProgressToken},
+    service::{Peer, RoleServer},
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OnceCell, mpsc};
+
+type Calls = Arc<Mutex<HashMap<String, mpsc::UnboundedSender<EventEnvelope>>>>;
+
+/// Routes step lifecycle events to the tool calls whose runs emitted them
+pub(crate) struct ProgressRouter {
+    event_bus: Arc<dyn EventBus>,
+    subscribed: OnceCell<()>,
+    calls: Calls,
+}
+
+impl ProgressRouter {
+    pub fn new(event_bus: Arc<dyn EventBus>) -> Self {
+        Self {
+            event_bus,
+            subscribed: OnceCell::new(),
+            calls: Arc::default(),
+        }
+    }
+
+    /// Receive the step events correlated with `correlation_id`
+    ///
+    /// Events arrive until the returned [`TrackedCall`] is dropped. The bus is
+    /// subscribed to on first use and stays subscribed for the router's lifetime.
+    pub async fn track(
+        &self,
+        correlation_id: &str,
+    ) -> crate::Result<(TrackedCall, mpsc::UnboundedReceiver<EventEnvelope>)> {
+        self.subscribed
+            .get_or_try_init(|| async {
+                let calls = self.calls.clone();
+                let handler = Arc::new(move |event: EventEnvelope| {
+                    let Some(ref correlation_id) = event.correlation_id else {
+                        return;
+                    };
+                    if let Ok(calls) = calls.lock()
+                        && let Some(sender) = calls.get(correlation_id)
+                    {
+                        let _ = sender.send(event);
+                    }
+                });
+                self.event_bus
+                    .subscribe(EVENT_TOPIC_STEP_STATUS, handler)
+                    .await
+            })
+            .await?;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.insert(correlation_id.to_string(), sender);
+        }
+        Ok((
+            TrackedCall {
+                correlation_id: correlation_id.to_string(),
+                calls: self.calls.clone(),
+            },
+            receiver,
+        ))
+    }
+}
+
+/// A tool call receiving step events; stops routing them when dropped
+pub(crate) struct TrackedCall {
+    correlation_id: String,
+    calls: Calls,
+}
+
+impl Drop for TrackedCall {
+    fn drop(&mut self) {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.remove(&self.correlation_id);
+        }
+    }
+}
+
+/// Send each received step event to the client as a progress notification
+///
+/// Progress counts the notifications sent, since the number of steps a call will
+/// run is not known up front. Returns once the sender side is dropped and every
+/// buffered event has been sent.
+pub(crate) async fn forward_progress(
+    mut events: mpsc::UnboundedReceiver<EventEnvelope>,
+    progress_token: ProgressToken,
+    peer: Peer<RoleServer>,
+) {
+    let mut progress = 0.0;
+    while let Some(event) = events.recv().await {
+        progress += 1.0;
+        let step_id = event.payload["step_id"].as_str().unwrap_or_default();
+        let message = match event.payload["status"].as_str() {
+            Some("RUNNING") => format!("step '{}' started", step_id),
+            Some("SUCCEEDED") => format!("step '{}' succeeded", step_id),
+            _ => format!(
+                "step '{}' failed: {}",
+                step_id,
+                event.payload["error"].as_str().unwrap_or_default()
+            ),
+        };
+        let notification = ProgressNotificationParam {
+            progress_token: progress_token.clone(),
+            progress,
+            total: None,
+            message: Some(message),
+        };
+        if let Err(e) = peer.notify_progress(notification).await {
+            tracing::debug!("Failed to send MCP progress notification: {}", e);
+            return;
+        }
+    }
+}
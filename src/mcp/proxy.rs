@@ -0,0 +1,131 @@
+//! MCP proxy - exposes downstream MCP servers through BeemFlow's MCP endpoint
+//!
+//! With `mcp.aggregate: true`, the tools of every server in `mcpServers` are
+//! listed alongside BeemFlow's operations, namespaced as `{server}_{tool}`, and
+//! calls are routed over the engine's existing downstream connections.
+
+use super::McpManager;
+use crate::core::Dependencies;
+use rmcp::model::{CallToolResult, Content, JsonObject, RawContent, Tool};
+use std::sync::Arc;
+
+/// Routes namespaced tools to the downstream MCP servers they came from
+pub struct McpProxy {
+    manager: Arc<McpManager>,
+    servers: Vec<String>,
+}
+
+impl McpProxy {
+    /// Proxy for the servers in `mcpServers`, or `None` unless `mcp.aggregate` is set
+    ///
+    /// The servers are registered with the engine's MCP adapter, so flows and the
+    /// proxy share one connection per server.
+    pub fn from_deps(deps: &Dependencies) -> Option<Self> {
+        if !deps.config.mcp.as_ref().is_some_and(|mcp| mcp.aggregate) {
+            return None;
+        }
+
+        let mcp_adapter = deps.engine.mcp_adapter();
+        let mut servers = Vec::new();
+        for (name, config) in deps.config.mcp_servers.iter().flatten() {
+            mcp_adapter.register_server(name.clone(), config.into());
+            servers.push(name.clone());
+        }
+        servers.sort();
+
+        Some(Self {
+            manager: mcp_adapter.manager().clone(),
+            servers,
+        })
+    }
+
+    /// Tools of every downstream server, namespaced as `{server}_{tool}`
+    ///
+    /// Servers are started on first use. A server that fails to start is left
+    /// out of the listing with a warning.
+    pub async fn list_tools(&self) -> Vec<Tool> {
+        let started = futures::future::join_all(
+            self.servers
+                .iter()
+                .map(|name| self.manager.get_or_start_server(name)),
+        )
+        .await;
+
+        let mut tools = Vec::new();
+        for (name, server) in self.servers.iter().zip(started) {
+            match server {
+                Ok(server) => tools.extend(server.tools().into_iter().map(|mut tool| {
+                    tool.name = format!("{}_{}", name, tool.name).into();
+                    tool
+                })),
+                Err(e) => {
+                    tracing::warn!("Skipping unavailable MCP server '{}': {}", name, e);
+                }
+            }
+        }
+        tools
+    }
+
+    /// Split a namespaced tool name into its server and downstream tool name
+    ///
+    /// Server names may themselves contain underscores, so the longest server
+    /// name that prefixes `name` wins.
+    pub fn route<'a>(&self, name: &'a str) -> Option<(&str, &'a str)> {
+        self.servers
+            .iter()
+            .filter_map(|server| {
+                name.strip_prefix(server.as_str())
+                    .and_then(|rest| rest.strip_prefix('_'))
+                    .filter(|tool| !tool.is_empty())
+                    .map(|tool| (server.as_str(), tool))
+            })
+            .max_by_key(|(server, _)| server.len())
+    }
+
+    /// Call `tool` on `server`, prefixing errors with the server name
+    pub async fn call_tool(
+        &self,
+        server: &str,
+        tool: &str,
+        arguments: JsonObject,
+    ) -> CallToolResult {
+        let failed = |message: String| {
+            let message = format!("{}: {}", server, message);
+            tracing::error!("Proxied tool call failed: {}", message);
+            CallToolResult::error(vec![Content::text(message)])
+        };
+
+        let result = match self
+            .manager
+            .call_tool(server, tool, serde_json::Value::Object(arguments))
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => return failed(e.to_string()),
+        };
+        let mut result: CallToolResult = match serde_json::from_value(result) {
+            Ok(result) => result,
+            Err(e) => return failed(format!("invalid tool result: {}", e)),
+        };
+
+        if result.is_error == Some(true) {
+            match result
+                .content
+                .iter_mut()
+                .find_map(|content| match &mut content.raw {
+                    RawContent::Text(text) => Some(text),
+                    _ => None,
+                }) {
+                Some(text) => text.text = format!("{}: {}", server, text.text),
+                None => result
+                    .content
+                    .insert(0, Content::text(format!("{}: tool failed", server))),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+#[path = "proxy_test.rs"]
+mod tests;
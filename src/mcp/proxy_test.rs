@@ -0,0 +1,258 @@
+use super::*;
+use crate::config::{Config, McpConfig, McpServerConfig};
+use crate::core::OperationRegistry;
+use crate::mcp::McpServer;
+use crate::utils::TestEnvironment;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// Minimal stdio MCP server named by its first argument
+///
+/// `echo` answers with the server name and its arguments, `fail` returns an
+/// error result and unknown tools get a JSON-RPC error.
+const FAKE_SERVER: &str = r#"
+import json, sys
+
+name = sys.argv[1]
+
+def reply(id, result=None, error=None):
+    message = {"jsonrpc": "2.0", "id": id}
+    if error is not None:
+        message["error"] = error
+    else:
+        message["result"] = result
+    sys.stdout.write(json.dumps(message) + "\n")
+    sys.stdout.flush()
+
+for line in sys.stdin:
+    message = json.loads(line)
+    method, id = message.get("method"), message.get("id")
+    if id is None:
+        continue
+    if method == "initialize":
+        reply(id, {
+            "protocolVersion": message["params"]["protocolVersion"],
+            "capabilities": {"tools": {}},
+            "serverInfo": {"name": name, "version": "1.0.0"},
+        })
+    elif method == "tools/list":
+        schema = {"type": "object"}
+        reply(id, {"tools": [
+            {"name": "echo", "description": "Echo arguments", "inputSchema": schema},
+            {"name": "fail", "description": "Always fails", "inputSchema": schema},
+        ]})
+    elif method == "tools/call":
+        params = message["params"]
+        if params["name"] == "echo":
+            text = name + ":" + json.dumps(params.get("arguments") or {}, sort_keys=True)
+            reply(id, {"content": [{"type": "text", "text": text}]})
+        elif params["name"] == "fail":
+            reply(id, {"content": [{"type": "text", "text": "boom"}], "isError": True})
+        else:
+            reply(id, error={"code": -32602, "message": "unknown tool " + params["name"]})
+    else:
+        reply(id, error={"code": -32601, "message": "method not found"})
+"#;
+
+/// Dependencies whose config aggregates two fake servers and one that can't start
+async fn aggregating_deps(env: &TestEnvironment, dir: &TempDir) -> Dependencies {
+    let script = dir.path().join("fake_mcp_server.py");
+    std::fs::write(&script, FAKE_SERVER).unwrap();
+    let fake = |name: &str| McpServerConfig {
+        command: "python3".to_string(),
+        args: Some(vec![script.to_str().unwrap().to_string(), name.to_string()]),
+        transport: Some("stdio".to_string()),
+        ..Default::default()
+    };
+
+    let config = Config {
+        mcp: Some(McpConfig {
+            require_auth: false,
+            max_message_bytes: None,
+            aggregate: true,
+        }),
+        mcp_servers: Some(HashMap::from([
+            ("alpha".to_string(), fake("alpha")),
+            ("beta_tools".to_string(), fake("beta_tools")),
+            (
+                "broken".to_string(),
+                McpServerConfig {
+                    command: "/nonexistent/mcp-server".to_string(),
+                    ..Default::default()
+                },
+            ),
+        ])),
+        ..(*env.deps.config).clone()
+    };
+
+    Dependencies {
+        storage: env.deps.storage.clone(),
+        engine: env.deps.engine.clone(),
+        registry_manager: env.deps.registry_manager.clone(),
+        config: Arc::new(config),
+        oauth_client: env.deps.oauth_client.clone(),
+    }
+}
+
+fn has_python() -> bool {
+    std::process::Command::new("python3")
+        .arg("--version")
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+fn text(result: &CallToolResult) -> String {
+    result.content[0].as_text().unwrap().text.clone()
+}
+
+#[tokio::test]
+async fn test_proxy_disabled_by_default() {
+    let env = TestEnvironment::new().await;
+    assert!(McpProxy::from_deps(&env.deps).is_none());
+}
+
+#[tokio::test]
+async fn test_route_prefers_longest_server_name() {
+    let env = TestEnvironment::new().await;
+    let dir = TempDir::new().unwrap();
+    let proxy = McpProxy::from_deps(&aggregating_deps(&env, &dir).await).unwrap();
+
+    assert_eq!(proxy.route("alpha_echo"), Some(("alpha", "echo")));
+    assert_eq!(
+        proxy.route("beta_tools_list_all"),
+        Some(("beta_tools", "list_all"))
+    );
+    assert_eq!(proxy.route("alpha_"), None);
+    assert_eq!(proxy.route("gamma_echo"), None);
+    assert_eq!(proxy.route("beemflow_list_runs"), None);
+}
+
+#[tokio::test]
+async fn test_proxy_lists_namespaced_tools_and_routes_calls() {
+    if !has_python() {
+        eprintln!("python3 not available, skipping MCP proxy test");
+        return;
+    }
+    let env = TestEnvironment::new().await;
+    let dir = TempDir::new().unwrap();
+    let proxy = McpProxy::from_deps(&aggregating_deps(&env, &dir).await).unwrap();
+
+    // The broken server is skipped rather than failing the listing
+    let names: Vec<String> = proxy
+        .list_tools()
+        .await
+        .iter()
+        .map(|t| t.name.to_string())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "alpha_echo",
+            "alpha_fail",
+            "beta_tools_echo",
+            "beta_tools_fail"
+        ]
+    );
+
+    let args = |v: Value| v.as_object().unwrap().clone();
+    let result = proxy
+        .call_tool("alpha", "echo", args(json!({"x": 1})))
+        .await;
+    assert_eq!(text(&result), r#"alpha:{"x": 1}"#);
+    let result = proxy
+        .call_tool("beta_tools", "echo", args(json!({"y": 2})))
+        .await;
+    assert_eq!(text(&result), r#"beta_tools:{"y": 2}"#);
+
+    // Downstream errors carry the server name
+    let result = proxy.call_tool("alpha", "fail", args(json!({}))).await;
+    assert_eq!(result.is_error, Some(true));
+    assert_eq!(text(&result), "alpha: boom");
+
+    let result = proxy.call_tool("beta_tools", "nope", args(json!({}))).await;
+    assert_eq!(result.is_error, Some(true));
+    assert!(
+        text(&result).starts_with("beta_tools: "),
+        "{}",
+        text(&result)
+    );
+    assert!(
+        text(&result).contains("unknown tool nope"),
+        "{}",
+        text(&result)
+    );
+}
+
+#[tokio::test]
+async fn test_mcp_server_exposes_downstream_tools() {
+    if !has_python() {
+        eprintln!("python3 not available, skipping MCP proxy test");
+        return;
+    }
+    let env = TestEnvironment::new().await;
+    let dir = TempDir::new().unwrap();
+    let server = McpServer::new(Arc::new(OperationRegistry::new(
+        aggregating_deps(&env, &dir).await,
+    )));
+
+    let (mut client_in, server_in) = tokio::io::duplex(1024 * 1024);
+    let (server_out, client_out) = tokio::io::duplex(1024 * 1024);
+    let served = tokio::spawn(crate::mcp::stdio::serve(
+        server,
+        server_in,
+        server_out,
+        1024 * 1024,
+    ));
+    let mut responses = BufReader::new(client_out).lines();
+
+    let mut send = async |message: Value| {
+        client_in
+            .write_all(format!("{}\n", message).as_bytes())
+            .await
+            .unwrap();
+    };
+    send(
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+        "protocolVersion": "2024-11-05", "capabilities": {},
+        "clientInfo": {"name": "test", "version": "1"}}}),
+    )
+    .await;
+    send(json!({"jsonrpc": "2.0", "method": "notifications/initialized"})).await;
+    send(json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list", "params": {}})).await;
+    send(json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+        "params": {"name": "beta_tools_echo", "arguments": {"z": 3}}}))
+    .await;
+
+    let mut by_id = HashMap::new();
+    while by_id.len() < 3 {
+        let line = tokio::time::timeout(Duration::from_secs(30), responses.next_line())
+            .await
+            .expect("server should answer")
+            .unwrap()
+            .unwrap();
+        let response: Value = serde_json::from_str(&line).unwrap();
+        by_id.insert(response["id"].as_i64().unwrap(), response);
+    }
+
+    // Operations and downstream tools are listed side by side
+    let tools: Vec<&str> = by_id[&2]["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert!(tools.contains(&"beemflow_list_runs"));
+    assert!(tools.contains(&"alpha_echo"));
+    assert!(tools.contains(&"beta_tools_fail"));
+
+    assert_eq!(
+        by_id[&3]["result"]["content"][0]["text"],
+        r#"beta_tools:{"z": 3}"#
+    );
+
+    drop(client_in);
+    let _ = tokio::time::timeout(Duration::from_secs(10), served).await;
+}
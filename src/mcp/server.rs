@@ -2,9 +2,13 @@
 //!
 //! Exposes BeemFlow operations as MCP tools for AI assistants (Claude Desktop, ChatGPT, etc.)
 //! Uses the official `rmcp` SDK with auto-generation from operation metadata.
+//! With `mcp.aggregate: true`, downstream MCP servers are proxied too (see [`McpProxy`]).
 
+use super::progress::{ProgressRouter, forward_progress};
+use super::proxy::McpProxy;
 use crate::Result;
 use crate::auth::middleware::validate_token;
+use crate::auth::tenant::TenantScope;
 use crate::core::OperationRegistry;
 use crate::storage::Storage;
 use axum::{
@@ -19,9 +23,9 @@ use rmcp::{
     handler::server::ServerHandler,
     model::{
         CallToolRequestParam, CallToolResult, Content, ListToolsResult, PaginatedRequestParam,
-        ServerCapabilities, ServerInfo, Tool, ToolsCapability,
+        ProgressToken, ServerCapabilities, ServerInfo, Tool, ToolsCapability,
     },
-    service::{RequestContext, RoleServer, ServiceExt},
+    service::{RequestContext, RoleServer},
     transport::streamable_http_server::{
         StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
     },
@@ -41,32 +45,47 @@ pub struct McpServerState {
 /// MCP Server that exposes BeemFlow operations as tools
 pub struct McpServer {
     operations: Arc<OperationRegistry>,
+    proxy: Option<Arc<McpProxy>>,
+    progress: Arc<ProgressRouter>,
 }
 
 impl McpServer {
     /// Create a new MCP server
     pub fn new(operations: Arc<OperationRegistry>) -> Self {
-        Self { operations }
+        let deps = operations.get_dependencies();
+        let proxy = McpProxy::from_deps(&deps).map(Arc::new);
+        let progress = Arc::new(ProgressRouter::new(deps.engine.event_bus().clone()));
+        Self {
+            operations,
+            proxy,
+            progress,
+        }
     }
 
     /// Serve over stdio (for Claude Desktop, etc.)
+    ///
+    /// Malformed and oversized messages get JSON-RPC error responses instead of
+    /// ending the session, and the client closing either pipe is a clean exit.
+    /// The size limit comes from `mcp.maxMessageBytes` (default 10MB).
     pub async fn serve_stdio(&self) -> Result<()> {
         tracing::info!("Starting MCP server on stdio using official rmcp SDK");
 
-        // Use official SDK's stdio transport and serve
-        let service = self
-            .clone()
-            .serve(rmcp::transport::io::stdio())
-            .await
-            .map_err(|e| {
-                crate::BeemFlowError::internal(format!("Failed to start MCP server: {}", e))
-            })?;
-
-        // Wait for completion
-        service
-            .waiting()
-            .await
-            .map_err(|e| crate::BeemFlowError::internal(format!("MCP server error: {}", e)))?;
+        let max_message_bytes = self
+            .operations
+            .get_dependencies()
+            .config
+            .mcp
+            .as_ref()
+            .and_then(|mcp| mcp.max_message_bytes)
+            .unwrap_or(super::stdio::DEFAULT_MAX_MESSAGE_BYTES);
+
+        super::stdio::serve(
+            self.clone(),
+            tokio::io::stdin(),
+            tokio::io::stdout(),
+            max_message_bytes,
+        )
+        .await?;
 
         tracing::info!("MCP server shutdown");
         Ok(())
@@ -210,22 +229,7 @@ impl McpServer {
 
     /// Auto-generate MCP tools from operation metadata using generated registration functions
     fn get_tools_list(&self) -> Vec<Tool> {
-        let deps = self.operations.get_dependencies();
-
-        // Call generated registration functions from each operation group
-        let mut tools: Vec<Tool> = [
-            crate::core::flows::flows::register_mcp_tools,
-            crate::core::runs::runs::register_mcp_tools,
-            crate::core::tools::tools::register_mcp_tools,
-            crate::core::mcp::mcp::register_mcp_tools,
-            crate::core::system::system::register_mcp_tools,
-        ]
-        .into_iter()
-        .flat_map(|register_fn| register_fn(deps.clone()))
-        .collect();
-
-        // Sort tools by name for consistent output
-        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        let tools = operation_tools(self.operations.get_dependencies());
 
         tracing::info!(
             "Auto-generated {} MCP tools from operation metadata",
@@ -235,10 +239,34 @@ impl McpServer {
     }
 }
 
+/// Every operation's MCP tool, sorted by name, as listed by the server
+pub(crate) fn operation_tools(deps: Arc<crate::core::Dependencies>) -> Vec<Tool> {
+    // Call generated registration functions from each operation group
+    let mut tools: Vec<Tool> = [
+        crate::core::flows::flows::register_mcp_tools,
+        crate::core::events::events::register_mcp_tools,
+        crate::core::runs::runs::register_mcp_tools,
+        crate::core::tools::tools::register_mcp_tools,
+        crate::core::mcp::mcp::register_mcp_tools,
+        crate::core::oauth::oauth::register_mcp_tools,
+        crate::core::system::system::register_mcp_tools,
+        crate::core::webhooks::webhooks::register_mcp_tools,
+    ]
+    .into_iter()
+    .flat_map(|register_fn| register_fn(deps.clone()))
+    .collect();
+
+    // Sort tools by name for consistent output
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    tools
+}
+
 impl Clone for McpServer {
     fn clone(&self) -> Self {
         Self {
             operations: Arc::clone(&self.operations),
+            proxy: self.proxy.clone(),
+            progress: Arc::clone(&self.progress),
         }
     }
 }
@@ -259,7 +287,10 @@ impl ServerHandler for McpServer {
         _request: Option<PaginatedRequestParam>,
         _context: RequestContext<RoleServer>,
     ) -> std::result::Result<ListToolsResult, McpError> {
-        let tools = self.get_tools_list();
+        let mut tools = self.get_tools_list();
+        if let Some(ref proxy) = self.proxy {
+            tools.extend(proxy.list_tools().await);
+        }
 
         Ok(ListToolsResult {
             tools,
@@ -270,7 +301,7 @@ impl ServerHandler for McpServer {
     async fn call_tool(
         &self,
         request: CallToolRequestParam,
-        _context: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> std::result::Result<CallToolResult, McpError> {
         let tool_name = request.name.as_ref();
         let arguments_map = request.arguments.clone().unwrap_or_default();
@@ -278,11 +309,51 @@ impl ServerHandler for McpServer {
 
         tracing::debug!("Calling tool: {} with args: {:?}", tool_name, arguments);
 
+        // Namespaced downstream tools go to the server they came from
+        if let Some(ref proxy) = self.proxy
+            && !tool_name.starts_with("beemflow_")
+            && let Some((server, tool)) = proxy.route(tool_name)
+        {
+            let proxy = proxy.clone();
+            let (server, tool) = (server.to_string(), tool.to_string());
+            let arguments_map = request.arguments.unwrap_or_default();
+            return isolated(tool_name, async move {
+                proxy.call_tool(&server, &tool, arguments_map).await
+            })
+            .await;
+        }
+
         // Strip "beemflow_" prefix to get the actual operation name
         let operation_name = tool_name.strip_prefix("beemflow_").unwrap_or(tool_name);
 
-        // Execute operation via registry
-        match self.operations.execute(operation_name, arguments).await {
+        // Execute operation via registry, scoped to the tenant of the caller's token
+        let tenant_id = request_tenant(&context);
+        let operations = self.operations.clone();
+        let name = operation_name.to_string();
+        let execution = match context.meta.get_progress_token() {
+            Some(progress_token) => {
+                self.execute_with_progress(
+                    tool_name,
+                    name,
+                    arguments,
+                    progress_token,
+                    tenant_id,
+                    context,
+                )
+                .await?
+            }
+            None => {
+                isolated(
+                    tool_name,
+                    with_request_tenant(tenant_id, async move {
+                        operations.execute(&name, arguments).await
+                    }),
+                )
+                .await?
+            }
+        };
+
+        match execution {
             Ok(result) => {
                 let result_text =
                     serde_json::to_string_pretty(&result).unwrap_or_else(|_| "{}".to_string());
@@ -299,6 +370,83 @@ impl ServerHandler for McpServer {
     }
 }
 
+impl McpServer {
+    /// Execute an operation, reporting the steps of the runs it starts as MCP
+    /// progress notifications for `progress_token`
+    ///
+    /// Every notification is sent before the call's result.
+    async fn execute_with_progress(
+        &self,
+        tool_name: &str,
+        operation_name: String,
+        arguments: Value,
+        progress_token: ProgressToken,
+        tenant_id: Option<String>,
+        context: RequestContext<RoleServer>,
+    ) -> std::result::Result<Result<Value>, McpError> {
+        let correlation_id = format!("mcp-{}", uuid::Uuid::new_v4());
+        let (tracked, events) = self
+            .progress
+            .track(&correlation_id)
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let forwarder = tokio::spawn(forward_progress(events, progress_token, context.peer));
+
+        let operations = self.operations.clone();
+        let execution = isolated(
+            tool_name,
+            crate::event::with_correlation_id(
+                correlation_id,
+                with_request_tenant(tenant_id, async move {
+                    operations.execute(&operation_name, arguments).await
+                }),
+            ),
+        )
+        .await;
+
+        // Stop routing; the forwarder drains what was already routed, then exits
+        drop(tracked);
+        let _ = forwarder.await;
+        execution
+    }
+}
+
+/// Tenant of the HTTP request carrying an MCP call, set by [`mcp_oauth_middleware`]
+/// or [`mcp_tenant_middleware`]
+fn request_tenant(context: &RequestContext<RoleServer>) -> Option<String> {
+    context
+        .extensions
+        .get::<axum::http::request::Parts>()
+        .and_then(|parts| parts.extensions.get::<TenantScope>())
+        .and_then(|scope| scope.0.clone())
+}
+
+/// Run `future` scoped to `tenant_id`, or unscoped without one
+async fn with_request_tenant<F: std::future::Future>(
+    tenant_id: Option<String>,
+    future: F,
+) -> F::Output {
+    match tenant_id {
+        Some(tenant_id) => crate::auth::with_tenant(tenant_id, future).await,
+        None => future.await,
+    }
+}
+
+/// Run a tool handler on its own task so a panic fails only that request
+///
+/// A panic becomes a JSON-RPC internal error (-32603) instead of leaving the
+/// request unanswered.
+pub(crate) async fn isolated<T, F>(tool_name: &str, handler: F) -> std::result::Result<T, McpError>
+where
+    T: Send + 'static,
+    F: std::future::Future<Output = T> + Send + 'static,
+{
+    tokio::spawn(handler).await.map_err(|e| {
+        tracing::error!("Tool {} panicked: {}", tool_name, e);
+        McpError::internal_error(format!("Tool {} failed unexpectedly", tool_name), None)
+    })
+}
+
 // OAuth middleware state for MCP
 #[derive(Clone)]
 pub struct McpAuthState {
@@ -309,7 +457,7 @@ pub struct McpAuthState {
 // OAuth middleware for MCP
 pub async fn mcp_oauth_middleware(
     State(state): State<Arc<McpAuthState>>,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Response {
     let token = request
@@ -336,7 +484,13 @@ pub async fn mcp_oauth_middleware(
     };
 
     match validate_token(&state.storage, token).await {
-        Ok(user) if user.scopes.iter().any(|s| s.starts_with("mcp")) => next.run(request).await,
+        Ok(user) if user.scopes.iter().any(|s| s.starts_with("mcp")) => {
+            // Tool calls run on their own tasks; they read the tenant from the request
+            match scope_to_tenant(&state.storage, Some(&user), &mut request).await {
+                Ok(()) => next.run(request).await,
+                Err(response) => response,
+            }
+        }
         Ok(_) => (axum::http::StatusCode::FORBIDDEN, "Insufficient scopes").into_response(),
         Err(e) => {
             tracing::warn!("MCP OAuth failed: {}", e);
@@ -395,7 +549,8 @@ pub fn create_mcp_routes(state: Arc<McpServerState>) -> Router {
         any(move |req| async move { streamable_service.clone().handle(req).await }),
     );
 
-    // Conditionally apply OAuth middleware if issuer is configured
+    // Apply OAuth middleware if issuer is configured; otherwise tokens are optional
+    // until tenants are configured
     if let Some(oauth_issuer) = state.oauth_issuer.clone() {
         let auth_state = Arc::new(McpAuthState {
             storage: state.storage.clone(),
@@ -406,11 +561,85 @@ pub fn create_mcp_routes(state: Arc<McpServerState>) -> Router {
             auth_state,
             mcp_oauth_middleware,
         ));
+    } else {
+        router = router.layer(axum::middleware::from_fn_with_state(
+            state.storage.clone(),
+            mcp_tenant_middleware,
+        ));
     }
 
     router
 }
 
+/// Scope MCP requests to their bearer token's tenant when no OAuth server runs
+///
+/// The token is optional until tenants are configured; see
+/// [`crate::auth::tenant::resolve_tenant`].
+pub async fn mcp_tenant_middleware(
+    State(storage): State<Arc<dyn Storage>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let user = match request.headers().get(axum::http::header::AUTHORIZATION) {
+        Some(header) => {
+            let token = header
+                .to_str()
+                .ok()
+                .and_then(|value| value.strip_prefix("Bearer "));
+            match token {
+                Some(token) => match validate_token(&storage, token).await {
+                    Ok(user) => Some(user),
+                    Err(e) => {
+                        tracing::warn!("MCP token rejected: {}", e);
+                        return (axum::http::StatusCode::UNAUTHORIZED, "Invalid token")
+                            .into_response();
+                    }
+                },
+                None => {
+                    return (
+                        axum::http::StatusCode::UNAUTHORIZED,
+                        "Invalid Authorization header format",
+                    )
+                        .into_response();
+                }
+            }
+        }
+        None => None,
+    };
+
+    match scope_to_tenant(&storage, user.as_ref(), &mut request).await {
+        Ok(()) => next.run(request).await,
+        Err(response) => response,
+    }
+}
+
+/// Record the tenant of an MCP request's principal for its tool calls
+///
+/// Fails with the response to send when the request may not run.
+async fn scope_to_tenant(
+    storage: &Arc<dyn Storage>,
+    user: Option<&crate::auth::AuthenticatedUser>,
+    request: &mut Request,
+) -> std::result::Result<(), Response> {
+    match crate::auth::tenant::resolve_tenant(storage, user).await {
+        Ok(tenant_id) => {
+            request.extensions_mut().insert(TenantScope(tenant_id));
+            Ok(())
+        }
+        Err(crate::BeemFlowError::OAuth(message)) => {
+            Err((axum::http::StatusCode::UNAUTHORIZED, message).into_response())
+        }
+        Err(e) => {
+            tracing::error!("Failed to look up the tenant of an MCP request: {}", e);
+            Err((
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal error",
+            )
+                .into_response())
+        }
+    }
+}
+
 /// Create StreamableHttpService from McpServer
 fn create_streamable_service(
     mcp_server: McpServer,
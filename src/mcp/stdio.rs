@@ -0,0 +1,342 @@
+//! Hardened stdio transport for the MCP server
+//!
+//! rmcp's stdio transport buffers lines without bound and ends the session on the
+//! first line it cannot decode. [`serve`] sits between the pipes and the rmcp
+//! service instead: it frames lines itself, answers oversized and malformed frames
+//! with JSON-RPC errors, and only forwards messages rmcp can decode. When the
+//! client closes stdin the session ends cleanly once in-flight requests have been
+//! answered; when the client closes stdout the session ends immediately.
+
+use crate::{BeemFlowError, Result};
+use rmcp::{handler::server::ServerHandler, model::ClientJsonRpcMessage, service::ServiceExt};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{Notify, mpsc};
+
+/// Default largest accepted JSON-RPC message (10MB)
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// How long to wait for in-flight requests after the client closes stdin
+const EOF_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Capacity of the in-process pipe between the framing layer and rmcp
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+
+/// Serve `service` over a line-delimited JSON-RPC stream
+///
+/// Returns `Ok(())` when either side of the stream is closed by the client.
+pub(crate) async fn serve<S, R, W>(
+    service: S,
+    input: R,
+    output: W,
+    max_message_bytes: usize,
+) -> Result<()>
+where
+    S: ServerHandler,
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (ours, theirs) = tokio::io::duplex(PIPE_CAPACITY);
+    let (from_service, to_service) = tokio::io::split(ours);
+    let (out_tx, out_rx) = mpsc::unbounded_channel::<String>();
+    let in_flight = InFlight::default();
+
+    let mut writer = tokio::spawn(write_frames(output, out_rx));
+    let reader = tokio::spawn(read_frames(
+        BufReader::new(input),
+        to_service,
+        out_tx.clone(),
+        in_flight.clone(),
+        max_message_bytes,
+    ));
+    let pump = tokio::spawn(pump_responses(
+        BufReader::new(from_service),
+        out_tx,
+        in_flight,
+    ));
+
+    let session = async {
+        let running = service
+            .serve(tokio::io::split(theirs))
+            .await
+            .map_err(|e| BeemFlowError::internal(format!("Failed to start MCP server: {}", e)))?;
+        running
+            .waiting()
+            .await
+            .map_err(|e| BeemFlowError::internal(format!("MCP server error: {}", e)))
+    };
+
+    let outcome = tokio::select! {
+        result = session => result.map(|_| ()),
+        _ = &mut writer => {
+            tracing::info!("MCP client closed stdout, shutting down");
+            reader.abort();
+            pump.abort();
+            return Ok(());
+        }
+    };
+
+    // The service has stopped; let the remaining responses reach the client.
+    let input_closed = reader.is_finished();
+    reader.abort();
+    let _ = pump.await;
+    let _ = writer.await;
+
+    match outcome {
+        // A client that disconnects before initializing is not a server failure
+        Err(e) if input_closed => {
+            tracing::info!("MCP client disconnected: {}", e);
+            Ok(())
+        }
+        other => other,
+    }
+}
+
+/// Request ids forwarded to the service and not yet answered
+#[derive(Clone, Default)]
+struct InFlight {
+    ids: Arc<Mutex<HashSet<String>>>,
+    answered: Arc<Notify>,
+}
+
+impl InFlight {
+    fn insert(&self, id: &Value) {
+        self.ids
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.to_string());
+    }
+
+    fn remove(&self, id: &Value) {
+        let removed = self
+            .ids
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&id.to_string());
+        if removed {
+            self.answered.notify_one();
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ids
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+    }
+
+    /// Wait until every forwarded request has been answered
+    async fn drained(&self) {
+        while !self.is_empty() {
+            self.answered.notified().await;
+        }
+    }
+}
+
+/// One line read from the client
+enum Frame {
+    Line,
+    Oversized,
+    Eof,
+}
+
+/// Read one newline-terminated frame into `line`, holding at most `max` bytes
+///
+/// Oversized frames are consumed up to their newline and discarded, so a single
+/// huge message cannot exhaust memory or desynchronize the stream.
+async fn read_frame<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    max: usize,
+    line: &mut Vec<u8>,
+) -> std::io::Result<Frame> {
+    line.clear();
+    let mut oversized = false;
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Ok(match (oversized, line.is_empty()) {
+                (true, _) => Frame::Oversized,
+                (false, true) => Frame::Eof,
+                (false, false) => Frame::Line,
+            });
+        }
+
+        let newline = available.iter().position(|b| *b == b'\n');
+        let chunk = &available[..newline.unwrap_or(available.len())];
+        if !oversized {
+            if line.len() + chunk.len() > max {
+                oversized = true;
+                line.clear();
+            } else {
+                line.extend_from_slice(chunk);
+            }
+        }
+
+        let consumed = newline.map_or(available.len(), |i| i + 1);
+        reader.consume(consumed);
+        if newline.is_some() {
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            return Ok(if oversized {
+                Frame::Oversized
+            } else {
+                Frame::Line
+            });
+        }
+    }
+}
+
+/// Frame client input, answering bad frames and forwarding good ones to the service
+async fn read_frames<R, W>(
+    mut input: R,
+    mut to_service: W,
+    out: mpsc::UnboundedSender<String>,
+    in_flight: InFlight,
+    max_message_bytes: usize,
+) where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut line = Vec::new();
+    loop {
+        let frame = match read_frame(&mut input, max_message_bytes, &mut line).await {
+            Ok(frame) => frame,
+            Err(e) => {
+                tracing::warn!("MCP stdin read failed, treating as disconnect: {}", e);
+                Frame::Eof
+            }
+        };
+
+        match frame {
+            Frame::Eof => break,
+            Frame::Oversized => {
+                tracing::warn!(
+                    "Rejected MCP message larger than {} bytes",
+                    max_message_bytes
+                );
+                let _ = out.send(error_response(
+                    Value::Null,
+                    INVALID_REQUEST,
+                    format!("Message exceeds {} bytes", max_message_bytes),
+                ));
+            }
+            Frame::Line if line.iter().all(u8::is_ascii_whitespace) => {}
+            Frame::Line => match check_message(&line) {
+                Ok(request_id) => {
+                    if let Some(id) = &request_id {
+                        in_flight.insert(id);
+                    }
+                    line.push(b'\n');
+                    if to_service.write_all(&line).await.is_err() {
+                        // The service stopped; nothing left to answer requests
+                        break;
+                    }
+                }
+                Err(Some(response)) => {
+                    let _ = out.send(response);
+                }
+                Err(None) => {}
+            },
+        }
+    }
+
+    // Give in-flight requests a chance to answer before the service sees EOF
+    if tokio::time::timeout(EOF_DRAIN_TIMEOUT, in_flight.drained())
+        .await
+        .is_err()
+    {
+        tracing::warn!("MCP client disconnected with requests still running");
+    }
+    let _ = to_service.shutdown().await;
+}
+
+/// Check a frame is a message the service can decode
+///
+/// Returns the request id for requests, or the error response to send back
+/// (`None` for undecodable notifications, which get no response).
+fn check_message(line: &[u8]) -> std::result::Result<Option<Value>, Option<String>> {
+    let value: Value = serde_json::from_slice(line).map_err(|e| {
+        tracing::warn!("Rejected malformed MCP message: {}", e);
+        Some(error_response(
+            Value::Null,
+            PARSE_ERROR,
+            format!("Parse error: {}", e),
+        ))
+    })?;
+
+    let id = value.get("id").filter(|id| !id.is_null()).cloned();
+    let is_request = value.get("method").is_some() && id.is_some();
+
+    if let Err(e) = serde_json::from_value::<ClientJsonRpcMessage>(value.clone()) {
+        if value.get("method").is_some() && id.is_none() {
+            tracing::debug!("Ignoring unsupported MCP notification: {}", e);
+            return Err(None);
+        }
+        tracing::warn!("Rejected invalid MCP message: {}", e);
+        return Err(Some(error_response(
+            id.unwrap_or(Value::Null),
+            INVALID_REQUEST,
+            format!("Invalid request: {}", e),
+        )));
+    }
+
+    Ok(if is_request { id } else { None })
+}
+
+/// Relay service output to the client, marking answered requests
+async fn pump_responses<R: AsyncBufRead + Unpin>(
+    from_service: R,
+    out: mpsc::UnboundedSender<String>,
+    in_flight: InFlight,
+) {
+    let mut lines = from_service.lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if let Ok(message) = serde_json::from_str::<Value>(&line)
+            && message.get("method").is_none()
+            && let Some(id) = message.get("id")
+        {
+            in_flight.remove(id);
+        }
+        if out.send(line).is_err() {
+            break;
+        }
+    }
+}
+
+/// Write outgoing frames until every sender is gone or the client stops reading
+async fn write_frames<W: AsyncWrite + Unpin>(
+    mut output: W,
+    mut frames: mpsc::UnboundedReceiver<String>,
+) {
+    while let Some(frame) = frames.recv().await {
+        let written = async {
+            output.write_all(frame.as_bytes()).await?;
+            output.write_all(b"\n").await?;
+            output.flush().await
+        };
+        if let Err(e) = written.await {
+            tracing::info!("MCP stdout closed: {}", e);
+            return;
+        }
+    }
+}
+
+fn error_response(id: Value, code: i64, message: String) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message},
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+#[path = "stdio_test.rs"]
+mod tests;
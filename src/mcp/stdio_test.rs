@@ -0,0 +1,247 @@
+use super::*;
+use crate::core::OperationRegistry;
+use crate::mcp::McpServer;
+use crate::mcp::server::isolated;
+use crate::utils::TestEnvironment;
+use rmcp::{
+    ErrorData as McpError,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, ServerCapabilities, ServerInfo,
+        ToolsCapability,
+    },
+    service::{RequestContext, RoleServer},
+};
+use tokio::io::DuplexStream;
+
+const INITIALIZE: &str = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"test","version":"1"}}}"#;
+const INITIALIZED: &str = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+
+/// Run `server` over piped stdio, feed it `frames`, close stdin and collect every
+/// response written before the server exits
+async fn run_script<S: ServerHandler>(server: S, frames: Vec<String>, max: usize) -> Vec<Value> {
+    let (client_in, server_in) = tokio::io::duplex(1024 * 1024);
+    let (server_out, client_out) = tokio::io::duplex(1024 * 1024);
+
+    let served = tokio::spawn(serve(server, server_in, server_out, max));
+    let collected = tokio::spawn(collect(client_out));
+
+    let mut client_in = client_in;
+    for frame in frames {
+        client_in.write_all(frame.as_bytes()).await.unwrap();
+        client_in.write_all(b"\n").await.unwrap();
+    }
+    drop(client_in);
+
+    let result = tokio::time::timeout(Duration::from_secs(10), served)
+        .await
+        .expect("server should exit after stdin closes")
+        .unwrap();
+    assert!(result.is_ok(), "server should exit cleanly: {:?}", result);
+    collected.await.unwrap()
+}
+
+async fn collect(output: DuplexStream) -> Vec<Value> {
+    let mut lines = BufReader::new(output).lines();
+    let mut responses = Vec::new();
+    while let Ok(Some(line)) = lines.next_line().await {
+        responses.push(serde_json::from_str(&line).expect("server wrote invalid JSON"));
+    }
+    responses
+}
+
+fn response_for<'a>(responses: &'a [Value], id: &Value) -> &'a Value {
+    responses
+        .iter()
+        .find(|r| &r["id"] == id)
+        .unwrap_or_else(|| panic!("no response for id {}: {:?}", id, responses))
+}
+
+fn request(id: i64, method: &str, params: Value) -> String {
+    json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}).to_string()
+}
+
+#[tokio::test]
+async fn test_bad_frames_answered_and_session_survives() {
+    let env = TestEnvironment::new().await;
+    let server = McpServer::new(Arc::new(OperationRegistry::new(env.deps)));
+
+    let oversized = request(3, "tools/list", json!({"padding": "x".repeat(8 * 1024)}));
+    let frames = vec![
+        INITIALIZE.to_string(),
+        INITIALIZED.to_string(),
+        "this is not json".to_string(),
+        oversized,
+        r#"{"jsonrpc":"2.0","id":4,"params":{}}"#.to_string(),
+        String::new(),
+        request(5, "tools/list", json!({})),
+    ];
+
+    let responses = run_script(server, frames, 4 * 1024).await;
+
+    assert!(response_for(&responses, &json!(1))["result"].is_object());
+
+    let errors: Vec<i64> = responses
+        .iter()
+        .filter(|r| r["id"].is_null())
+        .map(|r| r["error"]["code"].as_i64().unwrap())
+        .collect();
+    assert_eq!(errors, vec![PARSE_ERROR, INVALID_REQUEST]);
+
+    let invalid = response_for(&responses, &json!(4));
+    assert_eq!(invalid["error"]["code"], INVALID_REQUEST);
+
+    // The session outlived every bad frame
+    let tools = response_for(&responses, &json!(5));
+    assert!(!tools["result"]["tools"].as_array().unwrap().is_empty());
+    assert_eq!(responses.len(), 5);
+}
+
+#[tokio::test]
+async fn test_disconnect_before_initialize_is_clean() {
+    let env = TestEnvironment::new().await;
+    let server = McpServer::new(Arc::new(OperationRegistry::new(env.deps)));
+
+    let responses = run_script(server, vec![], DEFAULT_MAX_MESSAGE_BYTES).await;
+    assert!(responses.is_empty());
+}
+
+#[tokio::test]
+async fn test_closed_stdout_ends_session() {
+    let env = TestEnvironment::new().await;
+    let server = McpServer::new(Arc::new(OperationRegistry::new(env.deps)));
+
+    let (mut client_in, server_in) = tokio::io::duplex(1024);
+    let (server_out, client_out) = tokio::io::duplex(1024);
+    drop(client_out);
+
+    let served = tokio::spawn(serve(server, server_in, server_out, 1024 * 1024));
+    client_in.write_all(INITIALIZE.as_bytes()).await.unwrap();
+    client_in.write_all(b"\n").await.unwrap();
+
+    let result = tokio::time::timeout(Duration::from_secs(10), served)
+        .await
+        .expect("server should exit once stdout is gone")
+        .unwrap();
+    assert!(result.is_ok());
+}
+
+/// Handler whose `boom` tool panics and whose other tools answer after a delay
+#[derive(Clone)]
+struct ScriptedHandler;
+
+impl ServerHandler for ScriptedHandler {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities {
+                tools: Some(ToolsCapability::default()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        let name = request.name.to_string();
+        isolated(&name.clone(), async move {
+            if name == "boom" {
+                panic!("tool handler exploded");
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            CallToolResult::success(vec![Content::text(name)])
+        })
+        .await
+    }
+}
+
+#[tokio::test]
+async fn test_panicking_tool_becomes_internal_error() {
+    let frames = vec![
+        INITIALIZE.to_string(),
+        INITIALIZED.to_string(),
+        request(2, "tools/call", json!({"name": "boom"})),
+        request(3, "tools/call", json!({"name": "slow"})),
+    ];
+
+    // stdin closes right after the slow call; its response must still be flushed
+    let responses = run_script(ScriptedHandler, frames, DEFAULT_MAX_MESSAGE_BYTES).await;
+
+    let panicked = response_for(&responses, &json!(2));
+    assert_eq!(panicked["error"]["code"], -32603);
+
+    let slow = response_for(&responses, &json!(3));
+    assert_eq!(slow["result"]["content"][0]["text"], "slow");
+}
+
+#[tokio::test]
+async fn test_progress_notifications_for_flow_steps() {
+    let env = TestEnvironment::new().await;
+    let registry = Arc::new(OperationRegistry::new(env.deps));
+    let flow = "name: progress_flow\nversion: 1.0.0\non: cli.manual\nsteps:\n  - id: first\n    use: core.echo\n    with:\n      text: one\n  - id: second\n    use: core.echo\n    with:\n      text: \"{{ outputs.first.text }} two\"\n";
+    registry
+        .execute("save_flow", json!({"content": flow}))
+        .await
+        .unwrap();
+    let server = McpServer::new(registry);
+
+    let start = |id: i64, meta: Value| {
+        request(
+            id,
+            "tools/call",
+            json!({
+                "name": "beemflow_start_run",
+                "arguments": {"flow_name": "progress_flow", "draft": true, "event": {"call": id}},
+                "_meta": meta,
+            }),
+        )
+    };
+    let frames = vec![
+        INITIALIZE.to_string(),
+        INITIALIZED.to_string(),
+        start(2, json!({"progressToken": "run-1"})),
+        start(3, json!({})),
+    ];
+    let responses = run_script(server, frames, DEFAULT_MAX_MESSAGE_BYTES).await;
+
+    let result = response_for(&responses, &json!(2));
+    assert_eq!(result["result"]["isError"], false, "{}", result);
+    assert!(response_for(&responses, &json!(3))["result"].is_object());
+
+    let notifications: Vec<&Value> = responses
+        .iter()
+        .filter(|r| r["method"] == "notifications/progress")
+        .collect();
+    let messages: Vec<&str> = notifications
+        .iter()
+        .map(|n| {
+            // Only the call that asked for progress gets notified
+            assert_eq!(n["params"]["progressToken"], "run-1");
+            n["params"]["message"].as_str().unwrap()
+        })
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            "step 'first' started",
+            "step 'first' succeeded",
+            "step 'second' started",
+            "step 'second' succeeded",
+        ]
+    );
+    let progress: Vec<f64> = notifications
+        .iter()
+        .map(|n| n["params"]["progress"].as_f64().unwrap())
+        .collect();
+    assert_eq!(progress, vec![1.0, 2.0, 3.0, 4.0]);
+
+    // Every notification precedes the call's result
+    let result_at = responses.iter().position(|r| r["id"] == json!(2)).unwrap();
+    let last_notification = responses
+        .iter()
+        .rposition(|r| r["method"] == "notifications/progress")
+        .unwrap();
+    assert!(last_notification < result_at);
+}
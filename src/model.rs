@@ -9,7 +9,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::ops::Deref;
 use uuid::Uuid;
@@ -21,14 +21,20 @@ use uuid::Uuid;
 /// Flow identifier with validation
 ///
 /// Ensures flow names are:
-/// - Non-empty
-/// - Valid identifiers (alphanumeric, underscore, hyphen)
-/// - Not just whitespace
+/// - Non-empty and at most [`FlowName::MAX_LEN`] characters
+/// - Made only of ASCII letters, digits, underscore, and hyphen
+///
+/// Flow names become file names under `flows_dir`, so this charset also rules out
+/// path separators and `..`. Deserialization validates too, so operation inputs
+/// and flow definitions with invalid names are rejected at the boundary.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(transparent)]
+#[serde(try_from = "String", into = "String")]
 pub struct FlowName(String);
 
 impl FlowName {
+    /// Maximum flow name length
+    pub const MAX_LEN: usize = 128;
+
     /// Create a new FlowName with validation
     pub fn new(name: impl Into<String>) -> crate::Result<Self> {
         let name = name.into();
@@ -40,13 +46,21 @@ impl FlowName {
             ));
         }
 
-        // Validate characters (alphanumeric, underscore, hyphen, dot)
+        if name.len() > Self::MAX_LEN {
+            return Err(crate::BeemFlowError::validation(format!(
+                "Flow name is too long ({} characters, max {})",
+                name.len(),
+                Self::MAX_LEN
+            )));
+        }
+
+        // Validate characters (ASCII alphanumeric, underscore, hyphen)
         if !name
             .chars()
-            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
         {
             return Err(crate::BeemFlowError::validation(format!(
-                "Flow name '{}' contains invalid characters (only alphanumeric, _, -, . allowed)",
+                "Flow name '{}' contains invalid characters (only a-z, A-Z, 0-9, _, - allowed)",
                 name
             )));
         }
@@ -54,6 +68,12 @@ impl FlowName {
         Ok(Self(name))
     }
 
+    /// Create without validation - ONLY FOR TESTING
+    #[cfg(test)]
+    pub(crate) fn unchecked(name: String) -> Self {
+        Self(name)
+    }
+
     /// Get the raw string value
     #[inline]
     pub fn as_str(&self) -> &str {
@@ -67,6 +87,47 @@ impl FlowName {
     }
 }
 
+impl TryFrom<String> for FlowName {
+    type Error = crate::BeemFlowError;
+
+    fn try_from(name: String) -> crate::Result<Self> {
+        Self::new(name)
+    }
+}
+
+impl TryFrom<&str> for FlowName {
+    type Error = crate::BeemFlowError;
+
+    fn try_from(name: &str) -> crate::Result<Self> {
+        Self::new(name)
+    }
+}
+
+impl From<FlowName> for String {
+    fn from(name: FlowName) -> Self {
+        name.0
+    }
+}
+
+impl schemars::JsonSchema for FlowName {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "FlowName".into()
+    }
+
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "pattern": "^[A-Za-z0-9_-]+$",
+            "minLength": 1,
+            "maxLength": Self::MAX_LEN,
+        })
+    }
+}
+
 impl Deref for FlowName {
     type Target = str;
 
@@ -87,12 +148,6 @@ impl AsRef<str> for FlowName {
     }
 }
 
-impl From<String> for FlowName {
-    fn from(name: String) -> Self {
-        Self::new(name).expect("Invalid flow name")
-    }
-}
-
 /// Step identifier with validation
 ///
 /// Ensures step IDs are:
@@ -186,25 +241,31 @@ impl From<String> for StepId {
 
 /// Resume token for paused runs (awaiting events)
 ///
-/// Opaque identifier for resuming paused workflow runs.
-/// Validates that the token is a valid UUID v4.
+/// Opaque identifier for resuming paused workflow runs: 256 random bits,
+/// hex-encoded. UUIDs are accepted too, as generated by earlier versions.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct ResumeToken(String);
 
 impl ResumeToken {
-    /// Create a new resume token (generates a UUID v4)
+    /// Create a new resume token from 256 random bits
     pub fn generate() -> Self {
-        Self(Uuid::new_v4().to_string())
+        Self(hex::encode(rand::random::<[u8; 32]>()))
     }
 
     /// Create from an existing string with validation
     pub fn new(token: impl Into<String>) -> crate::Result<Self> {
         let token = token.into();
 
-        // Validate it's a valid UUID
-        Uuid::parse_str(&token)
-            .map_err(|_| crate::BeemFlowError::validation("Resume token must be a valid UUID"))?;
+        let is_random = token.len() == 64
+            && token
+                .bytes()
+                .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+        if !is_random && Uuid::parse_str(&token).is_err() {
+            return Err(crate::BeemFlowError::validation(
+                "Resume token must be 64 hex characters or a UUID",
+            ));
+        }
 
         Ok(Self(token))
     }
@@ -253,7 +314,7 @@ pub type RunId = Uuid;
 // ============================================================================
 
 /// A complete workflow definition
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Flow {
     /// Unique workflow identifier (REQUIRED)
     pub name: FlowName,
@@ -262,6 +323,24 @@ pub struct Flow {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 
+    /// Person responsible for the flow (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+
+    /// Team responsible for the flow (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+
+    /// Labels for finding the flow, e.g. `billing` (optional; lowercase letters,
+    /// digits, `.`, `_` and `-`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+
+    /// Key/value labels recorded on every run of the flow, e.g. `team: billing`
+    /// (optional; labels given when a run starts win)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<HashMap<String, String>>,
+
     /// Semantic version (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
@@ -278,6 +357,10 @@ pub struct Flow {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vars: Option<HashMap<String, serde_json::Value>>,
 
+    /// JSON Schema the triggering event must satisfy (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<serde_json::Value>,
+
     /// Array of execution steps (REQUIRED)
     pub steps: Vec<Step>,
 
@@ -285,25 +368,154 @@ pub struct Flow {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub catch: Option<Vec<Step>>,
 
+    /// Steps run after the run succeeds (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_success: Option<Vec<Step>>,
+
+    /// Steps run after the run fails, following any `catch` steps (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<Vec<Step>>,
+
     /// MCP server configurations (optional)
     #[serde(skip_serializing_if = "Option::is_none", rename = "mcpServers")]
     pub mcp_servers: Option<HashMap<String, McpServerConfig>>,
+
+    /// Validate step inputs against tool manifests (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict_params: Option<bool>,
+
+    /// Per-environment overlays, selected when a run starts (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environments: Option<HashMap<String, FlowEnvironment>>,
+
+    /// Named blob store (from config `blobStores`) the flow's steps write to (optional;
+    /// default: the configured `blob` store)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_store: Option<String>,
 }
 
+/// Overlay applied to a flow when it runs in a named environment
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct FlowEnvironment {
+    /// Variables merged over the flow's base `vars` (overlay wins)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vars: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Key of a stage-keyed var's value used outside the stages it names
+pub const STAGE_DEFAULT_KEY: &str = "default";
+
 impl Flow {
+    /// Apply the named environment's overlay to this flow
+    ///
+    /// Stage-keyed vars resolve to their value for `environment` (see
+    /// [`Flow::resolve_stage_vars`]). Fails if `environments` doesn't declare
+    /// the environment, or a stage-keyed var has no value for it.
+    pub fn in_environment(&self, environment: &str) -> crate::Result<Flow> {
+        if !self.has_environment(environment) {
+            return Err(crate::BeemFlowError::validation(format!(
+                "Flow '{}' has no environment '{}'",
+                self.name, environment
+            )));
+        }
+        let mut flow = self.clone();
+        if let Some(overlay_vars) = self
+            .environments
+            .as_ref()
+            .and_then(|environments| environments.get(environment))
+            .and_then(|overlay| overlay.vars.clone())
+        {
+            flow.vars
+                .get_or_insert_with(HashMap::new)
+                .extend(overlay_vars);
+        }
+        flow.resolve_stage_vars(Some(environment))?;
+        Ok(flow)
+    }
+
+    /// Whether `environments` declares `environment`
+    pub fn has_environment(&self, environment: &str) -> bool {
+        self.environments
+            .as_ref()
+            .is_some_and(|environments| environments.contains_key(environment))
+    }
+
+    /// Whether the var `value` is stage-keyed
+    ///
+    /// Only a non-empty map whose keys are all declared environments or
+    /// [`STAGE_DEFAULT_KEY`] is; flows without `environments` have none.
+    fn is_stage_keyed(&self, value: &serde_json::Value) -> bool {
+        let Some(environments) = &self.environments else {
+            return false;
+        };
+        value.as_object().is_some_and(|stages| {
+            !stages.is_empty()
+                && stages
+                    .keys()
+                    .all(|key| key == STAGE_DEFAULT_KEY || environments.contains_key(key))
+        })
+    }
+
+    /// Resolve stage-keyed vars for the active `stage`
+    ///
+    /// A stage-keyed var (see [`Flow::is_stage_keyed`]) takes the value under
+    /// the stage, falling back to the one under `default`; it is an error for
+    /// it to have neither. Other vars are left as they are.
+    pub fn resolve_stage_vars(&mut self, stage: Option<&str>) -> crate::Result<()> {
+        let Some(vars) = self.vars.as_ref() else {
+            return Ok(());
+        };
+        let mut resolved = HashMap::new();
+        for (name, value) in vars {
+            let Some(stages) = value.as_object().filter(|_| self.is_stage_keyed(value)) else {
+                continue;
+            };
+            let value = stage
+                .and_then(|stage| stages.get(stage))
+                .or_else(|| stages.get(STAGE_DEFAULT_KEY))
+                .ok_or_else(|| {
+                    crate::BeemFlowError::validation(match stage {
+                        Some(stage) => format!(
+                            "Var '{}' has no value for environment '{}' and no '{}'",
+                            name, stage, STAGE_DEFAULT_KEY
+                        ),
+                        None => format!(
+                            "Var '{}' is keyed by environment but has no '{}' for runs without one",
+                            name, STAGE_DEFAULT_KEY
+                        ),
+                    })
+                })?;
+            resolved.insert(name.clone(), value.clone());
+        }
+        if let Some(vars) = self.vars.as_mut() {
+            vars.extend(resolved);
+        }
+        Ok(())
+    }
+
     /// Create a minimal flow for testing with a valid name
     #[cfg(test)]
     pub fn test(name: &str) -> Self {
         Self {
             name: FlowName::new(name).expect("Invalid test flow name"),
             description: None,
+            owner: None,
+            team: None,
+            tags: None,
+            labels: None,
             version: None,
             on: None,
             cron: None,
             vars: None,
+            input_schema: None,
             steps: Vec::new(),
             catch: None,
+            on_success: None,
+            on_failure: None,
             mcp_servers: None,
+            strict_params: None,
+            environments: None,
+            blob_store: None,
         }
     }
 }
@@ -312,21 +524,31 @@ impl Flow {
 impl Default for Flow {
     fn default() -> Self {
         Self {
-            name: FlowName::from("default_flow".to_string()),
+            name: FlowName::new("default_flow").expect("default flow name is valid"),
             description: None,
+            owner: None,
+            team: None,
+            tags: None,
+            labels: None,
             version: None,
             on: None,
             cron: None,
             vars: None,
+            input_schema: None,
             steps: Vec::new(),
             catch: None,
+            on_success: None,
+            on_failure: None,
             mcp_servers: None,
+            strict_params: None,
+            environments: None,
+            blob_store: None,
         }
     }
 }
 
 /// Trigger type for workflow execution
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Trigger {
     /// Single trigger type as string
@@ -356,6 +578,30 @@ impl Trigger {
         }
     }
 
+    /// Event fields supplied by the trigger entries' `defaults` objects
+    ///
+    /// Only object entries (`- event: topic`) can carry `defaults`; where two
+    /// entries default the same field, the first declared wins.
+    pub fn event_defaults(&self) -> HashMap<String, serde_json::Value> {
+        let entries: &[serde_json::Value] = match self {
+            Trigger::Single(_) | Trigger::Multiple(_) => &[],
+            Trigger::Complex(values) => values,
+            Trigger::Raw(serde_json::Value::Array(values)) => values,
+            Trigger::Raw(value) => std::slice::from_ref(value),
+        };
+
+        let mut defaults = HashMap::new();
+        for fields in entries
+            .iter()
+            .filter_map(|entry| entry.get("defaults")?.as_object())
+        {
+            for (key, value) in fields {
+                defaults.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+        defaults
+    }
+
     /// Check if a JSON value matches a trigger type (string or {event: "..."})
     fn value_matches(value: &serde_json::Value, trigger_type: &str) -> bool {
         value.as_str().is_some_and(|s| s == trigger_type)
@@ -368,7 +614,7 @@ impl Trigger {
 }
 
 /// A single workflow step
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Step {
     /// Unique step identifier (REQUIRED)
     pub id: StepId,
@@ -420,6 +666,39 @@ pub struct Step {
     /// Time delay configuration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub wait: Option<WaitSpec>,
+
+    /// Validate inputs against the tool manifest (overrides flow-level setting)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict_params: Option<bool>,
+
+    /// Fail the step if it runs longer than this in total (e.g. "30m")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_total: Option<String>,
+
+    /// Fail the step if it reports no progress for this long (e.g. "2m")
+    ///
+    /// The timer restarts on every progress report, so actively progressing steps
+    /// keep running.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_idle: Option<String>,
+
+    /// Named blob store the step writes to (overrides flow-level setting)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_store: Option<String>,
+
+    /// Environment of the process behind the step's tool (templates are rendered)
+    ///
+    /// For `mcp://` steps, merged over the server's `env`; the step then talks to
+    /// its own instance of the server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<HashMap<String, String>>,
+
+    /// Keep running the flow if this step fails (top-level steps only)
+    ///
+    /// The step is recorded as failed and its error is available to later steps
+    /// as `steps.<id>.error`; steps depending on it still run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continue_on_error: Option<bool>,
 }
 
 impl Step {
@@ -440,6 +719,12 @@ impl Step {
             retry: None,
             await_event: None,
             wait: None,
+            strict_params: None,
+            timeout_total: None,
+            timeout_idle: None,
+            blob_store: None,
+            env: None,
+            continue_on_error: None,
         }
     }
 }
@@ -461,22 +746,33 @@ impl Default for Step {
             retry: None,
             await_event: None,
             wait: None,
+            strict_params: None,
+            timeout_total: None,
+            timeout_idle: None,
+            blob_store: None,
+            env: None,
+            continue_on_error: None,
         }
     }
 }
 
 /// Retry configuration for a step
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RetrySpec {
     /// Total attempts (including first)
     pub attempts: u32,
 
     /// Delay between attempts in seconds
     pub delay_sec: u64,
+
+    /// Longest delay between attempts in seconds, including one a server asks
+    /// for with `Retry-After` (default: 300)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_delay_sec: Option<u64>,
 }
 
 /// Event wait configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AwaitEventSpec {
     /// Event source
     pub source: String,
@@ -491,7 +787,7 @@ pub struct AwaitEventSpec {
 }
 
 /// Time delay configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WaitSpec {
     /// Wait seconds
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -503,7 +799,7 @@ pub struct WaitSpec {
 }
 
 /// MCP server configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct McpServerConfig {
     /// Command to execute
     pub command: String,
@@ -557,6 +853,66 @@ pub struct Run {
     /// Step execution records
     #[serde(skip_serializing_if = "Option::is_none")]
     pub steps: Option<Vec<StepRun>>,
+
+    /// Environment the run was started in (see `Flow::environments`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+
+    /// Correlation ID of the action that started the run, shared with the
+    /// events it emits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+
+    /// Labels from the flow and the caller that started the run
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, String>,
+
+    /// Resources the run consumed, recorded when it finishes or pauses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<RunUsage>,
+
+    /// Tenant the run belongs to (see [`crate::auth::tenant`]); `None` in
+    /// single-tenant deployments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+/// Resources consumed by a run, summed over its resumes and re-executions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunUsage {
+    /// Time spent executing steps (milliseconds)
+    pub wall_time_ms: u64,
+
+    /// Invocations per tool; retries and poll attempts each count
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tool_invocations: BTreeMap<String, u64>,
+
+    /// Requests sent by HTTP tools
+    pub http_requests: u64,
+
+    /// Response body bytes received by HTTP tools
+    pub http_response_bytes: u64,
+
+    /// Tool calls made to MCP servers
+    pub mcp_calls: u64,
+
+    /// LLM tokens reported in the `usage` of HTTP tool responses
+    pub llm_tokens: u64,
+}
+
+impl RunUsage {
+    /// Add another usage record to this one
+    pub fn add(&mut self, other: &RunUsage) {
+        self.wall_time_ms += other.wall_time_ms;
+        for (tool, count) in &other.tool_invocations {
+            *self.tool_invocations.entry(tool.clone()).or_default() += count;
+        }
+        self.http_requests += other.http_requests;
+        self.http_response_bytes += other.http_response_bytes;
+        self.mcp_calls += other.mcp_calls;
+        self.llm_tokens += other.llm_tokens;
+    }
 }
 
 /// Run execution status
@@ -608,9 +964,36 @@ pub struct StepRun {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 
+    /// What made the step fail (if failed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<crate::StepErrorKind>,
+
+    /// Rendered tool inputs sent to the adapter (secrets redacted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<HashMap<String, serde_json::Value>>,
+
     /// Step outputs
     #[serde(skip_serializing_if = "Option::is_none")]
     pub outputs: Option<HashMap<String, serde_json::Value>>,
+
+    /// Latest progress reported by the adapter (for long-running steps)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<StepProgress>,
+}
+
+/// Progress snapshot reported by a running step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepProgress {
+    /// Completion percentage (0-100), if the total amount of work is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f64>,
+
+    /// Human-readable progress message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// When the progress was reported
+    pub updated_at: DateTime<Utc>,
 }
 
 /// Step execution status
@@ -636,6 +1019,143 @@ pub enum StepStatus {
     Skipped,
 }
 
+/// Webhook request kept for replay (see `webhooks replay`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    /// Unique payload identifier
+    pub id: Uuid,
+
+    /// Webhook provider the request was sent to (`/webhooks/{provider}`)
+    pub provider: String,
+
+    /// Request headers, with secret-bearing headers redacted
+    pub headers: HashMap<String, String>,
+
+    /// Parsed JSON body
+    pub body: serde_json::Value,
+
+    /// When the request was received
+    pub received_at: DateTime<Utc>,
+}
+
+/// Outbound HTTP request queued for retry by `core.deliver`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboundDelivery {
+    /// Unique delivery identifier
+    pub id: Uuid,
+
+    /// HTTP method
+    pub method: String,
+
+    /// Target URL
+    pub url: String,
+
+    /// Request headers; empty while the delivery is sealed
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+
+    /// JSON request body; absent while the delivery is sealed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+
+    /// Headers and body encrypted for storage (see [`crate::engine::delivery`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sealed: Option<String>,
+
+    /// Pace of requests to the URL's host, shared with other deliveries to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<crate::adapter::RateLimit>,
+
+    /// Attempts made so far
+    pub attempts: u32,
+
+    /// Attempts after which the delivery is dead-lettered
+    pub max_attempts: u32,
+
+    /// Why the latest attempt failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+
+    /// When the next attempt is due
+    pub next_attempt_at: DateTime<Utc>,
+
+    /// When the delivery was first attempted
+    pub created_at: DateTime<Utc>,
+
+    /// When the delivery was moved to the dead-letter queue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_lettered_at: Option<DateTime<Utc>>,
+
+    /// Run that made the delivery
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<Uuid>,
+}
+
+/// Backend location of a blob behind a stable `beemflow://blobs/<id>` URL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlobRecord {
+    /// Blob identifier (the `<id>` in its URL)
+    pub id: Uuid,
+
+    /// Blob store driver holding the object (`filesystem`, `s3`)
+    pub backend: String,
+
+    /// Named store from `blob_stores` holding the object, `None` for the default store
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store: Option<String>,
+
+    /// Backend-native location (`file://...`, `s3://bucket/key`)
+    pub location: String,
+
+    /// When the blob was stored
+    pub created_at: DateTime<Utc>,
+}
+
+/// Event to enqueue in the outbox alongside a storage write
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingEvent {
+    /// Topic the event is published on
+    pub topic: String,
+
+    /// Event payload
+    pub payload: serde_json::Value,
+
+    /// What emitted the event
+    #[serde(default)]
+    pub source: crate::event::EventSource,
+
+    /// Correlation ID of the action that caused the event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+/// Event recorded in the outbox, published to the event bus after its write commits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboxEvent {
+    /// Outbox identifier; consumers dedupe redeliveries on it
+    pub id: Uuid,
+
+    /// Topic the event is published on
+    pub topic: String,
+
+    /// Position of the event within its topic, starting at 1
+    pub sequence: i64,
+
+    /// Event payload
+    pub payload: serde_json::Value,
+
+    /// What emitted the event
+    #[serde(default)]
+    pub source: crate::event::EventSource,
+
+    /// Correlation ID of the action that caused the event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+
+    /// When the event was enqueued
+    pub created_at: DateTime<Utc>,
+}
+
 /// OAuth credential for managing OAuth2.0 credentials
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OAuthCredential {
@@ -665,6 +1185,10 @@ pub struct OAuthCredential {
 
     /// Last update time
     pub updated_at: DateTime<Utc>,
+
+    /// Tenant the credential belongs to; `None` for credentials shared by all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
 }
 
 // Validation macros for required fields
@@ -792,6 +1316,10 @@ pub struct OAuthClient {
 
     /// Last update time
     pub updated_at: DateTime<Utc>,
+
+    /// Tenant the client's tokens are scoped to (see [`crate::auth::tenant`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
 }
 
 /// OAuth token information
@@ -858,6 +1386,147 @@ mod tests {
     // Model Tests
     // ========================================================================
 
+    #[test]
+    fn test_flow_environment_overlay() {
+        let yaml = r##"
+name: notify
+on: cli.manual
+vars:
+  channel: "#dev"
+  retries: 1
+environments:
+  prod:
+    vars:
+      channel: "#alerts"
+      endpoint: https://api.example.com
+  staging: {}
+steps:
+  - id: post
+    use: core.echo
+"##;
+        let flow: Flow = serde_yaml::from_str(yaml).unwrap();
+
+        let prod = flow.in_environment("prod").unwrap();
+        let vars = prod.vars.unwrap();
+        assert_eq!(vars["channel"], "#alerts"); // overlay wins
+        assert_eq!(vars["retries"], 1); // base kept
+        assert_eq!(vars["endpoint"], "https://api.example.com");
+
+        // An empty overlay leaves the base untouched
+        assert_eq!(flow.in_environment("staging").unwrap().vars, flow.vars);
+        assert!(flow.in_environment("qa").is_err());
+    }
+
+    #[test]
+    fn test_stage_keyed_vars_follow_the_active_stage() {
+        let yaml = r##"
+name: notify
+on: cli.manual
+vars:
+  api_url:
+    dev: http://localhost:8080
+    prod: https://api.example.com
+    default: https://staging.example.com
+  retries:
+    prod: 5
+    default: 1
+environments:
+  dev: {}
+  prod: {}
+steps:
+  - id: post
+    use: core.echo
+"##;
+        let flow: Flow = serde_yaml::from_str(yaml).unwrap();
+        let vars = |environment: &str| flow.in_environment(environment).unwrap().vars.unwrap();
+
+        assert_eq!(vars("dev")["api_url"], "http://localhost:8080");
+        assert_eq!(vars("prod")["api_url"], "https://api.example.com");
+        assert_eq!(vars("prod")["retries"], 5);
+        assert_eq!(vars("dev")["retries"], 1);
+
+        // Only declared environments count
+        assert!(flow.has_environment("dev"));
+        assert!(flow.in_environment("qa").is_err());
+
+        // Without a stage, `default` applies
+        let mut base = flow.clone();
+        base.resolve_stage_vars(None).unwrap();
+        let vars = base.vars.unwrap();
+        assert_eq!(vars["api_url"], "https://staging.example.com");
+        assert_eq!(vars["retries"], 1);
+    }
+
+    #[test]
+    fn test_stage_keyed_vars_need_a_value_for_the_stage() {
+        let yaml = r##"
+name: notify
+on: cli.manual
+vars:
+  retries:
+    prod: 5
+environments:
+  dev: {}
+  prod: {}
+steps:
+  - id: post
+    use: core.echo
+"##;
+        let flow: Flow = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(
+            flow.in_environment("prod").unwrap().vars.unwrap()["retries"],
+            5
+        );
+        let err = flow.in_environment("dev").unwrap_err().to_string();
+        assert!(
+            err.contains("'retries'") && err.contains("'dev'"),
+            "{}",
+            err
+        );
+        assert!(flow.clone().resolve_stage_vars(None).is_err());
+    }
+
+    #[test]
+    fn test_object_vars_are_not_stage_keyed() {
+        let yaml = r##"
+name: notify
+on: cli.manual
+vars:
+  defaults:
+    default: 10
+    prod: 20
+  headers:
+    accept: application/json
+  routing:
+    prod: main
+    default: fallback
+    region: eu
+environments:
+  prod: {}
+steps:
+  - id: post
+    use: core.echo
+"##;
+        let flow: Flow = serde_yaml::from_str(yaml).unwrap();
+        let prod = flow.in_environment("prod").unwrap().vars.unwrap();
+
+        // A key that isn't a declared environment makes a map an ordinary object
+        assert_eq!(prod["headers"]["accept"], "application/json");
+        assert_eq!(prod["routing"]["region"], "eu");
+        assert_eq!(prod["routing"]["prod"], "main");
+        // Maps of declared environments and `default` are stage-keyed
+        assert_eq!(prod["defaults"], 20);
+
+        // Without `environments`, no var is stage-keyed, and no key is an environment
+        let mut plain = flow.clone();
+        plain.environments = None;
+        assert!(!plain.has_environment("prod"));
+        let vars = plain.vars.clone().unwrap();
+        plain.resolve_stage_vars(None).unwrap();
+        assert_eq!(plain.vars.unwrap(), vars);
+    }
+
     #[test]
     fn test_flow_deserialization() {
         let yaml = r#"
@@ -890,6 +1559,25 @@ steps:
         assert!(!multiple.includes("http.request"));
     }
 
+    #[test]
+    fn test_trigger_event_defaults() {
+        let trigger: Trigger = serde_json::from_value(serde_json::json!([
+            "cli.manual",
+            {"event": "order.created", "defaults": {"region": "eu", "priority": "low"}},
+            {"event": "order.updated", "defaults": {"region": "us"}}
+        ]))
+        .unwrap();
+        let defaults = trigger.event_defaults();
+        assert_eq!(defaults["region"], "eu");
+        assert_eq!(defaults["priority"], "low");
+
+        assert!(
+            Trigger::Single("cli.manual".to_string())
+                .event_defaults()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_oauth_credential_expired() {
         let mut cred = OAuthCredential {
@@ -902,6 +1590,7 @@ steps:
             scope: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            tenant_id: None,
         };
 
         assert!(cred.is_expired());
@@ -920,7 +1609,7 @@ steps:
         assert!(FlowName::new("my-flow").is_ok());
         assert!(FlowName::new("my_flow_123").is_ok());
         assert!(FlowName::new("MyFlow").is_ok());
-        assert!(FlowName::new("flow.name").is_ok());
+        assert!(FlowName::new("a".repeat(FlowName::MAX_LEN)).is_ok());
     }
 
     #[test]
@@ -929,6 +1618,12 @@ steps:
         assert!(FlowName::new("   ").is_err());
         assert!(FlowName::new("my flow").is_err()); // spaces not allowed
         assert!(FlowName::new("my/flow").is_err()); // slashes not allowed
+        assert!(FlowName::new("flow.name").is_err()); // dots not allowed
+        assert!(FlowName::new("../../etc/cron.d/evil").is_err());
+        assert!(FlowName::new("..").is_err());
+        assert!(FlowName::new("fl\u{00f6}w").is_err()); // ASCII only
+        assert!(FlowName::new("a".repeat(FlowName::MAX_LEN + 1)).is_err());
+        assert!(FlowName::try_from("my\\flow").is_err());
     }
 
     #[test]
@@ -952,6 +1647,8 @@ steps:
         let token1 = ResumeToken::generate();
         let token2 = ResumeToken::generate();
         assert_ne!(token1, token2); // Should be unique
+        assert_eq!(token1.len(), 64); // 256 bits, hex-encoded
+        assert!(ResumeToken::new(token1.into_inner()).is_ok());
     }
 
     #[test]
@@ -993,5 +1690,8 @@ steps:
 
         let deserialized: FlowName = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized, flow);
+
+        // Deserialization validates
+        assert!(serde_json::from_str::<FlowName>("\"../evil\"").is_err());
     }
 }
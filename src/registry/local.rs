@@ -22,6 +22,11 @@ impl LocalRegistry {
         Self { path: path_buf }
     }
 
+    /// File the registry is stored in
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
     /// List all entries from local registry
     pub async fn list_servers(&self) -> Result<Vec<RegistryEntry>> {
         if !self.path.exists() {
@@ -9,6 +9,8 @@ use crate::{Result, config::Config};
 pub struct RegistryManager {
     registries: Vec<Box<dyn RegistrySource>>,
     secrets_provider: std::sync::Arc<dyn crate::secrets::SecretsProvider>,
+    /// Path of the writable local registry
+    local_path: String,
 }
 
 /// Trait for registry sources
@@ -71,6 +73,7 @@ impl RegistryManager {
         Self {
             registries,
             secrets_provider,
+            local_path: String::new(),
         }
     }
 
@@ -123,6 +126,7 @@ impl RegistryManager {
         Self {
             registries,
             secrets_provider,
+            local_path: local_path.to_string(),
         }
     }
 
@@ -244,28 +248,91 @@ impl RegistryManager {
         Ok(None)
     }
 
+    /// Path of the writable local registry file
+    pub fn local_registry_path(&self) -> std::path::PathBuf {
+        LocalRegistry::new(&self.local_path).path().to_path_buf()
+    }
+
+    /// Add or replace an entry in the local registry
+    ///
+    /// Writes to the local registry this manager reads from, so the entry is
+    /// visible to subsequent lookups.
+    pub async fn upsert_local_entry(&self, mut entry: RegistryEntry) -> Result<()> {
+        entry.registry = Some("local".to_string());
+        LocalRegistry::new(&self.local_path)
+            .upsert_entry(entry)
+            .await
+    }
+
     /// Register a tool from a manifest in the local registry
-    pub async fn register_tool_from_manifest(&self, manifest: serde_json::Value) -> Result<()> {
-        // Deserialize manifest to RegistryEntry
-        let mut entry: RegistryEntry = serde_json::from_value(manifest).map_err(|e| {
+    ///
+    /// The manifest must describe a `tool` with a name and an `http(s)`
+    /// endpoint. Returns the entry as stored.
+    pub async fn register_tool_from_manifest(
+        &self,
+        manifest: serde_json::Value,
+    ) -> Result<RegistryEntry> {
+        let entry: RegistryEntry = serde_json::from_value(manifest).map_err(|e| {
             crate::error::BeemFlowError::validation(format!("Invalid tool manifest: {}", e))
         })?;
+        let mut installed = self.register_tools(vec![entry]).await?;
+        Ok(installed.remove(0))
+    }
 
-        // Ensure it's a tool
-        if entry.entry_type != "tool" {
-            return Err(crate::error::BeemFlowError::validation(format!(
-                "Expected type 'tool', got '{}'",
-                entry.entry_type
-            )));
+    /// Register tools in the local registry, validating all before writing any
+    pub async fn register_tools(&self, entries: Vec<RegistryEntry>) -> Result<Vec<RegistryEntry>> {
+        for entry in &entries {
+            validate_tool_manifest(entry)?;
         }
 
-        // Mark as local registry
-        entry.registry = Some("local".to_string());
+        let mut installed = Vec::with_capacity(entries.len());
+        for mut entry in entries {
+            entry.registry = Some("local".to_string());
+            self.upsert_local_entry(entry.clone()).await?;
+            installed.push(entry);
+        }
+        Ok(installed)
+    }
+}
+
+/// Check a tool manifest is complete enough to be called
+fn validate_tool_manifest(entry: &RegistryEntry) -> Result<()> {
+    let invalid = |msg: String| {
+        crate::error::BeemFlowError::validation(format!("Invalid tool manifest: {}", msg))
+    };
+
+    if entry.entry_type != "tool" {
+        return Err(invalid(format!(
+            "expected type 'tool', got '{}'",
+            entry.entry_type
+        )));
+    }
+    if entry.name.trim().is_empty() {
+        return Err(invalid("'name' must not be empty".to_string()));
+    }
 
-        // Use the default local registry path (~/.beemflow/registry.json)
-        let local_path = crate::constants::default_local_registry_path();
-        let local_registry = LocalRegistry::new(local_path);
-        local_registry.upsert_entry(entry).await?;
-        Ok(())
+    let endpoint = entry
+        .endpoint
+        .as_deref()
+        .ok_or_else(|| invalid(format!("'{}' has no 'endpoint'", entry.name)))?;
+    match url::Url::parse(endpoint) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+        _ => {
+            return Err(invalid(format!(
+                "'{}' endpoint '{}' is not an http(s) URL",
+                entry.name, endpoint
+            )));
+        }
     }
+
+    if let Some(method) = &entry.method
+        && reqwest::Method::from_bytes(method.to_uppercase().as_bytes()).is_err()
+    {
+        return Err(invalid(format!(
+            "'{}' has invalid method '{}'",
+            entry.name, method
+        )));
+    }
+
+    Ok(())
 }
@@ -69,6 +69,18 @@ pub struct RegistryEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub headers: Option<HashMap<String, String>>,
 
+    /// HTTP body encoding (for tools): json (default), form or raw
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_format: Option<crate::adapter::BodyFormat>,
+
+    /// Call rate limit shared by all runs (for tools)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<crate::adapter::RateLimit>,
+
+    /// Read `text/event-stream` responses, assembling their deltas (for tools)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<crate::adapter::StreamSetting>,
+
     /// MCP command (for mcp_server)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub command: Option<String>,
@@ -17,6 +17,9 @@ fn test_registry_operations() {
         endpoint: Some("https://example.com/api".to_string()),
         method: Some("GET".to_string()),
         headers: None,
+        body_format: None,
+        rate_limit: None,
+        stream: None,
         command: None,
         args: None,
         env: None,
@@ -131,3 +134,43 @@ async fn test_registry_manager_fallback() {
         "Should have entries from default registry"
     );
 }
+
+#[tokio::test]
+async fn test_remote_registry_retries_server_errors() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+    // Registered later mocks take precedence until they are used up
+    Mock::given(method("GET"))
+        .and(path("/registry.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            {"type": "tool", "name": "remote.tool"}
+        ])))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/registry.json"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    let remote = RemoteRegistry::new(&format!("{}/registry.json", mock_server.uri()), "hub");
+    let entries = remote.list_servers().await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].registry.as_deref(), Some("hub"));
+
+    // Client errors are not retried
+    let missing = RemoteRegistry::new(&format!("{}/missing.json", mock_server.uri()), "hub");
+    let err = missing.list_servers().await.unwrap_err().to_string();
+    assert!(err.contains("Failed to fetch registry 'hub'"), "{}", err);
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(
+        requests
+            .iter()
+            .filter(|r| r.url.path() == "/missing.json")
+            .count(),
+        1
+    );
+}
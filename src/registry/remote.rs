@@ -3,6 +3,7 @@
 //! Fetches tools from remote HTTP registries.
 
 use super::*;
+use crate::utils::retry::{RetryPolicy, is_transient_http_error, with_backoff};
 
 /// Remote registry (HTTP-based)
 pub struct RemoteRegistry {
@@ -20,21 +21,25 @@ impl RemoteRegistry {
     }
 
     /// List all entries from remote registry
+    ///
+    /// Connection failures, timeouts and `5xx` responses are retried with backoff.
     pub async fn list_servers(&self) -> Result<Vec<RegistryEntry>> {
-        // Fetch from URL
-        let response = reqwest::get(&self.url).await.map_err(|e| {
-            crate::BeemFlowError::Network(crate::error::NetworkError::Http(format!(
-                "Failed to fetch registry '{}' from {}: {}",
-                self.name, self.url, e
-            )))
-        })?;
-
-        let mut entries: Vec<RegistryEntry> = response.json().await.map_err(|e| {
-            crate::BeemFlowError::Network(crate::error::NetworkError::Http(format!(
-                "Failed to parse registry '{}' from {}: {}",
-                self.name, self.url, e
-            )))
-        })?;
+        let mut entries: Vec<RegistryEntry> =
+            with_backoff(&RetryPolicy::default(), is_transient_http_error, || async {
+                reqwest::get(&self.url)
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await
+            })
+            .await
+            .map_err(|e| {
+                let action = if e.is_decode() { "parse" } else { "fetch" };
+                crate::BeemFlowError::Network(crate::error::NetworkError::Http(format!(
+                    "Failed to {} registry '{}' from {}: {}",
+                    action, self.name, self.url, e
+                )))
+            })?;
 
         // Label all entries with registry name
         for entry in &mut entries {
@@ -15,10 +15,10 @@ use super::*;
 ///
 /// ## Design
 ///
-/// - **Zero-sized type**: No runtime overhead
 /// - **Simple 1:1 mapping**: Environment variables directly become secrets
 /// - **No filtering**: All environment variables are accessible
-/// - **.env support**: Automatically loads .env file on creation
+/// - **.env support**: Automatically loads .env file on creation, or reads the
+///   files listed in `secrets.envFiles` (see [`EnvSecretsProvider::with_env_files`])
 ///
 /// ## Usage
 ///
@@ -31,7 +31,10 @@ use super::*;
 /// # Ok(())
 /// # }
 /// ```
-pub struct EnvSecretsProvider;
+pub struct EnvSecretsProvider {
+    /// Variables read from `secrets.envFiles`, consulted after the process env
+    file_vars: HashMap<String, String>,
+}
 
 impl EnvSecretsProvider {
     /// Create a new environment-based secrets provider
@@ -58,7 +61,43 @@ impl EnvSecretsProvider {
         // Failure is not an error - .env files are optional
         let _ = dotenvy::dotenv();
 
-        Self
+        Self {
+            file_vars: HashMap::new(),
+        }
+    }
+
+    /// Create a provider that reads `files` instead of the .env file
+    ///
+    /// Files are read in order, each overriding variables from those before
+    /// it, and never override the process environment: process env > later
+    /// file > earlier file. Relative paths are resolved against the current
+    /// directory, and missing files are skipped.
+    ///
+    /// The files are kept by the provider rather than copied into the process
+    /// environment, so a provider created after a file is edited sees the edit.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use beemflow::secrets::EnvSecretsProvider;
+    ///
+    /// // Values in .env.local win over those in .env
+    /// let provider = EnvSecretsProvider::with_env_files(&[".env", ".env.local"]);
+    /// ```
+    pub fn with_env_files<P: AsRef<std::path::Path>>(files: &[P]) -> Self {
+        let mut file_vars = HashMap::new();
+        for file in files {
+            let file = file.as_ref();
+            let vars = dotenvy::from_path_iter(file)
+                .and_then(|vars| vars.collect::<std::result::Result<Vec<_>, _>>());
+            match vars {
+                Ok(vars) => file_vars.extend(vars),
+                Err(e) if e.not_found() => {}
+                Err(e) => tracing::warn!("Failed to load env file {}: {}", file.display(), e),
+            }
+        }
+
+        Self { file_vars }
     }
 }
 
@@ -73,13 +112,17 @@ impl SecretsProvider for EnvSecretsProvider {
     async fn get_secret(&self, key: &str) -> Result<Option<String>> {
         // Simple: environment variables directly become secrets
         // This is the ONLY place where std::env::var() should be called
-        Ok(std::env::var(key).ok())
+        Ok(std::env::var(key)
+            .ok()
+            .or_else(|| self.file_vars.get(key).cloned()))
     }
 
     async fn get_all_secrets(&self) -> Result<HashMap<String, String>> {
-        // Return ALL environment variables
+        // Return ALL environment variables, over those from env files
         // This is the ONLY place where std::env::vars() should be called
-        Ok(std::env::vars().collect())
+        let mut secrets = self.file_vars.clone();
+        secrets.extend(std::env::vars());
+        Ok(secrets)
     }
 }
 
@@ -132,6 +175,80 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_env_provider_later_env_file_overrides_earlier() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = dir.path().join(".env");
+        let env_local = dir.path().join(".env.local");
+        std::fs::write(
+            &env,
+            "ENV_FILES_TEST_SHARED=from_env\nENV_FILES_TEST_BASE=base\n",
+        )
+        .unwrap();
+        std::fs::write(&env_local, "ENV_FILES_TEST_SHARED=from_env_local\n").unwrap();
+
+        let provider =
+            EnvSecretsProvider::with_env_files(&[env, env_local, dir.path().join(".env.missing")]);
+
+        assert_eq!(
+            provider.get_secret("ENV_FILES_TEST_SHARED").await.unwrap(),
+            Some("from_env_local".to_string())
+        );
+        assert_eq!(
+            provider.get_secret("ENV_FILES_TEST_BASE").await.unwrap(),
+            Some("base".to_string())
+        );
+        assert_eq!(
+            provider.get_all_secrets().await.unwrap()["ENV_FILES_TEST_SHARED"],
+            "from_env_local"
+        );
+        // The files stay with the provider
+        assert!(std::env::var("ENV_FILES_TEST_SHARED").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_env_provider_sees_edited_env_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let env_local = dir.path().join(".env.local");
+        std::fs::write(&env_local, "ENV_FILES_TEST_EDITED=before\n").unwrap();
+        let before = EnvSecretsProvider::with_env_files(std::slice::from_ref(&env_local));
+
+        std::fs::write(&env_local, "ENV_FILES_TEST_EDITED=after\n").unwrap();
+        let after = EnvSecretsProvider::with_env_files(&[env_local]);
+
+        assert_eq!(
+            before.get_secret("ENV_FILES_TEST_EDITED").await.unwrap(),
+            Some("before".to_string())
+        );
+        assert_eq!(
+            after.get_secret("ENV_FILES_TEST_EDITED").await.unwrap(),
+            Some("after".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_env_provider_process_env_overrides_env_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = dir.path().join(".env");
+        let env_local = dir.path().join(".env.local");
+        std::fs::write(&env, "ENV_FILES_TEST_PROCESS=from_env\n").unwrap();
+        std::fs::write(&env_local, "ENV_FILES_TEST_PROCESS=from_env_local\n").unwrap();
+        unsafe {
+            std::env::set_var("ENV_FILES_TEST_PROCESS", "from_process");
+        }
+
+        let provider = EnvSecretsProvider::with_env_files(&[env, env_local]);
+
+        assert_eq!(
+            provider.get_secret("ENV_FILES_TEST_PROCESS").await.unwrap(),
+            Some("from_process".to_string())
+        );
+
+        unsafe {
+            std::env::remove_var("ENV_FILES_TEST_PROCESS");
+        }
+    }
+
     #[tokio::test]
     async fn test_env_provider_default() {
         let provider = EnvSecretsProvider::new();
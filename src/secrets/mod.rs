@@ -24,9 +24,12 @@ pub use env::EnvSecretsProvider;
 use crate::Result;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+/// Placeholder written in place of secret values when data is persisted or displayed
+pub const REDACTED: &str = "***REDACTED***";
+
 /// Provides access to secrets and environment variables
 ///
 /// This is the ONLY way to access environment variables in BeemFlow.
@@ -212,6 +215,142 @@ pub async fn expand_value(value: &str, provider: &Arc<dyn SecretsProvider>) -> R
     Ok(result)
 }
 
+/// Collect the names of secrets referenced by a template string
+///
+/// Recognizes `secrets.NAME`, `secrets['NAME']`, `secrets["NAME"]` and `$env:NAME`.
+pub fn referenced_secret_names(template: &str) -> HashSet<String> {
+    static SECRET_REF_PATTERN: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r#"secrets\.([A-Za-z_][A-Za-z0-9_]*)|secrets\[\s*['"]([^'"]+)['"]\s*\]|\$env:([A-Za-z_][A-Za-z0-9_]*)"#,
+        )
+        .expect("Invalid secret reference regex")
+    });
+
+    SECRET_REF_PATTERN
+        .captures_iter(template)
+        .filter_map(|caps| caps.get(1).or(caps.get(2)).or(caps.get(3)))
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+/// Replace every occurrence of the given secret values inside a JSON value with [`REDACTED`]
+///
+/// Strings are searched recursively through arrays and objects. Empty secret
+/// values are ignored so they never blank out unrelated data.
+pub fn redact_value(value: &serde_json::Value, secret_values: &[String]) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::String(s) => {
+            let mut redacted = s.clone();
+            for secret in secret_values.iter().filter(|v| !v.is_empty()) {
+                if redacted.contains(secret.as_str()) {
+                    redacted = redacted.replace(secret.as_str(), REDACTED);
+                }
+            }
+            Value::String(redacted)
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| redact_value(item, secret_values))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), redact_value(v, secret_values)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// What happens when a step output contains a secret value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputScanAction {
+    /// Replace the secret with [`REDACTED`] and continue
+    #[default]
+    Redact,
+    /// Fail the step
+    Fail,
+}
+
+/// Scans step outputs for a run's secret values before they are stored or returned
+///
+/// Enabled by `secrets.outputScan` in the config. Clones share the value list.
+#[derive(Debug, Clone)]
+pub struct OutputScanner {
+    action: OutputScanAction,
+    secret_values: Arc<Vec<String>>,
+}
+
+impl OutputScanner {
+    /// Scan for `secret_values`; empty values are ignored
+    pub fn new(action: OutputScanAction, secret_values: Vec<String>) -> Self {
+        Self {
+            action,
+            secret_values: Arc::new(
+                secret_values
+                    .into_iter()
+                    .filter(|v| !v.is_empty())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Check the outputs of `step_id`
+    ///
+    /// Returns them with secrets redacted, or an error when the action is `fail`.
+    /// The error doesn't say which secret was found.
+    pub fn scan(
+        &self,
+        step_id: &str,
+        outputs: HashMap<String, serde_json::Value>,
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        let leaked = outputs
+            .values()
+            .any(|value| contains_secret(value, &self.secret_values));
+        if !leaked {
+            return Ok(outputs);
+        }
+
+        match self.action {
+            OutputScanAction::Redact => {
+                tracing::warn!(
+                    "Redacted a secret value from the outputs of step '{}'",
+                    step_id
+                );
+                Ok(outputs
+                    .iter()
+                    .map(|(k, v)| (k.clone(), redact_value(v, &self.secret_values)))
+                    .collect())
+            }
+            OutputScanAction::Fail => Err(crate::BeemFlowError::step_execution(
+                step_id,
+                crate::StepErrorKind::Validation,
+                "output contains a secret value",
+            )),
+        }
+    }
+}
+
+/// Whether any string inside `value` contains one of `secret_values` verbatim
+fn contains_secret(value: &serde_json::Value, secret_values: &[String]) -> bool {
+    use serde_json::Value;
+
+    match value {
+        Value::String(s) => secret_values
+            .iter()
+            .any(|secret| s.contains(secret.as_str())),
+        Value::Array(items) => items
+            .iter()
+            .any(|item| contains_secret(item, secret_values)),
+        Value::Object(map) => map.values().any(|v| contains_secret(v, secret_values)),
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -315,4 +454,36 @@ mod tests {
             std::env::remove_var("TEST_SECRET");
         }
     }
+
+    #[test]
+    fn test_referenced_secret_names() {
+        let names = referenced_secret_names(
+            "Bearer {{ secrets.API_KEY }} {{ secrets['OTHER'] }} $env:HOME_TOKEN {{ vars.x }}",
+        );
+
+        assert_eq!(names.len(), 3);
+        assert!(names.contains("API_KEY"));
+        assert!(names.contains("OTHER"));
+        assert!(names.contains("HOME_TOKEN"));
+    }
+
+    #[test]
+    fn test_redact_value_nested() {
+        let value = serde_json::json!({
+            "headers": {"Authorization": "Bearer sk-123"},
+            "items": ["sk-123", "public"],
+            "count": 3
+        });
+
+        let redacted = redact_value(&value, &["sk-123".to_string(), String::new()]);
+
+        assert_eq!(
+            redacted,
+            serde_json::json!({
+                "headers": {"Authorization": format!("Bearer {}", REDACTED)},
+                "items": [REDACTED, "public"],
+                "count": 3
+            })
+        );
+    }
 }
@@ -0,0 +1,625 @@
+//! Read-through cache of deployed flows
+//!
+//! [`CachedStorage`] wraps another [`Storage`] and memoizes the flow reads
+//! that webhook routing and run starts repeat: a flow's deployed version, the
+//! content of a flow version, and the list of all deployed flows. Deploys,
+//! rollbacks and disables made through it invalidate the flow's entries at
+//! once; changes made by other processes are seen when entries expire. Every
+//! other call goes straight to the wrapped storage.
+//!
+//! Enabled with `storage.flowCache` in `flow.config.json`:
+//!
+//! ```json
+//! {"storage": {"driver": "postgres", "dsn": "...", "flowCache": {"ttlSecs": 30, "capacity": 1000}}}
+//! ```
+
+use super::integrity::IntegrityReport;
+use super::search::FlowSearchResult;
+use super::{
+    FlowFilter, FlowRunStats, FlowSnapshot, FlowStorage, FlowSummary, OAuthStorage, OutboxStorage,
+    PageCursor, RunFilter, RunStorage, StateStorage, Storage, WriteBatch,
+};
+use crate::config::FlowCacheConfig;
+use crate::{Result, model::*};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// What a cache entry holds
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum CacheKey {
+    /// A flow's deployed version
+    DeployedVersion(String),
+    /// The content of one version of a flow
+    VersionContent(String, String),
+    /// Every deployed flow with its content
+    AllDeployed,
+}
+
+impl CacheKey {
+    /// Whether the entry depends on flow `name`'s deployments
+    fn concerns(&self, name: &str) -> bool {
+        match self {
+            CacheKey::DeployedVersion(flow) | CacheKey::VersionContent(flow, _) => flow == name,
+            CacheKey::AllDeployed => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum CachedValue {
+    Text(Option<String>),
+    Flows(Arc<Vec<(String, String)>>),
+}
+
+struct CacheEntry {
+    value: CachedValue,
+    stored_at: Instant,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    /// Bumped by every invalidation, so reads that raced one aren't stored
+    generation: u64,
+}
+
+/// Cache hits and misses since the cache was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Storage caching deployed flow reads of the storage it wraps
+pub struct CachedStorage {
+    inner: Arc<dyn Storage>,
+    ttl: Duration,
+    capacity: usize,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CachedStorage {
+    /// Wrap `inner`, caching as `config` says
+    pub fn new(inner: Arc<dyn Storage>, config: &FlowCacheConfig) -> Self {
+        Self {
+            inner,
+            ttl: Duration::from_secs(config.ttl_secs),
+            capacity: config.capacity.max(1),
+            state: Mutex::new(CacheState::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cache hits and misses so far
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The live entry for `key`, if any, and the generation to store a fresh read under
+    fn lookup(&self, key: &CacheKey) -> (Option<CachedValue>, u64) {
+        let mut state = self.state.lock();
+        let live = match state.entries.get(key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => Some(entry.value.clone()),
+            Some(_) => {
+                state.entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counter = if live.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        (live, state.generation)
+    }
+
+    /// Store a value read at `generation`, unless an invalidation came since
+    fn store(&self, key: CacheKey, value: CachedValue, generation: u64) {
+        let mut state = self.state.lock();
+        if state.generation != generation {
+            return;
+        }
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(&key) {
+            let ttl = self.ttl;
+            state
+                .entries
+                .retain(|_, entry| entry.stored_at.elapsed() < ttl);
+            if state.entries.len() >= self.capacity
+                && let Some(oldest) = state
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.stored_at)
+                    .map(|(key, _)| key.clone())
+            {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.entries.insert(
+            key,
+            CacheEntry {
+                value,
+                stored_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Drop every entry that depends on flow `name`'s deployments
+    fn invalidate(&self, name: &FlowName) {
+        let mut state = self.state.lock();
+        state.generation += 1;
+        state.entries.retain(|key, _| !key.concerns(name.as_str()));
+    }
+
+    /// Read an optional string through the cache
+    async fn read_text<F>(&self, key: CacheKey, read: F) -> Result<Option<String>>
+    where
+        F: std::future::Future<Output = Result<Option<String>>>,
+    {
+        let generation = match self.lookup(&key) {
+            (Some(CachedValue::Text(text)), _) => return Ok(text),
+            (_, generation) => generation,
+        };
+        let text = read.await?;
+        self.store(key, CachedValue::Text(text.clone()), generation);
+        Ok(text)
+    }
+}
+
+#[async_trait]
+impl RunStorage for CachedStorage {
+    async fn save_run(&self, run: &Run) -> Result<()> {
+        self.inner.save_run(run).await
+    }
+
+    async fn get_run(&self, id: Uuid) -> Result<Option<Run>> {
+        self.inner.get_run(id).await
+    }
+
+    async fn list_runs(&self, limit: usize, offset: usize) -> Result<Vec<Run>> {
+        self.inner.list_runs(limit, offset).await
+    }
+
+    async fn list_runs_for_tenant(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Run>> {
+        self.inner
+            .list_runs_for_tenant(tenant_id, limit, offset)
+            .await
+    }
+
+    async fn list_runs_after(
+        &self,
+        filter: &RunFilter,
+        limit: usize,
+        after: Option<&PageCursor<Uuid>>,
+    ) -> Result<Vec<Run>> {
+        self.inner.list_runs_after(filter, limit, after).await
+    }
+
+    async fn list_runs_by_flow_and_status(
+        &self,
+        flow_name: &str,
+        status: RunStatus,
+        exclude_id: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<Run>> {
+        self.inner
+            .list_runs_by_flow_and_status(flow_name, status, exclude_id, limit)
+            .await
+    }
+
+    async fn delete_run(&self, id: Uuid) -> Result<()> {
+        self.inner.delete_run(id).await
+    }
+
+    async fn try_insert_run(&self, run: &Run) -> Result<bool> {
+        self.inner.try_insert_run(run).await
+    }
+
+    async fn run_stats(
+        &self,
+        since: DateTime<Utc>,
+        flow_name: Option<&str>,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<FlowRunStats>> {
+        self.inner.run_stats(since, flow_name, tenant_id).await
+    }
+
+    async fn save_step(&self, step: &StepRun) -> Result<()> {
+        self.inner.save_step(step).await
+    }
+
+    async fn get_steps(&self, run_id: Uuid) -> Result<Vec<StepRun>> {
+        self.inner.get_steps(run_id).await
+    }
+
+    async fn verify_integrity(&self, flow_name: Option<&str>) -> Result<IntegrityReport> {
+        self.inner.verify_integrity(flow_name).await
+    }
+}
+
+#[async_trait]
+impl StateStorage for CachedStorage {
+    async fn register_wait(&self, token: Uuid, wake_at: Option<i64>) -> Result<()> {
+        self.inner.register_wait(token, wake_at).await
+    }
+
+    async fn resolve_wait(&self, token: Uuid) -> Result<Option<Run>> {
+        self.inner.resolve_wait(token).await
+    }
+
+    async fn save_paused_run(
+        &self,
+        token: &str,
+        source: &str,
+        data: serde_json::Value,
+    ) -> Result<()> {
+        self.inner.save_paused_run(token, source, data).await
+    }
+
+    async fn load_paused_runs(&self) -> Result<HashMap<String, serde_json::Value>> {
+        self.inner.load_paused_runs().await
+    }
+
+    async fn find_paused_runs_by_source(
+        &self,
+        source: &str,
+    ) -> Result<Vec<(String, serde_json::Value)>> {
+        self.inner.find_paused_runs_by_source(source).await
+    }
+
+    async fn delete_paused_run(&self, token: &str) -> Result<()> {
+        self.inner.delete_paused_run(token).await
+    }
+
+    async fn fetch_and_delete_paused_run(&self, token: &str) -> Result<Option<serde_json::Value>> {
+        self.inner.fetch_and_delete_paused_run(token).await
+    }
+
+    async fn save_webhook_payload(&self, payload: &WebhookPayload, keep: usize) -> Result<()> {
+        self.inner.save_webhook_payload(payload, keep).await
+    }
+
+    async fn get_webhook_payload(&self, id: Uuid) -> Result<Option<WebhookPayload>> {
+        self.inner.get_webhook_payload(id).await
+    }
+
+    async fn list_webhook_payloads(
+        &self,
+        provider: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<WebhookPayload>> {
+        self.inner.list_webhook_payloads(provider, limit).await
+    }
+
+    async fn save_blob_record(&self, record: &BlobRecord) -> Result<()> {
+        self.inner.save_blob_record(record).await
+    }
+
+    async fn get_blob_record(&self, id: Uuid) -> Result<Option<BlobRecord>> {
+        self.inner.get_blob_record(id).await
+    }
+
+    async fn list_blob_records(&self) -> Result<Vec<BlobRecord>> {
+        self.inner.list_blob_records().await
+    }
+
+    async fn delete_blob_record(&self, id: Uuid) -> Result<()> {
+        self.inner.delete_blob_record(id).await
+    }
+
+    async fn save_run_checkpoint(&self, run_id: Uuid, data: serde_json::Value) -> Result<()> {
+        self.inner.save_run_checkpoint(run_id, data).await
+    }
+
+    async fn load_run_checkpoints(&self) -> Result<Vec<(Uuid, serde_json::Value)>> {
+        self.inner.load_run_checkpoints().await
+    }
+
+    async fn delete_run_checkpoint(&self, run_id: Uuid) -> Result<()> {
+        self.inner.delete_run_checkpoint(run_id).await
+    }
+
+    async fn save_delivery(&self, delivery: &OutboundDelivery) -> Result<()> {
+        self.inner.save_delivery(delivery).await
+    }
+
+    async fn list_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<OutboundDelivery>> {
+        self.inner.list_due_deliveries(now, limit).await
+    }
+
+    async fn claim_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<OutboundDelivery>> {
+        self.inner
+            .claim_due_deliveries(now, lease_until, limit)
+            .await
+    }
+
+    async fn delete_delivery(&self, id: Uuid) -> Result<()> {
+        self.inner.delete_delivery(id).await
+    }
+
+    async fn dead_letter_delivery(&self, delivery: &OutboundDelivery) -> Result<()> {
+        self.inner.dead_letter_delivery(delivery).await
+    }
+
+    async fn list_dead_letters(
+        &self,
+        limit: usize,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<OutboundDelivery>> {
+        self.inner.list_dead_letters(limit, tenant_id).await
+    }
+}
+
+#[async_trait]
+impl FlowStorage for CachedStorage {
+    async fn deploy_flow_version(
+        &self,
+        flow_name: &FlowName,
+        version: &str,
+        content: &str,
+    ) -> Result<()> {
+        let result = self
+            .inner
+            .deploy_flow_version(flow_name, version, content)
+            .await;
+        self.invalidate(flow_name);
+        result
+    }
+
+    async fn deploy_flow(&self, flow_name: &FlowName, content: &str) -> Result<String> {
+        let result = self.inner.deploy_flow(flow_name, content).await;
+        self.invalidate(flow_name);
+        result
+    }
+
+    async fn set_deployed_version(&self, flow_name: &FlowName, version: &str) -> Result<()> {
+        let result = self.inner.set_deployed_version(flow_name, version).await;
+        self.invalidate(flow_name);
+        result
+    }
+
+    async fn get_deployed_version(&self, flow_name: &FlowName) -> Result<Option<String>> {
+        self.read_text(
+            CacheKey::DeployedVersion(flow_name.to_string()),
+            self.inner.get_deployed_version(flow_name),
+        )
+        .await
+    }
+
+    async fn get_flow_version_content(
+        &self,
+        flow_name: &FlowName,
+        version: &str,
+    ) -> Result<Option<String>> {
+        self.read_text(
+            CacheKey::VersionContent(flow_name.to_string(), version.to_string()),
+            self.inner.get_flow_version_content(flow_name, version),
+        )
+        .await
+    }
+
+    async fn list_flow_versions(&self, flow_name: &FlowName) -> Result<Vec<FlowSnapshot>> {
+        self.inner.list_flow_versions(flow_name).await
+    }
+
+    async fn list_flow_versions_after(
+        &self,
+        flow_name: &FlowName,
+        limit: usize,
+        after: Option<&PageCursor<String>>,
+    ) -> Result<Vec<FlowSnapshot>> {
+        self.inner
+            .list_flow_versions_after(flow_name, limit, after)
+            .await
+    }
+
+    async fn get_latest_deployed_version_from_history(
+        &self,
+        flow_name: &FlowName,
+    ) -> Result<Option<String>> {
+        self.inner
+            .get_latest_deployed_version_from_history(flow_name)
+            .await
+    }
+
+    async fn unset_deployed_version(&self, flow_name: &FlowName) -> Result<()> {
+        let result = self.inner.unset_deployed_version(flow_name).await;
+        self.invalidate(flow_name);
+        result
+    }
+
+    async fn get_flow_tenant(&self, flow_name: &FlowName) -> Result<Option<String>> {
+        self.inner.get_flow_tenant(flow_name).await
+    }
+
+    async fn claim_flow_tenant(&self, flow_name: &FlowName, tenant_id: &str) -> Result<String> {
+        self.inner.claim_flow_tenant(flow_name, tenant_id).await
+    }
+
+    async fn get_flow_for_tenant(
+        &self,
+        flow_name: &FlowName,
+        tenant_id: &str,
+    ) -> Result<Option<String>> {
+        self.inner.get_flow_for_tenant(flow_name, tenant_id).await
+    }
+
+    async fn list_all_deployed_flows(&self) -> Result<Vec<(String, String)>> {
+        let generation = match self.lookup(&CacheKey::AllDeployed) {
+            (Some(CachedValue::Flows(flows)), _) => return Ok(flows.as_ref().clone()),
+            (_, generation) => generation,
+        };
+        let flows = self.inner.list_all_deployed_flows().await?;
+        self.store(
+            CacheKey::AllDeployed,
+            CachedValue::Flows(Arc::new(flows.clone())),
+            generation,
+        );
+        Ok(flows)
+    }
+
+    async fn find_flow_names_by_topic(&self, topic: &str) -> Result<Vec<FlowName>> {
+        self.inner.find_flow_names_by_topic(topic).await
+    }
+
+    async fn list_deployed_flow_summaries(&self, filter: &FlowFilter) -> Result<Vec<FlowSummary>> {
+        self.inner.list_deployed_flow_summaries(filter).await
+    }
+
+    async fn search_flows(&self, query: &str, limit: usize) -> Result<Vec<FlowSearchResult>> {
+        self.inner.search_flows(query, limit).await
+    }
+}
+
+#[async_trait]
+impl OAuthStorage for CachedStorage {
+    async fn save_oauth_credential(&self, credential: &OAuthCredential) -> Result<()> {
+        self.inner.save_oauth_credential(credential).await
+    }
+
+    async fn get_oauth_credential(
+        &self,
+        provider: &str,
+        integration: &str,
+    ) -> Result<Option<OAuthCredential>> {
+        self.inner.get_oauth_credential(provider, integration).await
+    }
+
+    async fn list_oauth_credentials(&self) -> Result<Vec<OAuthCredential>> {
+        self.inner.list_oauth_credentials().await
+    }
+
+    async fn list_oauth_credentials_for_tenant(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Vec<OAuthCredential>> {
+        self.inner
+            .list_oauth_credentials_for_tenant(tenant_id)
+            .await
+    }
+
+    async fn delete_oauth_credential(&self, id: &str) -> Result<()> {
+        self.inner.delete_oauth_credential(id).await
+    }
+
+    async fn delete_expired_oauth_credentials(&self, now: DateTime<Utc>) -> Result<u64> {
+        self.inner.delete_expired_oauth_credentials(now).await
+    }
+
+    async fn refresh_oauth_credential(
+        &self,
+        id: &str,
+        new_token: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        self.inner
+            .refresh_oauth_credential(id, new_token, expires_at)
+            .await
+    }
+
+    async fn save_oauth_provider(&self, provider: &OAuthProvider) -> Result<()> {
+        self.inner.save_oauth_provider(provider).await
+    }
+
+    async fn get_oauth_provider(&self, id: &str) -> Result<Option<OAuthProvider>> {
+        self.inner.get_oauth_provider(id).await
+    }
+
+    async fn list_oauth_providers(&self) -> Result<Vec<OAuthProvider>> {
+        self.inner.list_oauth_providers().await
+    }
+
+    async fn delete_oauth_provider(&self, id: &str) -> Result<()> {
+        self.inner.delete_oauth_provider(id).await
+    }
+
+    async fn save_oauth_client(&self, client: &OAuthClient) -> Result<()> {
+        self.inner.save_oauth_client(client).await
+    }
+
+    async fn get_oauth_client(&self, id: &str) -> Result<Option<OAuthClient>> {
+        self.inner.get_oauth_client(id).await
+    }
+
+    async fn list_oauth_clients(&self) -> Result<Vec<OAuthClient>> {
+        self.inner.list_oauth_clients().await
+    }
+
+    async fn delete_oauth_client(&self, id: &str) -> Result<()> {
+        self.inner.delete_oauth_client(id).await
+    }
+
+    async fn save_oauth_token(&self, token: &OAuthToken) -> Result<()> {
+        self.inner.save_oauth_token(token).await
+    }
+
+    async fn get_oauth_token_by_code(&self, code: &str) -> Result<Option<OAuthToken>> {
+        self.inner.get_oauth_token_by_code(code).await
+    }
+
+    async fn get_oauth_token_by_access(&self, access: &str) -> Result<Option<OAuthToken>> {
+        self.inner.get_oauth_token_by_access(access).await
+    }
+
+    async fn get_oauth_token_by_refresh(&self, refresh: &str) -> Result<Option<OAuthToken>> {
+        self.inner.get_oauth_token_by_refresh(refresh).await
+    }
+
+    async fn delete_oauth_token_by_code(&self, code: &str) -> Result<()> {
+        self.inner.delete_oauth_token_by_code(code).await
+    }
+
+    async fn delete_oauth_token_by_access(&self, access: &str) -> Result<()> {
+        self.inner.delete_oauth_token_by_access(access).await
+    }
+
+    async fn delete_oauth_token_by_refresh(&self, refresh: &str) -> Result<()> {
+        self.inner.delete_oauth_token_by_refresh(refresh).await
+    }
+}
+
+#[async_trait]
+impl OutboxStorage for CachedStorage {
+    async fn commit(&self, batch: &WriteBatch) -> Result<Vec<OutboxEvent>> {
+        self.inner.commit(batch).await
+    }
+
+    async fn list_unsent_events(&self, limit: usize) -> Result<Vec<OutboxEvent>> {
+        self.inner.list_unsent_events(limit).await
+    }
+
+    async fn mark_events_sent(&self, ids: &[Uuid]) -> Result<()> {
+        self.inner.mark_events_sent(ids).await
+    }
+
+    async fn prune_sent_events(&self, sent_before: DateTime<Utc>) -> Result<u64> {
+        self.inner.prune_sent_events(sent_before).await
+    }
+}
@@ -0,0 +1,129 @@
+use super::cached::{CacheStats, CachedStorage};
+use super::*;
+use crate::config::FlowCacheConfig;
+
+const FLOW_V1: &str = "name: hook\non: webhook.github\nsteps: []\nversion: 1.0.0\n";
+const FLOW_V2: &str = "name: hook\non: webhook.github\nsteps: []\nversion: 2.0.0\n";
+
+async fn cached(config: FlowCacheConfig) -> (Arc<dyn Storage>, CachedStorage) {
+    let inner: Arc<dyn Storage> = Arc::new(SqliteStorage::new(":memory:").await.unwrap());
+    let cache = CachedStorage::new(inner.clone(), &config);
+    (inner, cache)
+}
+
+#[tokio::test]
+async fn test_repeated_reads_hit_the_cache() {
+    let (inner, cache) = cached(FlowCacheConfig::default()).await;
+    let name = FlowName::new("hook").unwrap();
+    cache
+        .deploy_flow_version(&name, "1.0.0", FLOW_V1)
+        .await
+        .unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(
+            cache.get_deployed_version(&name).await.unwrap().as_deref(),
+            Some("1.0.0")
+        );
+        assert_eq!(
+            cache
+                .get_flow_version_content(&name, "1.0.0")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(FLOW_V1)
+        );
+        assert_eq!(cache.list_all_deployed_flows().await.unwrap().len(), 1);
+    }
+    assert_eq!(cache.stats(), CacheStats { hits: 6, misses: 3 });
+
+    // Served from the cache: a change made behind its back isn't seen
+    inner.unset_deployed_version(&name).await.unwrap();
+    assert_eq!(
+        cache.get_deployed_version(&name).await.unwrap().as_deref(),
+        Some("1.0.0")
+    );
+    assert_eq!(cache.list_all_deployed_flows().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_deploys_invalidate_the_flow() {
+    let (_inner, cache) = cached(FlowCacheConfig::default()).await;
+    let name = FlowName::new("hook").unwrap();
+    let other = FlowName::new("other").unwrap();
+    cache
+        .deploy_flow_version(&name, "1.0.0", FLOW_V1)
+        .await
+        .unwrap();
+    cache
+        .deploy_flow_version(&other, "1.0.0", "name: other\non: cli.manual\nsteps: []\n")
+        .await
+        .unwrap();
+    cache.get_deployed_version(&name).await.unwrap();
+    cache.get_deployed_version(&other).await.unwrap();
+    cache.list_all_deployed_flows().await.unwrap();
+
+    // A new version is read fresh, everywhere it appears
+    cache
+        .deploy_flow_version(&name, "2.0.0", FLOW_V2)
+        .await
+        .unwrap();
+    assert_eq!(
+        cache.get_deployed_version(&name).await.unwrap().as_deref(),
+        Some("2.0.0")
+    );
+    let flows = cache.list_all_deployed_flows().await.unwrap();
+    assert!(flows.contains(&("hook".to_string(), FLOW_V2.to_string())));
+    // Other flows' entries are kept
+    let before = cache.stats();
+    cache.get_deployed_version(&other).await.unwrap();
+    assert_eq!(cache.stats().hits, before.hits + 1);
+
+    // Rollbacks and disables invalidate too
+    cache.set_deployed_version(&name, "1.0.0").await.unwrap();
+    assert_eq!(
+        cache.get_deployed_version(&name).await.unwrap().as_deref(),
+        Some("1.0.0")
+    );
+    cache.unset_deployed_version(&name).await.unwrap();
+    assert_eq!(cache.get_deployed_version(&name).await.unwrap(), None);
+    assert_eq!(cache.list_all_deployed_flows().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_entries_expire_and_capacity_is_bounded() {
+    let (inner, cache) = cached(FlowCacheConfig {
+        ttl_secs: 1,
+        capacity: 2,
+    })
+    .await;
+    let names: Vec<FlowName> = ["a", "b", "c"]
+        .iter()
+        .map(|n| FlowName::new(*n).unwrap())
+        .collect();
+    for name in &names {
+        cache.get_deployed_version(name).await.unwrap();
+    }
+    // "a" was evicted to make room for "c"
+    let before = cache.stats();
+    cache.get_deployed_version(&names[2]).await.unwrap();
+    cache.get_deployed_version(&names[0]).await.unwrap();
+    assert_eq!(cache.stats().hits, before.hits + 1);
+    assert_eq!(cache.stats().misses, before.misses + 1);
+
+    // Changes made elsewhere are seen once the entry expires
+    inner
+        .deploy_flow_version(&names[2], "1.0.0", "name: c\non: cli.manual\nsteps: []\n")
+        .await
+        .unwrap();
+    assert_eq!(cache.get_deployed_version(&names[2]).await.unwrap(), None);
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    assert_eq!(
+        cache
+            .get_deployed_version(&names[2])
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("1.0.0")
+    );
+}
@@ -2,35 +2,43 @@
 //!
 //! Pure functions for working with .flow.yaml files on disk.
 //! These handle the "working copy" of flows before deployment.
+//!
+//! Names are [`FlowName`]s, so they cannot contain path separators or `..`. Every
+//! path is additionally checked to resolve inside `flows_dir`, which catches
+//! symlinks planted in the flows directory.
 
+use crate::model::FlowName;
 use crate::{BeemFlowError, Result};
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
-const FLOW_EXTENSION: &str = ".flow.yaml";
+/// Suffix of flow files in the flows directory
+pub(crate) const FLOW_EXTENSION: &str = ".flow.yaml";
 
 /// Save a flow to the filesystem (atomic write)
 ///
 /// # Arguments
 /// * `flows_dir` - Base directory for flows (e.g., ~/.beemflow/flows)
-/// * `name` - Flow name (alphanumeric, hyphens, underscores only)
+/// * `name` - Flow name
 /// * `content` - YAML content (validated before writing)
 ///
 /// # Returns
 /// `Ok(true)` if file was updated, `Ok(false)` if created new
-pub async fn save_flow(flows_dir: impl AsRef<Path>, name: &str, content: &str) -> Result<bool> {
-    validate_flow_name(name)?;
-
-    let path = build_flow_path(flows_dir, name);
-    let existed = path.exists();
+pub async fn save_flow(
+    flows_dir: impl AsRef<Path>,
+    name: &FlowName,
+    content: &str,
+) -> Result<bool> {
+    let flows_dir = flows_dir.as_ref();
 
     // Validate YAML before writing (fail fast)
     crate::dsl::parse_string(content, None)?;
 
-    // Create parent directory if needed
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).await?;
-    }
+    // Create flows directory if needed
+    fs::create_dir_all(flows_dir).await?;
+
+    let path = checked_flow_path(flows_dir, name).await?;
+    let existed = path.exists();
 
     // Atomic write: temp file + rename
     let temp_path = path.with_extension("tmp");
@@ -44,10 +52,8 @@ pub async fn save_flow(flows_dir: impl AsRef<Path>, name: &str, content: &str) -
 ///
 /// # Returns
 /// `Ok(Some(content))` if found, `Ok(None)` if not found
-pub async fn get_flow(flows_dir: impl AsRef<Path>, name: &str) -> Result<Option<String>> {
-    validate_flow_name(name)?;
-
-    let path = build_flow_path(&flows_dir, name);
+pub async fn get_flow(flows_dir: impl AsRef<Path>, name: &FlowName) -> Result<Option<String>> {
+    let path = checked_flow_path(flows_dir.as_ref(), name).await?;
 
     match fs::read_to_string(&path).await {
         Ok(content) => Ok(Some(content)),
@@ -58,9 +64,11 @@ pub async fn get_flow(flows_dir: impl AsRef<Path>, name: &str) -> Result<Option<
 
 /// List all flows in the filesystem
 ///
+/// Files whose names are not valid flow names are skipped.
+///
 /// # Returns
 /// Sorted list of flow names (without .flow.yaml extension)
-pub async fn list_flows(flows_dir: impl AsRef<Path>) -> Result<Vec<String>> {
+pub async fn list_flows(flows_dir: impl AsRef<Path>) -> Result<Vec<FlowName>> {
     let flows_dir = flows_dir.as_ref();
 
     // Return empty list if directory doesn't exist yet
@@ -76,25 +84,25 @@ pub async fn list_flows(flows_dir: impl AsRef<Path>) -> Result<Vec<String>> {
 
         // Check if it matches *.flow.yaml
         if let Some(file_name) = path.file_name().and_then(|s| s.to_str())
-            && file_name.ends_with(FLOW_EXTENSION)
+            && let Some(name) = file_name.strip_suffix(FLOW_EXTENSION)
         {
-            let name = file_name.trim_end_matches(FLOW_EXTENSION);
-            flows.push(name.to_string());
+            match FlowName::new(name) {
+                Ok(name) => flows.push(name),
+                Err(_) => tracing::debug!("Skipping flow file with invalid name: {}", file_name),
+            }
         }
     }
 
-    flows.sort();
+    flows.sort_by(|a, b| a.as_str().cmp(b.as_str()));
     Ok(flows)
 }
 
 /// Delete a flow from the filesystem
-pub async fn delete_flow(flows_dir: impl AsRef<Path>, name: &str) -> Result<()> {
-    validate_flow_name(name)?;
-
-    let path = build_flow_path(&flows_dir, name);
+pub async fn delete_flow(flows_dir: impl AsRef<Path>, name: &FlowName) -> Result<()> {
+    let path = checked_flow_path(flows_dir.as_ref(), name).await?;
 
     if !path.exists() {
-        return Err(BeemFlowError::not_found("Flow", name));
+        return Err(BeemFlowError::not_found("Flow", name.as_str()));
     }
 
     fs::remove_file(&path).await?;
@@ -102,43 +110,46 @@ pub async fn delete_flow(flows_dir: impl AsRef<Path>, name: &str) -> Result<()>
 }
 
 /// Check if a flow exists on the filesystem
-pub async fn flow_exists(flows_dir: impl AsRef<Path>, name: &str) -> Result<bool> {
-    validate_flow_name(name)?;
-    let path = build_flow_path(&flows_dir, name);
+pub async fn flow_exists(flows_dir: impl AsRef<Path>, name: &FlowName) -> Result<bool> {
+    let path = checked_flow_path(flows_dir.as_ref(), name).await?;
     Ok(path.exists())
 }
 
 // Private helpers
 
-fn validate_flow_name(name: &str) -> Result<()> {
-    // Prevent path traversal and invalid characters
-    if name.is_empty() {
-        return Err(BeemFlowError::validation("Flow name cannot be empty"));
-    }
+fn build_flow_path(flows_dir: &Path, name: &FlowName) -> PathBuf {
+    flows_dir.join(format!("{}{}", name, FLOW_EXTENSION))
+}
 
-    if name.contains("..") || name.contains('/') || name.contains('\\') {
-        return Err(BeemFlowError::validation(
-            "Invalid flow name: path separators and '..' not allowed",
-        ));
-    }
+/// Build a flow's path and verify it resolves inside `flows_dir`
+///
+/// An existing file (or symlink) is canonicalized and must stay under the
+/// canonical flows directory; dangling symlinks are rejected.
+async fn checked_flow_path(flows_dir: &Path, name: &FlowName) -> Result<PathBuf> {
+    let path = build_flow_path(flows_dir, name);
 
-    // Only allow alphanumeric, hyphens, and underscores
-    if !name
-        .chars()
-        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
-    {
-        return Err(BeemFlowError::validation(
-            "Flow name must contain only alphanumeric characters, hyphens, and underscores",
-        ));
+    let root = match fs::canonicalize(flows_dir).await {
+        Ok(root) => root,
+        // Nothing can exist under a directory that doesn't exist yet
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(path),
+        Err(e) => return Err(e.into()),
+    };
+
+    let resolved = match fs::symlink_metadata(&path).await {
+        Ok(_) => fs::canonicalize(&path).await.ok(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            path.file_name().map(|file_name| root.join(file_name))
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    match resolved {
+        Some(resolved) if resolved.starts_with(&root) => Ok(path),
+        _ => Err(BeemFlowError::validation(format!(
+            "Flow '{}' resolves outside the flows directory",
+            name
+        ))),
     }
-
-    Ok(())
-}
-
-fn build_flow_path(flows_dir: impl AsRef<Path>, name: &str) -> PathBuf {
-    flows_dir
-        .as_ref()
-        .join(format!("{}{}", name, FLOW_EXTENSION))
 }
 
 #[cfg(test)]
@@ -146,21 +157,29 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    fn name(s: &str) -> FlowName {
+        FlowName::new(s).unwrap()
+    }
+
     #[tokio::test]
     async fn test_save_and_get_flow() {
         let temp = TempDir::new().unwrap();
         let content = "name: test\nsteps: []";
 
         // Save new flow
-        let created = save_flow(temp.path(), "test_flow", content).await.unwrap();
+        let created = save_flow(temp.path(), &name("test_flow"), content)
+            .await
+            .unwrap();
         assert!(!created); // First time = created
 
         // Get flow back
-        let retrieved = get_flow(temp.path(), "test_flow").await.unwrap();
+        let retrieved = get_flow(temp.path(), &name("test_flow")).await.unwrap();
         assert_eq!(retrieved, Some(content.to_string()));
 
         // Update existing flow
-        let updated = save_flow(temp.path(), "test_flow", content).await.unwrap();
+        let updated = save_flow(temp.path(), &name("test_flow"), content)
+            .await
+            .unwrap();
         assert!(updated); // Second time = updated
     }
 
@@ -170,49 +189,83 @@ mod tests {
 
         // Empty directory
         let flows = list_flows(temp.path()).await.unwrap();
-        assert_eq!(flows, Vec::<String>::new());
+        assert_eq!(flows, Vec::<FlowName>::new());
 
         // Add flows
-        save_flow(temp.path(), "flow1", "name: flow1\nsteps: []")
+        save_flow(temp.path(), &name("flow1"), "name: flow1\nsteps: []")
             .await
             .unwrap();
-        save_flow(temp.path(), "flow2", "name: flow2\nsteps: []")
+        save_flow(temp.path(), &name("flow2"), "name: flow2\nsteps: []")
             .await
             .unwrap();
 
+        // Files that aren't valid flow names are skipped
+        std::fs::write(temp.path().join("bad name.flow.yaml"), "name: x").unwrap();
+
         let flows = list_flows(temp.path()).await.unwrap();
-        assert_eq!(flows, vec!["flow1", "flow2"]);
+        assert_eq!(flows, vec![name("flow1"), name("flow2")]);
     }
 
     #[tokio::test]
     async fn test_delete_flow() {
         let temp = TempDir::new().unwrap();
-        save_flow(temp.path(), "test", "name: test\nsteps: []")
+        save_flow(temp.path(), &name("test"), "name: test\nsteps: []")
             .await
             .unwrap();
 
-        delete_flow(temp.path(), "test").await.unwrap();
+        delete_flow(temp.path(), &name("test")).await.unwrap();
 
-        let exists = flow_exists(temp.path(), "test").await.unwrap();
+        let exists = flow_exists(temp.path(), &name("test")).await.unwrap();
         assert!(!exists);
     }
 
     #[tokio::test]
     async fn test_path_traversal_prevention() {
-        let temp = TempDir::new().unwrap();
-
-        let result = save_flow(temp.path(), "../evil", "name: evil").await;
-        assert!(result.is_err());
+        // Traversal names cannot be constructed in the first place
+        assert!(FlowName::new("../evil").is_err());
+        assert!(FlowName::new("foo/../bar").is_err());
+        assert!(FlowName::new("../../etc/cron.d/evil").is_err());
+    }
 
-        let result = save_flow(temp.path(), "foo/../bar", "name: bar").await;
-        assert!(result.is_err());
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_escape_rejected() {
+        let temp = TempDir::new().unwrap();
+        let flows_dir = temp.path().join("flows");
+        std::fs::create_dir(&flows_dir).unwrap();
+        let outside = temp.path().join("secret.txt");
+        std::fs::write(&outside, "name: secret\nsteps: []").unwrap();
+
+        // Symlink inside flows_dir pointing outside it
+        std::os::unix::fs::symlink(&outside, flows_dir.join("evil.flow.yaml")).unwrap();
+        assert!(get_flow(&flows_dir, &name("evil")).await.is_err());
+        assert!(
+            save_flow(&flows_dir, &name("evil"), "name: evil\nsteps: []")
+                .await
+                .is_err()
+        );
+        assert!(delete_flow(&flows_dir, &name("evil")).await.is_err());
+        assert!(outside.exists());
+
+        // Dangling symlinks would create files outside flows_dir on write
+        std::os::unix::fs::symlink(
+            temp.path().join("missing.txt"),
+            flows_dir.join("dangling.flow.yaml"),
+        )
+        .unwrap();
+        assert!(
+            save_flow(&flows_dir, &name("dangling"), "name: dangling\nsteps: []")
+                .await
+                .is_err()
+        );
+        assert!(!temp.path().join("missing.txt").exists());
     }
 
     #[tokio::test]
     async fn test_invalid_yaml_rejected() {
         let temp = TempDir::new().unwrap();
 
-        let result = save_flow(temp.path(), "bad", "invalid: [yaml").await;
+        let result = save_flow(temp.path(), &name("bad"), "invalid: [yaml").await;
         assert!(result.is_err());
     }
 
@@ -220,7 +273,7 @@ mod tests {
     async fn test_get_nonexistent_flow() {
         let temp = TempDir::new().unwrap();
 
-        let result = get_flow(temp.path(), "nonexistent").await.unwrap();
+        let result = get_flow(temp.path(), &name("nonexistent")).await.unwrap();
         assert_eq!(result, None);
     }
 
@@ -228,7 +281,7 @@ mod tests {
     async fn test_delete_nonexistent_flow() {
         let temp = TempDir::new().unwrap();
 
-        let result = delete_flow(temp.path(), "nonexistent").await;
+        let result = delete_flow(temp.path(), &name("nonexistent")).await;
         assert!(result.is_err());
     }
 
@@ -236,32 +289,18 @@ mod tests {
     async fn test_invalid_flow_names() {
         let temp = TempDir::new().unwrap();
 
-        // Empty name
-        let result = save_flow(temp.path(), "", "name: test").await;
-        assert!(result.is_err());
-
-        // Special characters
-        let result = save_flow(temp.path(), "foo/bar", "name: test").await;
-        assert!(result.is_err());
-
-        let result = save_flow(temp.path(), "foo\\bar", "name: test").await;
-        assert!(result.is_err());
+        // Empty name and special characters are rejected by FlowName
+        assert!(FlowName::new("").is_err());
+        assert!(FlowName::new("foo/bar").is_err());
+        assert!(FlowName::new("foo\\bar").is_err());
 
         // Valid names
-        assert!(
-            save_flow(temp.path(), "valid-name", "name: test\nsteps: []")
-                .await
-                .is_ok()
-        );
-        assert!(
-            save_flow(temp.path(), "valid_name", "name: test\nsteps: []")
-                .await
-                .is_ok()
-        );
-        assert!(
-            save_flow(temp.path(), "validName123", "name: test\nsteps: []")
-                .await
-                .is_ok()
-        );
+        for valid in ["valid-name", "valid_name", "validName123"] {
+            assert!(
+                save_flow(temp.path(), &name(valid), "name: test\nsteps: []")
+                    .await
+                    .is_ok()
+            );
+        }
     }
 }
@@ -0,0 +1,309 @@
+//! Tamper-evident run history (`storage.integrityChain`)
+//!
+//! With the chain enabled, every run and step write also appends an entry to the
+//! append-only `run_history` table. An entry holds the record's canonical JSON and
+//! a SHA-256 hash over that content and the hash of the previous entry of the
+//! same flow, so editing, reordering or dropping an entry breaks the link to every
+//! entry after it. Status transitions append new entries instead of rewriting old
+//! ones; the latest hash of each record is also kept in its row's `record_hash`.
+//!
+//! [`RunStorage::verify_integrity`](super::RunStorage::verify_integrity) walks the
+//! chains, then checks the live run and step rows still match their latest entry.
+
+use super::RunStorage;
+use super::sql_common::{run_status_to_str, step_status_to_str};
+use crate::model::{Run, StepRun};
+use crate::{BeemFlowError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use uuid::Uuid;
+
+/// `prev_hash` of the first entry of a flow's chain
+pub const GENESIS_HASH: &str = "";
+
+/// Kind of record a history entry snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    Run,
+    Step,
+}
+
+impl RecordKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecordKind::Run => "run",
+            RecordKind::Step => "step",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "run" => Ok(RecordKind::Run),
+            "step" => Ok(RecordKind::Step),
+            other => Err(BeemFlowError::storage(format!(
+                "Unknown run history record kind: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// An entry of a flow's history chain, as stored
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    /// Position in the flow's chain, from 1
+    pub sequence: i64,
+    pub kind: RecordKind,
+    pub record_id: Uuid,
+    /// Canonical JSON of the record
+    pub content: String,
+    pub prev_hash: String,
+    pub record_hash: String,
+}
+
+/// Outcome of verifying the history chains
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Flows whose chain was walked
+    pub flows_checked: usize,
+    /// History entries checked
+    pub entries_checked: usize,
+    /// First place the history stops matching, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub divergence: Option<IntegrityDivergence>,
+}
+
+impl IntegrityReport {
+    /// Whether every checked chain and record is intact
+    pub fn is_intact(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+/// Where and why a chain stopped matching
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityDivergence {
+    pub flow_name: String,
+    /// Sequence number of the offending (or missing) history entry
+    pub sequence: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_kind: Option<RecordKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_id: Option<Uuid>,
+    pub reason: String,
+}
+
+/// Serialize JSON with object keys sorted at every level and no whitespace
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// Canonical content of a run, as chained
+///
+/// Times are whole seconds, the precision every backend stores, so a record
+/// read back hashes the same as when it was written.
+pub fn run_content(run: &Run) -> Result<String> {
+    Ok(canonical_json(&serde_json::json!({
+        "id": run.id,
+        "flow_name": run.flow_name,
+        "event": run.event,
+        "vars": run.vars,
+        "status": run_status_to_str(run.status),
+        "started_at": run.started_at.timestamp(),
+        "ended_at": run.ended_at.map(|at| at.timestamp()),
+        "environment": run.environment,
+        "correlation_id": run.correlation_id,
+        "labels": run.labels,
+        "usage": serde_json::to_value(&run.usage)?,
+    })))
+}
+
+/// Canonical content of a step, as chained
+pub fn step_content(step: &StepRun) -> Result<String> {
+    let progress = step.progress.as_ref().map(|progress| {
+        serde_json::json!({
+            "percent": progress.percent,
+            "message": progress.message,
+            "updated_at": progress.updated_at.timestamp(),
+        })
+    });
+    let mut content = serde_json::json!({
+        "id": step.id,
+        "run_id": step.run_id,
+        "step_name": step.step_name,
+        "status": step_status_to_str(step.status),
+        "started_at": step.started_at.timestamp(),
+        "ended_at": step.ended_at.map(|at| at.timestamp()),
+        "inputs": step.inputs.clone().unwrap_or_default(),
+        "outputs": step.outputs.clone().unwrap_or_default(),
+        "error": step.error,
+        "progress": progress,
+    });
+    // Only when set, so entries written before steps had a kind still verify
+    if let Some(kind) = step.error_kind {
+        content["error_kind"] = serde_json::json!(kind.as_str());
+    }
+    Ok(canonical_json(&content))
+}
+
+/// Hash of an entry: SHA-256 over the previous entry's hash, the kind and the content
+pub fn chain_hash(prev_hash: &str, kind: RecordKind, content: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(kind.as_str().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(content.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Head of a flow's chain, updated with every entry appended
+#[derive(Debug, Clone)]
+pub struct ChainHead {
+    pub flow_name: String,
+    pub last_sequence: i64,
+    pub last_hash: String,
+}
+
+/// Check one flow's chain, in sequence order, and the live records it covers
+///
+/// Adds the entries checked to `report` and records the first divergence.
+/// Records deleted since (e.g. by retention) are not a divergence; their
+/// entries stay in the chain.
+pub async fn verify_chain<S: RunStorage + ?Sized>(
+    storage: &S,
+    head: &ChainHead,
+    entries: &[HistoryEntry],
+    report: &mut IntegrityReport,
+) -> Result<()> {
+    report.flows_checked += 1;
+    let diverged = |sequence: i64, entry: Option<&HistoryEntry>, reason: &str| {
+        Some(IntegrityDivergence {
+            flow_name: head.flow_name.clone(),
+            sequence,
+            record_kind: entry.map(|entry| entry.kind),
+            record_id: entry.map(|entry| entry.record_id),
+            reason: reason.to_string(),
+        })
+    };
+
+    let mut prev_hash = GENESIS_HASH;
+    let mut latest = HashMap::new();
+    for (expected, entry) in (1..).zip(entries) {
+        report.entries_checked += 1;
+        if entry.sequence != expected {
+            report.divergence = diverged(expected, None, "entry is missing from the chain");
+            return Ok(());
+        }
+        if entry.prev_hash != prev_hash {
+            report.divergence = diverged(
+                entry.sequence,
+                Some(entry),
+                "entry does not link to the previous entry of the flow",
+            );
+            return Ok(());
+        }
+        if chain_hash(&entry.prev_hash, entry.kind, &entry.content) != entry.record_hash {
+            report.divergence = diverged(
+                entry.sequence,
+                Some(entry),
+                "entry hash does not match its content",
+            );
+            return Ok(());
+        }
+        prev_hash = &entry.record_hash;
+        latest.insert((entry.kind, entry.record_id), entry);
+    }
+    if head.last_sequence != entries.len() as i64 || head.last_hash != prev_hash {
+        report.divergence = diverged(
+            entries.len() as i64 + 1,
+            None,
+            "chain ends before its head; trailing entries were removed",
+        );
+        return Ok(());
+    }
+
+    // Live rows must still hold the content of their latest entry
+    let mut latest: Vec<_> = latest.into_values().collect();
+    latest.sort_by_key(|entry| entry.sequence);
+    let mut steps_by_run: HashMap<Uuid, Vec<StepRun>> = HashMap::new();
+    for entry in latest {
+        let live = match entry.kind {
+            RecordKind::Run => storage
+                .get_run(entry.record_id)
+                .await?
+                .map(|run| run_content(&run))
+                .transpose()?,
+            RecordKind::Step => {
+                let content: Value = serde_json::from_str(&entry.content)?;
+                let run_id = content
+                    .get("run_id")
+                    .and_then(Value::as_str)
+                    .and_then(|id| Uuid::parse_str(id).ok());
+                match run_id {
+                    Some(run_id) => {
+                        let steps = match steps_by_run.entry(run_id) {
+                            Entry::Occupied(steps) => steps.into_mut(),
+                            Entry::Vacant(slot) => slot.insert(storage.get_steps(run_id).await?),
+                        };
+                        steps
+                            .iter()
+                            .find(|step| step.id == entry.record_id)
+                            .map(step_content)
+                            .transpose()?
+                    }
+                    None => None,
+                }
+            }
+        };
+        if let Some(live) = live
+            && live != entry.content
+        {
+            report.divergence = diverged(
+                entry.sequence,
+                Some(entry),
+                "record differs from its latest history entry",
+            );
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
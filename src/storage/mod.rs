@@ -6,15 +6,23 @@
 //! - `RunStorage`: Run and step execution tracking
 //! - `FlowStorage`: Flow definition management and versioning
 //! - `OAuthStorage`: OAuth credentials, providers, clients, and tokens
-//! - `StateStorage`: Paused runs and wait tokens for durable execution
+//! - `StateStorage`: Paused runs, wait tokens and webhook payload history
+//! - `OutboxStorage`: Transactional run/step writes and the event outbox
 //! - `Storage`: Composition trait implementing all of the above
+//!
+//! The `remote` driver forwards every call to an external storage service; see [`remote`].
+//! Any backend can be wrapped in a read-through cache of deployed flows; see [`cached`].
 
+pub mod cached;
 pub mod flows; // Pure functions for filesystem flow operations
+pub mod integrity;
 pub mod postgres;
+pub mod remote;
+pub mod search;
 pub mod sql_common;
 pub mod sqlite;
 
-use crate::{Result, model::*};
+use crate::{BeemFlowError, Result, model::*};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
@@ -40,6 +48,28 @@ pub trait RunStorage: Send + Sync {
     /// Returns runs ordered by started_at DESC
     async fn list_runs(&self, limit: usize, offset: usize) -> Result<Vec<Run>>;
 
+    /// List the runs of one tenant with pagination
+    ///
+    /// Same ordering and cap as `list_runs`; runs without a tenant are left out.
+    async fn list_runs_for_tenant(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Run>>;
+
+    /// List runs matching `filter` after a keyset position
+    ///
+    /// Returns up to `limit` runs (capped at 10,000) ordered by
+    /// `(started_at, id)` DESC, starting after `after`. Unlike offsets, a
+    /// position stays valid while new runs are inserted.
+    async fn list_runs_after(
+        &self,
+        filter: &RunFilter,
+        limit: usize,
+        after: Option<&PageCursor<Uuid>>,
+    ) -> Result<Vec<Run>>;
+
     /// List runs filtered by flow name and status, ordered by most recent first
     /// This is optimized for finding previous successful runs without loading all data
     async fn list_runs_by_flow_and_status(
@@ -57,12 +87,32 @@ pub trait RunStorage: Send + Sync {
     /// Returns true if inserted, false if run already exists (based on ID)
     async fn try_insert_run(&self, run: &Run) -> Result<bool>;
 
+    /// Aggregate run statistics per flow for runs started at or after `since`
+    ///
+    /// Counts and duration percentiles (nearest-rank over finished runs) are computed
+    /// in the database. Rows are ordered by failure count, most failures first.
+    /// With `tenant_id`, only that tenant's runs are counted.
+    async fn run_stats(
+        &self,
+        since: DateTime<Utc>,
+        flow_name: Option<&str>,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<FlowRunStats>>;
+
     // Step methods
     /// Save a step execution
     async fn save_step(&self, step: &StepRun) -> Result<()>;
 
     /// Get steps for a run
     async fn get_steps(&self, run_id: Uuid) -> Result<Vec<StepRun>>;
+
+    // Integrity chain
+    /// Walk the run history chains (`storage.integrityChain`) and report the first divergence
+    ///
+    /// Checks every flow's chain, or only `flow_name`'s, then checks the live run
+    /// and step rows against their latest entries. See [`integrity`].
+    async fn verify_integrity(&self, flow_name: Option<&str>)
+    -> Result<integrity::IntegrityReport>;
 }
 
 /// State storage for durable execution (paused runs, wait tokens)
@@ -100,6 +150,91 @@ pub trait StateStorage: Send + Sync {
     /// Atomically fetch and delete a paused run
     /// Returns None if not found, preventing double-resume
     async fn fetch_and_delete_paused_run(&self, token: &str) -> Result<Option<serde_json::Value>>;
+
+    // Webhook payload history
+    /// Save a received webhook payload, keeping only the newest `keep` per provider
+    async fn save_webhook_payload(&self, payload: &WebhookPayload, keep: usize) -> Result<()>;
+
+    /// Get a stored webhook payload by ID
+    async fn get_webhook_payload(&self, id: Uuid) -> Result<Option<WebhookPayload>>;
+
+    /// List stored webhook payloads, newest first, optionally for one provider
+    async fn list_webhook_payloads(
+        &self,
+        provider: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<WebhookPayload>>;
+
+    // Blob locations
+    /// Record where a blob is stored
+    async fn save_blob_record(&self, record: &BlobRecord) -> Result<()>;
+
+    /// Get a blob's location by ID
+    async fn get_blob_record(&self, id: Uuid) -> Result<Option<BlobRecord>>;
+
+    /// List all blob records, oldest first
+    async fn list_blob_records(&self) -> Result<Vec<BlobRecord>>;
+
+    /// Delete a blob record (the object itself is left to the blob store)
+    async fn delete_blob_record(&self, id: Uuid) -> Result<()>;
+
+    // Run checkpoints
+    /// Save the checkpoint of an in-progress run, replacing its previous one
+    async fn save_run_checkpoint(&self, run_id: Uuid, data: serde_json::Value) -> Result<()>;
+
+    /// Load the checkpoints of all runs that have one
+    async fn load_run_checkpoints(&self) -> Result<Vec<(Uuid, serde_json::Value)>>;
+
+    /// Delete a run's checkpoint
+    async fn delete_run_checkpoint(&self, run_id: Uuid) -> Result<()>;
+
+    // Outbound deliveries
+    /// Queue a delivery for retry, replacing its previous state
+    async fn save_delivery(&self, delivery: &OutboundDelivery) -> Result<()>;
+
+    /// Queued deliveries due at `now`, longest overdue first
+    async fn list_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<OutboundDelivery>>;
+
+    /// Claim up to `limit` deliveries due at `now`, longest overdue first
+    ///
+    /// Claimed deliveries are atomically pushed back to `lease_until`, so other
+    /// workers sharing the storage skip them until then. A worker that crashes
+    /// mid-attempt thus leaves its deliveries to be retried once the lease ends.
+    async fn claim_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<OutboundDelivery>>;
+
+    /// Remove a delivery from the retry queue
+    async fn delete_delivery(&self, id: Uuid) -> Result<()>;
+
+    /// Move a delivery from the retry queue to the dead-letter queue, atomically
+    async fn dead_letter_delivery(&self, delivery: &OutboundDelivery) -> Result<()>;
+
+    /// Dead-lettered deliveries, newest first
+    ///
+    /// With `tenant_id`, only deliveries made by that tenant's runs.
+    async fn list_dead_letters(
+        &self,
+        limit: usize,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<OutboundDelivery>>;
+}
+
+/// Compute the content-addressed version used by `FlowStorage::deploy_flow`
+///
+/// First 12 hex characters of the SHA-256 digest of the flow content.
+pub fn content_version(content: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let digest = Sha256::digest(content.as_bytes());
+    hex::encode(digest)[..12].to_string()
 }
 
 /// Flow versioning and deployment storage (database-backed)
@@ -111,35 +246,98 @@ pub trait FlowStorage: Send + Sync {
     /// Deploy a flow version (creates immutable snapshot)
     async fn deploy_flow_version(
         &self,
-        flow_name: &str,
+        flow_name: &FlowName,
         version: &str,
         content: &str,
     ) -> Result<()>;
 
+    /// Deploy flow content under a version derived from its content hash
+    ///
+    /// Idempotent: if a snapshot with identical content already exists, no new
+    /// snapshot is created and that version is marked deployed instead.
+    /// Returns the deployed version. Use `deploy_flow_version` for manual versioning.
+    async fn deploy_flow(&self, flow_name: &FlowName, content: &str) -> Result<String> {
+        let version = content_version(content);
+
+        match self.get_flow_version_content(flow_name, &version).await? {
+            Some(existing) if existing == content => {
+                self.set_deployed_version(flow_name, &version).await?;
+            }
+            Some(_) => {
+                return Err(BeemFlowError::validation(format!(
+                    "Version '{}' of flow '{}' already exists with different content",
+                    version, flow_name
+                )));
+            }
+            None => {
+                self.deploy_flow_version(flow_name, &version, content)
+                    .await?;
+            }
+        }
+
+        Ok(version)
+    }
+
     /// Set which version is currently deployed for a flow
-    async fn set_deployed_version(&self, flow_name: &str, version: &str) -> Result<()>;
+    async fn set_deployed_version(&self, flow_name: &FlowName, version: &str) -> Result<()>;
 
     /// Get the currently deployed version for a flow
-    async fn get_deployed_version(&self, flow_name: &str) -> Result<Option<String>>;
+    async fn get_deployed_version(&self, flow_name: &FlowName) -> Result<Option<String>>;
 
     /// Get the content of a specific deployed version
     async fn get_flow_version_content(
         &self,
-        flow_name: &str,
+        flow_name: &FlowName,
         version: &str,
     ) -> Result<Option<String>>;
 
     /// List all deployed versions for a flow
-    async fn list_flow_versions(&self, flow_name: &str) -> Result<Vec<FlowSnapshot>>;
+    async fn list_flow_versions(&self, flow_name: &FlowName) -> Result<Vec<FlowSnapshot>>;
+
+    /// List deployed versions for a flow after a keyset position
+    ///
+    /// Versions are ordered by `(deployed_at, version)` DESC.
+    async fn list_flow_versions_after(
+        &self,
+        flow_name: &FlowName,
+        limit: usize,
+        after: Option<&PageCursor<String>>,
+    ) -> Result<Vec<FlowSnapshot>>;
 
     /// Get the most recently deployed version from history (for enable)
     async fn get_latest_deployed_version_from_history(
         &self,
-        flow_name: &str,
+        flow_name: &FlowName,
     ) -> Result<Option<String>>;
 
     /// Remove deployed version pointer (for disable)
-    async fn unset_deployed_version(&self, flow_name: &str) -> Result<()>;
+    async fn unset_deployed_version(&self, flow_name: &FlowName) -> Result<()>;
+
+    /// Get the tenant owning a flow name, if one has claimed it
+    async fn get_flow_tenant(&self, flow_name: &FlowName) -> Result<Option<String>>;
+
+    /// Claim a flow name for `tenant_id` unless another tenant already owns it
+    ///
+    /// Returns the owning tenant after the claim; a caller whose tenant isn't
+    /// returned must not touch the flow.
+    async fn claim_flow_tenant(&self, flow_name: &FlowName, tenant_id: &str) -> Result<String>;
+
+    /// Get the deployed content of a flow owned by `tenant_id`
+    ///
+    /// Flows another tenant owns, or no tenant, read as not deployed.
+    async fn get_flow_for_tenant(
+        &self,
+        flow_name: &FlowName,
+        tenant_id: &str,
+    ) -> Result<Option<String>> {
+        if self.get_flow_tenant(flow_name).await?.as_deref() != Some(tenant_id) {
+            return Ok(None);
+        }
+        let Some(version) = self.get_deployed_version(flow_name).await? else {
+            return Ok(None);
+        };
+        self.get_flow_version_content(flow_name, &version).await
+    }
 
     /// List all currently deployed flows with their content
     ///
@@ -163,7 +361,40 @@ pub trait FlowStorage: Send + Sync {
     ///     engine.start(&name, event, false).await?;
     /// }
     /// ```
-    async fn find_flow_names_by_topic(&self, topic: &str) -> Result<Vec<String>>;
+    async fn find_flow_names_by_topic(&self, topic: &str) -> Result<Vec<FlowName>>;
+
+    /// List deployed flows with their ownership metadata, filtered by `filter`
+    ///
+    /// Metadata is indexed from the deployed version's content at deploy time, so
+    /// filtering happens in the database. Sorted by flow name.
+    async fn list_deployed_flow_summaries(&self, filter: &FlowFilter) -> Result<Vec<FlowSummary>>;
+
+    /// Search deployed flows for every term of `query`, best matches first
+    ///
+    /// See [`search`] for the fields searched and how results rank. This default
+    /// scans the content of every deployed flow; the SQL backends query the
+    /// index written at deploy time instead.
+    async fn search_flows(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<search::FlowSearchResult>> {
+        let terms = search::search_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut candidates = Vec::new();
+        for (name, content) in self.list_all_deployed_flows().await? {
+            let Some(version) = self.get_deployed_version(&FlowName::new(&name)?).await? else {
+                continue;
+            };
+            for field in search::index_flow(&content) {
+                candidates.push((name.clone(), version.clone(), field));
+            }
+        }
+        Ok(search::rank(&terms, candidates, limit))
+    }
 }
 
 /// OAuth storage for credentials, providers, clients, and tokens
@@ -183,9 +414,30 @@ pub trait OAuthStorage: Send + Sync {
     /// List OAuth credentials
     async fn list_oauth_credentials(&self) -> Result<Vec<OAuthCredential>>;
 
+    /// List the OAuth credentials of one tenant
+    ///
+    /// Credentials without a tenant are left out.
+    async fn list_oauth_credentials_for_tenant(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Vec<OAuthCredential>> {
+        Ok(self
+            .list_oauth_credentials()
+            .await?
+            .into_iter()
+            .filter(|credential| credential.tenant_id.as_deref() == Some(tenant_id))
+            .collect())
+    }
+
     /// Delete OAuth credential by ID
     async fn delete_oauth_credential(&self, id: &str) -> Result<()>;
 
+    /// Delete credentials that expired before `now` and have no refresh token
+    ///
+    /// Such credentials can never be renewed. Credentials with a refresh token are
+    /// always kept, even when expired. Returns the number of credentials deleted.
+    async fn delete_expired_oauth_credentials(&self, now: DateTime<Utc>) -> Result<u64>;
+
     /// Refresh OAuth credential token
     async fn refresh_oauth_credential(
         &self,
@@ -243,14 +495,63 @@ pub trait OAuthStorage: Send + Sync {
     async fn delete_oauth_token_by_refresh(&self, refresh: &str) -> Result<()>;
 }
 
+/// Transactional writes and the event outbox
+///
+/// Events are recorded in the same transaction as the run and step writes that
+/// produce them, then published by the outbox dispatcher
+/// ([`crate::event::OutboxDispatcher`]), so a crash between the write and the
+/// publish delays the event instead of losing it.
+#[async_trait]
+pub trait OutboxStorage: Send + Sync {
+    /// Save runs and steps and enqueue events, all or nothing
+    ///
+    /// Each event gets the next sequence number of its topic. Returns the
+    /// enqueued events.
+    async fn commit(&self, batch: &WriteBatch) -> Result<Vec<OutboxEvent>>;
+
+    /// Oldest unsent events, in the order they were enqueued
+    async fn list_unsent_events(&self, limit: usize) -> Result<Vec<OutboxEvent>>;
+
+    /// Mark events as published
+    async fn mark_events_sent(&self, ids: &[Uuid]) -> Result<()>;
+
+    /// Delete events sent before `sent_before`, then the sequence counters of
+    /// topics left without any events
+    ///
+    /// A pruned topic starts again at sequence 1. Returns the number of events deleted.
+    async fn prune_sent_events(&self, sent_before: DateTime<Utc>) -> Result<u64>;
+}
+
+/// Writes applied atomically by [`OutboxStorage::commit`]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct WriteBatch {
+    /// Runs to save (upserted like [`RunStorage::save_run`])
+    #[serde(default)]
+    pub runs: Vec<Run>,
+    /// Steps to save (upserted like [`RunStorage::save_step`])
+    #[serde(default)]
+    pub steps: Vec<StepRun>,
+    /// Events to enqueue
+    #[serde(default)]
+    pub events: Vec<PendingEvent>,
+}
+
 /// Complete storage trait combining all focused storage traits
 ///
 /// This trait provides the full storage interface by composing all focused traits.
 /// Implementations can implement each focused trait separately for better modularity.
-pub trait Storage: RunStorage + StateStorage + FlowStorage + OAuthStorage {}
+pub trait Storage: RunStorage + StateStorage + FlowStorage + OAuthStorage + OutboxStorage {}
 
 /// Blanket implementation: any type implementing all focused traits also implements Storage
-impl<T> Storage for T where T: RunStorage + StateStorage + FlowStorage + OAuthStorage {}
+impl<T> Storage for T where T: RunStorage + StateStorage + FlowStorage + OAuthStorage + OutboxStorage
+{}
+
+/// Keyset position of the last item of a page: its sort time and unique key
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PageCursor<K> {
+    pub at: DateTime<Utc>,
+    pub key: K,
+}
 
 /// Flow snapshot represents a deployed flow version
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -261,7 +562,95 @@ pub struct FlowSnapshot {
     pub is_live: bool,
 }
 
+/// Ownership metadata of a flow, as listed by `list_flows`
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct FlowSummary {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Currently deployed version, if the flow is deployed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployed_version: Option<String>,
+}
+
+impl FlowSummary {
+    /// Summarize a parsed flow
+    pub fn of(flow: &Flow) -> Self {
+        Self {
+            name: flow.name.to_string(),
+            description: flow.description.clone(),
+            owner: flow.owner.clone(),
+            team: flow.team.clone(),
+            tags: flow.tags.clone().unwrap_or_default(),
+            deployed_version: None,
+        }
+    }
+}
+
+/// Filters for listing flows; unset fields match every flow
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FlowFilter {
+    pub tag: Option<String>,
+    pub owner: Option<String>,
+    pub team: Option<String>,
+    /// Tenant owning the flow (see [`FlowStorage::get_flow_tenant`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+impl FlowFilter {
+    /// Whether a flow summary passes every set filter
+    ///
+    /// Summaries don't carry their tenant, so `tenant_id` is left to the caller.
+    pub fn matches(&self, summary: &FlowSummary) -> bool {
+        self.tag
+            .as_ref()
+            .is_none_or(|tag| summary.tags.contains(tag))
+            && self
+                .owner
+                .as_ref()
+                .is_none_or(|owner| summary.owner.as_ref() == Some(owner))
+            && self
+                .team
+                .as_ref()
+                .is_none_or(|team| summary.team.as_ref() == Some(team))
+    }
+}
+
+/// Filters for listing runs; unset fields match every run
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RunFilter {
+    /// Label key and the value it must have
+    pub label: Option<(String, String)>,
+    /// Tenant the run must belong to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+/// Aggregated run statistics for a single flow over a time window
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FlowRunStats {
+    pub flow_name: String,
+    pub total: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    /// Median duration of finished runs (milliseconds)
+    pub p50_duration_ms: Option<i64>,
+    /// 95th percentile duration of finished runs (milliseconds)
+    pub p95_duration_ms: Option<i64>,
+    /// Resources consumed by the runs, summed
+    #[serde(default)]
+    pub usage: crate::model::RunUsage,
+}
+
 pub use postgres::PostgresStorage;
+pub use remote::RemoteStorage;
 pub use sqlite::SqliteStorage;
 
 /// Create a storage backend from configuration
@@ -269,15 +658,26 @@ pub async fn create_storage_from_config(
     config: &crate::config::StorageConfig,
 ) -> crate::Result<Arc<dyn Storage>> {
     match config.driver.as_str() {
-        "sqlite" => Ok(Arc::new(SqliteStorage::new(&config.dsn).await?)),
-        "postgres" => Ok(Arc::new(PostgresStorage::new(&config.dsn).await?)),
+        "sqlite" => Ok(Arc::new(
+            SqliteStorage::new(&config.dsn)
+                .await?
+                .with_integrity_chain(config.integrity_chain),
+        )),
+        "postgres" => Ok(Arc::new(
+            PostgresStorage::connect(&config.dsn, &config.pool.clone().unwrap_or_default())
+                .await?
+                .with_integrity_chain(config.integrity_chain),
+        )),
+        "remote" => Ok(Arc::new(RemoteStorage::new(&config.dsn)?)),
         _ => Err(crate::BeemFlowError::config(format!(
-            "Unknown storage driver: {}. Supported: sqlite, postgres",
+            "Unknown storage driver: {}. Supported: sqlite, postgres, remote",
             config.driver
         ))),
     }
 }
 
+#[cfg(test)]
+mod cached_test;
 #[cfg(test)]
 mod postgres_test;
 #[cfg(test)]
@@ -2,48 +2,406 @@
 //!
 //! Provides a production-ready PostgreSQL implementation of the Storage trait.
 
-use super::{FlowSnapshot, FlowStorage, OAuthStorage, RunStorage, StateStorage, sql_common::*};
+use super::integrity::{self, ChainHead, HistoryEntry, IntegrityReport, RecordKind, chain_hash};
+use super::search::{self, FlowSearchResult, SearchField};
+use super::{
+    FlowFilter, FlowRunStats, FlowSnapshot, FlowStorage, FlowSummary, OAuthStorage, OutboxStorage,
+    PageCursor, RunFilter, RunStorage, StateStorage, WriteBatch, sql_common::*,
+};
+use crate::config::StoragePoolConfig;
 use crate::{BeemFlowError, Result, model::*};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use sqlx::{PgPool, Row, postgres::PgRow};
+use sqlx::{
+    PgConnection, PgPool, Postgres, Row,
+    postgres::{PgArguments, PgPoolOptions, PgRow},
+    query::Query,
+};
 use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
 use uuid::Uuid;
 
+/// Migration converting the initial schema's TEXT and BIGINT columns to UUID,
+/// JSONB and TIMESTAMPTZ; rows written outside BeemFlow may not convert
+const NATIVE_COLUMN_TYPES_MIGRATION: i64 = 20250101000011;
+
+/// Describe a failed migration, with what to do when it is one that converts data
+///
+/// Each migration runs in a transaction, so a failed one leaves the schema as it was.
+pub(crate) fn migration_error(e: sqlx::migrate::MigrateError) -> BeemFlowError {
+    match e {
+        sqlx::migrate::MigrateError::ExecuteMigration(_, NATIVE_COLUMN_TYPES_MIGRATION) => {
+            BeemFlowError::storage(format!(
+                "Failed to run migrations: {}; the schema was left unchanged. This migration \
+                 converts ids to UUID, documents to JSONB and times to TIMESTAMPTZ, so a row \
+                 whose id isn't a UUID or whose document isn't JSON stops it; find and fix \
+                 those rows (see \"Upgrading PostgreSQL\" in the README) and restart",
+                e
+            ))
+        }
+        e => BeemFlowError::storage(format!("Failed to run migrations: {}", e)),
+    }
+}
+
 /// PostgreSQL storage implementation
 pub struct PostgresStorage {
     pool: PgPool,
+    retry_connection_errors: bool,
+    integrity_chain: bool,
 }
 
 impl PostgresStorage {
     /// Create a new PostgreSQL storage from a connection string
     pub async fn new(database_url: &str) -> Result<Self> {
-        let pool = PgPool::connect(database_url).await.map_err(|e| {
+        Self::connect(database_url, &StoragePoolConfig::default()).await
+    }
+
+    /// Create a new PostgreSQL storage with explicit pool health and retry settings
+    pub async fn connect(database_url: &str, pool_config: &StoragePoolConfig) -> Result<Self> {
+        let mut options = PgPoolOptions::new().test_before_acquire(pool_config.test_before_acquire);
+        if let Some(max) = pool_config.max_connections {
+            options = options.max_connections(max);
+        }
+        if let Some(secs) = pool_config.idle_timeout_secs {
+            options = options.idle_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = pool_config.max_lifetime_secs {
+            options = options.max_lifetime(Duration::from_secs(secs));
+        }
+
+        let pool = options.connect(database_url).await.map_err(|e| {
             BeemFlowError::storage(format!("Failed to connect to PostgreSQL: {}", e))
         })?;
 
-        // Run PostgreSQL-specific migrations
-        sqlx::migrate!("./migrations/postgres")
-            .run(&pool)
-            .await
-            .map_err(|e| BeemFlowError::storage(format!("Failed to run migrations: {}", e)))?;
+        // Run PostgreSQL-specific migrations, refusing schemas this binary can't
+        // migrate and confirming the schema is current afterwards
+        let migrator = sqlx::migrate!("./migrations/postgres");
+        verify_schema(&mut *pool.acquire().await?, &migrator, "PostgreSQL", false).await?;
+        migrator.run(&pool).await.map_err(migration_error)?;
+        verify_schema(&mut *pool.acquire().await?, &migrator, "PostgreSQL", true).await?;
+
+        Ok(Self {
+            pool,
+            retry_connection_errors: pool_config.retry_connection_errors,
+            integrity_chain: false,
+        })
+    }
+
+    /// Chain every run and step write into the tamper-evident run history
+    ///
+    /// See [`integrity`]. Off by default; writes then skip the history entirely.
+    pub fn with_integrity_chain(mut self, enabled: bool) -> Self {
+        self.integrity_chain = enabled;
+        self
+    }
+
+    /// Run an idempotent query, retrying it once if the connection was lost
+    ///
+    /// The retry acquires a fresh connection from the pool. Query errors
+    /// (constraint violations, bad SQL, ...) are returned as-is.
+    async fn reconnecting<T, F, Fut>(&self, query: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = std::result::Result<T, sqlx::Error>>,
+    {
+        match query().await {
+            Err(e) if self.retry_connection_errors && is_connection_error(&e) => {
+                tracing::warn!("PostgreSQL connection lost, retrying query: {}", e);
+                Ok(query().await?)
+            }
+            result => Ok(result?),
+        }
+    }
+
+    /// Upsert query for a run (shared by `save_run` and `commit`)
+    fn upsert_run<'q>(
+        run: &'q Run,
+        event: &'q serde_json::Value,
+        vars: &'q serde_json::Value,
+        labels: &'q Option<serde_json::Value>,
+        usage: &'q Option<serde_json::Value>,
+    ) -> Query<'q, Postgres, PgArguments> {
+        sqlx::query(
+            "INSERT INTO runs (id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+             ON CONFLICT(id) DO UPDATE SET
+                flow_name = EXCLUDED.flow_name,
+                event = EXCLUDED.event,
+                vars = EXCLUDED.vars,
+                status = EXCLUDED.status,
+                started_at = EXCLUDED.started_at,
+                ended_at = EXCLUDED.ended_at,
+                environment = EXCLUDED.environment,
+                correlation_id = EXCLUDED.correlation_id,
+                labels = EXCLUDED.labels,
+                usage = EXCLUDED.usage,
+                tenant_id = EXCLUDED.tenant_id",
+            )
+            .bind(run.id)
+            .bind(run.flow_name.as_str())
+            .bind(event)
+            .bind(vars)
+            .bind(run_status_to_str(run.status))
+            .bind(run.started_at)
+            .bind(run.ended_at)
+            .bind(&run.environment)
+            .bind(&run.correlation_id)
+            .bind(labels)
+            .bind(usage)
+            .bind(&run.tenant_id)
+    }
+
+    /// Upsert query for a step (shared by `save_step` and `commit`)
+    fn upsert_step<'q>(
+        step: &'q StepRun,
+        inputs: &'q serde_json::Value,
+        outputs: &'q serde_json::Value,
+    ) -> Query<'q, Postgres, PgArguments> {
+        let progress = step.progress.as_ref();
+        sqlx::query(
+            "INSERT INTO steps (id, run_id, step_name, status, started_at, ended_at, inputs, outputs, error,
+                                error_kind, progress_percent, progress_message, progress_updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+             ON CONFLICT(id) DO UPDATE SET
+                run_id = EXCLUDED.run_id,
+                step_name = EXCLUDED.step_name,
+                status = EXCLUDED.status,
+                started_at = EXCLUDED.started_at,
+                ended_at = EXCLUDED.ended_at,
+                inputs = EXCLUDED.inputs,
+                outputs = EXCLUDED.outputs,
+                error = EXCLUDED.error,
+                error_kind = EXCLUDED.error_kind,
+                progress_percent = EXCLUDED.progress_percent,
+                progress_message = EXCLUDED.progress_message,
+                progress_updated_at = EXCLUDED.progress_updated_at"
+        )
+            .bind(step.id)
+            .bind(step.run_id)
+            .bind(step.step_name.as_str())
+            .bind(step_status_to_str(step.status))
+            .bind(step.started_at)
+            .bind(step.ended_at)
+            .bind(inputs)
+            .bind(outputs)
+            .bind(&step.error)
+            .bind(step.error_kind.map(|kind| kind.as_str()))
+            .bind(progress.and_then(|p| p.percent))
+            .bind(progress.and_then(|p| p.message.as_deref()))
+            .bind(progress.map(|p| p.updated_at))
+    }
 
-        Ok(Self { pool })
+    /// Append a run's state to its flow's history chain
+    async fn chain_run(conn: &mut PgConnection, run: &Run) -> Result<()> {
+        let content = integrity::run_content(run)?;
+        Self::append_history(
+            conn,
+            run.flow_name.as_str(),
+            RecordKind::Run,
+            run.id,
+            &content,
+        )
+        .await
     }
+
+    /// Append a step's state to the history chain of its run's flow
+    async fn chain_step(conn: &mut PgConnection, step: &StepRun) -> Result<()> {
+        let flow_name: Option<String> =
+            sqlx::query_scalar("SELECT flow_name FROM runs WHERE id = $1")
+                .bind(step.run_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+        let content = integrity::step_content(step)?;
+        Self::append_history(
+            conn,
+            &flow_name.unwrap_or_default(),
+            RecordKind::Step,
+            step.id,
+            &content,
+        )
+        .await
+    }
+
+    /// Append a history entry and stamp the record's row with its hash
+    ///
+    /// The head upsert locks the flow's head row until the transaction ends, so
+    /// concurrent writers extend the chain one at a time.
+    async fn append_history(
+        conn: &mut PgConnection,
+        flow_name: &str,
+        kind: RecordKind,
+        record_id: Uuid,
+        content: &str,
+    ) -> Result<()> {
+        let (sequence, prev_hash): (i64, String) = sqlx::query_as(
+            "INSERT INTO run_history_heads (flow_name, last_sequence, last_hash) VALUES ($1, 1, '')
+             ON CONFLICT(flow_name) DO UPDATE SET last_sequence = run_history_heads.last_sequence + 1
+             RETURNING last_sequence, last_hash",
+        )
+        .bind(flow_name)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let record_hash = chain_hash(&prev_hash, kind, content);
+        sqlx::query(
+            "INSERT INTO run_history (flow_name, sequence, record_kind, record_id, content, prev_hash, record_hash, recorded_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(flow_name)
+        .bind(sequence)
+        .bind(kind.as_str())
+        .bind(record_id)
+        .bind(content)
+        .bind(&prev_hash)
+        .bind(&record_hash)
+        .bind(Utc::now())
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query("UPDATE run_history_heads SET last_hash = $1 WHERE flow_name = $2")
+            .bind(&record_hash)
+            .bind(flow_name)
+            .execute(&mut *conn)
+            .await?;
+
+        let stamp = match kind {
+            RecordKind::Run => "UPDATE runs SET record_hash = $1 WHERE id = $2",
+            RecordKind::Step => "UPDATE steps SET record_hash = $1 WHERE id = $2",
+        };
+        sqlx::query(stamp)
+            .bind(&record_hash)
+            .bind(record_id)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Enqueue an event with the next sequence number of its topic
+    ///
+    /// The upsert locks the topic's counter row until the transaction ends, so
+    /// concurrent writers get increasing sequence numbers.
+    async fn enqueue_event(conn: &mut PgConnection, event: &PendingEvent) -> Result<OutboxEvent> {
+        let sequence: i64 = sqlx::query_scalar(
+            "INSERT INTO event_topic_sequences (topic, last_sequence) VALUES ($1, 1)
+             ON CONFLICT(topic) DO UPDATE SET last_sequence = event_topic_sequences.last_sequence + 1
+             RETURNING last_sequence",
+        )
+        .bind(&event.topic)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let queued = OutboxEvent {
+            id: Uuid::new_v4(),
+            topic: event.topic.clone(),
+            sequence,
+            payload: event.payload.clone(),
+            source: event.source.clone(),
+            correlation_id: event.correlation_id.clone(),
+            created_at: Utc::now(),
+        };
+        sqlx::query(
+            "INSERT INTO event_outbox (id, topic, sequence, payload, source, correlation_id, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(queued.id)
+        .bind(&queued.topic)
+        .bind(queued.sequence)
+        .bind(&queued.payload)
+        .bind(serde_json::to_value(&queued.source)?)
+        .bind(&queued.correlation_id)
+        .bind(queued.created_at)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(queued)
+    }
+
+    fn parse_outbox_event(row: &PgRow) -> Result<OutboxEvent> {
+        Ok(OutboxEvent {
+            id: row.try_get("id")?,
+            topic: row.try_get("topic")?,
+            sequence: row.try_get("sequence")?,
+            payload: row.try_get("payload")?,
+            source: row
+                .try_get::<Option<serde_json::Value>, _>("source")?
+                .map(serde_json::from_value)
+                .transpose()?
+                .unwrap_or_default(),
+            correlation_id: row.try_get("correlation_id")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+
     fn parse_run(row: &PgRow) -> Result<Run> {
         Ok(Run {
             id: row.try_get("id")?,
-            flow_name: row.try_get::<String, _>("flow_name")?.into(),
+            flow_name: row.try_get::<String, _>("flow_name")?.try_into()?,
             event: parse_hashmap_from_jsonb(row.try_get("event")?),
             vars: parse_hashmap_from_jsonb(row.try_get("vars")?),
             status: parse_run_status(&row.try_get::<String, _>("status")?),
             started_at: row.try_get("started_at")?,
             ended_at: row.try_get("ended_at")?,
             steps: None,
+            environment: row.try_get("environment")?,
+            correlation_id: row.try_get("correlation_id")?,
+            labels: row
+                .try_get::<Option<serde_json::Value>, _>("labels")?
+                .map(serde_json::from_value)
+                .transpose()?
+                .unwrap_or_default(),
+            usage: row
+                .try_get::<Option<serde_json::Value>, _>("usage")?
+                .map(serde_json::from_value)
+                .transpose()?,
+            tenant_id: row.try_get("tenant_id")?,
+        })
+    }
+
+    fn parse_webhook_payload(row: &PgRow) -> Result<WebhookPayload> {
+        Ok(WebhookPayload {
+            id: row.try_get("id")?,
+            provider: row.try_get("provider")?,
+            headers: serde_json::from_value(row.try_get("headers")?)?,
+            body: row.try_get("body")?,
+            received_at: row.try_get("received_at")?,
+        })
+    }
+
+    fn parse_delivery(row: &PgRow) -> Result<OutboundDelivery> {
+        Ok(OutboundDelivery {
+            id: row.try_get("id")?,
+            method: row.try_get("method")?,
+            url: row.try_get("url")?,
+            headers: serde_json::from_value(row.try_get("headers")?)?,
+            body: row.try_get("body")?,
+            sealed: row.try_get("sealed")?,
+            rate_limit: row
+                .try_get::<Option<serde_json::Value>, _>("rate_limit")?
+                .map(serde_json::from_value)
+                .transpose()?,
+            attempts: row.try_get::<i32, _>("attempts")? as u32,
+            max_attempts: row.try_get::<i32, _>("max_attempts")? as u32,
+            last_error: row.try_get("last_error")?,
+            next_attempt_at: row.try_get("next_attempt_at")?,
+            created_at: row.try_get("created_at")?,
+            dead_lettered_at: row.try_get("dead_lettered_at").ok().flatten(),
+            run_id: row.try_get("run_id")?,
+        })
+    }
+
+    fn parse_blob_record(row: &PgRow) -> Result<BlobRecord> {
+        Ok(BlobRecord {
+            id: row.try_get("id")?,
+            backend: row.try_get("backend")?,
+            store: row.try_get("store")?,
+            location: row.try_get("location")?,
+            created_at: row.try_get("created_at")?,
         })
     }
 
     fn parse_step(row: &PgRow) -> Result<StepRun> {
+        let inputs_json: Option<serde_json::Value> = row.try_get("inputs")?;
         let outputs_json: serde_json::Value = row.try_get("outputs")?;
 
         Ok(StepRun {
@@ -53,50 +411,81 @@ impl PostgresStorage {
             status: parse_step_status(&row.try_get::<String, _>("status")?),
             started_at: row.try_get("started_at")?,
             ended_at: row.try_get("ended_at")?,
+            inputs: inputs_json
+                .as_ref()
+                .and_then(|v| v.as_object())
+                .map(|m| m.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
             outputs: outputs_json
                 .as_object()
                 .map(|m| m.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
             error: row.try_get("error")?,
+            error_kind: row
+                .try_get::<Option<String>, _>("error_kind")?
+                .and_then(|kind| kind.parse().ok()),
+            progress: row
+                .try_get::<Option<DateTime<Utc>>, _>("progress_updated_at")?
+                .map(|updated_at| -> Result<StepProgress> {
+                    Ok(StepProgress {
+                        percent: row.try_get("progress_percent")?,
+                        message: row.try_get("progress_message")?,
+                        updated_at,
+                    })
+                })
+                .transpose()?,
         })
     }
 }
 
+/// Whether an error means the connection was lost rather than the query failing
+pub(crate) fn is_connection_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::Protocol(_) => true,
+        // SQLSTATE class 08 (connection exception) and server shutdown/startup
+        sqlx::Error::Database(db) => db.code().is_some_and(|code| {
+            code.starts_with("08") || matches!(code.as_ref(), "57P01" | "57P02" | "57P03")
+        }),
+        _ => false,
+    }
+}
+
 #[async_trait]
 impl RunStorage for PostgresStorage {
     // Run methods
     async fn save_run(&self, run: &Run) -> Result<()> {
-        sqlx::query(
-            "INSERT INTO runs (id, flow_name, event, vars, status, started_at, ended_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
-             ON CONFLICT(id) DO UPDATE SET
-                flow_name = EXCLUDED.flow_name,
-                event = EXCLUDED.event,
-                vars = EXCLUDED.vars,
-                status = EXCLUDED.status,
-                started_at = EXCLUDED.started_at,
-                ended_at = EXCLUDED.ended_at",
-        )
-        .bind(run.id)
-        .bind(run.flow_name.as_str())
-        .bind(serde_json::to_value(&run.event)?)
-        .bind(serde_json::to_value(&run.vars)?)
-        .bind(run_status_to_str(run.status))
-        .bind(run.started_at)
-        .bind(run.ended_at)
-        .execute(&self.pool)
-        .await?;
+        let event = serde_json::to_value(&run.event)?;
+        let vars = serde_json::to_value(&run.vars)?;
+        let labels = labels_to_json(&run.labels);
+        let usage = run.usage.as_ref().map(serde_json::to_value).transpose()?;
+        if !self.integrity_chain {
+            self.reconnecting(|| {
+                Self::upsert_run(run, &event, &vars, &labels, &usage).execute(&self.pool)
+            })
+            .await?;
+            return Ok(());
+        }
+
+        // Not retried: the history entry is appended once per write
+        let mut tx = self.pool.begin().await?;
+        Self::upsert_run(run, &event, &vars, &labels, &usage)
+            .execute(&mut *tx)
+            .await?;
+        Self::chain_run(&mut tx, run).await?;
+        tx.commit().await?;
 
         Ok(())
     }
 
     async fn get_run(&self, id: Uuid) -> Result<Option<Run>> {
-        let row = sqlx::query(
-            "SELECT id, flow_name, event, vars, status, started_at, ended_at 
-             FROM runs WHERE id = $1",
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await?;
+        let row = self
+            .reconnecting(|| {
+                sqlx::query(
+                    "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
+                     FROM runs WHERE id = $1",
+                )
+                .bind(id)
+                .fetch_optional(&self.pool)
+            })
+            .await?;
 
         match row {
             Some(row) => Ok(Some(Self::parse_run(&row)?)),
@@ -109,7 +498,7 @@ impl RunStorage for PostgresStorage {
         let capped_limit = limit.min(10_000);
 
         let rows = sqlx::query(
-            "SELECT id, flow_name, event, vars, status, started_at, ended_at
+            "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
              FROM runs
              ORDER BY started_at DESC
              LIMIT $1 OFFSET $2",
@@ -128,6 +517,94 @@ impl RunStorage for PostgresStorage {
         Ok(runs)
     }
 
+    async fn list_runs_for_tenant(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Run>> {
+        let capped_limit = limit.min(10_000);
+
+        let rows = sqlx::query(
+            "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
+             FROM runs
+             WHERE tenant_id = $1
+             ORDER BY started_at DESC
+             LIMIT $2 OFFSET $3",
+        )
+        .bind(tenant_id)
+        .bind(capped_limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut runs = Vec::new();
+        for row in rows {
+            if let Ok(run) = Self::parse_run(&row) {
+                runs.push(run);
+            }
+        }
+        Ok(runs)
+    }
+
+    async fn list_runs_after(
+        &self,
+        filter: &RunFilter,
+        limit: usize,
+        after: Option<&PageCursor<Uuid>>,
+    ) -> Result<Vec<Run>> {
+        let capped_limit = limit.min(10_000);
+        // Containment (`@>`) can use the GIN index on labels
+        let label = filter
+            .label
+            .as_ref()
+            .map(|(key, value)| serde_json::json!({ key: value }));
+
+        let rows = match after {
+            Some(after) => {
+                sqlx::query(
+                    "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
+                     FROM runs
+                     WHERE ($1::JSONB IS NULL OR labels @> $1)
+                       AND ($5::TEXT IS NULL OR tenant_id = $5)
+                       AND (started_at, id) < ($2, $3)
+                     ORDER BY started_at DESC, id DESC
+                     LIMIT $4",
+                )
+                .bind(&label)
+                .bind(after.at)
+                .bind(after.key)
+                .bind(capped_limit as i64)
+                .bind(&filter.tenant_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
+                     FROM runs
+                     WHERE ($1::JSONB IS NULL OR labels @> $1)
+                       AND ($3::TEXT IS NULL OR tenant_id = $3)
+                     ORDER BY started_at DESC, id DESC
+                     LIMIT $2",
+                )
+                .bind(&label)
+                .bind(capped_limit as i64)
+                .bind(&filter.tenant_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        let mut runs = Vec::new();
+        for row in rows {
+            if let Ok(run) = Self::parse_run(&row) {
+                runs.push(run);
+            }
+        }
+        Ok(runs)
+    }
+
     async fn list_runs_by_flow_and_status(
         &self,
         flow_name: &str,
@@ -140,7 +617,7 @@ impl RunStorage for PostgresStorage {
         // Build query with optional exclude clause
         let query = if let Some(id) = exclude_id {
             sqlx::query(
-                "SELECT id, flow_name, event, vars, status, started_at, ended_at
+                "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
                  FROM runs
                  WHERE flow_name = $1 AND status = $2 AND id != $3
                  ORDER BY started_at DESC
@@ -152,7 +629,7 @@ impl RunStorage for PostgresStorage {
             .bind(limit as i64)
         } else {
             sqlx::query(
-                "SELECT id, flow_name, event, vars, status, started_at, ended_at
+                "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
                  FROM runs
                  WHERE flow_name = $1 AND status = $2
                  ORDER BY started_at DESC
@@ -185,9 +662,11 @@ impl RunStorage for PostgresStorage {
     }
 
     async fn try_insert_run(&self, run: &Run) -> Result<bool> {
-        let result = sqlx::query(
-            "INSERT INTO runs (id, flow_name, event, vars, status, started_at, ended_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+        // Not retried: if the insert committed before the connection dropped, a
+        // retry would report the run as a duplicate
+        let insert = sqlx::query(
+            "INSERT INTO runs (id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
              ON CONFLICT(id) DO NOTHING",
         )
         .bind(run.id)
@@ -197,49 +676,130 @@ impl RunStorage for PostgresStorage {
         .bind(run_status_to_str(run.status))
         .bind(run.started_at)
         .bind(run.ended_at)
-        .execute(&self.pool)
-        .await?;
+        .bind(&run.environment)
+        .bind(&run.correlation_id)
+        .bind(labels_to_json(&run.labels))
+        .bind(run.usage.as_ref().map(serde_json::to_value).transpose()?)
+        .bind(&run.tenant_id);
 
         // Returns true if a row was inserted, false if conflict occurred
-        Ok(result.rows_affected() == 1)
+        if !self.integrity_chain {
+            return Ok(insert.execute(&self.pool).await?.rows_affected() == 1);
+        }
+        let mut tx = self.pool.begin().await?;
+        let inserted = insert.execute(&mut *tx).await?.rows_affected() == 1;
+        if inserted {
+            Self::chain_run(&mut tx, run).await?;
+        }
+        tx.commit().await?;
+
+        Ok(inserted)
     }
 
-    // Step methods
-    async fn save_step(&self, step: &StepRun) -> Result<()> {
-        sqlx::query(
-            "INSERT INTO steps (id, run_id, step_name, status, started_at, ended_at, outputs, error)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-             ON CONFLICT(id) DO UPDATE SET
-                run_id = EXCLUDED.run_id,
-                step_name = EXCLUDED.step_name,
-                status = EXCLUDED.status,
-                started_at = EXCLUDED.started_at,
-                ended_at = EXCLUDED.ended_at,
-                outputs = EXCLUDED.outputs,
-                error = EXCLUDED.error"
+    async fn run_stats(
+        &self,
+        since: DateTime<Utc>,
+        flow_name: Option<&str>,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<FlowRunStats>> {
+        let rows = sqlx::query(
+            "SELECT flow_name,
+                COUNT(*) AS total,
+                COUNT(*) FILTER (WHERE status = 'SUCCEEDED') AS succeeded,
+                COUNT(*) FILTER (WHERE status = 'FAILED') AS failed,
+                percentile_disc(0.5) WITHIN GROUP (
+                    ORDER BY (EXTRACT(EPOCH FROM (ended_at - started_at)) * 1000)::DOUBLE PRECISION
+                ) FILTER (WHERE ended_at IS NOT NULL) AS p50,
+                percentile_disc(0.95) WITHIN GROUP (
+                    ORDER BY (EXTRACT(EPOCH FROM (ended_at - started_at)) * 1000)::DOUBLE PRECISION
+                ) FILTER (WHERE ended_at IS NOT NULL) AS p95
+             FROM runs
+             WHERE started_at >= $1 AND ($2::TEXT IS NULL OR flow_name = $2)
+                AND ($3::TEXT IS NULL OR tenant_id = $3)
+             GROUP BY flow_name
+             ORDER BY failed DESC, flow_name",
         )
-        .bind(step.id)
-        .bind(step.run_id)
-        .bind(step.step_name.as_str())
-        .bind(step_status_to_str(step.status))
-        .bind(step.started_at)
-        .bind(step.ended_at)
-        .bind(serde_json::to_value(&step.outputs)?)
-        .bind(&step.error)
-        .execute(&self.pool)
+        .bind(since)
+        .bind(flow_name)
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut stats = rows
+            .iter()
+            .map(|row| {
+                Ok(FlowRunStats {
+                    flow_name: row.try_get("flow_name")?,
+                    total: row.try_get::<i64, _>("total")? as u64,
+                    succeeded: row.try_get::<i64, _>("succeeded")? as u64,
+                    failed: row.try_get::<i64, _>("failed")? as u64,
+                    p50_duration_ms: row
+                        .try_get::<Option<f64>, _>("p50")?
+                        .map(|ms| ms.round() as i64),
+                    p95_duration_ms: row
+                        .try_get::<Option<f64>, _>("p95")?
+                        .map(|ms| ms.round() as i64),
+                    usage: Default::default(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let usage_rows = sqlx::query(
+            "SELECT flow_name, usage FROM runs
+             WHERE started_at >= $1 AND ($2::TEXT IS NULL OR flow_name = $2)
+                AND ($3::TEXT IS NULL OR tenant_id = $3) AND usage IS NOT NULL",
+        )
+        .bind(since)
+        .bind(flow_name)
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
         .await?;
+        let usages = usage_rows
+            .iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("flow_name")?,
+                    serde_json::from_value(row.try_get("usage")?)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        add_run_usage(&mut stats, usages);
+
+        Ok(stats)
+    }
+
+    // Step methods
+    async fn save_step(&self, step: &StepRun) -> Result<()> {
+        let inputs = serde_json::to_value(&step.inputs)?;
+        let outputs = serde_json::to_value(&step.outputs)?;
+        if !self.integrity_chain {
+            self.reconnecting(|| Self::upsert_step(step, &inputs, &outputs).execute(&self.pool))
+                .await?;
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        Self::upsert_step(step, &inputs, &outputs)
+            .execute(&mut *tx)
+            .await?;
+        Self::chain_step(&mut tx, step).await?;
+        tx.commit().await?;
 
         Ok(())
     }
 
     async fn get_steps(&self, run_id: Uuid) -> Result<Vec<StepRun>> {
-        let rows = sqlx::query(
-            "SELECT id, run_id, step_name, status, started_at, ended_at, outputs, error 
-             FROM steps WHERE run_id = $1",
-        )
-        .bind(run_id)
-        .fetch_all(&self.pool)
-        .await?;
+        let rows = self
+            .reconnecting(|| {
+                sqlx::query(
+                    "SELECT id, run_id, step_name, status, started_at, ended_at, inputs, outputs, error,
+                            error_kind, progress_percent, progress_message, progress_updated_at
+                     FROM steps WHERE run_id = $1",
+                )
+                .bind(run_id)
+                .fetch_all(&self.pool)
+            })
+            .await?;
 
         let mut steps = Vec::new();
         for row in rows {
@@ -249,111 +809,487 @@ impl RunStorage for PostgresStorage {
         }
         Ok(steps)
     }
+
+    async fn verify_integrity(&self, flow_name: Option<&str>) -> Result<IntegrityReport> {
+        let heads = self
+            .reconnecting(|| {
+                sqlx::query(
+                    "SELECT flow_name, last_sequence, last_hash FROM run_history_heads
+                     WHERE $1::TEXT IS NULL OR flow_name = $1
+                     ORDER BY flow_name",
+                )
+                .bind(flow_name)
+                .fetch_all(&self.pool)
+            })
+            .await?;
+
+        let mut report = IntegrityReport::default();
+        for row in heads {
+            let head = ChainHead {
+                flow_name: row.try_get("flow_name")?,
+                last_sequence: row.try_get("last_sequence")?,
+                last_hash: row.try_get("last_hash")?,
+            };
+            let entries = self
+                .reconnecting(|| {
+                    sqlx::query(
+                        "SELECT sequence, record_kind, record_id, content, prev_hash, record_hash
+                         FROM run_history WHERE flow_name = $1 ORDER BY sequence",
+                    )
+                    .bind(&head.flow_name)
+                    .fetch_all(&self.pool)
+                })
+                .await?
+                .iter()
+                .map(|row| -> Result<HistoryEntry> {
+                    Ok(HistoryEntry {
+                        sequence: row.try_get("sequence")?,
+                        kind: RecordKind::parse(&row.try_get::<String, _>("record_kind")?)?,
+                        record_id: row.try_get("record_id")?,
+                        content: row.try_get("content")?,
+                        prev_hash: row.try_get("prev_hash")?,
+                        record_hash: row.try_get("record_hash")?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            integrity::verify_chain(self, &head, &entries, &mut report).await?;
+            if !report.is_intact() {
+                break;
+            }
+        }
+
+        Ok(report)
+    }
 }
 
 #[async_trait]
 impl StateStorage for PostgresStorage {
     // Wait/timeout methods
     async fn register_wait(&self, token: Uuid, wake_at: Option<i64>) -> Result<()> {
+        self.reconnecting(|| {
+            sqlx::query(
+                "INSERT INTO waits (token, wake_at) VALUES ($1, $2)
+                 ON CONFLICT(token) DO UPDATE SET wake_at = EXCLUDED.wake_at",
+            )
+            .bind(token.to_string())
+            .bind(wake_at)
+            .execute(&self.pool)
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    async fn resolve_wait(&self, token: Uuid) -> Result<Option<Run>> {
+        sqlx::query("DELETE FROM waits WHERE token = $1")
+            .bind(token.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        // Postgres storage doesn't resolve waits to specific runs
+        Ok(None)
+    }
+
+    // Paused run methods
+    async fn save_paused_run(
+        &self,
+        token: &str,
+        source: &str,
+        data: serde_json::Value,
+    ) -> Result<()> {
+        self.reconnecting(|| {
+            sqlx::query(
+                "INSERT INTO paused_runs (token, source, data) VALUES ($1, $2, $3)
+                 ON CONFLICT(token) DO UPDATE SET source = EXCLUDED.source, data = EXCLUDED.data",
+            )
+            .bind(token)
+            .bind(source)
+            .bind(&data)
+            .execute(&self.pool)
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    async fn load_paused_runs(&self) -> Result<HashMap<String, serde_json::Value>> {
+        let rows = self
+            .reconnecting(|| {
+                sqlx::query("SELECT token, data FROM paused_runs").fetch_all(&self.pool)
+            })
+            .await?;
+
+        let mut result = HashMap::new();
+        for row in rows {
+            let token: String = row.try_get("token")?;
+            let data: serde_json::Value = row.try_get("data")?;
+            result.insert(token, data);
+        }
+
+        Ok(result)
+    }
+
+    async fn find_paused_runs_by_source(
+        &self,
+        source: &str,
+    ) -> Result<Vec<(String, serde_json::Value)>> {
+        let rows = self
+            .reconnecting(|| {
+                sqlx::query("SELECT token, data FROM paused_runs WHERE source = $1")
+                    .bind(source)
+                    .fetch_all(&self.pool)
+            })
+            .await?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            let token: String = row.try_get("token")?;
+            let data: serde_json::Value = row.try_get("data")?;
+            result.push((token, data));
+        }
+
+        Ok(result)
+    }
+
+    async fn delete_paused_run(&self, token: &str) -> Result<()> {
+        sqlx::query("DELETE FROM paused_runs WHERE token = $1")
+            .bind(token)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_and_delete_paused_run(&self, token: &str) -> Result<Option<serde_json::Value>> {
+        // Use DELETE ... RETURNING for atomic fetch-and-delete
+        let row = sqlx::query("DELETE FROM paused_runs WHERE token = $1 RETURNING data")
+            .bind(token)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => {
+                let data: serde_json::Value = row.try_get("data")?;
+                Ok(Some(data))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Webhook payload history
+    async fn save_webhook_payload(&self, payload: &WebhookPayload, keep: usize) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO webhook_payloads (id, provider, headers, body, received_at)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(payload.id)
+        .bind(&payload.provider)
+        .bind(serde_json::to_value(&payload.headers)?)
+        .bind(&payload.body)
+        .bind(payload.received_at)
+        .execute(&mut *tx)
+        .await?;
+
+        // Trim to the newest `keep` payloads for this provider
+        sqlx::query(
+            "DELETE FROM webhook_payloads WHERE provider = $1 AND id NOT IN (
+                SELECT id FROM webhook_payloads WHERE provider = $1
+                ORDER BY received_at DESC LIMIT $2
+             )",
+        )
+        .bind(&payload.provider)
+        .bind(keep as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_webhook_payload(&self, id: Uuid) -> Result<Option<WebhookPayload>> {
+        let row = sqlx::query(
+            "SELECT id, provider, headers, body, received_at FROM webhook_payloads WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(Self::parse_webhook_payload).transpose()
+    }
+
+    async fn list_webhook_payloads(
+        &self,
+        provider: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<WebhookPayload>> {
+        let rows = sqlx::query(
+            "SELECT id, provider, headers, body, received_at FROM webhook_payloads
+             WHERE $1::TEXT IS NULL OR provider = $1
+             ORDER BY received_at DESC LIMIT $2",
+        )
+        .bind(provider)
+        .bind(limit.min(10_000) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::parse_webhook_payload).collect()
+    }
+
+    // Blob locations
+    async fn save_blob_record(&self, record: &BlobRecord) -> Result<()> {
         sqlx::query(
-            "INSERT INTO waits (token, wake_at) VALUES ($1, $2) 
-             ON CONFLICT(token) DO UPDATE SET wake_at = EXCLUDED.wake_at",
+            "INSERT INTO blobs (id, backend, store, location, created_at) VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT(id) DO UPDATE SET
+                backend = EXCLUDED.backend,
+                store = EXCLUDED.store,
+                location = EXCLUDED.location,
+                created_at = EXCLUDED.created_at",
         )
-        .bind(token.to_string())
-        .bind(wake_at)
+        .bind(record.id)
+        .bind(&record.backend)
+        .bind(&record.store)
+        .bind(&record.location)
+        .bind(record.created_at)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    async fn resolve_wait(&self, token: Uuid) -> Result<Option<Run>> {
-        sqlx::query("DELETE FROM waits WHERE token = $1")
-            .bind(token.to_string())
-            .execute(&self.pool)
+    async fn get_blob_record(&self, id: Uuid) -> Result<Option<BlobRecord>> {
+        let row = self
+            .reconnecting(|| {
+                sqlx::query(
+                    "SELECT id, backend, store, location, created_at FROM blobs WHERE id = $1",
+                )
+                .bind(id)
+                .fetch_optional(&self.pool)
+            })
             .await?;
 
-        // Postgres storage doesn't resolve waits to specific runs
-        Ok(None)
+        row.as_ref().map(Self::parse_blob_record).transpose()
     }
 
-    // Paused run methods
-    async fn save_paused_run(
-        &self,
-        token: &str,
-        source: &str,
-        data: serde_json::Value,
-    ) -> Result<()> {
-        sqlx::query(
-            "INSERT INTO paused_runs (token, source, data) VALUES ($1, $2, $3)
-             ON CONFLICT(token) DO UPDATE SET source = EXCLUDED.source, data = EXCLUDED.data",
+    async fn list_blob_records(&self) -> Result<Vec<BlobRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, backend, store, location, created_at FROM blobs ORDER BY created_at, id",
         )
-        .bind(token)
-        .bind(source)
-        .bind(data)
-        .execute(&self.pool)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(())
+        rows.iter().map(Self::parse_blob_record).collect()
     }
 
-    async fn load_paused_runs(&self) -> Result<HashMap<String, serde_json::Value>> {
-        let rows = sqlx::query("SELECT token, data FROM paused_runs")
-            .fetch_all(&self.pool)
+    async fn delete_blob_record(&self, id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM blobs WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
             .await?;
 
-        let mut result = HashMap::new();
-        for row in rows {
-            let token: String = row.try_get("token")?;
-            let data: serde_json::Value = row.try_get("data")?;
-            result.insert(token, data);
-        }
+        Ok(())
+    }
 
-        Ok(result)
+    // Run checkpoints
+    async fn save_run_checkpoint(&self, run_id: Uuid, data: serde_json::Value) -> Result<()> {
+        self.reconnecting(|| {
+            sqlx::query(
+                "INSERT INTO run_checkpoints (run_id, data, updated_at) VALUES ($1, $2, $3)
+                 ON CONFLICT(run_id) DO UPDATE SET data = EXCLUDED.data, updated_at = EXCLUDED.updated_at",
+            )
+            .bind(run_id)
+            .bind(&data)
+            .bind(Utc::now())
+            .execute(&self.pool)
+        })
+        .await?;
+
+        Ok(())
     }
 
-    async fn find_paused_runs_by_source(
-        &self,
-        source: &str,
-    ) -> Result<Vec<(String, serde_json::Value)>> {
-        let rows = sqlx::query("SELECT token, data FROM paused_runs WHERE source = $1")
-            .bind(source)
-            .fetch_all(&self.pool)
+    async fn load_run_checkpoints(&self) -> Result<Vec<(Uuid, serde_json::Value)>> {
+        let rows = self
+            .reconnecting(|| {
+                sqlx::query("SELECT run_id, data FROM run_checkpoints ORDER BY updated_at")
+                    .fetch_all(&self.pool)
+            })
             .await?;
 
         let mut result = Vec::new();
         for row in rows {
-            let token: String = row.try_get("token")?;
+            let run_id: Uuid = row.try_get("run_id")?;
             let data: serde_json::Value = row.try_get("data")?;
-            result.push((token, data));
+            result.push((run_id, data));
         }
 
         Ok(result)
     }
 
-    async fn delete_paused_run(&self, token: &str) -> Result<()> {
-        sqlx::query("DELETE FROM paused_runs WHERE token = $1")
-            .bind(token)
+    async fn delete_run_checkpoint(&self, run_id: Uuid) -> Result<()> {
+        self.reconnecting(|| {
+            sqlx::query("DELETE FROM run_checkpoints WHERE run_id = $1")
+                .bind(run_id)
+                .execute(&self.pool)
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    // Outbound deliveries
+    async fn save_delivery(&self, delivery: &OutboundDelivery) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO outbound_deliveries
+                (id, method, url, headers, body, sealed, rate_limit, attempts, max_attempts, last_error,
+                 next_attempt_at, created_at, run_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+             ON CONFLICT(id) DO UPDATE SET
+                attempts = EXCLUDED.attempts,
+                max_attempts = EXCLUDED.max_attempts,
+                last_error = EXCLUDED.last_error,
+                next_attempt_at = EXCLUDED.next_attempt_at",
+        )
+        .bind(delivery.id)
+        .bind(&delivery.method)
+        .bind(&delivery.url)
+        .bind(serde_json::to_value(&delivery.headers)?)
+        .bind(&delivery.body)
+        .bind(&delivery.sealed)
+        .bind(
+            delivery
+                .rate_limit
+                .as_ref()
+                .map(serde_json::to_value)
+                .transpose()?,
+        )
+        .bind(delivery.attempts as i32)
+        .bind(delivery.max_attempts as i32)
+        .bind(&delivery.last_error)
+        .bind(delivery.next_attempt_at)
+        .bind(delivery.created_at)
+        .bind(delivery.run_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<OutboundDelivery>> {
+        let rows = sqlx::query(
+            "SELECT * FROM outbound_deliveries WHERE next_attempt_at <= $1
+             ORDER BY next_attempt_at, created_at LIMIT $2",
+        )
+        .bind(now)
+        .bind(limit.min(10_000) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::parse_delivery).collect()
+    }
+
+    async fn claim_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<OutboundDelivery>> {
+        let rows = sqlx::query(
+            "UPDATE outbound_deliveries SET next_attempt_at = $1
+             WHERE id IN (
+                SELECT id FROM outbound_deliveries WHERE next_attempt_at <= $2
+                ORDER BY next_attempt_at, created_at LIMIT $3
+                FOR UPDATE SKIP LOCKED
+             )
+             RETURNING *",
+        )
+        .bind(lease_until)
+        .bind(now)
+        .bind(limit.min(10_000) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut claimed = rows
+            .iter()
+            .map(Self::parse_delivery)
+            .collect::<Result<Vec<_>>>()?;
+        claimed.sort_by_key(|delivery| delivery.created_at);
+        Ok(claimed)
+    }
+
+    async fn delete_delivery(&self, id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM outbound_deliveries WHERE id = $1")
+            .bind(id)
             .execute(&self.pool)
             .await?;
 
         Ok(())
     }
 
-    async fn fetch_and_delete_paused_run(&self, token: &str) -> Result<Option<serde_json::Value>> {
-        // Use DELETE ... RETURNING for atomic fetch-and-delete
-        let row = sqlx::query("DELETE FROM paused_runs WHERE token = $1 RETURNING data")
-            .bind(token)
-            .fetch_optional(&self.pool)
+    async fn dead_letter_delivery(&self, delivery: &OutboundDelivery) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO dead_letter_deliveries
+                (id, method, url, headers, body, sealed, rate_limit, attempts, max_attempts, last_error,
+                 next_attempt_at, created_at, dead_lettered_at, run_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+             ON CONFLICT(id) DO NOTHING",
+        )
+        .bind(delivery.id)
+        .bind(&delivery.method)
+        .bind(&delivery.url)
+        .bind(serde_json::to_value(&delivery.headers)?)
+        .bind(&delivery.body)
+        .bind(&delivery.sealed)
+        .bind(
+            delivery
+                .rate_limit
+                .as_ref()
+                .map(serde_json::to_value)
+                .transpose()?,
+        )
+        .bind(delivery.attempts as i32)
+        .bind(delivery.max_attempts as i32)
+        .bind(&delivery.last_error)
+        .bind(delivery.next_attempt_at)
+        .bind(delivery.created_at)
+        .bind(delivery.dead_lettered_at.unwrap_or_else(Utc::now))
+        .bind(delivery.run_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM outbound_deliveries WHERE id = $1")
+            .bind(delivery.id)
+            .execute(&mut *tx)
             .await?;
 
-        match row {
-            Some(row) => {
-                let data: serde_json::Value = row.try_get("data")?;
-                Ok(Some(data))
-            }
-            None => Ok(None),
-        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list_dead_letters(
+        &self,
+        limit: usize,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<OutboundDelivery>> {
+        let rows = sqlx::query(
+            "SELECT * FROM dead_letter_deliveries
+             WHERE $1::TEXT IS NULL
+                OR run_id::TEXT IN (SELECT id::TEXT FROM runs WHERE tenant_id = $1)
+             ORDER BY dead_lettered_at DESC LIMIT $2",
+        )
+        .bind(tenant_id)
+        .bind(limit.min(10_000) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::parse_delivery).collect()
     }
 }
 
@@ -362,14 +1298,16 @@ impl FlowStorage for PostgresStorage {
     // Flow versioning methods
     async fn deploy_flow_version(
         &self,
-        flow_name: &str,
+        flow_name: &FlowName,
         version: &str,
         content: &str,
     ) -> Result<()> {
         let now = Utc::now();
 
-        // Parse flow to extract trigger topics
+        // Parse flow to extract trigger topics, ownership metadata and search fields
         let topics = extract_topics_from_flow_yaml(content);
+        let summary = extract_summary_from_flow_yaml(content);
+        let search_fields = search::index_flow(content);
 
         // Start transaction
         let mut tx = self.pool.begin().await?;
@@ -378,7 +1316,7 @@ impl FlowStorage for PostgresStorage {
         let exists = sqlx::query(
             "SELECT 1 FROM flow_versions WHERE flow_name = $1 AND version = $2 LIMIT 1",
         )
-        .bind(flow_name)
+        .bind(flow_name.as_str())
         .bind(version)
         .fetch_optional(&mut *tx)
         .await?;
@@ -392,16 +1330,31 @@ impl FlowStorage for PostgresStorage {
 
         // Save new version snapshot
         sqlx::query(
-            "INSERT INTO flow_versions (flow_name, version, content, deployed_at)
-            VALUES ($1, $2, $3, $4)",
+            "INSERT INTO flow_versions (flow_name, version, content, deployed_at, description, owner, team)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)",
         )
-        .bind(flow_name)
+        .bind(flow_name.as_str())
         .bind(version)
         .bind(content)
         .bind(now)
+        .bind(&summary.description)
+        .bind(&summary.owner)
+        .bind(&summary.team)
         .execute(&mut *tx)
         .await?;
 
+        for tag in &summary.tags {
+            sqlx::query(
+                "INSERT INTO flow_tags (flow_name, version, tag) VALUES ($1, $2, $3)
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(flow_name.as_str())
+            .bind(version)
+            .bind(tag)
+            .execute(&mut *tx)
+            .await?;
+        }
+
         // Update deployed version pointer
         sqlx::query(
             "INSERT INTO deployed_flows (flow_name, deployed_version, deployed_at)
@@ -410,7 +1363,7 @@ impl FlowStorage for PostgresStorage {
                 deployed_version = EXCLUDED.deployed_version,
                 deployed_at = EXCLUDED.deployed_at",
         )
-        .bind(flow_name)
+        .bind(flow_name.as_str())
         .bind(version)
         .bind(now)
         .execute(&mut *tx)
@@ -424,18 +1377,33 @@ impl FlowStorage for PostgresStorage {
                  VALUES ($1, $2, $3)
                  ON CONFLICT DO NOTHING",
             )
-            .bind(flow_name)
+            .bind(flow_name.as_str())
             .bind(version)
             .bind(&topic)
             .execute(&mut *tx)
             .await?;
         }
 
+        for field in search_fields {
+            sqlx::query(
+                "INSERT INTO flow_search (flow_name, version, field, weight, text)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(flow_name.as_str())
+            .bind(version)
+            .bind(&field.field)
+            .bind(field.weight)
+            .bind(&field.text)
+            .execute(&mut *tx)
+            .await?;
+        }
+
         tx.commit().await?;
         Ok(())
     }
 
-    async fn set_deployed_version(&self, flow_name: &str, version: &str) -> Result<()> {
+    async fn set_deployed_version(&self, flow_name: &FlowName, version: &str) -> Result<()> {
         let now = Utc::now();
 
         sqlx::query(
@@ -445,7 +1413,7 @@ impl FlowStorage for PostgresStorage {
                 deployed_version = EXCLUDED.deployed_version,
                 deployed_at = EXCLUDED.deployed_at",
         )
-        .bind(flow_name)
+        .bind(flow_name.as_str())
         .bind(version)
         .bind(now)
         .execute(&self.pool)
@@ -454,10 +1422,13 @@ impl FlowStorage for PostgresStorage {
         Ok(())
     }
 
-    async fn get_deployed_version(&self, flow_name: &str) -> Result<Option<String>> {
-        let row = sqlx::query("SELECT deployed_version FROM deployed_flows WHERE flow_name = $1")
-            .bind(flow_name)
-            .fetch_optional(&self.pool)
+    async fn get_deployed_version(&self, flow_name: &FlowName) -> Result<Option<String>> {
+        let row = self
+            .reconnecting(|| {
+                sqlx::query("SELECT deployed_version FROM deployed_flows WHERE flow_name = $1")
+                    .bind(flow_name.as_str())
+                    .fetch_optional(&self.pool)
+            })
             .await?;
 
         Ok(row.and_then(|r| r.try_get("deployed_version").ok()))
@@ -465,20 +1436,24 @@ impl FlowStorage for PostgresStorage {
 
     async fn get_flow_version_content(
         &self,
-        flow_name: &str,
+        flow_name: &FlowName,
         version: &str,
     ) -> Result<Option<String>> {
-        let row =
-            sqlx::query("SELECT content FROM flow_versions WHERE flow_name = $1 AND version = $2")
-                .bind(flow_name)
+        let row = self
+            .reconnecting(|| {
+                sqlx::query(
+                    "SELECT content FROM flow_versions WHERE flow_name = $1 AND version = $2",
+                )
+                .bind(flow_name.as_str())
                 .bind(version)
                 .fetch_optional(&self.pool)
-                .await?;
+            })
+            .await?;
 
         Ok(row.and_then(|r| r.try_get("content").ok()))
     }
 
-    async fn list_flow_versions(&self, flow_name: &str) -> Result<Vec<FlowSnapshot>> {
+    async fn list_flow_versions(&self, flow_name: &FlowName) -> Result<Vec<FlowSnapshot>> {
         let rows = sqlx::query(
             "SELECT v.version, v.deployed_at,
                 CASE WHEN d.deployed_version = v.version THEN true ELSE false END as is_live
@@ -487,7 +1462,7 @@ impl FlowStorage for PostgresStorage {
              WHERE v.flow_name = $1
              ORDER BY v.deployed_at DESC",
         )
-        .bind(flow_name)
+        .bind(flow_name.as_str())
         .fetch_all(&self.pool)
         .await?;
 
@@ -508,9 +1483,69 @@ impl FlowStorage for PostgresStorage {
         Ok(snapshots)
     }
 
+    async fn list_flow_versions_after(
+        &self,
+        flow_name: &FlowName,
+        limit: usize,
+        after: Option<&PageCursor<String>>,
+    ) -> Result<Vec<FlowSnapshot>> {
+        let capped_limit = limit.min(10_000);
+
+        let rows = match after {
+            Some(after) => {
+                sqlx::query(
+                    "SELECT v.version, v.deployed_at,
+                        CASE WHEN d.deployed_version = v.version THEN true ELSE false END as is_live
+                     FROM flow_versions v
+                     LEFT JOIN deployed_flows d ON v.flow_name = d.flow_name
+                     WHERE v.flow_name = $1 AND (v.deployed_at, v.version) < ($2, $3)
+                     ORDER BY v.deployed_at DESC, v.version DESC
+                     LIMIT $4",
+                )
+                .bind(flow_name.as_str())
+                .bind(after.at)
+                .bind(&after.key)
+                .bind(capped_limit as i64)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT v.version, v.deployed_at,
+                        CASE WHEN d.deployed_version = v.version THEN true ELSE false END as is_live
+                     FROM flow_versions v
+                     LEFT JOIN deployed_flows d ON v.flow_name = d.flow_name
+                     WHERE v.flow_name = $1
+                     ORDER BY v.deployed_at DESC, v.version DESC
+                     LIMIT $2",
+                )
+                .bind(flow_name.as_str())
+                .bind(capped_limit as i64)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        let mut snapshots = Vec::new();
+        for row in rows {
+            let version: String = row.try_get("version")?;
+            let deployed_at: DateTime<Utc> = row.try_get("deployed_at")?;
+            let is_live: bool = row.try_get("is_live")?;
+
+            snapshots.push(FlowSnapshot {
+                flow_name: flow_name.to_string(),
+                version,
+                deployed_at,
+                is_live,
+            });
+        }
+
+        Ok(snapshots)
+    }
+
     async fn get_latest_deployed_version_from_history(
         &self,
-        flow_name: &str,
+        flow_name: &FlowName,
     ) -> Result<Option<String>> {
         let row = sqlx::query(
             "SELECT version FROM flow_versions
@@ -518,21 +1553,45 @@ impl FlowStorage for PostgresStorage {
              ORDER BY deployed_at DESC, version DESC
              LIMIT 1",
         )
-        .bind(flow_name)
+        .bind(flow_name.as_str())
         .fetch_optional(&self.pool)
         .await?;
 
         Ok(row.and_then(|r| r.try_get("version").ok()))
     }
 
-    async fn unset_deployed_version(&self, flow_name: &str) -> Result<()> {
+    async fn unset_deployed_version(&self, flow_name: &FlowName) -> Result<()> {
         sqlx::query("DELETE FROM deployed_flows WHERE flow_name = $1")
-            .bind(flow_name)
+            .bind(flow_name.as_str())
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
+    async fn get_flow_tenant(&self, flow_name: &FlowName) -> Result<Option<String>> {
+        let row = sqlx::query("SELECT tenant_id FROM flow_tenants WHERE flow_name = $1")
+            .bind(flow_name.as_str())
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|row| row.try_get("tenant_id")).transpose()?)
+    }
+
+    async fn claim_flow_tenant(&self, flow_name: &FlowName, tenant_id: &str) -> Result<String> {
+        sqlx::query(
+            "INSERT INTO flow_tenants (flow_name, tenant_id) VALUES ($1, $2)
+             ON CONFLICT(flow_name) DO NOTHING",
+        )
+        .bind(flow_name.as_str())
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+        let row = sqlx::query("SELECT tenant_id FROM flow_tenants WHERE flow_name = $1")
+            .bind(flow_name.as_str())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row.try_get("tenant_id")?)
+    }
+
     async fn list_all_deployed_flows(&self) -> Result<Vec<(String, String)>> {
         let rows = sqlx::query(
             "SELECT d.flow_name, v.content
@@ -554,7 +1613,7 @@ impl FlowStorage for PostgresStorage {
         Ok(result)
     }
 
-    async fn find_flow_names_by_topic(&self, topic: &str) -> Result<Vec<String>> {
+    async fn find_flow_names_by_topic(&self, topic: &str) -> Result<Vec<FlowName>> {
         let rows = sqlx::query(
             "SELECT DISTINCT ft.flow_name
              FROM flow_triggers ft
@@ -568,9 +1627,202 @@ impl FlowStorage for PostgresStorage {
 
         Ok(rows
             .into_iter()
-            .filter_map(|row| row.try_get("flow_name").ok())
+            .filter_map(|row| row.try_get::<String, _>("flow_name").ok())
+            .filter_map(|name| FlowName::new(name).ok())
             .collect())
     }
+    async fn list_deployed_flow_summaries(&self, filter: &FlowFilter) -> Result<Vec<FlowSummary>> {
+        let rows = sqlx::query(
+            "SELECT d.flow_name, d.deployed_version, v.description, v.owner, v.team
+             FROM deployed_flows d
+             INNER JOIN flow_versions v
+               ON d.flow_name = v.flow_name
+               AND d.deployed_version = v.version
+             WHERE ($1::TEXT IS NULL OR v.owner = $1)
+               AND ($2::TEXT IS NULL OR v.team = $2)
+               AND ($3::TEXT IS NULL OR EXISTS (
+                   SELECT 1 FROM flow_tags t
+                   WHERE t.flow_name = d.flow_name AND t.version = d.deployed_version AND t.tag = $3
+               ))
+               AND ($4::TEXT IS NULL OR EXISTS (
+                   SELECT 1 FROM flow_tenants ft
+                   WHERE ft.flow_name = d.flow_name AND ft.tenant_id = $4
+               ))
+             ORDER BY d.flow_name",
+        )
+        .bind(&filter.owner)
+        .bind(&filter.team)
+        .bind(&filter.tag)
+        .bind(&filter.tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let tag_rows = sqlx::query(
+            "SELECT t.flow_name, t.tag
+             FROM flow_tags t
+             INNER JOIN deployed_flows d
+               ON t.flow_name = d.flow_name
+               AND t.version = d.deployed_version
+             ORDER BY t.flow_name, t.tag",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        for row in tag_rows {
+            tags.entry(row.try_get("flow_name")?)
+                .or_default()
+                .push(row.try_get("tag")?);
+        }
+
+        rows.into_iter()
+            .map(|row| {
+                let name: String = row.try_get("flow_name")?;
+                Ok(FlowSummary {
+                    tags: tags.remove(&name).unwrap_or_default(),
+                    name,
+                    description: row.try_get("description")?,
+                    owner: row.try_get("owner")?,
+                    team: row.try_get("team")?,
+                    deployed_version: Some(row.try_get("deployed_version")?),
+                })
+            })
+            .collect()
+    }
+
+    async fn search_flows(&self, query: &str, limit: usize) -> Result<Vec<FlowSearchResult>> {
+        let terms = search::search_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Rows holding a word starting with any term; rank keeps flows matching all
+        let ts_query = terms
+            .iter()
+            .map(|term| format!("{}:*", term))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        let rows = self
+            .reconnecting(|| {
+                sqlx::query(
+                    "SELECT s.flow_name, s.version, s.field, s.weight, s.text
+                     FROM flow_search s
+                     INNER JOIN deployed_flows d
+                        ON d.flow_name = s.flow_name AND d.deployed_version = s.version
+                     WHERE s.document @@ to_tsquery('simple', $1)",
+                )
+                .bind(&ts_query)
+                .fetch_all(&self.pool)
+            })
+            .await?;
+
+        let candidates = rows
+            .iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("flow_name")?,
+                    row.try_get("version")?,
+                    SearchField {
+                        field: row.try_get("field")?,
+                        text: row.try_get("text")?,
+                        weight: row.try_get("weight")?,
+                    },
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(search::rank(&terms, candidates, limit))
+    }
+}
+
+#[async_trait]
+impl OutboxStorage for PostgresStorage {
+    async fn commit(&self, batch: &WriteBatch) -> Result<Vec<OutboxEvent>> {
+        let mut tx = self.pool.begin().await?;
+        for run in &batch.runs {
+            let event = serde_json::to_value(&run.event)?;
+            let vars = serde_json::to_value(&run.vars)?;
+            let labels = labels_to_json(&run.labels);
+            let usage = run.usage.as_ref().map(serde_json::to_value).transpose()?;
+            Self::upsert_run(run, &event, &vars, &labels, &usage)
+                .execute(&mut *tx)
+                .await?;
+            if self.integrity_chain {
+                Self::chain_run(&mut tx, run).await?;
+            }
+        }
+        for step in &batch.steps {
+            let inputs = serde_json::to_value(&step.inputs)?;
+            let outputs = serde_json::to_value(&step.outputs)?;
+            Self::upsert_step(step, &inputs, &outputs)
+                .execute(&mut *tx)
+                .await?;
+            if self.integrity_chain {
+                Self::chain_step(&mut tx, step).await?;
+            }
+        }
+        let mut queued = Vec::with_capacity(batch.events.len());
+        for event in &batch.events {
+            queued.push(Self::enqueue_event(&mut tx, event).await?);
+        }
+        tx.commit().await?;
+
+        Ok(queued)
+    }
+
+    async fn list_unsent_events(&self, limit: usize) -> Result<Vec<OutboxEvent>> {
+        let rows = self
+            .reconnecting(|| {
+                sqlx::query(
+                    "SELECT id, topic, sequence, payload, source, correlation_id, created_at FROM event_outbox
+                     WHERE sent_at IS NULL
+                     ORDER BY position
+                     LIMIT $1",
+                )
+                .bind(limit as i64)
+                .fetch_all(&self.pool)
+            })
+            .await?;
+
+        rows.iter().map(Self::parse_outbox_event).collect()
+    }
+
+    async fn mark_events_sent(&self, ids: &[Uuid]) -> Result<()> {
+        let sent_at = Utc::now();
+        self.reconnecting(|| {
+            sqlx::query(
+                "UPDATE event_outbox SET sent_at = $1 WHERE id = ANY($2) AND sent_at IS NULL",
+            )
+            .bind(sent_at)
+            .bind(ids)
+            .execute(&self.pool)
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Counters locked by an in-flight `commit` are skipped, so a topic that is
+    /// being written to is never reset under its writer
+    async fn prune_sent_events(&self, sent_before: DateTime<Utc>) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let deleted =
+            sqlx::query("DELETE FROM event_outbox WHERE sent_at IS NOT NULL AND sent_at < $1")
+                .bind(sent_before)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        sqlx::query(
+            "DELETE FROM event_topic_sequences WHERE topic IN (
+                 SELECT topic FROM event_topic_sequences s
+                 WHERE NOT EXISTS (SELECT 1 FROM event_outbox o WHERE o.topic = s.topic)
+                 FOR UPDATE SKIP LOCKED
+             )",
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(deleted)
+    }
 }
 
 #[async_trait]
@@ -579,15 +1831,16 @@ impl OAuthStorage for PostgresStorage {
     async fn save_oauth_credential(&self, credential: &OAuthCredential) -> Result<()> {
         sqlx::query(
             "INSERT INTO oauth_credentials
-             (id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             (id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at, tenant_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
              ON CONFLICT(provider, integration) DO UPDATE SET
                 id = EXCLUDED.id,
                 access_token = EXCLUDED.access_token,
                 refresh_token = EXCLUDED.refresh_token,
                 expires_at = EXCLUDED.expires_at,
                 scope = EXCLUDED.scope,
-                updated_at = EXCLUDED.updated_at"
+                updated_at = EXCLUDED.updated_at,
+                tenant_id = EXCLUDED.tenant_id"
         )
         .bind(&credential.id)
         .bind(&credential.provider)
@@ -598,6 +1851,7 @@ impl OAuthStorage for PostgresStorage {
         .bind(&credential.scope)
         .bind(credential.created_at)
         .bind(Utc::now())
+        .bind(&credential.tenant_id)
         .execute(&self.pool)
         .await?;
 
@@ -610,7 +1864,7 @@ impl OAuthStorage for PostgresStorage {
         integration: &str,
     ) -> Result<Option<OAuthCredential>> {
         let row = sqlx::query(
-            "SELECT id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at
+            "SELECT id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at, tenant_id
              FROM oauth_credentials
              WHERE provider = $1 AND integration = $2"
         )
@@ -630,6 +1884,7 @@ impl OAuthStorage for PostgresStorage {
                 scope: row.try_get("scope")?,
                 created_at: row.try_get("created_at")?,
                 updated_at: row.try_get("updated_at")?,
+                tenant_id: row.try_get("tenant_id")?,
             })),
             None => Ok(None),
         }
@@ -637,7 +1892,7 @@ impl OAuthStorage for PostgresStorage {
 
     async fn list_oauth_credentials(&self) -> Result<Vec<OAuthCredential>> {
         let rows = sqlx::query(
-            "SELECT id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at
+            "SELECT id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at, tenant_id
              FROM oauth_credentials
              ORDER BY created_at DESC"
         )
@@ -656,6 +1911,7 @@ impl OAuthStorage for PostgresStorage {
                 scope: row.try_get("scope")?,
                 created_at: row.try_get("created_at")?,
                 updated_at: row.try_get("updated_at")?,
+                tenant_id: row.try_get("tenant_id")?,
             });
         }
 
@@ -675,6 +1931,19 @@ impl OAuthStorage for PostgresStorage {
         Ok(())
     }
 
+    async fn delete_expired_oauth_credentials(&self, now: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM oauth_credentials
+             WHERE expires_at IS NOT NULL AND expires_at < $1
+               AND (refresh_token IS NULL OR refresh_token = '')",
+        )
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     async fn refresh_oauth_credential(
         &self,
         id: &str,
@@ -815,8 +2084,8 @@ impl OAuthStorage for PostgresStorage {
 
         sqlx::query(
             "INSERT INTO oauth_clients
-             (id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             (id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at, tenant_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
              ON CONFLICT(id) DO UPDATE SET
                 secret = EXCLUDED.secret,
                 name = EXCLUDED.name,
@@ -824,7 +2093,8 @@ impl OAuthStorage for PostgresStorage {
                 grant_types = EXCLUDED.grant_types,
                 response_types = EXCLUDED.response_types,
                 scope = EXCLUDED.scope,
-                updated_at = EXCLUDED.updated_at"
+                updated_at = EXCLUDED.updated_at,
+                tenant_id = EXCLUDED.tenant_id"
         )
         .bind(&client.id)
         .bind(&client.secret)
@@ -835,6 +2105,7 @@ impl OAuthStorage for PostgresStorage {
         .bind(&client.scope)
         .bind(client.created_at)
         .bind(Utc::now())
+        .bind(&client.tenant_id)
         .execute(&self.pool)
         .await?;
 
@@ -843,7 +2114,7 @@ impl OAuthStorage for PostgresStorage {
 
     async fn get_oauth_client(&self, id: &str) -> Result<Option<OAuthClient>> {
         let row = sqlx::query(
-            "SELECT id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at
+            "SELECT id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at, tenant_id
              FROM oauth_clients
              WHERE id = $1"
         )
@@ -869,6 +2140,7 @@ impl OAuthStorage for PostgresStorage {
                     logo_uri: None,
                     created_at: row.try_get("created_at")?,
                     updated_at: row.try_get("updated_at")?,
+                    tenant_id: row.try_get("tenant_id")?,
                 }))
             }
             None => Ok(None),
@@ -877,7 +2149,7 @@ impl OAuthStorage for PostgresStorage {
 
     async fn list_oauth_clients(&self) -> Result<Vec<OAuthClient>> {
         let rows = sqlx::query(
-            "SELECT id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at
+            "SELECT id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at, tenant_id
              FROM oauth_clients
              ORDER BY created_at DESC"
         )
@@ -907,6 +2179,7 @@ impl OAuthStorage for PostgresStorage {
                     logo_uri: None,
                     created_at: row.try_get("created_at")?,
                     updated_at: row.try_get("updated_at")?,
+                    tenant_id: row.try_get("tenant_id")?,
                 });
             }
         }
@@ -0,0 +1,701 @@
+//! Remote storage driver
+//!
+//! [`RemoteStorage`] implements every storage trait by calling a storage service
+//! over JSON-over-HTTP, which lets operators plug in a custom backend without
+//! forking BeemFlow. Configure it with `storage: {driver: "remote", dsn: "https://..."}`,
+//! where the DSN is the service's base URL.
+//!
+//! The protocol lives in [`protocol`]; [`serve`] exposes any [`Storage`] over it.
+//! Connections are pooled, every call has a strict timeout, and read-only calls are
+//! retried on transport failures and `5xx` responses. Writes are never retried.
+//!
+//! [`Storage`]: crate::storage::Storage
+
+pub mod protocol;
+mod server;
+
+pub use server::serve;
+
+use self::protocol::*;
+use super::integrity::IntegrityReport;
+use super::search::FlowSearchResult;
+use super::{
+    FlowFilter, FlowRunStats, FlowSnapshot, FlowStorage, FlowSummary, OAuthStorage, OutboxStorage,
+    PageCursor, RunFilter, RunStorage, StateStorage, WriteBatch,
+};
+use crate::utils::retry::{RetryPolicy, with_backoff};
+use crate::{BeemFlowError, Result, model::*};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Timeout for a whole call, including reading the response
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Timeout for establishing a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Attempts made for idempotent calls
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubles on each further retry
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Storage backed by a remote storage service
+#[derive(Debug, Clone)]
+pub struct RemoteStorage {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl RemoteStorage {
+    /// Create a remote storage client for the service at `dsn`
+    ///
+    /// The DSN must be an `http` or `https` URL. No request is made until the
+    /// first storage call.
+    pub fn new(dsn: &str) -> Result<Self> {
+        let url = url::Url::parse(dsn)
+            .map_err(|e| BeemFlowError::config(format!("Invalid remote storage DSN: {}", e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(BeemFlowError::config(format!(
+                "Remote storage DSN must be an http(s) URL, got scheme '{}'",
+                url.scheme()
+            )));
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .build()
+            .map_err(|e| BeemFlowError::storage(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            base_url: dsn.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Perform a storage call, retrying idempotent calls on transient failures
+    async fn call<R: RemoteRequest>(&self, request: R) -> Result<R::Response> {
+        let policy = RetryPolicy {
+            max_attempts: if R::IDEMPOTENT { MAX_ATTEMPTS } else { 1 },
+            initial_backoff: RETRY_BACKOFF,
+            ..RetryPolicy::default()
+        };
+        with_backoff(
+            &policy,
+            |failure| matches!(failure, Failure::Transient(_)),
+            || self.send(&request),
+        )
+        .await
+        .map_err(|(Failure::Transient(e) | Failure::Permanent(e))| e)
+    }
+
+    async fn send<R: RemoteRequest>(
+        &self,
+        request: &R,
+    ) -> std::result::Result<R::Response, Failure> {
+        let url = format!("{}{}", self.base_url, R::PATH);
+        let response = self
+            .client
+            .post(&url)
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| {
+                Failure::Transient(BeemFlowError::storage(format!(
+                    "Remote storage request to {} failed: {}",
+                    url, e
+                )))
+            })?;
+
+        let status = response.status();
+        let body = response.bytes().await.map_err(|e| {
+            Failure::Transient(BeemFlowError::storage(format!(
+                "Failed to read remote storage response from {}: {}",
+                url, e
+            )))
+        })?;
+
+        if status.is_success() {
+            return serde_json::from_slice(&body).map_err(|e| {
+                Failure::Permanent(BeemFlowError::storage(format!(
+                    "Invalid remote storage response from {}: {}",
+                    url, e
+                )))
+            });
+        }
+
+        let err = match serde_json::from_slice::<RemoteError>(&body) {
+            Ok(RemoteError::Validation { message }) => BeemFlowError::validation(message),
+            Ok(RemoteError::NotFound { entity, id }) => BeemFlowError::not_found(entity, id),
+            Ok(RemoteError::Storage { message }) => BeemFlowError::storage(message),
+            Err(_) => BeemFlowError::storage(format!(
+                "Remote storage returned HTTP {} for {}",
+                status, url
+            )),
+        };
+
+        if status.is_server_error() {
+            Err(Failure::Transient(err))
+        } else {
+            Err(Failure::Permanent(err))
+        }
+    }
+}
+
+/// Outcome of a failed attempt
+enum Failure {
+    /// May succeed if retried (transport errors, `5xx`)
+    Transient(BeemFlowError),
+    /// Retrying cannot help (`4xx`, malformed responses)
+    Permanent(BeemFlowError),
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Transient(e) | Failure::Permanent(e) => e.fmt(f),
+        }
+    }
+}
+
+#[async_trait]
+impl RunStorage for RemoteStorage {
+    async fn save_run(&self, run: &Run) -> Result<()> {
+        self.call(SaveRun { run: run.clone() }).await
+    }
+
+    async fn get_run(&self, id: Uuid) -> Result<Option<Run>> {
+        self.call(GetRun { id }).await
+    }
+
+    async fn list_runs(&self, limit: usize, offset: usize) -> Result<Vec<Run>> {
+        self.call(ListRuns { limit, offset }).await
+    }
+
+    async fn list_runs_for_tenant(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Run>> {
+        self.call(ListRunsForTenant {
+            tenant_id: tenant_id.to_string(),
+            limit,
+            offset,
+        })
+        .await
+    }
+
+    async fn list_runs_after(
+        &self,
+        filter: &RunFilter,
+        limit: usize,
+        after: Option<&PageCursor<Uuid>>,
+    ) -> Result<Vec<Run>> {
+        self.call(ListRunsAfter {
+            filter: filter.clone(),
+            limit,
+            after: after.cloned(),
+        })
+        .await
+    }
+
+    async fn list_runs_by_flow_and_status(
+        &self,
+        flow_name: &str,
+        status: RunStatus,
+        exclude_id: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<Run>> {
+        self.call(ListRunsByFlowAndStatus {
+            flow_name: flow_name.to_string(),
+            status,
+            exclude_id,
+            limit,
+        })
+        .await
+    }
+
+    async fn delete_run(&self, id: Uuid) -> Result<()> {
+        self.call(DeleteRun { id }).await
+    }
+
+    async fn try_insert_run(&self, run: &Run) -> Result<bool> {
+        self.call(TryInsertRun { run: run.clone() }).await
+    }
+
+    async fn run_stats(
+        &self,
+        since: DateTime<Utc>,
+        flow_name: Option<&str>,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<FlowRunStats>> {
+        self.call(RunStats {
+            since,
+            flow_name: flow_name.map(str::to_string),
+            tenant_id: tenant_id.map(str::to_string),
+        })
+        .await
+    }
+
+    async fn save_step(&self, step: &StepRun) -> Result<()> {
+        self.call(SaveStep { step: step.clone() }).await
+    }
+
+    async fn get_steps(&self, run_id: Uuid) -> Result<Vec<StepRun>> {
+        self.call(GetSteps { run_id }).await
+    }
+
+    async fn verify_integrity(&self, flow_name: Option<&str>) -> Result<IntegrityReport> {
+        self.call(VerifyIntegrity {
+            flow_name: flow_name.map(str::to_string),
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl StateStorage for RemoteStorage {
+    async fn register_wait(&self, token: Uuid, wake_at: Option<i64>) -> Result<()> {
+        self.call(RegisterWait { token, wake_at }).await
+    }
+
+    async fn resolve_wait(&self, token: Uuid) -> Result<Option<Run>> {
+        self.call(ResolveWait { token }).await
+    }
+
+    async fn save_paused_run(
+        &self,
+        token: &str,
+        source: &str,
+        data: serde_json::Value,
+    ) -> Result<()> {
+        self.call(SavePausedRun {
+            token: token.to_string(),
+            source: source.to_string(),
+            data,
+        })
+        .await
+    }
+
+    async fn load_paused_runs(&self) -> Result<HashMap<String, serde_json::Value>> {
+        self.call(LoadPausedRuns {}).await
+    }
+
+    async fn find_paused_runs_by_source(
+        &self,
+        source: &str,
+    ) -> Result<Vec<(String, serde_json::Value)>> {
+        self.call(FindPausedRunsBySource {
+            source: source.to_string(),
+        })
+        .await
+    }
+
+    async fn delete_paused_run(&self, token: &str) -> Result<()> {
+        self.call(DeletePausedRun {
+            token: token.to_string(),
+        })
+        .await
+    }
+
+    async fn fetch_and_delete_paused_run(&self, token: &str) -> Result<Option<serde_json::Value>> {
+        self.call(FetchAndDeletePausedRun {
+            token: token.to_string(),
+        })
+        .await
+    }
+
+    async fn save_webhook_payload(&self, payload: &WebhookPayload, keep: usize) -> Result<()> {
+        self.call(SaveWebhookPayload {
+            payload: payload.clone(),
+            keep,
+        })
+        .await
+    }
+
+    async fn get_webhook_payload(&self, id: Uuid) -> Result<Option<WebhookPayload>> {
+        self.call(GetWebhookPayload { id }).await
+    }
+
+    async fn list_webhook_payloads(
+        &self,
+        provider: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<WebhookPayload>> {
+        self.call(ListWebhookPayloads {
+            provider: provider.map(str::to_string),
+            limit,
+        })
+        .await
+    }
+
+    async fn save_blob_record(&self, record: &BlobRecord) -> Result<()> {
+        self.call(SaveBlobRecord {
+            record: record.clone(),
+        })
+        .await
+    }
+
+    async fn get_blob_record(&self, id: Uuid) -> Result<Option<BlobRecord>> {
+        self.call(GetBlobRecord { id }).await
+    }
+
+    async fn list_blob_records(&self) -> Result<Vec<BlobRecord>> {
+        self.call(ListBlobRecords {}).await
+    }
+
+    async fn delete_blob_record(&self, id: Uuid) -> Result<()> {
+        self.call(DeleteBlobRecord { id }).await
+    }
+
+    async fn save_run_checkpoint(&self, run_id: Uuid, data: serde_json::Value) -> Result<()> {
+        self.call(SaveRunCheckpoint { run_id, data }).await
+    }
+
+    async fn load_run_checkpoints(&self) -> Result<Vec<(Uuid, serde_json::Value)>> {
+        self.call(LoadRunCheckpoints {}).await
+    }
+
+    async fn delete_run_checkpoint(&self, run_id: Uuid) -> Result<()> {
+        self.call(DeleteRunCheckpoint { run_id }).await
+    }
+
+    async fn save_delivery(&self, delivery: &OutboundDelivery) -> Result<()> {
+        self.call(SaveDelivery {
+            delivery: delivery.clone(),
+        })
+        .await
+    }
+
+    async fn list_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<OutboundDelivery>> {
+        self.call(ListDueDeliveries { now, limit }).await
+    }
+
+    async fn claim_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<OutboundDelivery>> {
+        self.call(ClaimDueDeliveries {
+            now,
+            lease_until,
+            limit,
+        })
+        .await
+    }
+
+    async fn delete_delivery(&self, id: Uuid) -> Result<()> {
+        self.call(DeleteDelivery { id }).await
+    }
+
+    async fn dead_letter_delivery(&self, delivery: &OutboundDelivery) -> Result<()> {
+        self.call(DeadLetterDelivery {
+            delivery: delivery.clone(),
+        })
+        .await
+    }
+
+    async fn list_dead_letters(
+        &self,
+        limit: usize,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<OutboundDelivery>> {
+        self.call(ListDeadLetters {
+            limit,
+            tenant_id: tenant_id.map(str::to_string),
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl FlowStorage for RemoteStorage {
+    async fn deploy_flow_version(
+        &self,
+        flow_name: &FlowName,
+        version: &str,
+        content: &str,
+    ) -> Result<()> {
+        self.call(DeployFlowVersion {
+            flow_name: flow_name.clone(),
+            version: version.to_string(),
+            content: content.to_string(),
+        })
+        .await
+    }
+
+    async fn set_deployed_version(&self, flow_name: &FlowName, version: &str) -> Result<()> {
+        self.call(SetDeployedVersion {
+            flow_name: flow_name.clone(),
+            version: version.to_string(),
+        })
+        .await
+    }
+
+    async fn get_deployed_version(&self, flow_name: &FlowName) -> Result<Option<String>> {
+        self.call(GetDeployedVersion {
+            flow_name: flow_name.clone(),
+        })
+        .await
+    }
+
+    async fn get_flow_version_content(
+        &self,
+        flow_name: &FlowName,
+        version: &str,
+    ) -> Result<Option<String>> {
+        self.call(GetFlowVersionContent {
+            flow_name: flow_name.clone(),
+            version: version.to_string(),
+        })
+        .await
+    }
+
+    async fn list_flow_versions(&self, flow_name: &FlowName) -> Result<Vec<FlowSnapshot>> {
+        self.call(ListFlowVersions {
+            flow_name: flow_name.clone(),
+        })
+        .await
+    }
+
+    async fn list_flow_versions_after(
+        &self,
+        flow_name: &FlowName,
+        limit: usize,
+        after: Option<&PageCursor<String>>,
+    ) -> Result<Vec<FlowSnapshot>> {
+        self.call(ListFlowVersionsAfter {
+            flow_name: flow_name.clone(),
+            limit,
+            after: after.cloned(),
+        })
+        .await
+    }
+
+    async fn get_latest_deployed_version_from_history(
+        &self,
+        flow_name: &FlowName,
+    ) -> Result<Option<String>> {
+        self.call(GetLatestDeployedVersionFromHistory {
+            flow_name: flow_name.clone(),
+        })
+        .await
+    }
+
+    async fn unset_deployed_version(&self, flow_name: &FlowName) -> Result<()> {
+        self.call(UnsetDeployedVersion {
+            flow_name: flow_name.clone(),
+        })
+        .await
+    }
+
+    async fn get_flow_tenant(&self, flow_name: &FlowName) -> Result<Option<String>> {
+        self.call(GetFlowTenant {
+            flow_name: flow_name.clone(),
+        })
+        .await
+    }
+
+    async fn claim_flow_tenant(&self, flow_name: &FlowName, tenant_id: &str) -> Result<String> {
+        self.call(ClaimFlowTenant {
+            flow_name: flow_name.clone(),
+            tenant_id: tenant_id.to_string(),
+        })
+        .await
+    }
+
+    async fn list_all_deployed_flows(&self) -> Result<Vec<(String, String)>> {
+        self.call(ListAllDeployedFlows {}).await
+    }
+
+    async fn list_deployed_flow_summaries(&self, filter: &FlowFilter) -> Result<Vec<FlowSummary>> {
+        self.call(ListDeployedFlowSummaries {
+            filter: filter.clone(),
+        })
+        .await
+    }
+
+    async fn search_flows(&self, query: &str, limit: usize) -> Result<Vec<FlowSearchResult>> {
+        self.call(SearchFlows {
+            query: query.to_string(),
+            limit,
+        })
+        .await
+    }
+
+    async fn find_flow_names_by_topic(&self, topic: &str) -> Result<Vec<FlowName>> {
+        self.call(FindFlowNamesByTopic {
+            topic: topic.to_string(),
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl OutboxStorage for RemoteStorage {
+    async fn commit(&self, batch: &WriteBatch) -> Result<Vec<OutboxEvent>> {
+        self.call(Commit {
+            batch: batch.clone(),
+        })
+        .await
+    }
+
+    async fn list_unsent_events(&self, limit: usize) -> Result<Vec<OutboxEvent>> {
+        self.call(ListUnsentEvents { limit }).await
+    }
+
+    async fn mark_events_sent(&self, ids: &[Uuid]) -> Result<()> {
+        self.call(MarkEventsSent { ids: ids.to_vec() }).await
+    }
+
+    async fn prune_sent_events(&self, sent_before: DateTime<Utc>) -> Result<u64> {
+        self.call(PruneSentEvents { sent_before }).await
+    }
+}
+
+#[async_trait]
+impl OAuthStorage for RemoteStorage {
+    async fn save_oauth_credential(&self, credential: &OAuthCredential) -> Result<()> {
+        self.call(SaveOAuthCredential {
+            credential: credential.clone(),
+        })
+        .await
+    }
+
+    async fn get_oauth_credential(
+        &self,
+        provider: &str,
+        integration: &str,
+    ) -> Result<Option<OAuthCredential>> {
+        self.call(GetOAuthCredential {
+            provider: provider.to_string(),
+            integration: integration.to_string(),
+        })
+        .await
+    }
+
+    async fn list_oauth_credentials(&self) -> Result<Vec<OAuthCredential>> {
+        self.call(ListOAuthCredentials {}).await
+    }
+
+    async fn delete_oauth_credential(&self, id: &str) -> Result<()> {
+        self.call(DeleteOAuthCredential { id: id.to_string() })
+            .await
+    }
+
+    async fn delete_expired_oauth_credentials(&self, now: DateTime<Utc>) -> Result<u64> {
+        self.call(DeleteExpiredOAuthCredentials { now }).await
+    }
+
+    async fn refresh_oauth_credential(
+        &self,
+        id: &str,
+        new_token: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        self.call(RefreshOAuthCredential {
+            id: id.to_string(),
+            new_token: new_token.to_string(),
+            expires_at,
+        })
+        .await
+    }
+
+    async fn save_oauth_provider(&self, provider: &OAuthProvider) -> Result<()> {
+        self.call(SaveOAuthProvider {
+            provider: provider.clone(),
+        })
+        .await
+    }
+
+    async fn get_oauth_provider(&self, id: &str) -> Result<Option<OAuthProvider>> {
+        self.call(GetOAuthProvider { id: id.to_string() }).await
+    }
+
+    async fn list_oauth_providers(&self) -> Result<Vec<OAuthProvider>> {
+        self.call(ListOAuthProviders {}).await
+    }
+
+    async fn delete_oauth_provider(&self, id: &str) -> Result<()> {
+        self.call(DeleteOAuthProvider { id: id.to_string() }).await
+    }
+
+    async fn save_oauth_client(&self, client: &OAuthClient) -> Result<()> {
+        self.call(SaveOAuthClient {
+            client: client.clone(),
+        })
+        .await
+    }
+
+    async fn get_oauth_client(&self, id: &str) -> Result<Option<OAuthClient>> {
+        self.call(GetOAuthClient { id: id.to_string() }).await
+    }
+
+    async fn list_oauth_clients(&self) -> Result<Vec<OAuthClient>> {
+        self.call(ListOAuthClients {}).await
+    }
+
+    async fn delete_oauth_client(&self, id: &str) -> Result<()> {
+        self.call(DeleteOAuthClient { id: id.to_string() }).await
+    }
+
+    async fn save_oauth_token(&self, token: &OAuthToken) -> Result<()> {
+        self.call(SaveOAuthToken {
+            token: token.clone(),
+        })
+        .await
+    }
+
+    async fn get_oauth_token_by_code(&self, code: &str) -> Result<Option<OAuthToken>> {
+        self.call(GetOAuthTokenByCode {
+            code: code.to_string(),
+        })
+        .await
+    }
+
+    async fn get_oauth_token_by_access(&self, access: &str) -> Result<Option<OAuthToken>> {
+        self.call(GetOAuthTokenByAccess {
+            access: access.to_string(),
+        })
+        .await
+    }
+
+    async fn get_oauth_token_by_refresh(&self, refresh: &str) -> Result<Option<OAuthToken>> {
+        self.call(GetOAuthTokenByRefresh {
+            refresh: refresh.to_string(),
+        })
+        .await
+    }
+
+    async fn delete_oauth_token_by_code(&self, code: &str) -> Result<()> {
+        self.call(DeleteOAuthTokenByCode {
+            code: code.to_string(),
+        })
+        .await
+    }
+
+    async fn delete_oauth_token_by_access(&self, access: &str) -> Result<()> {
+        self.call(DeleteOAuthTokenByAccess {
+            access: access.to_string(),
+        })
+        .await
+    }
+
+    async fn delete_oauth_token_by_refresh(&self, refresh: &str) -> Result<()> {
+        self.call(DeleteOAuthTokenByRefresh {
+            refresh: refresh.to_string(),
+        })
+        .await
+    }
+}
@@ -0,0 +1,343 @@
+//! Wire protocol for the remote storage driver
+//!
+//! Every storage trait method maps to one `POST` endpoint. The request body is the
+//! method's arguments as a JSON object and a successful (`200`) response body is the
+//! method's return value. Failures carry a [`RemoteError`] with a `4xx`/`5xx` status.
+
+use crate::model::*;
+use crate::storage::integrity::IntegrityReport;
+use crate::storage::search::FlowSearchResult;
+use crate::storage::{
+    FlowFilter, FlowRunStats, FlowSnapshot, FlowSummary, PageCursor, RunFilter, WriteBatch,
+};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A storage call: its endpoint path, response type, and retry safety
+pub trait RemoteRequest: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// Endpoint path, relative to the driver's base URL
+    const PATH: &'static str;
+
+    /// Whether the call only reads, so a failed attempt can safely be retried
+    const IDEMPOTENT: bool;
+
+    /// Value returned by the storage method
+    type Response: Serialize + DeserializeOwned + Send + 'static;
+}
+
+/// Error returned by the server when a storage call fails
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RemoteError {
+    /// Invalid input (`400`)
+    Validation { message: String },
+    /// A referenced record does not exist (`404`)
+    NotFound { entity: String, id: String },
+    /// Any other backend failure (`500`)
+    Storage { message: String },
+}
+
+macro_rules! requests {
+    ($(
+        $(#[$doc:meta])*
+        $name:ident => $path:literal, $response:ty, idempotent = $idempotent:literal {
+            $($field:ident: $ty:ty),* $(,)?
+        }
+    )*) => {$(
+        $(#[$doc])*
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct $name {
+            $(pub $field: $ty,)*
+        }
+
+        impl RemoteRequest for $name {
+            const PATH: &'static str = $path;
+            const IDEMPOTENT: bool = $idempotent;
+            type Response = $response;
+        }
+    )*};
+}
+
+requests! {
+    // RunStorage
+
+    /// [`RunStorage::save_run`](crate::storage::RunStorage::save_run)
+    SaveRun => "/runs/save_run", (), idempotent = false { run: Run }
+    /// [`RunStorage::get_run`](crate::storage::RunStorage::get_run)
+    GetRun => "/runs/get_run", Option<Run>, idempotent = true { id: Uuid }
+    /// [`RunStorage::list_runs`](crate::storage::RunStorage::list_runs)
+    ListRuns => "/runs/list_runs", Vec<Run>, idempotent = true { limit: usize, offset: usize }
+    /// [`RunStorage::list_runs_for_tenant`](crate::storage::RunStorage::list_runs_for_tenant)
+    ListRunsForTenant => "/runs/list_runs_for_tenant", Vec<Run>, idempotent = true {
+        tenant_id: String,
+        limit: usize,
+        offset: usize,
+    }
+    /// [`RunStorage::list_runs_after`](crate::storage::RunStorage::list_runs_after)
+    ListRunsAfter => "/runs/list_runs_after", Vec<Run>, idempotent = true {
+        filter: RunFilter,
+        limit: usize,
+        after: Option<PageCursor<Uuid>>,
+    }
+    /// [`RunStorage::list_runs_by_flow_and_status`](crate::storage::RunStorage::list_runs_by_flow_and_status)
+    ListRunsByFlowAndStatus => "/runs/list_runs_by_flow_and_status", Vec<Run>, idempotent = true {
+        flow_name: String,
+        status: RunStatus,
+        exclude_id: Option<Uuid>,
+        limit: usize,
+    }
+    /// [`RunStorage::delete_run`](crate::storage::RunStorage::delete_run)
+    DeleteRun => "/runs/delete_run", (), idempotent = false { id: Uuid }
+    /// [`RunStorage::try_insert_run`](crate::storage::RunStorage::try_insert_run)
+    TryInsertRun => "/runs/try_insert_run", bool, idempotent = false { run: Run }
+    /// [`RunStorage::run_stats`](crate::storage::RunStorage::run_stats)
+    RunStats => "/runs/run_stats", Vec<FlowRunStats>, idempotent = true {
+        since: DateTime<Utc>,
+        flow_name: Option<String>,
+        tenant_id: Option<String>,
+    }
+    /// [`RunStorage::save_step`](crate::storage::RunStorage::save_step)
+    SaveStep => "/runs/save_step", (), idempotent = false { step: StepRun }
+    /// [`RunStorage::get_steps`](crate::storage::RunStorage::get_steps)
+    GetSteps => "/runs/get_steps", Vec<StepRun>, idempotent = true { run_id: Uuid }
+    /// [`RunStorage::verify_integrity`](crate::storage::RunStorage::verify_integrity)
+    VerifyIntegrity => "/runs/verify_integrity", IntegrityReport, idempotent = true {
+        flow_name: Option<String>,
+    }
+
+    // StateStorage
+
+    /// [`StateStorage::register_wait`](crate::storage::StateStorage::register_wait)
+    RegisterWait => "/state/register_wait", (), idempotent = false {
+        token: Uuid,
+        wake_at: Option<i64>,
+    }
+    /// [`StateStorage::resolve_wait`](crate::storage::StateStorage::resolve_wait)
+    ResolveWait => "/state/resolve_wait", Option<Run>, idempotent = false { token: Uuid }
+    /// [`StateStorage::save_paused_run`](crate::storage::StateStorage::save_paused_run)
+    SavePausedRun => "/state/save_paused_run", (), idempotent = false {
+        token: String,
+        source: String,
+        data: serde_json::Value,
+    }
+    /// [`StateStorage::load_paused_runs`](crate::storage::StateStorage::load_paused_runs)
+    LoadPausedRuns => "/state/load_paused_runs", HashMap<String, serde_json::Value>, idempotent = true {}
+    /// [`StateStorage::find_paused_runs_by_source`](crate::storage::StateStorage::find_paused_runs_by_source)
+    FindPausedRunsBySource => "/state/find_paused_runs_by_source", Vec<(String, serde_json::Value)>, idempotent = true {
+        source: String,
+    }
+    /// [`StateStorage::delete_paused_run`](crate::storage::StateStorage::delete_paused_run)
+    DeletePausedRun => "/state/delete_paused_run", (), idempotent = false { token: String }
+    /// [`StateStorage::fetch_and_delete_paused_run`](crate::storage::StateStorage::fetch_and_delete_paused_run)
+    FetchAndDeletePausedRun => "/state/fetch_and_delete_paused_run", Option<serde_json::Value>, idempotent = false {
+        token: String,
+    }
+    /// [`StateStorage::save_webhook_payload`](crate::storage::StateStorage::save_webhook_payload)
+    SaveWebhookPayload => "/state/save_webhook_payload", (), idempotent = false {
+        payload: WebhookPayload,
+        keep: usize,
+    }
+    /// [`StateStorage::get_webhook_payload`](crate::storage::StateStorage::get_webhook_payload)
+    GetWebhookPayload => "/state/get_webhook_payload", Option<WebhookPayload>, idempotent = true { id: Uuid }
+    /// [`StateStorage::list_webhook_payloads`](crate::storage::StateStorage::list_webhook_payloads)
+    ListWebhookPayloads => "/state/list_webhook_payloads", Vec<WebhookPayload>, idempotent = true {
+        provider: Option<String>,
+        limit: usize,
+    }
+    /// [`StateStorage::save_blob_record`](crate::storage::StateStorage::save_blob_record)
+    SaveBlobRecord => "/state/save_blob_record", (), idempotent = false { record: BlobRecord }
+    /// [`StateStorage::get_blob_record`](crate::storage::StateStorage::get_blob_record)
+    GetBlobRecord => "/state/get_blob_record", Option<BlobRecord>, idempotent = true { id: Uuid }
+    /// [`StateStorage::list_blob_records`](crate::storage::StateStorage::list_blob_records)
+    ListBlobRecords => "/state/list_blob_records", Vec<BlobRecord>, idempotent = true {}
+    /// [`StateStorage::delete_blob_record`](crate::storage::StateStorage::delete_blob_record)
+    DeleteBlobRecord => "/state/delete_blob_record", (), idempotent = false { id: Uuid }
+    /// [`StateStorage::save_run_checkpoint`](crate::storage::StateStorage::save_run_checkpoint)
+    SaveRunCheckpoint => "/state/save_run_checkpoint", (), idempotent = true {
+        run_id: Uuid,
+        data: serde_json::Value,
+    }
+    /// [`StateStorage::load_run_checkpoints`](crate::storage::StateStorage::load_run_checkpoints)
+    LoadRunCheckpoints => "/state/load_run_checkpoints", Vec<(Uuid, serde_json::Value)>, idempotent = true {}
+    /// [`StateStorage::delete_run_checkpoint`](crate::storage::StateStorage::delete_run_checkpoint)
+    DeleteRunCheckpoint => "/state/delete_run_checkpoint", (), idempotent = true { run_id: Uuid }
+    /// [`StateStorage::save_delivery`](crate::storage::StateStorage::save_delivery)
+    SaveDelivery => "/state/save_delivery", (), idempotent = true { delivery: OutboundDelivery }
+    /// [`StateStorage::list_due_deliveries`](crate::storage::StateStorage::list_due_deliveries)
+    ListDueDeliveries => "/state/list_due_deliveries", Vec<OutboundDelivery>, idempotent = true {
+        now: DateTime<Utc>,
+        limit: usize,
+    }
+    /// [`StateStorage::claim_due_deliveries`](crate::storage::StateStorage::claim_due_deliveries)
+    ClaimDueDeliveries => "/state/claim_due_deliveries", Vec<OutboundDelivery>, idempotent = false {
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    }
+    /// [`StateStorage::delete_delivery`](crate::storage::StateStorage::delete_delivery)
+    DeleteDelivery => "/state/delete_delivery", (), idempotent = true { id: Uuid }
+    /// [`StateStorage::dead_letter_delivery`](crate::storage::StateStorage::dead_letter_delivery)
+    DeadLetterDelivery => "/state/dead_letter_delivery", (), idempotent = true {
+        delivery: OutboundDelivery,
+    }
+    /// [`StateStorage::list_dead_letters`](crate::storage::StateStorage::list_dead_letters)
+    ListDeadLetters => "/state/list_dead_letters", Vec<OutboundDelivery>, idempotent = true {
+        limit: usize,
+        tenant_id: Option<String>,
+    }
+
+    // FlowStorage
+
+    /// [`FlowStorage::deploy_flow_version`](crate::storage::FlowStorage::deploy_flow_version)
+    DeployFlowVersion => "/flows/deploy_flow_version", (), idempotent = false {
+        flow_name: FlowName,
+        version: String,
+        content: String,
+    }
+    /// [`FlowStorage::set_deployed_version`](crate::storage::FlowStorage::set_deployed_version)
+    SetDeployedVersion => "/flows/set_deployed_version", (), idempotent = false {
+        flow_name: FlowName,
+        version: String,
+    }
+    /// [`FlowStorage::get_deployed_version`](crate::storage::FlowStorage::get_deployed_version)
+    GetDeployedVersion => "/flows/get_deployed_version", Option<String>, idempotent = true {
+        flow_name: FlowName,
+    }
+    /// [`FlowStorage::get_flow_version_content`](crate::storage::FlowStorage::get_flow_version_content)
+    GetFlowVersionContent => "/flows/get_flow_version_content", Option<String>, idempotent = true {
+        flow_name: FlowName,
+        version: String,
+    }
+    /// [`FlowStorage::list_flow_versions`](crate::storage::FlowStorage::list_flow_versions)
+    ListFlowVersions => "/flows/list_flow_versions", Vec<FlowSnapshot>, idempotent = true {
+        flow_name: FlowName,
+    }
+    /// [`FlowStorage::list_flow_versions_after`](crate::storage::FlowStorage::list_flow_versions_after)
+    ListFlowVersionsAfter => "/flows/list_flow_versions_after", Vec<FlowSnapshot>, idempotent = true {
+        flow_name: FlowName,
+        limit: usize,
+        after: Option<PageCursor<String>>,
+    }
+    /// [`FlowStorage::get_latest_deployed_version_from_history`](crate::storage::FlowStorage::get_latest_deployed_version_from_history)
+    GetLatestDeployedVersionFromHistory => "/flows/get_latest_deployed_version_from_history", Option<String>, idempotent = true {
+        flow_name: FlowName,
+    }
+    /// [`FlowStorage::unset_deployed_version`](crate::storage::FlowStorage::unset_deployed_version)
+    UnsetDeployedVersion => "/flows/unset_deployed_version", (), idempotent = false {
+        flow_name: FlowName,
+    }
+    /// [`FlowStorage::get_flow_tenant`](crate::storage::FlowStorage::get_flow_tenant)
+    GetFlowTenant => "/flows/get_flow_tenant", Option<String>, idempotent = true {
+        flow_name: FlowName,
+    }
+    /// [`FlowStorage::claim_flow_tenant`](crate::storage::FlowStorage::claim_flow_tenant)
+    ClaimFlowTenant => "/flows/claim_flow_tenant", String, idempotent = false {
+        flow_name: FlowName,
+        tenant_id: String,
+    }
+    /// [`FlowStorage::list_all_deployed_flows`](crate::storage::FlowStorage::list_all_deployed_flows)
+    ListAllDeployedFlows => "/flows/list_all_deployed_flows", Vec<(String, String)>, idempotent = true {}
+    /// [`FlowStorage::list_deployed_flow_summaries`](crate::storage::FlowStorage::list_deployed_flow_summaries)
+    ListDeployedFlowSummaries => "/flows/list_deployed_flow_summaries", Vec<FlowSummary>, idempotent = true {
+        filter: FlowFilter,
+    }
+    /// [`FlowStorage::search_flows`](crate::storage::FlowStorage::search_flows)
+    SearchFlows => "/flows/search_flows", Vec<FlowSearchResult>, idempotent = true {
+        query: String,
+        limit: usize,
+    }
+    /// [`FlowStorage::find_flow_names_by_topic`](crate::storage::FlowStorage::find_flow_names_by_topic)
+    FindFlowNamesByTopic => "/flows/find_flow_names_by_topic", Vec<FlowName>, idempotent = true {
+        topic: String,
+    }
+
+    // OutboxStorage
+
+    /// [`OutboxStorage::commit`](crate::storage::OutboxStorage::commit)
+    Commit => "/outbox/commit", Vec<OutboxEvent>, idempotent = false {
+        batch: WriteBatch,
+    }
+    /// [`OutboxStorage::list_unsent_events`](crate::storage::OutboxStorage::list_unsent_events)
+    ListUnsentEvents => "/outbox/list_unsent_events", Vec<OutboxEvent>, idempotent = true {
+        limit: usize,
+    }
+    /// [`OutboxStorage::mark_events_sent`](crate::storage::OutboxStorage::mark_events_sent)
+    MarkEventsSent => "/outbox/mark_events_sent", (), idempotent = true {
+        ids: Vec<Uuid>,
+    }
+    /// [`OutboxStorage::prune_sent_events`](crate::storage::OutboxStorage::prune_sent_events)
+    PruneSentEvents => "/outbox/prune_sent_events", u64, idempotent = true {
+        sent_before: DateTime<Utc>,
+    }
+
+    // OAuthStorage
+
+    /// [`OAuthStorage::save_oauth_credential`](crate::storage::OAuthStorage::save_oauth_credential)
+    SaveOAuthCredential => "/oauth/save_oauth_credential", (), idempotent = false {
+        credential: OAuthCredential,
+    }
+    /// [`OAuthStorage::get_oauth_credential`](crate::storage::OAuthStorage::get_oauth_credential)
+    GetOAuthCredential => "/oauth/get_oauth_credential", Option<OAuthCredential>, idempotent = true {
+        provider: String,
+        integration: String,
+    }
+    /// [`OAuthStorage::list_oauth_credentials`](crate::storage::OAuthStorage::list_oauth_credentials)
+    ListOAuthCredentials => "/oauth/list_oauth_credentials", Vec<OAuthCredential>, idempotent = true {}
+    /// [`OAuthStorage::delete_oauth_credential`](crate::storage::OAuthStorage::delete_oauth_credential)
+    DeleteOAuthCredential => "/oauth/delete_oauth_credential", (), idempotent = false { id: String }
+    /// [`OAuthStorage::delete_expired_oauth_credentials`](crate::storage::OAuthStorage::delete_expired_oauth_credentials)
+    DeleteExpiredOAuthCredentials => "/oauth/delete_expired_oauth_credentials", u64, idempotent = false {
+        now: DateTime<Utc>,
+    }
+    /// [`OAuthStorage::refresh_oauth_credential`](crate::storage::OAuthStorage::refresh_oauth_credential)
+    RefreshOAuthCredential => "/oauth/refresh_oauth_credential", (), idempotent = false {
+        id: String,
+        new_token: String,
+        expires_at: Option<DateTime<Utc>>,
+    }
+    /// [`OAuthStorage::save_oauth_provider`](crate::storage::OAuthStorage::save_oauth_provider)
+    SaveOAuthProvider => "/oauth/save_oauth_provider", (), idempotent = false {
+        provider: OAuthProvider,
+    }
+    /// [`OAuthStorage::get_oauth_provider`](crate::storage::OAuthStorage::get_oauth_provider)
+    GetOAuthProvider => "/oauth/get_oauth_provider", Option<OAuthProvider>, idempotent = true { id: String }
+    /// [`OAuthStorage::list_oauth_providers`](crate::storage::OAuthStorage::list_oauth_providers)
+    ListOAuthProviders => "/oauth/list_oauth_providers", Vec<OAuthProvider>, idempotent = true {}
+    /// [`OAuthStorage::delete_oauth_provider`](crate::storage::OAuthStorage::delete_oauth_provider)
+    DeleteOAuthProvider => "/oauth/delete_oauth_provider", (), idempotent = false { id: String }
+    /// [`OAuthStorage::save_oauth_client`](crate::storage::OAuthStorage::save_oauth_client)
+    SaveOAuthClient => "/oauth/save_oauth_client", (), idempotent = false { client: OAuthClient }
+    /// [`OAuthStorage::get_oauth_client`](crate::storage::OAuthStorage::get_oauth_client)
+    GetOAuthClient => "/oauth/get_oauth_client", Option<OAuthClient>, idempotent = true { id: String }
+    /// [`OAuthStorage::list_oauth_clients`](crate::storage::OAuthStorage::list_oauth_clients)
+    ListOAuthClients => "/oauth/list_oauth_clients", Vec<OAuthClient>, idempotent = true {}
+    /// [`OAuthStorage::delete_oauth_client`](crate::storage::OAuthStorage::delete_oauth_client)
+    DeleteOAuthClient => "/oauth/delete_oauth_client", (), idempotent = false { id: String }
+    /// [`OAuthStorage::save_oauth_token`](crate::storage::OAuthStorage::save_oauth_token)
+    SaveOAuthToken => "/oauth/save_oauth_token", (), idempotent = false { token: OAuthToken }
+    /// [`OAuthStorage::get_oauth_token_by_code`](crate::storage::OAuthStorage::get_oauth_token_by_code)
+    GetOAuthTokenByCode => "/oauth/get_oauth_token_by_code", Option<OAuthToken>, idempotent = true {
+        code: String,
+    }
+    /// [`OAuthStorage::get_oauth_token_by_access`](crate::storage::OAuthStorage::get_oauth_token_by_access)
+    GetOAuthTokenByAccess => "/oauth/get_oauth_token_by_access", Option<OAuthToken>, idempotent = true {
+        access: String,
+    }
+    /// [`OAuthStorage::get_oauth_token_by_refresh`](crate::storage::OAuthStorage::get_oauth_token_by_refresh)
+    GetOAuthTokenByRefresh => "/oauth/get_oauth_token_by_refresh", Option<OAuthToken>, idempotent = true {
+        refresh: String,
+    }
+    /// [`OAuthStorage::delete_oauth_token_by_code`](crate::storage::OAuthStorage::delete_oauth_token_by_code)
+    DeleteOAuthTokenByCode => "/oauth/delete_oauth_token_by_code", (), idempotent = false { code: String }
+    /// [`OAuthStorage::delete_oauth_token_by_access`](crate::storage::OAuthStorage::delete_oauth_token_by_access)
+    DeleteOAuthTokenByAccess => "/oauth/delete_oauth_token_by_access", (), idempotent = false {
+        access: String,
+    }
+    /// [`OAuthStorage::delete_oauth_token_by_refresh`](crate::storage::OAuthStorage::delete_oauth_token_by_refresh)
+    DeleteOAuthTokenByRefresh => "/oauth/delete_oauth_token_by_refresh", (), idempotent = false {
+        refresh: String,
+    }
+}
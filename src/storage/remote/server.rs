@@ -0,0 +1,225 @@
+//! Server side of the remote storage protocol
+//!
+//! [`serve`] exposes any [`Storage`] implementation over the endpoints defined in
+//! [`protocol`](super::protocol), so one BeemFlow instance (or a standalone storage
+//! service) can back others.
+
+use super::protocol::*;
+use crate::storage::Storage;
+use crate::{BeemFlowError, error::StorageError};
+use axum::{
+    Json, Router,
+    extract::{DefaultBodyLimit, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use std::future::Future;
+use std::sync::Arc;
+
+/// Largest accepted request body (runs carry step inputs and outputs)
+const MAX_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+type StorageState = Arc<dyn Storage>;
+
+/// Build a router serving `storage` over the remote storage protocol
+///
+/// Mount it under any prefix and point clients at it with
+/// `storage: {driver: "remote", dsn: "<base url>"}`. The router applies no
+/// authentication; bind it to a private interface or put it behind a proxy.
+pub fn serve(storage: Arc<dyn Storage>) -> Router {
+    Routes(Router::new())
+        // RunStorage
+        .on(|s, r: SaveRun| async move { s.save_run(&r.run).await })
+        .on(|s, r: GetRun| async move { s.get_run(r.id).await })
+        .on(|s, r: ListRuns| async move { s.list_runs(r.limit, r.offset).await })
+        .on(|s, r: ListRunsForTenant| async move {
+            s.list_runs_for_tenant(&r.tenant_id, r.limit, r.offset).await
+        })
+        .on(|s, r: ListRunsAfter| async move { s.list_runs_after(&r.filter, r.limit, r.after.as_ref()).await })
+        .on(|s, r: ListRunsByFlowAndStatus| async move {
+            s.list_runs_by_flow_and_status(&r.flow_name, r.status, r.exclude_id, r.limit)
+                .await
+        })
+        .on(|s, r: DeleteRun| async move { s.delete_run(r.id).await })
+        .on(|s, r: TryInsertRun| async move { s.try_insert_run(&r.run).await })
+        .on(|s, r: RunStats| async move {
+            s.run_stats(r.since, r.flow_name.as_deref(), r.tenant_id.as_deref())
+                .await
+        })
+        .on(|s, r: SaveStep| async move { s.save_step(&r.step).await })
+        .on(|s, r: GetSteps| async move { s.get_steps(r.run_id).await })
+        .on(|s, r: VerifyIntegrity| async move { s.verify_integrity(r.flow_name.as_deref()).await })
+        // StateStorage
+        .on(|s, r: RegisterWait| async move { s.register_wait(r.token, r.wake_at).await })
+        .on(|s, r: ResolveWait| async move { s.resolve_wait(r.token).await })
+        .on(|s, r: SavePausedRun| async move {
+            s.save_paused_run(&r.token, &r.source, r.data).await
+        })
+        .on(|s, _: LoadPausedRuns| async move { s.load_paused_runs().await })
+        .on(|s, r: FindPausedRunsBySource| async move {
+            s.find_paused_runs_by_source(&r.source).await
+        })
+        .on(|s, r: DeletePausedRun| async move { s.delete_paused_run(&r.token).await })
+        .on(|s, r: FetchAndDeletePausedRun| async move {
+            s.fetch_and_delete_paused_run(&r.token).await
+        })
+        .on(|s, r: SaveWebhookPayload| async move {
+            s.save_webhook_payload(&r.payload, r.keep).await
+        })
+        .on(|s, r: GetWebhookPayload| async move { s.get_webhook_payload(r.id).await })
+        .on(|s, r: ListWebhookPayloads| async move {
+            s.list_webhook_payloads(r.provider.as_deref(), r.limit).await
+        })
+        .on(|s, r: SaveBlobRecord| async move { s.save_blob_record(&r.record).await })
+        .on(|s, r: GetBlobRecord| async move { s.get_blob_record(r.id).await })
+        .on(|s, _: ListBlobRecords| async move { s.list_blob_records().await })
+        .on(|s, r: DeleteBlobRecord| async move { s.delete_blob_record(r.id).await })
+        .on(|s, r: SaveRunCheckpoint| async move {
+            s.save_run_checkpoint(r.run_id, r.data).await
+        })
+        .on(|s, _: LoadRunCheckpoints| async move { s.load_run_checkpoints().await })
+        .on(|s, r: DeleteRunCheckpoint| async move { s.delete_run_checkpoint(r.run_id).await })
+        .on(|s, r: SaveDelivery| async move { s.save_delivery(&r.delivery).await })
+        .on(|s, r: ListDueDeliveries| async move { s.list_due_deliveries(r.now, r.limit).await })
+        .on(|s, r: ClaimDueDeliveries| async move {
+            s.claim_due_deliveries(r.now, r.lease_until, r.limit).await
+        })
+        .on(|s, r: DeleteDelivery| async move { s.delete_delivery(r.id).await })
+        .on(|s, r: DeadLetterDelivery| async move { s.dead_letter_delivery(&r.delivery).await })
+        .on(|s, r: ListDeadLetters| async move {
+            s.list_dead_letters(r.limit, r.tenant_id.as_deref()).await
+        })
+        // FlowStorage
+        .on(|s, r: DeployFlowVersion| async move {
+            s.deploy_flow_version(&r.flow_name, &r.version, &r.content)
+                .await
+        })
+        .on(|s, r: SetDeployedVersion| async move {
+            s.set_deployed_version(&r.flow_name, &r.version).await
+        })
+        .on(|s, r: GetDeployedVersion| async move { s.get_deployed_version(&r.flow_name).await })
+        .on(|s, r: GetFlowVersionContent| async move {
+            s.get_flow_version_content(&r.flow_name, &r.version).await
+        })
+        .on(|s, r: ListFlowVersions| async move { s.list_flow_versions(&r.flow_name).await })
+        .on(|s, r: ListFlowVersionsAfter| async move {
+            s.list_flow_versions_after(&r.flow_name, r.limit, r.after.as_ref())
+                .await
+        })
+        .on(|s, r: GetLatestDeployedVersionFromHistory| async move {
+            s.get_latest_deployed_version_from_history(&r.flow_name)
+                .await
+        })
+        .on(|s, r: UnsetDeployedVersion| async move {
+            s.unset_deployed_version(&r.flow_name).await
+        })
+        .on(|s, r: GetFlowTenant| async move { s.get_flow_tenant(&r.flow_name).await })
+        .on(|s, r: ClaimFlowTenant| async move {
+            s.claim_flow_tenant(&r.flow_name, &r.tenant_id).await
+        })
+        .on(|s, _: ListAllDeployedFlows| async move { s.list_all_deployed_flows().await })
+        .on(|s, r: ListDeployedFlowSummaries| async move {
+            s.list_deployed_flow_summaries(&r.filter).await
+        })
+        .on(|s, r: SearchFlows| async move { s.search_flows(&r.query, r.limit).await })
+        .on(|s, r: FindFlowNamesByTopic| async move {
+            s.find_flow_names_by_topic(&r.topic).await
+        })
+        // OutboxStorage
+        .on(|s, r: Commit| async move { s.commit(&r.batch).await })
+        .on(|s, r: ListUnsentEvents| async move { s.list_unsent_events(r.limit).await })
+        .on(|s, r: MarkEventsSent| async move { s.mark_events_sent(&r.ids).await })
+        .on(|s, r: PruneSentEvents| async move { s.prune_sent_events(r.sent_before).await })
+        // OAuthStorage
+        .on(|s, r: SaveOAuthCredential| async move {
+            s.save_oauth_credential(&r.credential).await
+        })
+        .on(|s, r: GetOAuthCredential| async move {
+            s.get_oauth_credential(&r.provider, &r.integration).await
+        })
+        .on(|s, _: ListOAuthCredentials| async move { s.list_oauth_credentials().await })
+        .on(|s, r: DeleteOAuthCredential| async move { s.delete_oauth_credential(&r.id).await })
+        .on(|s, r: DeleteExpiredOAuthCredentials| async move {
+            s.delete_expired_oauth_credentials(r.now).await
+        })
+        .on(|s, r: RefreshOAuthCredential| async move {
+            s.refresh_oauth_credential(&r.id, &r.new_token, r.expires_at)
+                .await
+        })
+        .on(|s, r: SaveOAuthProvider| async move { s.save_oauth_provider(&r.provider).await })
+        .on(|s, r: GetOAuthProvider| async move { s.get_oauth_provider(&r.id).await })
+        .on(|s, _: ListOAuthProviders| async move { s.list_oauth_providers().await })
+        .on(|s, r: DeleteOAuthProvider| async move { s.delete_oauth_provider(&r.id).await })
+        .on(|s, r: SaveOAuthClient| async move { s.save_oauth_client(&r.client).await })
+        .on(|s, r: GetOAuthClient| async move { s.get_oauth_client(&r.id).await })
+        .on(|s, _: ListOAuthClients| async move { s.list_oauth_clients().await })
+        .on(|s, r: DeleteOAuthClient| async move { s.delete_oauth_client(&r.id).await })
+        .on(|s, r: SaveOAuthToken| async move { s.save_oauth_token(&r.token).await })
+        .on(|s, r: GetOAuthTokenByCode| async move { s.get_oauth_token_by_code(&r.code).await })
+        .on(|s, r: GetOAuthTokenByAccess| async move {
+            s.get_oauth_token_by_access(&r.access).await
+        })
+        .on(|s, r: GetOAuthTokenByRefresh| async move {
+            s.get_oauth_token_by_refresh(&r.refresh).await
+        })
+        .on(|s, r: DeleteOAuthTokenByCode| async move {
+            s.delete_oauth_token_by_code(&r.code).await
+        })
+        .on(|s, r: DeleteOAuthTokenByAccess| async move {
+            s.delete_oauth_token_by_access(&r.access).await
+        })
+        .on(|s, r: DeleteOAuthTokenByRefresh| async move {
+            s.delete_oauth_token_by_refresh(&r.refresh).await
+        })
+        .0
+        .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
+        .with_state(storage)
+}
+
+/// Router builder that registers one endpoint per protocol request type
+struct Routes(Router<StorageState>);
+
+impl Routes {
+    fn on<R, F, Fut>(self, handler: F) -> Self
+    where
+        R: RemoteRequest,
+        F: Fn(StorageState, R) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = crate::Result<R::Response>> + Send + 'static,
+    {
+        let route = post(
+            move |State(storage): State<StorageState>, Json(request): Json<R>| async move {
+                match handler(storage, request).await {
+                    Ok(value) => Json(value).into_response(),
+                    Err(e) => error_response(e),
+                }
+            },
+        );
+        Self(self.0.route(R::PATH, route))
+    }
+}
+
+/// Map a storage error onto the protocol's error body and status
+fn error_response(err: BeemFlowError) -> Response {
+    let (status, body) = match err {
+        BeemFlowError::Validation(message) => {
+            (StatusCode::BAD_REQUEST, RemoteError::Validation { message })
+        }
+        BeemFlowError::Storage(StorageError::NotFound { entity, id }) => {
+            (StatusCode::NOT_FOUND, RemoteError::NotFound { entity, id })
+        }
+        BeemFlowError::Storage(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            RemoteError::Storage {
+                message: e.to_string(),
+            },
+        ),
+        other => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            RemoteError::Storage {
+                message: other.to_string(),
+            },
+        ),
+    };
+    (status, Json(body)).into_response()
+}
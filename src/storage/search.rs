@@ -0,0 +1,279 @@
+//! Full-text search over deployed flows (`search_flows`)
+//!
+//! At deploy time each flow version is broken into [`SearchField`]s: its name,
+//! description and tags, and for every step its id, its `use:` tool and the
+//! literal strings of its `with:` block. Backends index those fields (FTS5 on
+//! SQLite, `tsvector` on Postgres) and hand the candidate fields of deployed
+//! versions to [`rank`], which keeps the flows matching every query term and
+//! orders them so name and description matches come before step content.
+
+use crate::model::{Flow, Step};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Weight of a match on the flow name
+pub const WEIGHT_NAME: i64 = 100;
+/// Weight of a match on the description
+pub const WEIGHT_DESCRIPTION: i64 = 50;
+/// Weight of a match on a tag
+pub const WEIGHT_TAG: i64 = 30;
+/// Weight of a match on a step id or `use:` tool
+pub const WEIGHT_STEP: i64 = 10;
+/// Weight of a match on a literal inside a `with:` block
+pub const WEIGHT_WITH: i64 = 5;
+
+/// Characters kept on each side of the first match in a snippet
+const SNIPPET_CONTEXT: usize = 40;
+
+/// A searchable piece of a flow version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchField {
+    /// Path of the field in the flow, e.g. `steps.notify.with.channel`
+    pub field: String,
+    pub text: String,
+    pub weight: i64,
+}
+
+/// A field of a flow that matched the query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlowSearchMatch {
+    pub field: String,
+    /// The field's text around the match, query terms wrapped in `**`
+    pub snippet: String,
+}
+
+/// A deployed flow matching a search
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlowSearchResult {
+    pub flow_name: String,
+    /// Deployed version that matched
+    pub version: String,
+    /// Sum of the weights of the matching fields
+    pub score: i64,
+    /// Matching fields, highest weight first
+    pub matches: Vec<FlowSearchMatch>,
+}
+
+/// Lowercased alphanumeric terms of a query, without duplicates
+pub fn search_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+    {
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Searchable fields of flow content; none if it doesn't parse
+pub fn index_flow(content: &str) -> Vec<SearchField> {
+    crate::dsl::parse_string(content, None)
+        .map(|flow| flow_fields(&flow))
+        .unwrap_or_default()
+}
+
+/// Searchable fields of a parsed flow
+pub fn flow_fields(flow: &Flow) -> Vec<SearchField> {
+    let mut fields = vec![SearchField {
+        field: "name".to_string(),
+        text: flow.name.to_string(),
+        weight: WEIGHT_NAME,
+    }];
+    if let Some(ref description) = flow.description {
+        fields.push(SearchField {
+            field: "description".to_string(),
+            text: description.clone(),
+            weight: WEIGHT_DESCRIPTION,
+        });
+    }
+    for (i, tag) in flow.tags.iter().flatten().enumerate() {
+        fields.push(SearchField {
+            field: format!("tags[{}]", i),
+            text: tag.clone(),
+            weight: WEIGHT_TAG,
+        });
+    }
+
+    let sections = [
+        ("steps", Some(&flow.steps)),
+        ("catch", flow.catch.as_ref()),
+        ("on_success", flow.on_success.as_ref()),
+        ("on_failure", flow.on_failure.as_ref()),
+    ];
+    for (section, steps) in sections {
+        for step in steps.into_iter().flatten() {
+            step_fields(section, step, &mut fields);
+        }
+    }
+    fields
+}
+
+/// Fields of `step` and its nested steps, under `section`
+fn step_fields(section: &str, step: &Step, fields: &mut Vec<SearchField>) {
+    let path = format!("{}.{}", section, step.id);
+    fields.push(SearchField {
+        field: format!("{}.id", path),
+        text: step.id.to_string(),
+        weight: WEIGHT_STEP,
+    });
+    if let Some(ref tool) = step.use_ {
+        fields.push(SearchField {
+            field: format!("{}.use", path),
+            text: tool.clone(),
+            weight: WEIGHT_STEP,
+        });
+    }
+    if let Some(ref with) = step.with {
+        let with: BTreeMap<_, _> = with.iter().collect();
+        for (key, value) in with {
+            literal_fields(&format!("{}.with.{}", path, key), value, fields);
+        }
+    }
+    for nested in [&step.do_, &step.steps].into_iter().flatten().flatten() {
+        step_fields(section, nested, fields);
+    }
+}
+
+/// Literal strings of a `with:` value; templated strings are left out
+fn literal_fields(path: &str, value: &Value, fields: &mut Vec<SearchField>) {
+    match value {
+        Value::String(text) if !text.contains("{{") && !text.trim().is_empty() => {
+            fields.push(SearchField {
+                field: path.to_string(),
+                text: text.clone(),
+                weight: WEIGHT_WITH,
+            });
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                literal_fields(&format!("{}[{}]", path, i), item, fields);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                literal_fields(&format!("{}.{}", path, key), item, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Rank candidate fields of deployed flow versions against `terms`
+///
+/// Candidates are `(flow_name, version, field)`. A flow matches when every
+/// term occurs (case-insensitively) in at least one of its fields. Flows are
+/// ordered by their best matching field's weight, then by score, then by name,
+/// and the first `limit` are returned.
+pub fn rank(
+    terms: &[String],
+    candidates: impl IntoIterator<Item = (String, String, SearchField)>,
+    limit: usize,
+) -> Vec<FlowSearchResult> {
+    let mut by_flow: HashMap<(String, String), Vec<SearchField>> = HashMap::new();
+    for (flow_name, version, field) in candidates {
+        let text = field.text.to_lowercase();
+        if terms.iter().any(|term| text.contains(term.as_str())) {
+            by_flow.entry((flow_name, version)).or_default().push(field);
+        }
+    }
+
+    let mut results: Vec<(i64, FlowSearchResult)> = by_flow
+        .into_iter()
+        .filter(|(_, fields)| {
+            terms.iter().all(|term| {
+                fields
+                    .iter()
+                    .any(|field| field.text.to_lowercase().contains(term.as_str()))
+            })
+        })
+        .map(|((flow_name, version), mut fields)| {
+            fields.sort_by(|a, b| b.weight.cmp(&a.weight).then_with(|| a.field.cmp(&b.field)));
+            fields.dedup_by(|a, b| a.field == b.field);
+            let best = fields.first().map(|field| field.weight).unwrap_or_default();
+            let result = FlowSearchResult {
+                flow_name,
+                version,
+                score: fields.iter().map(|field| field.weight).sum(),
+                matches: fields
+                    .iter()
+                    .map(|field| FlowSearchMatch {
+                        field: field.field.clone(),
+                        snippet: snippet(&field.text, terms),
+                    })
+                    .collect(),
+            };
+            (best, result)
+        })
+        .collect();
+
+    results.sort_by(|(a_best, a), (b_best, b)| {
+        b_best
+            .cmp(a_best)
+            .then_with(|| b.score.cmp(&a.score))
+            .then_with(|| a.flow_name.cmp(&b.flow_name))
+    });
+    results
+        .into_iter()
+        .take(limit)
+        .map(|(_, result)| result)
+        .collect()
+}
+
+/// `text` around its first match, with every occurrence of a term wrapped in `**`
+pub fn snippet(text: &str, terms: &[String]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let term_chars: Vec<Vec<char>> = terms.iter().map(|term| term.chars().collect()).collect();
+
+    // Character ranges of every match, left to right, without overlaps
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < lower.len() {
+        let found = term_chars
+            .iter()
+            .filter(|term| !term.is_empty() && lower[i..].starts_with(term))
+            .map(Vec::len)
+            .max();
+        match found {
+            Some(len) => {
+                spans.push((i, i + len));
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+
+    let first = spans.first().map(|(start, _)| *start).unwrap_or(0);
+    let from = first.saturating_sub(SNIPPET_CONTEXT);
+    let to = (first + SNIPPET_CONTEXT * 2).min(chars.len());
+
+    let mut out = String::new();
+    if from > 0 {
+        out.push('…');
+    }
+    let mut pos = from;
+    for (start, end) in spans {
+        if end <= from || start >= to {
+            continue;
+        }
+        let (start, end) = (start.max(from), end.min(to));
+        out.extend(&chars[pos..start]);
+        out.push_str("**");
+        out.extend(&chars[start..end]);
+        out.push_str("**");
+        pos = end;
+    }
+    out.extend(&chars[pos..to]);
+    if to < chars.len() {
+        out.push('…');
+    }
+    out
+}
@@ -68,6 +68,16 @@ pub fn extract_topics_from_flow_yaml(content: &str) -> Vec<String> {
     }
 }
 
+/// Extract ownership metadata from flow YAML content for indexing
+///
+/// Called during deployment to populate the metadata columns of flow_versions and
+/// the flow_tags table. Returns an empty summary if the content does not parse.
+pub fn extract_summary_from_flow_yaml(content: &str) -> crate::storage::FlowSummary {
+    crate::dsl::parse_string(content, None)
+        .map(|flow| crate::storage::FlowSummary::of(&flow))
+        .unwrap_or_default()
+}
+
 // ============================================================================
 // Status Conversions (used by both backends)
 // ============================================================================
@@ -122,6 +132,23 @@ pub fn step_status_to_str(status: StepStatus) -> &'static str {
     }
 }
 
+/// Run labels as stored: a JSON object, or NULL for none
+pub fn labels_to_json(labels: &HashMap<String, String>) -> Option<serde_json::Value> {
+    (!labels.is_empty()).then(|| serde_json::json!(labels))
+}
+
+/// Sum each run's usage into the stats of its flow
+pub fn add_run_usage(
+    stats: &mut [crate::storage::FlowRunStats],
+    usages: impl IntoIterator<Item = (String, RunUsage)>,
+) {
+    for (flow_name, usage) in usages {
+        if let Some(flow) = stats.iter_mut().find(|s| s.flow_name == flow_name) {
+            flow.usage.add(&usage);
+        }
+    }
+}
+
 // ============================================================================
 // SQLite-specific Helpers
 // ============================================================================
@@ -139,3 +166,78 @@ pub fn parse_hashmap_from_jsonb(val: serde_json::Value) -> HashMap<String, serde
         .map(|m| m.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
         .unwrap_or_default()
 }
+
+// ============================================================================
+// Schema Version Check (used by both backends at startup)
+// ============================================================================
+
+/// Check the migrations recorded in the database against those embedded in the binary
+///
+/// `applied` and `dirty` come from the database's `_sqlx_migrations` table,
+/// `known` from the binary. Before migrating (`require_current = false`) an
+/// older schema is fine; afterwards it must be fully up to date. Errors say
+/// what to do instead of surfacing later as missing-column failures.
+pub fn check_schema_version(
+    backend: &str,
+    applied: &[i64],
+    dirty: Option<i64>,
+    known: &[i64],
+    require_current: bool,
+) -> crate::Result<()> {
+    let current = applied.iter().copied().max().unwrap_or(0);
+    let latest = known.iter().copied().max().unwrap_or(0);
+
+    if let Some(version) = dirty {
+        return Err(crate::BeemFlowError::storage(format!(
+            "{} migration {} failed partway through on an earlier start; repair the schema by hand, \
+             delete its row from _sqlx_migrations and restart",
+            backend, version
+        )));
+    }
+    if let Some(unknown) = applied.iter().filter(|v| !known.contains(v)).max() {
+        return Err(crate::BeemFlowError::storage(format!(
+            "{} schema has migration {} which this binary doesn't know (latest: {}); \
+             it was migrated by a newer BeemFlow, so upgrade BeemFlow or point storage.dsn \
+             at a database for this version",
+            backend, unknown, latest
+        )));
+    }
+    if require_current && current < latest {
+        return Err(crate::BeemFlowError::storage(format!(
+            "{} schema is at migration {} but this binary requires {}; \
+             restart with a database user allowed to apply migrations",
+            backend, current, latest
+        )));
+    }
+    Ok(())
+}
+
+/// Read the schema state of `conn` and check it with [`check_schema_version`]
+pub async fn verify_schema<C>(
+    conn: &mut C,
+    migrator: &sqlx::migrate::Migrator,
+    backend: &str,
+    require_current: bool,
+) -> crate::Result<()>
+where
+    C: sqlx::migrate::Migrate + ?Sized,
+{
+    let read_error = |e: sqlx::migrate::MigrateError| {
+        crate::BeemFlowError::storage(format!("Failed to read {} schema version: {}", backend, e))
+    };
+    conn.ensure_migrations_table().await.map_err(read_error)?;
+    let dirty = conn.dirty_version().await.map_err(read_error)?;
+    let applied: Vec<i64> = conn
+        .list_applied_migrations()
+        .await
+        .map_err(read_error)?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+    let known: Vec<i64> = migrator
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+        .map(|m| m.version)
+        .collect();
+    check_schema_version(backend, &applied, dirty, &known, require_current)
+}
@@ -3,13 +3,18 @@
 //! Provides persistent storage for flows, runs, steps, and OAuth data using SQLite.
 
 use crate::model::*;
+use crate::storage::integrity::{
+    self, ChainHead, HistoryEntry, IntegrityReport, RecordKind, chain_hash,
+};
+use crate::storage::search::{self, FlowSearchResult, SearchField};
 use crate::storage::{
-    FlowSnapshot, FlowStorage, OAuthStorage, RunStorage, StateStorage, sql_common::*,
+    FlowFilter, FlowRunStats, FlowSnapshot, FlowStorage, FlowSummary, OAuthStorage, OutboxStorage,
+    PageCursor, RunFilter, RunStorage, StateStorage, WriteBatch, sql_common::*,
 };
 use crate::{BeemFlowError, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use sqlx::{Row, SqlitePool, sqlite::SqliteRow};
+use sqlx::{Row, SqliteConnection, SqliteExecutor, SqlitePool, sqlite::SqliteRow};
 use std::collections::HashMap;
 use std::path::Path;
 use uuid::Uuid;
@@ -17,6 +22,7 @@ use uuid::Uuid;
 /// SQLite storage backend
 pub struct SqliteStorage {
     pool: SqlitePool,
+    integrity_chain: bool,
 }
 
 impl SqliteStorage {
@@ -39,13 +45,6 @@ impl SqliteStorage {
         // Extract actual file path for directory creation
         let file_path = dsn.strip_prefix("sqlite:").unwrap_or(dsn);
 
-        // Validate path to prevent directory traversal attacks
-        if file_path.contains("..") {
-            return Err(BeemFlowError::config(
-                "Database path cannot contain '..' (path traversal not allowed)",
-            ));
-        }
-
         // Create parent directory if needed (unless it's :memory:)
         if file_path != ":memory:"
             && let Some(parent) = Path::new(file_path).parent()
@@ -72,19 +71,252 @@ impl SqliteStorage {
             .execute(&pool)
             .await?;
 
-        // Run SQLite-specific migrations
-        sqlx::migrate!("./migrations/sqlite")
+        // Run SQLite-specific migrations, refusing schemas this binary can't
+        // migrate and confirming the schema is current afterwards
+        let migrator = sqlx::migrate!("./migrations/sqlite");
+        verify_schema(&mut *pool.acquire().await?, &migrator, "SQLite", false).await?;
+        migrator
             .run(&pool)
             .await
             .map_err(|e| BeemFlowError::storage(format!("Failed to run migrations: {}", e)))?;
+        verify_schema(&mut *pool.acquire().await?, &migrator, "SQLite", true).await?;
+
+        Ok(Self {
+            pool,
+            integrity_chain: false,
+        })
+    }
+
+    /// Chain every run and step write into the tamper-evident run history
+    ///
+    /// See [`integrity`]. Off by default; writes then skip the history entirely.
+    pub fn with_integrity_chain(mut self, enabled: bool) -> Self {
+        self.integrity_chain = enabled;
+        self
+    }
+
+    /// Upsert a run (shared by `save_run` and `commit`)
+    async fn upsert_run<'e, E: SqliteExecutor<'e>>(executor: E, run: &Run) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO runs (id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                flow_name = excluded.flow_name,
+                event = excluded.event,
+                vars = excluded.vars,
+                status = excluded.status,
+                started_at = excluded.started_at,
+                ended_at = excluded.ended_at,
+                environment = excluded.environment,
+                correlation_id = excluded.correlation_id,
+                labels = excluded.labels,
+                usage = excluded.usage,
+                tenant_id = excluded.tenant_id",
+        )
+        .bind(run.id.to_string())
+        .bind(run.flow_name.as_str())
+        .bind(serde_json::to_string(&run.event)?)
+        .bind(serde_json::to_string(&run.vars)?)
+        .bind(run_status_to_str(run.status))
+        .bind(run.started_at.timestamp())
+        .bind(run.ended_at.map(|dt| dt.timestamp()))
+        .bind(&run.environment)
+        .bind(&run.correlation_id)
+        .bind(labels_to_json(&run.labels).map(|labels| labels.to_string()))
+        .bind(run.usage.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(&run.tenant_id)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Upsert a step (shared by `save_step` and `commit`)
+    async fn upsert_step<'e, E: SqliteExecutor<'e>>(executor: E, step: &StepRun) -> Result<()> {
+        let progress = step.progress.as_ref();
+        sqlx::query(
+            "INSERT INTO steps (id, run_id, step_name, status, started_at, ended_at, inputs, outputs, error,
+                                error_kind, progress_percent, progress_message, progress_updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                run_id = excluded.run_id,
+                step_name = excluded.step_name,
+                status = excluded.status,
+                started_at = excluded.started_at,
+                ended_at = excluded.ended_at,
+                inputs = excluded.inputs,
+                outputs = excluded.outputs,
+                error = excluded.error,
+                error_kind = excluded.error_kind,
+                progress_percent = excluded.progress_percent,
+                progress_message = excluded.progress_message,
+                progress_updated_at = excluded.progress_updated_at"
+        )
+        .bind(step.id.to_string())
+        .bind(step.run_id.to_string())
+        .bind(step.step_name.as_str())
+        .bind(step_status_to_str(step.status))
+        .bind(step.started_at.timestamp())
+        .bind(step.ended_at.map(|dt| dt.timestamp()))
+        .bind(serde_json::to_string(&step.inputs)?)
+        .bind(serde_json::to_string(&step.outputs)?)
+        .bind(&step.error)
+        .bind(step.error_kind.map(|kind| kind.as_str()))
+        .bind(progress.and_then(|p| p.percent))
+        .bind(progress.and_then(|p| p.message.as_deref()))
+        .bind(progress.map(|p| p.updated_at.timestamp()))
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
 
-        Ok(Self { pool })
+    /// Append a run's state to its flow's history chain
+    async fn chain_run(conn: &mut SqliteConnection, run: &Run) -> Result<()> {
+        let content = integrity::run_content(run)?;
+        Self::append_history(
+            conn,
+            run.flow_name.as_str(),
+            RecordKind::Run,
+            run.id,
+            &content,
+        )
+        .await
+    }
+
+    /// Append a step's state to the history chain of its run's flow
+    async fn chain_step(conn: &mut SqliteConnection, step: &StepRun) -> Result<()> {
+        let flow_name: Option<String> =
+            sqlx::query_scalar("SELECT flow_name FROM runs WHERE id = ?")
+                .bind(step.run_id.to_string())
+                .fetch_optional(&mut *conn)
+                .await?;
+        let content = integrity::step_content(step)?;
+        Self::append_history(
+            conn,
+            &flow_name.unwrap_or_default(),
+            RecordKind::Step,
+            step.id,
+            &content,
+        )
+        .await
+    }
+
+    /// Append a history entry and stamp the record's row with its hash
+    ///
+    /// The head upsert takes the database write lock, so no other writer can
+    /// extend the chain until the transaction ends.
+    async fn append_history(
+        conn: &mut SqliteConnection,
+        flow_name: &str,
+        kind: RecordKind,
+        record_id: Uuid,
+        content: &str,
+    ) -> Result<()> {
+        let (sequence, prev_hash): (i64, String) = sqlx::query_as(
+            "INSERT INTO run_history_heads (flow_name, last_sequence, last_hash) VALUES (?, 1, '')
+             ON CONFLICT(flow_name) DO UPDATE SET last_sequence = last_sequence + 1
+             RETURNING last_sequence, last_hash",
+        )
+        .bind(flow_name)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let record_hash = chain_hash(&prev_hash, kind, content);
+        sqlx::query(
+            "INSERT INTO run_history (flow_name, sequence, record_kind, record_id, content, prev_hash, record_hash, recorded_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(flow_name)
+        .bind(sequence)
+        .bind(kind.as_str())
+        .bind(record_id.to_string())
+        .bind(content)
+        .bind(&prev_hash)
+        .bind(&record_hash)
+        .bind(Utc::now().timestamp())
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query("UPDATE run_history_heads SET last_hash = ? WHERE flow_name = ?")
+            .bind(&record_hash)
+            .bind(flow_name)
+            .execute(&mut *conn)
+            .await?;
+
+        let stamp = match kind {
+            RecordKind::Run => "UPDATE runs SET record_hash = ? WHERE id = ?",
+            RecordKind::Step => "UPDATE steps SET record_hash = ? WHERE id = ?",
+        };
+        sqlx::query(stamp)
+            .bind(&record_hash)
+            .bind(record_id.to_string())
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Enqueue an event with the next sequence number of its topic
+    async fn enqueue_event(
+        conn: &mut SqliteConnection,
+        event: &PendingEvent,
+    ) -> Result<OutboxEvent> {
+        let sequence: i64 = sqlx::query_scalar(
+            "INSERT INTO event_topic_sequences (topic, last_sequence) VALUES (?, 1)
+             ON CONFLICT(topic) DO UPDATE SET last_sequence = last_sequence + 1
+             RETURNING last_sequence",
+        )
+        .bind(&event.topic)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let queued = OutboxEvent {
+            id: Uuid::new_v4(),
+            topic: event.topic.clone(),
+            sequence,
+            payload: event.payload.clone(),
+            source: event.source.clone(),
+            correlation_id: event.correlation_id.clone(),
+            created_at: Utc::now(),
+        };
+        sqlx::query(
+            "INSERT INTO event_outbox (id, topic, sequence, payload, source, correlation_id, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(queued.id.to_string())
+        .bind(&queued.topic)
+        .bind(queued.sequence)
+        .bind(serde_json::to_string(&queued.payload)?)
+        .bind(serde_json::to_string(&queued.source)?)
+        .bind(&queued.correlation_id)
+        .bind(queued.created_at.timestamp())
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(queued)
+    }
+
+    fn parse_outbox_event(row: &SqliteRow) -> Result<OutboxEvent> {
+        Ok(OutboxEvent {
+            id: Uuid::parse_str(&row.try_get::<String, _>("id")?)?,
+            topic: row.try_get("topic")?,
+            sequence: row.try_get("sequence")?,
+            payload: serde_json::from_str(&row.try_get::<String, _>("payload")?)?,
+            source: row
+                .try_get::<Option<String>, _>("source")?
+                .map(|source| serde_json::from_str(&source))
+                .transpose()?
+                .unwrap_or_default(),
+            correlation_id: row.try_get("correlation_id")?,
+            created_at: DateTime::from_timestamp(row.try_get("created_at")?, 0)
+                .unwrap_or_else(Utc::now),
+        })
     }
 
     fn parse_run(row: &SqliteRow) -> Result<Run> {
         Ok(Run {
             id: Uuid::parse_str(&row.try_get::<String, _>("id")?)?,
-            flow_name: row.try_get::<String, _>("flow_name")?.into(),
+            flow_name: row.try_get::<String, _>("flow_name")?.try_into()?,
             event: serde_json::from_str(&row.try_get::<String, _>("event")?)?,
             vars: serde_json::from_str(&row.try_get::<String, _>("vars")?)?,
             status: parse_run_status(&row.try_get::<String, _>("status")?),
@@ -94,6 +326,73 @@ impl SqliteStorage {
                 .try_get::<Option<i64>, _>("ended_at")?
                 .map(|ts| DateTime::from_timestamp(ts, 0).unwrap_or_else(Utc::now)),
             steps: None,
+            environment: row.try_get("environment")?,
+            correlation_id: row.try_get("correlation_id")?,
+            labels: row
+                .try_get::<Option<String>, _>("labels")?
+                .map(|labels| serde_json::from_str(&labels))
+                .transpose()?
+                .unwrap_or_default(),
+            usage: row
+                .try_get::<Option<String>, _>("usage")?
+                .map(|usage| serde_json::from_str(&usage))
+                .transpose()?,
+            tenant_id: row.try_get("tenant_id")?,
+        })
+    }
+
+    fn parse_webhook_payload(row: &SqliteRow) -> Result<WebhookPayload> {
+        Ok(WebhookPayload {
+            id: Uuid::parse_str(&row.try_get::<String, _>("id")?)?,
+            provider: row.try_get("provider")?,
+            headers: serde_json::from_str(&row.try_get::<String, _>("headers")?)?,
+            body: serde_json::from_str(&row.try_get::<String, _>("body")?)?,
+            received_at: DateTime::from_timestamp(row.try_get("received_at")?, 0)
+                .unwrap_or_else(Utc::now),
+        })
+    }
+
+    fn parse_delivery(row: &SqliteRow) -> Result<OutboundDelivery> {
+        let timestamp = |ts: i64| DateTime::from_timestamp(ts, 0).unwrap_or_else(Utc::now);
+        Ok(OutboundDelivery {
+            id: Uuid::parse_str(&row.try_get::<String, _>("id")?)?,
+            method: row.try_get("method")?,
+            url: row.try_get("url")?,
+            headers: serde_json::from_str(&row.try_get::<String, _>("headers")?)?,
+            body: row
+                .try_get::<Option<String>, _>("body")?
+                .map(|s| serde_json::from_str(&s))
+                .transpose()?,
+            sealed: row.try_get("sealed")?,
+            rate_limit: row
+                .try_get::<Option<String>, _>("rate_limit")?
+                .map(|s| serde_json::from_str(&s))
+                .transpose()?,
+            attempts: row.try_get::<i64, _>("attempts")? as u32,
+            max_attempts: row.try_get::<i64, _>("max_attempts")? as u32,
+            last_error: row.try_get("last_error")?,
+            next_attempt_at: timestamp(row.try_get("next_attempt_at")?),
+            created_at: timestamp(row.try_get("created_at")?),
+            dead_lettered_at: row
+                .try_get::<Option<i64>, _>("dead_lettered_at")
+                .ok()
+                .flatten()
+                .map(timestamp),
+            run_id: row
+                .try_get::<Option<String>, _>("run_id")?
+                .map(|id| Uuid::parse_str(&id))
+                .transpose()?,
+        })
+    }
+
+    fn parse_blob_record(row: &SqliteRow) -> Result<BlobRecord> {
+        Ok(BlobRecord {
+            id: Uuid::parse_str(&row.try_get::<String, _>("id")?)?,
+            backend: row.try_get("backend")?,
+            store: row.try_get("store")?,
+            location: row.try_get("location")?,
+            created_at: DateTime::from_timestamp(row.try_get("created_at")?, 0)
+                .unwrap_or_else(Utc::now),
         })
     }
 
@@ -108,8 +407,26 @@ impl SqliteStorage {
             ended_at: row
                 .try_get::<Option<i64>, _>("ended_at")?
                 .map(|ts| DateTime::from_timestamp(ts, 0).unwrap_or_else(Utc::now)),
+            inputs: row
+                .try_get::<Option<String>, _>("inputs")?
+                .map(|s| serde_json::from_str(&s))
+                .transpose()?
+                .flatten(),
             outputs: serde_json::from_str(&row.try_get::<String, _>("outputs")?)?,
             error: row.try_get("error")?,
+            error_kind: row
+                .try_get::<Option<String>, _>("error_kind")?
+                .and_then(|kind| kind.parse().ok()),
+            progress: row
+                .try_get::<Option<i64>, _>("progress_updated_at")?
+                .map(|ts| -> Result<StepProgress> {
+                    Ok(StepProgress {
+                        percent: row.try_get("progress_percent")?,
+                        message: row.try_get("progress_message")?,
+                        updated_at: DateTime::from_timestamp(ts, 0).unwrap_or_else(Utc::now),
+                    })
+                })
+                .transpose()?,
         })
     }
 }
@@ -118,33 +435,20 @@ impl SqliteStorage {
 impl RunStorage for SqliteStorage {
     // Run methods
     async fn save_run(&self, run: &Run) -> Result<()> {
-        sqlx::query(
-            "INSERT INTO runs (id, flow_name, event, vars, status, started_at, ended_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?)
-             ON CONFLICT(id) DO UPDATE SET
-                flow_name = excluded.flow_name,
-                event = excluded.event,
-                vars = excluded.vars,
-                status = excluded.status,
-                started_at = excluded.started_at,
-                ended_at = excluded.ended_at",
-        )
-        .bind(run.id.to_string())
-        .bind(run.flow_name.as_str())
-        .bind(serde_json::to_string(&run.event)?)
-        .bind(serde_json::to_string(&run.vars)?)
-        .bind(run_status_to_str(run.status))
-        .bind(run.started_at.timestamp())
-        .bind(run.ended_at.map(|dt| dt.timestamp()))
-        .execute(&self.pool)
-        .await?;
+        if !self.integrity_chain {
+            return Self::upsert_run(&self.pool, run).await;
+        }
+        let mut tx = self.pool.begin().await?;
+        Self::upsert_run(&mut *tx, run).await?;
+        Self::chain_run(&mut tx, run).await?;
+        tx.commit().await?;
 
         Ok(())
     }
 
     async fn get_run(&self, id: Uuid) -> Result<Option<Run>> {
         let row = sqlx::query(
-            "SELECT id, flow_name, event, vars, status, started_at, ended_at 
+            "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
              FROM runs WHERE id = ?",
         )
         .bind(id.to_string())
@@ -162,11 +466,41 @@ impl RunStorage for SqliteStorage {
         let capped_limit = limit.min(10_000);
 
         let rows = sqlx::query(
-            "SELECT id, flow_name, event, vars, status, started_at, ended_at
+            "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
+             FROM runs
+             ORDER BY started_at DESC
+             LIMIT ? OFFSET ?",
+        )
+        .bind(capped_limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut runs = Vec::new();
+        for row in rows {
+            if let Ok(run) = Self::parse_run(&row) {
+                runs.push(run);
+            }
+        }
+        Ok(runs)
+    }
+
+    async fn list_runs_for_tenant(
+        &self,
+        tenant_id: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Run>> {
+        let capped_limit = limit.min(10_000);
+
+        let rows = sqlx::query(
+            "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
              FROM runs
+             WHERE tenant_id = ?
              ORDER BY started_at DESC
              LIMIT ? OFFSET ?",
         )
+        .bind(tenant_id)
         .bind(capped_limit as i64)
         .bind(offset as i64)
         .fetch_all(&self.pool)
@@ -181,6 +515,66 @@ impl RunStorage for SqliteStorage {
         Ok(runs)
     }
 
+    async fn list_runs_after(
+        &self,
+        filter: &RunFilter,
+        limit: usize,
+        after: Option<&PageCursor<Uuid>>,
+    ) -> Result<Vec<Run>> {
+        let capped_limit = limit.min(10_000);
+        let (label_key, label_value) = filter.label.clone().unzip();
+
+        let rows = match after {
+            Some(after) => {
+                sqlx::query(
+                    "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
+                     FROM runs
+                     WHERE (?1 IS NULL OR EXISTS (
+                         SELECT 1 FROM json_each(runs.labels) l WHERE l.key = ?1 AND l.value = ?2
+                     ))
+                       AND (?6 IS NULL OR tenant_id = ?6)
+                       AND (started_at, id) < (?3, ?4)
+                     ORDER BY started_at DESC, id DESC
+                     LIMIT ?5",
+                )
+                .bind(&label_key)
+                .bind(&label_value)
+                .bind(after.at.timestamp())
+                .bind(after.key.to_string())
+                .bind(capped_limit as i64)
+                .bind(&filter.tenant_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
+                     FROM runs
+                     WHERE (?1 IS NULL OR EXISTS (
+                         SELECT 1 FROM json_each(runs.labels) l WHERE l.key = ?1 AND l.value = ?2
+                     ))
+                       AND (?4 IS NULL OR tenant_id = ?4)
+                     ORDER BY started_at DESC, id DESC
+                     LIMIT ?3",
+                )
+                .bind(&label_key)
+                .bind(&label_value)
+                .bind(capped_limit as i64)
+                .bind(&filter.tenant_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        let mut runs = Vec::new();
+        for row in rows {
+            if let Ok(run) = Self::parse_run(&row) {
+                runs.push(run);
+            }
+        }
+        Ok(runs)
+    }
+
     async fn list_runs_by_flow_and_status(
         &self,
         flow_name: &str,
@@ -193,7 +587,7 @@ impl RunStorage for SqliteStorage {
         // Build query with optional exclude clause
         let query = if let Some(id) = exclude_id {
             sqlx::query(
-                "SELECT id, flow_name, event, vars, status, started_at, ended_at
+                "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
                  FROM runs
                  WHERE flow_name = ? AND status = ? AND id != ?
                  ORDER BY started_at DESC
@@ -205,7 +599,7 @@ impl RunStorage for SqliteStorage {
             .bind(limit as i64)
         } else {
             sqlx::query(
-                "SELECT id, flow_name, event, vars, status, started_at, ended_at
+                "SELECT id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id
                  FROM runs
                  WHERE flow_name = ? AND status = ?
                  ORDER BY started_at DESC
@@ -242,9 +636,9 @@ impl RunStorage for SqliteStorage {
     }
 
     async fn try_insert_run(&self, run: &Run) -> Result<bool> {
-        let result = sqlx::query(
-            "INSERT INTO runs (id, flow_name, event, vars, status, started_at, ended_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?)
+        let insert = sqlx::query(
+            "INSERT INTO runs (id, flow_name, event, vars, status, started_at, ended_at, environment, correlation_id, labels, usage, tenant_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
              ON CONFLICT(id) DO NOTHING",
         )
         .bind(run.id.to_string())
@@ -254,44 +648,127 @@ impl RunStorage for SqliteStorage {
         .bind(run_status_to_str(run.status))
         .bind(run.started_at.timestamp())
         .bind(run.ended_at.map(|dt| dt.timestamp()))
-        .execute(&self.pool)
-        .await?;
+        .bind(&run.environment)
+        .bind(&run.correlation_id)
+        .bind(labels_to_json(&run.labels).map(|labels| labels.to_string()))
+        .bind(run.usage.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(&run.tenant_id);
 
         // Returns true if a row was inserted, false if conflict occurred
-        Ok(result.rows_affected() == 1)
+        if !self.integrity_chain {
+            return Ok(insert.execute(&self.pool).await?.rows_affected() == 1);
+        }
+        let mut tx = self.pool.begin().await?;
+        let inserted = insert.execute(&mut *tx).await?.rows_affected() == 1;
+        if inserted {
+            Self::chain_run(&mut tx, run).await?;
+        }
+        tx.commit().await?;
+
+        Ok(inserted)
     }
 
-    // Step methods
-    async fn save_step(&self, step: &StepRun) -> Result<()> {
-        sqlx::query(
-            "INSERT INTO steps (id, run_id, step_name, status, started_at, ended_at, outputs, error)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)
-             ON CONFLICT(id) DO UPDATE SET
-                run_id = excluded.run_id,
-                step_name = excluded.step_name,
-                status = excluded.status,
-                started_at = excluded.started_at,
-                ended_at = excluded.ended_at,
-                outputs = excluded.outputs,
-                error = excluded.error"
+    async fn run_stats(
+        &self,
+        since: DateTime<Utc>,
+        flow_name: Option<&str>,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<FlowRunStats>> {
+        // SQLite has no percentile aggregate: rank finished runs per flow with window
+        // functions and pick the nearest-rank rows (ceil(p * n)).
+        let rows = sqlx::query(
+            "WITH windowed AS (
+                SELECT flow_name, status, started_at, ended_at
+                FROM runs
+                WHERE started_at >= ? AND (? IS NULL OR flow_name = ?)
+                    AND (? IS NULL OR tenant_id = ?)
+             ),
+             ranked AS (
+                SELECT flow_name, ended_at - started_at AS duration,
+                    ROW_NUMBER() OVER (PARTITION BY flow_name ORDER BY ended_at - started_at) AS rn,
+                    COUNT(*) OVER (PARTITION BY flow_name) AS cnt
+                FROM windowed
+                WHERE ended_at IS NOT NULL
+             )
+             SELECT w.flow_name,
+                COUNT(*) AS total,
+                SUM(CASE WHEN w.status = 'SUCCEEDED' THEN 1 ELSE 0 END) AS succeeded,
+                SUM(CASE WHEN w.status = 'FAILED' THEN 1 ELSE 0 END) AS failed,
+                (SELECT r.duration FROM ranked r
+                    WHERE r.flow_name = w.flow_name AND r.rn = (r.cnt + 1) / 2) AS p50,
+                (SELECT r.duration FROM ranked r
+                    WHERE r.flow_name = w.flow_name AND r.rn = (r.cnt * 95 + 99) / 100) AS p95
+             FROM windowed w
+             GROUP BY w.flow_name
+             ORDER BY failed DESC, w.flow_name",
         )
-        .bind(step.id.to_string())
-        .bind(step.run_id.to_string())
-        .bind(step.step_name.as_str())
-        .bind(step_status_to_str(step.status))
-        .bind(step.started_at.timestamp())
-        .bind(step.ended_at.map(|dt| dt.timestamp()))
-        .bind(serde_json::to_string(&step.outputs)?)
-        .bind(&step.error)
-        .execute(&self.pool)
+        .bind(since.timestamp())
+        .bind(flow_name)
+        .bind(flow_name)
+        .bind(tenant_id)
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut stats = rows
+            .iter()
+            .map(|row| {
+                Ok(FlowRunStats {
+                    flow_name: row.try_get("flow_name")?,
+                    total: row.try_get::<i64, _>("total")? as u64,
+                    succeeded: row.try_get::<i64, _>("succeeded")? as u64,
+                    failed: row.try_get::<i64, _>("failed")? as u64,
+                    // Timestamps are stored in seconds
+                    p50_duration_ms: row.try_get::<Option<i64>, _>("p50")?.map(|s| s * 1000),
+                    p95_duration_ms: row.try_get::<Option<i64>, _>("p95")?.map(|s| s * 1000),
+                    usage: Default::default(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let usage_rows = sqlx::query(
+            "SELECT flow_name, usage FROM runs
+             WHERE started_at >= ? AND (? IS NULL OR flow_name = ?)
+                AND (? IS NULL OR tenant_id = ?) AND usage IS NOT NULL",
+        )
+        .bind(since.timestamp())
+        .bind(flow_name)
+        .bind(flow_name)
+        .bind(tenant_id)
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
         .await?;
+        let usages = usage_rows
+            .iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("flow_name")?,
+                    serde_json::from_str(&row.try_get::<String, _>("usage")?)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        add_run_usage(&mut stats, usages);
+
+        Ok(stats)
+    }
+
+    // Step methods
+    async fn save_step(&self, step: &StepRun) -> Result<()> {
+        if !self.integrity_chain {
+            return Self::upsert_step(&self.pool, step).await;
+        }
+        let mut tx = self.pool.begin().await?;
+        Self::upsert_step(&mut *tx, step).await?;
+        Self::chain_step(&mut tx, step).await?;
+        tx.commit().await?;
 
         Ok(())
     }
 
     async fn get_steps(&self, run_id: Uuid) -> Result<Vec<StepRun>> {
         let rows = sqlx::query(
-            "SELECT id, run_id, step_name, status, started_at, ended_at, outputs, error 
+            "SELECT id, run_id, step_name, status, started_at, ended_at, inputs, outputs, error,
+                    error_kind, progress_percent, progress_message, progress_updated_at
              FROM steps WHERE run_id = ?",
         )
         .bind(run_id.to_string())
@@ -306,6 +783,53 @@ impl RunStorage for SqliteStorage {
         }
         Ok(steps)
     }
+
+    async fn verify_integrity(&self, flow_name: Option<&str>) -> Result<IntegrityReport> {
+        let heads = sqlx::query(
+            "SELECT flow_name, last_sequence, last_hash FROM run_history_heads
+             WHERE ? IS NULL OR flow_name = ?
+             ORDER BY flow_name",
+        )
+        .bind(flow_name)
+        .bind(flow_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut report = IntegrityReport::default();
+        for row in heads {
+            let head = ChainHead {
+                flow_name: row.try_get("flow_name")?,
+                last_sequence: row.try_get("last_sequence")?,
+                last_hash: row.try_get("last_hash")?,
+            };
+            let entries = sqlx::query(
+                "SELECT sequence, record_kind, record_id, content, prev_hash, record_hash
+                 FROM run_history WHERE flow_name = ? ORDER BY sequence",
+            )
+            .bind(&head.flow_name)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| -> Result<HistoryEntry> {
+                Ok(HistoryEntry {
+                    sequence: row.try_get("sequence")?,
+                    kind: RecordKind::parse(&row.try_get::<String, _>("record_kind")?)?,
+                    record_id: Uuid::parse_str(&row.try_get::<String, _>("record_id")?)?,
+                    content: row.try_get("content")?,
+                    prev_hash: row.try_get("prev_hash")?,
+                    record_hash: row.try_get("record_hash")?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+            integrity::verify_chain(self, &head, &entries, &mut report).await?;
+            if !report.is_intact() {
+                break;
+            }
+        }
+
+        Ok(report)
+    }
 }
 
 #[async_trait]
@@ -418,6 +942,332 @@ impl StateStorage for SqliteStorage {
             None => Ok(None),
         }
     }
+
+    // Webhook payload history
+    async fn save_webhook_payload(&self, payload: &WebhookPayload, keep: usize) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO webhook_payloads (id, provider, headers, body, received_at)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(payload.id.to_string())
+        .bind(&payload.provider)
+        .bind(serde_json::to_string(&payload.headers)?)
+        .bind(serde_json::to_string(&payload.body)?)
+        .bind(payload.received_at.timestamp())
+        .execute(&mut *tx)
+        .await?;
+
+        // Trim to the newest `keep` payloads for this provider
+        sqlx::query(
+            "DELETE FROM webhook_payloads WHERE provider = ? AND id NOT IN (
+                SELECT id FROM webhook_payloads WHERE provider = ?
+                ORDER BY received_at DESC, rowid DESC LIMIT ?
+             )",
+        )
+        .bind(&payload.provider)
+        .bind(&payload.provider)
+        .bind(keep as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_webhook_payload(&self, id: Uuid) -> Result<Option<WebhookPayload>> {
+        let row = sqlx::query(
+            "SELECT id, provider, headers, body, received_at FROM webhook_payloads WHERE id = ?",
+        )
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(Self::parse_webhook_payload).transpose()
+    }
+
+    async fn list_webhook_payloads(
+        &self,
+        provider: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<WebhookPayload>> {
+        let rows = sqlx::query(
+            "SELECT id, provider, headers, body, received_at FROM webhook_payloads
+             WHERE ? IS NULL OR provider = ?
+             ORDER BY received_at DESC, rowid DESC LIMIT ?",
+        )
+        .bind(provider)
+        .bind(provider)
+        .bind(limit.min(10_000) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::parse_webhook_payload).collect()
+    }
+
+    // Blob locations
+    async fn save_blob_record(&self, record: &BlobRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO blobs (id, backend, store, location, created_at) VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                backend = excluded.backend,
+                store = excluded.store,
+                location = excluded.location,
+                created_at = excluded.created_at",
+        )
+        .bind(record.id.to_string())
+        .bind(&record.backend)
+        .bind(&record.store)
+        .bind(&record.location)
+        .bind(record.created_at.timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_blob_record(&self, id: Uuid) -> Result<Option<BlobRecord>> {
+        let row =
+            sqlx::query("SELECT id, backend, store, location, created_at FROM blobs WHERE id = ?")
+                .bind(id.to_string())
+                .fetch_optional(&self.pool)
+                .await?;
+
+        row.as_ref().map(Self::parse_blob_record).transpose()
+    }
+
+    async fn list_blob_records(&self) -> Result<Vec<BlobRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, backend, store, location, created_at FROM blobs ORDER BY created_at, rowid",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::parse_blob_record).collect()
+    }
+
+    async fn delete_blob_record(&self, id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM blobs WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Run checkpoints
+    async fn save_run_checkpoint(&self, run_id: Uuid, data: serde_json::Value) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO run_checkpoints (run_id, data, updated_at) VALUES (?, ?, ?)
+             ON CONFLICT(run_id) DO UPDATE SET data = excluded.data, updated_at = excluded.updated_at",
+        )
+        .bind(run_id.to_string())
+        .bind(serde_json::to_string(&data)?)
+        .bind(Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn load_run_checkpoints(&self) -> Result<Vec<(Uuid, serde_json::Value)>> {
+        let rows = sqlx::query("SELECT run_id, data FROM run_checkpoints ORDER BY updated_at")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            let run_id: String = row.try_get("run_id")?;
+            let data_json: String = row.try_get("data")?;
+            if let (Ok(run_id), Ok(data)) =
+                (Uuid::parse_str(&run_id), serde_json::from_str(&data_json))
+            {
+                result.push((run_id, data));
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn delete_run_checkpoint(&self, run_id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM run_checkpoints WHERE run_id = ?")
+            .bind(run_id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Outbound deliveries
+    async fn save_delivery(&self, delivery: &OutboundDelivery) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO outbound_deliveries
+                (id, method, url, headers, body, sealed, rate_limit, attempts, max_attempts, last_error,
+                 next_attempt_at, created_at, run_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                attempts = excluded.attempts,
+                max_attempts = excluded.max_attempts,
+                last_error = excluded.last_error,
+                next_attempt_at = excluded.next_attempt_at",
+        )
+        .bind(delivery.id.to_string())
+        .bind(&delivery.method)
+        .bind(&delivery.url)
+        .bind(serde_json::to_string(&delivery.headers)?)
+        .bind(
+            delivery
+                .body
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        )
+        .bind(&delivery.sealed)
+        .bind(
+            delivery
+                .rate_limit
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        )
+        .bind(delivery.attempts as i64)
+        .bind(delivery.max_attempts as i64)
+        .bind(&delivery.last_error)
+        .bind(delivery.next_attempt_at.timestamp())
+        .bind(delivery.created_at.timestamp())
+        .bind(delivery.run_id.map(|id| id.to_string()))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<OutboundDelivery>> {
+        let rows = sqlx::query(
+            "SELECT * FROM outbound_deliveries WHERE next_attempt_at <= ?
+             ORDER BY next_attempt_at, rowid LIMIT ?",
+        )
+        .bind(now.timestamp())
+        .bind(limit.min(10_000) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::parse_delivery).collect()
+    }
+
+    async fn claim_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<OutboundDelivery>> {
+        let rows = sqlx::query(
+            "UPDATE outbound_deliveries SET next_attempt_at = ?
+             WHERE id IN (
+                SELECT id FROM outbound_deliveries WHERE next_attempt_at <= ?
+                ORDER BY next_attempt_at, rowid LIMIT ?
+             )
+             RETURNING *",
+        )
+        .bind(lease_until.timestamp())
+        .bind(now.timestamp())
+        .bind(limit.min(10_000) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut claimed = rows
+            .iter()
+            .map(Self::parse_delivery)
+            .collect::<Result<Vec<_>>>()?;
+        claimed.sort_by_key(|delivery| delivery.created_at);
+        Ok(claimed)
+    }
+
+    async fn delete_delivery(&self, id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM outbound_deliveries WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn dead_letter_delivery(&self, delivery: &OutboundDelivery) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO dead_letter_deliveries
+                (id, method, url, headers, body, sealed, rate_limit, attempts, max_attempts, last_error,
+                 next_attempt_at, created_at, dead_lettered_at, run_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO NOTHING",
+        )
+        .bind(delivery.id.to_string())
+        .bind(&delivery.method)
+        .bind(&delivery.url)
+        .bind(serde_json::to_string(&delivery.headers)?)
+        .bind(
+            delivery
+                .body
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        )
+        .bind(&delivery.sealed)
+        .bind(
+            delivery
+                .rate_limit
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        )
+        .bind(delivery.attempts as i64)
+        .bind(delivery.max_attempts as i64)
+        .bind(&delivery.last_error)
+        .bind(delivery.next_attempt_at.timestamp())
+        .bind(delivery.created_at.timestamp())
+        .bind(
+            delivery
+                .dead_lettered_at
+                .unwrap_or_else(Utc::now)
+                .timestamp(),
+        )
+        .bind(delivery.run_id.map(|id| id.to_string()))
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM outbound_deliveries WHERE id = ?")
+            .bind(delivery.id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list_dead_letters(
+        &self,
+        limit: usize,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<OutboundDelivery>> {
+        let rows = sqlx::query(
+            "SELECT * FROM dead_letter_deliveries
+             WHERE ? IS NULL OR run_id IN (SELECT id FROM runs WHERE tenant_id = ?)
+             ORDER BY dead_lettered_at DESC, rowid DESC LIMIT ?",
+        )
+        .bind(tenant_id)
+        .bind(tenant_id)
+        .bind(limit.min(10_000) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::parse_delivery).collect()
+    }
 }
 
 #[async_trait]
@@ -425,14 +1275,16 @@ impl FlowStorage for SqliteStorage {
     // Flow versioning methods
     async fn deploy_flow_version(
         &self,
-        flow_name: &str,
+        flow_name: &FlowName,
         version: &str,
         content: &str,
     ) -> Result<()> {
         let now = Utc::now().timestamp();
 
-        // Parse flow to extract trigger topics
+        // Parse flow to extract trigger topics, ownership metadata and search fields
         let topics = extract_topics_from_flow_yaml(content);
+        let summary = extract_summary_from_flow_yaml(content);
+        let search_fields = search::index_flow(content);
 
         // Start transaction
         let mut tx = self.pool.begin().await?;
@@ -440,7 +1292,7 @@ impl FlowStorage for SqliteStorage {
         // Check if this version already exists (enforce version immutability)
         let exists =
             sqlx::query("SELECT 1 FROM flow_versions WHERE flow_name = ? AND version = ? LIMIT 1")
-                .bind(flow_name)
+                .bind(flow_name.as_str())
                 .bind(version)
                 .fetch_optional(&mut *tx)
                 .await?;
@@ -454,16 +1306,30 @@ impl FlowStorage for SqliteStorage {
 
         // Save new version snapshot
         sqlx::query(
-            "INSERT INTO flow_versions (flow_name, version, content, deployed_at)
-             VALUES (?, ?, ?, ?)",
+            "INSERT INTO flow_versions (flow_name, version, content, deployed_at, description, owner, team)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
         )
-        .bind(flow_name)
+        .bind(flow_name.as_str())
         .bind(version)
         .bind(content)
         .bind(now)
+        .bind(&summary.description)
+        .bind(&summary.owner)
+        .bind(&summary.team)
         .execute(&mut *tx)
         .await?;
 
+        for tag in &summary.tags {
+            sqlx::query(
+                "INSERT OR IGNORE INTO flow_tags (flow_name, version, tag) VALUES (?, ?, ?)",
+            )
+            .bind(flow_name.as_str())
+            .bind(version)
+            .bind(tag)
+            .execute(&mut *tx)
+            .await?;
+        }
+
         // Update deployed version pointer
         sqlx::query(
             "INSERT INTO deployed_flows (flow_name, deployed_version, deployed_at)
@@ -472,7 +1338,7 @@ impl FlowStorage for SqliteStorage {
                 deployed_version = excluded.deployed_version,
                 deployed_at = excluded.deployed_at",
         )
-        .bind(flow_name)
+        .bind(flow_name.as_str())
         .bind(version)
         .bind(now)
         .execute(&mut *tx)
@@ -486,18 +1352,32 @@ impl FlowStorage for SqliteStorage {
                  VALUES (?, ?, ?)
                  ON CONFLICT DO NOTHING",
             )
-            .bind(flow_name)
+            .bind(flow_name.as_str())
             .bind(version)
             .bind(&topic)
             .execute(&mut *tx)
             .await?;
         }
 
+        for field in search_fields {
+            sqlx::query(
+                "INSERT INTO flow_search (flow_name, version, field, weight, text)
+                 VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(flow_name.as_str())
+            .bind(version)
+            .bind(&field.field)
+            .bind(field.weight)
+            .bind(&field.text)
+            .execute(&mut *tx)
+            .await?;
+        }
+
         tx.commit().await?;
         Ok(())
     }
 
-    async fn set_deployed_version(&self, flow_name: &str, version: &str) -> Result<()> {
+    async fn set_deployed_version(&self, flow_name: &FlowName, version: &str) -> Result<()> {
         let now = Utc::now().timestamp();
 
         sqlx::query(
@@ -507,7 +1387,7 @@ impl FlowStorage for SqliteStorage {
                 deployed_version = excluded.deployed_version,
                 deployed_at = excluded.deployed_at",
         )
-        .bind(flow_name)
+        .bind(flow_name.as_str())
         .bind(version)
         .bind(now)
         .execute(&self.pool)
@@ -516,9 +1396,9 @@ impl FlowStorage for SqliteStorage {
         Ok(())
     }
 
-    async fn get_deployed_version(&self, flow_name: &str) -> Result<Option<String>> {
+    async fn get_deployed_version(&self, flow_name: &FlowName) -> Result<Option<String>> {
         let row = sqlx::query("SELECT deployed_version FROM deployed_flows WHERE flow_name = ?")
-            .bind(flow_name)
+            .bind(flow_name.as_str())
             .fetch_optional(&self.pool)
             .await?;
 
@@ -527,12 +1407,12 @@ impl FlowStorage for SqliteStorage {
 
     async fn get_flow_version_content(
         &self,
-        flow_name: &str,
+        flow_name: &FlowName,
         version: &str,
     ) -> Result<Option<String>> {
         let row =
             sqlx::query("SELECT content FROM flow_versions WHERE flow_name = ? AND version = ?")
-                .bind(flow_name)
+                .bind(flow_name.as_str())
                 .bind(version)
                 .fetch_optional(&self.pool)
                 .await?;
@@ -540,7 +1420,7 @@ impl FlowStorage for SqliteStorage {
         Ok(row.and_then(|r| r.try_get("content").ok()))
     }
 
-    async fn list_flow_versions(&self, flow_name: &str) -> Result<Vec<FlowSnapshot>> {
+    async fn list_flow_versions(&self, flow_name: &FlowName) -> Result<Vec<FlowSnapshot>> {
         let rows = sqlx::query(
             "SELECT v.version, v.deployed_at,
                 CASE WHEN d.deployed_version = v.version THEN 1 ELSE 0 END as is_live
@@ -549,7 +1429,7 @@ impl FlowStorage for SqliteStorage {
              WHERE v.flow_name = ?
              ORDER BY v.deployed_at DESC",
         )
-        .bind(flow_name)
+        .bind(flow_name.as_str())
         .fetch_all(&self.pool)
         .await?;
 
@@ -570,9 +1450,69 @@ impl FlowStorage for SqliteStorage {
         Ok(snapshots)
     }
 
+    async fn list_flow_versions_after(
+        &self,
+        flow_name: &FlowName,
+        limit: usize,
+        after: Option<&PageCursor<String>>,
+    ) -> Result<Vec<FlowSnapshot>> {
+        let capped_limit = limit.min(10_000);
+
+        let rows = match after {
+            Some(after) => {
+                sqlx::query(
+                    "SELECT v.version, v.deployed_at,
+                        CASE WHEN d.deployed_version = v.version THEN 1 ELSE 0 END as is_live
+                     FROM flow_versions v
+                     LEFT JOIN deployed_flows d ON v.flow_name = d.flow_name
+                     WHERE v.flow_name = ? AND (v.deployed_at, v.version) < (?, ?)
+                     ORDER BY v.deployed_at DESC, v.version DESC
+                     LIMIT ?",
+                )
+                .bind(flow_name.as_str())
+                .bind(after.at.timestamp())
+                .bind(&after.key)
+                .bind(capped_limit as i64)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    "SELECT v.version, v.deployed_at,
+                        CASE WHEN d.deployed_version = v.version THEN 1 ELSE 0 END as is_live
+                     FROM flow_versions v
+                     LEFT JOIN deployed_flows d ON v.flow_name = d.flow_name
+                     WHERE v.flow_name = ?
+                     ORDER BY v.deployed_at DESC, v.version DESC
+                     LIMIT ?",
+                )
+                .bind(flow_name.as_str())
+                .bind(capped_limit as i64)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        let mut snapshots = Vec::new();
+        for row in rows {
+            let version: String = row.try_get("version")?;
+            let deployed_at_unix: i64 = row.try_get("deployed_at")?;
+            let is_live: i32 = row.try_get("is_live")?;
+
+            snapshots.push(FlowSnapshot {
+                flow_name: flow_name.to_string(),
+                version,
+                deployed_at: DateTime::from_timestamp(deployed_at_unix, 0).unwrap_or_else(Utc::now),
+                is_live: is_live == 1,
+            });
+        }
+
+        Ok(snapshots)
+    }
+
     async fn get_latest_deployed_version_from_history(
         &self,
-        flow_name: &str,
+        flow_name: &FlowName,
     ) -> Result<Option<String>> {
         let row = sqlx::query(
             "SELECT version FROM flow_versions
@@ -580,21 +1520,45 @@ impl FlowStorage for SqliteStorage {
              ORDER BY deployed_at DESC, version DESC
              LIMIT 1",
         )
-        .bind(flow_name)
+        .bind(flow_name.as_str())
         .fetch_optional(&self.pool)
         .await?;
 
         Ok(row.and_then(|r| r.try_get("version").ok()))
     }
 
-    async fn unset_deployed_version(&self, flow_name: &str) -> Result<()> {
+    async fn unset_deployed_version(&self, flow_name: &FlowName) -> Result<()> {
         sqlx::query("DELETE FROM deployed_flows WHERE flow_name = ?")
-            .bind(flow_name)
+            .bind(flow_name.as_str())
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
+    async fn get_flow_tenant(&self, flow_name: &FlowName) -> Result<Option<String>> {
+        let row = sqlx::query("SELECT tenant_id FROM flow_tenants WHERE flow_name = ?")
+            .bind(flow_name.as_str())
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|row| row.try_get("tenant_id")).transpose()?)
+    }
+
+    async fn claim_flow_tenant(&self, flow_name: &FlowName, tenant_id: &str) -> Result<String> {
+        sqlx::query(
+            "INSERT INTO flow_tenants (flow_name, tenant_id) VALUES (?, ?)
+             ON CONFLICT(flow_name) DO NOTHING",
+        )
+        .bind(flow_name.as_str())
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+        let row = sqlx::query("SELECT tenant_id FROM flow_tenants WHERE flow_name = ?")
+            .bind(flow_name.as_str())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row.try_get("tenant_id")?)
+    }
+
     async fn list_all_deployed_flows(&self) -> Result<Vec<(String, String)>> {
         let rows = sqlx::query(
             "SELECT d.flow_name, v.content
@@ -616,7 +1580,7 @@ impl FlowStorage for SqliteStorage {
         Ok(result)
     }
 
-    async fn find_flow_names_by_topic(&self, topic: &str) -> Result<Vec<String>> {
+    async fn find_flow_names_by_topic(&self, topic: &str) -> Result<Vec<FlowName>> {
         let rows = sqlx::query(
             "SELECT DISTINCT ft.flow_name
              FROM flow_triggers ft
@@ -630,9 +1594,182 @@ impl FlowStorage for SqliteStorage {
 
         Ok(rows
             .into_iter()
-            .filter_map(|row| row.try_get("flow_name").ok())
+            .filter_map(|row| row.try_get::<String, _>("flow_name").ok())
+            .filter_map(|name| FlowName::new(name).ok())
             .collect())
     }
+    async fn list_deployed_flow_summaries(&self, filter: &FlowFilter) -> Result<Vec<FlowSummary>> {
+        let rows = sqlx::query(
+            "SELECT d.flow_name, d.deployed_version, v.description, v.owner, v.team
+             FROM deployed_flows d
+             INNER JOIN flow_versions v
+               ON d.flow_name = v.flow_name
+               AND d.deployed_version = v.version
+             WHERE (?1 IS NULL OR v.owner = ?1)
+               AND (?2 IS NULL OR v.team = ?2)
+               AND (?3 IS NULL OR EXISTS (
+                   SELECT 1 FROM flow_tags t
+                   WHERE t.flow_name = d.flow_name AND t.version = d.deployed_version AND t.tag = ?3
+               ))
+               AND (?4 IS NULL OR EXISTS (
+                   SELECT 1 FROM flow_tenants ft
+                   WHERE ft.flow_name = d.flow_name AND ft.tenant_id = ?4
+               ))
+             ORDER BY d.flow_name",
+        )
+        .bind(&filter.owner)
+        .bind(&filter.team)
+        .bind(&filter.tag)
+        .bind(&filter.tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let tag_rows = sqlx::query(
+            "SELECT t.flow_name, t.tag
+             FROM flow_tags t
+             INNER JOIN deployed_flows d
+               ON t.flow_name = d.flow_name
+               AND t.version = d.deployed_version
+             ORDER BY t.flow_name, t.tag",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        for row in tag_rows {
+            tags.entry(row.try_get("flow_name")?)
+                .or_default()
+                .push(row.try_get("tag")?);
+        }
+
+        rows.into_iter()
+            .map(|row| {
+                let name: String = row.try_get("flow_name")?;
+                Ok(FlowSummary {
+                    tags: tags.remove(&name).unwrap_or_default(),
+                    name,
+                    description: row.try_get("description")?,
+                    owner: row.try_get("owner")?,
+                    team: row.try_get("team")?,
+                    deployed_version: Some(row.try_get("deployed_version")?),
+                })
+            })
+            .collect()
+    }
+
+    async fn search_flows(&self, query: &str, limit: usize) -> Result<Vec<FlowSearchResult>> {
+        let terms = search::search_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Rows holding a word starting with any term; rank keeps flows matching all
+        let fts_query = terms
+            .iter()
+            .map(|term| format!("\"{}\"*", term))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let rows = sqlx::query(
+            "SELECT flow_search.flow_name, flow_search.version, flow_search.field,
+                    flow_search.weight, flow_search.text
+             FROM flow_search
+             INNER JOIN deployed_flows d
+                ON d.flow_name = flow_search.flow_name AND d.deployed_version = flow_search.version
+             WHERE flow_search MATCH ?",
+        )
+        .bind(fts_query)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let candidates = rows
+            .iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("flow_name")?,
+                    row.try_get("version")?,
+                    SearchField {
+                        field: row.try_get("field")?,
+                        text: row.try_get("text")?,
+                        weight: row.try_get("weight")?,
+                    },
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(search::rank(&terms, candidates, limit))
+    }
+}
+
+#[async_trait]
+impl OutboxStorage for SqliteStorage {
+    async fn commit(&self, batch: &WriteBatch) -> Result<Vec<OutboxEvent>> {
+        let mut tx = self.pool.begin().await?;
+        for run in &batch.runs {
+            Self::upsert_run(&mut *tx, run).await?;
+            if self.integrity_chain {
+                Self::chain_run(&mut tx, run).await?;
+            }
+        }
+        for step in &batch.steps {
+            Self::upsert_step(&mut *tx, step).await?;
+            if self.integrity_chain {
+                Self::chain_step(&mut tx, step).await?;
+            }
+        }
+        let mut queued = Vec::with_capacity(batch.events.len());
+        for event in &batch.events {
+            queued.push(Self::enqueue_event(&mut tx, event).await?);
+        }
+        tx.commit().await?;
+
+        Ok(queued)
+    }
+
+    async fn list_unsent_events(&self, limit: usize) -> Result<Vec<OutboxEvent>> {
+        let rows = sqlx::query(
+            "SELECT id, topic, sequence, payload, source, correlation_id, created_at FROM event_outbox
+             WHERE sent_at IS NULL
+             ORDER BY position
+             LIMIT ?",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::parse_outbox_event).collect()
+    }
+
+    async fn mark_events_sent(&self, ids: &[Uuid]) -> Result<()> {
+        let sent_at = Utc::now().timestamp();
+        let mut tx = self.pool.begin().await?;
+        for id in ids {
+            sqlx::query("UPDATE event_outbox SET sent_at = ? WHERE id = ? AND sent_at IS NULL")
+                .bind(sent_at)
+                .bind(id.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn prune_sent_events(&self, sent_before: DateTime<Utc>) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let deleted =
+            sqlx::query("DELETE FROM event_outbox WHERE sent_at IS NOT NULL AND sent_at < ?")
+                .bind(sent_before.timestamp())
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        sqlx::query(
+            "DELETE FROM event_topic_sequences
+             WHERE NOT EXISTS (SELECT 1 FROM event_outbox WHERE event_outbox.topic = event_topic_sequences.topic)",
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(deleted)
+    }
 }
 
 #[async_trait]
@@ -643,8 +1780,8 @@ impl OAuthStorage for SqliteStorage {
 
         sqlx::query(
             "INSERT OR REPLACE INTO oauth_credentials
-             (id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+             (id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at, tenant_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(&credential.id)
         .bind(&credential.provider)
@@ -655,6 +1792,7 @@ impl OAuthStorage for SqliteStorage {
         .bind(&credential.scope)
         .bind(credential.created_at.timestamp())
         .bind(now)
+        .bind(&credential.tenant_id)
         .execute(&self.pool)
         .await?;
 
@@ -667,7 +1805,7 @@ impl OAuthStorage for SqliteStorage {
         integration: &str,
     ) -> Result<Option<OAuthCredential>> {
         let row = sqlx::query(
-            "SELECT id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at
+            "SELECT id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at, tenant_id
              FROM oauth_credentials 
              WHERE provider = ? AND integration = ?"
         )
@@ -694,6 +1832,7 @@ impl OAuthStorage for SqliteStorage {
                         .unwrap_or_else(Utc::now),
                     updated_at: DateTime::from_timestamp(updated_at_unix, 0)
                         .unwrap_or_else(Utc::now),
+                    tenant_id: row.try_get("tenant_id")?,
                 }))
             }
             None => Ok(None),
@@ -702,7 +1841,7 @@ impl OAuthStorage for SqliteStorage {
 
     async fn list_oauth_credentials(&self) -> Result<Vec<OAuthCredential>> {
         let rows = sqlx::query(
-            "SELECT id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at
+            "SELECT id, provider, integration, access_token, refresh_token, expires_at, scope, created_at, updated_at, tenant_id
              FROM oauth_credentials 
              ORDER BY created_at DESC"
         )
@@ -725,6 +1864,7 @@ impl OAuthStorage for SqliteStorage {
                 scope: row.try_get("scope")?,
                 created_at: DateTime::from_timestamp(created_at_unix, 0).unwrap_or_else(Utc::now),
                 updated_at: DateTime::from_timestamp(updated_at_unix, 0).unwrap_or_else(Utc::now),
+                tenant_id: row.try_get("tenant_id")?,
             });
         }
 
@@ -741,6 +1881,19 @@ impl OAuthStorage for SqliteStorage {
         Ok(())
     }
 
+    async fn delete_expired_oauth_credentials(&self, now: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM oauth_credentials
+             WHERE expires_at IS NOT NULL AND expires_at < ?
+               AND (refresh_token IS NULL OR refresh_token = '')",
+        )
+        .bind(now.timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     async fn refresh_oauth_credential(
         &self,
         id: &str,
@@ -884,8 +2037,8 @@ impl OAuthStorage for SqliteStorage {
 
         sqlx::query(
             "INSERT OR REPLACE INTO oauth_clients
-             (id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+             (id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at, tenant_id)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(&client.id)
         .bind(&client.secret)
@@ -896,6 +2049,7 @@ impl OAuthStorage for SqliteStorage {
         .bind(&client.scope)
         .bind(client.created_at.timestamp())
         .bind(now)
+        .bind(&client.tenant_id)
         .execute(&self.pool)
         .await?;
 
@@ -904,7 +2058,7 @@ impl OAuthStorage for SqliteStorage {
 
     async fn get_oauth_client(&self, id: &str) -> Result<Option<OAuthClient>> {
         let row = sqlx::query(
-            "SELECT id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at
+            "SELECT id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at, tenant_id
              FROM oauth_clients
              WHERE id = ?"
         )
@@ -934,6 +2088,7 @@ impl OAuthStorage for SqliteStorage {
                         .unwrap_or_else(Utc::now),
                     updated_at: DateTime::from_timestamp(updated_at_unix, 0)
                         .unwrap_or_else(Utc::now),
+                    tenant_id: row.try_get("tenant_id")?,
                 }))
             }
             None => Ok(None),
@@ -942,7 +2097,7 @@ impl OAuthStorage for SqliteStorage {
 
     async fn list_oauth_clients(&self) -> Result<Vec<OAuthClient>> {
         let rows = sqlx::query(
-            "SELECT id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at
+            "SELECT id, secret, name, redirect_uris, grant_types, response_types, scope, created_at, updated_at, tenant_id
              FROM oauth_clients
              ORDER BY created_at DESC"
         )
@@ -976,6 +2131,7 @@ impl OAuthStorage for SqliteStorage {
                         .unwrap_or_else(Utc::now),
                     updated_at: DateTime::from_timestamp(updated_at_unix, 0)
                         .unwrap_or_else(Utc::now),
+                    tenant_id: row.try_get("tenant_id")?,
                 });
             }
         }
@@ -9,13 +9,18 @@ async fn test_save_and_get_run() {
     let storage = SqliteStorage::new(":memory:").await.unwrap();
     let run = Run {
         id: Uuid::new_v4(),
-        flow_name: "test".to_string().into(),
+        flow_name: FlowName::new("test").unwrap(),
         event: HashMap::new(),
         vars: HashMap::new(),
         status: RunStatus::Running,
         started_at: Utc::now(),
         ended_at: None,
         steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
     };
 
     storage.save_run(&run).await.unwrap();
@@ -37,6 +42,7 @@ async fn test_oauth_credentials() {
         scope: None,
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        tenant_id: None,
     };
 
     storage.save_oauth_credential(&cred).await.unwrap();
@@ -53,18 +59,24 @@ async fn test_flow_versioning() {
     let storage = SqliteStorage::new(":memory:").await.unwrap();
 
     storage
-        .deploy_flow_version("my_flow", "v1", "content1")
+        .deploy_flow_version(&FlowName::new("my_flow").unwrap(), "v1", "content1")
         .await
         .unwrap();
     storage
-        .deploy_flow_version("my_flow", "v2", "content2")
+        .deploy_flow_version(&FlowName::new("my_flow").unwrap(), "v2", "content2")
         .await
         .unwrap();
 
-    let deployed = storage.get_deployed_version("my_flow").await.unwrap();
+    let deployed = storage
+        .get_deployed_version(&FlowName::new("my_flow").unwrap())
+        .await
+        .unwrap();
     assert_eq!(deployed, Some("v2".to_string()));
 
-    let versions = storage.list_flow_versions("my_flow").await.unwrap();
+    let versions = storage
+        .list_flow_versions(&FlowName::new("my_flow").unwrap())
+        .await
+        .unwrap();
     assert_eq!(versions.len(), 2);
     assert!(versions.iter().any(|v| v.version == "v2" && v.is_live));
 }
@@ -112,7 +124,7 @@ async fn test_all_operations_comprehensive() {
     // Add a run
     let run = Run {
         id: run_id,
-        flow_name: "test_flow".to_string().into(),
+        flow_name: FlowName::new("test_flow").unwrap(),
         event: {
             let mut m = HashMap::new();
             m.insert("key".to_string(), serde_json::json!("value"));
@@ -123,6 +135,11 @@ async fn test_all_operations_comprehensive() {
         started_at: Utc::now(),
         ended_at: None,
         steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
     };
 
     storage.save_run(&run).await.unwrap();
@@ -148,14 +165,17 @@ async fn test_all_operations_comprehensive() {
         run_id,
         step_name: "test_step".to_string().into(),
         status: StepStatus::Succeeded,
+        inputs: None,
         outputs: Some({
             let mut m = HashMap::new();
             m.insert("result".to_string(), serde_json::json!("success"));
             m
         }),
         error: None,
+        error_kind: None,
         started_at: Utc::now(),
         ended_at: Some(Utc::now()),
+        progress: None,
     };
 
     storage.save_step(&step).await.unwrap();
@@ -202,10 +222,13 @@ async fn test_save_step_for_non_existent_run() {
         run_id: Uuid::new_v4(), // Non-existent run
         step_name: "test_step".to_string().into(),
         status: StepStatus::Running,
+        inputs: None,
         outputs: Some(HashMap::new()),
         error: None,
+        error_kind: None,
         started_at: Utc::now(),
         ended_at: None,
+        progress: None,
     };
 
     // Should succeed (foreign key not enforced or gracefully handled)
@@ -222,13 +245,18 @@ async fn test_list_runs_multiple() {
     for i in 0..5 {
         let run = Run {
             id: Uuid::new_v4(),
-            flow_name: format!("flow_{}", i).into(),
+            flow_name: FlowName::new(format!("flow_{}", i)).unwrap(),
             event: HashMap::new(),
             vars: HashMap::new(),
             status: RunStatus::Succeeded,
             started_at: Utc::now(),
             ended_at: Some(Utc::now()),
             steps: None,
+            environment: None,
+            correlation_id: None,
+            labels: HashMap::new(),
+            usage: None,
+            tenant_id: None,
         };
         storage.save_run(&run).await.unwrap();
     }
@@ -297,6 +325,7 @@ async fn test_oauth_credential_list_and_delete() {
             scope: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            tenant_id: None,
         };
         storage.save_oauth_credential(&cred).await.unwrap();
     }
@@ -324,6 +353,7 @@ async fn test_oauth_credential_refresh() {
         scope: None,
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        tenant_id: None,
     };
 
     storage.save_oauth_credential(&cred).await.unwrap();
@@ -365,13 +395,18 @@ async fn test_multiple_steps_same_run() {
     let run_id = Uuid::new_v4();
     let run = Run {
         id: run_id,
-        flow_name: "multi_step_flow".to_string().into(),
+        flow_name: FlowName::new("multi_step_flow").unwrap(),
         event: HashMap::new(),
         vars: HashMap::new(),
         status: RunStatus::Running,
         started_at: Utc::now(),
         ended_at: None,
         steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
     };
     storage.save_run(&run).await.unwrap();
 
@@ -382,10 +417,13 @@ async fn test_multiple_steps_same_run() {
             run_id,
             step_name: format!("step_{}", i).into(),
             status: StepStatus::Succeeded,
+            inputs: None,
             outputs: Some(HashMap::new()),
             error: None,
+            error_kind: None,
             started_at: Utc::now(),
             ended_at: Some(Utc::now()),
+            progress: None,
         };
         storage.save_step(&step).await.unwrap();
     }
@@ -421,13 +459,18 @@ async fn test_auto_create_database_file() {
     // Verify it's functional - try to save a run
     let run = Run {
         id: Uuid::new_v4(),
-        flow_name: "test".to_string().into(),
+        flow_name: FlowName::new("test").unwrap(),
         event: HashMap::new(),
         vars: HashMap::new(),
         status: RunStatus::Running,
         started_at: Utc::now(),
         ended_at: None,
         steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
     };
 
     storage.save_run(&run).await.unwrap();
@@ -479,7 +522,11 @@ async fn test_reuse_existing_database() {
     {
         let storage = SqliteStorage::new(db_path_str).await.unwrap();
         storage
-            .deploy_flow_version("existing_flow", "1.0.0", "test content")
+            .deploy_flow_version(
+                &FlowName::new("existing_flow").unwrap(),
+                "1.0.0",
+                "test content",
+            )
             .await
             .unwrap();
     }
@@ -488,11 +535,14 @@ async fn test_reuse_existing_database() {
     let storage = SqliteStorage::new(db_path_str).await.unwrap();
 
     // Verify existing data is accessible
-    let version = storage.get_deployed_version("existing_flow").await.unwrap();
+    let version = storage
+        .get_deployed_version(&FlowName::new("existing_flow").unwrap())
+        .await
+        .unwrap();
     assert_eq!(version, Some("1.0.0".to_string()));
 
     let content = storage
-        .get_flow_version_content("existing_flow", "1.0.0")
+        .get_flow_version_content(&FlowName::new("existing_flow").unwrap(), "1.0.0")
         .await
         .unwrap();
     assert_eq!(content, Some("test content".to_string()));
@@ -519,13 +569,18 @@ async fn test_sqlite_prefix_handling() {
     // Verify it's functional - test with run operations
     let run = Run {
         id: Uuid::new_v4(),
-        flow_name: "test".to_string().into(),
+        flow_name: FlowName::new("test").unwrap(),
         event: HashMap::new(),
         vars: HashMap::new(),
         status: RunStatus::Running,
         started_at: Utc::now(),
         ended_at: None,
         steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
     };
     storage.save_run(&run).await.unwrap();
     let retrieved = storage.get_run(run.id).await.unwrap();
@@ -554,13 +609,18 @@ async fn test_concurrent_database_access() {
             let storage = SqliteStorage::new(&path).await.unwrap();
             let run = Run {
                 id: Uuid::new_v4(),
-                flow_name: format!("flow_{}", i).into(),
+                flow_name: FlowName::new(format!("flow_{}", i)).unwrap(),
                 event: HashMap::new(),
                 vars: HashMap::new(),
                 status: RunStatus::Running,
                 started_at: Utc::now(),
                 ended_at: None,
                 steps: None,
+                environment: None,
+                correlation_id: None,
+                labels: HashMap::new(),
+                usage: None,
+                tenant_id: None,
             };
             storage.save_run(&run).await.unwrap();
         });
@@ -584,13 +644,18 @@ async fn test_memory_database_still_works() {
 
     let run = Run {
         id: Uuid::new_v4(),
-        flow_name: "test".to_string().into(),
+        flow_name: FlowName::new("test").unwrap(),
         event: HashMap::new(),
         vars: HashMap::new(),
         status: RunStatus::Running,
         started_at: Utc::now(),
         ended_at: None,
         steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
     };
     storage.save_run(&run).await.unwrap();
     let runs = storage.list_runs(1000, 0).await.unwrap();
@@ -599,3 +664,44 @@ async fn test_memory_database_still_works() {
     // Memory databases should not create any files
     // (This is implicitly tested by the fact that no file path is involved)
 }
+
+#[tokio::test]
+async fn test_schema_version_mismatch_is_actionable() {
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("schema.db");
+    let db_path_str = db_path.to_str().unwrap();
+    drop(SqliteStorage::new(db_path_str).await.unwrap());
+
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", db_path_str))
+        .await
+        .unwrap();
+
+    // A migration from a newer binary is refused before anything is applied
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+         VALUES (99990101000001, 'from the future', TRUE, X'00', 0)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let err = SqliteStorage::new(db_path_str).await.err().unwrap();
+    assert!(err.to_string().contains("99990101000001"), "{}", err);
+    assert!(err.to_string().contains("upgrade BeemFlow"), "{}", err);
+
+    // So is a schema left behind by a migration that failed partway
+    sqlx::query("DELETE FROM _sqlx_migrations WHERE version = 99990101000001")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "UPDATE _sqlx_migrations SET success = FALSE
+         WHERE version = (SELECT MAX(version) FROM _sqlx_migrations)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let err = SqliteStorage::new(db_path_str).await.err().unwrap();
+    assert!(err.to_string().contains("failed partway"), "{}", err);
+}
@@ -5,8 +5,8 @@
 use crate::{BeemFlowError, Result, config::TracingConfig};
 use once_cell::sync::Lazy;
 use prometheus::{
-    CounterVec, Encoder, HistogramOpts, HistogramVec, TextEncoder, register_counter_vec,
-    register_histogram_vec,
+    CounterVec, Encoder, HistogramOpts, HistogramVec, IntGauge, IntGaugeVec, TextEncoder,
+    register_counter_vec, register_histogram_vec, register_int_gauge, register_int_gauge_vec,
 };
 
 /// HTTP requests total counter
@@ -63,6 +63,65 @@ static STEP_EXECUTIONS_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Executor tasks currently holding a concurrency permit
+static EXECUTOR_TASKS_IN_FLIGHT: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "beemflow_executor_tasks_in_flight",
+        "Number of parallel/foreach tasks currently running in the executor",
+        &["kind"]
+    )
+    .unwrap()
+});
+
+/// Executor tasks that had to wait for a concurrency permit
+static EXECUTOR_TASKS_WAITED_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "beemflow_executor_tasks_waited_total",
+        "Total number of parallel/foreach tasks that waited on max_concurrent_tasks",
+        &["kind"]
+    )
+    .unwrap()
+});
+
+/// Resources consumed by runs, per flow (see [`crate::model::RunUsage`])
+static RUN_USAGE_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "beemflow_run_usage_total",
+        "Resources consumed by runs: wall_time_seconds, http_requests, http_response_bytes, mcp_calls, llm_tokens",
+        &["flow", "resource"]
+    )
+    .unwrap()
+});
+
+/// Tool invocations made by runs, per flow and tool
+static RUN_TOOL_INVOCATIONS_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "beemflow_run_tool_invocations_total",
+        "Total number of tool invocations made by runs",
+        &["flow", "tool"]
+    )
+    .unwrap()
+});
+
+/// Events dropped for in-process subscribers that fell behind, per topic subscribed to
+static EVENTS_DROPPED_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "beemflow_events_dropped_total",
+        "Total number of events dropped for in-process event bus subscribers that fell behind",
+        &["topic"]
+    )
+    .unwrap()
+});
+
+/// Runs waiting in the run queue for an execution slot
+static RUN_QUEUE_DEPTH: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "beemflow_run_queue_depth",
+        "Number of runs waiting in the run queue for limits.maxConcurrentRuns"
+    )
+    .unwrap()
+});
+
 /// Initialize telemetry based on configuration
 ///
 /// Currently sets up Prometheus metrics (which are automatically registered via once_cell).
@@ -118,6 +177,76 @@ pub fn record_step_execution(flow_name: &str, step_name: &str, status: &str) {
         .inc();
 }
 
+/// Add the usage of one run execution to its flow's counters
+pub fn record_run_usage(flow_name: &str, usage: &crate::model::RunUsage) {
+    for (resource, amount) in [
+        ("wall_time_seconds", usage.wall_time_ms as f64 / 1000.0),
+        ("http_requests", usage.http_requests as f64),
+        ("http_response_bytes", usage.http_response_bytes as f64),
+        ("mcp_calls", usage.mcp_calls as f64),
+        ("llm_tokens", usage.llm_tokens as f64),
+    ] {
+        RUN_USAGE_TOTAL
+            .with_label_values(&[flow_name, resource])
+            .inc_by(amount);
+    }
+    for (tool, count) in &usage.tool_invocations {
+        RUN_TOOL_INVOCATIONS_TOTAL
+            .with_label_values(&[flow_name, tool])
+            .inc_by(*count as f64);
+    }
+}
+
+/// Record that an executor task had to wait for a concurrency permit
+pub fn record_task_waited(kind: &str) {
+    EXECUTOR_TASKS_WAITED_TOTAL.with_label_values(&[kind]).inc();
+}
+
+/// Number of executor tasks of this kind that waited for a concurrency permit
+pub fn tasks_waited(kind: &str) -> f64 {
+    EXECUTOR_TASKS_WAITED_TOTAL.with_label_values(&[kind]).get()
+}
+
+/// Record events dropped for a subscriber to `topic` that fell behind
+pub fn record_events_dropped(topic: &str, count: u64) {
+    EVENTS_DROPPED_TOTAL
+        .with_label_values(&[topic])
+        .inc_by(count as f64);
+}
+
+/// Number of events dropped for subscribers to `topic`
+pub fn events_dropped(topic: &str) -> f64 {
+    EVENTS_DROPPED_TOTAL.with_label_values(&[topic]).get()
+}
+
+/// Record runs entering (positive) or leaving (negative) the run queue
+pub fn record_run_queue_depth(delta: i64) {
+    RUN_QUEUE_DEPTH.add(delta);
+}
+
+/// Number of runs waiting in the run queue
+pub fn run_queue_depth() -> i64 {
+    RUN_QUEUE_DEPTH.get()
+}
+
+/// Mark an executor task as in flight until the returned guard is dropped
+pub fn track_task_in_flight(kind: &str) -> TaskInFlightGuard {
+    let gauge = EXECUTOR_TASKS_IN_FLIGHT.with_label_values(&[kind]);
+    gauge.inc();
+    TaskInFlightGuard { gauge }
+}
+
+/// Decrements the in-flight task gauge when dropped
+pub struct TaskInFlightGuard {
+    gauge: prometheus::IntGauge,
+}
+
+impl Drop for TaskInFlightGuard {
+    fn drop(&mut self) {
+        self.gauge.dec();
+    }
+}
+
 /// Get Prometheus metrics in text format
 pub fn get_metrics() -> Result<String> {
     let encoder = TextEncoder::new();
@@ -174,6 +303,8 @@ mod tests {
         record_flow_execution("test_flow", "success");
         record_flow_duration("test_flow", 1.5);
         record_step_execution("test_flow", "step1", "success");
+        record_task_waited("test");
+        drop(track_task_in_flight("test"));
 
         // Get metrics
         let metrics = get_metrics().unwrap();
@@ -184,6 +315,8 @@ mod tests {
         assert!(metrics.contains("beemflow_flow_executions_total"));
         assert!(metrics.contains("beemflow_flow_execution_duration_seconds"));
         assert!(metrics.contains("beemflow_step_executions_total"));
+        assert!(metrics.contains("beemflow_executor_tasks_waited_total"));
+        assert!(metrics.contains("beemflow_executor_tasks_in_flight"));
     }
 
     #[test]
@@ -2,11 +2,285 @@
 //!
 //! Common utilities used throughout BeemFlow.
 
+pub mod retry;
+
 use crate::config::{BlobConfig, Config};
 use crate::storage::SqliteStorage;
+use crate::{BeemFlowError, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use std::sync::Arc;
 use tempfile::TempDir;
 
+/// Parse a short duration like "90s", "30m", "24h" or "7d"
+pub fn parse_duration(value: &str) -> Result<chrono::Duration> {
+    let value = value.trim();
+    let invalid = || {
+        BeemFlowError::validation(format!(
+            "Invalid duration '{}': expected a positive number followed by s, m, h or d",
+            value
+        ))
+    };
+
+    let unit = value.chars().last().ok_or_else(invalid)?;
+    let amount: i64 = value[..value.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| invalid())?;
+    if amount <= 0 {
+        return Err(invalid());
+    }
+
+    let duration = match unit {
+        's' => chrono::TimeDelta::try_seconds(amount),
+        'm' => chrono::TimeDelta::try_minutes(amount),
+        'h' => chrono::TimeDelta::try_hours(amount),
+        'd' => chrono::TimeDelta::try_days(amount),
+        _ => return Err(invalid()),
+    };
+    duration.ok_or_else(|| BeemFlowError::validation(format!("Duration '{}' is too long", value)))
+}
+
+/// `at` moved by `duration` (backwards with `earlier`), or an error if that
+/// leaves the representable range
+pub fn shift_time(
+    at: chrono::DateTime<chrono::Utc>,
+    duration: chrono::Duration,
+    earlier: bool,
+) -> Result<chrono::DateTime<chrono::Utc>> {
+    let shifted = if earlier {
+        at.checked_sub_signed(duration)
+    } else {
+        at.checked_add_signed(duration)
+    };
+    shifted.ok_or_else(|| BeemFlowError::validation("Duration is too long"))
+}
+
+/// Render a unified diff between two texts, with three lines of context
+///
+/// Returns an empty string when the texts have the same lines.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    const CONTEXT: usize = 3;
+
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&a, &b);
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Group changes into hunks (op index ranges), merging overlapping context
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for i in changes {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let old_lines = |ops: &[DiffOp]| {
+        ops.iter()
+            .filter(|op| !matches!(op, DiffOp::Insert(_)))
+            .count()
+    };
+    let new_lines = |ops: &[DiffOp]| {
+        ops.iter()
+            .filter(|op| !matches!(op, DiffOp::Delete(_)))
+            .count()
+    };
+    let line_start = |before: usize, len: usize| if len == 0 { before } else { before + 1 };
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (start, end) in hunks {
+        let hunk = &ops[start..end];
+        let (old_len, new_len) = (old_lines(hunk), new_lines(hunk));
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            line_start(old_lines(&ops[..start]), old_len),
+            old_len,
+            line_start(new_lines(&ops[..start]), new_len),
+            new_len
+        ));
+        for op in hunk {
+            let line = match *op {
+                DiffOp::Equal(i) => format!(" {}", a[i]),
+                DiffOp::Delete(i) => format!("-{}", a[i]),
+                DiffOp::Insert(j) => format!("+{}", b[j]),
+            };
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// A line-level edit: index into the old (Equal/Delete) or new (Insert) lines
+enum DiffOp {
+    Equal(usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Line diff via longest common subsequence
+///
+/// Inputs too large for the quadratic table are reported as a full replacement.
+fn diff_lines(a: &[&str], b: &[&str]) -> Vec<DiffOp> {
+    const MAX_CELLS: usize = 4_000_000;
+
+    let (n, m) = (a.len(), b.len());
+    if n.saturating_mul(m) > MAX_CELLS {
+        return (0..n)
+            .map(DiffOp::Delete)
+            .chain((0..m).map(DiffOp::Insert))
+            .collect();
+    }
+
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            ops.push(DiffOp::Equal(i));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push(DiffOp::Delete(i));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Insert(j));
+            j += 1;
+        }
+    }
+    ops.extend((i..n).map(DiffOp::Delete));
+    ops.extend((j..m).map(DiffOp::Insert));
+    ops
+}
+
+/// Key pattern [`SecretMasker::default`] treats as sensitive (case-insensitive)
+pub const DEFAULT_SENSITIVE_KEYS: &str =
+    r"(?i)token|secret|passw(or)?d|api[_-]?key|authorization|cookie|credential|private[_-]?key";
+
+/// Masks secret values in JSON before it is shown to a user
+///
+/// String values (and strings in arrays) under object keys matching the
+/// sensitive-key pattern are replaced with [`REDACTED`](crate::secrets::REDACTED),
+/// at any depth. Objects under such keys are walked rather than wiped, so a
+/// parameter schema for an `api_key` field stays readable. Values that
+/// only reference a secret (`$env:NAME`, `$oauth:provider:integration`,
+/// `{{ secrets.NAME }}`) are kept, since they name the secret without revealing
+/// it. Keys ending in `url` or `uri` (e.g. `token_url`) are never masked.
+///
+/// ```
+/// use beemflow::utils::mask_secrets;
+/// use serde_json::json;
+///
+/// let masked = mask_secrets(&json!({
+///     "headers": {"Authorization": "Bearer sk-123", "Accept": "text/plain"},
+///     "env": {"API_KEY": "$env:API_KEY"},
+/// }));
+/// assert_eq!(masked["headers"]["Authorization"], "***REDACTED***");
+/// assert_eq!(masked["headers"]["Accept"], "text/plain");
+/// assert_eq!(masked["env"]["API_KEY"], "$env:API_KEY");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SecretMasker {
+    sensitive_keys: Regex,
+}
+
+impl Default for SecretMasker {
+    fn default() -> Self {
+        Self {
+            sensitive_keys: Regex::new(DEFAULT_SENSITIVE_KEYS)
+                .expect("default sensitive-key pattern is valid"),
+        }
+    }
+}
+
+impl SecretMasker {
+    /// Create a masker for keys matching `pattern` (a regular expression)
+    pub fn new(pattern: &str) -> Result<Self> {
+        let sensitive_keys = Regex::new(pattern).map_err(|e| {
+            BeemFlowError::validation(format!("Invalid sensitive-key pattern: {}", e))
+        })?;
+        Ok(Self { sensitive_keys })
+    }
+
+    /// Whether values under `key` are masked
+    pub fn is_sensitive_key(&self, key: &str) -> bool {
+        let lower = key.to_lowercase();
+        let is_location = lower.ends_with("url") || lower.ends_with("uri");
+        !is_location && self.sensitive_keys.is_match(key)
+    }
+
+    /// Return a copy of `value` with sensitive values masked
+    pub fn mask(&self, value: &serde_json::Value) -> serde_json::Value {
+        self.mask_under(value, false)
+    }
+
+    fn mask_under(&self, value: &serde_json::Value, sensitive: bool) -> serde_json::Value {
+        use serde_json::Value;
+
+        match value {
+            Value::String(s) if sensitive && !is_secret_reference(s) => {
+                Value::String(crate::secrets::REDACTED.to_string())
+            }
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.mask_under(item, sensitive))
+                    .collect(),
+            ),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| {
+                        let masked = self.mask_under(v, self.is_sensitive_key(k));
+                        (k.clone(), masked)
+                    })
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+/// Mask secret values in `value` with the default sensitive-key pattern
+pub fn mask_secrets(value: &serde_json::Value) -> serde_json::Value {
+    SecretMasker::default().mask(value)
+}
+
+/// A whole value that only references a secret, optionally after an auth scheme
+static SECRET_REFERENCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?:(?:Bearer|Basic|Token)\s+)?(?:\$env:[A-Za-z_][A-Za-z0-9_]*|\$oauth:[A-Za-z0-9_.-]+:[A-Za-z0-9_.-]+|\{\{\s*secrets\.[A-Za-z_][A-Za-z0-9_]*\s*\}\})$",
+    )
+    .expect("secret reference pattern is valid")
+});
+
+/// Whether a string references a secret (`$env:`, `$oauth:`, `secrets.`) rather than holding one
+///
+/// The whole value must be the reference, so a literal with one appended is still masked.
+fn is_secret_reference(value: &str) -> bool {
+    SECRET_REFERENCE.is_match(value.trim())
+}
+
 /// Test environment with isolated temporary directories (test builds only)
 ///
 /// This struct provides a complete, isolated test environment that mirrors production:
@@ -78,9 +352,23 @@ impl TestEnvironment {
         // Create config for test environment
         let config = Arc::new(Config {
             flows_dir: Some(beemflow_dir.join("flows").to_str().unwrap().to_string()),
+            registries: Some(vec![crate::config::RegistryConfig {
+                registry_type: "local".to_string(),
+                name: None,
+                url: None,
+                path: Some(
+                    beemflow_dir
+                        .join("registry.json")
+                        .to_str()
+                        .unwrap()
+                        .to_string(),
+                ),
+                api_key: None,
+            }]),
             blob: Some(BlobConfig {
                 driver: Some("filesystem".to_string()),
                 bucket: None,
+                region: None,
                 directory: Some(beemflow_dir.join("files").to_str().unwrap().to_string()),
             }),
             ..Default::default()
@@ -124,7 +412,7 @@ impl TestEnvironment {
 
         // Create registry manager for shared use
         let registry_manager = Arc::new(crate::registry::RegistryManager::standard(
-            None,
+            Some(&config),
             secrets_provider.clone(),
         ));
 
@@ -141,6 +429,7 @@ impl TestEnvironment {
             secrets_provider.clone(),
             config.clone(),
             oauth_client.clone(),
+            Arc::new(crate::event::InProcEventBus::new()),
             1000, // max_concurrent_tasks
         ));
 
@@ -163,6 +452,140 @@ impl TestEnvironment {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(
+            parse_duration("90s").unwrap(),
+            chrono::Duration::seconds(90)
+        );
+        assert_eq!(
+            parse_duration("30m").unwrap(),
+            chrono::Duration::minutes(30)
+        );
+        assert_eq!(parse_duration("24h").unwrap(), chrono::Duration::hours(24));
+        assert_eq!(parse_duration("7d").unwrap(), chrono::Duration::days(7));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("0h").is_err());
+        assert!(parse_duration("7w").is_err());
+        assert!(parse_duration("lots").is_err());
+
+        // Non-ASCII units and amounts are rejected rather than split mid-character
+        assert!(parse_duration("7é").is_err());
+        assert!(parse_duration("é").is_err());
+        assert!(parse_duration("٣d").is_err());
+
+        // Amounts beyond what a duration can hold are errors, not panics
+        assert!(parse_duration("9223372036854775807d").is_err());
+        assert!(parse_duration("99999999999999s").is_ok());
+    }
+
+    #[test]
+    fn test_shift_time_overflow() {
+        let now = chrono::Utc::now();
+        let long = parse_duration("99999999d").unwrap();
+        assert!(shift_time(now, long, false).is_err());
+        assert!(shift_time(now, long, true).is_err());
+        assert_eq!(
+            shift_time(now, chrono::Duration::days(1), true).unwrap(),
+            now - chrono::Duration::days(1)
+        );
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+
+        assert_eq!(
+            unified_diff(old, new, "raw", "rendered"),
+            "--- raw\n+++ rendered\n\
+             @@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -8,3 +8,4 @@\n h\n i\n j\n+k\n"
+        );
+        assert_eq!(unified_diff(old, old, "raw", "rendered"), "");
+        assert_eq!(
+            unified_diff("", "x\n", "raw", "rendered"),
+            "--- raw\n+++ rendered\n@@ -0,0 +1,1 @@\n+x\n"
+        );
+    }
+
+    #[test]
+    fn test_mask_secrets_masks_sensitive_keys_only() {
+        use serde_json::json;
+
+        let value = json!({
+            "name": "acme",
+            "headers": {
+                "Authorization": "Bearer sk-live-123",
+                "X-Api-Key": "k-123",
+                "Accept": "application/json"
+            },
+            "env": {"GITHUB_TOKEN": "ghp_abc", "DB_PASSWORD": "hunter2", "REGION": "eu"},
+            "client_secret": "cs-1",
+            "tokens": ["t-1", "t-2"],
+            "max_tokens": 512,
+            "token_url": "https://auth.example.com/token",
+            "credentials": [{"id": "c1", "access_token": "at-1"}]
+        });
+
+        let masked = mask_secrets(&value);
+        let redacted = crate::secrets::REDACTED;
+        assert_eq!(masked["headers"]["Authorization"], redacted);
+        assert_eq!(masked["headers"]["X-Api-Key"], redacted);
+        assert_eq!(masked["env"]["GITHUB_TOKEN"], redacted);
+        assert_eq!(masked["env"]["DB_PASSWORD"], redacted);
+        assert_eq!(masked["client_secret"], redacted);
+        assert_eq!(masked["tokens"], json!([redacted, redacted]));
+        assert_eq!(masked["credentials"][0]["access_token"], redacted);
+
+        assert_eq!(masked["name"], "acme");
+        assert_eq!(masked["headers"]["Accept"], "application/json");
+        assert_eq!(masked["env"]["REGION"], "eu");
+        assert_eq!(masked["max_tokens"], 512);
+        assert_eq!(masked["token_url"], "https://auth.example.com/token");
+        assert_eq!(masked["credentials"][0]["id"], "c1");
+    }
+
+    #[test]
+    fn test_mask_secrets_keeps_references_and_schemas() {
+        use serde_json::json;
+
+        let value = json!({
+            "headers": {"Authorization": "Bearer $env:ACME_KEY"},
+            "env": {"GITHUB": "$oauth:github:default", "TOKEN": "{{ secrets.TOKEN }}"},
+            "parameters": {
+                "properties": {"api_key": {"type": "string", "description": "Acme key"}},
+                "required": ["api_key"]
+            }
+        });
+
+        assert_eq!(mask_secrets(&value), value);
+
+        // A literal secret next to a reference is not a reference
+        let value = json!({"headers": {
+            "Authorization": "sk-live-123 $env:ACME_KEY",
+            "X-Api-Key": "$env:ACME_KEY sk-live-123",
+            "X-Token": "sk-live-123/secrets.TOKEN",
+            "Cookie": "$oauth:github:default;sk-live-123"
+        }});
+        let masked = mask_secrets(&value);
+        for header in ["Authorization", "X-Api-Key", "X-Token", "Cookie"] {
+            assert_eq!(masked["headers"][header], crate::secrets::REDACTED);
+        }
+    }
+
+    #[test]
+    fn test_secret_masker_custom_pattern() {
+        use serde_json::json;
+
+        let masker = SecretMasker::new("(?i)^session$").unwrap();
+        let masked = masker.mask(&json!({"session": "s-1", "token": "t-1"}));
+        assert_eq!(masked["session"], crate::secrets::REDACTED);
+        assert_eq!(masked["token"], "t-1");
+
+        assert!(SecretMasker::new("(unclosed").is_err());
+    }
+
     #[tokio::test]
     async fn test_environment_creates_structure() {
         let env = TestEnvironment::new().await;
@@ -170,14 +593,18 @@ mod tests {
         // Verify storage is functional
         env.deps
             .storage
-            .deploy_flow_version("test_flow", "1.0.0", "content")
+            .deploy_flow_version(
+                &crate::model::FlowName::new("test_flow").unwrap(),
+                "1.0.0",
+                "content",
+            )
             .await
             .expect("Should be able to write to database");
 
         let content = env
             .deps
             .storage
-            .get_flow_version_content("test_flow", "1.0.0")
+            .get_flow_version_content(&crate::model::FlowName::new("test_flow").unwrap(), "1.0.0")
             .await
             .expect("Should be able to read from database");
 
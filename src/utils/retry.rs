@@ -0,0 +1,129 @@
+//! Retrying fallible async operations with exponential backoff
+//!
+//! [`with_backoff`] is shared by the network clients that talk to flaky
+//! services (remote registries, OAuth token endpoints, remote storage) so they
+//! all retry the same way.
+
+use rand::Rng;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+/// How [`with_backoff`] retries a failing operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Factor the delay grows by after each retry
+    pub multiplier: f64,
+    /// Longest delay between two attempts
+    pub max_backoff: Duration,
+    /// Fraction of each delay that is randomized, from 0.0 (none) to 1.0
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            multiplier: 2.0,
+            max_backoff: Duration::from_secs(5),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that runs the operation once
+    pub fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retry number `retry` (1 for the first retry)
+    ///
+    /// Jitter shortens the delay by up to `jitter` of it, so retries from many
+    /// clients don't line up.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self
+            .initial_backoff
+            .mul_f64(self.multiplier.max(1.0).powi(exponent).min(1e9))
+            .min(self.max_backoff);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return delay;
+        }
+        delay.mul_f64(1.0 - jitter * rand::rng().random::<f64>())
+    }
+}
+
+/// Run `op`, retrying it while it fails with an error matched by `retry_if`
+///
+/// Gives up after `policy.max_attempts` attempts or on the first error
+/// `retry_if` rejects, returning that error.
+pub async fn with_backoff<T, E, F, Fut, P>(
+    policy: &RetryPolicy,
+    retry_if: P,
+    mut op: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E) -> bool,
+    E: Display,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < max_attempts && retry_if(&e) => {
+                let delay = policy.backoff(attempt);
+                tracing::debug!(
+                    "Attempt {}/{} failed, retrying in {:?}: {}",
+                    attempt,
+                    max_attempts,
+                    delay,
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Delay a `Retry-After` header value asks for, given as seconds or an HTTP date
+///
+/// Dates in the past mean no delay; values that are neither are ignored.
+pub fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&chrono::Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Whether a failed HTTP request may succeed if sent again
+///
+/// Connection failures, timeouts and `5xx` responses are transient; `4xx`
+/// responses and undecodable bodies are not.
+pub fn is_transient_http_error(e: &reqwest::Error) -> bool {
+    e.is_connect() || e.is_timeout() || e.status().is_some_and(|status| status.is_server_error())
+}
+
+#[cfg(test)]
+#[path = "retry_test.rs"]
+mod tests;
@@ -0,0 +1,124 @@
+use super::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+fn fast_policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::from_millis(1),
+        jitter: 0.0,
+        ..RetryPolicy::default()
+    }
+}
+
+#[tokio::test]
+async fn test_succeeds_after_retries() {
+    let calls = AtomicU32::new(0);
+    let result: Result<&str, String> = with_backoff(
+        &fast_policy(3),
+        |_| true,
+        || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err("unavailable".to_string()),
+                _ => Ok("done"),
+            }
+        },
+    )
+    .await;
+    assert_eq!(result, Ok("done"));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_attempts() {
+    let calls = AtomicU32::new(0);
+    let result: Result<(), String> = with_backoff(
+        &fast_policy(4),
+        |_| true,
+        || async {
+            let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Err(format!("failure {}", n))
+        },
+    )
+    .await;
+    assert_eq!(result, Err("failure 4".to_string()));
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_unmatched_error_is_not_retried() {
+    let calls = AtomicU32::new(0);
+    let result: Result<(), String> = with_backoff(
+        &fast_policy(5),
+        |e: &String| e.starts_with("transient"),
+        || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err("transient: reset".to_string()),
+                _ => Err("permanent: forbidden".to_string()),
+            }
+        },
+    )
+    .await;
+    assert_eq!(result, Err("permanent: forbidden".to_string()));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // A single-attempt policy never retries
+    calls.store(0, Ordering::SeqCst);
+    let result: Result<(), String> = with_backoff(
+        &RetryPolicy::no_retry(),
+        |_| true,
+        || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err("transient".to_string())
+        },
+    )
+    .await;
+    assert!(result.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_backoff_grows_caps_and_jitters() {
+    let policy = RetryPolicy {
+        initial_backoff: Duration::from_millis(100),
+        multiplier: 2.0,
+        max_backoff: Duration::from_millis(350),
+        jitter: 0.0,
+        ..RetryPolicy::default()
+    };
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff(3), Duration::from_millis(350));
+    assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(350));
+
+    let jittered = RetryPolicy {
+        jitter: 0.5,
+        ..policy
+    };
+    for _ in 0..100 {
+        let delay = jittered.backoff(2);
+        assert!(delay > Duration::from_millis(100) && delay <= Duration::from_millis(200));
+    }
+}
+
+#[test]
+fn test_parse_retry_after() {
+    let now = chrono::DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    assert_eq!(parse_retry_after("2", now), Some(Duration::from_secs(2)));
+    assert_eq!(
+        parse_retry_after(" 120 ", now),
+        Some(Duration::from_secs(120))
+    );
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+        Some(Duration::from_secs(30))
+    );
+    // A date already past means retry now
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(parse_retry_after("soon", now), None);
+    assert_eq!(parse_retry_after("-1", now), None);
+}
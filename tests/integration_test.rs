@@ -3,6 +3,8 @@
 //! Tests the complete system end-to-end
 
 use beemflow::dsl::{Validator, parse_file, parse_string};
+use beemflow::engine::resume::storage_key;
+use beemflow::model::FlowName;
 use beemflow::storage::{FlowStorage, RunStorage};
 use beemflow::{Engine, Flow};
 use std::collections::HashMap;
@@ -63,10 +65,25 @@ steps:
     use: core.echo
 "#;
 
-    let invalid_flow = parse_string(invalid_yaml, None).unwrap();
+    // Flow names are validated while parsing
+    let err = parse_string(invalid_yaml, None).unwrap_err();
+    assert!(err.to_string().contains("Flow name cannot be empty"));
 
-    // Validation should catch the empty name
-    assert!(Validator::validate(&invalid_flow).is_err());
+    // continue_on_error only applies to top-level steps
+    let nested_yaml = r#"
+name: test_flow
+on: cli.manual
+steps:
+  - id: fan_out
+    parallel: true
+    steps:
+      - id: notify
+        use: core.echo
+        continue_on_error: true
+"#;
+    let flow = parse_string(nested_yaml, None).unwrap();
+    let err = Validator::validate(&flow).unwrap_err();
+    assert!(err.to_string().contains("continue_on_error"), "{}", err);
 }
 
 #[tokio::test]
@@ -216,13 +233,13 @@ async fn test_storage_operations() {
     // Test flow versioning
     env.deps
         .storage
-        .deploy_flow_version("test_flow", "1.0.0", "content")
+        .deploy_flow_version(&FlowName::new("test_flow").unwrap(), "1.0.0", "content")
         .await
         .unwrap();
     let retrieved = env
         .deps
         .storage
-        .get_flow_version_content("test_flow", "1.0.0")
+        .get_flow_version_content(&FlowName::new("test_flow").unwrap(), "1.0.0")
         .await
         .unwrap();
     assert_eq!(retrieved.unwrap(), "content");
@@ -230,13 +247,18 @@ async fn test_storage_operations() {
     // Test run storage
     let run = beemflow::model::Run {
         id: Uuid::new_v4(),
-        flow_name: "test".to_string().into(),
+        flow_name: FlowName::new("test").unwrap(),
         event: HashMap::new(),
         vars: HashMap::new(),
         status: beemflow::model::RunStatus::Running,
         started_at: Utc::now(),
         ended_at: None,
         steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
     };
 
     env.deps.storage.save_run(&run).await.unwrap();
@@ -336,7 +358,11 @@ async fn test_cli_reuses_existing_database() {
     {
         let storage = SqliteStorage::new(db_path_str).await.unwrap();
         storage
-            .deploy_flow_version("persisted_flow", "1.0.0", "content")
+            .deploy_flow_version(
+                &FlowName::new("persisted_flow").unwrap(),
+                "1.0.0",
+                "content",
+            )
             .await
             .unwrap();
     }
@@ -345,7 +371,7 @@ async fn test_cli_reuses_existing_database() {
     {
         let storage = SqliteStorage::new(db_path_str).await.unwrap();
         let version = storage
-            .get_deployed_version("persisted_flow")
+            .get_deployed_version(&FlowName::new("persisted_flow").unwrap())
             .await
             .unwrap();
         assert_eq!(
@@ -388,13 +414,18 @@ async fn test_cli_with_missing_parent_directory() {
     use beemflow::model::{Run, RunStatus};
     let run = Run {
         id: uuid::Uuid::new_v4(),
-        flow_name: "test".to_string().into(),
+        flow_name: FlowName::new("test").unwrap(),
         event: std::collections::HashMap::new(),
         vars: std::collections::HashMap::new(),
         status: RunStatus::Running,
         started_at: chrono::Utc::now(),
         ended_at: None,
         steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
     };
     storage.save_run(&run).await.unwrap();
     let runs = storage.list_runs(1000, 0).await.unwrap();
@@ -670,7 +701,10 @@ steps:
         .unwrap();
 
     // Verify version 2.0.0 is deployed
-    let version = storage.get_deployed_version("rollback_flow").await.unwrap();
+    let version = storage
+        .get_deployed_version(&FlowName::new("rollback_flow").unwrap())
+        .await
+        .unwrap();
     assert_eq!(version, Some("2.0.0".to_string()));
 
     // Rollback to version 1.0.0
@@ -690,7 +724,10 @@ steps:
     );
 
     // Verify version 1.0.0 is now deployed
-    let version_after = storage.get_deployed_version("rollback_flow").await.unwrap();
+    let version_after = storage
+        .get_deployed_version(&FlowName::new("rollback_flow").unwrap())
+        .await
+        .unwrap();
     assert_eq!(version_after, Some("1.0.0".to_string()));
 
     // Try to rollback to non-existent version
@@ -751,7 +788,7 @@ steps:
 
     // Verify deployed
     let version = storage
-        .get_deployed_version("disable_enable_test")
+        .get_deployed_version(&FlowName::new("disable_enable_test").unwrap())
         .await
         .unwrap();
     assert_eq!(version, Some("1.0.0".to_string()));
@@ -769,7 +806,7 @@ steps:
 
     // Verify disabled
     let version_after_disable = storage
-        .get_deployed_version("disable_enable_test")
+        .get_deployed_version(&FlowName::new("disable_enable_test").unwrap())
         .await
         .unwrap();
     assert_eq!(version_after_disable, None, "Should be disabled");
@@ -801,7 +838,7 @@ steps:
 
     // Verify re-enabled with same version
     let version_after_enable = storage
-        .get_deployed_version("disable_enable_test")
+        .get_deployed_version(&FlowName::new("disable_enable_test").unwrap())
         .await
         .unwrap();
     assert_eq!(
@@ -901,7 +938,7 @@ steps:
 
     // Verify v2.0.0 is deployed
     let version = storage
-        .get_deployed_version("no_rollback_test")
+        .get_deployed_version(&FlowName::new("no_rollback_test").unwrap())
         .await
         .unwrap();
     assert_eq!(version, Some("2.0.0".to_string()));
@@ -929,7 +966,7 @@ steps:
         .unwrap();
 
     let version_after = storage
-        .get_deployed_version("no_rollback_test")
+        .get_deployed_version(&FlowName::new("no_rollback_test").unwrap())
         .await
         .unwrap();
     assert_eq!(
@@ -1022,6 +1059,162 @@ steps:
     );
 }
 
+#[tokio::test]
+async fn test_update_flow_patches_deployed_version() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps);
+
+    let flow_content = r#"name: greeter
+version: "1.0.0"
+on: cli.manual
+steps:
+  - id: greet
+    use: core.echo
+    with:
+      text: "hello""#;
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "greeter", "content": flow_content}),
+        )
+        .await
+        .unwrap();
+    registry
+        .execute("deploy_flow", serde_json::json!({"name": "greeter"}))
+        .await
+        .unwrap();
+
+    // Change a step input; the unchanged version moves to the next one
+    let result = registry
+        .execute(
+            "update_flow",
+            serde_json::json!({
+                "name": "greeter",
+                "patch": [
+                    {"op": "test", "path": "/steps/0/id", "value": "greet"},
+                    {"op": "replace", "path": "/steps/0/with/text", "value": "good morning"}
+                ]
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result["from_version"], "1.0.0");
+    assert_eq!(result["version"], "1.0.1");
+
+    let run = registry
+        .execute("start_run", serde_json::json!({"flow_name": "greeter"}))
+        .await
+        .unwrap();
+    assert_eq!(run["outputs"]["greet"]["text"], "good morning");
+
+    // Merge patches work too
+    let result = registry
+        .execute(
+            "update_flow",
+            serde_json::json!({"name": "greeter", "patch": {"description": "Greets", "version": "2.0.0"}}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result["version"], "2.0.0");
+
+    // A patch producing an invalid flow is rejected and nothing is deployed
+    for patch in [
+        serde_json::json!([{"op": "replace", "path": "/steps", "value": "not a list"}]),
+        serde_json::json!([{"op": "remove", "path": "/steps/0/id"}]),
+        serde_json::json!({"name": "renamed"}),
+    ] {
+        let err = registry
+            .execute(
+                "update_flow",
+                serde_json::json!({"name": "greeter", "patch": patch}),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, beemflow::BeemFlowError::Validation(_)),
+            "{:?}",
+            err
+        );
+    }
+    let history = registry
+        .execute("flow_history", serde_json::json!({"name": "greeter"}))
+        .await
+        .unwrap();
+    let mut versions: Vec<_> = history["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["version"].as_str().unwrap())
+        .collect();
+    versions.sort();
+    assert_eq!(versions, ["1.0.0", "1.0.1", "2.0.0"]);
+}
+
+#[tokio::test]
+async fn test_search_finds_newly_deployed_flows() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps);
+
+    let flow_content = r#"name: invoice_digest
+version: "1.0.0"
+description: Send the invoice digest
+on: cli.manual
+steps:
+  - id: announce
+    use: core.echo
+    with:
+      text: "Posting to the finance channel""#;
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "invoice_digest", "content": flow_content}),
+        )
+        .await
+        .unwrap();
+
+    // Drafts are not searched
+    let search = || registry.execute("search_flows", serde_json::json!({"q": "finance"}));
+    assert_eq!(search().await.unwrap()["results"], serde_json::json!([]));
+
+    registry
+        .execute("deploy_flow", serde_json::json!({"name": "invoice_digest"}))
+        .await
+        .unwrap();
+    let results = search().await.unwrap()["results"].clone();
+    assert_eq!(results[0]["flow_name"], "invoice_digest");
+    assert_eq!(results[0]["version"], "1.0.0");
+    assert_eq!(
+        results[0]["matches"][0]["field"],
+        "steps.announce.with.text"
+    );
+    assert_eq!(
+        results[0]["matches"][0]["snippet"],
+        "Posting to the **finance** channel"
+    );
+
+    registry
+        .execute(
+            "disable_flow",
+            serde_json::json!({"name": "invoice_digest"}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(search().await.unwrap()["results"], serde_json::json!([]));
+
+    assert!(
+        registry
+            .execute("search_flows", serde_json::json!({"q": "--"}))
+            .await
+            .is_err()
+    );
+}
+
 // ============================================================================
 // Flow Restore Tests
 // ============================================================================
@@ -1542,3 +1735,2684 @@ steps:
         "Second run should access previous run's save_message output"
     );
 }
+
+#[tokio::test]
+async fn test_runs_flow_reads_other_flow_latest_run() {
+    let import_yaml = r#"
+name: daily_import
+on: cli.manual
+steps:
+  - id: fetch
+    use: core.echo
+    with:
+      text: "rows for {{ event.day }}"
+"#;
+    let report_yaml = r#"
+name: daily_report
+on: cli.manual
+steps:
+  - id: report
+    use: core.echo
+    with:
+      text: |
+        {% if runs.flow("daily_import").latest.id %}
+        Imported: {{ runs.flow("daily_import").latest.steps.fetch.output.text }}
+        {% else %}
+        No import yet
+        {% endif %}
+"#;
+
+    let import_flow = parse_string(import_yaml, None).unwrap();
+    let report_flow = parse_string(report_yaml, None).unwrap();
+    let engine = Engine::for_testing().await;
+
+    let text = |result: &beemflow::engine::ExecutionResult| {
+        result.outputs["report"]["text"]
+            .as_str()
+            .unwrap()
+            .trim()
+            .to_string()
+    };
+
+    // Before the import has run
+    let mut event = HashMap::new();
+    event.insert("attempt".to_string(), serde_json::json!(1));
+    let before = engine.execute(&report_flow, event).await.unwrap();
+    assert_eq!(text(&before), "No import yet");
+
+    // After a successful import
+    let mut event = HashMap::new();
+    event.insert("day".to_string(), serde_json::json!("monday"));
+    engine.execute(&import_flow, event).await.unwrap();
+
+    let mut event = HashMap::new();
+    event.insert("attempt".to_string(), serde_json::json!(2));
+    let after = engine.execute(&report_flow, event).await.unwrap();
+    assert_eq!(text(&after), "Imported: rows for monday");
+}
+
+// ============================================================================
+// Event Publishing Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_publish_event_triggers_subscribed_flow() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps);
+
+    let flow_content = r#"name: order_listener
+version: "1.0.0"
+on: order.created
+steps:
+  - id: echo_order
+    use: core.echo
+    with:
+      text: "Order {{ event.order_id }}""#;
+
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({
+                "name": "order_listener",
+                "content": flow_content
+            }),
+        )
+        .await
+        .expect("Should save flow");
+    registry
+        .execute("deploy_flow", serde_json::json!({"name": "order_listener"}))
+        .await
+        .expect("Should deploy flow");
+
+    let result = registry
+        .execute(
+            "publish_event",
+            serde_json::json!({
+                "topic": "order.created",
+                "payload": {"order_id": 42}
+            }),
+        )
+        .await
+        .expect("Publishing should succeed");
+
+    let triggered = result["triggered"].as_array().unwrap();
+    assert_eq!(triggered.len(), 1, "Subscribed flow should be triggered");
+    assert_eq!(triggered[0]["flow_name"], "order_listener");
+
+    let run = registry
+        .execute(
+            "get_run",
+            serde_json::json!({"run_id": triggered[0]["run_id"]}),
+        )
+        .await
+        .expect("Triggered run should exist");
+    assert_eq!(run["flow_name"], "order_listener");
+    assert_eq!(run["event"]["order_id"], 42);
+}
+
+#[tokio::test]
+async fn test_publish_event_unsubscribed_topic_is_noop() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps);
+
+    let result = registry
+        .execute(
+            "publish_event",
+            serde_json::json!({"topic": "nobody.listens"}),
+        )
+        .await
+        .expect("Publishing to an unsubscribed topic should succeed");
+
+    assert_eq!(result["topic"], "nobody.listens");
+    assert!(result["triggered"].as_array().unwrap().is_empty());
+
+    let runs = registry
+        .execute("list_runs", serde_json::json!({}))
+        .await
+        .unwrap();
+    assert!(
+        runs["items"].as_array().unwrap().is_empty(),
+        "No runs should start"
+    );
+}
+
+#[tokio::test]
+async fn test_list_operations_paginate_with_cursors() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let storage = env.deps.storage.clone();
+    let registry = OperationRegistry::new(env.deps.clone());
+
+    let flow_content = "name: paged_flow\non: cli.manual\nsteps:\n  - id: greet\n    use: core.echo\n    with:\n      text: hi\n";
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "paged_flow", "content": flow_content}),
+        )
+        .await
+        .unwrap();
+    for i in 0..3 {
+        registry
+            .execute(
+                "start_run",
+                serde_json::json!({"flow_name": "paged_flow", "event": {"n": i}, "draft": true}),
+            )
+            .await
+            .unwrap();
+    }
+
+    let first = registry
+        .execute("list_runs", serde_json::json!({"limit": 2}))
+        .await
+        .unwrap();
+    assert_eq!(first["items"].as_array().unwrap().len(), 2);
+    let cursor = first["next_cursor"].as_str().expect("more runs remain");
+    let second = registry
+        .execute(
+            "list_runs",
+            serde_json::json!({"limit": 2, "cursor": cursor}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(second["items"].as_array().unwrap().len(), 1);
+    assert!(second["next_cursor"].is_null());
+    let mut ids: Vec<&str> = first["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .chain(second["items"].as_array().unwrap())
+        .map(|run| run["id"].as_str().unwrap())
+        .collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 3);
+
+    // Offsets still work and keep their bare array output
+    let legacy = registry
+        .execute("list_runs", serde_json::json!({"limit": 2, "offset": 1}))
+        .await
+        .unwrap();
+    assert_eq!(legacy.as_array().unwrap().len(), 2);
+
+    for input in [
+        serde_json::json!({"cursor": "not-a-cursor"}),
+        serde_json::json!({"cursor": cursor, "offset": 1}),
+    ] {
+        assert!(registry.execute("list_runs", input).await.is_err());
+    }
+
+    // Flow history pages the same way
+    let flow = beemflow::model::FlowName::new("paged_flow").unwrap();
+    for version in ["1.0.0", "2.0.0", "3.0.0"] {
+        storage
+            .deploy_flow_version(&flow, version, flow_content)
+            .await
+            .unwrap();
+    }
+    let mut versions = Vec::new();
+    let mut input = serde_json::json!({"name": "paged_flow", "limit": 2});
+    loop {
+        let page = registry
+            .execute("flow_history", input.clone())
+            .await
+            .unwrap();
+        versions.extend(
+            page["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| v["version"].as_str().unwrap().to_string()),
+        );
+        match page["next_cursor"].as_str() {
+            Some(cursor) => input["cursor"] = serde_json::json!(cursor),
+            None => break,
+        }
+    }
+    versions.sort();
+    assert_eq!(versions, ["1.0.0", "2.0.0", "3.0.0"]);
+}
+
+// ============================================================================
+// Run Statistics Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_runs_stats_operation() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps);
+
+    let flow_content = r#"name: stats_flow
+version: "1.0.0"
+on: cli.manual
+steps:
+  - id: greet
+    use: core.echo
+    with:
+      text: "hi""#;
+
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "stats_flow", "content": flow_content}),
+        )
+        .await
+        .expect("Should save flow");
+
+    for i in 0..2 {
+        registry
+            .execute(
+                "start_run",
+                serde_json::json!({"flow_name": "stats_flow", "event": {"n": i}, "draft": true}),
+            )
+            .await
+            .expect("Run should succeed");
+    }
+
+    let stats = registry
+        .execute("runs_stats", serde_json::json!({"window": "7d"}))
+        .await
+        .expect("Stats should succeed");
+
+    assert_eq!(stats["window"], "7d");
+    let flows = stats["flows"].as_array().unwrap();
+    assert_eq!(flows.len(), 1);
+    assert_eq!(flows[0]["flow_name"], "stats_flow");
+    assert_eq!(flows[0]["succeeded"], 2);
+    assert_eq!(flows[0]["success_rate"], 1.0);
+    assert_eq!(stats["totals"]["total"], 2);
+
+    let err = registry
+        .execute("runs_stats", serde_json::json!({"window": "lots"}))
+        .await;
+    assert!(err.is_err(), "Invalid window should be rejected");
+
+    // Windows reaching past the representable range are errors, not panics
+    for window in ["99999999d", "7é"] {
+        let err = registry
+            .execute("runs_stats", serde_json::json!({"window": window}))
+            .await;
+        assert!(err.is_err(), "Window '{}' should be rejected", window);
+    }
+}
+
+#[tokio::test]
+async fn test_flow_bundle_export_import() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::core::flows::{BundleImport, import_bundle};
+    use beemflow::registry::RegistryEntry;
+    use beemflow::utils::TestEnvironment;
+
+    let cli_import = |overwrite| BundleImport {
+        all_entry_types: true,
+        overwrite,
+    };
+    let source = TestEnvironment::new().await;
+
+    // A custom tool that authenticates through the GitHub OAuth provider
+    let tool: RegistryEntry = serde_json::from_value(serde_json::json!({
+        "type": "tool",
+        "name": "acme.lookup",
+        "endpoint": "https://acme.example.com/lookup",
+        "method": "GET",
+        "headers": {"Authorization": "$oauth:github:default", "X-Api-Key": "sk-live-123"}
+    }))
+    .unwrap();
+    source
+        .deps
+        .registry_manager
+        .upsert_local_entry(tool)
+        .await
+        .unwrap();
+
+    let source_ops = OperationRegistry::new(source.deps.clone());
+    let flow_content = r#"name: bundled
+version: "1.0.0"
+on: cli.manual
+steps:
+  - id: lookup
+    use: acme.lookup
+  - id: fanout
+    parallel: true
+    steps:
+      - id: records
+        use: mcp://airtable/list_records
+      - id: echo
+        use: core.echo
+        with:
+          text: "done"
+  - id: unknown
+    use: nowhere.tool"#;
+    source_ops
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "bundled", "content": flow_content}),
+        )
+        .await
+        .expect("Should save flow");
+
+    let bundle = source_ops
+        .execute("export_flow_bundle", serde_json::json!({"name": "bundled"}))
+        .await
+        .expect("Export should succeed");
+
+    let entries: Vec<(&str, &str)> = bundle["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| (e["name"].as_str().unwrap(), e["type"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("acme.lookup", "tool"),
+            ("airtable", "mcp_server"),
+            ("github", "oauth_provider"),
+        ]
+    );
+    assert_eq!(bundle["missing"], serde_json::json!(["nowhere.tool"]));
+    assert_eq!(bundle["content"], flow_content);
+
+    // Import into a fresh environment
+    let target = TestEnvironment::new().await;
+    assert!(
+        target
+            .deps
+            .registry_manager
+            .get_server("acme.lookup")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // Literal secrets are masked on export; references are kept
+    let tool_entry = &bundle["entries"][0];
+    assert_eq!(
+        tool_entry["headers"]["Authorization"],
+        "$oauth:github:default"
+    );
+    assert_ne!(tool_entry["headers"]["X-Api-Key"], "sk-live-123");
+
+    // Over HTTP and MCP only tools are imported, and never from a file
+    let target_ops = OperationRegistry::new(target.deps.clone());
+    let err = target_ops
+        .execute("import_flow_bundle", serde_json::json!({"bundle": bundle}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("imported with the CLI"), "{}", err);
+    assert!(
+        target_ops
+            .execute(
+                "import_flow_bundle",
+                serde_json::json!({"file": "/tmp/bundle.json"})
+            )
+            .await
+            .is_err()
+    );
+    let mut tools_only = bundle.clone();
+    tools_only["entries"] = serde_json::json!([tool_entry]);
+    let result = target_ops
+        .execute(
+            "import_flow_bundle",
+            serde_json::json!({"bundle": tools_only}),
+        )
+        .await
+        .expect("Import of tools should succeed");
+    assert_eq!(result["status"], "created");
+    assert_eq!(result["name"], "bundled");
+    assert_eq!(result["entries"], serde_json::json!(["acme.lookup"]));
+
+    // The CLI accepts every entry type; entries the target's registries
+    // already have unchanged (here all of them) are left alone
+    let full: beemflow::core::flows::flows::FlowBundle =
+        serde_json::from_value(bundle.clone()).unwrap();
+    let result = import_bundle(&target.deps, full.clone(), cli_import(false))
+        .await
+        .expect("CLI import should succeed");
+    assert_eq!(result.status, "updated");
+    assert!(result.entries.is_empty(), "{:?}", result.entries);
+
+    // An entry that differs is only replaced with --overwrite
+    let mut changed = full;
+    changed.entries[0].endpoint = Some("https://evil.example.com".to_string());
+    let err = import_bundle(&target.deps, changed.clone(), cli_import(false))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("--overwrite"), "{}", err);
+    let result = import_bundle(&target.deps, changed, cli_import(true))
+        .await
+        .expect("Overwrite should succeed");
+    assert_eq!(result.entries, vec!["acme.lookup"]);
+
+    let restored = target
+        .deps
+        .registry_manager
+        .get_server("acme.lookup")
+        .await
+        .unwrap()
+        .expect("Tool should be registered");
+    assert_eq!(
+        restored.endpoint.as_deref(),
+        Some("https://evil.example.com")
+    );
+    assert_eq!(restored.registry.as_deref(), Some("local"));
+
+    let flow = target_ops
+        .execute("get_flow", serde_json::json!({"name": "bundled"}))
+        .await
+        .expect("Flow should be saved");
+    assert_eq!(flow["content"], flow_content);
+
+    // Bundles from an unknown format version are rejected
+    let mut future = bundle.clone();
+    future["bundle_version"] = serde_json::json!(99);
+    assert!(
+        target_ops
+            .execute("import_flow_bundle", serde_json::json!({"bundle": future}))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_remote_storage_backed_by_another_instance() {
+    use beemflow::config::{Config, StorageConfig};
+    use beemflow::core::{OperationRegistry, create_dependencies};
+    use beemflow::model::RunStatus;
+    use beemflow::storage::{RemoteStorage, StateStorage, remote};
+    use beemflow::utils::TestEnvironment;
+
+    // Instance A serves its SQLite storage over the remote storage protocol
+    let backing = TestEnvironment::new().await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let dsn = format!("http://{}/storage", listener.local_addr().unwrap());
+    let router = axum::Router::new().nest("/storage", remote::serve(backing.deps.storage.clone()));
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    // Instance B uses instance A for all persistence
+    let temp = tempfile::TempDir::new().unwrap();
+    let config = Config {
+        storage: StorageConfig {
+            driver: "remote".to_string(),
+            dsn: dsn.clone(),
+            pool: None,
+            integrity_chain: false,
+            flow_cache: None,
+        },
+        flows_dir: Some(temp.path().join("flows").to_str().unwrap().to_string()),
+        ..Default::default()
+    };
+    let registry = OperationRegistry::new(create_dependencies(&config).await.unwrap());
+
+    let flow_content = r#"name: remote_lifecycle
+version: "1.0.0"
+on: cli.manual
+vars:
+  token: "remote-token"
+steps:
+  - id: greet
+    use: core.echo
+    with:
+      text: "hello"
+  - id: wait
+    await_event:
+      source: remote_test
+      match:
+        token: "{{ token }}"
+      timeout: 1h
+  - id: done
+    use: core.echo
+    with:
+      text: "resumed with {{ event.value }}""#;
+
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "remote_lifecycle", "content": flow_content}),
+        )
+        .await
+        .unwrap();
+    registry
+        .execute(
+            "deploy_flow",
+            serde_json::json!({"name": "remote_lifecycle"}),
+        )
+        .await
+        .unwrap();
+
+    // The deployment lives in instance A
+    let flow_name = FlowName::new("remote_lifecycle").unwrap();
+    assert!(
+        backing
+            .deps
+            .storage
+            .get_deployed_version(&flow_name)
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    // The run pauses; its paused state is stored in instance A
+    let _ = registry
+        .execute(
+            "start_run",
+            serde_json::json!({"flow_name": "remote_lifecycle", "event": {}}),
+        )
+        .await;
+    let paused = backing
+        .deps
+        .storage
+        .find_paused_runs_by_source("remote_test")
+        .await
+        .unwrap();
+    assert_eq!(paused.len(), 1);
+    assert_eq!(paused[0].0, storage_key("remote-token"));
+
+    registry
+        .execute(
+            "resume_run",
+            serde_json::json!({"token": "remote-token", "event": {"value": 42}}),
+        )
+        .await
+        .unwrap();
+
+    let runs = backing.deps.storage.list_runs(10, 0).await.unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].status, RunStatus::Succeeded);
+    let steps = backing.deps.storage.get_steps(runs[0].id).await.unwrap();
+    let done = steps
+        .iter()
+        .find(|s| s.step_name.as_str() == "done")
+        .unwrap();
+    assert_eq!(done.outputs.as_ref().unwrap()["text"], "resumed with 42");
+
+    // Reads through the driver see the same data, and backend errors come back
+    let remote = RemoteStorage::new(&dsn).unwrap();
+    assert_eq!(
+        remote.get_run(runs[0].id).await.unwrap().unwrap().id,
+        runs[0].id
+    );
+    assert!(
+        remote
+            .get_run(uuid::Uuid::new_v4())
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(remote.load_paused_runs().await.unwrap().is_empty());
+    assert!(
+        remote
+            .set_deployed_version(&flow_name, "missing")
+            .await
+            .is_err()
+    );
+
+    assert!(RemoteStorage::new("ftp://example.com").is_err());
+}
+
+#[tokio::test]
+async fn test_system_check_reports_each_subsystem() {
+    use beemflow::config::RegistryConfig;
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+    use std::sync::Arc;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/registry.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/gone.json"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let env = TestEnvironment::new().await;
+    let check = |config: beemflow::config::Config| {
+        let mut deps = env.deps.clone();
+        deps.config = Arc::new(config);
+        async move {
+            OperationRegistry::new(deps)
+                .execute("system_check", serde_json::json!({"timeout_secs": 5}))
+                .await
+        }
+    };
+    let with_registry = |file: &str| {
+        let mut config = (*env.deps.config).clone();
+        config
+            .registries
+            .get_or_insert_with(Vec::new)
+            .push(RegistryConfig {
+                registry_type: "remote".to_string(),
+                name: Some("hub".to_string()),
+                url: Some(format!("{}/{}", server.uri(), file)),
+                path: None,
+                api_key: None,
+            });
+        config
+    };
+
+    // Everything answers
+    let report = check(with_registry("registry.json")).await.unwrap();
+    assert_eq!(report["degraded"], false);
+    let names: Vec<_> = report["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| {
+            assert_eq!(c["ok"], true, "{} failed: {}", c["name"], c["error"]);
+            assert!(c["latency_ms"].is_u64());
+            c["name"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(names, ["storage", "blob", "event_bus", "registry:hub"]);
+
+    // A failing remote registry degrades the report without failing it
+    let report = check(with_registry("gone.json")).await.unwrap();
+    assert_eq!(report["degraded"], true);
+    let registry = &report["checks"][3];
+    assert_eq!(registry["ok"], false);
+    assert_eq!(registry["critical"], false);
+    assert!(registry["error"].as_str().unwrap().contains("404"));
+
+    // A blob store that can't be written fails the check
+    let not_a_dir = tempfile::NamedTempFile::new().unwrap();
+    let mut config = (*env.deps.config).clone();
+    config.blob.as_mut().unwrap().directory = Some(not_a_dir.path().display().to_string());
+    let err = check(config).await.unwrap_err().to_string();
+    assert!(err.contains("blob ("), "{}", err);
+    assert!(!err.contains("storage ("), "{}", err);
+}
+
+#[tokio::test]
+async fn test_describe_tool_masks_secrets() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::registry::RegistryEntry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let entries = [
+        serde_json::json!({
+            "type": "tool",
+            "name": "acme.search",
+            "description": "Search Acme records",
+            "version": "2.1.0",
+            "endpoint": "https://acme.example.com/search",
+            "method": "POST",
+            "headers": {
+                "Authorization": "Bearer sk-live-123",
+                "X-Api-Key": "$env:ACME_KEY",
+                "Content-Type": "application/json"
+            },
+            "parameters": {
+                "type": "object",
+                "properties": {"query": {"type": "string"}},
+                "required": ["query"]
+            }
+        }),
+        serde_json::json!({
+            "type": "mcp_server",
+            "name": "acme-mcp",
+            "command": "npx",
+            "args": ["-y", "acme-mcp"],
+            "env": {"ACME_TOKEN": "tok-literal", "ACME_REGION": "eu", "GITHUB": "$oauth:github:default"}
+        }),
+    ];
+    for entry in entries {
+        let entry: RegistryEntry = serde_json::from_value(entry).unwrap();
+        env.deps
+            .registry_manager
+            .upsert_local_entry(entry)
+            .await
+            .unwrap();
+    }
+    let registry = OperationRegistry::new(env.deps.clone());
+
+    let tool = registry
+        .execute("describe_tool", serde_json::json!({"name": "acme.search"}))
+        .await
+        .expect("Should describe HTTP tool");
+    assert_eq!(tool["endpoint"], "https://acme.example.com/search");
+    assert_eq!(tool["method"], "POST");
+    assert_eq!(tool["version"], "2.1.0");
+    assert_eq!(tool["parameters"]["required"], serde_json::json!(["query"]));
+    assert_eq!(tool["headers"]["Authorization"], "***REDACTED***");
+    assert_eq!(tool["headers"]["X-Api-Key"], "$env:ACME_KEY");
+    assert_eq!(tool["headers"]["Content-Type"], "application/json");
+    assert_eq!(tool["requires_oauth"], false);
+
+    let server = registry
+        .execute("describe_tool", serde_json::json!({"name": "acme-mcp"}))
+        .await
+        .expect("Should describe MCP server");
+    assert_eq!(server["type"], "mcp_server");
+    assert_eq!(server["command"], "npx");
+    assert_eq!(server["env"]["ACME_TOKEN"], "***REDACTED***");
+    assert_eq!(server["env"]["ACME_REGION"], "eu");
+    assert_eq!(server["requires_oauth"], true);
+    assert!(!server.to_string().contains("tok-literal"));
+
+    // Listings and raw manifests are masked the same way
+    for (op, input) in [
+        ("list_tools", serde_json::json!({})),
+        (
+            "get_tool_manifest",
+            serde_json::json!({"name": "acme.search"}),
+        ),
+        ("search_tools", serde_json::json!({"query": "acme"})),
+        ("list_mcp_servers", serde_json::json!({})),
+        ("search_mcp_servers", serde_json::json!({"query": "acme"})),
+    ] {
+        let output = registry.execute(op, input).await.unwrap().to_string();
+        assert!(!output.contains("sk-live-123"), "{} leaked a header", op);
+        assert!(
+            !output.contains("tok-literal"),
+            "{} leaked an env value",
+            op
+        );
+    }
+    let manifest = registry
+        .execute(
+            "get_tool_manifest",
+            serde_json::json!({"name": "acme.search"}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(manifest["headers"]["X-Api-Key"], "$env:ACME_KEY");
+
+    // The pre-describe response of GET /tools/{name} stays available
+    let legacy = registry
+        .execute(
+            "describe_tool",
+            serde_json::json!({"name": "acme.search", "format": "manifest"}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(legacy, manifest);
+
+    let missing = registry
+        .execute("describe_tool", serde_json::json!({"name": "nope.tool"}))
+        .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_install_tool_from_manifest_and_url() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps.clone());
+
+    // Inline manifest
+    let installed = registry
+        .execute(
+            "install_tool",
+            serde_json::json!({"manifest": {
+                "type": "tool",
+                "name": "acme.ping",
+                "endpoint": "https://acme.example.com/ping",
+                "method": "GET",
+                "headers": {"Authorization": "Bearer sk-live-123"}
+            }}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(installed["source"], "manifest");
+    assert_eq!(installed["entries"][0]["name"], "acme.ping");
+    assert_eq!(installed["entries"][0]["registry"], "local");
+    assert_ne!(
+        installed["entries"][0]["headers"]["Authorization"],
+        "Bearer sk-live-123"
+    );
+
+    // Manifest and OpenAPI spec served over HTTP
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/manifest.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "type": "tool",
+            "name": "acme.lookup",
+            "endpoint": "https://acme.example.com/lookup",
+            "method": "POST"
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/openapi.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "pets"},
+            "servers": [{"url": "https://pets.example.com"}],
+            "paths": {
+                "/pets": {
+                    "get": {"summary": "List pets"},
+                    "post": {"summary": "Add a pet"}
+                }
+            }
+        })))
+        .mount(&server)
+        .await;
+
+    let installed = registry
+        .execute(
+            "install_tool",
+            serde_json::json!({"from_url": format!("{}/manifest.json", server.uri())}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(installed["source"], "url");
+    assert_eq!(installed["entries"][0]["name"], "acme.lookup");
+
+    let installed = registry
+        .execute(
+            "install_tool",
+            serde_json::json!({"from_url": format!("{}/openapi.json", server.uri())}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(installed["source"], "openapi");
+    let mut names: Vec<_> = installed["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["name"].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["pets.pets_get", "pets.pets_post"]);
+
+    // Everything installed is visible through the registry
+    for name in [
+        "acme.ping",
+        "acme.lookup",
+        "pets.pets_get",
+        "pets.pets_post",
+    ] {
+        let tool = registry
+            .execute("get_tool_manifest", serde_json::json!({"name": name}))
+            .await
+            .unwrap();
+        assert_eq!(tool["registry"], "local", "{} not installed locally", name);
+    }
+
+    // Invalid manifests are rejected without being written
+    for manifest in [
+        serde_json::json!({"type": "mcp_server", "name": "acme.bad", "endpoint": "https://x.example.com"}),
+        serde_json::json!({"type": "tool", "name": "acme.bad"}),
+        serde_json::json!({"type": "tool", "name": "acme.bad", "endpoint": "ftp://x.example.com"}),
+    ] {
+        let err = registry
+            .execute("install_tool", serde_json::json!({"manifest": manifest}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid tool manifest"), "{}", err);
+    }
+    assert!(
+        registry
+            .execute("get_tool_manifest", serde_json::json!({"name": "acme.bad"}))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_search_tools_fuzzy_ranking() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::registry::RegistryEntry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    for (name, description) in [
+        ("acmehub.create_ticket", "Create a ticket in AcmeHub"),
+        ("acmehub.list_tickets", "List AcmeHub tickets"),
+        ("acmelab.deploy", "Deploy with AcmeLab"),
+        ("weather.forecast", "Get the forecast"),
+    ] {
+        let entry: RegistryEntry = serde_json::from_value(serde_json::json!({
+            "type": "tool",
+            "name": name,
+            "description": description,
+            "endpoint": "https://example.com",
+        }))
+        .unwrap();
+        env.deps
+            .registry_manager
+            .upsert_local_entry(entry)
+            .await
+            .unwrap();
+    }
+    let registry = OperationRegistry::new(env.deps.clone());
+
+    // A near-miss of a default registry tool name still finds it, best first
+    let result = registry
+        .execute("search_tools", serde_json::json!({"query": "githb"}))
+        .await
+        .unwrap();
+    let tools = result["tools"].as_array().unwrap();
+    assert!(!tools.is_empty(), "githb should match github tools");
+    assert!(tools[0]["name"].as_str().unwrap().starts_with("github."));
+    let scores: Vec<f64> = tools.iter().map(|t| t["score"].as_f64().unwrap()).collect();
+    assert!(scores.windows(2).all(|w| w[0] >= w[1]));
+
+    // Closer matches outrank looser ones, unrelated tools are dropped
+    let result = registry
+        .execute(
+            "search_tools",
+            serde_json::json!({"query": "acmehb", "limit": 10}),
+        )
+        .await
+        .unwrap();
+    let names: Vec<&str> = result["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        &names[..2],
+        ["acmehub.create_ticket", "acmehub.list_tickets"]
+    );
+    let lab = names.iter().position(|n| *n == "acmelab.deploy");
+    assert!(lab.is_none_or(|i| i >= 2));
+    assert!(!names.contains(&"weather.forecast"));
+
+    // Substring matches still work and score highest; limit caps the results
+    let result = registry
+        .execute(
+            "search_tools",
+            serde_json::json!({"query": "ticket", "limit": 1}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result["tools"].as_array().unwrap().len(), 1);
+    assert_eq!(result["tools"][0]["score"], 1.0);
+    assert!(result["total"].as_u64().unwrap() >= 2);
+
+    // GET /tools?q= runs the same search
+    let listed = registry
+        .execute("list_tools", serde_json::json!({"q": "acmehb", "limit": 2}))
+        .await
+        .unwrap();
+    let listed: Vec<&str> = listed["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(listed, ["acmehub.create_ticket", "acmehub.list_tickets"]);
+}
+
+#[tokio::test]
+async fn test_flow_environments_overlay_vars_and_record_run() {
+    use beemflow::config::{Config, StorageConfig};
+    use beemflow::core::{OperationRegistry, create_dependencies};
+
+    let temp = tempfile::TempDir::new().unwrap();
+    let config = Config {
+        storage: StorageConfig {
+            driver: "sqlite".to_string(),
+            dsn: temp.path().join("flow.db").to_str().unwrap().to_string(),
+            pool: None,
+            integrity_chain: false,
+            flow_cache: None,
+        },
+        flows_dir: Some(temp.path().join("flows").to_str().unwrap().to_string()),
+        default_environment: Some("staging".to_string()),
+        ..Default::default()
+    };
+    let registry = OperationRegistry::new(create_dependencies(&config).await.unwrap());
+
+    let flow_content = r##"name: notify
+version: "1.0.0"
+on: cli.manual
+vars:
+  channel: "#dev"
+  region: eu
+environments:
+  staging:
+    vars:
+      channel: "#staging"
+  prod:
+    vars:
+      channel: "#alerts"
+      typo_var: true
+steps:
+  - id: post
+    use: core.echo
+    with:
+      text: "{{ vars.channel }} in {{ vars.region }}""##;
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "notify", "content": flow_content}),
+        )
+        .await
+        .unwrap();
+
+    // Validation flags overlay vars missing from the base
+    let validated = registry
+        .execute("validate_flow", serde_json::json!({"name": "notify"}))
+        .await
+        .unwrap();
+    let warnings = validated["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].as_str().unwrap().contains("typo_var"));
+
+    // Deploy stores the full flow; unknown environments are rejected
+    let deployed = registry
+        .execute(
+            "deploy_flow",
+            serde_json::json!({"name": "notify", "environment": "prod"}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(deployed["environment"], "prod");
+    assert!(
+        registry
+            .execute(
+                "deploy_flow",
+                serde_json::json!({"name": "notify", "environment": "qa"}),
+            )
+            .await
+            .is_err()
+    );
+
+    // Explicit environment: overlay wins, base vars remain
+    let started = registry
+        .execute(
+            "start_run",
+            serde_json::json!({"flow_name": "notify", "environment": "prod", "event": {"n": 1}}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(started["outputs"]["post"]["text"], "#alerts in eu");
+    let run = registry
+        .execute("get_run", serde_json::json!({"run_id": started["run_id"]}))
+        .await
+        .unwrap();
+    assert_eq!(run["environment"], "prod");
+    assert_eq!(run["vars"]["channel"], "#alerts");
+
+    // No environment requested: config defaultEnvironment applies
+    let started = registry
+        .execute(
+            "start_run",
+            serde_json::json!({"flow_name": "notify", "event": {"n": 2}}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(started["outputs"]["post"]["text"], "#staging in eu");
+    let run = registry
+        .execute("get_run", serde_json::json!({"run_id": started["run_id"]}))
+        .await
+        .unwrap();
+    assert_eq!(run["environment"], "staging");
+
+    // Requesting an environment the flow doesn't define fails
+    let err = registry
+        .execute(
+            "start_run",
+            serde_json::json!({"flow_name": "notify", "environment": "qa", "event": {"n": 3}}),
+        )
+        .await;
+    assert!(err.is_err());
+}
+
+#[tokio::test]
+async fn test_manual_runs_inherit_trigger_defaults() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps.clone());
+
+    let flow_content = r##"name: defaulted
+on:
+  - cli.manual
+  - event: order.created
+    defaults:
+      region: eu
+      priority: low
+input_schema:
+  type: object
+  required: [region, order_id]
+steps:
+  - id: route
+    use: core.echo
+    with:
+      text: "{{ event.order_id }} {{ event.region }} {{ event.priority }}""##;
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "defaulted", "content": flow_content}),
+        )
+        .await
+        .unwrap();
+
+    // Fields the event leaves out come from the defaults; given ones win
+    let started = registry
+        .execute(
+            "start_run",
+            serde_json::json!({
+                "flow_name": "defaulted",
+                "draft": true,
+                "event": {"order_id": "o-1", "priority": "high"}
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(started["outputs"]["route"]["text"], "o-1 eu high");
+
+    // Defaults don't stand in for fields the trigger doesn't default
+    assert!(
+        registry
+            .execute(
+                "start_run",
+                serde_json::json!({"flow_name": "defaulted", "draft": true, "event": {"priority": "high"}}),
+            )
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_stage_keyed_vars_resolve_for_the_run_environment() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps.clone());
+
+    let flow_content = r##"name: staged
+on: cli.manual
+vars:
+  api_url:
+    dev: http://localhost:8080
+    prod: https://api.example.com
+    default: https://staging.example.com
+environments:
+  dev: {}
+  prod: {}
+steps:
+  - id: call
+    use: core.echo
+    with:
+      text: "{{ vars.api_url }}""##;
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "staged", "content": flow_content}),
+        )
+        .await
+        .unwrap();
+
+    let text_in = |environment: Option<&str>, n: u32| {
+        let registry = &registry;
+        let mut input =
+            serde_json::json!({"flow_name": "staged", "draft": true, "event": {"n": n}});
+        if let Some(environment) = environment {
+            input["environment"] = serde_json::json!(environment);
+        }
+        async move {
+            let started = registry.execute("start_run", input).await.unwrap();
+            started["outputs"]["call"]["text"].clone()
+        }
+    };
+
+    // Switching the stage switches the value; no stage falls back to `default`
+    assert_eq!(text_in(Some("dev"), 1).await, "http://localhost:8080");
+    assert_eq!(text_in(Some("prod"), 2).await, "https://api.example.com");
+    assert_eq!(text_in(None, 3).await, "https://staging.example.com");
+
+    // A stage `environments` doesn't declare is unknown
+    assert!(
+        registry
+            .execute(
+                "start_run",
+                serde_json::json!({"flow_name": "staged", "draft": true, "environment": "qa", "event": {"n": 4}}),
+            )
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_list_operations_parity() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps.clone());
+
+    let result = registry
+        .execute("list_operations", serde_json::json!({"check_parity": true}))
+        .await
+        .expect("every operation should be reachable on its declared surfaces");
+
+    let operations = result["operations"].as_array().unwrap();
+    assert_eq!(operations.len(), registry.get_all_metadata().len());
+    assert_eq!(result["total"], operations.len());
+
+    let spec = operations.iter().find(|op| op["name"] == "spec").unwrap();
+    assert_eq!(spec["http_path"], "/spec");
+    assert_eq!(spec["cli_pattern"], "spec");
+    assert_eq!(spec["mcp_tool"], true);
+    assert_eq!(spec["missing"], serde_json::json!([]));
+
+    // Without the check, entries carry no parity details
+    let result = registry
+        .execute("list_operations", serde_json::json!({}))
+        .await
+        .unwrap();
+    assert!(result["operations"][0].get("missing").is_none());
+    assert!(result["operations"][0]["schema"].is_object());
+}
+
+#[tokio::test]
+async fn test_list_flows_filters_by_metadata() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::model::FlowName;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps.clone());
+    let flow = |name: &str, owner: &str, tags: &str| {
+        format!(
+            "name: {}\nversion: 1.0.0\ndescription: {} flow\nowner: {}\ntags: [{}]\non: cli.manual\nsteps:\n  - id: s\n    use: core.echo\n    with:\n      text: hi\n",
+            name, name, owner, tags
+        )
+    };
+
+    for (name, owner, tags) in [
+        ("invoices", "alice", "billing"),
+        ("refunds", "bob", "billing, ops"),
+        ("reports", "alice", "analytics"),
+    ] {
+        registry
+            .execute(
+                "save_flow",
+                serde_json::json!({"content": flow(name, owner, tags)}),
+            )
+            .await
+            .unwrap();
+    }
+    registry
+        .execute("deploy_flow", serde_json::json!({"name": "invoices"}))
+        .await
+        .unwrap();
+    // Deployed without a flow file: filtered by storage
+    env.deps
+        .storage
+        .deploy_flow_version(
+            &FlowName::new("archived").unwrap(),
+            "1.0.0",
+            &flow("archived", "alice", "billing"),
+        )
+        .await
+        .unwrap();
+
+    let names = |result: &serde_json::Value| -> Vec<String> {
+        result["flows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let all = registry
+        .execute("list_flows", serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(
+        names(&all),
+        vec!["archived", "invoices", "refunds", "reports"]
+    );
+
+    let billing = registry
+        .execute("list_flows", serde_json::json!({"tag": "billing"}))
+        .await
+        .unwrap();
+    assert_eq!(names(&billing), vec!["archived", "invoices", "refunds"]);
+
+    let alice_billing = registry
+        .execute(
+            "list_flows",
+            serde_json::json!({"tag": "billing", "owner": "alice"}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(names(&alice_billing), vec!["archived", "invoices"]);
+    let invoices = &alice_billing["flows"][1];
+    assert_eq!(invoices["owner"], "alice");
+    assert_eq!(invoices["description"], "invoices flow");
+    assert_eq!(invoices["tags"], serde_json::json!(["billing"]));
+    assert_eq!(invoices["deployed_version"], "1.0.0");
+
+    let got = registry
+        .execute("get_flow", serde_json::json!({"name": "refunds"}))
+        .await
+        .unwrap();
+    assert_eq!(got["owner"], "bob");
+    assert_eq!(got["tags"], serde_json::json!(["billing", "ops"]));
+
+    // Tags are limited to lowercase letters, digits, '.', '_' and '-'
+    let bad = registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"content": flow("bad_tags", "alice", "\"Billing Team\"")}),
+        )
+        .await;
+    assert!(
+        bad.is_err(),
+        "Tags outside the allowed charset should be rejected"
+    );
+}
+
+#[tokio::test]
+async fn test_deploy_dry_run_reports_without_deploying() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::model::FlowName;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps.clone());
+    let name = FlowName::new("planned").unwrap();
+    let save = |version: &str, text: &str| {
+        let registry = &registry;
+        let content = format!(
+            "name: planned\nversion: {}\non: cli.manual\nsteps:\n  - id: s\n    use: core.echo\n    with:\n      text: {}\n",
+            version, text
+        );
+        async move {
+            registry
+                .execute("save_flow", serde_json::json!({"content": content}))
+                .await
+                .unwrap();
+        }
+    };
+    let dry_run = || {
+        registry.execute(
+            "deploy_flow",
+            serde_json::json!({"name": "planned", "dry_run": true}),
+        )
+    };
+
+    // Never deployed: a new version would be deployed, and nothing is stored
+    save("1.0.0", "hi").await;
+    let planned = dry_run().await.unwrap();
+    assert_eq!(planned["status"], "would_deploy");
+    assert_eq!(planned["dry_run"], true);
+    assert_eq!(planned["version"], "1.0.0");
+    assert!(planned["file_hash"].as_str().is_some_and(|h| h.len() == 64));
+    assert!(planned.get("deployed_version").is_none());
+    assert_eq!(
+        env.deps.storage.get_deployed_version(&name).await.unwrap(),
+        None
+    );
+    assert!(
+        env.deps
+            .storage
+            .list_flow_versions(&name)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Deployed from the current file: unchanged
+    registry
+        .execute("deploy_flow", serde_json::json!({"name": "planned"}))
+        .await
+        .unwrap();
+    let planned = dry_run().await.unwrap();
+    assert_eq!(planned["status"], "unchanged");
+    assert_eq!(planned["deployed_version"], "1.0.0");
+
+    // A new version would replace the deployed one, which stays deployed
+    save("1.1.0", "changed").await;
+    let planned = dry_run().await.unwrap();
+    assert_eq!(planned["status"], "would_deploy");
+    assert_eq!(planned["version"], "1.1.0");
+    assert_eq!(planned["deployed_version"], "1.0.0");
+    assert_eq!(
+        env.deps.storage.get_deployed_version(&name).await.unwrap(),
+        Some("1.0.0".to_string())
+    );
+    assert_eq!(
+        env.deps
+            .storage
+            .list_flow_versions(&name)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // Errors the deploy would hit are reported: an existing version with new content
+    save("1.0.0", "edited again").await;
+    let err = dry_run().await.unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_flow_status_reports_deployment_drift() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::model::FlowName;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps.clone());
+    let flow = |name: &str, text: &str| {
+        format!(
+            "name: {}\nversion: 1.0.0\non: cli.manual\nsteps:\n  - id: s\n    use: core.echo\n    with:\n      text: {}\n",
+            name, text
+        )
+    };
+    let save = |content: String| {
+        let registry = &registry;
+        async move {
+            registry
+                .execute("save_flow", serde_json::json!({"content": content}))
+                .await
+                .unwrap();
+        }
+    };
+
+    // in-sync: deployed from the current file
+    save(flow("synced", "hi")).await;
+    registry
+        .execute("deploy_flow", serde_json::json!({"name": "synced"}))
+        .await
+        .unwrap();
+    // draft-ahead: file edited after deploying
+    save(flow("edited", "hi")).await;
+    registry
+        .execute("deploy_flow", serde_json::json!({"name": "edited"}))
+        .await
+        .unwrap();
+    save(flow("edited", "changed")).await;
+    // file-only: never deployed
+    save(flow("draft", "hi")).await;
+    // deployed-only: no flow file
+    env.deps
+        .storage
+        .deploy_flow_version(
+            &FlowName::new("orphan").unwrap(),
+            "1.0.0",
+            &flow("orphan", "hi"),
+        )
+        .await
+        .unwrap();
+
+    let status = registry
+        .execute("flow_status", serde_json::json!({}))
+        .await
+        .unwrap();
+    let flows = status["flows"].as_array().unwrap();
+    let summary: Vec<(&str, &str)> = flows
+        .iter()
+        .map(|f| (f["name"].as_str().unwrap(), f["status"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("draft", "file-only"),
+            ("edited", "draft-ahead"),
+            ("orphan", "deployed-only"),
+            ("synced", "in-sync"),
+        ]
+    );
+    assert_eq!(flows[3]["file_hash"], flows[3]["deployed_hash"]);
+    assert_ne!(flows[1]["file_hash"], flows[1]["deployed_hash"]);
+    assert!(flows[0].get("deployed_hash").is_none());
+    assert!(flows[2].get("file_hash").is_none());
+
+    // list_flows carries the same status
+    let listed = registry
+        .execute("list_flows", serde_json::json!({}))
+        .await
+        .unwrap();
+    let listed: Vec<(&str, &str)> = listed["flows"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| (f["name"].as_str().unwrap(), f["status"].as_str().unwrap()))
+        .collect();
+    assert_eq!(listed, summary);
+
+    // fail_on_drift only fails when a checked flow has drifted
+    let single = registry
+        .execute(
+            "flow_status",
+            serde_json::json!({"name": "synced", "fail_on_drift": true}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(single["flows"][0]["status"], "in-sync");
+
+    let err = registry
+        .execute("flow_status", serde_json::json!({"fail_on_drift": true}))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("edited (draft-ahead)"), "{}", err);
+    assert!(!err.contains("synced"), "{}", err);
+
+    let missing = registry
+        .execute("flow_status", serde_json::json!({"name": "nope"}))
+        .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_rerun_step_after_fixing_draft_flow() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps);
+
+    let save = |content: &'static str| {
+        registry.execute(
+            "save_flow",
+            serde_json::json!({"name": "rerun_test", "content": content}),
+        )
+    };
+
+    // `publish` has no topic, so the first run fails there
+    save(
+        r#"name: rerun_test
+on: cli.manual
+steps:
+  - id: fetch
+    use: core.echo
+    with:
+      text: "{{ event.id }}"
+  - id: publish
+    use: core.publish
+    with:
+      payload: {}
+"#,
+    )
+    .await
+    .unwrap();
+    let started = registry
+        .execute(
+            "start_run",
+            serde_json::json!({"flow_name": "rerun_test", "event": {"id": "42"}, "draft": true}),
+        )
+        .await;
+    assert!(started.is_err());
+    let runs = registry
+        .execute("list_runs", serde_json::json!({}))
+        .await
+        .unwrap();
+    let run_id = runs["items"][0]["id"].as_str().unwrap().to_string();
+
+    save(
+        r#"name: rerun_test
+on: cli.manual
+steps:
+  - id: fetch
+    use: core.echo
+    with:
+      text: "{{ event.id }}"
+  - id: publish
+    use: core.publish
+    with:
+      topic: orders
+      payload:
+        id: "{{ steps.fetch.text }}"
+"#,
+    )
+    .await
+    .unwrap();
+    let rerun = registry
+        .execute(
+            "rerun_step",
+            serde_json::json!({"run_id": run_id, "step_id": "publish", "draft": true}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(rerun["run_id"], serde_json::json!(run_id));
+    assert_eq!(rerun["steps"], serde_json::json!(["publish"]));
+    assert_eq!(rerun["outputs"]["publish"]["payload"]["id"], "42");
+
+    let run = registry
+        .execute("get_run", serde_json::json!({"run_id": run_id}))
+        .await
+        .unwrap();
+    assert_eq!(run["status"], "SUCCEEDED");
+    // Step records only come with the run on request
+    assert!(run.get("steps").is_none(), "{}", run);
+    let run = registry
+        .execute(
+            "get_run",
+            serde_json::json!({"run_id": run_id, "include_steps": true}),
+        )
+        .await
+        .unwrap();
+    let steps = run["steps"].as_array().unwrap();
+    assert!(!steps.is_empty());
+    assert!(steps.iter().all(|step| step.get("inputs").is_none()));
+
+    // Steps the flow does not define are rejected
+    let missing = registry
+        .execute(
+            "rerun_step",
+            serde_json::json!({"run_id": run_id, "step_id": "nope", "draft": true}),
+        )
+        .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_deploy_rejects_unresolvable_tools() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let flows_dir = beemflow::config::get_flows_dir(&env.deps.config);
+    let registry = OperationRegistry::new(env.deps);
+
+    let content = r#"name: missing_tools
+version: "1.0.0"
+on: cli.manual
+steps:
+  - id: fetch
+    use: http.fetch
+    with:
+      url: "https://example.com"
+  - id: page
+    use: notion.create_page
+"#;
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "missing_tools", "content": content}),
+        )
+        .await
+        .unwrap();
+
+    let err = registry
+        .execute("deploy_flow", serde_json::json!({"name": "missing_tools"}))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("notion.create_page (step 'page')"), "{}", err);
+    assert!(!err.contains("http.fetch"), "{}", err);
+    assert!(err.contains("--skip_tool_check"), "{}", err);
+
+    // Lint treats unresolvable tools as an error too
+    let file = flows_dir.join("missing_tools.flow.yaml");
+    let lint = registry
+        .execute("lint_flow", serde_json::json!({"file": file}))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(lint.contains("notion.create_page"), "{}", lint);
+
+    let deployed = registry
+        .execute(
+            "deploy_flow",
+            serde_json::json!({"name": "missing_tools", "skip_tool_check": true}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(deployed["status"], "deployed");
+}
+
+#[tokio::test]
+async fn test_strict_validation_flags_unknown_tools_and_providers() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let storage = env.deps.storage.clone();
+    let registry = OperationRegistry::new(env.deps);
+
+    let content = r#"name: references
+version: "1.0.0"
+on: cli.manual
+steps:
+  - id: page
+    use: notion.create_page
+  - id: fetch
+    use: http
+    with:
+      url: "https://api.acme.test/items"
+      headers:
+        Authorization: "Bearer $oauth:acme:default"
+  - id: each
+    foreach: "{{ vars.items }}"
+    as: item
+    do:
+      - id: sync
+        use: core.echo
+        with:
+          text: "$oauth:acme:default"
+"#;
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "references", "content": content}),
+        )
+        .await
+        .unwrap();
+
+    // Offline validation only checks the flow itself
+    let validated = registry
+        .execute("validate_flow", serde_json::json!({"name": "references"}))
+        .await
+        .unwrap();
+    assert_eq!(validated["status"], "valid");
+
+    let err = registry
+        .execute(
+            "validate_flow",
+            serde_json::json!({"name": "references", "strict": true}),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("notion.create_page (step 'page')"), "{}", err);
+    assert!(
+        err.contains("OAuth providers that are not configured"),
+        "{}",
+        err
+    );
+    assert!(err.contains("acme (step 'fetch', 'sync')"), "{}", err);
+
+    // A stored provider is configured
+    let now = chrono::Utc::now();
+    storage
+        .save_oauth_provider(&beemflow::model::OAuthProvider {
+            id: "acme".to_string(),
+            name: "Acme".to_string(),
+            client_id: "acme-client".to_string(),
+            client_secret: "acme-secret".to_string(),
+            auth_url: "https://acme.test/authorize".to_string(),
+            token_url: "https://acme.test/token".to_string(),
+            scopes: None,
+            auth_params: None,
+            created_at: now,
+            updated_at: now,
+        })
+        .await
+        .unwrap();
+    let err = registry
+        .execute(
+            "validate_flow",
+            serde_json::json!({"name": "references", "strict": true}),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("notion.create_page"), "{}", err);
+    assert!(!err.contains("OAuth providers"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_system_gc_removes_orphaned_paused_runs() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let storage = env.deps.storage.clone();
+    let registry = OperationRegistry::new(env.deps);
+
+    // Pause one run on each of two draft flows
+    for name in ["keep_paused", "drop_paused"] {
+        let content = format!(
+            r#"name: {name}
+on: cli.manual
+steps:
+  - id: wait
+    await_event:
+      source: test
+      match:
+        token: "{name}"
+      timeout: 1h
+"#
+        );
+        registry
+            .execute(
+                "save_flow",
+                serde_json::json!({"name": name, "content": content}),
+            )
+            .await
+            .expect("Should save flow");
+        let _ = registry
+            .execute(
+                "start_run",
+                serde_json::json!({"flow_name": name, "event": {}, "draft": true}),
+            )
+            .await;
+    }
+    let paused = storage.load_paused_runs().await.unwrap();
+    assert_eq!(paused.len(), 2, "Both runs should be paused");
+    let dropped_run = paused[&storage_key("drop_paused")]["run_id"].clone();
+
+    // Orphan one run by deleting its flow, and leave a dangling entry behind
+    registry
+        .execute("delete_flow", serde_json::json!({"name": "drop_paused"}))
+        .await
+        .expect("Should delete flow");
+    storage
+        .save_paused_run("dangling", "test", serde_json::json!({"not": "a run"}))
+        .await
+        .unwrap();
+
+    // A dry run reports without removing
+    let report = registry
+        .execute("system_gc", serde_json::json!({"dry_run": true}))
+        .await
+        .unwrap();
+    assert_eq!(report["paused_runs"], 1);
+    assert_eq!(report["subscriptions"], 1);
+    assert_eq!(storage.load_paused_runs().await.unwrap().len(), 3);
+
+    let report = registry
+        .execute("system_gc", serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(report["paused_runs"], 1);
+    assert_eq!(report["subscriptions"], 1);
+    assert_eq!(report["run_ids"], serde_json::json!([dropped_run]));
+
+    let remaining = storage.load_paused_runs().await.unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert!(remaining.contains_key(&storage_key("keep_paused")));
+    let run_id = dropped_run.as_str().unwrap().parse().unwrap();
+    let run = storage.get_run(run_id).await.unwrap().unwrap();
+    assert_eq!(run.status, beemflow::model::RunStatus::Failed);
+
+    // Running again finds nothing left to clean
+    let report = registry
+        .execute("system_gc", serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(report["paused_runs"], 0);
+    assert_eq!(report["subscriptions"], 0);
+}
+
+#[tokio::test]
+async fn test_runs_carry_and_filter_by_labels() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps.clone());
+
+    let flow_content = "name: labelled_flow\non: cli.manual\nlabels:\n  team: billing\n  env: dev\nsteps:\n  - id: greet\n    use: core.echo\n    with:\n      text: \"{{ event.n }}\"\n";
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "labelled_flow", "content": flow_content}),
+        )
+        .await
+        .unwrap();
+
+    // Labels given at start win over the flow's
+    let mut run_ids = Vec::new();
+    for (n, labels) in [
+        (1, serde_json::json!({"env": "prod", "ticket": "OPS-12"})),
+        (2, serde_json::json!({"env": "prod"})),
+        (3, serde_json::json!(null)),
+    ] {
+        let started = registry
+            .execute(
+                "start_run",
+                serde_json::json!({"flow_name": "labelled_flow", "event": {"n": n}, "draft": true, "labels": labels}),
+            )
+            .await
+            .unwrap();
+        run_ids.push(started["run_id"].as_str().unwrap().to_string());
+    }
+
+    let run = registry
+        .execute("get_run", serde_json::json!({"run_id": run_ids[0]}))
+        .await
+        .unwrap();
+    assert_eq!(
+        run["labels"],
+        serde_json::json!({"team": "billing", "env": "prod", "ticket": "OPS-12"})
+    );
+    let run = registry
+        .execute("get_run", serde_json::json!({"run_id": run_ids[2]}))
+        .await
+        .unwrap();
+    assert_eq!(
+        run["labels"],
+        serde_json::json!({"team": "billing", "env": "dev"})
+    );
+
+    let listed_ids = |page: serde_json::Value| {
+        let mut ids: Vec<String> = page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|run| run["id"].as_str().unwrap().to_string())
+            .collect();
+        ids.sort();
+        ids
+    };
+    let prod = registry
+        .execute("list_runs", serde_json::json!({"label": "env=prod"}))
+        .await
+        .unwrap();
+    let mut expected = vec![run_ids[0].clone(), run_ids[1].clone()];
+    expected.sort();
+    assert_eq!(listed_ids(prod), expected);
+
+    let ticket = registry
+        .execute("list_runs", serde_json::json!({"label": "ticket=OPS-12"}))
+        .await
+        .unwrap();
+    assert_eq!(listed_ids(ticket), vec![run_ids[0].clone()]);
+
+    let billing = registry
+        .execute("list_runs", serde_json::json!({"label": "team=billing"}))
+        .await
+        .unwrap();
+    assert_eq!(billing["items"].as_array().unwrap().len(), 3);
+
+    for input in [
+        serde_json::json!({"label": "env"}),
+        serde_json::json!({"label": "env=prod", "offset": 0}),
+    ] {
+        assert!(registry.execute("list_runs", input).await.is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_export_runs_operation() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::model::{Run, RunStatus, StepId, StepRun, StepStatus};
+    use beemflow::utils::TestEnvironment;
+    use chrono::{Duration, Timelike, Utc};
+    use uuid::Uuid;
+
+    let env = TestEnvironment::new().await;
+    let storage = env.deps.storage.clone();
+    let registry = OperationRegistry::new(env.deps);
+
+    let flow_content = r#"name: export_flow
+version: "1.0.0"
+on: cli.manual
+steps:
+  - id: fetch
+    use: http.fetch
+    with:
+      url: "https://example.com"
+  - id: notify
+    use: core.echo
+    with:
+      text: "done""#;
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "export_flow", "content": flow_content}),
+        )
+        .await
+        .expect("Should save flow");
+    registry
+        .execute("deploy_flow", serde_json::json!({"name": "export_flow"}))
+        .await
+        .expect("Should deploy flow");
+
+    // Whole seconds, the precision timestamps are stored at
+    let now = Utc::now().with_nanosecond(0).unwrap();
+    let run_at = |status, started_at: chrono::DateTime<Utc>| Run {
+        id: Uuid::new_v4(),
+        flow_name: FlowName::new("export_flow").unwrap(),
+        event: HashMap::new(),
+        vars: HashMap::new(),
+        status,
+        started_at,
+        ended_at: Some(started_at + Duration::seconds(2)),
+        steps: None,
+        environment: None,
+        correlation_id: None,
+        labels: HashMap::new(),
+        usage: None,
+        tenant_id: None,
+    };
+    let step_of = |run: &Run, name: &str, status, offset, error: Option<&str>| StepRun {
+        id: Uuid::new_v4(),
+        run_id: run.id,
+        step_name: StepId::new(name).unwrap(),
+        status,
+        started_at: run.started_at + Duration::seconds(offset),
+        ended_at: Some(run.started_at + Duration::seconds(offset + 1)),
+        error: error.map(str::to_string),
+        error_kind: None,
+        inputs: None,
+        outputs: None,
+        progress: None,
+    };
+
+    // An old failed run whose error needs quoting, and a recent run whose fetch was retried
+    let failed = run_at(RunStatus::Failed, now - Duration::days(2));
+    let succeeded = run_at(RunStatus::Succeeded, now - Duration::hours(1));
+    storage.save_run(&failed).await.unwrap();
+    storage.save_run(&succeeded).await.unwrap();
+    storage
+        .save_step(&step_of(
+            &failed,
+            "fetch",
+            StepStatus::Failed,
+            0,
+            Some("timeout, said \"upstream\"\nstack trace"),
+        ))
+        .await
+        .unwrap();
+    for (name, status, offset) in [
+        ("fetch", StepStatus::Failed, 0),
+        ("fetch", StepStatus::Succeeded, 1),
+        ("notify", StepStatus::Succeeded, 2),
+    ] {
+        storage
+            .save_step(&step_of(&succeeded, name, status, offset, None))
+            .await
+            .unwrap();
+    }
+
+    // CSV: header, one row per run, fields quoted per RFC 4180
+    let csv = registry
+        .execute("export_runs", serde_json::json!({}))
+        .await
+        .expect("CSV export should succeed");
+    assert_eq!(csv["runs"], 2);
+    assert_eq!(csv["steps"], 0);
+    let content = csv["content"].as_str().unwrap();
+    let lines: Vec<&str> = content.split("\r\n").collect();
+    assert_eq!(
+        lines[0],
+        "run_id,flow,status,started,ended,duration_ms,trigger,error"
+    );
+    assert!(
+        lines[1].starts_with(&succeeded.id.to_string()),
+        "{}",
+        lines[1]
+    );
+    assert!(lines[1].contains(",SUCCEEDED,"));
+    assert!(lines[1].ends_with(",2000,cli.manual,"), "{}", lines[1]);
+    assert!(
+        lines[2].ends_with(r#",cli.manual,"fetch: timeout, said ""upstream""""#),
+        "{}",
+        lines[2]
+    );
+    assert_eq!(lines[3], "");
+
+    // since limits the runs to a window ending now
+    let recent = registry
+        .execute("export_runs", serde_json::json!({"since": "1d"}))
+        .await
+        .unwrap();
+    assert_eq!(recent["runs"], 1);
+    assert!(
+        !recent["content"]
+            .as_str()
+            .unwrap()
+            .contains(&failed.id.to_string())
+    );
+
+    // JSON lines with steps: run rows, then a row per step record
+    let jsonl = registry
+        .execute(
+            "export_runs",
+            serde_json::json!({"format": "jsonl", "include_steps": true}),
+        )
+        .await
+        .expect("JSONL export should succeed");
+    assert_eq!(jsonl["format"], "jsonl");
+    assert_eq!(jsonl["runs"], 2);
+    assert_eq!(jsonl["steps"], 4);
+    let rows: Vec<serde_json::Value> = jsonl["content"]
+        .as_str()
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(rows.len(), 6);
+    assert_eq!(rows[1]["error"], "fetch: timeout, said \"upstream\"");
+    let fetches: Vec<&serde_json::Value> = rows[2..]
+        .iter()
+        .filter(|row| row["run_id"] == succeeded.id.to_string() && row["step"] == "fetch")
+        .collect();
+    assert_eq!(fetches.len(), 2);
+    assert_eq!(fetches[0]["tool"], "http.fetch");
+    assert_eq!(fetches[0]["status"], "FAILED");
+    assert_eq!(fetches[1]["attempt"], 2);
+    assert_eq!(fetches[1]["duration_ms"], 1000);
+
+    // Writing to a file is left to the CLI
+    let err = registry
+        .execute("export_runs", serde_json::json!({"output": "runs.csv"}))
+        .await;
+    assert!(err.is_err(), "output should be rejected outside the CLI");
+}
+
+#[tokio::test]
+async fn test_flow_mcp_server_env_is_isolated_per_run() {
+    // Two homes with different flows; `flow serve --mcp-stdio` lists the flows of
+    // the BEEMFLOW_HOME it was started with
+    let homes: Vec<tempfile::TempDir> = ["alpha", "beta"]
+        .iter()
+        .map(|name| {
+            let home = tempfile::tempdir().unwrap();
+            std::fs::create_dir_all(home.path().join("flows")).unwrap();
+            std::fs::write(
+                home.path().join(format!("flows/{}.flow.yaml", name)),
+                format!(
+                    "name: {}\non: cli.manual\nsteps:\n  - id: s\n    use: core.echo\n    with:\n      text: hi\n",
+                    name
+                ),
+            )
+            .unwrap();
+            home
+        })
+        .collect();
+    let home_of = |i: usize| homes[i].path().to_str().unwrap().to_string();
+
+    let flow = parse_string(
+        &format!(
+            r#"name: mcp_env
+on: cli.manual
+mcpServers:
+  beemflow:
+    command: {}
+    args: ["serve", "--mcp-stdio"]
+    env:
+      BEEMFLOW_HOME: "{{{{ event.home }}}}"
+steps:
+  - id: listed
+    use: mcp://beemflow/beemflow_list_flows
+  - id: overridden
+    use: mcp://beemflow/beemflow_list_flows
+    env:
+      BEEMFLOW_HOME: "{{{{ event.other_home }}}}"
+"#,
+            env!("CARGO_BIN_EXE_flow")
+        ),
+        None,
+    )
+    .unwrap();
+
+    let engine = Engine::for_testing().await;
+    let event = |home: usize, other: usize| {
+        HashMap::from([
+            ("home".to_string(), serde_json::json!(home_of(home))),
+            ("other_home".to_string(), serde_json::json!(home_of(other))),
+        ])
+    };
+    // Same server name, different env, running at the same time
+    let (first, second) = tokio::join!(
+        engine.execute(&flow, event(0, 1)),
+        engine.execute(&flow, event(1, 0)),
+    );
+
+    let listed =
+        |outputs: &HashMap<String, serde_json::Value>, step: &str| outputs[step].to_string();
+    let first = first.unwrap().outputs;
+    let second = second.unwrap().outputs;
+    assert!(listed(&first, "listed").contains("alpha"));
+    assert!(!listed(&first, "listed").contains("beta"));
+    assert!(listed(&second, "listed").contains("beta"));
+    assert!(!listed(&second, "listed").contains("alpha"));
+
+    // Step env is merged over the flow's
+    assert!(listed(&first, "overridden").contains("beta"));
+    assert!(listed(&second, "overridden").contains("alpha"));
+}
+
+#[tokio::test]
+async fn test_mcp_instance_started_once_for_concurrent_callers() {
+    use std::sync::Arc;
+
+    let home = tempfile::tempdir().unwrap();
+    let config = beemflow::model::McpServerConfig {
+        command: env!("CARGO_BIN_EXE_flow").to_string(),
+        args: Some(vec!["serve".to_string(), "--mcp-stdio".to_string()]),
+        env: Some(HashMap::from([(
+            "BEEMFLOW_HOME".to_string(),
+            home.path().to_str().unwrap().to_string(),
+        )])),
+        port: None,
+        transport: None,
+        endpoint: None,
+    };
+    let manager =
+        beemflow::mcp::McpManager::new(Arc::new(beemflow::secrets::EnvSecretsProvider::new()));
+
+    let (first, second) = tokio::join!(
+        manager.get_or_start_instance("beemflow", &config),
+        manager.get_or_start_instance("beemflow", &config),
+    );
+    assert!(Arc::ptr_eq(&first.unwrap(), &second.unwrap()));
+}
+
+#[tokio::test]
+async fn test_reloader_deploys_changed_flows() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::core::reload::Reloader;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let storage = env.deps.storage.clone();
+    let flows_dir = beemflow::config::get_flows_dir(&env.deps.config);
+    let config_dir = tempfile::tempdir().unwrap();
+    let config_path = config_dir.path().join("flow.config.json");
+    let registry = OperationRegistry::new(env.deps.clone());
+
+    let flow_at = |version: &str, text: &str| {
+        format!(
+            "name: reload_flow\nversion: \"{}\"\non: cli.manual\nsteps:\n  - id: greet\n    use: core.echo\n    with:\n      text: \"{}\"\n",
+            version, text
+        )
+    };
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "reload_flow", "content": flow_at("1.0.0", "one")}),
+        )
+        .await
+        .expect("Should save flow");
+    registry
+        .execute("deploy_flow", serde_json::json!({"name": "reload_flow"}))
+        .await
+        .expect("Should deploy flow");
+
+    let mut reloader = Reloader::new(env.deps.clone(), &config_path).await;
+    assert_eq!(reloader.check().await, Default::default());
+
+    // A new version in the file is deployed
+    let flow_path = flows_dir.join("reload_flow.flow.yaml");
+    std::fs::write(&flow_path, flow_at("2.0.0", "two")).unwrap();
+    let report = reloader.check().await;
+    assert_eq!(report.deployed, vec!["reload_flow".to_string()]);
+    let name = FlowName::new("reload_flow").unwrap();
+    assert_eq!(
+        storage
+            .get_deployed_version(&name)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("2.0.0")
+    );
+
+    // Editing the deployed version without bumping it is reported, not deployed
+    std::fs::write(&flow_path, flow_at("2.0.0", "edited")).unwrap();
+    let report = reloader.check().await;
+    assert!(report.deployed.is_empty());
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(report.rejected[0].0, "reload_flow");
+    assert!(
+        report.rejected[0].1.contains("bump its version"),
+        "{}",
+        report.rejected[0].1
+    );
+
+    // An invalid file keeps the deployed version
+    std::fs::write(&flow_path, "name: reload_flow\nsteps: [").unwrap();
+    let report = reloader.check().await;
+    assert!(report.deployed.is_empty());
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(
+        storage
+            .get_deployed_version(&name)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("2.0.0")
+    );
+
+    // Unchanged files are not reported again
+    assert_eq!(reloader.check().await, Default::default());
+
+    // An invalid config is ignored; a valid one reloads registry tools
+    std::fs::write(&config_path, "{not json").unwrap();
+    assert!(!reloader.check().await.tools_reloaded);
+    std::fs::write(
+        &config_path,
+        r#"{"storage": {"driver": "sqlite", "dsn": "sqlite::memory:"}}"#,
+    )
+    .unwrap();
+    assert!(reloader.check().await.tools_reloaded);
+}
+
+#[tokio::test]
+async fn test_tenants_see_only_their_own_flows_and_runs() {
+    use beemflow::auth::with_tenant;
+    use beemflow::core::OperationRegistry;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps.clone());
+    let flow_content = "name: acme_flow\non: cli.manual\nsteps:\n  - id: greet\n    use: core.echo\n    with:\n      text: hi\n";
+
+    let started = with_tenant("acme".to_string(), async {
+        registry
+            .execute(
+                "save_flow",
+                serde_json::json!({"name": "acme_flow", "content": flow_content}),
+            )
+            .await
+            .unwrap();
+        registry
+            .execute(
+                "start_run",
+                serde_json::json!({"flow_name": "acme_flow", "event": {}, "draft": true}),
+            )
+            .await
+            .unwrap()
+    })
+    .await;
+    let run_id = started["run_id"].clone();
+
+    let (acme_runs, acme_flows) = with_tenant("acme".to_string(), async {
+        (
+            registry
+                .execute("list_runs", serde_json::json!({}))
+                .await
+                .unwrap(),
+            registry
+                .execute("list_flows", serde_json::json!({}))
+                .await
+                .unwrap(),
+        )
+    })
+    .await;
+    assert_eq!(acme_runs["items"].as_array().unwrap().len(), 1);
+    assert_eq!(acme_flows["flows"].as_array().unwrap().len(), 1);
+
+    with_tenant("globex".to_string(), async {
+        let runs = registry
+            .execute("list_runs", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert!(runs["items"].as_array().unwrap().is_empty());
+        let flows = registry
+            .execute("list_flows", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert!(flows["flows"].as_array().unwrap().is_empty());
+
+        assert!(
+            registry
+                .execute("get_run", serde_json::json!({"run_id": run_id}))
+                .await
+                .is_err()
+        );
+        assert!(
+            registry
+                .execute("get_flow", serde_json::json!({"name": "acme_flow"}))
+                .await
+                .is_err()
+        );
+        // Nor can another tenant take the flow over or run it
+        assert!(
+            registry
+                .execute(
+                    "save_flow",
+                    serde_json::json!({"name": "acme_flow", "content": flow_content}),
+                )
+                .await
+                .is_err()
+        );
+        assert!(
+            registry
+                .execute(
+                    "start_run",
+                    serde_json::json!({"flow_name": "acme_flow", "event": {"n": 2}, "draft": true}),
+                )
+                .await
+                .is_err()
+        );
+    })
+    .await;
+
+    // Unscoped callers see everything, as in a single-tenant deployment
+    let runs = registry
+        .execute("list_runs", serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(runs["items"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_tenants_see_only_their_own_run_stats_and_exports() {
+    use beemflow::auth::with_tenant;
+    use beemflow::core::OperationRegistry;
+    use beemflow::core::export::{ExportOptions, write_export};
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps.clone());
+
+    // Each tenant runs a flow of its own
+    for tenant in ["acme", "globex"] {
+        let name = format!("{}_flow", tenant);
+        let content = format!(
+            "name: {}\non: cli.manual\nsteps:\n  - id: greet\n    use: core.echo\n    with:\n      text: hi\n",
+            name
+        );
+        with_tenant(tenant.to_string(), async {
+            registry
+                .execute(
+                    "save_flow",
+                    serde_json::json!({"name": name, "content": content}),
+                )
+                .await
+                .unwrap();
+            registry
+                .execute(
+                    "start_run",
+                    serde_json::json!({"flow_name": name, "event": {}, "draft": true}),
+                )
+                .await
+                .unwrap();
+        })
+        .await;
+    }
+
+    with_tenant("acme".to_string(), async {
+        let stats = registry
+            .execute("runs_stats", serde_json::json!({"window": "1h"}))
+            .await
+            .unwrap();
+        let flows: Vec<&str> = stats["flows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["flow_name"].as_str().unwrap())
+            .collect();
+        assert_eq!(flows, vec!["acme_flow"]);
+        assert_eq!(stats["totals"]["total"], 1);
+
+        let mut out = Vec::new();
+        let counts = write_export(
+            env.deps.storage.as_ref(),
+            &ExportOptions::default(),
+            &mut out,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(counts.runs, 1);
+        let csv = String::from_utf8(out).unwrap();
+        assert!(csv.contains("acme_flow"), "{}", csv);
+        assert!(!csv.contains("globex_flow"), "{}", csv);
+
+        // The integrity chains span every tenant
+        assert!(
+            registry
+                .execute("verify_integrity", serde_json::json!({}))
+                .await
+                .is_err()
+        );
+    })
+    .await;
+
+    // Unscoped callers see both tenants
+    let stats = registry
+        .execute("runs_stats", serde_json::json!({"window": "1h"}))
+        .await
+        .unwrap();
+    assert_eq!(stats["totals"]["total"], 2);
+}
+
+/// Lines written by `runs tail`, without their timestamps and durations
+fn tail_lines(out: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(out)
+        .lines()
+        .map(|line| {
+            let (_, text) = line.split_once("  ").unwrap();
+            match text.find(" (") {
+                Some(at) => {
+                    let rest = &text[at..];
+                    let end = rest.find(')').unwrap();
+                    format!("{}{}", &text[..at], &rest[end + 1..])
+                }
+                None => text.to_string(),
+            }
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_runs_tail_follows_step_events_until_the_run_ends() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::core::tail::tail_run;
+    use beemflow::model::RunStatus;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = std::sync::Arc::new(OperationRegistry::new(env.deps.clone()));
+    let flow_content = "name: tailed_flow\non: cli.manual\nsteps:\n  - id: fetch\n    use: core.echo\n    with:\n      text: hi\n  - id: pause\n    use: core.wait\n    with:\n      seconds: 1\n  - id: report\n    use: core.echo\n    with:\n      text: done\n";
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "tailed_flow", "content": flow_content}),
+        )
+        .await
+        .unwrap();
+
+    let running = {
+        let registry = registry.clone();
+        tokio::spawn(async move {
+            registry
+                .execute(
+                    "start_run",
+                    serde_json::json!({"flow_name": "tailed_flow", "event": {}, "draft": true}),
+                )
+                .await
+        })
+    };
+    let run_id = loop {
+        if let Some(run) = env.deps.storage.list_runs(1, 0).await.unwrap().pop() {
+            break run.id;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    };
+
+    // A long poll interval: the steps after the first read come from events
+    let mut out = Vec::new();
+    let started = std::time::Instant::now();
+    let status = tail_run(
+        env.deps.storage.as_ref(),
+        Some(env.deps.engine.event_bus()),
+        run_id,
+        std::time::Duration::from_secs(60),
+        &mut out,
+    )
+    .await
+    .unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(30));
+    running.await.unwrap().unwrap();
+
+    assert_eq!(status, RunStatus::Succeeded);
+    assert_eq!(
+        tail_lines(&out),
+        [
+            "fetch  started",
+            "fetch  succeeded",
+            "pause  started",
+            "pause  succeeded",
+            "report  started",
+            "report  succeeded",
+            "run succeeded",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_runs_tail_polls_without_a_shared_event_bus() {
+    use beemflow::core::OperationRegistry;
+    use beemflow::core::tail::tail_run;
+    use beemflow::model::RunStatus;
+    use beemflow::utils::TestEnvironment;
+
+    let env = TestEnvironment::new().await;
+    let registry = OperationRegistry::new(env.deps.clone());
+    let flow_content = "name: failing_tail\non: cli.manual\nsteps:\n  - id: greet\n    use: core.echo\n    with:\n      text: hi\n  - id: broken\n    use: no.such.tool\n";
+    registry
+        .execute(
+            "save_flow",
+            serde_json::json!({"name": "failing_tail", "content": flow_content}),
+        )
+        .await
+        .unwrap();
+    let _ = registry
+        .execute(
+            "start_run",
+            serde_json::json!({"flow_name": "failing_tail", "event": {}, "draft": true}),
+        )
+        .await;
+    let run_id = env.deps.storage.list_runs(1, 0).await.unwrap()[0].id;
+
+    let mut out = Vec::new();
+    let status = tail_run(
+        env.deps.storage.as_ref(),
+        None,
+        run_id,
+        std::time::Duration::from_millis(20),
+        &mut out,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(status, RunStatus::Failed);
+    let lines = tail_lines(&out);
+    assert_eq!(lines[..2], ["greet  started", "greet  succeeded"]);
+    assert_eq!(lines.last().unwrap(), "run failed");
+
+    assert!(
+        tail_run(
+            env.deps.storage.as_ref(),
+            None,
+            uuid::Uuid::new_v4(),
+            std::time::Duration::from_millis(20),
+            &mut Vec::new(),
+        )
+        .await
+        .is_err()
+    );
+}
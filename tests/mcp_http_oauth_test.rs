@@ -30,6 +30,7 @@ async fn create_test_client(storage: &Arc<dyn Storage>, scopes: &str) -> OAuthCl
         logo_uri: None,
         created_at: now,
         updated_at: now,
+        tenant_id: None,
     };
 
     storage.save_oauth_client(&client).await.unwrap();